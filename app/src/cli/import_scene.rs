@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+
+use ambient_build::pipelines::scenes::format::{is_scene_path, Scene};
+use ambient_native_std::{asset_cache::AssetCache, asset_url::AbsAssetUrl};
+use ambient_package_semantic_native::add_to_semantic_and_register_components;
+use anyhow::Context;
+use clap::Parser;
+
+#[derive(Parser, Clone, Debug)]
+/// Validate a scene in the scene interchange format, and import it into the package in the
+/// current directory
+pub struct ImportScene {
+    /// The path to the scene (`.scene.json` or `.scene.toml`)
+    pub path: PathBuf,
+    /// The path of the imported scene in the assets folder; defaults to the file name of the
+    /// scene. The scene is converted to the format of its extension
+    #[arg(long)]
+    pub output: Option<String>,
+    /// Print the hierarchy of the scene instead of importing it
+    #[arg(long)]
+    pub preview: bool,
+    /// Skip the components that are not known, with a warning, instead of failing
+    #[arg(long)]
+    pub skip_unknown_components: bool,
+}
+
+pub async fn handle(args: &ImportScene, assets: &AssetCache) -> anyhow::Result<()> {
+    let file_name = args
+        .path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .context("Invalid scene path")?;
+    if !is_scene_path(file_name) {
+        anyhow::bail!("{file_name} is not a scene; scenes end with `.scene.json` or `.scene.toml`");
+    }
+    let output = args.output.as_deref().unwrap_or(file_name);
+    if !is_scene_path(output) {
+        anyhow::bail!("The output {output} must end with `.scene.json` or `.scene.toml`");
+    }
+
+    let data = std::fs::read(&args.path)
+        .with_context(|| format!("Failed to read the scene at {:?}", args.path))?;
+    let scene = Scene::parse(file_name, &data)?;
+
+    // The components of the package, and of its dependencies, can be used by the scene
+    let manifest_path = Path::new("ambient.toml");
+    let mut allow_unknown_components = args.skip_unknown_components;
+    if manifest_path.exists() {
+        let mut semantic = ambient_package_semantic::Semantic::new(false).await?;
+        let url = AbsAssetUrl::from_file_path(manifest_path.canonicalize()?);
+        if let Err(err) = add_to_semantic_and_register_components(&mut semantic, &url).await {
+            tracing::warn!("Failed to read the components of the package: {err:?}");
+            allow_unknown_components = true;
+        }
+    } else if !args.preview {
+        anyhow::bail!("There is no package in the current directory to import the scene into");
+    } else {
+        allow_unknown_components = true;
+    }
+
+    let scene_dir = args.path.parent().unwrap_or(Path::new(""));
+    let warnings = scene.validate(allow_unknown_components, |model| {
+        scene_dir.join(model).is_file()
+    })?;
+    for warning in warnings {
+        tracing::warn!("{warning}");
+    }
+
+    if args.preview {
+        print_scene(&scene, file_name);
+        return Ok(());
+    }
+
+    ambient_build::pipelines::import_scene(
+        args.path.clone(),
+        output,
+        &scene,
+        args.skip_unknown_components,
+    )
+    .context("Failed to import scene")?;
+    ambient_build::build_assets(assets, Path::new("assets"), Path::new("build"), true).await?;
+
+    Ok(())
+}
+
+/// Prints the hierarchy of `scene`, with the model and the components of each entity.
+fn print_scene(scene: &Scene, file_name: &str) {
+    println!(
+        "Scene {:?} (format version {}, {} entities)",
+        scene.name.as_deref().unwrap_or(file_name),
+        scene.format_version,
+        scene.entities.len()
+    );
+
+    let (roots, children) = scene.hierarchy();
+    let mut stack = roots
+        .into_iter()
+        .rev()
+        .map(|index| (index, 1))
+        .collect::<Vec<_>>();
+    while let Some((index, depth)) = stack.pop() {
+        let entity = &scene.entities[index];
+        let indent = "  ".repeat(depth);
+        match &entity.name {
+            Some(name) => println!("{indent}{name} ({})", entity.id),
+            None => println!("{indent}{}", entity.id),
+        }
+        println!(
+            "{indent}  translation: {}, rotation: {}, scale: {}",
+            entity.translation, entity.rotation, entity.scale
+        );
+        if let Some(model) = &entity.model {
+            println!("{indent}  model: {model}");
+        }
+        for (path, value) in &entity.components {
+            println!("{indent}  {path} = {value}");
+        }
+        stack.extend(
+            children[index]
+                .iter()
+                .rev()
+                .map(|&child| (child, depth + 1)),
+        );
+    }
+}
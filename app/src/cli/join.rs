@@ -5,7 +5,10 @@ use ambient_native_std::{
 use ambient_network::native::client::ResolvedAddr;
 use clap::Parser;
 
-use crate::{client, server::QUIC_INTERFACE_PORT};
+use crate::{
+    client::{self, ClientStart},
+    server::QUIC_INTERFACE_PORT,
+};
 
 use super::ClientCli;
 
@@ -19,26 +22,37 @@ pub struct Join {
 }
 
 pub fn handle(args: &Join, rt: &tokio::runtime::Runtime, assets: AssetCache) -> anyhow::Result<()> {
-    let assets_ref = &assets;
-    let server_addr = rt.block_on(async move {
-        let Some(mut host) = args.host.as_ref().cloned() else {
-            return Ok(ResolvedAddr::localhost_with_port(QUIC_INTERFACE_PORT));
-        };
+    let start = {
+        let (host, assets) = (args.host.clone(), assets.clone());
+        async move {
+            let server_addr = resolve_host(host, &assets).await?;
+            anyhow::Ok((ClientStart::Server(server_addr), None))
+        }
+    };
+    client::run(rt, assets, start, &args.client, None)
+}
 
-        if host.starts_with("http://") || host.starts_with("https://") {
-            tracing::info!("NOTE: Joining server by http url is still experimental and can be removed without warning.");
+/// Resolves the address of the server to join, which defaults to localhost.
+pub async fn resolve_host(
+    host: Option<String>,
+    assets: &AssetCache,
+) -> anyhow::Result<ResolvedAddr> {
+    let Some(mut host) = host else {
+        return Ok(ResolvedAddr::localhost_with_port(QUIC_INTERFACE_PORT));
+    };
 
-            let reqwest = &ReqwestClientKey.get(assets_ref);
-            host = reqwest.get(host).send().await?.text().await?;
+    if host.starts_with("http://") || host.starts_with("https://") {
+        tracing::info!("NOTE: Joining server by http url is still experimental and can be removed without warning.");
 
-            if host.is_empty() {
-                anyhow::bail!("Failed to resolve host");
-            }
-        }
-        if !host.contains(':') {
-            host = format!("{host}:{QUIC_INTERFACE_PORT}");
+        let reqwest = &ReqwestClientKey.get(assets);
+        host = reqwest.get(host).send().await?.text().await?;
+
+        if host.is_empty() {
+            anyhow::bail!("Failed to resolve host");
         }
-        ResolvedAddr::lookup_host(&host).await
-    })?;
-    client::run(rt, assets, server_addr, &args.client, None)
+    }
+    if !host.contains(':') {
+        host = format!("{host}:{QUIC_INTERFACE_PORT}");
+    }
+    ResolvedAddr::lookup_host(&host).await
 }
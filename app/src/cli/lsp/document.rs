@@ -0,0 +1,247 @@
+//! Text utilities for the documents that the language server works on. Positions in the protocol
+//! count UTF-16 code units, while the columns here are byte offsets into a line.
+
+use lsp_types::Position;
+
+use super::index::SymbolKind;
+
+/// The line at `position`, and the byte offset of the position in it.
+pub fn line_and_column(text: &str, position: Position) -> Option<(&str, usize)> {
+    let line = text.lines().nth(position.line as usize)?;
+    let mut units = 0;
+    for (column, c) in line.char_indices() {
+        if units >= position.character as usize {
+            return Some((line, column));
+        }
+        units += c.len_utf16();
+    }
+    Some((line, line.len()))
+}
+
+fn to_position(line: usize, text: &str, column: usize) -> Position {
+    Position::new(line as u32, text[..column].encode_utf16().count() as u32)
+}
+
+fn is_path_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == ':'
+}
+
+/// The part of the path that ends at `column`, e.g. `ambient_core::trans`.
+pub fn path_before(line: &str, column: usize) -> &str {
+    let before = &line[..column];
+    let start = before.rfind(|c| !is_path_char(c)).map_or(0, |i| {
+        i + before[i..].chars().next().map_or(1, char::len_utf8)
+    });
+    before[start..].trim_start_matches(':')
+}
+
+/// The path up to the end of the identifier at `column`, e.g. `transform::translation` when
+/// `column` is in `translation`, and `transform` when it is in `transform`.
+pub fn path_at(line: &str, column: usize) -> Option<&str> {
+    let end = line[column..]
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .map_or(line.len(), |i| column + i);
+    let path = path_before(line, end);
+    (!path.is_empty()).then_some(path)
+}
+
+/// The kinds of items that can be written at `column` of `line` in a manifest, going by the table
+/// and the key that it is in.
+pub fn manifest_context(text: &str, line: usize, column: usize) -> Option<&'static [SymbolKind]> {
+    let lines = text.lines().collect::<Vec<_>>();
+    let before = lines.get(line)?.get(..column)?;
+
+    let header = lines[..line]
+        .iter()
+        .rev()
+        .map(|line| line.trim())
+        .find(|line| line.starts_with('['))
+        .map(|line| line.trim_matches(|c| c == '[' || c == ']').trim())
+        .unwrap_or_default();
+
+    // The key whose value is being written, if any, including the keys of multi-line arrays
+    let key = match before.rfind('=') {
+        Some(i) => Some(before[..i].trim_end()),
+        None => lines[..line]
+            .iter()
+            .rev()
+            .take_while(|line| !line.trim_start().starts_with('[') && !line.contains(']'))
+            .find_map(|line| {
+                line.split_once('=')
+                    .filter(|(_, value)| value.contains('['))
+            })
+            .map(|(key, _)| key),
+    };
+    let key = key.map(|key| {
+        let key = key.trim_end();
+        key.rfind(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .map_or(key, |i| &key[i + 1..])
+    });
+
+    match key {
+        None if header.starts_with("concepts.")
+            && (header.ends_with(".components.required")
+                || header.ends_with(".components.optional")) =>
+        {
+            Some(&[SymbolKind::Component])
+        }
+        Some("extends") if header.starts_with("concepts") => Some(&[SymbolKind::Concept]),
+        Some("type" | "element_type") if header.starts_with("components") => {
+            Some(&[SymbolKind::Type])
+        }
+        Some(_) if header.starts_with("messages.") && header.ends_with(".fields") => {
+            Some(&[SymbolKind::Type])
+        }
+        _ => None,
+    }
+}
+
+/// Where `key` is defined in the `section` table of a manifest, either as a table of its own
+/// (`[components.key]`) or as a key of the section (`key = { ... }` under `[components]`).
+pub fn find_definition(text: &str, section: &str, key: &str) -> Option<Position> {
+    let quoted = format!("\"{key}\"");
+    let matches = |name: &str| name == key || name == quoted;
+
+    let mut in_section = false;
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if trimmed.starts_with('[') {
+            let header = trimmed.trim_start_matches('[');
+            let header = header.split(']').next().unwrap_or_default().trim();
+            in_section = header == section;
+
+            let Some(name) = header
+                .strip_prefix(section)
+                .and_then(|rest| rest.strip_prefix('.'))
+            else {
+                continue;
+            };
+            let name = match name.strip_prefix('"').and_then(|name| name.split_once('"')) {
+                Some((name, _)) => format!("\"{name}\""),
+                None => name.split('.').next().unwrap_or_default().to_string(),
+            };
+            if matches(&name) {
+                let column = line.find(name.as_str()).unwrap_or(indent);
+                return Some(to_position(i, line, column));
+            }
+        } else if in_section {
+            let name = trimmed.split('=').next().unwrap_or_default().trim();
+            if matches(name) {
+                return Some(to_position(i, line, indent));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn positions_count_utf16_code_units() {
+        let text = "a = \"é😀x\"\nsecond";
+        let line = "a = \"é😀x\"";
+
+        assert_eq!(line_and_column(text, Position::new(0, 5)), Some((line, 5)));
+        assert_eq!(line_and_column(text, Position::new(0, 6)), Some((line, 7)));
+        assert_eq!(line_and_column(text, Position::new(0, 8)), Some((line, 11)));
+        assert_eq!(
+            line_and_column(text, Position::new(0, 100)),
+            Some((line, line.len()))
+        );
+        assert_eq!(
+            line_and_column(text, Position::new(1, 0)),
+            Some(("second", 0))
+        );
+        assert_eq!(line_and_column(text, Position::new(2, 0)), None);
+
+        assert_eq!(to_position(0, line, 11), Position::new(0, 8));
+        for (column, _) in line.char_indices() {
+            let position = to_position(0, line, column);
+            assert_eq!(line_and_column(text, position), Some((line, column)));
+        }
+    }
+
+    #[test]
+    fn paths() {
+        let line = "let t = ambient_core::trans";
+        assert_eq!(path_before(line, line.len()), "ambient_core::trans");
+        assert_eq!(path_before("foo(components::", 16), "components::");
+        assert_eq!(path_before("é::a::b", "é::a::b".len()), "a::b");
+        assert_eq!(path_before("a = ", 4), "");
+
+        let line = "let s = transform::translation();";
+        let transform = line.find("transform").unwrap();
+        let translation = line.find("translation").unwrap();
+        assert_eq!(
+            path_at(line, translation + 3),
+            Some("transform::translation")
+        );
+        assert_eq!(path_at(line, transform + 3), Some("transform"));
+        assert_eq!(
+            path_at(line, line.find('(').unwrap()),
+            Some("transform::translation")
+        );
+        assert_eq!(path_at(" = x", 0), None);
+    }
+
+    #[test]
+    fn manifest_contexts() {
+        let context = |text: &str| {
+            let line = text.lines().count() - 1;
+            let column = text.lines().last().unwrap().len();
+            manifest_context(text, line, column)
+        };
+        let types: Option<&[SymbolKind]> = Some(&[SymbolKind::Type]);
+        let components: Option<&[SymbolKind]> = Some(&[SymbolKind::Component]);
+        let concepts: Option<&[SymbolKind]> = Some(&[SymbolKind::Concept]);
+
+        assert_eq!(context("[components]\nhealth = { type = \"F3"), types);
+        assert_eq!(context("[components.health]\nelement_type = \""), types);
+        assert_eq!(context("[components]\nhealth = { name = \""), None);
+        assert_eq!(context("[concepts.character]\nextends = [\""), concepts);
+        assert_eq!(
+            context("[concepts.character]\nextends = [\n    \"ambient_core::"),
+            concepts
+        );
+        assert_eq!(
+            context("[concepts.character.components.required]\nhealth = {}\nhea"),
+            components
+        );
+        assert_eq!(context("[messages.hit.fields]\ndamage = \""), types);
+        assert_eq!(context("[package]\nname = \""), None);
+    }
+
+    #[test]
+    fn definitions() {
+        let text = "[components]\n\
+            health = { type = \"F32\" }\n\
+            \"max health\" = { type = \"F32\" }\n\
+            \n\
+            [components.speed]\n\
+            type = \"F32\"\n\
+            \n\
+            [concepts.speed]\n";
+
+        assert_eq!(
+            find_definition(text, "components", "health"),
+            Some(Position::new(1, 0))
+        );
+        assert_eq!(
+            find_definition(text, "components", "max health"),
+            Some(Position::new(2, 0))
+        );
+        assert_eq!(
+            find_definition(text, "components", "speed"),
+            Some(Position::new(4, 12))
+        );
+        assert_eq!(
+            find_definition(text, "concepts", "speed"),
+            Some(Position::new(7, 10))
+        );
+        assert_eq!(find_definition(text, "components", "type"), None);
+        assert_eq!(find_definition(text, "messages", "health"), None);
+    }
+}
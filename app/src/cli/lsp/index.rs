@@ -0,0 +1,692 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use ambient_package::Manifest;
+use ambient_package_semantic::{
+    Item, ItemId, ItemMap, ResolvableItemId, RetrievableFile, Scope, Semantic, Type, TypeInner,
+};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Documentation, Location, MarkupContent, MarkupKind, Range,
+    Url,
+};
+
+use super::document;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    Component,
+    Concept,
+    Message,
+    Type,
+}
+impl SymbolKind {
+    pub const ALL: &'static [Self] = &[Self::Component, Self::Concept, Self::Message, Self::Type];
+
+    /// The table of manifests that symbols of this kind are defined in.
+    fn section(self) -> &'static str {
+        match self {
+            Self::Component => "components",
+            Self::Concept => "concepts",
+            Self::Message => "messages",
+            Self::Type => "enums",
+        }
+    }
+
+    /// The module of the generated Rust code that symbols of this kind are in.
+    fn module(self) -> &'static str {
+        match self {
+            Self::Component => "components",
+            Self::Concept => "concepts",
+            Self::Message => "messages",
+            Self::Type => "types",
+        }
+    }
+
+    fn completion_kind(self) -> CompletionItemKind {
+        match self {
+            Self::Component => CompletionItemKind::FIELD,
+            Self::Concept => CompletionItemKind::STRUCT,
+            Self::Message => CompletionItemKind::EVENT,
+            Self::Type => CompletionItemKind::ENUM,
+        }
+    }
+}
+impl fmt::Display for SymbolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Component => write!(f, "Component"),
+            Self::Concept => write!(f, "Concept"),
+            Self::Message => write!(f, "Message"),
+            Self::Type => write!(f, "Type"),
+        }
+    }
+}
+
+/// An item that can be referred to from the manifests or the Rust code of a package.
+#[derive(Clone, Debug)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    /// The path of the item in the manifests of the package, e.g. `ambient_core::transform::scale`
+    pub path: String,
+    /// The path of the item in the Rust code generated for the package, if it can be reached
+    pub rust_path: Option<String>,
+    /// A summary of the item, such as the type of a component
+    pub detail: Option<String>,
+    /// The documentation of the item, in Markdown
+    pub docs: String,
+    /// Where the item is defined, if it is in a local manifest
+    pub location: Option<Location>,
+}
+
+/// The items that a package can refer to, found through the same semantic analysis that its code
+/// is generated from.
+#[derive(Default)]
+pub struct Index {
+    pub symbols: Vec<Symbol>,
+    /// The scope of each local manifest of the package, which references in it are resolved from
+    pub manifests: HashMap<PathBuf, String>,
+}
+impl Index {
+    pub async fn build(manifest_path: &Path) -> anyhow::Result<Self> {
+        let mut semantic = Semantic::new(false).await?;
+        let package_id = semantic
+            .add_package(RetrievableFile::Path(manifest_path.to_path_buf()), None)
+            .await?;
+        semantic.resolve_all()?;
+
+        let items = &semantic.items;
+        let mut builder = Builder {
+            items,
+            index: Index::default(),
+        };
+        for type_id in semantic.root_scope().types.values() {
+            let type_ = items.get(*type_id);
+            if let TypeInner::Primitive(primitive) = type_.inner {
+                let path = type_.data.id.to_string();
+                builder.index.symbols.push(Symbol {
+                    kind: SymbolKind::Type,
+                    docs: docs(
+                        SymbolKind::Type,
+                        &path,
+                        None,
+                        &[format!("Rust: `{primitive}`")],
+                    ),
+                    path,
+                    rust_path: None,
+                    detail: None,
+                    location: None,
+                });
+            }
+        }
+
+        let package = items.get(package_id);
+        let file = ManifestFile::read(manifest_path.to_path_buf(), String::new()).ok();
+        builder.add_scope(package.scope_id, "", Some("packages::this"), file.as_ref());
+
+        let mut dependencies = package.dependencies.iter().collect::<Vec<_>>();
+        dependencies.sort_by_key(|(alias, _)| alias.as_str());
+        for (alias, dependency) in dependencies {
+            let dependency = items.get(dependency.id);
+            let file = dependency
+                .source
+                .as_local_path()
+                .and_then(|path| ManifestFile::read(path, alias.to_string()).ok());
+            builder.add_scope(
+                dependency.scope_id,
+                alias.as_str(),
+                Some(&format!("packages::{alias}")),
+                file.as_ref(),
+            );
+        }
+
+        let ambient = items.get(semantic.ambient_package_id);
+        builder.add_scope(
+            ambient.scope_id,
+            "ambient_core",
+            Some("ambient_api::core"),
+            None,
+        );
+
+        Ok(builder.index)
+    }
+
+    /// Finds the item that `path` refers to in the manifest at `file`. Like the references of
+    /// manifests, it is looked up in the scope of the manifest first, and then in its parents.
+    pub fn resolve_manifest_path(&self, file: &Path, path: &str) -> Option<&Symbol> {
+        let mut scope = self.manifests.get(file).map_or("", String::as_str);
+        loop {
+            let full_path = join(scope, path);
+            if let Some(symbol) = self.symbols.iter().find(|s| s.path == full_path) {
+                return Some(symbol);
+            }
+            if scope.is_empty() {
+                return None;
+            }
+            scope = scope.rsplit_once("::").map_or("", |(parent, _)| parent);
+        }
+    }
+
+    /// Finds the item that `path` refers to in the Rust code `text`. Paths can be partial, like
+    /// `components::speed`; single identifiers are only looked up if their module is mentioned in
+    /// the code, as they are usually imported with `use`.
+    pub fn resolve_rust_path(&self, path: &str, text: &str) -> Option<&Symbol> {
+        let suffix = format!("::{path}");
+        self.symbols.iter().find(|symbol| {
+            let Some(rust_path) = &symbol.rust_path else {
+                return false;
+            };
+            if rust_path != path && !rust_path.ends_with(&suffix) {
+                return false;
+            }
+            if path.contains("::") {
+                return true;
+            }
+            let mut segments = rust_path.rsplit("::").skip(1);
+            match (segments.next(), segments.next()) {
+                (Some(module), Some(parent)) => text.contains(&format!("{parent}::{module}")),
+                _ => false,
+            }
+        })
+    }
+
+    /// The completions of the path segment after `module` (empty, or ending with `::`), for the
+    /// items of the given kinds. Paths in manifests must be complete, while Rust paths can start
+    /// from any module, like `components::`.
+    pub fn completions(
+        &self,
+        module: &str,
+        kinds: &[SymbolKind],
+        rust: bool,
+    ) -> Vec<CompletionItem> {
+        let mut modules = BTreeSet::new();
+        let mut completions = Vec::new();
+        for symbol in self.symbols.iter().filter(|s| kinds.contains(&s.kind)) {
+            let path = if rust {
+                let Some(rust_path) = &symbol.rust_path else {
+                    continue;
+                };
+                rust_path
+            } else {
+                &symbol.path
+            };
+            let Some(rest) = remainder(path, module, rust) else {
+                continue;
+            };
+            match rest.split_once("::") {
+                Some((segment, _)) => {
+                    modules.insert(segment);
+                }
+                None => completions.push(CompletionItem {
+                    label: rest.to_string(),
+                    kind: Some(symbol.kind.completion_kind()),
+                    detail: symbol.detail.clone(),
+                    documentation: Some(Documentation::MarkupContent(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: symbol.docs.clone(),
+                    })),
+                    ..Default::default()
+                }),
+            }
+        }
+        completions.extend(modules.into_iter().map(|segment| CompletionItem {
+            label: segment.to_string(),
+            kind: Some(CompletionItemKind::MODULE),
+            ..Default::default()
+        }));
+        completions
+    }
+}
+
+/// The rest of `path` after `module`, which must be at its start, or for Rust paths, after any
+/// `::` in it.
+fn remainder<'a>(path: &'a str, module: &str, rust: bool) -> Option<&'a str> {
+    if let Some(rest) = path.strip_prefix(module) {
+        return Some(rest);
+    }
+    if !rust {
+        return None;
+    }
+    path.match_indices(module)
+        .find(|(i, _)| path[..*i].ends_with("::"))
+        .map(|(i, _)| &path[i + module.len()..])
+}
+
+fn join(scope: &str, id: &str) -> String {
+    if scope.is_empty() {
+        id.to_string()
+    } else {
+        format!("{scope}::{id}")
+    }
+}
+
+/// A local manifest, which the items of a scope are defined in.
+struct ManifestFile {
+    path: PathBuf,
+    text: String,
+    manifest: Manifest,
+    /// The path of the scope of the manifest
+    scope: String,
+}
+impl ManifestFile {
+    fn read(path: PathBuf, scope: String) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(&path)?;
+        let manifest = Manifest::parse(&text)?;
+        Ok(Self {
+            path,
+            text,
+            manifest,
+            scope,
+        })
+    }
+
+    fn location(&self, kind: SymbolKind, path: &str) -> Option<Location> {
+        let key = path
+            .strip_prefix(&self.scope)
+            .map_or(path, |key| key.trim_start_matches("::"));
+        let position = document::find_definition(&self.text, kind.section(), key)?;
+        Some(Location {
+            uri: Url::from_file_path(&self.path).ok()?,
+            range: Range::new(position, position),
+        })
+    }
+}
+
+struct Builder<'a> {
+    items: &'a ItemMap,
+    index: Index,
+}
+impl Builder<'_> {
+    fn add_scope(
+        &mut self,
+        scope_id: ItemId<Scope>,
+        path: &str,
+        rust_path: Option<&str>,
+        file: Option<&ManifestFile>,
+    ) {
+        let items = self.items;
+        let scope = items.get(scope_id);
+        if let Some(file) = file.filter(|file| file.scope == path) {
+            self.index
+                .manifests
+                .insert(file.path.clone(), file.scope.clone());
+        }
+
+        for (id, component_id) in &scope.components {
+            let component = items.get(*component_id);
+            let type_ = type_name(items, &component.type_);
+            let mut extra = Vec::new();
+            if !component.attributes.is_empty() {
+                let attributes = component
+                    .attributes
+                    .iter()
+                    .filter_map(|attribute| attribute.as_resolved())
+                    .map(|attribute| format!("`{}`", items.get(attribute).data().id))
+                    .collect::<Vec<_>>();
+                extra.push(format!("Attributes: {}", attributes.join(", ")));
+            }
+            self.add(
+                SymbolKind::Component,
+                join(path, id.as_str()),
+                rust_path,
+                Some(type_),
+                &[component.name.as_deref(), component.description.as_deref()],
+                extra,
+                file,
+            );
+        }
+
+        for (id, concept_id) in &scope.concepts {
+            let concept = items.get(*concept_id);
+            let mut extra = Vec::new();
+            if !concept.extends.is_empty() {
+                let extends = list(items, concept.extends.iter());
+                extra.push(format!("Extends: {extends}"));
+            }
+            for (label, components) in [
+                ("Required", &concept.required_components),
+                ("Optional", &concept.optional_components),
+            ] {
+                if !components.is_empty() {
+                    let components = list(items, components.keys());
+                    extra.push(format!("{label} components: {components}"));
+                }
+            }
+            self.add(
+                SymbolKind::Concept,
+                join(path, id.as_str()),
+                rust_path,
+                None,
+                &[concept.name.as_deref(), concept.description.as_deref()],
+                extra,
+                file,
+            );
+        }
+
+        for (id, message_id) in &scope.messages {
+            let message = items.get(*message_id);
+            let extra = message
+                .fields
+                .iter()
+                .map(|(field, type_)| format!("- `{field}`: `{}`", type_name(items, type_)))
+                .collect();
+            self.add(
+                SymbolKind::Message,
+                join(path, id.as_str()),
+                rust_path,
+                None,
+                &[message.description.as_deref()],
+                extra,
+                file,
+            );
+        }
+
+        for (id, type_id) in &scope.types {
+            let Some(enum_) = items.get(*type_id).inner.as_enum() else {
+                continue;
+            };
+            let extra = enum_
+                .members
+                .iter()
+                .map(|(member, description)| format!("- `{member}`: {description}"))
+                .collect();
+            self.add(
+                SymbolKind::Type,
+                join(path, id.as_str()),
+                rust_path,
+                None,
+                &[enum_.description.as_deref()],
+                extra,
+                file,
+            );
+        }
+
+        for (id, child_id) in &scope.scopes {
+            let child_path = join(path, id.as_str());
+            let child_rust_path = rust_path.map(|rust_path| format!("{rust_path}::{id}"));
+            // Scopes are either included from other manifests, or are part of the item paths of
+            // their parent's manifest
+            let include;
+            let child_file =
+                match file.and_then(|file| Some((file, file.manifest.includes.get(id)?))) {
+                    Some((file, include_path)) => {
+                        let include_path = file
+                            .path
+                            .parent()
+                            .unwrap_or(Path::new(""))
+                            .join(include_path);
+                        include = ManifestFile::read(include_path, child_path.clone()).ok();
+                        include.as_ref()
+                    }
+                    None => file,
+                };
+            self.add_scope(
+                *child_id,
+                &child_path,
+                child_rust_path.as_deref(),
+                child_file,
+            );
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add(
+        &mut self,
+        kind: SymbolKind,
+        path: String,
+        rust_path: Option<&str>,
+        detail: Option<String>,
+        description: &[Option<&str>],
+        mut extra: Vec<String>,
+        file: Option<&ManifestFile>,
+    ) {
+        let id = path.rsplit("::").next().unwrap_or_default();
+        let rust_path = rust_path.map(|rust_path| format!("{rust_path}::{}::{id}", kind.module()));
+        if let Some(rust_path) = &rust_path {
+            extra.push(format!("Rust: `{rust_path}`"));
+        }
+        let mut paragraphs = description
+            .iter()
+            .flatten()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        paragraphs.extend(extra);
+        self.index.symbols.push(Symbol {
+            kind,
+            docs: docs(kind, &path, detail.as_deref(), &paragraphs),
+            location: file.and_then(|file| file.location(kind, &path)),
+            path,
+            rust_path,
+            detail,
+        });
+    }
+}
+
+/// The IDs of the items, for the documentation of the item that refers to them.
+fn list<'a, T: Item + 'a>(
+    items: &ItemMap,
+    ids: impl Iterator<Item = &'a ResolvableItemId<T>>,
+) -> String {
+    ids.filter_map(|id| id.as_resolved())
+        .map(|id| format!("`{}`", items.get(id).data().id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn docs(kind: SymbolKind, path: &str, detail: Option<&str>, paragraphs: &[String]) -> String {
+    let mut docs = format!("{kind} `{path}`");
+    if let Some(detail) = detail {
+        docs += &format!(": `{detail}`");
+    }
+    for paragraph in paragraphs {
+        // Lists are kept together
+        docs += if paragraph.starts_with("- ") {
+            "\n"
+        } else {
+            "\n\n"
+        };
+        docs += paragraph;
+    }
+    docs
+}
+
+/// The name of a type as it is written in manifests, e.g. `Vec<F32>`.
+fn type_name(items: &ItemMap, type_: &ResolvableItemId<Type>) -> String {
+    let Some(type_id) = type_.as_resolved() else {
+        return "?".to_string();
+    };
+    let type_ = items.get(type_id);
+    match &type_.inner {
+        TypeInner::Vec(inner) => {
+            format!(
+                "Vec<{}>",
+                type_name(items, &ResolvableItemId::Resolved(*inner))
+            )
+        }
+        TypeInner::Option(inner) => {
+            format!(
+                "Option<{}>",
+                type_name(items, &ResolvableItemId::Resolved(*inner))
+            )
+        }
+        TypeInner::Primitive(_) | TypeInner::Enum(_) => type_.data.id.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn symbol(kind: SymbolKind, path: &str, rust_path: Option<&str>) -> Symbol {
+        Symbol {
+            kind,
+            path: path.to_string(),
+            rust_path: rust_path.map(|s| s.to_string()),
+            detail: None,
+            docs: docs(kind, path, None, &[]),
+            location: None,
+        }
+    }
+
+    fn index() -> Index {
+        use SymbolKind::{Component, Concept, Type};
+        Index {
+            symbols: vec![
+                symbol(Type, "F32", None),
+                symbol(
+                    Component,
+                    "health",
+                    Some("packages::this::components::health"),
+                ),
+                symbol(
+                    Component,
+                    "player::speed",
+                    Some("packages::this::player::components::speed"),
+                ),
+                symbol(
+                    Concept,
+                    "player::character",
+                    Some("packages::this::player::concepts::character"),
+                ),
+                symbol(
+                    Component,
+                    "ambient_core::transform::translation",
+                    Some("ambient_api::core::transform::components::translation"),
+                ),
+                symbol(
+                    Component,
+                    "ambient_core::transform::rotation",
+                    Some("ambient_api::core::transform::components::rotation"),
+                ),
+            ],
+            manifests: HashMap::from([
+                (PathBuf::from("ambient.toml"), String::new()),
+                (PathBuf::from("player.toml"), "player".to_string()),
+            ]),
+        }
+    }
+
+    fn labels(completions: Vec<CompletionItem>) -> Vec<String> {
+        completions.into_iter().map(|c| c.label).collect()
+    }
+
+    #[test]
+    fn manifest_completions() {
+        let index = index();
+        let components = &[SymbolKind::Component];
+
+        assert_eq!(
+            labels(index.completions("", components, false)),
+            ["health", "ambient_core", "player"]
+        );
+        assert_eq!(
+            labels(index.completions("ambient_core::", components, false)),
+            ["transform"]
+        );
+        let completions = index.completions("ambient_core::transform::", components, false);
+        assert_eq!(completions[0].kind, Some(CompletionItemKind::FIELD));
+        assert_eq!(labels(completions), ["translation", "rotation"]);
+        assert_eq!(
+            labels(index.completions("", &[SymbolKind::Type], false)),
+            ["F32"]
+        );
+        // Paths in manifests are complete
+        assert!(index
+            .completions("transform::", components, false)
+            .is_empty());
+    }
+
+    #[test]
+    fn rust_completions() {
+        let index = index();
+
+        assert_eq!(
+            labels(index.completions("components::", SymbolKind::ALL, true)),
+            ["health", "speed", "translation", "rotation"]
+        );
+        let completions = index.completions("player::", SymbolKind::ALL, true);
+        assert!(completions
+            .iter()
+            .all(|c| c.kind == Some(CompletionItemKind::MODULE)));
+        assert_eq!(labels(completions), ["components", "concepts"]);
+        assert_eq!(
+            labels(index.completions("packages::", SymbolKind::ALL, true)),
+            ["this"]
+        );
+    }
+
+    #[test]
+    fn manifest_paths_are_resolved_from_the_scope_of_the_manifest() {
+        let index = index();
+        let resolve = |file: &str, path: &str| {
+            index
+                .resolve_manifest_path(Path::new(file), path)
+                .map(|symbol| symbol.path.as_str())
+        };
+
+        assert_eq!(resolve("player.toml", "speed"), Some("player::speed"));
+        assert_eq!(resolve("player.toml", "health"), Some("health"));
+        assert_eq!(
+            resolve("player.toml", "ambient_core::transform::translation"),
+            Some("ambient_core::transform::translation")
+        );
+        assert_eq!(resolve("ambient.toml", "speed"), None);
+        assert_eq!(
+            resolve("ambient.toml", "player::character"),
+            Some("player::character")
+        );
+    }
+
+    #[test]
+    fn rust_paths() {
+        let index = index();
+        let resolve = |path: &str, text: &str| {
+            index
+                .resolve_rust_path(path, text)
+                .map(|symbol| symbol.path.as_str())
+        };
+
+        assert_eq!(resolve("components::health", ""), Some("health"));
+        assert_eq!(
+            resolve("transform::components::rotation", ""),
+            Some("ambient_core::transform::rotation")
+        );
+        assert_eq!(resolve("ponents::health", ""), None);
+
+        // Single identifiers need their module to be mentioned
+        let this = "use packages::this::components::*;";
+        assert_eq!(resolve("health", this), Some("health"));
+        assert_eq!(resolve("speed", this), None);
+        assert_eq!(resolve("health", "use ambient_api::prelude::*;"), None);
+        assert_eq!(
+            resolve(
+                "translation",
+                "use ambient_api::core::transform::components::translation;"
+            ),
+            Some("ambient_core::transform::translation")
+        );
+    }
+
+    #[test]
+    fn hover_docs() {
+        let paragraphs = [
+            "Health".to_string(),
+            "- `a`: first".to_string(),
+            "- `b`: second".to_string(),
+            "Rust: `health`".to_string(),
+        ];
+        assert_eq!(
+            docs(SymbolKind::Component, "health", Some("F32"), &paragraphs),
+            "Component `health`: `F32`\n\nHealth\n- `a`: first\n- `b`: second\n\nRust: `health`"
+        );
+        assert_eq!(
+            index()
+                .resolve_manifest_path(Path::new("ambient.toml"), "F32")
+                .unwrap()
+                .docs,
+            "Type `F32`"
+        );
+    }
+}
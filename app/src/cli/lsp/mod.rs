@@ -0,0 +1,227 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+        Notification as _,
+    },
+    request::{Completion, GotoDefinition, HoverRequest, Request as _},
+    CompletionOptions, CompletionParams, CompletionResponse, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
+    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams,
+    HoverProviderCapability, MarkupContent, MarkupKind, OneOf, ServerCapabilities,
+    TextDocumentPositionParams, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+
+mod document;
+mod index;
+
+use index::{Index, Symbol, SymbolKind};
+
+#[derive(Parser, Clone, Debug)]
+/// Run a language server for the manifests and Rust code of packages, which editors can use for
+/// completion, hover documentation and go-to-definition of components, concepts and messages
+pub struct Lsp {}
+
+pub fn handle(_args: &Lsp, rt: &tokio::runtime::Runtime) -> anyhow::Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    connection.initialize(serde_json::to_value(ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![":".to_string(), "\"".to_string()]),
+            ..Default::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    })?)?;
+
+    Server {
+        rt,
+        documents: HashMap::new(),
+        indices: HashMap::new(),
+    }
+    .run(connection)?;
+    io_threads.join()?;
+    Ok(())
+}
+
+struct Server<'a> {
+    rt: &'a tokio::runtime::Runtime,
+    /// The text of the open documents, which may not have been saved
+    documents: HashMap<Url, String>,
+    /// The index of each package by the path of its manifest, or `None` if it failed to build
+    indices: HashMap<PathBuf, Option<Index>>,
+}
+impl Server<'_> {
+    fn run(&mut self, connection: Connection) -> anyhow::Result<()> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        break;
+                    }
+                    connection
+                        .sender
+                        .send(Message::Response(self.request(request)))?;
+                }
+                Message::Notification(notification) => self.notification(notification),
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn request(&mut self, request: Request) -> Response {
+        let method = request.method.clone();
+        match method.as_str() {
+            Completion::METHOD => respond::<Completion>(request, |params| self.completion(params)),
+            HoverRequest::METHOD => respond::<HoverRequest>(request, |params| self.hover(params)),
+            GotoDefinition::METHOD => {
+                respond::<GotoDefinition>(request, |params| self.definition(params))
+            }
+            _ => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request {method:?}"),
+            ),
+        }
+    }
+
+    fn notification(&mut self, notification: Notification) {
+        let params = notification.params;
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                if let Ok(params) = serde_json::from_value::<DidOpenTextDocumentParams>(params) {
+                    let document = params.text_document;
+                    self.documents.insert(document.uri, document.text);
+                }
+            }
+            DidChangeTextDocument::METHOD => {
+                if let Ok(params) = serde_json::from_value::<DidChangeTextDocumentParams>(params) {
+                    // The whole document is sent on each change
+                    if let Some(change) = params.content_changes.into_iter().last() {
+                        self.documents.insert(params.text_document.uri, change.text);
+                    }
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                if let Ok(params) = serde_json::from_value::<DidCloseTextDocumentParams>(params) {
+                    self.documents.remove(&params.text_document.uri);
+                }
+            }
+            DidSaveTextDocument::METHOD => {
+                if let Ok(params) = serde_json::from_value::<DidSaveTextDocumentParams>(params) {
+                    // The manifests of a package can change the items of the packages that depend
+                    // on it, so all of the indices are rebuilt
+                    if params.text_document.uri.path().ends_with(".toml") {
+                        self.indices.clear();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn completion(&mut self, params: CompletionParams) -> Option<CompletionResponse> {
+        let position = params.text_document_position;
+        let (path, text) = self.document(&position.text_document.uri)?;
+        let (line, column) = document::line_and_column(&text, position.position)?;
+        let typed = document::path_before(line, column);
+        let module = typed.rfind("::").map_or("", |i| &typed[..i + 2]);
+
+        let index = self.index(&path)?;
+        let completions = if index.manifests.contains_key(&path) {
+            let kinds = document::manifest_context(&text, position.position.line as usize, column)?;
+            index.completions(module, kinds, false)
+        } else if is_rust(&path) && !module.is_empty() {
+            index.completions(module, SymbolKind::ALL, true)
+        } else {
+            return None;
+        };
+        Some(CompletionResponse::Array(completions))
+    }
+
+    fn hover(&mut self, params: HoverParams) -> Option<Hover> {
+        let symbol = self.symbol_at(&params.text_document_position_params)?;
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: symbol.docs.clone(),
+            }),
+            range: None,
+        })
+    }
+
+    fn definition(&mut self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let symbol = self.symbol_at(&params.text_document_position_params)?;
+        Some(GotoDefinitionResponse::Scalar(symbol.location.clone()?))
+    }
+
+    /// The item that the path at `position` refers to.
+    fn symbol_at(&mut self, position: &TextDocumentPositionParams) -> Option<&Symbol> {
+        let (path, text) = self.document(&position.text_document.uri)?;
+        let (line, column) = document::line_and_column(&text, position.position)?;
+        let item_path = document::path_at(line, column)?;
+
+        let index = self.index(&path)?;
+        if index.manifests.contains_key(&path) {
+            index.resolve_manifest_path(&path, item_path)
+        } else if is_rust(&path) {
+            index.resolve_rust_path(item_path, &text)
+        } else {
+            None
+        }
+    }
+
+    /// The path and the current text of a document.
+    fn document(&self, uri: &Url) -> Option<(PathBuf, String)> {
+        let path = uri.to_file_path().ok()?;
+        let text = match self.documents.get(uri) {
+            Some(text) => text.clone(),
+            None => std::fs::read_to_string(&path).ok()?,
+        };
+        Some((path, text))
+    }
+
+    /// The index of the package that the file at `path` is in, which is built when it's first
+    /// needed.
+    fn index(&mut self, path: &Path) -> Option<&Index> {
+        let manifest = path
+            .ancestors()
+            .skip(1)
+            .map(|dir| dir.join("ambient.toml"))
+            .find(|manifest| manifest.exists())?;
+        let rt = self.rt;
+        self.indices
+            .entry(manifest)
+            .or_insert_with_key(|manifest| match rt.block_on(Index::build(manifest)) {
+                Ok(index) => Some(index),
+                Err(err) => {
+                    tracing::warn!("Failed to index the package at {manifest:?}: {err:?}");
+                    None
+                }
+            })
+            .as_ref()
+    }
+}
+
+fn is_rust(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "rs")
+}
+
+/// Responds to `request` with the result of `handler`.
+fn respond<R: lsp_types::request::Request>(
+    request: Request,
+    handler: impl FnOnce(R::Params) -> R::Result,
+) -> Response {
+    match serde_json::from_value::<R::Params>(request.params) {
+        Ok(params) => Response::new_ok(request.id, handler(params)),
+        Err(err) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, err.to_string()),
+    }
+}
@@ -1,21 +1,28 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
+use ambient_network::native::simulation::NetworkConditions;
 use clap::{Args, Parser, Subcommand};
 
 pub mod assets;
+pub mod import_scene;
 pub mod join;
 pub mod login;
+pub mod lsp;
 pub mod package;
+pub mod view;
 
 mod package_path;
 pub use package_path::*;
 
 use self::{
     assets::Assets,
+    import_scene::ImportScene,
     join::Join,
+    lsp::Lsp,
     package::{
         build::Build, deploy::Deploy, new::New, run::Run, serve::Serve, Package, PackageArgs,
     },
+    view::View,
 };
 
 #[derive(Parser, Clone)]
@@ -43,6 +50,9 @@ pub enum Commands {
         #[command(subcommand)]
         assets: Assets,
     },
+    ImportScene(ImportScene),
+    View(View),
+    Lsp(Lsp),
     /// Log into Ambient and save your API token to settings
     Login,
 }
@@ -82,10 +92,23 @@ pub struct ClientCli {
     #[command(subcommand)]
     pub golden_image: Option<GoldenImageCommand>,
 
+    /// Record the input of the player to this file, to replay it with `--replay-input`
+    #[arg(long, value_name = "FILE")]
+    pub record_input: Option<PathBuf>,
+
+    /// Replay the input recorded in this file instead of the input of the player. Golden image
+    /// tests start once the replay has ended
+    #[arg(long, value_name = "FILE", conflicts_with = "record_input")]
+    pub replay_input: Option<PathBuf>,
+
     /// The user ID to join this server with
     #[clap(short, long)]
     pub user_id: Option<String>,
 
+    /// Join the server as a spectator, without a player entity
+    #[arg(long)]
+    pub spectate: bool,
+
     /// Allows connecting to servers with a mismatched version. Only available in non-production builds.
     ///
     /// DO NOT USE THIS UNLESS YOU KNOW WHAT YOU ARE DOING.
@@ -112,13 +135,26 @@ pub struct ClientCli {
     /// Window height override
     #[arg(long)]
     pub window_height: Option<u32>,
+
+    /// Simulate this much latency, in milliseconds, on the connection to the server. The latency
+    /// is added to the round-trip time, half of it in each direction
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    pub simulate_latency: u64,
+
+    /// Simulate up to this much random jitter, in milliseconds, on the packets in each direction
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    pub simulate_jitter: u64,
+
+    /// Simulate the loss of this percentage of the packets in each direction
+    #[arg(long, value_name = "PCT", default_value_t = 0.)]
+    pub simulate_loss: f32,
 }
 
 impl Cli {
     /// Extract package-relevant state only
     pub fn package(&self) -> Option<&PackageArgs> {
         match &self.command {
-            Commands::Package { package } => Some(package.args()),
+            Commands::Package { package } => package.args(),
             Commands::New(New { package, .. }) => Some(package),
             Commands::Run(Run { package, .. }) => Some(package),
             Commands::Build(Build { package, .. }) => Some(package),
@@ -126,6 +162,9 @@ impl Cli {
             Commands::Serve(Serve { package, .. }) => Some(package),
             Commands::Join(Join { .. }) => None,
             Commands::Assets { .. } => None,
+            Commands::ImportScene(_) => None,
+            Commands::View(_) => None,
+            Commands::Lsp(_) => None,
             Commands::Login => None,
         }
     }
@@ -139,7 +178,24 @@ impl Cli {
             C::Run(Run { package, .. }) | C::Build(Build { package, .. }) => {
                 package.is_release().unwrap_or(false)
             }
-            C::New(_) | C::Join(_) | C::Assets { .. } | C::Package { .. } | C::Login => false,
+            C::New(_)
+            | C::Join(_)
+            | C::Assets { .. }
+            | C::ImportScene(_)
+            | C::View(_)
+            | C::Package { .. }
+            | C::Login => false,
+        }
+    }
+}
+
+impl ClientCli {
+    /// The network conditions to simulate, as set by the `--simulate-*` arguments
+    pub fn network_conditions(&self) -> NetworkConditions {
+        NetworkConditions {
+            latency: Duration::from_millis(self.simulate_latency),
+            jitter: Duration::from_millis(self.simulate_jitter),
+            loss: (self.simulate_loss / 100.).clamp(0., 1.),
         }
     }
 }
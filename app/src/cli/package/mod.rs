@@ -24,11 +24,23 @@ pub enum Package {
         #[command(flatten)]
         package: PackageArgs,
     },
+    /// Check the manifest, pipelines and referenced assets of a package without building it.
+    Validate {
+        #[command(flatten)]
+        package: PackageArgs,
+    },
+    /// Print the JSON schema of `ambient.toml`, which editors can use to check manifests
+    Schema {
+        /// Write the schema to this file instead of printing it
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
 }
 impl Package {
-    pub fn args(&self) -> &PackageArgs {
+    pub fn args(&self) -> Option<&PackageArgs> {
         match self {
-            Package::RegenerateId { package } => package,
+            Package::RegenerateId { package } | Package::Validate { package } => Some(package),
+            Package::Schema { .. } => None,
         }
     }
 }
@@ -131,6 +143,18 @@ pub struct HostCli {
     /// Shutdown the server after the specified number of seconds of inactivity
     #[arg(long)]
     pub shutdown_after_inactivity_seconds: Option<u64>,
+
+    /// The maximum number of players that can be connected at once (0 = unlimited)
+    ///
+    /// Defaults to the `max_players` value in the `[hosting]` section of the manifest
+    #[arg(long)]
+    pub max_players: Option<u32>,
+
+    /// The number of times per second the server simulation is stepped and world updates are sent to clients
+    ///
+    /// Defaults to the `tick_rate` value in the `[hosting]` section of the manifest, or 60
+    #[arg(long)]
+    pub tick_rate: Option<u32>,
 }
 
 pub fn handle(
@@ -140,7 +164,36 @@ pub fn handle(
 ) -> anyhow::Result<()> {
     match args {
         Package::RegenerateId { package } => regenerate_id(package),
+        Package::Validate { package } => validate(package),
+        Package::Schema { output } => schema(output.as_deref()),
+    }
+}
+
+fn validate(package: &PackageArgs) -> anyhow::Result<()> {
+    let package_path = package.package_path()?;
+    let Some(package_path) = &package_path.fs_path else {
+        anyhow::bail!("Cannot validate a remote package.");
+    };
+
+    let errors = ambient_build::validate::validate_package(package_path);
+    if errors.is_empty() {
+        println!("No problems found in {package_path:?}");
+        return Ok(());
     }
+    for error in &errors {
+        println!("{error}");
+    }
+    anyhow::bail!("Found {} problem(s) in {package_path:?}", errors.len());
+}
+
+fn schema(output: Option<&Path>) -> anyhow::Result<()> {
+    let schema = serde_json::to_string_pretty(&ambient_build::validate::manifest_schema())?;
+    match output {
+        Some(output) => std::fs::write(output, schema)
+            .with_context(|| format!("Failed to write the schema to {output:?}"))?,
+        None => println!("{schema}"),
+    }
+    Ok(())
 }
 
 fn regenerate_id(package: &PackageArgs) -> anyhow::Result<()> {
@@ -1,9 +1,18 @@
-use ambient_native_std::asset_cache::AssetCache;
+use ambient_native_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    asset_url::ContentBaseUrlKey,
+};
 use clap::Parser;
 
-use crate::{cli::ClientCli, client};
+use crate::{
+    cli::ClientCli,
+    client::{self, ClientStart},
+};
 
-use super::{serve, HostCli, PackageArgs};
+use super::{
+    build::{self, BuildDirectories},
+    serve, HostCli, PackageArgs,
+};
 
 #[derive(Parser, Clone, Debug)]
 /// Builds and runs the package locally
@@ -14,6 +23,10 @@ pub struct Run {
     pub host: HostCli,
     #[command(flatten)]
     pub run: ClientCli,
+    /// Start in the main menu of the package instead of starting a server: its client modules run
+    /// without a server until one of them connects to a server
+    #[arg(long)]
+    pub menu: bool,
 }
 
 pub fn handle(
@@ -22,19 +35,34 @@ pub fn handle(
     assets: AssetCache,
     release_build: bool,
 ) -> anyhow::Result<()> {
-    let server_handle = rt.block_on(serve::handle_inner(
-        &args.package,
-        &args.host,
-        assets.clone(),
-        release_build,
-    ))?;
-
     let package_path = args.package.package_path()?;
-    client::run(
-        rt,
-        assets,
-        server_handle.resolve_as_localhost(),
-        &args.run,
-        package_path.fs_path,
-    )
+
+    // The package is built, and its server started, while the client sets up its window
+    let (package, host) = (args.package.clone(), args.host.clone());
+    let build_assets = assets.clone();
+    if args.menu {
+        let start = async move {
+            let BuildDirectories {
+                build_root_path,
+                main_package_path,
+                main_package_name: _,
+            } = build::handle_inner(&package, &build_assets, release_build).await?;
+
+            // The assets of the package are loaded from its build until a server is joined
+            ContentBaseUrlKey.insert(&build_assets, build_root_path);
+
+            anyhow::Ok((ClientStart::Menu(main_package_path), None))
+        };
+        return client::run(rt, assets, start, &args.run, package_path.fs_path);
+    }
+
+    let start = async move {
+        let server_handle =
+            serve::handle_inner(&package, &host, build_assets, release_build).await?;
+        anyhow::Ok((
+            ClientStart::Server(server_handle.resolve_as_localhost()),
+            Some(server_handle),
+        ))
+    };
+    client::run(rt, assets, start, &args.run, package_path.fs_path)
 }
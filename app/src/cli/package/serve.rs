@@ -66,13 +66,16 @@ pub async fn handle_inner(
         working_directory,
         manifest,
         crypto,
+        None,
     )
     .await;
 
     Ok(server_handle)
 }
 
-fn get_crypto(host: &HostCli) -> anyhow::Result<ambient_network::native::server::Crypto> {
+pub(crate) fn get_crypto(
+    host: &HostCli,
+) -> anyhow::Result<ambient_network::native::server::Crypto> {
     let Some((cert_file, key_file)) = host.cert.as_ref().zip(host.key.as_ref()) else {
         #[cfg(feature = "no_bundled_certs")]
         {
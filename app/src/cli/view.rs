@@ -0,0 +1,369 @@
+use std::{collections::HashSet, f32::consts::PI, path::Path, str::FromStr};
+
+use ambient_app::{window_title, AppBuilder};
+use ambient_cameras::UICamera;
+use ambient_core::{
+    camera::{active_camera, far},
+    epoch_time, main_scene,
+    transform::{rotation, scale, translation},
+};
+use ambient_ecs::{
+    generated::{
+        animation::components::{
+            apply_animation_player, bind_ids, clip_duration, is_animation_player, looping,
+            play_clip_from_url, start_time,
+        },
+        hierarchy::components::{children, parent},
+        model::components::model_from_url,
+        rendering::components::{
+            color, light_ambient, light_diffuse, pbr_material_from_url, sky, sun,
+        },
+    },
+    Entity, EntityId, World,
+};
+use ambient_element::{
+    element_component, use_frame, use_state, Element, ElementComponentExt, Hooks,
+};
+use ambient_model_import::{model_crate::ModelCrate, ModelImportPipeline, ModelImportTransform};
+use ambient_native_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    asset_url::{AbsAssetUrl, AnimationAssetType, TypedAssetUrl},
+    download_asset::AssetsCacheDir,
+    math::SphericalCoords,
+    shapes::AABB,
+};
+use ambient_primitives::{Quad, UVSphere};
+use ambient_ui_native::{padding, Borders, FlowColumn, Text, UIExt, STREET};
+use anyhow::Context;
+use clap::{Parser, ValueEnum};
+use glam::{vec3, vec4, Quat, Vec3};
+
+#[derive(Parser, Clone, Debug)]
+/// Open a viewer for a single model, material or animation, to check it without a package
+pub struct View {
+    /// The path or URL of the asset. Models are imported from their source files (e.g. `.fbx` or
+    /// `.glb`), while materials (`.json`) and animations (`.anim`) are read from the build output
+    /// of a package
+    pub asset: String,
+    /// The lighting of the scene
+    #[arg(long, value_enum, default_value_t)]
+    pub lighting: LightingPreset,
+    /// The animation of the model to play; the animations of a model are listed in its stats
+    #[arg(long)]
+    pub animation: Option<String>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+pub enum LightingPreset {
+    /// A bright, neutral light, without a sky.
+    #[default]
+    Studio,
+    /// The sun in a sky.
+    Outdoor,
+    /// A dim, blue light, to check emissive materials.
+    Night,
+}
+
+/// What is being viewed, once it has been loaded.
+enum Preview {
+    Model {
+        url: AbsAssetUrl,
+        clip: Option<AbsAssetUrl>,
+        bounds: AABB,
+    },
+    Material {
+        url: AbsAssetUrl,
+    },
+    Animation {
+        model: String,
+        clip: AbsAssetUrl,
+    },
+}
+
+pub fn handle(args: &View, rt: &tokio::runtime::Runtime, assets: AssetCache) -> anyhow::Result<()> {
+    let url = if args.asset.contains("://") {
+        AbsAssetUrl::from_str(&args.asset)?
+    } else {
+        let path = Path::new(&args.asset);
+        AbsAssetUrl::from_file_path(
+            path.canonicalize()
+                .with_context(|| format!("Failed to find {path:?}"))?,
+        )
+    };
+    let (preview, stats) = rt.block_on(load(&url, args.animation.as_deref(), &assets))?;
+    for (name, value) in &stats {
+        println!("{name}: {value}");
+    }
+
+    let mut app = rt.block_on(
+        AppBuilder::simple_dual()
+            .examples_systems(true)
+            .with_asset_cache(assets)
+            .build(),
+    )?;
+    *app.world.resource_mut(window_title()) = format!("Ambient - {}", args.asset);
+    app.add_system(Box::new(ambient_primitives::systems()));
+    app.add_system(Box::new(ambient_sky::systems()));
+
+    let world = &mut app.world;
+    let (bounds, clip_node) = spawn_preview(world, &preview);
+    spawn_lighting(world, args.lighting, &bounds);
+    ambient_cameras::spherical::new(
+        bounds.center(),
+        SphericalCoords::new(PI / 3., -PI / 4., bounds.size().length().max(0.1) * 1.5),
+    )
+    .with(active_camera(), 0.)
+    .with(main_scene(), ())
+    .with(far(), 2000.)
+    .spawn(world);
+
+    Stats { stats, clip_node }.el().spawn_interactive(world);
+    UICamera.el().spawn_interactive(world);
+
+    app.run_blocking();
+    Ok(())
+}
+
+async fn load(
+    url: &AbsAssetUrl,
+    animation: Option<&str>,
+    assets: &AssetCache,
+) -> anyhow::Result<(Preview, Vec<(String, String)>)> {
+    match url.extension().as_deref() {
+        Some("json") => Ok((Preview::Material { url: url.clone() }, vec![])),
+        Some("anim") => {
+            let clip = TypedAssetUrl::<AnimationAssetType>::from(url.clone());
+            let model = clip
+                .model_crate()
+                .context("The animation is not in the build output of a model")?
+                .model();
+            let stats = vec![("Model".to_string(), model.to_string())];
+            let preview = Preview::Animation {
+                model: model.to_string(),
+                clip: url.clone(),
+            };
+            Ok((preview, stats))
+        }
+        _ => {
+            let model_crate = ModelImportPipeline::model(url.clone())
+                .add_step(ModelImportTransform::Finalize)
+                .produce_crate(assets)
+                .await
+                .with_context(|| format!("Failed to import the model {url}"))?;
+            let dir = AssetsCacheDir
+                .get(assets)
+                .join("view")
+                .join(url.relative_cache_path());
+            model_crate.write_to_fs(&dir).await;
+
+            let clip = match animation {
+                Some(animation) => {
+                    let path = model_crate
+                        .animations
+                        .content
+                        .keys()
+                        .find(|id| *id == animation)
+                        .map(|id| model_crate.animations.loc.path(id.clone()))
+                        .with_context(|| format!("The model has no animation {animation:?}"))?;
+                    Some(AbsAssetUrl::from_file_path(path.to_path(&dir)))
+                }
+                None => None,
+            };
+            let model = model_crate.model();
+            let preview = Preview::Model {
+                url: AbsAssetUrl::from_file_path(
+                    model_crate.models.loc.path(ModelCrate::MAIN).to_path(&dir),
+                ),
+                clip,
+                bounds: model.aabb().unwrap_or(AABB::ZERO),
+            };
+            Ok((preview, model_stats(&model_crate)))
+        }
+    }
+}
+
+fn model_stats(model_crate: &ModelCrate) -> Vec<(String, String)> {
+    let model = model_crate.model();
+    let triangles: u32 = model_crate
+        .meshes
+        .content
+        .values()
+        .map(|mesh| mesh.index_count() / 3)
+        .sum();
+    let bones = model
+        .skins()
+        .into_iter()
+        .flatten()
+        .flat_map(|skin| &skin.joints)
+        .collect::<HashSet<_>>()
+        .len();
+    let size = model.aabb().unwrap_or(AABB::ZERO).size();
+    let mut animations = model_crate
+        .animations
+        .content
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>();
+    animations.sort();
+    vec![
+        ("Triangles".to_string(), triangles.to_string()),
+        (
+            "Meshes".to_string(),
+            model_crate.meshes.content.len().to_string(),
+        ),
+        (
+            "Materials".to_string(),
+            model_crate.materials.content.len().to_string(),
+        ),
+        ("Bones".to_string(), bones.to_string()),
+        (
+            "Size".to_string(),
+            format!("{:.2} x {:.2} x {:.2}", size.x, size.y, size.z),
+        ),
+        (
+            "Animations".to_string(),
+            if animations.is_empty() {
+                "none".to_string()
+            } else {
+                animations.join(", ")
+            },
+        ),
+    ]
+}
+
+/// Spawns the asset, and returns its bounds and the animation node playing its clip, if any.
+fn spawn_preview(world: &mut World, preview: &Preview) -> (AABB, Option<EntityId>) {
+    match preview {
+        Preview::Model { url, clip, bounds } => {
+            let model = spawn_model(world, url.to_string());
+            let clip_node = clip
+                .as_ref()
+                .map(|clip| play_clip(world, model, clip.to_string()));
+            spawn_floor(world, bounds);
+            (*bounds, clip_node)
+        }
+        Preview::Material { url } => {
+            let bounds = AABB::new(-Vec3::ONE, Vec3::ONE);
+            UVSphere::default()
+                .el()
+                .with(pbr_material_from_url(), url.to_string())
+                .spawn_static(world);
+            spawn_floor(world, &bounds);
+            (bounds, None)
+        }
+        Preview::Animation { model, clip } => {
+            // The bounds of the model aren't known until it has loaded, so those of a humanoid
+            // are used
+            let bounds = AABB::new(vec3(-0.5, -0.5, 0.), vec3(0.5, 0.5, 2.));
+            let model = spawn_model(world, model.clone());
+            let clip_node = play_clip(world, model, clip.to_string());
+            spawn_floor(world, &bounds);
+            (bounds, Some(clip_node))
+        }
+    }
+}
+
+fn spawn_model(world: &mut World, url: String) -> EntityId {
+    Entity::new()
+        .with(model_from_url(), url)
+        .with(translation(), Vec3::ZERO)
+        .with(rotation(), Quat::IDENTITY)
+        .with(scale(), Vec3::ONE)
+        .with(main_scene(), ())
+        .spawn(world)
+}
+
+/// Plays the clip on the model in a loop, and returns the animation node.
+fn play_clip(world: &mut World, model: EntityId, clip: String) -> EntityId {
+    let time = *world.resource(epoch_time());
+    let node = Entity::new()
+        .with(play_clip_from_url(), clip)
+        .with(looping(), true)
+        .with(start_time(), time)
+        .spawn(world);
+    let player = Entity::new()
+        .with(is_animation_player(), ())
+        .with(children(), vec![node])
+        .spawn(world);
+    world.add_component(node, parent(), player).unwrap();
+    world
+        .add_component(model, apply_animation_player(), player)
+        .unwrap();
+    node
+}
+
+fn spawn_floor(world: &mut World, bounds: &AABB) {
+    let center = bounds.center();
+    Quad.el()
+        .with(
+            translation(),
+            vec3(center.x, center.y, bounds.min.z - 0.001),
+        )
+        .with(scale(), Vec3::ONE * bounds.size().length().max(1.) * 4.)
+        .with(color(), vec4(0.3, 0.3, 0.3, 1.))
+        .spawn_static(world);
+}
+
+fn spawn_lighting(world: &mut World, preset: LightingPreset, bounds: &AABB) {
+    let (sun_rotation, diffuse, ambient) = match preset {
+        LightingPreset::Studio => (
+            Quat::from_rotation_z(PI / 4.) * Quat::from_rotation_y(-1.),
+            Vec3::ONE * 2.,
+            Vec3::ONE * 0.3,
+        ),
+        LightingPreset::Outdoor => (
+            Quat::from_rotation_z(-PI / 3.) * Quat::from_rotation_y(-0.8),
+            vec3(1., 0.95, 0.85) * 2.5,
+            vec3(0.25, 0.3, 0.4),
+        ),
+        LightingPreset::Night => (
+            Quat::from_rotation_z(PI / 2.) * Quat::from_rotation_y(-0.6),
+            vec3(0.3, 0.4, 0.7) * 0.4,
+            vec3(0.02, 0.03, 0.06),
+        ),
+    };
+    Entity::new()
+        .with(sun(), 0.)
+        .with(rotation(), sun_rotation)
+        .with(main_scene(), ())
+        .with(light_diffuse(), diffuse)
+        .with(light_ambient(), ambient)
+        .spawn(world);
+    if let LightingPreset::Outdoor = preset {
+        Entity::new()
+            .with(sky(), ())
+            .with(translation(), bounds.center())
+            .spawn(world);
+    }
+}
+
+#[element_component]
+fn Stats(hooks: &mut Hooks, stats: Vec<(String, String)>, clip_node: Option<EntityId>) -> Element {
+    // The stats of an animation are known once its clip has loaded
+    let (clip_stats, set_clip_stats) = use_state(hooks, Vec::new());
+    use_frame(hooks, {
+        let loaded = !clip_stats.is_empty();
+        move |world| {
+            let Some(node) = clip_node.filter(|_| !loaded) else {
+                return;
+            };
+            let Ok(duration) = world.get(node, clip_duration()) else {
+                return;
+            };
+            let bones = world.get_ref(node, bind_ids()).map_or(0, |ids| ids.len());
+            set_clip_stats(vec![
+                ("Duration".to_string(), format!("{duration:.2}s")),
+                ("Animated bones".to_string(), bones.to_string()),
+            ]);
+        }
+    });
+
+    FlowColumn::el(
+        stats
+            .iter()
+            .chain(&clip_stats)
+            .map(|(name, value)| Text::el(format!("{name}: {value}"))),
+    )
+    .with(padding(), Borders::even(STREET).into())
+    .with_background(vec4(0., 0., 0., 0.5))
+}
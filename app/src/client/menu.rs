@@ -0,0 +1,52 @@
+//! The main menu of a package, which runs its client modules without a server.
+use ambient_core::{async_ecs::async_run, name, runtime};
+use ambient_ecs::{Entity, World};
+use ambient_native_std::{asset_url::AbsAssetUrl, cb, log_result};
+use ambient_network::is_synced_resources;
+use ambient_package_semantic_native::{WasmSpawnRequest, WasmSpawnResponse};
+
+use crate::server;
+
+/// Loads the package at `package_url` and its dependencies into `world`, and spawns their client
+/// modules.
+pub fn load_package(world: &mut World, package_url: AbsAssetUrl) {
+    // The packages are registered in the synced resources, which are usually received from the
+    // server
+    Entity::new()
+        .with(name(), "Synced resources".to_string())
+        .with(is_synced_resources(), ())
+        .with(
+            ambient_package_semantic_native::package_id_to_package_entity(),
+            Default::default(),
+        )
+        .spawn(world);
+
+    let async_run = world.resource(async_run()).clone();
+    world.resource(runtime()).spawn(async move {
+        match ambient_package_semantic::Semantic::new(false).await {
+            Ok(semantic) => async_run.run(move |world| {
+                log_result!(ambient_package_semantic_native::initialize_with_semantic(
+                    world,
+                    semantic,
+                    &package_url,
+                    cb(spawn_client_modules),
+                ));
+            }),
+            Err(err) => tracing::error!("Failed to load the menu package: {err:?}"),
+        }
+    });
+}
+
+/// There is no server to run the server modules on.
+fn spawn_client_modules(
+    world: &mut World,
+    request: WasmSpawnRequest,
+) -> anyhow::Result<WasmSpawnResponse> {
+    server::wasm::spawn_package(
+        world,
+        WasmSpawnRequest {
+            server_modules: vec![],
+            ..request
+        },
+    )
+}
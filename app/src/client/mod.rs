@@ -1,45 +1,79 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+use std::{collections::HashMap, future::Future, path::PathBuf, sync::Arc, time::Duration};
 
-use ambient_app::{fps_stats, window_title, AppBuilder};
+use ambient_app::{fps_stats, window_title, AppBuilder, DummySystem};
 use ambient_audio::{AudioMixer, AudioStream};
 use ambient_cameras::UICamera;
-use ambient_client_shared::game_view::GameView;
+use ambient_client_shared::{
+    cursor::CustomCursor,
+    game_view::GameView,
+    input_recording::{self, InputRecorder, InputReplay},
+};
 use ambient_core::{
     asset_cache, gpu, runtime,
     timing::TimingEventType,
-    window::{window_ctl, ExitStatus, WindowCtl},
+    window::{focus_loss_behavior, window_ctl, ExitStatus, WindowCtl},
 };
-use ambient_ecs::{Entity, SystemGroup};
+use ambient_ecs::{generated::messages, Entity, SystemGroup, World};
 use ambient_element::{
-    consume_context, element_component, use_effect, use_ref_with, use_spawn, use_state,
-    use_state_with, Element, ElementComponentExt, Group, Hooks,
+    consume_context, element_component, use_effect, use_ref_with, use_rerender_signal,
+    use_runtime_message, use_spawn, use_state, use_state_with, Element, ElementComponentExt, Group,
+    Hooks,
 };
 use ambient_native_std::{
     asset_cache::{AssetCache, SyncAssetKeyExt},
-    cb,
+    asset_url::AbsAssetUrl,
+    cb, Cb,
 };
 use ambient_network::{
-    client::{client_network_stats, GameClientRenderTarget},
+    client::{
+        client_network_stats, connect_to_server, pause_server, CleanupFunc, ClientState,
+        GameClientRenderTarget, LoadedFunc,
+    },
+    client_game_state::ClientGameState,
     hooks::use_remote_resource,
-    native::client::{ClientView, ResolvedAddr},
+    native::{
+        client::{ClientView, ResolvedAddr},
+        local_client::LocalClientView,
+        simulation::NetworkConditions,
+    },
 };
 use ambient_settings::SettingsKey;
 use ambient_sys::time::Instant;
-use ambient_ui_native::{Dock, WindowSized};
+use ambient_ui_native::{notifications::NotificationStack, Dock, WindowSized};
 use glam::uvec2;
+use parking_lot::Mutex;
 
+use self::world_switch::{WorldFade, Worlds};
 use crate::{
-    cli::{ClientCli, GoldenImageCommand},
+    cli::{join::resolve_host, ClientCli, GoldenImageCommand},
+    server::{self, ServerHandle},
     shared::{self, certs::CERT},
 };
 
+mod menu;
 mod wasm;
+mod world_switch;
+
+/// What the client does when it starts.
+#[derive(Debug, Clone)]
+pub enum ClientStart {
+    /// Connects to this server.
+    Server(ResolvedAddr),
+    /// Runs the client modules of the package at this URL without a server, until one of them
+    /// connects to a server.
+    Menu(AbsAssetUrl),
+}
 
 /// Construct an app and enter the main client view
+///
+/// `start` resolves what the client does when it starts, and the server that this client is
+/// hosting, if any, e.g. by building the package and starting its server. It runs while the
+/// window and the GPU are being set up, so that they don't add up to the startup time. If another
+/// player takes over as the host, the hosted server is handed over to them when the client quits.
 pub fn run(
     rt: &tokio::runtime::Runtime,
     assets: AssetCache,
-    server_addr: ResolvedAddr,
+    start: impl Future<Output = anyhow::Result<(ClientStart, Option<ServerHandle>)>>,
     args: &ClientCli,
     golden_image_output_dir: Option<PathBuf>,
 ) -> anyhow::Result<()> {
@@ -57,7 +91,20 @@ pub fn run(
     let mixer = audio_stream.as_ref().map(|v| v.mixer().clone());
     let settings = SettingsKey.get(&assets);
 
-    let user_id = match args.user_id.clone().or(settings.general.user_id) {
+    let input_recorder = match &args.record_input {
+        Some(path) => Some(Arc::new(Mutex::new(InputRecorder::create(path)?))),
+        None => None,
+    };
+    let input_replay = match &args.replay_input {
+        Some(path) => Some(Arc::new(Mutex::new(InputReplay::load(path)?))),
+        None => None,
+    };
+
+    let user_id = match args
+        .user_id
+        .clone()
+        .or_else(|| settings.general.user_id.clone())
+    {
         Some(user_id) => user_id,
         None => {
             let user_id = ambient_client_shared::util::random_username();
@@ -113,7 +160,12 @@ pub fn run(
         builder
     };
 
-    let mut app = rt.block_on(builder.build()).expect("Failed to create app");
+    let (app, start) = rt.block_on(futures::future::join(builder.build(), start));
+    let mut app = app.expect("Failed to create app");
+    let (start, hosted_server) = start?;
+
+    // This client may become the host through host migration
+    let hosted_server = Arc::new(Mutex::new(hosted_server));
 
     *app.world.resource_mut(window_title()) = "Ambient".to_string();
 
@@ -124,19 +176,35 @@ pub fn run(
     let fail_on_version_mismatch = !args.dev_allow_version_mismatch;
 
     MainApp {
-        server_addr,
-        user_id,
+        start,
+        user_id: user_id.clone(),
+        spectator: args.spectate,
         fail_on_version_mismatch,
+        network_conditions: args.network_conditions(),
         show_debug: is_debug,
         golden_image_cmd: args.golden_image,
         golden_image_output_dir,
+        input_recorder,
+        input_replay,
         cert,
-        mixer,
+        world_settings: WorldSettings {
+            mixer,
+            analytics_opt_out: settings.general.analytics_opt_out,
+            locale: settings.general.locale(),
+            quality_tier: settings.render.quality_tier.clone(),
+        },
+        hosted_server: hosted_server.clone(),
     }
     .el()
     .spawn_interactive(&mut app.world);
 
     let status = app.run_blocking();
+
+    let hosted_server = hosted_server.lock().take();
+    if let Some(hosted_server) = hosted_server {
+        rt.block_on(hosted_server.migrate_host(&user_id));
+    }
+
     match status {
         ExitStatus::SUCCESS => Ok(()),
         ExitStatus::FAILURE => {
@@ -176,79 +244,246 @@ fn TitleUpdater(hooks: &mut Hooks) -> Element {
     Element::new()
 }
 
+/// The settings of the client that are applied to each world it joins.
+#[derive(Clone, Debug)]
+struct WorldSettings {
+    mixer: Option<AudioMixer>,
+    analytics_opt_out: bool,
+    locale: String,
+    quality_tier: Option<String>,
+}
+impl WorldSettings {
+    fn initialize(
+        &self,
+        world: &mut World,
+        connect: Cb<dyn Fn(String) + Sync + Send>,
+    ) -> anyhow::Result<()> {
+        let assets = world.resource(asset_cache()).clone();
+
+        world.add_resource(ambient_core::locale(), self.locale.clone());
+        let quality_tier = self
+            .quality_tier
+            .clone()
+            .unwrap_or_else(|| ambient_client_shared::quality_tier::target_tier(world));
+        world.add_resource(ambient_core::quality_tier(), quality_tier);
+        world.add_resource(connect_to_server(), connect);
+        wasm::initialize(world, &assets, self.mixer.clone(), self.analytics_opt_out)?;
+        ambient_voice::client::initialize(world);
+
+        UICamera.el().spawn_static(world);
+        NotificationStack.el().spawn_interactive(world);
+
+        Ok(())
+    }
+}
+
 #[element_component]
 fn MainApp(
     hooks: &mut Hooks,
-    server_addr: ResolvedAddr,
+    start: ClientStart,
     golden_image_output_dir: Option<PathBuf>,
     user_id: String,
+    spectator: bool,
     fail_on_version_mismatch: bool,
+    network_conditions: NetworkConditions,
     show_debug: bool,
     golden_image_cmd: Option<GoldenImageCommand>,
+    input_recorder: Option<Arc<Mutex<InputRecorder>>>,
+    input_replay: Option<Arc<Mutex<InputReplay>>>,
     cert: Option<Vec<u8>>,
-    mixer: Option<AudioMixer>,
+    world_settings: WorldSettings,
+    hosted_server: Arc<Mutex<Option<ServerHandle>>>,
 ) -> Element {
     let (loaded, set_loaded) = use_state(hooks, false);
+    let assets = hooks.world.resource(asset_cache()).clone();
 
-    Group::el([
-        UICamera.el(),
-        ambient_client_shared::player::PlayerRawInputHandler.el(),
-        WindowSized::el([ClientView {
-            server_addr,
-            user_id,
-            fail_on_version_mismatch,
-            // NOTE: client.game_state is **locked** and accesible through game_state.
-            //
-            // This is to prevent another thread from updating using the client after connection but
-            // just before `on_loaded`. This is a very small window of time, but does occasionally
-            // happen, especially when joining a server which is already running and finished
-            // loading.
-            on_loaded: cb(move |_, game_state| {
-                let world = &mut game_state.world;
-                let assets = world.resource(asset_cache()).clone();
+    use_runtime_message::<messages::WindowFocusChange>(hooks, {
+        let mixer = world_settings.mixer.clone();
+        move |world, event| {
+            if let Some(mixer) = &mixer {
+                mixer.set_muted(!event.focused && world.resource(focus_loss_behavior()).mute_audio);
+            }
+        }
+    });
 
-                wasm::initialize(world, &assets, mixer.clone()).unwrap();
+    // The world being shown, and the world being preloaded when switching to another server
+    let worlds = use_ref_with(hooks, |_| Worlds::new(start));
+    let rerender = use_rerender_signal(hooks);
+    let connect: Cb<dyn Fn(String) + Sync + Send> = {
+        let assets = assets.clone();
+        let runtime = hooks.world.resource(runtime()).clone();
+        let worlds = worlds.clone();
+        let rerender = rerender.clone();
+        cb(move |address: String| {
+            let assets = assets.clone();
+            let worlds = worlds.clone();
+            let rerender = rerender.clone();
+            runtime.spawn(async move {
+                match resolve_host(Some(address.clone()), &assets).await {
+                    Ok(server_addr) => {
+                        tracing::info!("Connecting to {address:?}");
+                        worlds.lock().preload(ClientStart::Server(server_addr));
+                        rerender();
+                    }
+                    Err(err) => tracing::error!("Failed to resolve {address:?}: {err:?}"),
+                }
+            });
+        })
+    };
+    let pause: Cb<dyn Fn(bool) -> anyhow::Result<()> + Sync + Send> = {
+        let hosted_server = hosted_server.clone();
+        cb(move |paused: bool| match &*hosted_server.lock() {
+            Some(server) => server.set_paused(paused),
+            None => anyhow::bail!("This client is not hosting the server"),
+        })
+    };
 
-                UICamera.el().spawn_static(world);
+    // NOTE: client.game_state is **locked** and accesible through game_state.
+    //
+    // This is to prevent another thread from updating using the client after connection but
+    // just before `on_loaded`. This is a very small window of time, but does occasionally
+    // happen, especially when joining a server which is already running and finished
+    // loading.
+    let on_loaded = |menu_package: Option<AbsAssetUrl>| -> LoadedFunc {
+        let world_settings = world_settings.clone();
+        let connect = connect.clone();
+        let pause = pause.clone();
+        let set_loaded = set_loaded.clone();
+        cb(
+            move |_: &ClientState,
+                  game_state: &mut ClientGameState|
+                  -> anyhow::Result<CleanupFunc> {
+                let world = &mut game_state.world;
+                world_settings.initialize(world, connect.clone())?;
+                match &menu_package {
+                    Some(menu_package) => menu::load_package(world, menu_package.clone()),
+                    None => world.add_resource(pause_server(), pause.clone()),
+                }
                 set_loaded(true);
 
                 Ok(Box::new(|| {
                     tracing::info!("Disconnecting client");
                 }))
-            }),
-            systems_and_resources: cb(|| {
-                let mut resources = Entity::new();
-
-                let bistream_handlers = HashMap::new();
-                resources.set(
-                    ambient_network::client::bi_stream_handlers(),
-                    bistream_handlers,
-                );
-
-                let unistream_handlers = HashMap::new();
-                resources.set(
-                    ambient_network::client::uni_stream_handlers(),
-                    unistream_handlers,
-                );
-
-                let dgram_handlers = HashMap::new();
-                resources.set(ambient_network::client::datagram_handlers(), dgram_handlers);
-
-                (systems(), resources)
-            }),
-            cert,
-            create_rpc_registry: cb(shared::create_server_rpc_registry),
-            inner: Dock::el(vec![
-                TitleUpdater.el(),
-                if let Some(golden_image_cmd) = golden_image_cmd.filter(|_| loaded) {
-                    GoldenImageTest::el(golden_image_output_dir, golden_image_cmd)
-                } else {
-                    Element::new()
-                },
-                GameView { show_debug }.el(),
-            ]),
-        }
-        .el()]),
+            },
+        )
+    };
+    let replaying = input_replay.is_some();
+    // Golden images are taken once the replayed input has ended
+    let replay_duration = input_replay
+        .as_ref()
+        .map_or(Duration::ZERO, |replay| replay.lock().duration());
+    let systems_and_resources = cb(move || {
+        let mut resources = Entity::new();
+
+        let bistream_handlers = HashMap::new();
+        resources.set(
+            ambient_network::client::bi_stream_handlers(),
+            bistream_handlers,
+        );
+
+        let unistream_handlers = HashMap::new();
+        resources.set(
+            ambient_network::client::uni_stream_handlers(),
+            unistream_handlers,
+        );
+
+        let dgram_handlers = HashMap::new();
+        resources.set(ambient_network::client::datagram_handlers(), dgram_handlers);
+
+        (
+            systems(input_recorder.clone(), input_replay.clone()),
+            resources,
+        )
+    });
+    // The inner element has the same structure while the world is preloaded and once it is
+    // shown, so that the game view is kept when the world is swapped in
+    let inner = |slot: usize, background: bool| {
+        let on_preloaded = background.then(|| {
+            let worlds = worlds.clone();
+            cb(move || worlds.lock().on_preloaded(slot)) as Cb<dyn Fn() + Sync + Send>
+        });
+        Dock::el(vec![
+            if background {
+                Element::new()
+            } else {
+                TitleUpdater.el()
+            },
+            match golden_image_cmd.filter(|_| loaded && !background) {
+                Some(golden_image_cmd) => GoldenImageTest::el(
+                    golden_image_output_dir.clone(),
+                    golden_image_cmd,
+                    replay_duration,
+                ),
+                None => Element::new(),
+            },
+            GameView {
+                show_debug,
+                on_preloaded,
+            }
+            .el(),
+        ])
+    };
+
+    let slots = worlds.lock().slots().collect::<Vec<_>>();
+    let world_views = slots
+        .into_iter()
+        .map(|(slot, world, background)| match world {
+            Some(ClientStart::Server(server_addr)) => ClientView {
+                server_addr: server_addr.clone(),
+                local_server: hosted_server
+                    .lock()
+                    .as_ref()
+                    .and_then(|server| server.local_connector(server_addr)),
+                user_id: user_id.clone(),
+                spectator,
+                fail_on_version_mismatch,
+                network_conditions,
+                on_loaded: on_loaded(None),
+                systems_and_resources: systems_and_resources.clone(),
+                cert: cert.clone(),
+                create_rpc_registry: cb(shared::create_server_rpc_registry),
+                on_host_migration: Some({
+                    let assets = assets.clone();
+                    let hosted_server = hosted_server.clone();
+                    cb(move |snapshot, port| {
+                        let assets = assets.clone();
+                        let hosted_server = hosted_server.clone();
+                        Box::pin(async move {
+                            tracing::info!("Taking over as the host");
+                            let handle = server::start_migrated(assets, snapshot, port).await?;
+                            *hosted_server.lock() = Some(handle);
+                            Ok(()) as anyhow::Result<()>
+                        })
+                    })
+                }),
+                background,
+                inner: inner(slot, background),
+            }
+            .el()
+            // Connecting to another server in the same slot replaces the connection
+            .key(server_addr.addr.to_string()),
+            Some(ClientStart::Menu(menu_package)) => LocalClientView {
+                user_id: user_id.clone(),
+                systems_and_resources: systems_and_resources.clone(),
+                on_loaded: on_loaded(Some(menu_package)),
+                inner: inner(slot, background),
+            }
+            .el(),
+            None => Element::new(),
+        })
+        .collect::<Vec<_>>();
+
+    Group::el([
+        UICamera.el(),
+        // The replayed input replaces the input of the player
+        if replaying {
+            Element::new()
+        } else {
+            ambient_client_shared::player::PlayerRawInputHandler.el()
+        },
+        WindowSized::el(world_views),
+        WorldFade::el(worlds, rerender),
+        CustomCursor.el(),
     ])
 }
 
@@ -257,6 +492,7 @@ fn GoldenImageTest(
     hooks: &mut Hooks,
     golden_image_output_dir: Option<PathBuf>,
     golden_image_cmd: GoldenImageCommand,
+    replay_duration: Duration,
 ) -> Element {
     let (render_target, _) = consume_context::<GameClientRenderTarget>(hooks).unwrap();
     let render_target_ref = use_ref_with(hooks, |_| render_target.clone());
@@ -283,7 +519,8 @@ fn GoldenImageTest(
                 let gpu = world.resource(gpu()).clone();
                 world.resource(runtime()).spawn(async move {
                     // Wait until image is sufficiently converged.
-                    tokio::time::sleep(Duration::from_secs_f32(wait_seconds)).await;
+                    tokio::time::sleep(replay_duration + Duration::from_secs_f32(wait_seconds))
+                        .await;
 
                     // Capture current frame.
                     let render_target = render_target_ref.lock().clone();
@@ -334,6 +571,9 @@ fn GoldenImageTest(
                     interval.tick().await;
                     loop {
                         interval.tick().await;
+                        if start_time.elapsed() < replay_duration {
+                            continue;
+                        }
 
                         // Capture current frame.
                         let mut new = render_target
@@ -350,7 +590,8 @@ fn GoldenImageTest(
                         }
 
                         // Handle timeout.
-                        if start_time.elapsed().as_secs_f32() > timeout_seconds {
+                        if (start_time.elapsed() - replay_duration).as_secs_f32() > timeout_seconds
+                        {
                             tracing::error!(
                                 "Golden image check timed out after {timeout_seconds} seconds!"
                             );
@@ -399,10 +640,17 @@ fn GoldenImageTest(
     Element::new()
 }
 
-fn systems() -> SystemGroup {
+fn systems(
+    input_recorder: Option<Arc<Mutex<InputRecorder>>>,
+    input_replay: Option<Arc<Mutex<InputReplay>>>,
+) -> SystemGroup {
     SystemGroup::new(
         "client",
         vec![
+            match input_replay {
+                Some(replay) => input_recording::replay_system(replay),
+                None => Box::new(DummySystem),
+            },
             Box::new(ambient_prefab::systems()),
             Box::new(ambient_decals::client_systems()),
             Box::new(ambient_primitives::systems()),
@@ -414,6 +662,13 @@ fn systems() -> SystemGroup {
                 TimingEventType::ScriptingStarted,
                 TimingEventType::ScriptingFinished,
             )),
+            Box::new(ambient_client_shared::quality_tier::systems()),
+            Box::new(ambient_client_shared::loading_screen::systems()),
+            Box::new(ambient_voice::client::systems()),
+            match input_recorder {
+                Some(recorder) => input_recording::record_system(recorder),
+                None => Box::new(DummySystem),
+            },
             Box::new(ambient_client_shared::player::systems_final()),
         ],
     )
@@ -13,6 +13,7 @@ pub fn initialize(
     world: &mut World,
     assets: &AssetCache,
     mixer: Option<AudioMixer>,
+    analytics_opt_out: bool,
 ) -> anyhow::Result<()> {
     let messenger = Arc::new(
         |world: &World, id: EntityId, ty: MessageType, message: &str| {
@@ -32,7 +33,7 @@ pub fn initialize(
         world.add_resource(ambient_world_audio::audio_mixer(), mixer);
     }
 
-    ambient_wasm::client::initialize(world, assets, messenger)?;
+    ambient_wasm::client::initialize(world, assets, analytics_opt_out, messenger)?;
 
     Ok(())
 }
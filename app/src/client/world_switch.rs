@@ -0,0 +1,126 @@
+//! Switching between the worlds of the client, such as from the main menu to a server.
+//!
+//! The next world is loaded in the background while the current one keeps running. Once it has
+//! loaded, the screen fades out, the worlds are swapped, and the screen fades back in.
+use std::{sync::Arc, time::Duration};
+
+use ambient_core::transform::translation;
+use ambient_element::{
+    element_component, use_frame, use_state, Element, ElementComponentExt, Hooks,
+};
+use ambient_native_std::Cb;
+use ambient_sys::time::Instant;
+use ambient_ui_native::{UIExt, WindowSized};
+use glam::{vec4, Vec3};
+use parking_lot::Mutex;
+
+use super::ClientStart;
+
+/// How long each half of the fade between two worlds takes.
+const FADE_TIME: Duration = Duration::from_millis(300);
+
+/// The worlds of the client: the one that is shown, and the one being preloaded, if any.
+///
+/// Each world stays in the same slot for as long as it exists, so that its view is kept when it
+/// goes from being preloaded to being shown.
+#[derive(Debug)]
+pub(super) struct Worlds {
+    slots: [Option<ClientStart>; 2],
+    active: usize,
+    fade: Option<Fade>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fade {
+    started: Instant,
+    /// Whether the worlds have been swapped, and the new world is fading in
+    swapped: bool,
+}
+
+impl Worlds {
+    pub fn new(start: ClientStart) -> Self {
+        Self {
+            slots: [Some(start), None],
+            active: 0,
+            fade: None,
+        }
+    }
+
+    /// Starts loading `next` in the background, replacing the world being preloaded, if any.
+    pub fn preload(&mut self, next: ClientStart) {
+        self.slots[1 - self.active] = Some(next);
+        self.fade = None;
+    }
+
+    /// Returns the world in each slot, and whether it is being preloaded.
+    pub fn slots(&self) -> impl Iterator<Item = (usize, Option<ClientStart>, bool)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .map(|(slot, world)| (slot, world.clone(), slot != self.active))
+    }
+
+    /// Starts the fade to the world in `slot`, which has finished preloading.
+    pub fn on_preloaded(&mut self, slot: usize) {
+        if slot != self.active && self.slots[slot].is_some() && self.fade.is_none() {
+            self.fade = Some(Fade {
+                started: Instant::now(),
+                swapped: false,
+            });
+        }
+    }
+
+    /// Advances the fade, and returns the opacity of the screen covering the worlds, and whether
+    /// the worlds were swapped.
+    fn update(&mut self, now: Instant) -> (f32, bool) {
+        let Some(fade) = &mut self.fade else {
+            return (0., false);
+        };
+        let progress = (now.duration_since(fade.started).as_secs_f32() / FADE_TIME.as_secs_f32())
+            .clamp(0., 1.);
+
+        match (fade.swapped, progress >= 1.) {
+            (false, false) => (progress, false),
+            (false, true) => {
+                // The previous world is dropped once it is fully covered
+                fade.swapped = true;
+                fade.started = now;
+                self.slots[self.active] = None;
+                self.active = 1 - self.active;
+                (1., true)
+            }
+            (true, false) => (1. - progress, false),
+            (true, true) => {
+                self.fade = None;
+                (0., false)
+            }
+        }
+    }
+}
+
+/// Covers the worlds while fading between them, and calls `on_swap` when the worlds are swapped.
+#[element_component]
+pub(super) fn WorldFade(
+    hooks: &mut Hooks,
+    worlds: Arc<Mutex<Worlds>>,
+    on_swap: Cb<dyn Fn() + Sync + Send>,
+) -> Element {
+    let (opacity, set_opacity) = use_state(hooks, 0.);
+    use_frame(hooks, move |_| {
+        let (new_opacity, swapped) = worlds.lock().update(Instant::now());
+        if swapped {
+            on_swap();
+        }
+        if new_opacity != opacity {
+            set_opacity(new_opacity);
+        }
+    });
+
+    if opacity <= 0. {
+        return Element::new();
+    }
+    WindowSized(vec![])
+        .el()
+        .with_background(vec4(0., 0., 0., opacity))
+        .with(translation(), -Vec3::Z * 0.98)
+}
@@ -18,10 +18,16 @@ use serde::Deserialize;
 use std::path::Path;
 use tracing_subscriber::{filter::LevelFilter, registry, EnvFilter};
 
+/// Attributes the allocations to the subsystems of the engine; see [ambient_core::memory]
+#[cfg(feature = "memory-profiling")]
+#[global_allocator]
+static ALLOCATOR: ambient_core::memory::TrackingAllocator = ambient_core::memory::TrackingAllocator;
+
 fn main() -> anyhow::Result<()> {
     let rt = ambient_sys::task::make_native_multithreaded_runtime()?;
 
-    setup_logging()?;
+    // The language server talks to the editor over stdout, so it mustn't have logs written to it
+    setup_logging(std::env::args().nth(1).as_deref() == Some("lsp"))?;
 
     ambient_git_rev_init::init().expect("Should be called exactly once");
 
@@ -45,7 +51,6 @@ fn main() -> anyhow::Result<()> {
         }
     }
 
-    PhysicsKey.get(&assets); // Load physics
     AssetsCacheOnDisk.insert(&assets, false); // Disable disk caching for now; see https://github.com/AmbientRun/Ambient/issues/81
 
     let cli = if let Some(launch_json) = LaunchJson::load()? {
@@ -54,6 +59,21 @@ fn main() -> anyhow::Result<()> {
         Cli::parse()
     };
 
+    // PhysX takes a while to initialize, and is only used by the server and the asset pipeline, so
+    // it's loaded when it's first needed. When this process hosts a server, it's loaded in the
+    // background while the package builds.
+    let hosts_server = match &cli.command {
+        Commands::Serve(_) => true,
+        Commands::Run(run) => !run.menu,
+        _ => false,
+    };
+    if hosts_server {
+        let assets = assets.clone();
+        rt.spawn_blocking(move || {
+            PhysicsKey.get(&assets);
+        });
+    }
+
     if let Some(package) = cli.package() {
         if package.project {
             tracing::warn!("`-p`/`--project` has no semantic meaning.");
@@ -102,8 +122,11 @@ fn main() -> anyhow::Result<()> {
 
         // non-package commands
         Commands::Assets { assets: command } => rt.block_on(cli::assets::handle(command, &assets)),
+        Commands::ImportScene(args) => rt.block_on(cli::import_scene::handle(args, &assets)),
         Commands::Login => rt.block_on(cli::login::handle(&assets)),
         Commands::Join(join) => cli::join::handle(join, &rt, assets),
+        Commands::View(view) => cli::view::handle(view, &rt, assets),
+        Commands::Lsp(lsp) => cli::lsp::handle(lsp, &rt),
     }
 }
 
@@ -144,7 +167,7 @@ impl LaunchJson {
     }
 }
 
-fn setup_logging() -> anyhow::Result<()> {
+fn setup_logging(log_to_stderr: bool) -> anyhow::Result<()> {
     // This fixes the `<unknown time>` in log formatting, an alternative is to use UTC time
     unsafe { time::util::local_offset::set_soundness(time::util::local_offset::Soundness::Unsound) }
 
@@ -202,13 +225,21 @@ fn setup_logging() -> anyhow::Result<()> {
     }
 
     #[cfg(not(feature = "tracing-tree"))]
-    let format_layer = tracing_subscriber::fmt::Layer::new().compact().with_timer(
-        tracing_subscriber::fmt::time::LocalTime::new(
+    let format_layer = tracing_subscriber::fmt::Layer::new()
+        .compact()
+        .with_timer(tracing_subscriber::fmt::time::LocalTime::new(
             time::format_description::parse("[hour]:[minute]:[second]")
                 .expect("format string should be valid!"),
-        ),
-    );
+        ))
+        .with_writer(if log_to_stderr {
+            tracing_subscriber::fmt::writer::BoxMakeWriter::new(std::io::stderr)
+        } else {
+            tracing_subscriber::fmt::writer::BoxMakeWriter::new(std::io::stdout)
+        });
 
+    // tracing-tree always writes to stderr
+    #[cfg(feature = "tracing-tree")]
+    let _ = log_to_stderr;
     #[cfg(feature = "tracing-tree")]
     let format_layer = tracing_tree::HierarchicalLayer::default()
         .with_targets(false)
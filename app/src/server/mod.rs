@@ -7,10 +7,12 @@ use std::{
     time::Duration,
 };
 
-use ambient_core::{asset_cache, main_package_name, name, FIXED_SERVER_TICK_TIME};
+use ambient_core::{asset_cache, main_package_name, name};
 use ambient_ecs::{
-    dont_store, generated::network::components::no_sync, world_events, ComponentDesc, Entity,
-    Networked, SystemGroup, World, WorldContext, WorldEventsSystem, WorldStreamCompEvent,
+    dont_store,
+    generated::network::components::{max_players, no_sync, tick_rate},
+    world_events, ComponentDesc, Entity, Networked, SystemGroup, World, WorldContext,
+    WorldEventsSystem, WorldStreamCompEvent,
 };
 use ambient_native_std::{
     ambient_version,
@@ -19,14 +21,21 @@ use ambient_native_std::{
     cb,
 };
 use ambient_network::{
+    autosave::{self, Autosaves},
+    bans::BanList,
+    host_migration::{self, HostSnapshot},
     is_persistent_resources, is_synced_resources,
     native::{
         client::ResolvedAddr,
+        local_connection::LocalConnector,
         server::{Crypto, GameServer},
     },
-    server::{ForkingEvent, ProxySettings, SharedServerState, ShutdownEvent},
+    server::{
+        ban_list, ForkingEvent, ProxySettings, SharedServerState, ShutdownEvent, MAIN_INSTANCE_ID,
+    },
 };
 use ambient_sys::task::RuntimeHandle;
+use ambient_wasm::server::storage::{storage, Storage};
 use anyhow::Context;
 use axum::{
     extract::{Host, State},
@@ -40,13 +49,28 @@ use parking_lot::Mutex;
 use rustls::{Certificate, PrivateKey, ServerConfig};
 use tower_http::{cors::CorsLayer, services::ServeDir};
 
-use crate::{cli::package::HostCli, shared};
+use crate::{
+    cli::package::{serve::get_crypto, HostCli},
+    shared,
+};
 
 pub mod wasm;
 
 pub struct ServerHandle {
     addr: SocketAddr,
     join_handle: tokio::task::JoinHandle<()>,
+    server_state: Arc<Mutex<Option<SharedServerState>>>,
+    /// Whether another player can take over as the host when this server shuts down
+    host_migration: bool,
+    local_connector: LocalConnector,
+}
+impl std::fmt::Debug for ServerHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServerHandle")
+            .field("addr", &self.addr)
+            .field("host_migration", &self.host_migration)
+            .finish_non_exhaustive()
+    }
 }
 impl ServerHandle {
     pub async fn join(self) -> Result<(), tokio::task::JoinError> {
@@ -56,8 +80,115 @@ impl ServerHandle {
     pub fn resolve_as_localhost(&self) -> ResolvedAddr {
         ResolvedAddr::localhost_with_port(self.addr.port())
     }
+
+    /// Returns the connector that a client in this process uses to connect to the server
+    /// in-memory, if `server_addr` is the address of this server.
+    pub fn local_connector(&self, server_addr: &ResolvedAddr) -> Option<LocalConnector> {
+        (server_addr.addr == self.resolve_as_localhost().addr).then(|| self.local_connector.clone())
+    }
+
+    /// Hands the session over to one of the connected players, if host migration is enabled.
+    ///
+    /// Called when `host_user_id`, the player hosting the session, quits, before the server shuts
+    /// down.
+    pub async fn migrate_host(&self, host_user_id: &str) {
+        if !self.host_migration {
+            return;
+        }
+        let Some(state) = self.server_state.lock().clone() else {
+            return;
+        };
+
+        let migration =
+            host_migration::start_migration(&mut state.lock(), host_user_id, self.addr.port());
+        match migration {
+            Some(migration) => {
+                tracing::info!("{} is taking over as the host", migration.successor);
+                // Give the connections time to send the migration to their clients
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            None => tracing::info!("No players can take over as the host"),
+        }
+    }
+
+    /// Pauses or resumes the simulation of the server.
+    ///
+    /// Only servers with at most one connected player can be paused, as pausing would stop the
+    /// game for the other players too.
+    pub fn set_paused(&self, paused: bool) -> anyhow::Result<()> {
+        let state = self
+            .server_state
+            .lock()
+            .clone()
+            .context("The server has not started yet")?;
+        let mut state = state.lock();
+        anyhow::ensure!(
+            !paused || state.player_count() <= 1,
+            "Only single-player games can be paused"
+        );
+        state.paused = paused;
+        Ok(())
+    }
 }
 
+/// Starts a server that takes over from a previous host, restoring the world from `snapshot`.
+///
+/// The package is loaded from the content base URL of the previous host, and the server listens
+/// on `port` once it is free.
+pub async fn start_migrated(
+    assets: AssetCache,
+    snapshot: HostSnapshot,
+    port: u16,
+) -> anyhow::Result<ServerHandle> {
+    let content_base_url = ContentBaseUrlKey.get(&assets);
+    let manifest = content_base_url
+        .push("ambient.toml")?
+        .download_string(&assets)
+        .await
+        .context("Failed to download the manifest of the package")?;
+    let manifest = ambient_package::Manifest::parse(&manifest)?;
+
+    let host_cli = HostCli {
+        bind_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        public_host: None,
+        http_interface_port: None,
+        use_https: false,
+        quic_interface_port: Some(port),
+        no_proxy: true,
+        proxy: None,
+        proxy_pre_cache_assets: false,
+        cert: None,
+        key: None,
+        shutdown_after_inactivity_seconds: None,
+        max_players: None,
+        tick_rate: None,
+    };
+    let crypto = get_crypto(&host_cli)?;
+
+    // The previous host may be running on the same machine, and still be shutting down
+    for _ in 0..MIGRATION_BIND_ATTEMPTS {
+        if std::net::UdpSocket::bind(SocketAddr::new(host_cli.bind_address, port)).is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    Ok(start(
+        assets,
+        &host_cli,
+        content_base_url.clone(),
+        content_base_url,
+        std::env::current_dir()?,
+        manifest,
+        crypto,
+        Some(snapshot),
+    )
+    .await)
+}
+
+/// How many times to check whether the port of the previous host has been freed.
+const MIGRATION_BIND_ATTEMPTS: u32 = 20;
+
 #[allow(clippy::too_many_arguments)]
 pub async fn start(
     assets: AssetCache,
@@ -67,6 +198,7 @@ pub async fn start(
     working_directory: PathBuf,
     manifest: ambient_package::Manifest,
     crypto: Crypto,
+    snapshot: Option<HostSnapshot>,
 ) -> ServerHandle {
     let quic_interface_port = host_cli.quic_interface_port;
 
@@ -114,6 +246,7 @@ pub async fn start(
     };
 
     let addr = server.local_addr();
+    let local_connector = server.local_connector();
 
     tracing::info!("Created server, running at {addr}");
     let http_interface_port = host_cli.http_interface_port.unwrap_or(HTTP_INTERFACE_PORT);
@@ -131,6 +264,16 @@ pub async fn start(
         (_, addr) => addr.to_string(),
     };
 
+    // The other players must be able to download the package after this host has left
+    let content_is_local = matches!(build_root_path.to_file_path(), Ok(Some(_)));
+    let host_migration = manifest.hosting.host_migration && !content_is_local;
+    if manifest.hosting.host_migration && content_is_local {
+        tracing::warn!(
+            "Host migration is disabled, as the package is served by this host; deploy the package \
+            and run the deployment to enable it"
+        );
+    }
+
     // here the key is inserted into the asset cache
     let server_state_holder = Arc::new(Mutex::new(None));
     if let Ok(Some(build_path_fs)) = build_root_path.to_file_path() {
@@ -162,6 +305,18 @@ pub async fn start(
         );
     }
 
+    let player_limit = host_cli.max_players.unwrap_or(manifest.hosting.max_players);
+    let server_tick_rate = host_cli
+        .tick_rate
+        .or(manifest.hosting.tick_rate)
+        .filter(|rate| *rate > 0)
+        .unwrap_or(ambient_network::server::DEFAULT_TICK_RATE);
+    let tick_time = ambient_network::server::tick_time_from_rate(server_tick_rate);
+
+    // Locally-hosted builds are also streamed to the clients over the game connection
+    let asset_stream_root = build_root_path.to_file_path().ok().flatten();
+
+    let server_state = server_state_holder.clone();
     let join_handle = tokio::task::spawn(async move {
         let mut server_world = World::new_with_config("server", WorldContext::Server, true);
         server_world.init_shape_change_tracking();
@@ -169,7 +324,7 @@ pub async fn start(
         server_world
             .add_components(
                 server_world.resource_entity(),
-                create_resources(assets.clone()),
+                create_resources(assets.clone(), tick_time, asset_stream_root),
             )
             .unwrap();
 
@@ -178,10 +333,25 @@ pub async fn start(
         server_world
             .add_components(
                 server_world.resource_entity(),
-                Entity::new().with(main_package_name(), name),
+                Entity::new()
+                    .with(main_package_name(), name)
+                    .with(max_players(), player_limit)
+                    .with(tick_rate(), server_tick_rate),
             )
             .unwrap();
 
+        // The bans are kept next to the data of the server, so that they outlive it
+        let bans = BanList::load(working_directory.join("bans.json")).unwrap_or_else(|err| {
+            tracing::error!("{err:?}");
+            BanList::default()
+        });
+        let bans = Arc::new(Mutex::new(bans));
+        server_world.add_resource(ban_list(), bans.clone());
+        server_world.add_resource(
+            storage(),
+            Arc::new(Mutex::new(Storage::new(working_directory.join("storage")))),
+        );
+
         Entity::new()
             .with(ambient_core::name(), "Synced resources".to_string())
             .with(is_synced_resources(), ())
@@ -197,9 +367,33 @@ pub async fn start(
             .with(is_persistent_resources(), ())
             .spawn(&mut server_world);
 
+        let config = autosave::ServerConfig::load(&working_directory.join(autosave::CONFIG_FILE))
+            .unwrap_or_else(|err| {
+                tracing::error!("{err:?}");
+                autosave::ServerConfig::default()
+            });
+        let autosaves = config
+            .autosave
+            .map(|config| Autosaves::new(working_directory.join(autosave::SAVES_DIR), config));
+
+        // Restore the world of the previous host, or the last save, before the packages are started
+        let migrated = snapshot.is_some();
+        if let Some(snapshot) = snapshot {
+            snapshot.restore(&mut server_world);
+        } else if let Some(autosaves) = autosaves
+            .as_ref()
+            .filter(|autosaves| autosaves.config().load_on_start)
+        {
+            if let Some((path, save)) = autosaves.load_latest() {
+                tracing::info!(?path, "Loading the last save");
+                autosave::restore(&mut server_world, save);
+            }
+        }
+
         wasm::initialize(&mut server_world, &assets, working_directory.join("data"))
             .await
             .unwrap();
+        ambient_voice::server::initialize(&mut server_world);
 
         ambient_package_semantic_native::initialize(
             &mut server_world,
@@ -209,7 +403,35 @@ pub async fn start(
         .await
         .unwrap();
 
+        if migrated {
+            let server_state_holder = server_state_holder.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(host_migration::RECONNECT_TIMEOUT).await;
+                let Some(state) = server_state_holder.lock().clone() else {
+                    return;
+                };
+                let mut state = state.lock();
+                if let Some(instance) = state.instances.get_mut(MAIN_INSTANCE_ID) {
+                    host_migration::despawn_unclaimed_players(&mut instance.world);
+                }
+            });
+        }
+
+        let autosave_task = autosaves.clone().map(|autosaves| {
+            let server_state_holder = server_state_holder.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(autosaves.config().interval());
+                // The first tick completes immediately
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    save_world(&autosaves, &server_state_holder).await;
+                }
+            })
+        });
+
         tracing::debug!("Starting server");
+        let state_holder = server_state_holder.clone();
         server
             .run(
                 server_world,
@@ -217,25 +439,62 @@ pub async fn start(
                 Arc::new(on_forking_systems),
                 Arc::new(on_shutdown_systems),
                 Arc::new(is_sync_component),
-                Arc::new(move |state| *server_state_holder.lock() = Some(state)),
+                Arc::new(move |state| *state_holder.lock() = Some(state)),
             )
             .await;
+
+        // Save the final state of the world when the server stops
+        if let (Some(task), Some(autosaves)) = (autosave_task, autosaves) {
+            task.abort();
+            save_world(&autosaves, &server_state_holder).await;
+        }
+        // The bans are written in the background, so wait for the last changes to be saved
+        if let Err(err) = tokio::task::spawn_blocking(move || bans.lock().flush()).await {
+            tracing::error!("Failed to save the ban list: {err:?}");
+        }
     });
 
-    ServerHandle { addr, join_handle }
+    ServerHandle {
+        addr,
+        join_handle,
+        server_state,
+        host_migration,
+        local_connector,
+    }
+}
+
+/// Saves the main world of the server, if it has started.
+async fn save_world(autosaves: &Autosaves, server_state: &Mutex<Option<SharedServerState>>) {
+    let Some(state) = server_state.lock().clone() else {
+        return;
+    };
+    let save = {
+        let state = state.lock();
+        let Some(instance) = state.instances.get(MAIN_INSTANCE_ID) else {
+            return;
+        };
+        autosave::capture(&instance.world)
+    };
+
+    let autosaves = autosaves.clone();
+    match tokio::task::spawn_blocking(move || autosaves.save(&save)).await {
+        Ok(Ok(path)) => tracing::debug!(?path, "Saved the world"),
+        Ok(Err(err)) => tracing::error!("Failed to save the world: {err:?}"),
+        Err(err) => tracing::error!("Failed to save the world: {err:?}"),
+    }
 }
 
 fn systems(_world: &mut World) -> SystemGroup {
     SystemGroup::new(
         "server",
         vec![
-            ambient_physics::run_simulation_system(),
-            // Can happen *during* the physics step
+            // Waits for the physics step started at the end of the previous tick
+            ambient_physics::fetch_simulation_system(),
             Box::new(ambient_core::async_ecs::async_ecs_systems()),
             Box::new(ambient_prefab::systems()),
             Box::new(ambient_core::hierarchy::systems()),
-            // Happens after the physics step
-            ambient_physics::fetch_simulation_system(),
+            Box::new(ambient_core::tag_index::systems()),
+            Box::new(ambient_core::stable_ids::systems()),
             Box::new(ambient_animation::animation_systems()),
             Box::new(ambient_physics::physx::sync_ecs_physics()),
             Box::new(ambient_core::transform::TransformSystem::new()),
@@ -246,6 +505,8 @@ fn systems(_world: &mut World) -> SystemGroup {
             Box::new(ambient_physics::server_systems()),
             Box::new(ambient_package_semantic_native::server_systems()),
             Box::new(wasm::systems()),
+            // Runs while the world is replicated, and until the start of the next tick
+            ambient_physics::run_simulation_system(),
         ],
     )
 }
@@ -272,7 +533,11 @@ fn is_sync_component(component: ComponentDesc, _: WorldStreamCompEvent) -> bool
     component.has_attribute::<Networked>()
 }
 
-fn create_resources(assets: AssetCache) -> Entity {
+fn create_resources(
+    assets: AssetCache,
+    tick_time: Duration,
+    asset_stream_root: Option<PathBuf>,
+) -> Entity {
     let mut server_resources = Entity::new()
         .with(name(), "Resources".to_string())
         .with(asset_cache(), assets.clone())
@@ -282,13 +547,19 @@ fn create_resources(assets: AssetCache) -> Entity {
     server_resources.merge(ambient_core::async_ecs::async_ecs_resources());
     server_resources.set(ambient_core::runtime(), RuntimeHandle::current());
 
-    server_resources.merge(ambient_core::time_resources_start(FIXED_SERVER_TICK_TIME));
+    server_resources.merge(ambient_core::time_resources_start(tick_time));
 
     let mut bistream_handlers = HashMap::new();
     ambient_network::server::register_rpc_bi_stream_handler(
         &mut bistream_handlers,
         shared::create_server_rpc_registry(),
     );
+    if let Some(root) = asset_stream_root {
+        ambient_network::asset_stream::register_server_bi_stream_handler(
+            &mut bistream_handlers,
+            root,
+        );
+    }
     server_resources.set(
         ambient_network::server::bi_stream_handlers(),
         bistream_handlers,
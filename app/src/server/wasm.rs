@@ -32,7 +32,15 @@ pub async fn initialize(
     );
 
     let hosted = std::env::var("AMBIENT_HOSTED").is_ok();
-    ambient_wasm::server::initialize(world, assets, hosted, data_path, messenger)?;
+    let analytics_opt_out = std::env::var("AMBIENT_ANALYTICS_OPT_OUT").is_ok();
+    ambient_wasm::server::initialize(
+        world,
+        assets,
+        hosted,
+        analytics_opt_out,
+        data_path,
+        messenger,
+    )?;
 
     Ok(())
 }
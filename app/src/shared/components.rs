@@ -3,8 +3,12 @@ pub(crate) fn init() -> anyhow::Result<()> {
     ambient_network::init_all_components();
     ambient_physics::init_all_components();
     ambient_wasm::shared::init_all_components();
+    ambient_wasm::client::microphone::init_components();
+    ambient_wasm::server::storage::init_components();
+    ambient_wasm::server::interest::init_components();
     ambient_decals::init_components();
     ambient_world_audio::init_components();
+    ambient_voice::init_components();
     ambient_primitives::init_components();
     ambient_sky::init_components();
     ambient_water::init_components();
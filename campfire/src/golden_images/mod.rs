@@ -26,6 +26,8 @@ use crate::package::get_all_packages;
 
 const TEST_BASE_PATH: &str = "guest/rust";
 const TEST_MANIFEST: &str = "golden-image-manifest.toml";
+/// The input replayed by a test before its image is taken, if the test has one.
+const TEST_INPUT: &str = "golden-image-input.jsonl";
 
 #[derive(Parser, Clone)]
 pub struct GoldenImages {
@@ -192,8 +194,9 @@ fn update_tests(i: usize, ambient_path: &str, name: &str) -> (String, Vec<String
     let test_path = format!("{TEST_BASE_PATH}/{name}");
     let quic_port = (9000 + i as u16).to_string();
     let http_port = (10000 + i as u16).to_string();
+    let replay_input = replay_input_args(&test_path);
 
-    let args = vec![
+    let mut args = vec![
         "run".to_string(),
         "--release".to_string(),
         test_path,
@@ -204,6 +207,9 @@ fn update_tests(i: usize, ambient_path: &str, name: &str) -> (String, Vec<String
         "--http-interface-port".to_string(),
         http_port,
         "--mute-audio".to_string(),
+    ];
+    args.extend(replay_input);
+    args.extend([
         "golden-image-update".to_string(),
         // Todo: Ideally this waiting should be unnecessary, because
         // we only care about rendering the first frame of the test,
@@ -213,7 +219,7 @@ fn update_tests(i: usize, ambient_path: &str, name: &str) -> (String, Vec<String
         // flakiness.
         "--wait-seconds".to_string(),
         "60.0".to_string(),
-    ];
+    ]);
 
     (ambient_path.to_string(), args)
 }
@@ -222,8 +228,9 @@ fn check_tests(i: usize, ambient_path: &str, name: &str) -> (String, Vec<String>
     let test_path = format!("{TEST_BASE_PATH}/{name}");
     let quic_port = (9000 + i as u16).to_string();
     let http_port = (10000 + i as u16).to_string();
+    let replay_input = replay_input_args(&test_path);
 
-    let args = [
+    let mut args = vec![
         "run".to_string(),
         "--release".to_string(),
         test_path,
@@ -234,13 +241,28 @@ fn check_tests(i: usize, ambient_path: &str, name: &str) -> (String, Vec<String>
         "--http-interface-port".to_string(),
         http_port,
         "--mute-audio".to_string(),
+    ];
+    args.extend(replay_input);
+    args.extend([
         "golden-image-check".to_string(),
         // Todo: See notes on --wait-seconds from above.
         "--timeout-seconds".to_string(),
         "60.0".to_string(),
-    ];
+    ]);
 
-    (ambient_path.to_string(), args.to_vec())
+    (ambient_path.to_string(), args)
+}
+
+fn replay_input_args(test_path: &str) -> Vec<String> {
+    let input_path = PathBuf::from(test_path).join(TEST_INPUT);
+    if input_path.exists() {
+        vec![
+            "--replay-input".to_string(),
+            input_path.to_string_lossy().to_string(),
+        ]
+    } else {
+        vec![]
+    }
 }
 
 async fn run<S: AsRef<str>>(
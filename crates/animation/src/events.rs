@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use ambient_ecs::{
+    components,
+    generated::animation::{
+        components::{animation_event_names, animation_event_times},
+        messages::AnimationEvent,
+    },
+    world_events, Debuggable, EntityId, World, WorldEventsExt,
+};
+
+use crate::player::play_clip;
+
+components!("animation", {
+    @[Debuggable]
+    clip_playback: ClipPlayback,
+});
+
+/// The time at which a play clip node was sampled in the last frame, which the events that its
+/// playback passes are found from.
+#[derive(Debug, Clone)]
+pub struct ClipPlayback {
+    start_time: Option<Duration>,
+    /// The time in the clip, before it is wrapped for looping
+    time: f64,
+}
+
+/// A play clip node that was sampled this frame.
+#[derive(Debug, Clone)]
+pub(crate) struct PlayedClip {
+    pub node: EntityId,
+    pub start_time: Option<Duration>,
+    pub time: f64,
+    pub duration: f64,
+    pub looping: bool,
+}
+
+/// Sends an `AnimationEvent` message for each event of the clips of `player` that was passed
+/// since the last frame.
+pub(crate) fn send_animation_events(world: &mut World, player: EntityId, played: Vec<PlayedClip>) {
+    for clip in played {
+        // The clip is played from its current loop when it starts or restarts
+        let last_time = world
+            .get_ref(clip.node, clip_playback())
+            .ok()
+            .filter(|last| last.start_time == clip.start_time)
+            .map(|last| last.time);
+        for name in events(world, clip.node)
+            .into_iter()
+            .filter(|(_, time)| passed(&clip, last_time, *time as f64))
+            .map(|(name, _)| name)
+        {
+            world
+                .resource_mut(world_events())
+                .add_message(AnimationEvent {
+                    player,
+                    node: clip.node,
+                    name,
+                });
+        }
+        world
+            .add_component(
+                clip.node,
+                clip_playback(),
+                ClipPlayback {
+                    start_time: clip.start_time,
+                    time: clip.time,
+                },
+            )
+            .ok();
+    }
+}
+
+/// The events of the clip of `node` and of the node itself.
+fn events(world: &World, node: EntityId) -> Vec<(String, f32)> {
+    let mut events = match world.get_ref(node, play_clip()) {
+        Ok(clip) => clip
+            .events
+            .iter()
+            .map(|event| (event.name.clone(), event.time))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    if let Ok(names) = world.get_ref(node, animation_event_names()) {
+        let times = world
+            .get_ref(node, animation_event_times())
+            .map(|times| times.as_slice())
+            .unwrap_or_default();
+        events.extend(
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.clone(), times.get(i).copied().unwrap_or(0.))),
+        );
+    }
+    events
+}
+
+/// Whether the playback of the clip passed `event_time` since it was at `last_time`, which is
+/// exclusive, or since the start of its current loop if it just started. Events are sent at most
+/// once per frame, even if the clip looped several times.
+fn passed(clip: &PlayedClip, last_time: Option<f64>, event_time: f64) -> bool {
+    if clip.looping && clip.duration > 0. {
+        let next = match last_time {
+            // The first time after `last_time` at which a loop of the clip reaches the event
+            Some(last_time) => {
+                event_time
+                    + ((last_time - event_time) / clip.duration).floor() * clip.duration
+                    + clip.duration
+            }
+            None => {
+                (clip.time / clip.duration).floor() * clip.duration
+                    + event_time.rem_euclid(clip.duration)
+            }
+        };
+        next <= clip.time
+    } else {
+        let after_last = match last_time {
+            Some(last_time) => last_time < event_time,
+            None => true,
+        };
+        after_last && event_time <= clip.time
+    }
+}
+
+#[test]
+fn test_passed() {
+    let clip = |time, looping| PlayedClip {
+        node: EntityId::null(),
+        start_time: None,
+        time,
+        duration: 1.,
+        looping,
+    };
+    assert!(passed(&clip(0.6, false), Some(0.4), 0.5));
+    assert!(!passed(&clip(0.6, false), Some(0.5), 0.5));
+    assert!(passed(&clip(0., false), None, 0.));
+    assert!(!passed(&clip(2., false), Some(1.5), 0.5));
+    assert!(passed(&clip(2.6, true), Some(2.4), 0.5));
+    assert!(passed(&clip(3.1, true), Some(2.9), 0.));
+    assert!(!passed(&clip(2.4, true), Some(1.6), 0.5));
+    assert!(passed(&clip(5.2, true), None, 0.));
+    assert!(!passed(&clip(5.2, true), None, 0.5));
+}
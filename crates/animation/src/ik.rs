@@ -0,0 +1,227 @@
+use std::f32::consts::PI;
+
+use ambient_core::transform::{
+    fbx_pre_rotation, get_current_world_transform, local_to_parent, rotation,
+};
+use ambient_ecs::{
+    generated::{
+        animation::components::{
+            ik_look_at, ik_look_at_axis, ik_max_angle, ik_model, ik_pole, ik_target, ik_two_bone,
+            ik_weight,
+        },
+        hierarchy::components::parent,
+    },
+    query, EntityId, SystemGroup, World,
+};
+use ambient_model::animation_binder;
+use glam::{Quat, Vec3};
+
+const EPSILON: f32 = 1e-4;
+
+/// Modifies the pose of animated models with inverse kinematics. This runs after the animation
+/// players have been applied to the bones, and changes their `rotation`s.
+pub fn ik_systems() -> SystemGroup {
+    SystemGroup::new(
+        "ik_systems",
+        vec![
+            query((ik_two_bone(), ik_model(), ik_target())).to_system(|q, world, qs, _| {
+                for (id, (bind_id, model, target)) in q.collect_cloned(world, qs) {
+                    let pole = world.get(id, ik_pole()).ok();
+                    let weight = world.get(id, ik_weight()).unwrap_or(1.);
+                    solve_two_bone(world, model, &bind_id, target, pole, weight);
+                }
+            }),
+            query((ik_look_at(), ik_model(), ik_target())).to_system(|q, world, qs, _| {
+                for (id, (bind_id, model, target)) in q.collect_cloned(world, qs) {
+                    let axis = world.get(id, ik_look_at_axis()).unwrap_or(Vec3::Z);
+                    let max_angle = world.get(id, ik_max_angle()).unwrap_or(PI);
+                    let weight = world.get(id, ik_weight()).unwrap_or(1.);
+                    solve_look_at(world, model, &bind_id, target, axis, max_angle, weight);
+                }
+            }),
+        ],
+    )
+}
+
+fn solve_two_bone(
+    world: &mut World,
+    model: EntityId,
+    bind_id: &str,
+    target: EntityId,
+    pole: Option<EntityId>,
+    weight: f32,
+) -> Option<()> {
+    let end = bone(world, model, bind_id)?;
+    let middle = bone_parent(world, end)?;
+    let upper = bone_parent(world, middle)?;
+
+    let (upper_delta, middle_delta) = two_bone_rotations(
+        world_position(world, upper),
+        world_position(world, middle),
+        world_position(world, end),
+        world_position(world, target),
+        pole.map(|pole| world_position(world, pole)),
+    )?;
+    // The middle bone is rotated relative to the upper bone before it is rotated
+    let upper_parent_rotation = bone_parent_rotation(world, upper);
+    let middle_parent_rotation = world_rotation(world, upper);
+    rotate_bone(world, upper, upper_parent_rotation, upper_delta, weight);
+    rotate_bone(world, middle, middle_parent_rotation, middle_delta, weight);
+    Some(())
+}
+
+fn solve_look_at(
+    world: &mut World,
+    model: EntityId,
+    bind_id: &str,
+    target: EntityId,
+    axis: Vec3,
+    max_angle: f32,
+    weight: f32,
+) -> Option<()> {
+    let bone = bone(world, model, bind_id)?;
+    let (_, bone_rotation, position) =
+        get_current_world_transform(world, bone).to_scale_rotation_translation();
+    let forward = (bone_rotation * axis).try_normalize()?;
+    let direction = (world_position(world, target) - position).try_normalize()?;
+
+    let mut delta = Quat::from_rotation_arc(forward, direction);
+    let (delta_axis, angle) = delta.to_axis_angle();
+    if angle > max_angle {
+        delta = Quat::from_axis_angle(delta_axis, max_angle.max(0.));
+    }
+    let parent_rotation = bone_parent_rotation(world, bone);
+    rotate_bone(world, bone, parent_rotation, delta, weight);
+    Some(())
+}
+
+/// The world space rotations of the upper and middle bones of a chain with joints at `a`, `b` and
+/// `c` that bring its end to `t`, bending towards `pole` if there is one. The rotation of the
+/// middle bone is relative to its pose before the upper bone is rotated.
+fn two_bone_rotations(
+    a: Vec3,
+    b: Vec3,
+    c: Vec3,
+    t: Vec3,
+    pole: Option<Vec3>,
+) -> Option<(Quat, Quat)> {
+    let upper_length = (b - a).length();
+    let lower_length = (c - b).length();
+    if upper_length < EPSILON || lower_length < EPSILON {
+        return None;
+    }
+    // Targets out of reach are reached towards
+    let target_length = (t - a).length().clamp(
+        (upper_length - lower_length).abs() + EPSILON,
+        upper_length + lower_length - EPSILON,
+    );
+
+    // The chain bends in its current plane, or towards the pole if it's straight
+    let axis = match (c - a).cross(b - a).try_normalize() {
+        Some(axis) => axis,
+        None => match pole.and_then(|pole| (c - a).cross(pole - a).try_normalize()) {
+            Some(axis) => axis,
+            None => (c - a).try_normalize()?.any_orthonormal_vector(),
+        },
+    };
+
+    // The angles at the upper and middle joints, now and once the chain reaches the target
+    let angle = |u: Vec3, v: Vec3| u.normalize().dot(v.normalize()).clamp(-1., 1.).acos();
+    let law_of_cosines = |adjacent_0: f32, adjacent_1: f32, opposite: f32| {
+        ((adjacent_0.powi(2) + adjacent_1.powi(2) - opposite.powi(2))
+            / (2. * adjacent_0 * adjacent_1))
+            .clamp(-1., 1.)
+            .acos()
+    };
+    let upper_angle = angle(c - a, b - a);
+    let middle_angle = angle(a - b, c - b);
+    let upper_target_angle = law_of_cosines(upper_length, target_length, lower_length);
+    let middle_target_angle = law_of_cosines(upper_length, lower_length, target_length);
+
+    let upper_bend = Quat::from_axis_angle(axis, upper_target_angle - upper_angle);
+    let middle_bend = Quat::from_axis_angle(axis, middle_target_angle - middle_angle);
+
+    // The bent chain is then swung around the upper joint to point at the target
+    let bent_c = a + upper_bend * (b - a) + middle_bend * upper_bend * (c - b);
+    let direction = (t - a).try_normalize()?;
+    let swing = Quat::from_rotation_arc((bent_c - a).try_normalize()?, direction);
+    let mut upper_delta = swing * upper_bend;
+
+    // ...and twisted around the direction to the target to bend towards the pole
+    if let Some(pole) = pole {
+        let project = |v: Vec3| v - direction * direction.dot(v);
+        let middle = project(upper_delta * (b - a));
+        let pole = project(pole - a);
+        if middle.length_squared() > EPSILON && pole.length_squared() > EPSILON {
+            let twist = direction.dot(middle.cross(pole)).atan2(middle.dot(pole));
+            upper_delta = Quat::from_axis_angle(direction, twist) * upper_delta;
+        }
+    }
+
+    Some((upper_delta, upper_bend.inverse() * middle_bend * upper_bend))
+}
+
+/// Rotates `bone` by `delta` in world space, given the world rotation of its parent. The result is
+/// blended with its current pose by `weight`.
+fn rotate_bone(world: &mut World, bone: EntityId, parent_rotation: Quat, delta: Quat, weight: f32) {
+    // The `rotation` of a bone is applied after its parent's rotation and its FBX pre-rotation
+    let frame = parent_rotation * world.get(bone, fbx_pre_rotation()).unwrap_or_default();
+    if let Ok(bone_rotation) = world.get_mut(bone, rotation()) {
+        let rotated = frame.inverse() * delta * frame * *bone_rotation;
+        *bone_rotation = bone_rotation
+            .slerp(rotated, weight.clamp(0., 1.))
+            .normalize();
+    }
+}
+
+fn bone(world: &World, model: EntityId, bind_id: &str) -> Option<EntityId> {
+    world
+        .get_ref(model, animation_binder())
+        .ok()?
+        .get(bind_id)
+        .copied()
+}
+
+fn bone_parent(world: &World, bone: EntityId) -> Option<EntityId> {
+    if !world.has_component(bone, local_to_parent()) {
+        return None;
+    }
+    world.get(bone, parent()).ok()
+}
+
+fn bone_parent_rotation(world: &World, bone: EntityId) -> Quat {
+    bone_parent(world, bone).map_or(Quat::IDENTITY, |parent| world_rotation(world, parent))
+}
+
+fn world_position(world: &World, id: EntityId) -> Vec3 {
+    get_current_world_transform(world, id).w_axis.truncate()
+}
+
+fn world_rotation(world: &World, id: EntityId) -> Quat {
+    get_current_world_transform(world, id)
+        .to_scale_rotation_translation()
+        .1
+}
+
+#[test]
+fn test_two_bone_rotations() {
+    let (a, b, c) = (Vec3::ZERO, Vec3::new(0., 0.1, -1.), Vec3::new(0., 0., -2.));
+    for (t, pole) in [
+        (Vec3::new(0.5, 0., -1.5), None),
+        (Vec3::new(0., 0.8, -1.), Some(Vec3::new(0., 0., -3.))),
+        (Vec3::new(0., 0., -3.), Some(Vec3::new(0., 1., -1.))),
+    ] {
+        let (upper, middle) = two_bone_rotations(a, b, c, t, pole).unwrap();
+        let new_b = a + upper * (b - a);
+        let new_c = new_b + upper * middle * (c - b);
+        let reach = (b - a).length() + (c - b).length() - EPSILON;
+        let reachable = t.clamp_length_max(reach);
+        assert!(((new_b - a).length() - (b - a).length()).abs() < 1e-4);
+        assert!(new_c.distance(reachable) < 1e-2, "{new_c} {reachable}");
+        if let Some(pole) = pole {
+            let direction = t.normalize();
+            let towards_pole = (pole - direction * direction.dot(pole)).normalize();
+            assert!((new_b - direction * direction.dot(new_b)).dot(towards_pole) > 0.);
+        }
+    }
+}
@@ -1,19 +1,32 @@
 use ambient_ecs::SystemGroup;
 use player::animation_player_systems;
 
+mod events;
+mod ik;
 mod player;
 mod resources;
 mod retargeting;
+mod state_machine;
 
+pub use events::ClipPlayback;
 pub use resources::*;
 pub use retargeting::*;
+pub use state_machine::StateMachineRuntime;
 
 pub fn init_all_components() {
+    events::init_components();
     player::init_components();
+    state_machine::init_components();
 }
 
 pub fn animation_systems() -> SystemGroup {
-    animation_player_systems()
+    SystemGroup::new(
+        "animation_systems",
+        vec![
+            Box::new(animation_player_systems()),
+            Box::new(ik::ik_systems()),
+        ],
+    )
 }
 
 #[test]
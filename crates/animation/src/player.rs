@@ -5,16 +5,25 @@ use std::{
     time::Duration,
 };
 
-use ambient_core::{asset_cache, async_ecs::async_run, epoch_time, runtime};
+use ambient_core::{
+    asset_cache,
+    async_ecs::async_run,
+    epoch_time, runtime,
+    transform::{rotation, translation},
+};
 use ambient_ecs::{
     components,
     generated::animation::components::{
-        animation_errors, apply_animation_player, apply_base_pose, bind_ids, blend, clip_duration,
-        clip_load_error, clip_loaded, freeze_at_percentage, freeze_at_time, is_animation_player,
-        looping, mask_bind_ids, mask_weights, play_clip_from_url, retarget_animation_scaled,
-        retarget_model_from_url, speed, start_time,
+        animation_errors, apply_animation_player, apply_base_pose, bind_ids, blend,
+        blend_space_parameter, blend_space_thresholds, clip_duration, clip_load_error, clip_loaded,
+        freeze_at_percentage, freeze_at_time, is_animation_player, looping, mask_bind_ids,
+        mask_weights, play_clip_from_url, retarget_animation_scaled, retarget_model_from_url,
+        speed, start_time, state_machine_states,
     },
     generated::hierarchy::components::children,
+    generated::physics::components::{
+        ragdoll_bones, ragdoll_rotations, ragdoll_translations, ragdoll_weight,
+    },
     query, ComponentDesc, Debuggable, EntityId, SystemGroup, World,
 };
 use ambient_model::{animation_binder, ModelFromUrl};
@@ -28,6 +37,8 @@ use glam::{Quat, Vec3};
 use itertools::Itertools;
 
 use crate::{
+    events::{send_animation_events, PlayedClip},
+    state_machine::{animation_parameters, state_machine_runtime, update_state_machines},
     AnimationClip, AnimationClipRetargetedFromModel, AnimationOutput, AnimationRetargeting,
     AnimationTarget, AnimationTrackInterpolator, Vec3Field,
 };
@@ -64,9 +75,11 @@ fn sample_animation_node(
     world: &World,
     node: EntityId,
     time: Duration,
+    parameters: &HashMap<String, f32>,
     errors: &mut Vec<String>,
+    played: &mut Vec<PlayedClip>,
 ) -> HashMap<AnimationOutputKey, AnimationOutput> {
-    match sample_animation_node_inner(world, node, time, errors) {
+    match sample_animation_node_inner(world, node, time, parameters, errors, played) {
         Ok(val) => val,
         Err(err) => {
             errors.push(format!("Node {}: {:?}", node, err));
@@ -78,7 +91,9 @@ fn sample_animation_node_inner(
     world: &World,
     node: EntityId,
     time: Duration,
+    parameters: &HashMap<String, f32>,
     errors: &mut Vec<String>,
+    played: &mut Vec<PlayedClip>,
 ) -> anyhow::Result<HashMap<AnimationOutputKey, AnimationOutput>> {
     if world.get_ref(node, play_clip_from_url()).is_ok() {
         let clip = match world.get_ref(node, play_clip()) {
@@ -90,13 +105,22 @@ fn sample_animation_node_inner(
         } else if let Ok(freeze_at_percentage) = world.get(node, freeze_at_percentage()) {
             (freeze_at_percentage * clip.duration()) as f64
         } else {
-            let mut time = match world.get(node, start_time()) {
-                Ok(st) => time.saturating_sub(st).as_secs_f64(),
-                Err(_) => time.as_secs_f64(),
+            let start_time = world.get(node, start_time()).ok();
+            let mut time = match start_time {
+                Some(st) => time.saturating_sub(st).as_secs_f64(),
+                None => time.as_secs_f64(),
             };
             let speed = world.get(node, speed()).unwrap_or(1.);
             time *= speed as f64;
-            if world.get(node, looping()).unwrap_or(false) {
+            let looping = world.get(node, looping()).unwrap_or(false);
+            played.push(PlayedClip {
+                node,
+                start_time,
+                time,
+                duration: clip.duration() as f64,
+                looping,
+            });
+            if looping {
                 time %= clip.duration() as f64;
             }
             time
@@ -127,32 +151,98 @@ fn sample_animation_node_inner(
         if children.len() != 2 {
             anyhow::bail!("Animation blend node needs to have exactly two children");
         }
-        let mut output = sample_animation_node(world, children[0], time, errors);
-        let right = sample_animation_node(world, children[1], time, errors);
+        let left = sample_animation_node(world, children[0], time, parameters, errors, played);
+        let right = sample_animation_node(world, children[1], time, parameters, errors, played);
         let mask = world.get_ref(node, mask()).ok();
-        for (key, value) in right.into_iter() {
-            match output.entry(key.clone()) {
-                Entry::Occupied(mut o) => {
-                    let mut blend_weight = blend_weight;
-                    if let Some(mask) = &mask {
-                        if let AnimationTarget::BinderId(bind_id) = &key.target {
-                            if let Some(weight) = mask.get(bind_id) {
-                                blend_weight = *weight;
-                            }
+        Ok(mix_outputs(left, right, blend_weight, mask))
+    } else if let Ok(parameter) = world.get_ref(node, blend_space_parameter()) {
+        let thresholds = world
+            .get_ref(node, blend_space_thresholds())
+            .context("Blend space node needs `blend_space_thresholds`")?;
+        let children = world.get_ref(node, children())?;
+        if children.len() != thresholds.len() {
+            anyhow::bail!("Blend space node needs to have exactly one child per threshold");
+        }
+        let points = thresholds
+            .iter()
+            .copied()
+            .zip(children.iter().copied())
+            .sorted_by(|a, b| a.0.total_cmp(&b.0))
+            .collect_vec();
+        let value = parameters.get(parameter).copied().unwrap_or(0.);
+        // The two points around the value are blended, and the nearest one is used outside of
+        // the range of the thresholds
+        let next = points.partition_point(|&(threshold, _)| threshold <= value);
+        if next == 0 || next == points.len() {
+            return Ok(match points.get(next.saturating_sub(1)) {
+                Some(&(_, child)) => {
+                    sample_animation_node(world, child, time, parameters, errors, played)
+                }
+                None => Default::default(),
+            });
+        }
+        let (left_threshold, left) = points[next - 1];
+        let (right_threshold, right) = points[next];
+        let weight = (value - left_threshold) / (right_threshold - left_threshold);
+        let left = sample_animation_node(world, left, time, parameters, errors, played);
+        let right = sample_animation_node(world, right, time, parameters, errors, played);
+        Ok(mix_outputs(left, right, weight, None))
+    } else if world.has_component(node, state_machine_states()) {
+        // The state machine is updated before the tree is sampled, and hasn't been yet if it
+        // was just added
+        let Ok(runtime) = world.get_ref(node, state_machine_runtime()) else {
+            return Ok(Default::default());
+        };
+        let children = world.get_ref(node, children())?;
+        let state_node = |state: usize| {
+            children
+                .get(state)
+                .copied()
+                .context("State machine node needs to have exactly one child per state")
+        };
+        let current = state_node(runtime.current)?;
+        let current = sample_animation_node(world, current, time, parameters, errors, played);
+        Ok(match runtime.transition(time) {
+            Some((previous, weight)) => {
+                let previous = state_node(previous)?;
+                let previous =
+                    sample_animation_node(world, previous, time, parameters, errors, played);
+                mix_outputs(previous, current, weight, None)
+            }
+            None => current,
+        })
+    } else {
+        anyhow::bail!("Node is not a proper animation node")
+    }
+}
+
+/// Blends `right` into `left` by `weight`, which `mask` overrides for the bones it contains.
+fn mix_outputs(
+    mut left: HashMap<AnimationOutputKey, AnimationOutput>,
+    right: HashMap<AnimationOutputKey, AnimationOutput>,
+    weight: f32,
+    mask: Option<&HashMap<String, f32>>,
+) -> HashMap<AnimationOutputKey, AnimationOutput> {
+    for (key, value) in right.into_iter() {
+        match left.entry(key.clone()) {
+            Entry::Occupied(mut o) => {
+                let mut blend_weight = weight;
+                if let Some(mask) = &mask {
+                    if let AnimationTarget::BinderId(bind_id) = &key.target {
+                        if let Some(weight) = mask.get(bind_id) {
+                            blend_weight = *weight;
                         }
                     }
-                    let left = o.get_mut();
-                    *left = left.mix(value, blend_weight);
-                }
-                Entry::Vacant(v) => {
-                    v.insert(value);
                 }
+                let left = o.get_mut();
+                *left = left.mix(value, blend_weight);
+            }
+            Entry::Vacant(v) => {
+                v.insert(value);
             }
         }
-        Ok(output)
-    } else {
-        anyhow::bail!("Node is not a proper animation node")
     }
+    left
 }
 
 fn apply_animation_outputs_to_entity(
@@ -305,8 +395,27 @@ pub fn animation_player_systems() -> SystemGroup {
             query((is_animation_player(), children())).to_system(|q, world, qs, _| {
                 let time = *world.resource(epoch_time());
                 for (id, (_, children)) in q.collect_cloned(world, qs) {
-                    let mut errors = Default::default();
-                    let output = sample_animation_node(world, children[0], time, &mut errors);
+                    let parameters = animation_parameters(world, id);
+                    update_state_machines(world, children[0], &parameters, time);
+                }
+                // Sampling only reads the world, so the players are sampled in parallel
+                let world_ref: &World = world;
+                let sampled = q.par_map(world_ref, None, |id, (_, children)| {
+                    let parameters = animation_parameters(world_ref, id);
+                    let mut errors = Vec::new();
+                    let mut played = Vec::new();
+                    let output = sample_animation_node(
+                        world_ref,
+                        children[0],
+                        time,
+                        &parameters,
+                        &mut errors,
+                        &mut played,
+                    );
+                    (id, output, errors, played)
+                });
+                for (id, output, errors, played) in sampled {
+                    send_animation_events(world, id, played);
                     world
                         .add_component(id, animation_output(), AnimationOutputs(output))
                         .ok();
@@ -324,10 +433,49 @@ pub fn animation_player_systems() -> SystemGroup {
                     }
                 }
             }),
+            query((
+                ragdoll_weight(),
+                ragdoll_bones(),
+                ragdoll_rotations(),
+                ragdoll_translations(),
+                animation_binder(),
+            ))
+            .to_system(|q, world, qs, _| {
+                for (_, (&weight, bones, rotations, translations, binder)) in q.iter(world, qs) {
+                    if weight > 0. {
+                        blend_ragdoll_pose(world, binder, bones, rotations, translations, weight);
+                    }
+                }
+            }),
         ],
     )
 }
 
+/// Blends the bones of a ragdoll from their animated pose to the pose simulated by the server.
+fn blend_ragdoll_pose(
+    world: &World,
+    binder: &HashMap<String, EntityId>,
+    bones: &[String],
+    rotations: &[Quat],
+    translations: &[Vec3],
+    weight: f32,
+) {
+    let weight = weight.min(1.);
+    for ((bone, &ragdoll_rotation), &ragdoll_translation) in
+        bones.iter().zip(rotations).zip(translations)
+    {
+        let Some(&id) = binder.get(bone) else {
+            continue;
+        };
+        if let Ok(rotation) = world.get_mut_unsafe(id, rotation()) {
+            *rotation = rotation.slerp(ragdoll_rotation, weight);
+        }
+        if let Ok(translation) = world.get_mut_unsafe(id, translation()) {
+            *translation = translation.lerp(ragdoll_translation, weight);
+        }
+    }
+}
+
 fn build_base_pose(
     assets: &AssetCache,
     clip_url: &str,
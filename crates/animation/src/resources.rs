@@ -240,12 +240,23 @@ impl AnimationTrackInterpolator {
 
 pub type AnimationClipFromUrl = BincodeFromUrl<AnimationClip>;
 
+/// A named time in a clip, such as a footstep. An `AnimationEvent` message is sent when the
+/// playback of a clip passes it.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct AnimationClipEvent {
+    pub name: String,
+    /// The time in seconds from the start of the clip
+    pub time: f32,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AnimationClip {
     pub id: String,
     pub tracks: Vec<AnimationTrack>,
     pub start: f32,
     pub end: f32,
+    #[serde(default)]
+    pub events: Vec<AnimationClipEvent>,
 }
 impl AnimationClip {
     pub fn from_tracks(tracks: Vec<AnimationTrack>) -> Self {
@@ -260,6 +271,7 @@ impl AnimationClip {
             tracks,
             start: 0.,
             end,
+            events: Vec::new(),
         }
     }
     pub fn duration(&self) -> f32 {
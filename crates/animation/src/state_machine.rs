@@ -0,0 +1,206 @@
+use std::{collections::HashMap, time::Duration};
+
+use ambient_ecs::{
+    components,
+    generated::animation::components::{
+        animation_parameter_names, animation_parameter_values, freeze_at_percentage,
+        freeze_at_time, play_clip_from_url, start_time, state_machine_state, state_machine_states,
+        state_machine_transition_durations, state_machine_transition_from,
+        state_machine_transition_max, state_machine_transition_min,
+        state_machine_transition_parameters, state_machine_transition_to,
+    },
+    generated::hierarchy::components::children,
+    Debuggable, EntityId, World,
+};
+
+components!("animation", {
+    @[Debuggable]
+    state_machine_runtime: StateMachineRuntime,
+});
+
+/// The state of a state machine node, which is updated every frame before it is sampled.
+#[derive(Debug, Clone)]
+pub struct StateMachineRuntime {
+    /// The index of the current state
+    pub current: usize,
+    /// The index of the state that is being blended out, if a transition is in progress
+    pub previous: Option<usize>,
+    transition_start: Duration,
+    transition_duration: f32,
+}
+impl StateMachineRuntime {
+    fn new(time: Duration) -> Self {
+        Self {
+            current: 0,
+            previous: None,
+            transition_start: time,
+            transition_duration: 0.,
+        }
+    }
+
+    /// The previous state and the weight of the current state, if a transition is in progress.
+    pub fn transition(&self, time: Duration) -> Option<(usize, f32)> {
+        let previous = self.previous?;
+        let elapsed = time.saturating_sub(self.transition_start).as_secs_f32();
+        if elapsed >= self.transition_duration {
+            return None;
+        }
+        Some((previous, elapsed / self.transition_duration))
+    }
+}
+
+struct Transition {
+    from: String,
+    to: String,
+    duration: f32,
+    parameter: String,
+    min: f32,
+    max: f32,
+}
+impl Transition {
+    fn condition_met(&self, parameters: &HashMap<String, f32>) -> bool {
+        if self.parameter.is_empty() {
+            return true;
+        }
+        let value = parameters.get(&self.parameter).copied().unwrap_or(0.);
+        self.min <= value && value <= self.max
+    }
+}
+
+/// The transitions of a state machine node. Missing values of the transition components use
+/// their defaults.
+fn transitions(world: &World, node: EntityId) -> Vec<Transition> {
+    let to = world
+        .get_cloned(node, state_machine_transition_to())
+        .unwrap_or_default();
+    let from = world
+        .get_cloned(node, state_machine_transition_from())
+        .unwrap_or_default();
+    let durations = world
+        .get_cloned(node, state_machine_transition_durations())
+        .unwrap_or_default();
+    let parameters = world
+        .get_cloned(node, state_machine_transition_parameters())
+        .unwrap_or_default();
+    let min = world
+        .get_cloned(node, state_machine_transition_min())
+        .unwrap_or_default();
+    let max = world
+        .get_cloned(node, state_machine_transition_max())
+        .unwrap_or_default();
+    to.into_iter()
+        .enumerate()
+        .map(|(i, to)| Transition {
+            from: from.get(i).cloned().unwrap_or_default(),
+            to,
+            duration: durations.get(i).copied().unwrap_or(0.).max(0.),
+            parameter: parameters.get(i).cloned().unwrap_or_default(),
+            min: min.get(i).copied().unwrap_or(f32::NEG_INFINITY),
+            max: max.get(i).copied().unwrap_or(f32::INFINITY),
+        })
+        .collect()
+}
+
+/// The parameters of an animation player, which drive its state machines and blend spaces.
+pub(crate) fn animation_parameters(world: &World, player: EntityId) -> HashMap<String, f32> {
+    let Ok(names) = world.get_ref(player, animation_parameter_names()) else {
+        return HashMap::new();
+    };
+    let values = world
+        .get_ref(player, animation_parameter_values())
+        .map(|values| values.as_slice())
+        .unwrap_or_default();
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.clone(), values.get(i).copied().unwrap_or(0.)))
+        .collect()
+}
+
+/// Takes the transitions of the state machines in the tree of `node` whose conditions are met.
+pub(crate) fn update_state_machines(
+    world: &mut World,
+    node: EntityId,
+    parameters: &HashMap<String, f32>,
+    time: Duration,
+) {
+    if world.has_component(node, state_machine_states()) {
+        update_state_machine(world, node, parameters, time);
+    }
+    for child in world.get_cloned(node, children()).unwrap_or_default() {
+        update_state_machines(world, child, parameters, time);
+    }
+}
+
+fn update_state_machine(
+    world: &mut World,
+    node: EntityId,
+    parameters: &HashMap<String, f32>,
+    time: Duration,
+) {
+    let states = world
+        .get_cloned(node, state_machine_states())
+        .unwrap_or_default();
+    if states.is_empty() {
+        return;
+    }
+    let mut runtime = world
+        .get_cloned(node, state_machine_runtime())
+        .ok()
+        .filter(|runtime| runtime.current < states.len())
+        .unwrap_or_else(|| StateMachineRuntime::new(time));
+    if runtime.transition(time).is_none() {
+        runtime.previous = None;
+    }
+
+    // A transition that is in progress is finished before the next one is taken, so that the
+    // pose doesn't pop
+    if runtime.previous.is_none() {
+        let current = &states[runtime.current];
+        let next = transitions(world, node).into_iter().find_map(|transition| {
+            let from_current = transition.from.is_empty() || &transition.from == current;
+            if !from_current || &transition.to == current || !transition.condition_met(parameters) {
+                return None;
+            }
+            let to = states.iter().position(|state| *state == transition.to)?;
+            Some((to, transition.duration))
+        });
+        if let Some((to, duration)) = next {
+            runtime.previous = (duration > 0.).then_some(runtime.current);
+            runtime.current = to;
+            runtime.transition_start = time;
+            runtime.transition_duration = duration;
+            let state_node = world
+                .get_ref(node, children())
+                .ok()
+                .and_then(|children| children.get(to).copied());
+            if let Some(state_node) = state_node {
+                restart_clips(world, state_node, time);
+            }
+        }
+    }
+
+    let state = &states[runtime.current];
+    if world.get_ref(node, state_machine_state()).ok() != Some(state) {
+        world
+            .add_component(node, state_machine_state(), state.clone())
+            .ok();
+    }
+    world
+        .add_component(node, state_machine_runtime(), runtime)
+        .ok();
+}
+
+/// Plays the clips in the tree of `node` from the beginning, so that entering a state plays its
+/// animations from the start.
+fn restart_clips(world: &mut World, node: EntityId, time: Duration) {
+    if world.has_component(node, play_clip_from_url())
+        && !world.has_component(node, freeze_at_time())
+        && !world.has_component(node, freeze_at_percentage())
+    {
+        world.add_component(node, start_time(), time).ok();
+    }
+    for child in world.get_cloned(node, children()).unwrap_or_default() {
+        restart_clips(world, child, time);
+    }
+}
@@ -12,8 +12,9 @@ use ambient_core::{
     name, performance_samples, refcount_system, remove_at_time_system, runtime, timing,
     transform::TransformSystem,
     window::{
-        cursor_position, get_window_sizes, window_logical_size, window_physical_size,
-        window_scale_factor, ExitStatus, WindowCtl,
+        cursor_image, cursor_position, focus_loss_behavior, get_window_sizes, window_logical_size,
+        window_physical_size, window_scale_factor, CursorImage, ExitStatus, FocusLossBehavior,
+        WindowCtl,
     },
     ClientTimeResourcesSystem, PerformanceSample, RuntimeKey,
 };
@@ -41,7 +42,7 @@ use glam::{uvec2, vec2, IVec2, UVec2, Vec2};
 use parking_lot::Mutex;
 use renderers::{main_renderer, ui_renderer, MainRenderer, UiRenderer};
 use winit::{
-    dpi::PhysicalPosition,
+    dpi::{LogicalPosition, PhysicalPosition},
     event::{ElementState, Event, KeyboardInput, ModifiersState, VirtualKeyCode, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     window::{CursorGrabMode, Fullscreen, Window, WindowBuilder},
@@ -103,8 +104,10 @@ pub fn world_instance_systems(full: bool) -> SystemGroup {
             remove_at_time_system(),
             refcount_system(),
             Box::new(ambient_core::hierarchy::systems()),
+            Box::new(ambient_core::tag_index::systems()),
             Box::new(WorldEventsSystem),
             Box::new(ambient_focus::systems()),
+            Box::new(ambient_drag_drop::systems()),
             if full {
                 Box::new(ambient_input::picking::frame_systems())
             } else {
@@ -355,6 +358,8 @@ impl AppBuilder {
                 window
             };
             let window = Arc::new(window.build(&event_loop).unwrap());
+            // Let input methods compose text, which is sent as `TextInput` messages
+            window.set_ime_allowed(true);
             (Some(window), Some(event_loop))
         };
 
@@ -439,8 +444,11 @@ impl AppBuilder {
 
                 // Set a background color for the canvas to make it easier to tell where the canvas is for debugging purposes.
                 // Use the maximum available width and height as the canvas dimensions.
+                // `touch-action: none` stops the browser from scrolling and zooming the page on
+                // touch, so that the touches are sent to the window instead.
                 canvas.style().set_css_text(&format!(
-                    "background-color: black; width: {}px; height: {}px; z-index: 50",
+                    "background-color: black; width: {}px; height: {}px; z-index: 50; \
+                     touch-action: none",
                     max_width, max_height
                 ));
 
@@ -503,6 +511,11 @@ impl AppBuilder {
         GpuKey.insert(&assets, gpu.clone());
         // WindowKey.insert(&assets, window.clone());
 
+        // Compile the shaders and pipelines that were used by the previous runs before the
+        // renderers are created, so that they don't have to be compiled mid-game
+        #[cfg(not(target_os = "unknown"))]
+        gpu.pipeline_cache.prewarm(&gpu, &assets);
+
         tracing::debug!("Inserting app resources");
         let (ctl_tx, ctl_rx) = self.ctl.unwrap_or_else(flume::unbounded);
 
@@ -530,6 +543,14 @@ impl AppBuilder {
         world
             .add_components(world.resource_entity(), resources)
             .unwrap();
+        world.add_resource(
+            focus_loss_behavior(),
+            FocusLossBehavior {
+                minimize: settings.window.minimize_on_focus_loss,
+                mute_audio: settings.window.mute_on_focus_loss,
+                release_cursor: settings.window.release_cursor_on_focus_loss,
+            },
+        );
         tracing::debug!("Setup renderers");
         if self.ui_renderer || self.main_renderer {
             // let _span = info_span!("setup_renderers").entered();
@@ -554,7 +575,7 @@ impl AppBuilder {
             vec![
                 Box::new(ambient_timings::InputTimingSystem),
                 Box::new(assets_camera_systems()),
-                Box::new(ambient_input::event_systems()),
+                Box::new(ambient_input::event_systems(settings.input.mouse_mode)),
                 Box::new(renderers::systems()),
             ],
         );
@@ -564,6 +585,10 @@ impl AppBuilder {
 
         Ok(App {
             window_focused: true,
+            cursor_grab: CursorGrabMode::None,
+            cursor_visible: true,
+            cursor_image: None,
+            cursor_confinement: None,
             window,
             runtime,
             systems: SystemGroup::new(
@@ -572,6 +597,8 @@ impl AppBuilder {
                     Box::new(MeshBufferUpdate),
                     Box::new(world_instance_systems(true)),
                     ambient_input::cursor_lock_system(cursor_lock_rx),
+                    #[cfg(not(target_os = "unknown"))]
+                    Box::new(ambient_input::gamepad::GamepadSystem::new()),
                 ],
             ),
             world,
@@ -637,6 +664,11 @@ pub struct App {
     modifiers: ModifiersState,
 
     window_focused: bool,
+    cursor_grab: CursorGrabMode,
+    cursor_visible: bool,
+    cursor_image: Option<CursorImage>,
+    /// The rectangle the cursor is kept within, in logical pixels.
+    cursor_confinement: Option<(Vec2, Vec2)>,
     update_title_with_fps_stats: bool,
     #[cfg(target_os = "unknown")]
     _drop_handles: Vec<Box<dyn std::fmt::Debug>>,
@@ -795,6 +827,7 @@ impl App {
                     tracing::trace!(?v, "window control");
                     match v {
                         WindowCtl::GrabCursor(mode) => {
+                            self.cursor_grab = mode;
                             if let Some(window) = &self.window {
                                 match mode {
                                     CursorGrabMode::Confined | CursorGrabMode::Locked => {
@@ -820,16 +853,42 @@ impl App {
                                 window.set_cursor_grab(mode).ok();
                             }
                         }
-                        WindowCtl::ShowCursor(show) => {
+                        WindowCtl::ConfineCursor(rect) => {
+                            self.cursor_confinement = rect.map(|(a, b)| (a.min(b), a.max(b)));
+                            // The OS keeps the cursor within the window, and it is moved back
+                            // into the rectangle when it leaves it
+                            self.cursor_grab = if rect.is_some() {
+                                CursorGrabMode::Confined
+                            } else {
+                                CursorGrabMode::None
+                            };
                             if let Some(window) = &self.window {
-                                window.set_cursor_visible(show);
+                                window.set_cursor_grab(self.cursor_grab).ok();
                             }
                         }
+                        WindowCtl::ShowCursor(show) => {
+                            self.cursor_visible = show;
+                            update_cursor(
+                                world,
+                                self.window.as_deref(),
+                                self.cursor_visible,
+                                &self.cursor_image,
+                            );
+                        }
                         WindowCtl::SetCursorIcon(icon) => {
                             if let Some(window) = &self.window {
                                 window.set_cursor_icon(icon);
                             }
                         }
+                        WindowCtl::SetCursorImage(image) => {
+                            self.cursor_image = image;
+                            update_cursor(
+                                world,
+                                self.window.as_deref(),
+                                self.cursor_visible,
+                                &self.cursor_image,
+                            );
+                        }
                         WindowCtl::SetTitle(title) => {
                             if let Some(window) = &self.window {
                                 window.set_title(&title);
@@ -844,6 +903,17 @@ impl App {
                                 });
                             }
                         }
+                        WindowCtl::OverrideFocusLossBehavior {
+                            minimize,
+                            mute_audio,
+                            release_cursor,
+                        } => {
+                            let behavior = world.resource_mut(focus_loss_behavior());
+                            behavior.minimize = minimize.unwrap_or(behavior.minimize);
+                            behavior.mute_audio = mute_audio.unwrap_or(behavior.mute_audio);
+                            behavior.release_cursor =
+                                release_cursor.unwrap_or(behavior.release_cursor);
+                        }
                         WindowCtl::ExitProcess(exit_status) => {
                             *control_flow = ControlFlow::Exit;
                             return exit_status;
@@ -904,6 +974,25 @@ impl App {
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::Focused(focused) => {
                     self.window_focused = *focused;
+                    // Platforms differ in what they do on their own, so the window is always put
+                    // in the state the behavior asks for
+                    let behavior = *world.resource(focus_loss_behavior());
+                    if let Some(window) = &self.window {
+                        if !focused && behavior.minimize && window.fullscreen().is_some() {
+                            window.set_minimized(true);
+                        }
+                        if behavior.release_cursor {
+                            if *focused {
+                                window.set_cursor_grab(self.cursor_grab).ok();
+                                window.set_cursor_visible(
+                                    self.cursor_visible && self.cursor_image.is_none(),
+                                );
+                            } else {
+                                window.set_cursor_grab(CursorGrabMode::None).ok();
+                                window.set_cursor_visible(true);
+                            }
+                        }
+                    }
                 }
                 WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
                     *self.world.resource_mut(window_scale_factor()) = *scale_factor;
@@ -949,12 +1038,25 @@ impl App {
                 }
                 WindowEvent::CursorMoved { position, .. } => {
                     if self.window_focused {
-                        let p = vec2(position.x as f32, position.y as f32)
+                        let mut p = vec2(position.x as f32, position.y as f32)
                             / self
                                 .window
                                 .as_ref()
                                 .map(|x| x.scale_factor() as f32)
                                 .unwrap_or(1.);
+                        if let Some((min, max)) = self.cursor_confinement {
+                            let confined = p.clamp(min, max);
+                            if confined != p {
+                                if let Some(window) = &self.window {
+                                    window
+                                        .set_cursor_position(LogicalPosition::new(
+                                            confined.x, confined.y,
+                                        ))
+                                        .ok();
+                                }
+                                p = confined;
+                            }
+                        }
                         world
                             .set(world.resource_entity(), cursor_position(), p)
                             .unwrap();
@@ -1003,6 +1105,26 @@ impl System<Event<'static, ()>> for ExamplesSystem {
     }
 }
 
+/// Shows either the OS cursor or the cursor image, which is drawn by the UI of the client.
+fn update_cursor(
+    world: &mut World,
+    window: Option<&Window>,
+    visible: bool,
+    image: &Option<CursorImage>,
+) {
+    if let Some(window) = window {
+        window.set_cursor_visible(visible && image.is_none());
+    }
+    match image.clone().filter(|_| visible) {
+        Some(image) => world.add_resource(cursor_image(), image),
+        None => {
+            world
+                .remove_component(world.resource_entity(), cursor_image())
+                .ok();
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct MeshBufferUpdate;
 impl System for MeshBufferUpdate {
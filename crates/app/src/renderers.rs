@@ -6,10 +6,11 @@ use ambient_gizmos::render::GizmoRenderer;
 use ambient_gpu::{
     blit::{Blitter, BlitterKey},
     gpu::Gpu,
-    shader_module::DEPTH_FORMAT,
-    texture::{Texture, TextureView},
 };
-use ambient_native_std::{asset_cache::SyncAssetKeyExt, color::Color};
+use ambient_native_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    color::Color,
+};
 use ambient_renderer::{renderer_stats, RenderTarget, Renderer, RendererConfig, RendererTarget};
 use ambient_ui_native::app_background_color;
 use glam::{uvec2, UVec2};
@@ -94,16 +95,6 @@ impl MainRenderer {
 
         tracing::debug!("Creating self");
 
-        let is_srgb = gpu.swapchain_format().is_srgb();
-        let gamma_correction = if !is_srgb {
-            tracing::debug!(
-                "Output format is not in sRGB colorspace. Applying manual gamma correction."
-            );
-            Some(2.2)
-        } else {
-            None
-        };
-
         Self {
             main: if main {
                 tracing::debug!("Creating renderer");
@@ -136,12 +127,7 @@ impl MainRenderer {
             } else {
                 None
             },
-            blit: BlitterKey {
-                format: gpu.swapchain_format().into(),
-                min_filter: FilterMode::Nearest,
-                gamma_correction,
-            }
-            .get(assets),
+            blit: swapchain_blitter(gpu, assets),
             render_target,
             size: wind_size,
         }
@@ -295,10 +281,11 @@ impl System for MainRenderer {
     }
 }
 
+/// Renders the UI alone, for apps without a main scene.
 pub struct UiRenderer {
     ui_renderer: Renderer,
-    depth_buffer_view: Arc<TextureView>,
-    normals_view: Arc<TextureView>,
+    blit: Arc<Blitter>,
+    render_target: RenderTarget,
 }
 
 impl UiRenderer {
@@ -307,30 +294,6 @@ impl UiRenderer {
         let gpu = world.resource(gpu()).clone();
         let size = *world.resource(window_physical_size());
 
-        let depth_buffer = Arc::new(Self::create_depth_buffer(
-            &gpu,
-            &PhysicalSize::new(size.x, size.y),
-        ));
-
-        let normals = Arc::new(Texture::new(
-            &gpu,
-            &wgpu::TextureDescriptor {
-                label: Some("RenderTarget.depth_buffer"),
-                size: wgpu::Extent3d {
-                    width: size.x,
-                    height: size.y,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8Snorm,
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
-                    | wgpu::TextureUsages::TEXTURE_BINDING,
-                view_formats: &[],
-            },
-        ));
-
         let mut ui_renderer = Renderer::new(
             &gpu,
             assets,
@@ -343,36 +306,15 @@ impl UiRenderer {
         ui_renderer.post_transparent = Some(Box::new(GizmoRenderer::new(&gpu, assets)));
         Self {
             ui_renderer,
-            depth_buffer_view: Arc::new(depth_buffer.create_view(&Default::default())),
-            normals_view: Arc::new(normals.create_view(&Default::default())),
+            blit: swapchain_blitter(&gpu, assets),
+            render_target: RenderTarget::new(&gpu, size, None),
         }
     }
 
-    fn create_depth_buffer(gpu: &Gpu, size: &PhysicalSize<u32>) -> Texture {
-        Texture::new(
-            gpu,
-            &wgpu::TextureDescriptor {
-                label: Some("RenderTarget.depth_buffer"),
-                size: wgpu::Extent3d {
-                    width: size.width,
-                    height: size.height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: DEPTH_FORMAT,
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
-                    | wgpu::TextureUsages::TEXTURE_BINDING
-                    | wgpu::TextureUsages::COPY_SRC,
-                view_formats: &[],
-            },
-        )
-    }
-
     fn resize(&mut self, gpu: &Gpu, size: &PhysicalSize<u32>) {
-        let depth_buffer = Arc::new(Self::create_depth_buffer(gpu, size));
-        self.depth_buffer_view = Arc::new(depth_buffer.create_view(&Default::default()));
+        if size.width > 0 && size.height > 0 {
+            self.render_target = RenderTarget::new(gpu, uvec2(size.width, size.height), None);
+        }
     }
 
     fn render(&mut self, gpu: &Gpu, world: &mut World) {
@@ -391,29 +333,27 @@ impl UiRenderer {
                 .expect("Failed to acquire next swap chain texture")
         };
 
-        let window_size = world.resource(window_physical_size());
         let frame_view = frame
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
         let mut post_submit = Vec::new();
 
+        // The UI is rendered to a target rather than to the frame, as the frame can't be
+        // multisampled
         self.ui_renderer.render(
             gpu,
             world,
             &mut encoder,
             &mut post_submit,
-            RendererTarget::Direct {
-                color: &frame_view,
-                depth: &self.depth_buffer_view,
-                size: wgpu::Extent3d {
-                    width: window_size.x,
-                    height: window_size.y,
-                    depth_or_array_layers: 1,
-                },
-                normals: &self.normals_view,
-            },
+            RendererTarget::Target(&self.render_target),
             Some(app_background_color()),
         );
+        self.blit.run(
+            gpu,
+            &mut encoder,
+            &self.render_target.color_buffer_view,
+            &frame_view,
+        );
         {
             profiling::scope!("Submit");
             gpu.queue.submit(Some(encoder.finish()));
@@ -427,3 +367,22 @@ impl UiRenderer {
         }
     }
 }
+
+/// A blitter that copies render targets to the frames of the swapchain.
+fn swapchain_blitter(gpu: &Gpu, assets: &AssetCache) -> Arc<Blitter> {
+    let is_srgb = gpu.swapchain_format().is_srgb();
+    let gamma_correction = if !is_srgb {
+        tracing::debug!(
+            "Output format is not in sRGB colorspace. Applying manual gamma correction."
+        );
+        Some(2.2)
+    } else {
+        None
+    };
+    BlitterKey {
+        format: gpu.swapchain_format().into(),
+        min_filter: FilterMode::Nearest,
+        gamma_correction,
+    }
+    .get(assets)
+}
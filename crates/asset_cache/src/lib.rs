@@ -492,6 +492,26 @@ impl AssetCache {
     pub fn runtime(&self) -> &RuntimeHandle {
         &self.runtime
     }
+
+    /// Returns the number of entries of the cache, to find assets that are never released.
+    pub fn stats(&self) -> AssetCacheStats {
+        AssetCacheStats {
+            async_assets: self.async_cache.lock().len(),
+            sync_assets: self.sync.lock().len(),
+            timeline_entries: self.timeline.lock().assets.len(),
+        }
+    }
+}
+
+/// The number of entries of an [AssetCache].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AssetCacheStats {
+    /// Assets loaded with an [AsyncAssetKey], including the ones that are still loading
+    pub async_assets: usize,
+    /// Assets loaded with a [SyncAssetKey]
+    pub sync_assets: usize,
+    /// Assets that have been recorded in the [AssetsTimeline]
+    pub timeline_entries: usize,
 }
 
 impl std::fmt::Debug for AssetCache {
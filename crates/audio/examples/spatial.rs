@@ -145,6 +145,7 @@ async fn main() {
     let listener = Arc::new(Mutex::new(AudioListener {
         transform: Mat4::IDENTITY,
         ear_distance: Vec3::X * 0.2,
+        velocity: Vec3::ZERO,
     }));
 
     let spatial = Arc::new(Mutex::new(AudioEmitter {
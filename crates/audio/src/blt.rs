@@ -13,6 +13,59 @@ pub struct BltCoeffs {
     b2: f32,
 }
 
+impl BltCoeffs {
+    /// The coefficients of a filter that leaves the signal unchanged
+    pub const IDENTITY: Self = Self {
+        a1: 0.0,
+        a2: 0.0,
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+    };
+}
+
+/// The state of a biquad filter, which filters one sample at a time.
+#[derive(Debug, Clone)]
+pub struct Biquad {
+    c: BltCoeffs,
+    x1: Vec2,
+    x2: Vec2,
+    y1: Vec2,
+    y2: Vec2,
+}
+
+impl Biquad {
+    pub fn new(c: BltCoeffs) -> Self {
+        Self {
+            c,
+            x1: Vec2::ZERO,
+            x2: Vec2::ZERO,
+            y1: Vec2::ZERO,
+            y2: Vec2::ZERO,
+        }
+    }
+
+    /// Replaces the coefficients, keeping the state of the filter.
+    pub fn set_coeffs(&mut self, c: BltCoeffs) {
+        self.c = c;
+    }
+
+    pub fn process(&mut self, sample: Vec2) -> Vec2 {
+        let y = self.c.b0 * sample + self.c.b1 * self.x1 + self.c.b2 * self.x2
+            - self.c.a1 * self.y1
+            - self.c.a2 * self.y2;
+
+        // Slide
+        self.x2 = self.x1;
+        self.x1 = sample;
+
+        self.y2 = self.y1;
+        self.y1 = y;
+
+        y
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Hpf {
     pub freq: f32,
@@ -112,6 +165,112 @@ impl TransferFunction for Bpf {
     }
 }
 
+/// Boosts or cuts the frequencies below `freq` by `gain` dB.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LowShelf {
+    pub freq: f32,
+    pub gain: f32,
+}
+
+impl TransferFunction for LowShelf {
+    fn get_coeffs(&self, sample_freq: SampleRate) -> BltCoeffs {
+        let (a, re, alpha) = shelf_params(self.freq, self.gain, sample_freq);
+        let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+
+        let b0 = a * ((a + 1.0) - (a - 1.0) * re + sqrt_a_alpha);
+        let b1 = 2.0 * a * ((a - 1.0) - (a + 1.0) * re);
+        let b2 = a * ((a + 1.0) - (a - 1.0) * re - sqrt_a_alpha);
+
+        let a0 = (a + 1.0) + (a - 1.0) * re + sqrt_a_alpha;
+        let a1 = -2.0 * ((a - 1.0) + (a + 1.0) * re);
+        let a2 = (a + 1.0) + (a - 1.0) * re - sqrt_a_alpha;
+
+        // Normalization step
+        BltCoeffs {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// Boosts or cuts the frequencies above `freq` by `gain` dB.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighShelf {
+    pub freq: f32,
+    pub gain: f32,
+}
+
+impl TransferFunction for HighShelf {
+    fn get_coeffs(&self, sample_freq: SampleRate) -> BltCoeffs {
+        let (a, re, alpha) = shelf_params(self.freq, self.gain, sample_freq);
+        let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+
+        let b0 = a * ((a + 1.0) + (a - 1.0) * re + sqrt_a_alpha);
+        let b1 = -2.0 * a * ((a - 1.0) + (a + 1.0) * re);
+        let b2 = a * ((a + 1.0) + (a - 1.0) * re - sqrt_a_alpha);
+
+        let a0 = (a + 1.0) - (a - 1.0) * re + sqrt_a_alpha;
+        let a1 = 2.0 * ((a - 1.0) - (a + 1.0) * re);
+        let a2 = (a + 1.0) - (a - 1.0) * re - sqrt_a_alpha;
+
+        // Normalization step
+        BltCoeffs {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// Boosts or cuts the frequencies around `freq` by `gain` dB.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Peaking {
+    pub freq: f32,
+    pub gain: f32,
+    // In octaves
+    pub bandwidth: f32,
+}
+
+impl TransferFunction for Peaking {
+    fn get_coeffs(&self, sample_freq: SampleRate) -> BltCoeffs {
+        let a = 10f32.powf(self.gain / 40.0);
+        let w0 = TAU * self.freq / sample_freq as f32;
+        let re = w0.cos();
+
+        let alpha = w0.sin() * (2f32.ln() * 0.5 * self.bandwidth * w0 / w0.sin()).sinh();
+
+        let b0 = 1.0 + alpha * a;
+        let b1 = -2.0 * re;
+        let b2 = 1.0 - alpha * a;
+
+        let a0 = 1.0 + alpha / a;
+        let a1 = -2.0 * re;
+        let a2 = 1.0 - alpha / a;
+
+        // Normalization step
+        BltCoeffs {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// The amplitude, the cosine of the angular frequency, and the alpha of a shelving filter with a
+/// slope of 1.
+fn shelf_params(freq: f32, gain: f32, sample_freq: SampleRate) -> (f32, f32, f32) {
+    let a = 10f32.powf(gain / 40.0);
+    let w0 = TAU * freq / sample_freq as f32;
+    (a, w0.cos(), w0.sin() * 0.5 * 2f32.sqrt())
+}
+
 pub trait TransferFunction {
     fn get_coeffs(&self, sample_freq: SampleRate) -> BltCoeffs;
 }
@@ -122,13 +281,9 @@ where
     Vh: for<'x> Value<'x, Item = H>,
 {
     source: S,
-    c: BltCoeffs,
+    biquad: Biquad,
     filter: Vh,
     prev_filter: H,
-    x1: Vec2,
-    x2: Vec2,
-    y1: Vec2,
-    y2: Vec2,
 }
 
 impl<S, H, Vh> BilinearTransform<S, H, Vh>
@@ -144,11 +299,7 @@ where
 
         Self {
             source,
-            c,
-            x1: Vec2::ZERO,
-            x2: Vec2::ZERO,
-            y1: Vec2::ZERO,
-            y2: Vec2::ZERO,
+            biquad: Biquad::new(c),
             prev_filter: f,
             filter,
         }
@@ -164,25 +315,14 @@ where
     fn next_sample(&mut self) -> Option<crate::Frame> {
         let filter = self.filter.get();
         if self.prev_filter != *filter {
-            self.c = filter.get_coeffs(self.sample_rate());
+            self.biquad
+                .set_coeffs(filter.get_coeffs(self.sample_rate()));
             self.prev_filter = filter.clone();
         }
         drop(filter);
 
         let sample = self.source.next_sample()?;
-
-        let y = self.c.b0 * sample + self.c.b1 * self.x1 + self.c.b2 * self.x2
-            - self.c.a1 * self.y1
-            - self.c.a2 * self.y2;
-
-        // Slide
-        self.x2 = self.x1;
-        self.x1 = sample;
-
-        self.y2 = self.y1;
-        self.y1 = y;
-
-        Some(y)
+        Some(self.biquad.process(sample))
     }
 
     fn sample_rate(&self) -> SampleRate {
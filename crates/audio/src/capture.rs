@@ -0,0 +1,111 @@
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    InputCallbackInfo, Sample,
+};
+
+use crate::{
+    error::{Error, Result},
+    SampleRate,
+};
+
+/// Returns the names of the input devices, such as microphones, that can be captured with
+/// [AudioCapture::with_device].
+pub fn input_devices() -> Result<Vec<String>> {
+    Ok(cpal::default_host()
+        .input_devices()?
+        .filter_map(|device| device.name().ok())
+        .collect())
+}
+
+/// Captures audio from an input device, such as a microphone. Capturing stops when this is
+/// dropped.
+///
+/// Wraps a cpal Stream. Can not be moved across threads.
+pub struct AudioCapture {
+    _stream: cpal::Stream,
+    _device: cpal::Device,
+    sample_rate: SampleRate,
+}
+
+impl AudioCapture {
+    /// Starts capturing the default input device, calling `on_samples` with the captured samples,
+    /// downmixed to mono.
+    ///
+    /// `on_samples` is called on the audio thread, and should not block.
+    pub fn new<F>(on_samples: F) -> Result<Self>
+    where
+        F: FnMut(&[f32]) + Send + 'static,
+    {
+        Self::with_device(None, on_samples)
+    }
+
+    /// Like [AudioCapture::new], but captures the input device named `name`, as returned by
+    /// [input_devices], or the default one if it is `None`.
+    pub fn with_device<F>(name: Option<&str>, on_samples: F) -> Result<Self>
+    where
+        F: FnMut(&[f32]) + Send + 'static,
+    {
+        let host = cpal::default_host();
+        let device = match name {
+            Some(name) => host
+                .input_devices()?
+                .find(|device| device.name().map_or(false, |device| device == name))
+                .ok_or_else(|| Error::InputDeviceNotFound(name.to_string()))?,
+            None => host.default_input_device().ok_or(Error::NoInputDevice)?,
+        };
+
+        let config = device.default_input_config()?;
+        let format = config.sample_format();
+        let config: cpal::StreamConfig = config.into();
+
+        tracing::debug!("Audio capture config: {config:?}");
+        if config.channels < 1 {
+            return Err(Error::InvalidChannelCount(config.channels));
+        }
+
+        let err_func = |err| tracing::error!("Audio capture error: {err}");
+
+        let channels = config.channels as usize;
+
+        fn reader<T, F>(channels: usize, mut on_samples: F) -> impl FnMut(&[T], &InputCallbackInfo)
+        where
+            T: Sample,
+            F: FnMut(&[f32]) + Send + 'static,
+        {
+            let mut buf = Vec::new();
+            move |data, _| {
+                buf.clear();
+                buf.extend(data.chunks(channels).map(|frame| {
+                    frame.iter().map(|sample| sample.to_f32()).sum::<f32>() / channels as f32
+                }));
+
+                on_samples(&buf);
+            }
+        }
+
+        let stream = match format {
+            cpal::SampleFormat::I16 => {
+                device.build_input_stream(&config, reader::<i16, _>(channels, on_samples), err_func)
+            }
+            cpal::SampleFormat::U16 => {
+                device.build_input_stream(&config, reader::<u16, _>(channels, on_samples), err_func)
+            }
+            cpal::SampleFormat::F32 => {
+                device.build_input_stream(&config, reader::<f32, _>(channels, on_samples), err_func)
+            }
+        }?;
+
+        stream.play()?;
+
+        Ok(Self {
+            sample_rate: config.sample_rate.0 as _,
+            _stream: stream,
+            _device: device,
+        })
+    }
+
+    /// The sample rate of the captured samples.
+    pub fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+}
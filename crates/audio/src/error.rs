@@ -11,13 +11,19 @@ use crate::hrtf;
 pub enum Error {
     #[error("Failed to find audio output device")]
     NoOutputDevice,
+    #[error("Failed to find audio input device")]
+    NoInputDevice,
+    #[error("Failed to find audio input device {0:?}")]
+    InputDeviceNotFound(String),
+    #[error("Failed to list audio devices")]
+    Devices(#[from] cpal::DevicesError),
     #[error("Failed to find appropriate audio config")]
     NoOutputConfig,
     #[error("Default stream config error")]
     DefaultStreamConfigError(#[from] cpal::DefaultStreamConfigError),
-    #[error("Failed to build output stream")]
+    #[error("Failed to build audio stream")]
     BuildStreamError(#[from] BuildStreamError),
-    #[error("Failed to play output stream")]
+    #[error("Failed to play audio stream")]
     PlayStreamError(#[from] PlayStreamError),
     #[error("Failed to decode wav")]
     WavError(#[from] hound::Error),
@@ -6,6 +6,8 @@ mod stream;
 
 mod barycentric;
 pub mod blt;
+/// Capturing audio from input devices
+pub mod capture;
 /// Fast fourier transform
 pub mod hrtf;
 pub mod signal;
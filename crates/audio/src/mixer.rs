@@ -1,6 +1,9 @@
 use std::{
     future::Future,
-    sync::{Arc, Weak},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
     task::Poll,
     thread,
     time::Duration,
@@ -104,6 +107,7 @@ pub struct AudioMixerInner {
     pub sample_rate: SampleRate,
     waiters: Mutex<SignalVec>,
     sources: Mutex<SlotMap<SoundId, PlayingSound>>,
+    muted: AtomicBool,
 }
 
 impl std::fmt::Debug for AudioMixerInner {
@@ -122,6 +126,7 @@ impl AudioMixer {
                 sample_rate,
                 sources: Mutex::default(),
                 waiters: Default::default(),
+                muted: AtomicBool::new(false),
             }),
         }
     }
@@ -157,6 +162,30 @@ impl AudioMixer {
         self.inner.sources.lock().remove(key);
     }
 
+    /// Removes a playing sound from the mixer without stopping it, so that it can be played on
+    /// another mixer with the same sample rate.
+    pub fn take(&self, key: SoundId) -> Option<Box<dyn Source>> {
+        self.inner
+            .sources
+            .lock()
+            .remove(key)
+            .map(|sound| sound.source)
+    }
+
+    /// Returns true if both handles refer to the same mixer.
+    pub fn ptr_eq(&self, other: &AudioMixer) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Silences the output. The sounds keep playing while muted, so that they stay in sync.
+    pub fn set_muted(&self, muted: bool) {
+        self.inner.muted.store(muted, Ordering::Relaxed);
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.inner.muted.load(Ordering::Relaxed)
+    }
+
     fn notify_sound_waiters(&self, id: SoundId) {
         // Wake the wakers which are parked on this id, and remove them from the waiting list
         self.inner.waiters.lock().retain_mut(|(sound_id, signal)| {
@@ -192,6 +221,9 @@ impl Source for AudioMixer {
             true
         });
 
+        if self.is_muted() {
+            return Some(Frame::ZERO);
+        }
         Some(res)
     }
 
@@ -212,6 +244,9 @@ impl Source for AudioMixer {
 
             true
         });
+        if self.is_muted() {
+            output.fill(Frame::ZERO);
+        }
 
         output.len()
     }
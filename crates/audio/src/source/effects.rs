@@ -0,0 +1,249 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::{
+    blt::{Biquad, BltCoeffs, HighShelf, LowShelf, Lpf, Peaking, TransferFunction},
+    Frame, SampleRate, Source,
+};
+
+/// The reference rate of the delays of the reverb, which are scaled to the rate of the source.
+const REVERB_RATE: f32 = 44100.0;
+/// The delays of the comb filters of the reverb, in samples at [REVERB_RATE]
+const COMB_DELAYS: [usize; 4] = [1116, 1188, 1277, 1356];
+/// The delays of the all-pass filters of the reverb, in samples at [REVERB_RATE]
+const ALLPASS_DELAYS: [usize; 2] = [556, 441];
+const REVERB_INPUT_GAIN: f32 = 0.03;
+const REVERB_WET_GAIN: f32 = 3.0;
+
+/// The parameters of [Effects], which can be changed while it plays. The effects that are `None`
+/// are bypassed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EffectParams {
+    pub eq: Option<Equalizer>,
+    pub low_pass: Option<Lpf>,
+    pub reverb: Option<Reverb>,
+}
+
+/// A three band equalizer, with the gains in dB of a low shelf at 250 Hz, of a peak at 1 kHz, and
+/// of a high shelf at 4 kHz.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Equalizer {
+    pub low: f32,
+    pub mid: f32,
+    pub high: f32,
+}
+
+/// A simple reverb, made of parallel comb filters followed by all-pass filters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reverb {
+    /// How long the reverb rings, from 0 to 1
+    pub room_size: f32,
+    /// How quickly the high frequencies of the reverb fade, from 0 to 1
+    pub damping: f32,
+    /// How much of the output is reverb, from 0 (dry) to 1 (wet)
+    pub mix: f32,
+}
+
+impl Default for Reverb {
+    fn default() -> Self {
+        Self {
+            room_size: 0.5,
+            damping: 0.5,
+            mix: 0.3,
+        }
+    }
+}
+
+/// Applies insert effects to a source: an [Equalizer], then a low-pass filter, then a [Reverb].
+#[derive(Debug, Clone)]
+pub struct Effects<S> {
+    source: S,
+    params: Arc<Mutex<EffectParams>>,
+    prev_params: EffectParams,
+    eq: [Biquad; 3],
+    low_pass: Biquad,
+    combs: Vec<DelayLine>,
+    allpasses: Vec<DelayLine>,
+}
+
+impl<S: Source> Effects<S> {
+    pub fn new(source: S, params: Arc<Mutex<EffectParams>>) -> Self {
+        let scale = source.sample_rate() as f32 / REVERB_RATE;
+        let delay_line = |delay: &usize| DelayLine::new(((*delay as f32 * scale) as usize).max(1));
+        let mut effects = Self {
+            combs: COMB_DELAYS.iter().map(delay_line).collect(),
+            allpasses: ALLPASS_DELAYS.iter().map(delay_line).collect(),
+            source,
+            params,
+            prev_params: EffectParams::default(),
+            eq: [(); 3].map(|_| Biquad::new(BltCoeffs::IDENTITY)),
+            low_pass: Biquad::new(BltCoeffs::IDENTITY),
+        };
+        let params = effects.params.lock().clone();
+        effects.update_coeffs(&params);
+        effects.prev_params = params;
+        effects
+    }
+
+    fn update_coeffs(&mut self, params: &EffectParams) {
+        let rate = self.source.sample_rate();
+        if let Some(eq) = &params.eq {
+            let coeffs = eq_coeffs(eq, rate);
+            for (biquad, coeffs) in self.eq.iter_mut().zip(coeffs) {
+                biquad.set_coeffs(coeffs);
+            }
+        }
+        if let Some(low_pass) = &params.low_pass {
+            self.low_pass.set_coeffs(low_pass.get_coeffs(rate));
+        }
+    }
+
+    fn reverb(&mut self, reverb: &Reverb, dry: Frame) -> Frame {
+        let feedback = reverb.room_size.clamp(0.0, 1.0) * 0.28 + 0.7;
+        let damping = reverb.damping.clamp(0.0, 1.0) * 0.4;
+
+        let input = dry * REVERB_INPUT_GAIN;
+        let mut wet = Frame::ZERO;
+        for comb in &mut self.combs {
+            let out = comb.read();
+            comb.filter = out * (1.0 - damping) + comb.filter * damping;
+            comb.write(input + comb.filter * feedback);
+            wet += out;
+        }
+        for allpass in &mut self.allpasses {
+            let out = allpass.read();
+            allpass.write(wet + out * 0.5);
+            wet = out - wet;
+        }
+
+        let mix = reverb.mix.clamp(0.0, 1.0);
+        dry * (1.0 - mix) + wet * REVERB_WET_GAIN * mix
+    }
+}
+
+impl<S: Source> Source for Effects<S> {
+    fn next_sample(&mut self) -> Option<Frame> {
+        let params = self.params.lock().clone();
+        if params != self.prev_params {
+            self.update_coeffs(&params);
+            self.prev_params = params.clone();
+        }
+
+        let mut sample = self.source.next_sample()?;
+        if params.eq.is_some() {
+            for biquad in &mut self.eq {
+                sample = biquad.process(sample);
+            }
+        }
+        if params.low_pass.is_some() {
+            sample = self.low_pass.process(sample);
+        }
+        if let Some(reverb) = &params.reverb {
+            sample = self.reverb(reverb, sample);
+        }
+        Some(sample)
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.source.sample_rate()
+    }
+
+    fn sample_count(&self) -> Option<u64> {
+        self.source.sample_count()
+    }
+}
+
+fn eq_coeffs(eq: &Equalizer, rate: SampleRate) -> [BltCoeffs; 3] {
+    [
+        LowShelf {
+            freq: 250.0,
+            gain: eq.low,
+        }
+        .get_coeffs(rate),
+        Peaking {
+            freq: 1000.0,
+            gain: eq.mid,
+            bandwidth: 2.0,
+        }
+        .get_coeffs(rate),
+        HighShelf {
+            freq: 4000.0,
+            gain: eq.high,
+        }
+        .get_coeffs(rate),
+    ]
+}
+
+/// A circular buffer of frames, for the filters of the reverb.
+#[derive(Debug, Clone)]
+struct DelayLine {
+    buffer: Vec<Frame>,
+    cursor: usize,
+    /// The state of the low-pass filter of a comb filter
+    filter: Frame,
+}
+
+impl DelayLine {
+    fn new(len: usize) -> Self {
+        Self {
+            buffer: vec![Frame::ZERO; len],
+            cursor: 0,
+            filter: Frame::ZERO,
+        }
+    }
+
+    fn read(&self) -> Frame {
+        self.buffer[self.cursor]
+    }
+
+    fn write(&mut self, frame: Frame) {
+        self.buffer[self.cursor] = frame;
+        self.cursor = (self.cursor + 1) % self.buffer.len();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::SineWave;
+
+    fn peak(source: impl Source) -> f32 {
+        source
+            .samples_iter()
+            .skip(4410)
+            .take(4410)
+            .map(|v| v.x.abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn bypass() {
+        let params = Arc::new(Mutex::new(EffectParams::default()));
+        let dry = SineWave::new(440.0).take(std::time::Duration::from_millis(10));
+        let wet = Effects::new(dry.clone(), params);
+
+        assert_eq!(
+            dry.samples_iter().collect_vec(),
+            wet.samples_iter().collect_vec()
+        );
+    }
+
+    #[test]
+    fn low_pass() {
+        let params = Arc::new(Mutex::new(EffectParams {
+            low_pass: Some(Lpf {
+                freq: 500.0,
+                bandwidth: 1.0,
+            }),
+            ..Default::default()
+        }));
+        let low = Effects::new(SineWave::new(100.0), params.clone());
+        let high = Effects::new(SineWave::new(8000.0), params);
+
+        assert!(peak(low) > 0.9);
+        assert!(peak(high) < 0.1);
+    }
+}
@@ -2,6 +2,7 @@ mod buffered;
 mod chain;
 mod crossfade;
 pub(crate) mod dynamic_delay;
+mod effects;
 pub mod gain;
 pub mod history;
 mod mix;
@@ -10,6 +11,8 @@ mod oscilloscope;
 mod pad_to;
 mod pan;
 mod peek;
+mod pitch;
+mod playback;
 mod repeat;
 mod sample_bufferer;
 mod sample_rate;
@@ -30,12 +33,15 @@ pub use buffered::*;
 pub use chain::*;
 use circular_queue::CircularQueue;
 pub use crossfade::*;
+pub use effects::*;
 pub use gain::*;
 pub use mix::*;
 pub use onepole::*;
 pub use pan::*;
 use parking_lot::Mutex;
 pub use peek::*;
+pub use pitch::*;
+pub use playback::*;
 pub use repeat::*;
 pub use sample_rate::*;
 pub use slice::*;
@@ -167,6 +173,33 @@ pub trait Source: Send {
         Repeat::new(self)
     }
 
+    /// Plays the source at `pitch` times its rate, where 2 is an octave up and 0.5 is an octave
+    /// down.
+    fn pitch(self, pitch: f32) -> Pitch<Self>
+    where
+        Self: Sized,
+    {
+        Pitch::new(self, pitch)
+    }
+
+    /// Plays the source with the [PlaybackControls] that are shared through `controls`, which
+    /// can pause it, change its pitch, or jump to another time in it while it plays.
+    fn playback(self, looping: bool, controls: Arc<Mutex<PlaybackControls>>) -> Playback<Self>
+    where
+        Self: Sized + Clone,
+    {
+        Playback::new(self, looping, controls)
+    }
+
+    /// Applies the insert effects described by the [EffectParams] that are shared through
+    /// `params`, which can be changed while the source plays.
+    fn effects(self, params: Arc<Mutex<EffectParams>>) -> Effects<Self>
+    where
+        Self: Sized,
+    {
+        Effects::new(self, params)
+    }
+
     fn samples_iter(self) -> SampleIter<Self>
     where
         Self: Sized,
@@ -0,0 +1,34 @@
+use crate::{SampleRate, Source};
+
+/// A source played back at a different rate, which changes both its pitch and its speed.
+///
+/// The source reports a scaled sample rate, so the resampling happens where the source is
+/// converted to the sample rate of the output.
+#[derive(Debug, Clone)]
+pub struct Pitch<S> {
+    source: S,
+    pitch: f32,
+}
+
+impl<S> Pitch<S> {
+    pub fn new(source: S, pitch: f32) -> Self {
+        Self { source, pitch }
+    }
+}
+
+impl<S> Source for Pitch<S>
+where
+    S: Source,
+{
+    fn next_sample(&mut self) -> Option<crate::Frame> {
+        self.source.next_sample()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        ((self.source.sample_rate() as f32 * self.pitch).round() as SampleRate).max(1)
+    }
+
+    fn sample_count(&self) -> Option<u64> {
+        self.source.sample_count()
+    }
+}
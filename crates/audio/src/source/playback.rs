@@ -0,0 +1,182 @@
+use std::{sync::Arc, time::Duration};
+
+use parking_lot::Mutex;
+
+use crate::{Frame, SampleRate, Source};
+
+/// The controls of a [Playback], which can be changed while it plays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaybackControls {
+    /// The rate the source is played at, where 2 is an octave up and 0.5 is an octave down.
+    pub pitch: f32,
+    /// While paused, the playback outputs silence without advancing.
+    pub paused: bool,
+    /// The time to jump to in the source. It is cleared once the playback has jumped there.
+    pub seek: Option<Duration>,
+    /// Set once the source has ended, which never happens if the playback loops.
+    pub finished: bool,
+}
+
+impl Default for PlaybackControls {
+    fn default() -> Self {
+        Self {
+            pitch: 1.0,
+            paused: false,
+            seek: None,
+            finished: false,
+        }
+    }
+}
+
+/// A source that can be paused, sped up or slowed down, and jumped around in while it plays,
+/// through its shared [PlaybackControls].
+///
+/// Jumping restarts the source from a clone of the original, and skips the samples before the
+/// time jumped to.
+#[derive(Debug, Clone)]
+pub struct Playback<S> {
+    orig: S,
+    source: S,
+    looping: bool,
+    controls: Arc<Mutex<PlaybackControls>>,
+    /// The frames that the playback is between
+    current: Option<Frame>,
+    next: Option<Frame>,
+    /// How far the playback is from `current` to `next`
+    fraction: f32,
+}
+
+impl<S> Playback<S>
+where
+    S: Source + Clone,
+{
+    pub fn new(source: S, looping: bool, controls: Arc<Mutex<PlaybackControls>>) -> Self {
+        let mut playback = Self {
+            orig: source.clone(),
+            source,
+            looping,
+            controls,
+            current: None,
+            next: None,
+            fraction: 0.0,
+        };
+        playback.current = playback.read();
+        playback.next = playback.read();
+        playback
+    }
+
+    fn read(&mut self) -> Option<Frame> {
+        match self.source.next_sample() {
+            Some(frame) => Some(frame),
+            None if self.looping => {
+                self.source = self.orig.clone();
+                self.source.next_sample()
+            }
+            None => None,
+        }
+    }
+
+    fn seek(&mut self, time: Duration) {
+        self.source = self.orig.clone();
+        let mut skip = (time.as_secs_f64() * self.orig.sample_rate() as f64) as u64;
+        if self.looping {
+            if let Some(count) = self.orig.sample_count().filter(|&count| count > 0) {
+                skip %= count;
+            }
+        }
+        for _ in 0..skip {
+            if self.source.next_sample().is_none() {
+                break;
+            }
+        }
+
+        self.current = self.read();
+        self.next = self.read();
+        self.fraction = 0.0;
+    }
+}
+
+impl<S> Source for Playback<S>
+where
+    S: Source + Clone,
+{
+    fn next_sample(&mut self) -> Option<Frame> {
+        let mut controls = self.controls.lock();
+        if let Some(time) = controls.seek.take() {
+            self.seek(time);
+        }
+
+        let Some(current) = self.current else {
+            controls.finished = true;
+            return None;
+        };
+        if controls.paused {
+            return Some(Frame::ZERO);
+        }
+
+        // The frames in between the frames of the source are interpolated
+        let frame = current.lerp(self.next.unwrap_or(current), self.fraction);
+        self.fraction += controls.pitch.max(0.0);
+        while self.fraction >= 1.0 && self.current.is_some() {
+            self.fraction -= 1.0;
+            self.current = self.next;
+            self.next = self.read();
+        }
+        Some(frame)
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.orig.sample_rate()
+    }
+
+    /// The length of the playback depends on its controls, so it is unknown.
+    fn sample_count(&self) -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::vec2;
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::streaming_source::StreamingSource;
+
+    fn source() -> StreamingSource<std::vec::IntoIter<Frame>> {
+        StreamingSource::new((0..8).map(|v| vec2(v as f32, 0.0)).collect_vec(), 4)
+    }
+
+    #[test]
+    fn pitch() {
+        let controls = Arc::new(Mutex::new(PlaybackControls {
+            pitch: 2.0,
+            ..Default::default()
+        }));
+        let samples = Playback::new(source(), false, controls.clone())
+            .samples_iter()
+            .map(|v| v.x)
+            .collect_vec();
+
+        assert_eq!(samples, [0.0, 2.0, 4.0, 6.0]);
+        assert!(controls.lock().finished);
+    }
+
+    #[test]
+    fn pause_and_seek() {
+        let controls = Arc::new(Mutex::new(PlaybackControls::default()));
+        let mut playback = Playback::new(source(), true, controls.clone());
+
+        assert_eq!(playback.next_sample(), Some(vec2(0.0, 0.0)));
+        controls.lock().paused = true;
+        assert_eq!(playback.next_sample(), Some(Frame::ZERO));
+        controls.lock().paused = false;
+        assert_eq!(playback.next_sample(), Some(vec2(1.0, 0.0)));
+
+        // 2.5 seconds at 4 samples per second is 10 samples, which wraps around the 8 samples
+        controls.lock().seek = Some(Duration::from_secs_f32(2.5));
+        assert_eq!(playback.next_sample(), Some(vec2(2.0, 0.0)));
+        assert!(controls.lock().seek.is_none());
+        assert!(!controls.lock().finished);
+    }
+}
@@ -1,11 +1,11 @@
 use std::{ops::Deref, time::Duration};
 
-use glam::Vec3;
+use glam::{Mat4, Vec3};
 
 use crate::{
     hrtf::{Hrtf, HrtfContext, HrtfLib},
     value::Value,
-    AudioEmitter, AudioListener, Frame, Source, MAX_ANGULAR_SPEED, MAX_SPEED,
+    AudioEmitter, AudioListener, Frame, Source, MAX_ANGULAR_SPEED, MAX_EXTRAPOLATION, MAX_SPEED,
 };
 
 #[derive(Debug)]
@@ -14,6 +14,10 @@ pub struct Spatial<S, L, E> {
     /// Keep track of the previous position to not move the source too fast from one block to the
     /// next.
     prev_to_source: Vec3,
+    /// The last updated position of the emitter, and the time since it was updated.
+    emitter_pos: (Vec3, f32),
+    /// The last updated transform of the listener, and the time since it was updated.
+    listener_transform: (Mat4, f32),
     output_buffer: Box<[Frame]>,
     len: usize,
     cur: usize,
@@ -35,11 +39,11 @@ where
         let block_len = (sample_rate as f32 * BLOCK_DURATION.as_secs_f32()).round() as _;
         let buf_len = block_len * INTERPOLATION_STEPS as usize;
 
-        let ctx = {
+        let (ctx, emitter_pos, listener_transform) = {
             let listener = listener.get();
             let emitter = emitter.get();
 
-            Self::calculate_hrtf_context(
+            let ctx = Self::calculate_hrtf_context(
                 listener
                     .deref()
                     .transform()
@@ -47,7 +51,8 @@ where
                     .transform_point3(emitter.pos),
                 &listener,
                 &emitter,
-            )
+            );
+            (ctx, emitter.pos, listener.transform)
         };
 
         Self {
@@ -58,9 +63,31 @@ where
             len: 0,
             cur: 0,
             prev_to_source: ctx.to_source(),
+            emitter_pos: (emitter_pos, 0.0),
+            listener_transform: (listener_transform, 0.0),
         }
     }
 
+    /// Moves the emitter and the listener along their velocities, for the time since their
+    /// positions were last updated.
+    fn extrapolate(
+        &mut self,
+        mut listener: AudioListener,
+        mut emitter: AudioEmitter,
+    ) -> (AudioListener, AudioEmitter) {
+        if emitter.pos != self.emitter_pos.0 {
+            self.emitter_pos = (emitter.pos, 0.0);
+        }
+        if listener.transform != self.listener_transform.0 {
+            self.listener_transform = (listener.transform, 0.0);
+        }
+        emitter.pos += emitter.velocity * self.emitter_pos.1.min(MAX_EXTRAPOLATION);
+        listener.transform = Mat4::from_translation(
+            listener.velocity * self.listener_transform.1.min(MAX_EXTRAPOLATION),
+        ) * listener.transform;
+        (listener, emitter)
+    }
+
     fn calculate_hrtf_context(
         prev_to_source: Vec3,
         listener: &AudioListener,
@@ -114,8 +141,9 @@ where
             Some(s)
         } else {
             let ctx = {
-                let listener = self.listener.get();
-                let emitter = self.emitter.get();
+                let listener = *self.listener.get();
+                let emitter = *self.emitter.get();
+                let (listener, emitter) = self.extrapolate(listener, emitter);
 
                 let ctx = Self::calculate_hrtf_context(self.prev_to_source, &listener, &emitter);
                 self.prev_to_source = ctx.to_source();
@@ -124,6 +152,9 @@ where
             };
 
             let new_len = self.hrtf.process(ctx, &mut self.output_buffer);
+            let elapsed = new_len as f32 / self.hrtf.source().sample_rate() as f32;
+            self.emitter_pos.1 += elapsed;
+            self.listener_transform.1 += elapsed;
             self.len = new_len;
             self.cur = 1;
             if new_len == 0 {
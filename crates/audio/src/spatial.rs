@@ -17,6 +17,9 @@ pub(crate) const MAX_SPEED: f32 = 5.0;
 ///
 /// radians/block
 pub(crate) const MAX_ANGULAR_SPEED: f32 = 0.5;
+/// The longest time the positions of an emitter and a listener are extrapolated with their
+/// velocities without being updated, in seconds.
+pub(crate) const MAX_EXTRAPOLATION: f32 = 0.1;
 
 #[derive(
     Copy, Debug, Clone, serde::Serialize, serde::Deserialize, ambient_ui_native::ElementEditor,
@@ -24,6 +27,9 @@ pub(crate) const MAX_ANGULAR_SPEED: f32 = 0.5;
 pub struct AudioEmitter {
     pub amplitude: f32,
     pub pos: Vec3,
+    /// The velocity of the emitter in units/s, which its position is extrapolated with between
+    /// updates so that the Doppler effect is smooth.
+    pub velocity: Vec3,
     pub attenuation: Attenuation,
 }
 
@@ -32,6 +38,7 @@ impl Default for AudioEmitter {
         Self {
             amplitude: 1.0,
             pos: Default::default(),
+            velocity: Default::default(),
             attenuation: Default::default(),
         }
     }
@@ -42,6 +49,8 @@ pub struct AudioListener {
     /// The position of the right ear
     pub transform: Mat4,
     pub ear_distance: Vec3,
+    /// The velocity of the listener in units/s, in the space `transform` maps to.
+    pub velocity: Vec3,
 }
 
 impl AudioListener {
@@ -49,6 +58,7 @@ impl AudioListener {
         Self {
             transform,
             ear_distance,
+            velocity: Vec3::ZERO,
         }
     }
 
@@ -10,10 +10,13 @@ use std::{
 use ambient_asset_cache::{AssetCache, SyncAssetKeyExt};
 use ambient_native_std::{asset_url::AbsAssetUrl, AmbientVersion};
 use ambient_package::{
+    localization,
+    quality_tier::{self, QualityTier},
     BuildMetadata, BuildMetadataError, BuildSettings, Manifest as PackageManifest,
 };
 use ambient_package_semantic::{package_dependency_to_retrievable_file, RetrievableFile, Semantic};
 use ambient_package_semantic_native::add_to_semantic_and_register_components;
+use ambient_physics::physx::PhysicsKey;
 use ambient_shared_types::asset::BuildAsset;
 use ambient_std::path::path_to_unix_string_lossy;
 use anyhow::Context;
@@ -25,8 +28,10 @@ use walkdir::WalkDir;
 
 pub mod migrate;
 pub mod pipelines;
+pub mod validate;
 
 mod package_json;
+mod typescript;
 
 #[derive(Clone, Debug)]
 pub struct BuildResult {
@@ -205,10 +210,23 @@ pub async fn build_package(
             .await
             .context("Failed to create build directory")?;
 
-        let assets = if !settings.wasm_only {
-            build_assets(assets, &assets_path, &build_path, false).await?
+        let (assets, quality_tiers) = if !settings.wasm_only {
+            let built_assets = build_assets(assets, &assets_path, &build_path, false).await?;
+
+            let tiers_path = build_path.join("tiers");
+            if tiers_path.exists() {
+                tokio::fs::remove_dir_all(&tiers_path)
+                    .await
+                    .context("Failed to remove old quality tiers")?;
+            }
+            for (name, tier) in &manifest.build.tiers {
+                tracing::info!("Building assets for quality tier {name:?}...");
+                build_tier_assets(assets, &assets_path, &build_path, name, tier).await?;
+            }
+
+            (built_assets, manifest.build.tiers.keys().cloned().collect())
         } else {
-            vec![]
+            (vec![], vec![])
         };
 
         tracing::info!("Assets built, building source code...");
@@ -221,7 +239,15 @@ pub async fn build_package(
 
         tokio::fs::write(&output_manifest_path, toml::to_string(&manifest)?).await?;
 
-        write_metadata(&package_path, &build_path, settings, &assets).await?;
+        write_metadata(
+            &package_path,
+            &build_path,
+            &manifest,
+            settings,
+            &assets,
+            quality_tiers,
+        )
+        .await?;
     }
 
     // Deploy implies docs are always built, as they are required for deployment
@@ -234,6 +260,7 @@ pub async fn build_package(
         std::fs::remove_dir_all(&docs_path).ok();
         std::fs::create_dir_all(&docs_path)?;
         ambient_package_docgen::write(&docs_path, &json_path, false)?;
+        typescript::write(&json_path)?;
 
         tracing::info!("Docs built");
     }
@@ -269,6 +296,48 @@ pub async fn build_assets(
     assets_path: &Path,
     build_path: &Path,
     for_import_only: bool,
+) -> anyhow::Result<Vec<OutAsset>> {
+    process_assets(
+        assets,
+        assets_path,
+        &build_path.join("assets"),
+        for_import_only,
+        None,
+    )
+    .await
+}
+
+/// Builds the assets for a quality tier to `tiers/<name>/assets` in the build directory.
+async fn build_tier_assets(
+    assets: &AssetCache,
+    assets_path: &Path,
+    build_path: &Path,
+    name: &str,
+    tier: &QualityTier,
+) -> anyhow::Result<Vec<OutAsset>> {
+    // Intermediate results of the pipelines are cached in the asset cache by their source, so
+    // each tier gets a fresh cache to avoid reusing the output of the default build. PhysX only
+    // allows one instance per process, so the tier shares the one of the parent cache, which the
+    // models pipeline uses to cook colliders.
+    let tier_assets = AssetCache::new(assets.runtime().clone());
+    PhysicsKey.insert(&tier_assets, PhysicsKey.get(assets));
+    process_assets(
+        &tier_assets,
+        assets_path,
+        &build_path.join(quality_tier::assets_path(name)),
+        false,
+        Some(tier.clone()),
+    )
+    .await
+    .with_context(|| format!("Failed to build quality tier {name:?}"))
+}
+
+async fn process_assets(
+    assets: &AssetCache,
+    assets_path: &Path,
+    out_path: &Path,
+    for_import_only: bool,
+    quality_tier: Option<QualityTier>,
 ) -> anyhow::Result<Vec<OutAsset>> {
     let files = get_files_in_path(assets_path).map(Into::into).collect_vec();
 
@@ -283,15 +352,16 @@ pub async fn build_assets(
         assets: assets.clone(),
         files: FileCollection(Arc::new(files)),
         in_root: AbsAssetUrl::from_directory_path(assets_path),
-        out_root: AbsAssetUrl::from_directory_path(build_path.join("assets")),
+        out_root: AbsAssetUrl::from_directory_path(out_path),
         input_file_filter: None,
         package_name: "".to_string(),
+        quality_tier,
         write_file: Arc::new({
-            let build_path = build_path.to_owned();
+            let out_path = out_path.to_owned();
             let file_write_semaphore = file_write_semaphore.clone();
             move |path, contents| {
                 let file_write_semaphore = file_write_semaphore.clone();
-                let path = build_path.join("assets").join(path);
+                let path = out_path.join(path);
                 tracing::trace!("Writing file: {:?}", path);
 
                 if for_import_only {
@@ -403,8 +473,10 @@ fn get_component_paths(target: &str, build_path: &Path) -> Vec<String> {
 async fn write_metadata(
     package_path: &Path,
     build_path: &Path,
+    manifest: &PackageManifest,
     settings: &BuildSettings,
     assets: &[OutAsset],
+    quality_tiers: Vec<String>,
 ) -> anyhow::Result<BuildMetadata> {
     fn strip_path(path: PathBuf, prefix: &Path) -> PathBuf {
         path.strip_prefix(prefix)
@@ -413,6 +485,26 @@ async fn write_metadata(
     }
 
     let AmbientVersion { version, revision } = AmbientVersion::default();
+    let asset: Vec<_> = assets
+        .iter()
+        .flat_map(|a| {
+            Some(BuildAsset {
+                type_: a.type_,
+                input: a
+                    .source
+                    .as_ref()
+                    .and_then(|s| s.to_file_path().ok().flatten())
+                    .map(|p| strip_path(p, package_path)),
+                output: strip_path(a.content.as_content()?.to_file_path().ok()??, build_path),
+            })
+        })
+        .collect();
+    let localized_assets = asset
+        .iter()
+        .map(|a| path_to_unix_string_lossy(&a.output))
+        .filter(|path| localization::variant_locale(path, &manifest.localization.locales).is_some())
+        .collect();
+
     let metadata = BuildMetadata {
         ambient_version: version,
         ambient_revision: revision,
@@ -420,20 +512,9 @@ async fn write_metadata(
         server_component_paths: get_component_paths("server", build_path),
         last_build_time: Some(chrono::Utc::now().to_rfc3339()),
         settings: settings.clone(),
-        asset: assets
-            .iter()
-            .flat_map(|a| {
-                Some(BuildAsset {
-                    type_: a.type_,
-                    input: a
-                        .source
-                        .as_ref()
-                        .and_then(|s| s.to_file_path().ok().flatten())
-                        .map(|p| strip_path(p, package_path)),
-                    output: strip_path(a.content.as_content()?.to_file_path().ok()??, build_path),
-                })
-            })
-            .collect(),
+        asset,
+        localized_assets,
+        quality_tiers,
     };
     let metadata_path = build_path.join(BuildMetadata::FILENAME);
     tokio::fs::write(&metadata_path, toml::to_string(&metadata)?).await?;
@@ -1,6 +1,8 @@
-use ambient_native_std::asset_url::AssetType;
+use ambient_native_std::asset_url::{AbsAssetUrl, AssetType};
 use ambient_pipeline_types::audio::AudioPipeline;
-use ambient_world_audio::AudioNode;
+use ambient_world_audio::{
+    AudioEventBank, AudioNode, AUDIO_BANK_EXTENSION, BUILT_AUDIO_BANK_EXTENSION,
+};
 use anyhow::Context;
 use optivorbis::Remuxer;
 use std::io::Cursor;
@@ -19,9 +21,13 @@ pub async fn pipeline(ctx: &PipelineCtx, config: AudioPipeline) -> Vec<OutAsset>
             matches!(
                 file.extension().as_deref(),
                 Some("ogg") | Some("wav") | Some("mp3")
-            )
+            ) || is_audio_bank(file)
         },
         move |ctx, file| async move {
+            if is_audio_bank(&file) {
+                return audio_bank(&ctx, &file, config.convert).await;
+            }
+
             let contents = file.download_bytes(ctx.assets()).await?;
 
             let filename = file.decoded_path().file_name().unwrap().to_string();
@@ -94,6 +100,89 @@ pub async fn pipeline(ctx: &PipelineCtx, config: AudioPipeline) -> Vec<OutAsset>
     .await
 }
 
+fn is_audio_bank(file: &AbsAssetUrl) -> bool {
+    file.decoded_path().file_name().map_or(false, |name| {
+        name.ends_with(&format!(".{AUDIO_BANK_EXTENSION}"))
+    })
+}
+
+/// Validates an audio event bank, and points its samples to the files this pipeline writes for
+/// them.
+async fn audio_bank(
+    ctx: &PipelineCtx,
+    file: &AbsAssetUrl,
+    convert: bool,
+) -> anyhow::Result<Vec<OutAsset>> {
+    let filename = file.decoded_path().file_name().unwrap().to_string();
+    let mut bank: AudioEventBank = file
+        .download_toml(ctx.assets())
+        .await
+        .with_context(|| format!("Failed to parse the audio event bank {filename}"))?;
+
+    let mut errors = bank.validate();
+    for (name, event) in &mut bank.events {
+        for sample in &mut event.samples {
+            let exists = file
+                .join(&*sample)
+                .map_or(false, |url| ctx.get_downloadable_url(&url).is_ok());
+            let extension = sample
+                .rsplit_once('.')
+                .map(|(_, extension)| extension.to_ascii_lowercase());
+            let converted = match extension.as_deref() {
+                _ if !exists => {
+                    errors.push(format!(
+                        "The sample {sample:?} of the event {name:?} is missing"
+                    ));
+                    continue;
+                }
+                Some("ogg") => false,
+                Some("wav") => convert,
+                Some("mp3") => true,
+                _ => {
+                    errors.push(format!(
+                        "The sample {sample:?} of the event {name:?} is not an ogg, wav or mp3 file"
+                    ));
+                    continue;
+                }
+            };
+            if converted {
+                let (stem, _) = sample.rsplit_once('.').unwrap();
+                *sample = format!("{stem}.ogg");
+            }
+        }
+    }
+    if !errors.is_empty() {
+        anyhow::bail!(
+            "Invalid audio event bank {filename}:\n{}",
+            errors.join("\n")
+        );
+    }
+
+    let rel_path = ctx.in_root().relative_path(file.decoded_path());
+    let out_name = format!(
+        "{}{BUILT_AUDIO_BANK_EXTENSION}",
+        filename.trim_end_matches(AUDIO_BANK_EXTENSION)
+    );
+    let bank_url = ctx
+        .write_file(
+            rel_path.with_file_name(out_name),
+            serde_json::to_vec_pretty(&bank)?,
+        )
+        .await;
+
+    Ok(vec![OutAsset {
+        id: asset_id_from_url(file),
+        type_: AssetType::AudioEventBank,
+        hidden: false,
+        name: filename,
+        tags: Vec::new(),
+        categories: Default::default(),
+        preview: OutAssetPreview::None,
+        content: OutAssetContent::Content(bank_url),
+        source: Some(file.clone()),
+    }])
+}
+
 fn save_audio_graph(root: AudioNode) -> anyhow::Result<Vec<u8>> {
     Ok(serde_json::to_string_pretty(&root)
         .context("Invalid sound graph")?
@@ -0,0 +1,132 @@
+use std::collections::BTreeSet;
+
+use ambient_native_std::asset_url::AssetType;
+use ambient_pipeline_types::fonts::FontsPipeline;
+use anyhow::Context;
+use glyph_brush::ab_glyph::{Font, FontRef};
+use tracing::{info_span, Instrument};
+
+use super::{
+    context::PipelineCtx,
+    out_asset::{asset_id_from_url, OutAsset, OutAssetContent, OutAssetPreview},
+};
+
+mod sdf;
+
+pub async fn pipeline(ctx: &PipelineCtx, config: FontsPipeline) -> Vec<OutAsset> {
+    ctx.process_files(
+        |file| matches!(file.extension().as_deref(), Some("ttf") | Some("otf")),
+        move |ctx, file| {
+            let config = config.clone();
+            async move {
+                let contents = file.download_bytes(ctx.assets()).await?;
+
+                let filename = file.decoded_path().file_name().unwrap().to_string();
+                let rel_path = ctx.in_root().relative_path(file.decoded_path());
+
+                let characters = config.characters();
+                let contents = match &characters {
+                    Some(characters) => {
+                        tracing::debug!("Subsetting font to {} characters", characters.len());
+                        subset(&contents, characters)
+                            .with_context(|| format!("Failed to subset font {filename}"))?
+                    }
+                    None => contents,
+                };
+
+                let mut out_assets = vec![];
+                if let Some(atlas_config) = &config.sdf_atlas {
+                    let font = FontRef::try_from_slice(&contents)
+                        .with_context(|| format!("Failed to parse font {filename}"))?;
+                    let characters = characters
+                        .unwrap_or_else(|| font.codepoint_ids().map(|(_, c)| c).collect());
+                    let (image, atlas) = sdf::build_atlas(&font, &characters, atlas_config)?;
+
+                    let mut image_data = std::io::Cursor::new(vec![]);
+                    image
+                        .write_to(&mut image_data, image::ImageOutputFormat::Png)
+                        .context("Failed to encode SDF atlas")?;
+                    let image_url = ctx
+                        .write_file(rel_path.with_extension("sdf.png"), image_data.into_inner())
+                        .await;
+                    let atlas_url = ctx
+                        .write_file(
+                            rel_path.with_extension("sdf.json"),
+                            serde_json::to_vec_pretty(&atlas)?,
+                        )
+                        .await;
+
+                    out_assets.extend([
+                        OutAsset {
+                            id: asset_id_from_url(&file.push("sdf_image").unwrap()),
+                            type_: AssetType::Image,
+                            hidden: true,
+                            name: filename.clone(),
+                            tags: Vec::new(),
+                            categories: Default::default(),
+                            preview: OutAssetPreview::None,
+                            content: OutAssetContent::Content(image_url),
+                            source: None,
+                        },
+                        OutAsset {
+                            id: asset_id_from_url(&file.push("sdf_atlas").unwrap()),
+                            type_: AssetType::FontAtlas,
+                            hidden: false,
+                            name: filename.clone(),
+                            tags: Vec::new(),
+                            categories: Default::default(),
+                            preview: OutAssetPreview::None,
+                            content: OutAssetContent::Content(atlas_url),
+                            source: None,
+                        },
+                    ]);
+                }
+
+                let content_url = ctx.write_file(&rel_path, contents).await;
+                out_assets.push(OutAsset {
+                    id: asset_id_from_url(&file),
+                    type_: AssetType::Font,
+                    hidden: false,
+                    name: filename,
+                    tags: Vec::new(),
+                    categories: Default::default(),
+                    preview: OutAssetPreview::None,
+                    content: OutAssetContent::Content(content_url),
+                    source: Some(file.clone()),
+                });
+
+                Ok(out_assets)
+            }
+        },
+    )
+    .instrument(info_span!("fonts_pipeline"))
+    .await
+}
+
+/// Removes all glyphs that are not used by `characters` from the font.
+fn subset(data: &[u8], characters: &BTreeSet<char>) -> anyhow::Result<Vec<u8>> {
+    use allsorts::{binary::read::ReadScope, font_data::FontData};
+
+    let font = FontRef::try_from_slice(data).context("Failed to parse font")?;
+
+    // The subset must start with the `.notdef` glyph
+    let glyph_ids = std::iter::once(0)
+        .chain(
+            characters
+                .iter()
+                .map(|c| font.glyph_id(*c).0)
+                .filter(|id| *id != 0)
+                .collect::<BTreeSet<_>>(),
+        )
+        .collect::<Vec<_>>();
+
+    let font_data = ReadScope::new(data)
+        .read::<FontData<'_>>()
+        .map_err(|err| anyhow::anyhow!("Failed to read font tables: {err:?}"))?;
+    let provider = font_data
+        .table_provider(0)
+        .map_err(|err| anyhow::anyhow!("Failed to read font tables: {err:?}"))?;
+
+    allsorts::subset::subset(&provider, &glyph_ids)
+        .map_err(|err| anyhow::anyhow!("Failed to subset font: {err:?}"))
+}
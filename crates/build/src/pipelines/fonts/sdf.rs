@@ -0,0 +1,178 @@
+//! Generation of signed distance field atlases for fonts.
+//!
+//! Each glyph is rasterized at [SdfAtlas::glyph_size], padded by [SdfAtlas::spread] on each side,
+//! and converted to a distance field where 128 is the edge of the glyph, larger values are inside
+//! and smaller values are outside. The glyphs are packed into rows of a single-channel image.
+use std::collections::{BTreeMap, BTreeSet};
+
+use ambient_pipeline_types::fonts::SdfAtlas;
+use glyph_brush::ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+
+/// The default width of the atlas, if not specified in the pipeline.
+const DEFAULT_ATLAS_WIDTH: u32 = 512;
+
+/// Describes the contents of an SDF atlas image.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FontAtlas {
+    /// The size the glyphs were rasterized at, in pixels.
+    pub glyph_size: u32,
+    /// The distance from the edge of a glyph that is covered by the distance field, in pixels.
+    pub spread: u32,
+    pub width: u32,
+    pub height: u32,
+    /// The distance from the baseline to the top of the tallest glyph, in pixels.
+    pub ascent: f32,
+    /// The distance from the baseline to the bottom of the lowest glyph, in pixels. Usually negative.
+    pub descent: f32,
+    pub line_gap: f32,
+    pub glyphs: BTreeMap<char, AtlasGlyph>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AtlasGlyph {
+    /// The position of the glyph in the atlas, in pixels. Includes the spread.
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// The offset from the pen position to the top-left corner of the glyph in the atlas, in pixels.
+    pub offset: [f32; 2],
+    /// The horizontal distance to advance the pen by after this glyph, in pixels.
+    pub advance: f32,
+}
+
+pub fn build_atlas(
+    font: &FontRef,
+    characters: &BTreeSet<char>,
+    config: &SdfAtlas,
+) -> anyhow::Result<(GrayImage, FontAtlas)> {
+    let scale = font.as_scaled(PxScale::from(config.glyph_size as f32));
+    let spread = config.spread as usize;
+    let atlas_width = config.width.unwrap_or(DEFAULT_ATLAS_WIDTH);
+
+    let mut fields = vec![];
+    let mut glyphs = BTreeMap::new();
+    let (mut x, mut y, mut row_height) = (0, 0, 0);
+    for &character in characters {
+        let glyph_id = font.glyph_id(character);
+        if glyph_id.0 == 0 {
+            tracing::debug!("Font does not have a glyph for {character:?}");
+            continue;
+        }
+
+        let advance = scale.h_advance(glyph_id);
+        let Some(outline) = font.outline_glyph(glyph_id.with_scale(scale.scale())) else {
+            // Glyphs without an outline (e.g. spaces) only need their metrics
+            glyphs.insert(
+                character,
+                AtlasGlyph {
+                    x: 0,
+                    y: 0,
+                    width: 0,
+                    height: 0,
+                    offset: [0.0; 2],
+                    advance,
+                },
+            );
+            continue;
+        };
+
+        let bounds = outline.px_bounds();
+        let (glyph_width, glyph_height) = (bounds.width() as usize, bounds.height() as usize);
+        let (width, height) = (glyph_width + spread * 2, glyph_height + spread * 2);
+
+        let mut coverage = vec![0.0; width * height];
+        outline.draw(|gx, gy, c| {
+            let (gx, gy) = (gx as usize + spread, gy as usize + spread);
+            if gx < width && gy < height {
+                coverage[gy * width + gx] = c;
+            }
+        });
+
+        anyhow::ensure!(
+            width as u32 <= atlas_width,
+            "The glyph for {character:?} is wider than the atlas; increase the atlas width"
+        );
+        if x + width as u32 > atlas_width {
+            x = 0;
+            y += row_height;
+            row_height = 0;
+        }
+
+        glyphs.insert(
+            character,
+            AtlasGlyph {
+                x,
+                y,
+                width: width as u32,
+                height: height as u32,
+                offset: [bounds.min.x - spread as f32, bounds.min.y - spread as f32],
+                advance,
+            },
+        );
+        fields.push((
+            x,
+            y,
+            width,
+            signed_distance_field(&coverage, width, height, spread),
+        ));
+
+        x += width as u32;
+        row_height = row_height.max(height as u32);
+    }
+
+    let atlas_height = (y + row_height).max(1);
+    let mut image = GrayImage::new(atlas_width, atlas_height);
+    for (x, y, width, field) in fields {
+        for (i, value) in field.into_iter().enumerate() {
+            let (fx, fy) = ((i % width) as u32, (i / width) as u32);
+            image.put_pixel(x + fx, y + fy, image::Luma([value]));
+        }
+    }
+
+    Ok((
+        image,
+        FontAtlas {
+            glyph_size: config.glyph_size,
+            spread: config.spread,
+            width: atlas_width,
+            height: atlas_height,
+            ascent: scale.ascent(),
+            descent: scale.descent(),
+            line_gap: scale.line_gap(),
+            glyphs,
+        },
+    ))
+}
+
+/// Converts a coverage bitmap into a signed distance field, searching up to `spread` pixels away
+/// from each pixel for the nearest pixel on the other side of the edge.
+fn signed_distance_field(coverage: &[f32], width: usize, height: usize, spread: usize) -> Vec<u8> {
+    let inside = |x: usize, y: usize| coverage[y * width + x] >= 0.5;
+    let spread = spread.max(1);
+
+    let mut field = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let is_inside = inside(x, y);
+
+            let mut nearest_squared = (spread * spread) as f32;
+            for sy in y.saturating_sub(spread)..(y + spread + 1).min(height) {
+                for sx in x.saturating_sub(spread)..(x + spread + 1).min(width) {
+                    if inside(sx, sy) != is_inside {
+                        let (dx, dy) = (sx as f32 - x as f32, sy as f32 - y as f32);
+                        nearest_squared = nearest_squared.min(dx * dx + dy * dy);
+                    }
+                }
+            }
+
+            let distance = nearest_squared.sqrt().min(spread as f32);
+            let signed = if is_inside { distance } else { -distance };
+            let value = 0.5 + signed / (2.0 * spread as f32);
+            field.push((value.clamp(0.0, 1.0) * 255.0).round() as u8);
+        }
+    }
+    field
+}
@@ -5,6 +5,8 @@ use std::path::{Path, PathBuf};
 use toml::map::Map;
 use toml::Value;
 
+use super::scenes::format::Scene;
+
 pub fn import_audio(path: PathBuf, convert: bool) -> anyhow::Result<()> {
     let current_dir = std::env::current_dir().context("Error getting current directory")?;
     let asset_folder_path = current_dir.join("assets");
@@ -146,3 +148,144 @@ pub fn import_model(path: PathBuf, collider_from_model: bool) -> anyhow::Result<
     std::fs::copy(path.clone(), destination).context("Error copying audio file")?;
     Ok(())
 }
+
+/// Imports the scene at `path` into the assets of the package in the current directory, as
+/// `output`, along with the models it refers to, and adds the pipelines that process them.
+///
+/// `scene` is the validated scene at `path`; it is written in the format of the extension of
+/// `output`. Importing a scene again updates the scene and its models.
+pub fn import_scene(
+    path: PathBuf,
+    output: &str,
+    scene: &Scene,
+    skip_unknown_components: bool,
+) -> anyhow::Result<()> {
+    let current_dir = std::env::current_dir().context("Error getting current directory")?;
+    let asset_folder_path = current_dir.join("assets");
+    let tomlpath = current_dir.join("assets/pipeline.toml");
+
+    // The models keep their path relative to the scene
+    let output_dir = Path::new(output).parent().unwrap_or(Path::new(""));
+    let mut models = Vec::new();
+    for model in scene
+        .entities
+        .iter()
+        .filter_map(|entity| entity.model.as_ref())
+    {
+        let model_path = ambient_std::path::normalize(&output_dir.join(model));
+        if model_path.starts_with("..") {
+            anyhow::bail!("The model `{model}` would be outside of the assets folder");
+        }
+        let source = model_path.to_string_lossy().replace('\\', "/");
+        if !models.iter().any(|(_, other)| other == &source) {
+            models.push((model.clone(), source));
+        }
+    }
+
+    if !Path::new(&asset_folder_path).exists() {
+        std::fs::create_dir_all(&asset_folder_path)?;
+    }
+    if !Path::new(&tomlpath).exists() {
+        File::create(&tomlpath)?;
+    }
+
+    let mut file = File::open(&tomlpath)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+
+    let mut data: Value = match toml::from_str(&contents) {
+        Ok(v) => v,
+        Err(_) => Value::Table(Map::new()), // if we cannot parse the file, start with a fresh table
+    };
+
+    if let Value::Table(table) = &mut data {
+        let pipelines = match table.get_mut("pipelines") {
+            Some(Value::Array(arr)) => arr,
+            _ => {
+                table.insert("pipelines".to_string(), Value::Array(Vec::new()));
+                match table.get_mut("pipelines") {
+                    Some(Value::Array(arr)) => arr,
+                    _ => panic!("Unexpected state"),
+                }
+            }
+        };
+        let has_source = |pipelines: &Vec<Value>, type_: &str, source: &str| {
+            pipelines.iter().any(|pipeline| {
+                pipeline.get("type").and_then(Value::as_str) == Some(type_)
+                    && pipeline
+                        .get("sources")
+                        .and_then(Value::as_array)
+                        .map_or(false, |sources| {
+                            sources.contains(&Value::String(source.to_string()))
+                        })
+            })
+        };
+
+        let new_models = models
+            .iter()
+            .filter(|(_, source)| !has_source(pipelines, "Models", source))
+            .map(|(_, source)| Value::String(source.clone()))
+            .collect::<Vec<_>>();
+        if !new_models.is_empty() {
+            let mut new_pipeline = Map::new();
+            new_pipeline.insert(String::from("type"), Value::String(String::from("Models")));
+            new_pipeline.insert(String::from("sources"), Value::Array(new_models));
+            pipelines.push(Value::Table(new_pipeline));
+        }
+
+        if has_source(pipelines, "Scenes", output) {
+            println!("\n👉 Updating the imported scene...\n");
+        } else {
+            let mut new_pipeline = Map::new();
+            new_pipeline.insert(String::from("type"), Value::String(String::from("Scenes")));
+            if skip_unknown_components {
+                new_pipeline.insert(
+                    String::from("skip_unknown_components"),
+                    Value::Boolean(true),
+                );
+            }
+            new_pipeline.insert(
+                String::from("sources"),
+                Value::Array(vec![Value::String(output.to_string())]),
+            );
+            println!("\n👉 Importing scene...");
+            println!("📘 Read more about scene import here:");
+            println!("🔗 https://ambientrun.github.io/Ambient/reference/scenes.html\n");
+            pipelines.push(Value::Table(new_pipeline));
+        }
+    } else {
+        panic!("Expected table at the root of the TOML document");
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(tomlpath)?;
+    write!(file, "{}", toml::to_string(&data)?)?;
+
+    let destination = asset_folder_path.join(output);
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&destination, scene.to_string(output)?).context("Error writing scene file")?;
+
+    let scene_dir = path.parent().unwrap_or(Path::new(""));
+    for (model, source) in models {
+        let model_source = scene_dir.join(&model);
+        let model_destination = asset_folder_path.join(&source);
+        if let (Ok(a), Ok(b)) = (
+            model_source.canonicalize(),
+            model_destination.canonicalize(),
+        ) {
+            if a == b {
+                continue;
+            }
+        }
+        if let Some(parent) = model_destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&model_source, &model_destination)
+            .with_context(|| format!("Error copying model file {model_source:?}"))?;
+    }
+    Ok(())
+}
@@ -344,6 +344,9 @@ impl AsyncAssetKey<AssetResult<Arc<AbsAssetUrl>>> for PipeImage {
             if let Some(size) = self.cap_texture_sizes {
                 cap_texture_size(&mut image, size.size());
             }
+            if let Some(size) = ctx.quality_tier.as_ref().and_then(|t| t.max_texture_size) {
+                cap_texture_size(&mut image, size);
+            }
             image.write_to(&mut data, ImageOutputFormat::Png).unwrap();
         });
         Ok(Arc::new(
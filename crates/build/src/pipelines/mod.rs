@@ -2,6 +2,7 @@ use std::{collections::HashSet, sync::Arc};
 
 use ambient_asset_cache::SyncAssetKey;
 use ambient_native_std::{asset_cache::AssetCache, asset_url::AbsAssetUrl};
+use ambient_package::quality_tier::QualityTier;
 use ambient_pipeline_types::{models::ModelsPipeline, Pipeline, PipelineProcessor, PipelinesFile};
 use anyhow::Context;
 use context::PipelineCtx;
@@ -14,10 +15,13 @@ use out_asset::{OutAsset, OutAssetContent, OutAssetPreview};
 
 pub mod audio;
 pub mod context;
+pub mod fonts;
 pub mod importer;
 pub mod materials;
 pub mod models;
 pub mod out_asset;
+pub mod scenes;
+pub mod tilemaps;
 
 pub use importer::*;
 
@@ -27,6 +31,9 @@ pub async fn process_pipeline(pipeline: &Pipeline, ctx: PipelineCtx) -> Vec<OutA
         PipelineProcessor::Models(config) => models::pipeline(&ctx, config.clone()).await,
         PipelineProcessor::Materials(config) => materials::pipeline(&ctx, config.clone()).await,
         PipelineProcessor::Audio(config) => audio::pipeline(&ctx, config.clone()).await,
+        PipelineProcessor::Fonts(config) => fonts::pipeline(&ctx, config.clone()).await,
+        PipelineProcessor::Tilemaps(config) => tilemaps::pipeline(&ctx, config.clone()).await,
+        PipelineProcessor::Scenes(config) => scenes::pipeline(&ctx, config.clone()).await,
     };
 
     for asset in &mut assets {
@@ -116,6 +123,8 @@ pub struct ProcessCtx {
     pub(crate) files: FileCollection,
     pub(crate) input_file_filter: Option<String>,
     pub(crate) package_name: String,
+    /// The quality tier being built, if this is not the default build
+    pub(crate) quality_tier: Option<QualityTier>,
     pub(crate) in_root: AbsAssetUrl,
     pub(crate) out_root: AbsAssetUrl,
     pub(crate) write_file:
@@ -131,6 +140,7 @@ impl std::fmt::Debug for ProcessCtx {
             .field("files", &self.files)
             .field("input_file_filter", &self.input_file_filter)
             .field("package_name", &self.package_name)
+            .field("quality_tier", &self.quality_tier)
             .field("in_root", &self.in_root)
             .field("out_root", &self.out_root)
             .finish_non_exhaustive()
@@ -1,5 +1,3 @@
-use std::{path::PathBuf, sync::Arc};
-
 use ambient_core::hierarchy::children;
 use ambient_model_import::{apply_model_transform, model_crate::ModelCrate, TextureResolver};
 use ambient_native_std::asset_url::AssetType;
@@ -7,6 +5,7 @@ use ambient_physics::collider::collider_type;
 use ambient_pipeline_types::models::{Collider, ModelImporter, ModelsPipeline};
 use futures::FutureExt;
 use relative_path::RelativePath;
+use std::{path::PathBuf, sync::Arc};
 
 use super::{
     context::PipelineCtx,
@@ -67,9 +66,20 @@ async fn apply(
         .await?;
         model_crate.override_material(&mat.filter, material);
     }
+    for event in &pipeline.animation_events {
+        model_crate.add_animation_event(&event.animation, event.name.clone(), event.time);
+    }
     if let Some(max_size) = pipeline.cap_texture_sizes {
         model_crate.cap_texture_sizes(max_size.size());
     }
+    if let Some(tier) = &ctx.process_ctx.quality_tier {
+        if let Some(max_size) = tier.max_texture_size {
+            model_crate.cap_texture_sizes(max_size);
+        }
+        if let Some(max_levels) = tier.max_lod_levels {
+            model_crate.limit_lod_levels(max_levels);
+        }
+    }
     model_crate.finalize_model();
     match pipeline.collider {
         Collider::None => {}
@@ -0,0 +1,522 @@
+//! The scene interchange format: a JSON or TOML description of a hierarchy of entities, with their
+//! transforms, models and components.
+//!
+//! The format is meant to be written by external tools, such as a Blender exporter, and is
+//! versioned with [SCENE_FORMAT_VERSION] so that they can rely on it. Scenes are converted to
+//! prefabs by the `Scenes` pipeline, and imported into a package with `ambient import-scene`.
+use std::{collections::HashMap, fmt};
+
+use ambient_core::{
+    hierarchy::{children, parent},
+    name,
+    transform::{local_to_parent, local_to_world, rotation, scale, translation},
+};
+use ambient_ecs::{
+    generated::prefab::components::prefab_from_url, with_component_registry, ComponentEntry,
+    Serializable,
+};
+use glam::{Quat, Vec3};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// The version of the format that this version of Ambient reads.
+pub const SCENE_FORMAT_VERSION: u32 = 1;
+
+/// How far the length of a rotation can be from 1 before it is rejected.
+const ROTATION_TOLERANCE: f32 = 1e-2;
+
+/// Whether `path` is the path of a scene file; that is, whether it ends with `.scene.json` or
+/// `.scene.toml`.
+pub fn is_scene_path(path: &str) -> bool {
+    path.ends_with(".scene.json") || path.ends_with(".scene.toml")
+}
+
+/// A scene file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scene {
+    /// The version of the format the scene is written in; must be [SCENE_FORMAT_VERSION]
+    pub format_version: u32,
+    /// The name of the scene; defaults to the name of the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entities: Vec<SceneEntity>,
+}
+
+/// An entity of a [Scene].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SceneEntity {
+    /// The identifier of the entity, which must be unique in the scene. Parents refer to it.
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The id of the parent of the entity; entities without a parent are children of the root of
+    /// the scene
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// The translation of the entity, relative to its parent
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub translation: Vec3,
+    /// The rotation of the entity, relative to its parent, as a `[x, y, z, w]` quaternion
+    #[serde(default = "identity", skip_serializing_if = "is_identity")]
+    pub rotation: Quat,
+    /// The scale of the entity, relative to its parent
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub scale: Vec3,
+    /// The path of a model, relative to the scene file, that is spawned on the entity. The model
+    /// must be imported by a `Models` pipeline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// The other components of the entity, by full path, with their values in JSON
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub components: IndexMap<String, serde_json::Value>,
+}
+
+fn is_zero(value: &Vec3) -> bool {
+    *value == Vec3::ZERO
+}
+
+fn identity() -> Quat {
+    Quat::IDENTITY
+}
+
+fn is_identity(value: &Quat) -> bool {
+    *value == Quat::IDENTITY
+}
+
+fn one() -> Vec3 {
+    Vec3::ONE
+}
+
+fn is_one(value: &Vec3) -> bool {
+    *value == Vec3::ONE
+}
+
+/// Only the version of a scene, so that it can be checked before the rest of the scene is read.
+#[derive(Deserialize)]
+struct SceneVersion {
+    format_version: Option<u32>,
+}
+
+impl Scene {
+    /// Reads the scene in `data`, which is TOML if `file_name` ends with `.toml` and JSON
+    /// otherwise.
+    ///
+    /// The errors point at the field that could not be read, such as `entities[2].rotation`.
+    pub fn parse(file_name: &str, data: &[u8]) -> anyhow::Result<Self> {
+        let text = std::str::from_utf8(data)
+            .map_err(|err| anyhow::anyhow!("{file_name} is not valid UTF-8: {err}"))?;
+        let is_toml = file_name.ends_with(".toml");
+
+        let version: SceneVersion = if is_toml {
+            toml::from_str(text).map_err(|err| anyhow::anyhow!("{file_name}: {err}"))?
+        } else {
+            serde_json::from_str(text).map_err(|err| anyhow::anyhow!("{file_name}: {err}"))?
+        };
+        match version.format_version {
+            Some(SCENE_FORMAT_VERSION) => {}
+            Some(version) => anyhow::bail!(
+                "{file_name}: the scene is in version {version} of the format, but this version \
+                 of Ambient only reads version {SCENE_FORMAT_VERSION}"
+            ),
+            None => anyhow::bail!(
+                "{file_name}: the scene has no `format_version`; it must be \
+                 {SCENE_FORMAT_VERSION}"
+            ),
+        }
+
+        let result = if is_toml {
+            serde_path_to_error::deserialize(toml::Deserializer::new(text))
+                .map_err(|err| anyhow::anyhow!("{}: {}", err.path(), err.inner()))
+        } else {
+            serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(text))
+                .map_err(|err| anyhow::anyhow!("{}: {}", err.path(), err.inner()))
+        };
+        result.map_err(|err| anyhow::anyhow!("{file_name}: {err}"))
+    }
+
+    /// Writes the scene as TOML if `file_name` ends with `.toml`, and as JSON otherwise.
+    pub fn to_string(&self, file_name: &str) -> anyhow::Result<String> {
+        Ok(if file_name.ends_with(".toml") {
+            toml::to_string_pretty(self)?
+        } else {
+            serde_json::to_string_pretty(self)?
+        })
+    }
+
+    /// Checks that the scene can be converted to a prefab, and returns all the problems found.
+    ///
+    /// Components that are not registered are errors, unless `allow_unknown_components` is set,
+    /// in which case they are returned as warnings; this is useful when the components of the
+    /// package are not known. `model_exists` is called with the `model` of each entity that has
+    /// one.
+    pub fn validate(
+        &self,
+        allow_unknown_components: bool,
+        model_exists: impl Fn(&str) -> bool,
+    ) -> Result<Vec<SceneError>, SceneErrors> {
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+
+        let mut indices = HashMap::new();
+        for (index, entity) in self.entities.iter().enumerate() {
+            let mut error = |field: &str, message: String| {
+                errors.push(SceneError::new(index, entity, field, message))
+            };
+
+            if entity.id.is_empty() {
+                error("id", "the id is empty".to_string());
+            } else if let Some(other) = indices.get(entity.id.as_str()) {
+                error("id", format!("the id is already used by entities[{other}]"));
+            } else {
+                indices.insert(entity.id.as_str(), index);
+            }
+
+            if !entity.translation.is_finite() {
+                error("translation", "the translation is not finite".to_string());
+            }
+            if !entity.scale.is_finite() {
+                error("scale", "the scale is not finite".to_string());
+            }
+            let length = entity.rotation.length();
+            if !entity.rotation.is_finite() || (length - 1.).abs() > ROTATION_TOLERANCE {
+                error(
+                    "rotation",
+                    format!(
+                        "the rotation is not a unit quaternion (its length is {length}); \
+                         rotations are written as [x, y, z, w]"
+                    ),
+                );
+            }
+
+            if let Some(model) = &entity.model {
+                if model.is_empty() || model.starts_with('/') || model.contains("://") {
+                    error(
+                        "model",
+                        format!("`{model}` is not a path relative to the scene file"),
+                    );
+                } else if !model_exists(model) {
+                    error("model", format!("the model `{model}` does not exist"));
+                }
+            }
+
+            for (path, value) in &entity.components {
+                let field = format!("components.\"{path}\"");
+                match component_entry(path, value) {
+                    Ok(_) => {}
+                    Err(ComponentError::Unknown) if allow_unknown_components => {
+                        warnings.push(SceneError::new(
+                            index,
+                            entity,
+                            &field,
+                            format!("`{path}` is not a known component, and is skipped"),
+                        ));
+                    }
+                    Err(err) => error(&field, err.message(path, value)),
+                }
+            }
+        }
+
+        for (index, entity) in self.entities.iter().enumerate() {
+            let Some(parent) = &entity.parent else {
+                continue;
+            };
+            let message = if parent == &entity.id {
+                Some("the entity is its own parent".to_string())
+            } else if !indices.contains_key(parent.as_str()) {
+                Some(format!("there is no entity with the id `{parent}`"))
+            } else {
+                self.parent_cycle(&indices, index).map(|cycle| {
+                    format!(
+                        "the parents form a cycle: {}",
+                        cycle
+                            .iter()
+                            .map(|&index| format!("`{}`", self.entities[index].id))
+                            .collect::<Vec<_>>()
+                            .join(" -> ")
+                    )
+                })
+            };
+            if let Some(message) = message {
+                errors.push(SceneError::new(index, entity, "parent", message));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(warnings)
+        } else {
+            Err(SceneErrors(errors))
+        }
+    }
+
+    /// The indices of the entities of the cycle of parents that starts at `start`, if there is
+    /// one and `start` is its first entity, so that each cycle is only reported once.
+    fn parent_cycle(&self, indices: &HashMap<&str, usize>, start: usize) -> Option<Vec<usize>> {
+        let mut cycle = vec![start];
+        let mut index = start;
+        while let Some(next) = self.entities[index]
+            .parent
+            .as_deref()
+            .and_then(|parent| indices.get(parent).copied())
+        {
+            if next == start {
+                cycle.push(start);
+                return (cycle.iter().min() == Some(&start)).then_some(cycle);
+            }
+            if cycle.contains(&next) {
+                // A cycle further up, which is reported from its own entities
+                return None;
+            }
+            cycle.push(next);
+            index = next;
+        }
+        None
+    }
+
+    /// The root entities of the scene, and the children of each entity, by index.
+    pub fn hierarchy(&self) -> (Vec<usize>, Vec<Vec<usize>>) {
+        let indices: HashMap<_, _> = self
+            .entities
+            .iter()
+            .enumerate()
+            .map(|(index, entity)| (entity.id.as_str(), index))
+            .collect();
+        let mut roots = Vec::new();
+        let mut entity_children = vec![Vec::new(); self.entities.len()];
+        for (index, entity) in self.entities.iter().enumerate() {
+            match entity.parent.as_deref().and_then(|id| indices.get(id)) {
+                Some(&parent) => entity_children[parent].push(index),
+                None => roots.push(index),
+            }
+        }
+        (roots, entity_children)
+    }
+}
+
+/// Converts the JSON `value` of the component at `path` to a component.
+///
+/// A component without a value, such as a tag, is set with `true`.
+pub fn component_entry(
+    path: &str,
+    value: &serde_json::Value,
+) -> Result<ComponentEntry, ComponentError> {
+    let desc = with_component_registry(|registry| registry.get_by_path(path))
+        .ok_or(ComponentError::Unknown)?;
+    if let Some(field) = reserved_field(path) {
+        return Err(ComponentError::Reserved(field));
+    }
+    if !desc.has_attribute::<Serializable>() {
+        return Err(ComponentError::NotSerializable);
+    }
+    match desc.from_json(&value.to_string()) {
+        Ok(entry) => Ok(entry),
+        Err(_) if value == &serde_json::Value::Bool(true) => desc
+            .from_json("null")
+            .map_err(|err| ComponentError::InvalidValue(err.to_string())),
+        Err(err) => Err(ComponentError::InvalidValue(err.to_string())),
+    }
+}
+
+/// The field of [SceneEntity] that sets the component at `path`, if the component is set from
+/// the fields of the entity rather than from its `components`.
+fn reserved_field(path: &str) -> Option<&'static str> {
+    [
+        (translation().path(), "translation"),
+        (rotation().path(), "rotation"),
+        (scale().path(), "scale"),
+        (name().path(), "name"),
+        (parent().path(), "parent"),
+        (children().path(), "parent"),
+        (local_to_parent().path(), "translation"),
+        (local_to_world().path(), "translation"),
+        (prefab_from_url().path(), "model"),
+    ]
+    .into_iter()
+    .find(|(reserved, _)| *reserved == path)
+    .map(|(_, field)| field)
+}
+
+/// Why a component of a [SceneEntity] could not be converted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComponentError {
+    Unknown,
+    /// The component is set by this field of the entity
+    Reserved(&'static str),
+    NotSerializable,
+    InvalidValue(String),
+}
+impl ComponentError {
+    fn message(&self, path: &str, value: &serde_json::Value) -> String {
+        match self {
+            ComponentError::Unknown => format!(
+                "`{path}` is not a known component; components are written with their full \
+                 path, such as `ambient_core::physics::sphere_collider`"
+            ),
+            ComponentError::Reserved(field) => {
+                format!("`{path}` is set from the `{field}` field of the entity")
+            }
+            ComponentError::NotSerializable => {
+                format!("`{path}` cannot be stored, as it is not serializable")
+            }
+            ComponentError::InvalidValue(err) => {
+                format!("`{value}` is not a valid value for `{path}`: {err}")
+            }
+        }
+    }
+}
+
+/// A problem with an entity of a [Scene].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneError {
+    /// Where the problem is, such as `entities[2] ("lamp").parent`
+    pub location: String,
+    pub message: String,
+}
+impl SceneError {
+    fn new(index: usize, entity: &SceneEntity, field: &str, message: String) -> Self {
+        Self {
+            location: format!("entities[{index}] (\"{}\").{field}", entity.id),
+            message,
+        }
+    }
+}
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// The errors found by [Scene::validate].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneErrors(pub Vec<SceneError>);
+impl fmt::Display for SceneErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the scene has {} error(s):", self.0.len())?;
+        for error in &self.0 {
+            write!(f, "\n  - {error}")?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for SceneErrors {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entity(id: &str, parent: Option<&str>) -> SceneEntity {
+        SceneEntity {
+            id: id.to_string(),
+            name: None,
+            parent: parent.map(|parent| parent.to_string()),
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            model: None,
+            components: IndexMap::new(),
+        }
+    }
+
+    fn scene() -> Scene {
+        Scene {
+            format_version: SCENE_FORMAT_VERSION,
+            name: Some("level".to_string()),
+            entities: vec![
+                SceneEntity {
+                    name: Some("Lamp".to_string()),
+                    translation: Vec3::new(1., 2., 3.),
+                    rotation: Quat::from_xyzw(0., 0., 0.6, 0.8),
+                    scale: Vec3::splat(0.5),
+                    model: Some("models/lamp.glb".to_string()),
+                    components: IndexMap::from([
+                        ("game::light::intensity".to_string(), serde_json::json!(2.5)),
+                        (
+                            "game::light::tags".to_string(),
+                            serde_json::json!(["a", "b"]),
+                        ),
+                    ]),
+                    ..entity("lamp", None)
+                },
+                entity("bulb", Some("lamp")),
+            ],
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        for file_name in ["level.scene.json", "level.scene.toml"] {
+            let scene = scene();
+            let text = scene.to_string(file_name).unwrap();
+            assert_eq!(Scene::parse(file_name, text.as_bytes()).unwrap(), scene);
+        }
+
+        // Default transforms are left out
+        let text = scene().to_string("level.scene.json").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            value["entities"][1],
+            serde_json::json!({ "id": "bulb", "parent": "lamp" })
+        );
+    }
+
+    #[test]
+    fn unsupported_versions_are_rejected() {
+        let error = Scene::parse("level.scene.json", br#"{ "format_version": 2 }"#).unwrap_err();
+        assert!(error.to_string().contains("version 2"), "{error}");
+        let error = Scene::parse("level.scene.toml", b"format_version = 0").unwrap_err();
+        assert!(error.to_string().contains("version 0"), "{error}");
+        let error = Scene::parse("level.scene.json", br#"{ "entities": [] }"#).unwrap_err();
+        assert!(error.to_string().contains("no `format_version`"), "{error}");
+
+        // The version is checked before the rest of the scene, whose format may have changed
+        let error = Scene::parse(
+            "level.scene.json",
+            br#"{ "format_version": 2, "entities": { "lamp": {} } }"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("version 2"), "{error}");
+    }
+
+    #[test]
+    fn errors_point_at_the_field() {
+        let error = Scene::parse(
+            "level.scene.json",
+            br#"{
+                "format_version": 1,
+                "entities": [{ "id": "lamp" }, { "id": "bulb", "scale": 2 }]
+            }"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("entities[1].scale"), "{error}");
+    }
+
+    #[test]
+    fn parent_cycles() {
+        let scene = Scene {
+            entities: vec![
+                entity("a", Some("c")),
+                entity("b", Some("a")),
+                entity("c", Some("b")),
+                entity("d", Some("d")),
+                entity("e", Some("a")),
+            ],
+            ..scene()
+        };
+        let errors = scene.validate(false, |_| true).unwrap_err().0;
+        assert_eq!(
+            errors,
+            [
+                SceneError {
+                    location: "entities[0] (\"a\").parent".to_string(),
+                    message: "the parents form a cycle: `a` -> `c` -> `b` -> `a`".to_string(),
+                },
+                SceneError {
+                    location: "entities[3] (\"d\").parent".to_string(),
+                    message: "the entity is its own parent".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use ambient_core::{
+    hierarchy::{children, parent},
+    name,
+    transform::{local_to_parent, local_to_world, rotation, scale, translation},
+};
+use ambient_ecs::{
+    generated::prefab::components::prefab_from_url, Entity, EntityId, World, WorldContext,
+};
+use ambient_model_import::model_crate::ModelCrate;
+use ambient_native_std::asset_url::AssetType;
+use ambient_pipeline_types::scenes::ScenesPipeline;
+use anyhow::Context;
+use glam::{Mat4, Vec3};
+use tracing::{info_span, Instrument};
+
+use self::format::{component_entry, is_scene_path, Scene};
+use super::{
+    context::PipelineCtx,
+    out_asset::{asset_id_from_url, OutAsset, OutAssetContent, OutAssetPreview},
+};
+
+pub mod format;
+
+pub async fn pipeline(ctx: &PipelineCtx, config: ScenesPipeline) -> Vec<OutAsset> {
+    ctx.process_files(
+        |file| is_scene_path(&file.decoded_path().to_string()),
+        move |ctx, file| {
+            let config = config.clone();
+            async move {
+                let filename = file.decoded_path().file_name().unwrap().to_string();
+                let scene = Scene::parse(&filename, &file.download_bytes(ctx.assets()).await?)?;
+                let warnings = scene
+                    .validate(config.skip_unknown_components, |model| {
+                        file.join(model)
+                            .map_or(false, |url| ctx.get_downloadable_url(&url).is_ok())
+                    })
+                    .with_context(|| format!("Invalid scene {filename}"))?;
+                for warning in warnings {
+                    tracing::warn!("Scene {filename}: {warning}");
+                }
+
+                let model_path = ctx.in_root().relative_path(file.decoded_path());
+                let out_model_url = ctx.out_root().join(&model_path)?;
+                let prefab_dir = out_model_url.decoded_path().join("prefabs");
+
+                // The models are referred to by the URL of their output directory, relative to the
+                // prefab, which the `Models` pipeline writes to the same relative path as the
+                // source model
+                let mut models = HashMap::new();
+                for model in scene
+                    .entities
+                    .iter()
+                    .filter_map(|entity| entity.model.as_ref())
+                {
+                    let model_url = file.join(model)?;
+                    let out_url = ctx
+                        .out_root()
+                        .join(ctx.in_root().relative_path(model_url.decoded_path()))?;
+                    models.insert(
+                        model.clone(),
+                        prefab_dir.relative(out_url.decoded_path()).to_string(),
+                    );
+                }
+
+                let scene_name = scene.name.clone().unwrap_or_else(|| {
+                    filename
+                        .trim_end_matches(".json")
+                        .trim_end_matches(".toml")
+                        .trim_end_matches(".scene")
+                        .to_string()
+                });
+                let mut model_crate = ModelCrate::new();
+                model_crate
+                    .prefabs
+                    .insert(ModelCrate::MAIN, build_prefab(&scene, &scene_name, &models));
+                let model_url = ctx.write_model_crate(&model_crate, &model_path).await;
+
+                Ok(vec![OutAsset {
+                    id: asset_id_from_url(&file),
+                    type_: AssetType::Prefab,
+                    hidden: false,
+                    name: scene_name,
+                    tags: Vec::new(),
+                    categories: Default::default(),
+                    preview: OutAssetPreview::None,
+                    content: OutAssetContent::Content(model_url.prefab().unwrap_abs()),
+                    source: Some(file.clone()),
+                }])
+            }
+        },
+    )
+    .instrument(info_span!("scenes_pipeline"))
+    .await
+}
+
+/// Builds the prefab of a validated `scene`.
+///
+/// The root entity of the prefab is named after the scene, and the entities of the scene are its
+/// descendants. `models` maps the `model` of the entities to the URL of the model, relative to
+/// the prefab, which is spawned on the entity with `prefab_from_url`.
+fn build_prefab(scene: &Scene, scene_name: &str, models: &HashMap<String, String>) -> World {
+    let mut prefab = World::new("prefab_asset", WorldContext::Prefab);
+    let (roots, entity_children) = scene.hierarchy();
+
+    let ids = scene
+        .entities
+        .iter()
+        .map(|entity| {
+            let entity_rotation = entity.rotation.normalize();
+            let transform = Mat4::from_scale_rotation_translation(
+                entity.scale,
+                entity_rotation,
+                entity.translation,
+            );
+            let mut data = Entity::new()
+                .with(translation(), entity.translation)
+                .with(rotation(), entity_rotation)
+                .with(local_to_parent(), transform)
+                .with(local_to_world(), transform);
+            if entity.scale != Vec3::ONE {
+                data.set(scale(), entity.scale);
+            }
+            if let Some(entity_name) = &entity.name {
+                data.set(name(), entity_name.clone());
+            }
+            if let Some(url) = entity.model.as_ref().and_then(|model| models.get(model)) {
+                data.set(prefab_from_url(), url.clone());
+            }
+            // Unknown components are skipped, as they were allowed by the validation
+            for (path, value) in &entity.components {
+                if let Ok(entry) = component_entry(path, value) {
+                    data.set_entry(entry);
+                }
+            }
+            data.spawn(&mut prefab)
+        })
+        .collect::<Vec<_>>();
+
+    let root = Entity::new()
+        .with(name(), scene_name.to_string())
+        .spawn(&mut prefab);
+    let mut link = |parent_id: EntityId, child_ids: Vec<EntityId>| {
+        for &child in &child_ids {
+            prefab.add_component(child, parent(), parent_id).unwrap();
+        }
+        if !child_ids.is_empty() {
+            prefab
+                .add_component(parent_id, children(), child_ids)
+                .unwrap();
+        }
+    };
+    link(root, roots.iter().map(|&index| ids[index]).collect());
+    for (index, entity_children) in entity_children.into_iter().enumerate() {
+        link(
+            ids[index],
+            entity_children.iter().map(|&index| ids[index]).collect(),
+        );
+    }
+    prefab.add_resource(children(), vec![root]);
+    prefab
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use ambient_core::{
+    hierarchy::{children, parent},
+    name,
+    transform::{local_to_parent, local_to_world, rotation, translation},
+};
+use ambient_ecs::{
+    generated::tilemap::components::{
+        tilemap_object_class, tilemap_object_size, tilemap_size, tilemap_tile_size, tilemap_tiles,
+        tilemap_tileset_columns, tilemap_tileset_first_ids, tilemap_tileset_images,
+        tilemap_tileset_tile_counts,
+    },
+    with_component_registry, ComponentEntry, Entity, Serializable, World, WorldContext,
+};
+use ambient_model_import::model_crate::ModelCrate;
+use ambient_native_std::asset_url::AssetType;
+use ambient_physics::collider::{collider, ColliderDef};
+use ambient_pipeline_types::tilemaps::TilemapsPipeline;
+use anyhow::Context;
+use glam::{uvec2, vec2, vec3, Mat4, Quat, Vec2};
+use tracing::{info_span, Instrument};
+
+use self::tiled::{merge_solid_cells, Element, LayerKind, Map, PropertyValue, FLIP_FLAGS};
+use super::{
+    context::PipelineCtx,
+    out_asset::{asset_id_from_url, OutAsset, OutAssetContent, OutAssetPreview},
+};
+
+mod tiled;
+
+pub async fn pipeline(ctx: &PipelineCtx, config: TilemapsPipeline) -> Vec<OutAsset> {
+    ctx.process_files(
+        |file| file.extension().as_deref() == Some("tmx"),
+        move |ctx, file| {
+            let config = config.clone();
+            async move {
+                let filename = file.decoded_path().file_name().unwrap().to_string();
+                let element = Element::parse(&file.download_bytes(ctx.assets()).await?)
+                    .with_context(|| format!("Failed to parse map {filename}"))?;
+
+                let mut external_tilesets = HashMap::new();
+                for tileset in element.children("tileset") {
+                    let Some(source) = tileset.attribute("source") else {
+                        continue;
+                    };
+                    let data = ctx
+                        .get_downloadable_url(&file.join(source)?)?
+                        .download_bytes(ctx.assets())
+                        .await?;
+                    let tileset = Element::parse(&data)
+                        .with_context(|| format!("Failed to parse tileset {source}"))?;
+                    external_tilesets.insert(source.to_string(), tileset);
+                }
+                let map = Map::from_element(&element, &external_tilesets)
+                    .with_context(|| format!("Failed to read map {filename}"))?;
+
+                let model_path = ctx.in_root().relative_path(file.decoded_path());
+                let out_model_url = ctx.out_root().join(&model_path)?;
+                let prefab_dir = out_model_url.decoded_path().join("prefabs");
+
+                // The tileset images are copied next to the map, and referred to relative to the
+                // prefab
+                let mut images = Vec::new();
+                for tileset in &map.tilesets {
+                    let Some(image) = &tileset.image else {
+                        images.push(String::new());
+                        continue;
+                    };
+                    let base = match &tileset.source {
+                        Some(source) => file.join(source)?,
+                        None => file.clone(),
+                    };
+                    let image_url = ctx.get_downloadable_url(&base.join(image)?)?.clone();
+                    let content = image_url.download_bytes(ctx.assets()).await?;
+                    let out_url = ctx
+                        .write_file(
+                            ctx.in_root().relative_path(image_url.decoded_path()),
+                            content,
+                        )
+                        .await;
+                    images.push(prefab_dir.relative(out_url.decoded_path()).to_string());
+                }
+
+                let mut model_crate = ModelCrate::new();
+                model_crate.prefabs.insert(
+                    ModelCrate::MAIN,
+                    build_prefab(&map, &config, file.file_stem().unwrap_or_default(), images),
+                );
+                let model_url = ctx.write_model_crate(&model_crate, &model_path).await;
+
+                Ok(vec![OutAsset {
+                    id: asset_id_from_url(&file),
+                    type_: AssetType::Prefab,
+                    hidden: false,
+                    name: filename,
+                    tags: Vec::new(),
+                    categories: Default::default(),
+                    preview: OutAssetPreview::None,
+                    content: OutAssetContent::Content(model_url.prefab().unwrap_abs()),
+                    source: Some(file.clone()),
+                }])
+            }
+        },
+    )
+    .instrument(info_span!("tilemaps_pipeline"))
+    .await
+}
+
+/// Builds the prefab of `map`.
+///
+/// The root entity of the prefab has the tilemap and tileset components, and the colliders of the
+/// solid tiles. Its children are the visible tile layers and the objects of the object layers. The
+/// map is in the XY plane, with its top-left corner at the origin and Tiled's Y axis flipped, so
+/// that the map extends towards +X and -Y.
+fn build_prefab(
+    map: &Map,
+    config: &TilemapsPipeline,
+    map_name: &str,
+    tileset_images: Vec<String>,
+) -> World {
+    let scale = config.tile_size / map.tile_width as f32;
+    let tile_size = vec2(map.tile_width as f32, map.tile_height as f32) * scale;
+    let to_world = |pixels: Vec2| vec3(pixels.x, -pixels.y, 0.) * scale;
+
+    let mut prefab = World::new("prefab_asset", WorldContext::Prefab);
+    let mut boxes = Vec::new();
+    let mut child_entities = Vec::new();
+    for layer in &map.layers {
+        let offset = to_world(layer.offset);
+        match &layer.kind {
+            LayerKind::Tiles(tiles) => {
+                // The property of the layer takes precedence over the properties of its tiles
+                let layer_solid = layer
+                    .property(&config.collision_property)
+                    .and_then(PropertyValue::as_bool);
+                let solid = tiles
+                    .iter()
+                    .map(|&gid| match layer_solid {
+                        Some(solid) => solid && gid & !FLIP_FLAGS != 0,
+                        None => map
+                            .tile(gid)
+                            .and_then(|(tileset, id)| {
+                                tileset
+                                    .tile_property(id, &config.collision_property)?
+                                    .as_bool()
+                            })
+                            .unwrap_or(false),
+                    })
+                    .collect::<Vec<_>>();
+                for (column, row, columns, rows) in merge_solid_cells(map.width, &solid) {
+                    let size = vec2(columns as f32, rows as f32) * tile_size;
+                    let corner = vec2(column as f32, row as f32) * tile_size;
+                    let center = offset
+                        + vec3(
+                            corner.x + size.x / 2.,
+                            -(corner.y + size.y / 2.),
+                            config.collision_height / 2.,
+                        );
+                    boxes.push((size.extend(config.collision_height), center));
+                }
+
+                if layer.visible {
+                    child_entities.push(
+                        Entity::new()
+                            .with(name(), layer.name.clone())
+                            .with(tilemap_size(), uvec2(map.width, map.height))
+                            .with(tilemap_tile_size(), tile_size)
+                            .with(tilemap_tiles(), tiles.clone())
+                            .with(translation(), offset)
+                            .with(local_to_parent(), Mat4::from_translation(offset))
+                            .with(local_to_world(), Mat4::from_translation(offset)),
+                    );
+                }
+            }
+            LayerKind::Objects(objects) => {
+                for object in objects {
+                    let object_rotation = Quat::from_rotation_z(-object.rotation.to_radians());
+                    let center = to_world(layer.offset + object.position)
+                        + object_rotation * to_world(object.center_offset());
+                    let transform = Mat4::from_rotation_translation(object_rotation, center);
+                    let mut entity = Entity::new()
+                        .with(translation(), center)
+                        .with(rotation(), object_rotation)
+                        .with(local_to_parent(), transform)
+                        .with(local_to_world(), transform)
+                        .with(tilemap_object_size(), object.size * scale);
+                    if !object.name.is_empty() {
+                        entity.set(name(), object.name.clone());
+                    }
+                    if !object.class.is_empty() {
+                        entity.set(tilemap_object_class(), object.class.clone());
+                    }
+                    for (property, value) in &object.properties {
+                        match property_component(property, value) {
+                            Some(entry) => entity.set_entry(entry),
+                            None => tracing::warn!(
+                                "Skipping the property `{property}` of the object {} of map \
+                                 {map_name}, as it is not a component that accepts {value:?}",
+                                object.id
+                            ),
+                        }
+                    }
+                    child_entities.push(entity);
+                }
+            }
+        }
+    }
+
+    let mut root = Entity::new()
+        .with(name(), map_name.to_string())
+        .with(tilemap_size(), uvec2(map.width, map.height))
+        .with(tilemap_tile_size(), tile_size)
+        .with(
+            tilemap_tileset_first_ids(),
+            map.tilesets
+                .iter()
+                .map(|tileset| tileset.first_gid)
+                .collect(),
+        )
+        .with(tilemap_tileset_images(), tileset_images)
+        .with(
+            tilemap_tileset_columns(),
+            map.tilesets.iter().map(|tileset| tileset.columns).collect(),
+        )
+        .with(
+            tilemap_tileset_tile_counts(),
+            map.tilesets
+                .iter()
+                .map(|tileset| tileset.tile_count)
+                .collect(),
+        );
+    if !boxes.is_empty() {
+        root.set(collider(), ColliderDef::Boxes { boxes });
+    }
+    let children_ids = child_entities
+        .into_iter()
+        .map(|entity| entity.spawn(&mut prefab))
+        .collect::<Vec<_>>();
+    if !children_ids.is_empty() {
+        root.set(children(), children_ids.clone());
+    }
+    let root_id = root.spawn(&mut prefab);
+    for id in children_ids {
+        prefab.add_component(id, parent(), root_id).unwrap();
+    }
+    prefab.add_resource(children(), vec![root_id]);
+    prefab
+}
+
+/// Converts the custom property `property` of an object to a component, if `property` is the
+/// path of a component that can be deserialized from `value`.
+///
+/// Strings are parsed as JSON first, so that properties can hold vectors, lists and the like; a
+/// `true` boolean also sets components that have no value.
+fn property_component(property: &str, value: &PropertyValue) -> Option<ComponentEntry> {
+    let desc = with_component_registry(|registry| registry.get_by_path(property))?;
+    if !desc.has_attribute::<Serializable>() {
+        return None;
+    }
+
+    let json = |value: serde_json::Value| value.to_string();
+    let candidates = match value {
+        PropertyValue::String(value) | PropertyValue::File(value) => vec![
+            value.clone(),
+            json(serde_json::Value::String(value.clone())),
+        ],
+        PropertyValue::Int(value) => vec![value.to_string()],
+        PropertyValue::Float(value) => vec![json(serde_json::json!(value))],
+        PropertyValue::Bool(true) => vec!["true".to_string(), "null".to_string()],
+        PropertyValue::Bool(false) => vec!["false".to_string()],
+        PropertyValue::Color(color) => vec![json(serde_json::json!(color.to_array()))],
+        PropertyValue::Object(id) => vec![id.to_string()],
+    };
+    candidates
+        .into_iter()
+        .find_map(|candidate| desc.from_json(&candidate).ok())
+}
@@ -0,0 +1,672 @@
+//! The parts of Tiled's map (`.tmx`) and tileset (`.tsx`) formats that are imported.
+//!
+//! See <https://doc.mapeditor.org/en/stable/reference/tmx-map-format/>.
+use std::{collections::HashMap, io::Read, str::FromStr};
+
+use anyhow::{bail, Context};
+use glam::{vec2, Vec2, Vec4};
+use xml::reader::{EventReader, XmlEvent};
+
+/// The flip flags that Tiled stores in the highest bits of global tile IDs.
+pub const FLIP_FLAGS: u32 = 0xf000_0000;
+
+/// An XML element, with its attributes, children and text.
+#[derive(Debug, Clone, Default)]
+pub struct Element {
+    pub name: String,
+    pub attributes: HashMap<String, String>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+impl Element {
+    /// Parses the root element of an XML document.
+    pub fn parse(data: &[u8]) -> anyhow::Result<Self> {
+        let mut stack: Vec<Element> = Vec::new();
+        for event in EventReader::new(data) {
+            match event? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => stack.push(Element {
+                    name: name.local_name,
+                    attributes: attributes
+                        .into_iter()
+                        .map(|attribute| (attribute.name.local_name, attribute.value))
+                        .collect(),
+                    ..Default::default()
+                }),
+                XmlEvent::EndElement { .. } => {
+                    let element = stack.pop().context("Unbalanced XML")?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(&text);
+                    }
+                }
+                _ => {}
+            }
+        }
+        bail!("The document has no root element")
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(|value| value.as_str())
+    }
+
+    /// Parses the attribute `name`, if it is set.
+    pub fn parse_attribute<T: FromStr>(&self, name: &str) -> anyhow::Result<Option<T>> {
+        self.attribute(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid `{name}` of <{}>: {value:?}", self.name))
+            })
+            .transpose()
+    }
+
+    /// Parses the attribute `name`, which must be set.
+    pub fn required_attribute<T: FromStr>(&self, name: &str) -> anyhow::Result<T> {
+        self.parse_attribute(name)?
+            .with_context(|| format!("<{}> has no `{name}`", self.name))
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+/// The value of a custom property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    /// A color, as linear RGBA
+    Color(Vec4),
+    /// A path, relative to the file that defines the property
+    File(String),
+    /// The ID of an object of the map
+    Object(u32),
+}
+impl PropertyValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            PropertyValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// The custom properties of an element, by name.
+pub type Properties = Vec<(String, PropertyValue)>;
+
+fn properties(element: &Element) -> anyhow::Result<Properties> {
+    let Some(properties) = element.child("properties") else {
+        return Ok(Vec::new());
+    };
+    let mut result = Vec::new();
+    for property in properties.children("property") {
+        let name: String = property.required_attribute("name")?;
+        // Multi-line strings are stored as the text of the element
+        let value = property.attribute("value").unwrap_or(&property.text);
+        let value = match property.attribute("type").unwrap_or("string") {
+            "string" => PropertyValue::String(value.to_string()),
+            "file" => PropertyValue::File(value.to_string()),
+            "int" => PropertyValue::Int(parse_property(&name, value)?),
+            "float" => PropertyValue::Float(parse_property(&name, value)?),
+            "bool" => PropertyValue::Bool(parse_property(&name, value)?),
+            "object" => PropertyValue::Object(parse_property(&name, value)?),
+            "color" => PropertyValue::Color(
+                parse_color(value)
+                    .with_context(|| format!("Invalid color {value:?} of the property `{name}`"))?,
+            ),
+            ty => {
+                tracing::warn!(
+                    "Skipping the property `{name}`, as its type `{ty}` is not supported"
+                );
+                continue;
+            }
+        };
+        result.push((name, value));
+    }
+    Ok(result)
+}
+
+fn parse_property<T: FromStr>(name: &str, value: &str) -> anyhow::Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid value {value:?} of the property `{name}`"))
+}
+
+/// Parses a Tiled color, which is either `#RRGGBB` or `#AARRGGBB`, into linear RGBA.
+fn parse_color(value: &str) -> Option<Vec4> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    let argb = match hex.len() {
+        6 => 0xff00_0000 | u32::from_str_radix(hex, 16).ok()?,
+        8 => u32::from_str_radix(hex, 16).ok()?,
+        _ => return None,
+    };
+    let channel = |shift: u32| ((argb >> shift) & 0xff) as f32 / 255.;
+    let srgb_to_linear = |c: f32| {
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    Some(Vec4::new(
+        srgb_to_linear(channel(16)),
+        srgb_to_linear(channel(8)),
+        srgb_to_linear(channel(0)),
+        channel(24),
+    ))
+}
+
+/// A Tiled map. Only orthogonal, finite maps are supported.
+#[derive(Debug, Clone)]
+pub struct Map {
+    /// The size of the map, in tiles
+    pub width: u32,
+    pub height: u32,
+    /// The size of a tile, in pixels
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tilesets: Vec<Tileset>,
+    /// The layers of the map, from the bottom to the top. Group layers are flattened.
+    pub layers: Vec<Layer>,
+}
+impl Map {
+    /// Reads a map from its root element. `external_tilesets` are the root elements of the
+    /// tilesets that the map refers to, by their `source`.
+    pub fn from_element(
+        map: &Element,
+        external_tilesets: &HashMap<String, Element>,
+    ) -> anyhow::Result<Self> {
+        if map.name != "map" {
+            bail!("Expected a <map>, got <{}>", map.name);
+        }
+        let orientation = map.attribute("orientation").unwrap_or("orthogonal");
+        if orientation != "orthogonal" {
+            bail!("Only orthogonal maps are supported, but the map is {orientation}");
+        }
+        if map.attribute("infinite") == Some("1") {
+            bail!("Infinite maps are not supported");
+        }
+
+        let tilesets = map
+            .children("tileset")
+            .map(|tileset| {
+                let first_gid = tileset.required_attribute("firstgid")?;
+                match tileset.attribute("source") {
+                    Some(source) => {
+                        let external = external_tilesets
+                            .get(source)
+                            .with_context(|| format!("The tileset {source} was not loaded"))?;
+                        Tileset::from_element(external, first_gid, Some(source.to_string()))
+                            .with_context(|| format!("Failed to read the tileset {source}"))
+                    }
+                    None => Tileset::from_element(tileset, first_gid, None),
+                }
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut layers = Vec::new();
+        read_layers(map, Vec2::ZERO, true, &mut layers)?;
+
+        Ok(Self {
+            width: map.required_attribute("width")?,
+            height: map.required_attribute("height")?,
+            tile_width: map.required_attribute("tilewidth")?,
+            tile_height: map.required_attribute("tileheight")?,
+            tilesets,
+            layers,
+        })
+    }
+
+    /// Returns the tileset of the global tile ID `gid`, and the ID of the tile in it.
+    pub fn tile(&self, gid: u32) -> Option<(&Tileset, u32)> {
+        let gid = gid & !FLIP_FLAGS;
+        if gid == 0 {
+            return None;
+        }
+        let tileset = self
+            .tilesets
+            .iter()
+            .filter(|tileset| tileset.first_gid <= gid)
+            .max_by_key(|tileset| tileset.first_gid)?;
+        Some((tileset, gid - tileset.first_gid))
+    }
+}
+
+/// Reads the layers of `parent`, and of its group layers, into `layers`.
+fn read_layers(
+    parent: &Element,
+    offset: Vec2,
+    visible: bool,
+    layers: &mut Vec<Layer>,
+) -> anyhow::Result<()> {
+    for element in &parent.children {
+        if !matches!(element.name.as_str(), "layer" | "objectgroup" | "group") {
+            continue;
+        }
+        let name = element.attribute("name").unwrap_or_default().to_string();
+        let offset = offset
+            + vec2(
+                element.parse_attribute("offsetx")?.unwrap_or(0.),
+                element.parse_attribute("offsety")?.unwrap_or(0.),
+            );
+        let visible = visible && element.attribute("visible") != Some("0");
+        let properties = properties(element)
+            .with_context(|| format!("Failed to read the properties of the layer {name:?}"))?;
+
+        let kind = match element.name.as_str() {
+            "layer" => LayerKind::Tiles(
+                read_tiles(element)
+                    .with_context(|| format!("Failed to read the tiles of the layer {name:?}"))?,
+            ),
+            "objectgroup" => LayerKind::Objects(
+                element
+                    .children("object")
+                    .map(Object::from_element)
+                    .collect::<anyhow::Result<_>>()
+                    .with_context(|| format!("Failed to read the objects of the layer {name:?}"))?,
+            ),
+            _ => {
+                read_layers(element, offset, visible, layers)?;
+                continue;
+            }
+        };
+        layers.push(Layer {
+            name,
+            offset,
+            visible,
+            properties,
+            kind,
+        });
+    }
+    Ok(())
+}
+
+/// Reads the global tile IDs of a tile layer.
+fn read_tiles(layer: &Element) -> anyhow::Result<Vec<u32>> {
+    let data = layer.child("data").context("The layer has no data")?;
+    if data.child("chunk").is_some() {
+        bail!("Infinite maps are not supported");
+    }
+    let tiles = match data.attribute("encoding") {
+        None => data
+            .children("tile")
+            .map(|tile| Ok(tile.parse_attribute("gid")?.unwrap_or(0)))
+            .collect::<anyhow::Result<Vec<_>>>()?,
+        Some("csv") => data
+            .text
+            .split(',')
+            .map(|gid| gid.trim())
+            .filter(|gid| !gid.is_empty())
+            .map(|gid| {
+                gid.parse()
+                    .with_context(|| format!("Invalid tile ID {gid:?}"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?,
+        Some("base64") => {
+            let bytes = base64::decode(data.text.trim()).context("Invalid base64 tile data")?;
+            let mut decompressed = Vec::new();
+            match data.attribute("compression") {
+                None => decompressed = bytes,
+                Some("zlib") => {
+                    flate2::read::ZlibDecoder::new(bytes.as_slice())
+                        .read_to_end(&mut decompressed)
+                        .context("Invalid zlib tile data")?;
+                }
+                Some("gzip") => {
+                    flate2::read::GzDecoder::new(bytes.as_slice())
+                        .read_to_end(&mut decompressed)
+                        .context("Invalid gzip tile data")?;
+                }
+                Some(compression) => bail!("The {compression} compression is not supported"),
+            }
+            decompressed
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes(gid.try_into().unwrap()))
+                .collect()
+        }
+        Some(encoding) => bail!("The {encoding} encoding is not supported"),
+    };
+
+    let expected = layer.required_attribute::<usize>("width")?
+        * layer.required_attribute::<usize>("height")?;
+    if tiles.len() != expected {
+        bail!(
+            "The layer has {} tiles, but its size is {expected} tiles",
+            tiles.len()
+        );
+    }
+    Ok(tiles)
+}
+
+#[derive(Debug, Clone)]
+pub struct Tileset {
+    pub first_gid: u32,
+    /// The path of the tileset file, relative to the map, if the tileset is not embedded in it
+    pub source: Option<String>,
+    pub columns: u32,
+    pub tile_count: u32,
+    /// The path of the image of the tileset, relative to the file that defines the tileset.
+    /// Tilesets made of individual images have no image.
+    pub image: Option<String>,
+    /// The custom properties of the tiles that have some, by tile ID
+    pub tile_properties: HashMap<u32, Properties>,
+}
+impl Tileset {
+    fn from_element(
+        tileset: &Element,
+        first_gid: u32,
+        source: Option<String>,
+    ) -> anyhow::Result<Self> {
+        let mut tile_properties = HashMap::new();
+        for tile in tileset.children("tile") {
+            let properties = properties(tile)?;
+            if !properties.is_empty() {
+                tile_properties.insert(tile.required_attribute("id")?, properties);
+            }
+        }
+        Ok(Self {
+            first_gid,
+            source,
+            columns: tileset.parse_attribute("columns")?.unwrap_or(0),
+            tile_count: tileset.parse_attribute("tilecount")?.unwrap_or(0),
+            image: tileset
+                .child("image")
+                .and_then(|image| image.attribute("source"))
+                .map(|source| source.to_string()),
+            tile_properties,
+        })
+    }
+
+    /// Returns the custom property `name` of the tile `id`.
+    pub fn tile_property(&self, id: u32, name: &str) -> Option<&PropertyValue> {
+        self.tile_properties
+            .get(&id)?
+            .iter()
+            .find_map(|(property, value)| (property == name).then_some(value))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub name: String,
+    /// The offset of the layer, including the offsets of its groups, in pixels
+    pub offset: Vec2,
+    /// Whether the layer and its groups are visible
+    pub visible: bool,
+    pub properties: Properties,
+    pub kind: LayerKind,
+}
+impl Layer {
+    pub fn property(&self, name: &str) -> Option<&PropertyValue> {
+        self.properties
+            .iter()
+            .find_map(|(property, value)| (property == name).then_some(value))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LayerKind {
+    /// The global tile IDs of the layer, row by row
+    Tiles(Vec<u32>),
+    Objects(Vec<Object>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Object {
+    pub id: u32,
+    pub name: String,
+    pub class: String,
+    /// The position of the object, in pixels. This is the top-left corner of rectangles and
+    /// ellipses, and the bottom-left corner of tile objects.
+    pub position: Vec2,
+    /// The size of the object, in pixels
+    pub size: Vec2,
+    /// The clockwise rotation of the object around its position, in degrees
+    pub rotation: f32,
+    /// The global tile ID of tile objects
+    pub gid: Option<u32>,
+    pub properties: Properties,
+}
+impl Object {
+    fn from_element(object: &Element) -> anyhow::Result<Self> {
+        let id = object.required_attribute("id")?;
+        Ok(Self {
+            id,
+            name: object.attribute("name").unwrap_or_default().to_string(),
+            // The class was called type before Tiled 1.9
+            class: object
+                .attribute("class")
+                .or_else(|| object.attribute("type"))
+                .unwrap_or_default()
+                .to_string(),
+            position: vec2(
+                object.parse_attribute("x")?.unwrap_or(0.),
+                object.parse_attribute("y")?.unwrap_or(0.),
+            ),
+            size: vec2(
+                object.parse_attribute("width")?.unwrap_or(0.),
+                object.parse_attribute("height")?.unwrap_or(0.),
+            ),
+            rotation: object.parse_attribute("rotation")?.unwrap_or(0.),
+            gid: object.parse_attribute("gid")?,
+            properties: properties(object)
+                .with_context(|| format!("Failed to read the properties of the object {id}"))?,
+        })
+    }
+
+    /// The center of the object, in pixels, relative to its position and before its rotation.
+    pub fn center_offset(&self) -> Vec2 {
+        if self.gid.is_some() {
+            vec2(self.size.x / 2., -self.size.y / 2.)
+        } else {
+            self.size / 2.
+        }
+    }
+}
+
+/// Merges the solid cells of a grid of `width` columns into rectangles, which are returned as
+/// (column, row, columns, rows).
+pub fn merge_solid_cells(width: u32, solid: &[bool]) -> Vec<(u32, u32, u32, u32)> {
+    let width = width as usize;
+    if width == 0 {
+        return Vec::new();
+    }
+    let height = solid.len() / width;
+    let mut free = solid.to_vec();
+    let mut rects = Vec::new();
+    for row in 0..height {
+        let mut column = 0;
+        while column < width {
+            if !free[row * width + column] {
+                column += 1;
+                continue;
+            }
+            // Extend the rectangle to the right, then down while the rows below are solid too
+            let mut columns = 1;
+            while column + columns < width && free[row * width + column + columns] {
+                columns += 1;
+            }
+            let mut rows = 1;
+            while row + rows < height
+                && (column..column + columns).all(|c| free[(row + rows) * width + c])
+            {
+                rows += 1;
+            }
+            for r in row..row + rows {
+                for c in column..column + columns {
+                    free[r * width + c] = false;
+                }
+            }
+            rects.push((column as u32, row as u32, columns as u32, rows as u32));
+            column += columns;
+        }
+    }
+    rects
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use super::*;
+
+    const TERRAIN: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<tileset name="terrain" tilewidth="16" tileheight="16" tilecount="8" columns="4">
+  <image source="terrain.png" width="64" height="32"/>
+  <tile id="1">
+    <properties>
+      <property name="solid" type="bool" value="true"/>
+    </properties>
+  </tile>
+</tileset>"##;
+
+    fn base64_tiles(gids: &[u32], zlib: bool) -> String {
+        let bytes: Vec<u8> = gids.iter().flat_map(|gid| gid.to_le_bytes()).collect();
+        if !zlib {
+            return base64::encode(bytes);
+        }
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&bytes).unwrap();
+        base64::encode(encoder.finish().unwrap())
+    }
+
+    fn map(layers: &str) -> anyhow::Result<Map> {
+        let map = format!(
+            r##"<?xml version="1.0" encoding="UTF-8"?>
+<map orientation="orthogonal" width="2" height="2" tilewidth="16" tileheight="16" infinite="0">
+  <tileset firstgid="1" name="props" tilewidth="16" tileheight="16" tilecount="4" columns="2">
+    <image source="props.png" width="32" height="32"/>
+  </tileset>
+  <tileset firstgid="5" source="terrain.tsx"/>
+  {layers}
+</map>"##
+        );
+        let external_tilesets = HashMap::from([(
+            "terrain.tsx".to_string(),
+            Element::parse(TERRAIN.as_bytes())?,
+        )]);
+        Map::from_element(&Element::parse(map.as_bytes())?, &external_tilesets)
+    }
+
+    fn tiles(layer: &Layer) -> &[u32] {
+        match &layer.kind {
+            LayerKind::Tiles(tiles) => tiles,
+            LayerKind::Objects(_) => panic!("{} is not a tile layer", layer.name),
+        }
+    }
+
+    #[test]
+    fn csv_and_base64_layers() {
+        let gids = [1, 0, 6, 2];
+        let map = map(&format!(
+            r##"<layer name="csv" width="2" height="2">
+    <data encoding="csv">
+1,0,
+6,2
+</data>
+  </layer>
+  <group name="group" offsetx="8" visible="0">
+    <layer name="base64" width="2" height="2" offsety="4">
+      <data encoding="base64">{}</data>
+    </layer>
+    <layer name="zlib" width="2" height="2">
+      <data encoding="base64" compression="zlib">{}</data>
+    </layer>
+  </group>
+  <layer name="xml" width="2" height="2">
+    <data><tile gid="1"/><tile/><tile gid="6"/><tile gid="2"/></data>
+  </layer>"##,
+            base64_tiles(&gids, false),
+            base64_tiles(&gids, true)
+        ))
+        .unwrap();
+
+        let names = map.layers.iter().map(|layer| layer.name.as_str());
+        assert_eq!(names.collect::<Vec<_>>(), ["csv", "base64", "zlib", "xml"]);
+        for layer in &map.layers {
+            assert_eq!(tiles(layer), gids, "{}", layer.name);
+        }
+        assert!(map.layers[0].visible);
+        assert_eq!(map.layers[0].offset, Vec2::ZERO);
+        // Layers inherit the visibility and offset of their groups
+        assert!(!map.layers[1].visible);
+        assert_eq!(map.layers[1].offset, vec2(8., 4.));
+        assert_eq!(map.layers[2].offset, vec2(8., 0.));
+    }
+
+    #[test]
+    fn layers_must_match_the_size_of_the_map() {
+        let layer = format!(
+            r##"<layer name="short" width="2" height="2">
+    <data encoding="base64">{}</data>
+  </layer>"##,
+            base64_tiles(&[1, 2, 3], false)
+        );
+        assert!(map(&layer).is_err());
+        let layer = r##"<layer name="unknown" width="2" height="2">
+    <data encoding="hex">01000000</data>
+  </layer>"##;
+        assert!(map(layer).is_err());
+    }
+
+    #[test]
+    fn flip_flags_are_ignored_when_looking_up_tiles() {
+        let flipped_horizontally = 0x8000_0000;
+        let flipped_diagonally = 0x2000_0000;
+        let map = map(&format!(
+            r##"<layer name="flipped" width="2" height="2">
+    <data encoding="csv">{},0,{},2</data>
+  </layer>"##,
+            flipped_horizontally | 6,
+            flipped_horizontally | flipped_diagonally | 1
+        ))
+        .unwrap();
+
+        let gids = tiles(&map.layers[0]);
+        // The flags are kept in the layer, so that the renderer can flip the tiles
+        assert_eq!(gids[0] & FLIP_FLAGS, flipped_horizontally);
+        let (tileset, id) = map.tile(gids[0]).unwrap();
+        assert_eq!((tileset.first_gid, id), (5, 1));
+        let (tileset, id) = map.tile(gids[2]).unwrap();
+        assert_eq!((tileset.first_gid, id), (1, 0));
+        assert!(map.tile(gids[1]).is_none());
+        assert!(map.tile(flipped_horizontally).is_none());
+    }
+
+    #[test]
+    fn external_tilesets() {
+        let map = map("").unwrap();
+        let terrain = &map.tilesets[1];
+        assert_eq!(terrain.source.as_deref(), Some("terrain.tsx"));
+        assert_eq!(terrain.image.as_deref(), Some("terrain.png"));
+        assert_eq!((terrain.columns, terrain.tile_count), (4, 8));
+        assert_eq!(
+            terrain.tile_property(1, "solid"),
+            Some(&PropertyValue::Bool(true))
+        );
+        assert_eq!(terrain.tile_property(0, "solid"), None);
+
+        let map = Element::parse(
+            br##"<map width="1" height="1" tilewidth="16" tileheight="16">
+  <tileset firstgid="1" source="missing.tsx"/>
+</map>"##,
+        )
+        .unwrap();
+        assert!(Map::from_element(&map, &HashMap::new()).is_err());
+    }
+}
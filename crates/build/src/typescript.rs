@@ -0,0 +1,208 @@
+//! Generates TypeScript definitions for the components and messages of a package from its
+//! `ambient_package.json`, so that web tools can share the schema of the package.
+
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use ambient_package_json as json;
+
+/// The TypeScript types of the primitive types that don't map directly to a TypeScript type,
+/// in the form that they are serialized to JSON in.
+const PRIMITIVE_TYPES: &str = r#"/** An entity ID, encoded as base64. */
+export type EntityId = string;
+export type Vec2 = [number, number];
+export type Vec3 = [number, number, number];
+export type Vec4 = [number, number, number, number];
+export type Uvec2 = Vec2;
+export type Uvec3 = Vec3;
+export type Uvec4 = Vec4;
+export type Ivec2 = Vec2;
+export type Ivec3 = Vec3;
+export type Ivec4 = Vec4;
+/** A quaternion, as `[x, y, z, w]`. */
+export type Quat = Vec4;
+/** A 4x4 matrix of 16 numbers, in column-major order. */
+export type Mat4 = number[];
+export type Duration = { secs: number; nanos: number };
+/** A handle to a procedural asset, as a 128-bit integer in a string. */
+export type ProceduralHandle = string;
+"#;
+
+/// Writes `ambient_package.d.ts` next to the `ambient_package.json` at `json_path`.
+pub fn write(json_path: &Path) -> anyhow::Result<PathBuf> {
+    let manifest: json::Manifest = serde_json::from_str(&std::fs::read_to_string(json_path)?)?;
+    let output_path = json_path.with_file_name("ambient_package.d.ts");
+    std::fs::write(&output_path, generate(&manifest))?;
+    Ok(output_path)
+}
+
+fn generate(manifest: &json::Manifest) -> String {
+    let package = manifest.main_package();
+    let scope = manifest.get(&package.scope_id);
+
+    let mut out = format!(
+        "// Generated by Ambient from the package {:?} ({}). Do not edit.\n\n{PRIMITIVE_TYPES}\n",
+        package.name, package.data.id
+    );
+    write_scope(&mut out, manifest, scope, 0);
+
+    let mut components = vec![];
+    let mut messages = vec![];
+    collect(manifest, scope, &mut vec![], &mut components, &mut messages);
+
+    out += "\n/** The components of this package, by their ID. */\nexport interface Components {\n";
+    for component in components {
+        let name = component.name.as_deref().unwrap_or(&component.data.id);
+        write_doc(&mut out, 1, name, component.description.as_deref());
+        writeln!(
+            out,
+            "    {:?}: {};",
+            path(manifest, &component.data),
+            type_expr(manifest, &component.type_)
+        )
+        .unwrap();
+    }
+    out += "}\n";
+
+    out += "\n/** The messages of this package, by their ID. */\nexport interface Messages {\n";
+    for (namespace, message) in messages {
+        writeln!(
+            out,
+            "    {:?}: {};",
+            path(manifest, &message.data),
+            namespace
+                .iter()
+                .chain(std::iter::once(&message.data.id))
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(".")
+        )
+        .unwrap();
+    }
+    out += "}\n";
+
+    out
+}
+
+/// Writes the messages of `scope` as interfaces, and its subscopes as namespaces.
+fn write_scope(out: &mut String, manifest: &json::Manifest, scope: &json::Scope, depth: usize) {
+    let indent = "    ".repeat(depth);
+    for message_id in scope.messages.values() {
+        let message = manifest.get(message_id);
+        write_doc(out, depth, &message.data.id, message.description.as_deref());
+        writeln!(out, "{indent}export interface {} {{", message.data.id).unwrap();
+        for (field, type_id) in &message.fields {
+            writeln!(
+                out,
+                "{indent}    {field}: {};",
+                type_expr(manifest, type_id)
+            )
+            .unwrap();
+        }
+        writeln!(out, "{indent}}}").unwrap();
+    }
+    for (id, scope_id) in &scope.scopes {
+        writeln!(out, "{indent}export namespace {id} {{").unwrap();
+        write_scope(out, manifest, manifest.get(scope_id), depth + 1);
+        writeln!(out, "{indent}}}").unwrap();
+    }
+}
+
+/// Collects the components and messages of `scope` and its subscopes, with the namespace that
+/// each message is written to.
+fn collect<'a>(
+    manifest: &'a json::Manifest,
+    scope: &'a json::Scope,
+    namespace: &mut Vec<String>,
+    components: &mut Vec<&'a json::Component>,
+    messages: &mut Vec<(Vec<String>, &'a json::Message)>,
+) {
+    components.extend(scope.components.values().map(|id| manifest.get(id)));
+    messages.extend(
+        scope
+            .messages
+            .values()
+            .map(|id| (namespace.clone(), manifest.get(id))),
+    );
+    for (id, scope_id) in &scope.scopes {
+        namespace.push(id.clone());
+        collect(
+            manifest,
+            manifest.get(scope_id),
+            namespace,
+            components,
+            messages,
+        );
+        namespace.pop();
+    }
+}
+
+/// The TypeScript type of the JSON representation of the type.
+fn type_expr(manifest: &json::Manifest, id: &json::ItemId<json::Type>) -> String {
+    use json::PrimitiveType as PT;
+
+    match &manifest.get(id).inner {
+        json::TypeInner::Primitive(primitive) => match primitive {
+            PT::Empty => "null".to_string(),
+            PT::Bool => "boolean".to_string(),
+            PT::String => "string".to_string(),
+            PT::F32
+            | PT::F64
+            | PT::U8
+            | PT::U16
+            | PT::U32
+            | PT::U64
+            | PT::I8
+            | PT::I16
+            | PT::I32
+            | PT::I64 => "number".to_string(),
+            PT::ProceduralMeshHandle
+            | PT::ProceduralTextureHandle
+            | PT::ProceduralSamplerHandle
+            | PT::ProceduralMaterialHandle => "ProceduralHandle".to_string(),
+            // The remaining primitives have aliases with the same name
+            _ => format!("{primitive:?}"),
+        },
+        json::TypeInner::Vec(inner) => match &manifest.get(inner).inner {
+            json::TypeInner::Enum(_) => format!("({})[]", type_expr(manifest, inner)),
+            _ => format!("{}[]", type_expr(manifest, inner)),
+        },
+        json::TypeInner::Option(inner) => format!("{} | null", type_expr(manifest, inner)),
+        json::TypeInner::Enum(e) => e
+            .members
+            .keys()
+            .map(|member| format!("{member:?}"))
+            .collect::<Vec<_>>()
+            .join(" | "),
+    }
+}
+
+/// The ID that the item is registered with at runtime, e.g. `my_package::my_scope::my_component`.
+fn path(manifest: &json::Manifest, data: &json::ItemData) -> String {
+    let mut segments = vec![data.id.as_str()];
+    let mut parent_id = data.parent_id.as_ref();
+    while let Some(id) = parent_id {
+        let parent = manifest.get(id);
+        if !parent.data.id.is_empty() {
+            segments.push(&parent.data.id);
+        }
+        parent_id = parent.data.parent_id.as_ref();
+    }
+    segments.reverse();
+    segments.join("::")
+}
+
+fn write_doc(out: &mut String, depth: usize, name: &str, description: Option<&str>) {
+    let indent = "    ".repeat(depth);
+    let text = match description {
+        Some(description) => format!("**{name}**: {description}"),
+        None => format!("**{name}**"),
+    };
+    writeln!(out, "{indent}/**").unwrap();
+    for line in text.replace("*/", "*\\/").lines() {
+        writeln!(out, "{indent} * {line}").unwrap();
+    }
+    writeln!(out, "{indent} */").unwrap();
+}
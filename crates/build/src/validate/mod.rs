@@ -0,0 +1,568 @@
+//! Checks the manifests, pipelines and referenced assets of a package without building it, so
+//! that mistakes in them are reported with their location instead of failing the build later.
+
+use std::{
+    fmt::Display,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use ambient_package::{Manifest, ManifestParseError};
+use ambient_pipeline_types::{
+    MaterialsImporter, PipelinePbrMaterial, PipelineProcessor, PipelinesFile,
+};
+use ambient_std::path::path_to_unix_string_lossy;
+use itertools::Itertools;
+use serde_json::Value;
+use walkdir::WalkDir;
+
+/// The JSON schema of `ambient.toml`, which is also used to find the unknown keys of manifests.
+///
+/// ----- NOTE: Update this when changing `ambient_package::Manifest` -----
+const MANIFEST_SCHEMA: &str = include_str!("manifest.schema.json");
+
+/// The JSON schema of `ambient.toml`, which editors with TOML support can use to complete and
+/// check manifests.
+pub fn manifest_schema() -> Value {
+    serde_json::from_str(MANIFEST_SCHEMA).unwrap()
+}
+
+/// A problem in a file of a package.
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    pub path: PathBuf,
+    /// The line and column of the problem, starting from 1
+    pub position: Option<(usize, usize)>,
+    pub message: String,
+}
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.position {
+            Some((line, column)) => {
+                write!(
+                    f,
+                    "{}:{line}:{column}: {}",
+                    self.path.display(),
+                    self.message
+                )
+            }
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+/// Checks the manifest of the package at `package_path` and its includes against the manifest
+/// schema, the pipelines in its assets, and that the files they refer to exist.
+pub fn validate_package(package_path: &Path) -> Vec<ValidationError> {
+    let schema = manifest_schema();
+    let mut errors = Vec::new();
+    validate_manifest(
+        package_path,
+        &package_path.join("ambient.toml"),
+        &schema,
+        &mut errors,
+    );
+
+    let mut pipelines = WalkDir::new(package_path.join("assets"))
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.file_type().is_file()
+                && entry
+                    .file_name()
+                    .to_string_lossy()
+                    .ends_with("pipeline.toml")
+        })
+        .map(|entry| entry.into_path())
+        .collect::<Vec<_>>();
+    pipelines.sort();
+    for path in pipelines {
+        validate_pipelines(&path, &mut errors);
+    }
+    errors
+}
+
+fn read(path: &Path, errors: &mut Vec<ValidationError>) -> Option<String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(err) => {
+            errors.push(ValidationError {
+                path: path.to_owned(),
+                position: None,
+                message: format!("Failed to read the file: {err}"),
+            });
+            None
+        }
+    }
+}
+
+/// A file that is being validated, to locate its problems.
+struct Source<'a> {
+    path: &'a Path,
+    text: &'a str,
+}
+impl<'a> Source<'a> {
+    fn error(&self, span: Option<Range<usize>>, message: impl Into<String>) -> ValidationError {
+        let position = span.map(|span| {
+            let before = &self.text[..span.start.min(self.text.len())];
+            let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+            (
+                before.matches('\n').count() + 1,
+                before[line_start..].chars().count() + 1,
+            )
+        });
+        ValidationError {
+            path: self.path.to_owned(),
+            position,
+            message: message.into(),
+        }
+    }
+
+    /// The first occurrence of `value` as a string in the file.
+    fn find_string(&self, value: &str) -> Option<Range<usize>> {
+        [format!("\"{value}\""), format!("'{value}'")]
+            .iter()
+            .filter_map(|quoted| {
+                self.text
+                    .find(quoted.as_str())
+                    .map(|start| start..start + quoted.len())
+            })
+            .min_by_key(|span| span.start)
+    }
+
+    /// The first occurrence of `key` as the key of a value in the file.
+    fn find_key(&self, key: &str) -> Option<Range<usize>> {
+        self.text.match_indices(key).find_map(|(start, _)| {
+            let end = start + key.len();
+            let before = self.text[..start]
+                .trim_end_matches([' ', '\t'])
+                .chars()
+                .last();
+            let after = self.text[end..]
+                .trim_start_matches([' ', '\t'])
+                .chars()
+                .next();
+            let key_start = matches!(before, None | Some('\n' | '{' | ',' | '.' | '['));
+            let key_end = matches!(after, Some('=' | '.' | ']'));
+            (key_start && key_end).then_some(start..end)
+        })
+    }
+
+    fn toml_error(&self, err: &toml::de::Error) -> ValidationError {
+        self.error(err.span(), err.message())
+    }
+}
+
+fn validate_manifest(
+    package_path: &Path,
+    path: &Path,
+    schema: &Value,
+    errors: &mut Vec<ValidationError>,
+) {
+    let Some(text) = read(path, errors) else {
+        return;
+    };
+    let source = Source { path, text: &text };
+    let table = match toml::from_str::<toml::Value>(source.text) {
+        Ok(table) => table,
+        Err(err) => {
+            errors.push(source.toml_error(&err));
+            return;
+        }
+    };
+    check_keys(&table, schema, schema, "", &source, errors);
+
+    let manifest = match Manifest::parse(source.text) {
+        Ok(manifest) => manifest,
+        Err(ManifestParseError::TomlError(err)) => {
+            errors.push(source.toml_error(&err));
+            return;
+        }
+        Err(err) => {
+            errors.push(source.error(None, err.to_string()));
+            return;
+        }
+    };
+
+    let manifest_dir = path.parent().unwrap_or(package_path);
+    for (name, include) in manifest
+        .includes
+        .iter()
+        .sorted_by_key(|(name, _)| name.to_string())
+    {
+        let include_path = manifest_dir.join(include);
+        if include_path.is_file() {
+            validate_manifest(package_path, &include_path, schema, errors);
+        } else {
+            errors.push(source.error(
+                source.find_string(&path_to_unix_string_lossy(include)),
+                format!("The include `{name}` was not found at {include_path:?}"),
+            ));
+        }
+    }
+    for (name, dependency) in &manifest.dependencies {
+        let Some(dependency_path) = &dependency.path else {
+            if dependency.deployment.is_none()
+                && (dependency.id.is_none() || dependency.version.is_none())
+            {
+                errors.push(source.error(
+                    None,
+                    format!("The dependency `{name}` needs a `path`, or an `id` and a `version`"),
+                ));
+            }
+            continue;
+        };
+        if !manifest_dir
+            .join(dependency_path)
+            .join("ambient.toml")
+            .is_file()
+        {
+            errors.push(source.error(
+                source.find_string(&path_to_unix_string_lossy(dependency_path)),
+                format!(
+                    "The dependency `{name}` has no manifest at {:?}",
+                    manifest_dir.join(dependency_path).join("ambient.toml")
+                ),
+            ));
+        }
+    }
+    if let Some(background) = &manifest.loading_screen.background {
+        if !package_path.join("assets").join(background).is_file() {
+            errors.push(source.error(
+                source.find_string(background),
+                format!("The loading screen background {background:?} is not in the assets"),
+            ));
+        }
+    }
+}
+
+/// Reports the keys of `item` and its children which `schema` doesn't allow.
+fn check_keys(
+    item: &toml::Value,
+    schema: &Value,
+    root: &Value,
+    path: &str,
+    source: &Source,
+    errors: &mut Vec<ValidationError>,
+) {
+    let Some(schema) = select_schema(item, schema, root) else {
+        return;
+    };
+    if let Some(table) = item.as_table() {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let additional = schema.get("additionalProperties");
+        for (key, value) in table.iter() {
+            let key_path = if path.is_empty() {
+                key.to_string()
+            } else {
+                format!("{path}.{key}")
+            };
+            if let Some(property) = properties.and_then(|properties| properties.get(key)) {
+                check_keys(value, property, root, &key_path, source, errors);
+            } else if let Some(additional) = additional.filter(|additional| additional.is_object())
+            {
+                check_keys(value, additional, root, &key_path, source, errors);
+            } else if additional == Some(&Value::Bool(false)) {
+                let suggestion = properties
+                    .and_then(|properties| closest(key, properties.keys()))
+                    .map(|closest| format!("; did you mean `{closest}`?"))
+                    .unwrap_or_default();
+                errors.push(source.error(
+                    source.find_key(key),
+                    format!("Unknown key `{key_path}`{suggestion}"),
+                ));
+            }
+        }
+    } else if let Some(items) = schema.get("items") {
+        let path = format!("{path}[]");
+        for item in item.as_array().into_iter().flatten() {
+            check_keys(item, items, root, &path, source, errors);
+        }
+    }
+}
+
+/// Resolves the references of `schema`, and selects the variant of `oneOf` and `anyOf` schemas
+/// that `item` is meant to be: the variant with the same `type` key for tagged tables, or else
+/// the first variant of the same kind of value.
+fn select_schema<'a>(item: &toml::Value, schema: &'a Value, root: &'a Value) -> Option<&'a Value> {
+    let schema = match schema.get("$ref").and_then(Value::as_str) {
+        Some(reference) => root.pointer(reference.strip_prefix('#')?)?,
+        None => schema,
+    };
+    let Some(variants) = schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(Value::as_array)
+    else {
+        return Some(schema);
+    };
+    let tag = item.get("type").and_then(|tag| tag.as_str());
+    let is_table = item.is_table();
+    variants.iter().find_map(|variant| {
+        let variant = select_schema(item, variant, root)?;
+        let matches = match variant.pointer("/properties/type/const") {
+            Some(variant_tag) => tag.is_some() && variant_tag.as_str() == tag,
+            None => is_table == (variant.get("type") == Some(&Value::from("object"))),
+        };
+        matches.then_some(variant)
+    })
+}
+
+/// The key among `keys` that `key` is most likely a typo of.
+fn closest<'a>(key: &str, keys: impl Iterator<Item = &'a String>) -> Option<&'a str> {
+    keys.map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2.max(key.len() / 3))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn validate_pipelines(path: &Path, errors: &mut Vec<ValidationError>) {
+    let Some(text) = read(path, errors) else {
+        return;
+    };
+    let source = Source { path, text: &text };
+    let file = match toml::from_str::<PipelinesFile>(source.text) {
+        Ok(file) => file,
+        Err(err) => {
+            errors.push(source.toml_error(&err));
+            return;
+        }
+    };
+
+    // The sources and textures of the pipelines are relative to the pipeline file
+    let root = path.parent().unwrap_or(Path::new("."));
+    let files = WalkDir::new(root)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(root).ok()?;
+            Some(path_to_unix_string_lossy(relative))
+        })
+        .collect::<Vec<_>>();
+
+    for pipeline in &file.pipelines {
+        for pattern in &pipeline.sources {
+            match glob::Pattern::new(pattern) {
+                Ok(glob) => {
+                    if !files.iter().any(|file| glob.matches(file)) {
+                        errors.push(source.error(
+                            source.find_string(pattern),
+                            format!("The source pattern {pattern:?} does not match any file"),
+                        ));
+                    }
+                }
+                Err(err) => errors.push(source.error(
+                    source.find_string(pattern),
+                    format!("The source pattern {pattern:?} is invalid: {err}"),
+                )),
+            }
+        }
+
+        let materials = match &pipeline.processor {
+            PipelineProcessor::Materials(materials) => match materials.importer.as_ref() {
+                MaterialsImporter::Single(material) => vec![material],
+                MaterialsImporter::Quixel => vec![],
+            },
+            PipelineProcessor::Models(models) => models
+                .material_overrides
+                .iter()
+                .map(|material_override| &material_override.material)
+                .collect(),
+            _ => vec![],
+        };
+        for texture in materials.into_iter().flat_map(material_textures) {
+            if !texture.contains("://") && !root.join(texture).is_file() {
+                errors.push(source.error(
+                    source.find_string(texture),
+                    format!("The texture {texture:?} was not found"),
+                ));
+            }
+        }
+    }
+}
+
+fn material_textures(material: &PipelinePbrMaterial) -> impl Iterator<Item = &str> {
+    [
+        &material.base_color,
+        &material.opacity,
+        &material.normalmap,
+        &material.metallic_roughness,
+        &material.specular,
+    ]
+    .into_iter()
+    .filter_map(|texture| texture.as_deref())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Writes the `files` of a package to a new temporary directory, and returns its path.
+    fn package(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("ambient_validate_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        for (file, contents) in files {
+            let file = path.join(file);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, contents).unwrap();
+        }
+        path
+    }
+
+    const MANIFEST: &str = r#"[package]
+id = "ytdci6e6yiglca2d6awwpi2lt5h6sqvg"
+name = "Fixture"
+version = "0.0.1"
+content = { type = "Playable", example = true }
+
+[components]
+health = { type = "F32", attributes = ["Networked"], replication = { interval = 2 } }
+path = { type = { type = "Vec", element_type = "Vec3" } }
+
+[includes]
+extra = "extra.toml"
+
+[dependencies]
+other = { path = "deps/other" }
+
+[loading_screen]
+background = "background.png"
+tips = ["Jump with space"]
+"#;
+
+    /// A manifest with only a package, for includes and dependencies.
+    const PACKAGE: &str = r#"[package]
+name = "Other"
+version = "0.0.1"
+content = { type = "Asset", code = true }
+"#;
+
+    const PIPELINE: &str = r#"[[pipelines]]
+type = "Models"
+sources = ["*.glb"]
+
+[[pipelines]]
+type = "Materials"
+
+[pipelines.importer]
+type = "Single"
+name = "wood"
+base_color = "textures/wood.png"
+"#;
+
+    #[test]
+    fn valid_package() {
+        let path = package(
+            "valid",
+            &[
+                ("ambient.toml", MANIFEST),
+                ("extra.toml", PACKAGE),
+                ("deps/other/ambient.toml", PACKAGE),
+                ("assets/background.png", ""),
+                ("assets/pipeline.toml", PIPELINE),
+                ("assets/chair.glb", ""),
+                ("assets/textures/wood.png", ""),
+            ],
+        );
+
+        let errors = validate_package(&path);
+        assert!(errors.is_empty(), "{}", errors.iter().join("\n"));
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn invalid_package() {
+        let manifest = MANIFEST
+            .replace(
+                "name = \"Fixture\"",
+                "nmae = \"Fixture\"\nname = \"Fixture\"",
+            )
+            .replace("example = true", "exampel = true")
+            .replace("attributes = [", "atributes = [");
+        let path = package(
+            "invalid",
+            &[
+                ("ambient.toml", &manifest),
+                ("extra.toml", "[components\n"),
+                ("assets/pipeline.toml", PIPELINE),
+            ],
+        );
+
+        let errors = validate_package(&path)
+            .into_iter()
+            .map(|error| {
+                let file = error.path.strip_prefix(&path).unwrap().to_owned();
+                (
+                    path_to_unix_string_lossy(&file),
+                    error.position,
+                    error.message,
+                )
+            })
+            .collect::<Vec<_>>();
+        let error = |file: &str, position: Option<(usize, usize)>, message: &str| {
+            (file.to_string(), position, message.to_string())
+        };
+        let expected = [
+            error(
+                "ambient.toml",
+                Some((3, 1)),
+                "Unknown key `package.nmae`; did you mean `name`?",
+            ),
+            error(
+                "ambient.toml",
+                Some((6, 32)),
+                "Unknown key `package.content.exampel`; did you mean `example`?",
+            ),
+            error(
+                "ambient.toml",
+                Some((9, 26)),
+                "Unknown key `components.health.atributes`; did you mean `attributes`?",
+            ),
+        ];
+        assert_eq!(errors[..3], expected);
+
+        let messages = errors[3..]
+            .iter()
+            .map(|(file, _, message)| format!("{file}: {message}"))
+            .collect::<Vec<_>>();
+        let expected = [
+            "extra.toml: ",
+            "ambient.toml: The dependency `other` has no manifest",
+            "ambient.toml: The loading screen background \"background.png\" is not in the assets",
+            "assets/pipeline.toml: The source pattern \"*.glb\" does not match any file",
+            "assets/pipeline.toml: The texture \"textures/wood.png\" was not found",
+        ];
+        assert_eq!(messages.len(), expected.len(), "{messages:#?}");
+        for (message, expected) in messages.iter().zip(expected) {
+            assert!(message.starts_with(expected), "{messages:#?}");
+        }
+        // Syntax errors are located too
+        assert_eq!(errors[3].1.map(|(line, _)| line), Some(1));
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn schema_is_valid_json() {
+        let schema = manifest_schema();
+        assert_eq!(schema["type"], "object");
+        assert!(schema["properties"]["package"]["properties"]["name"].is_object());
+    }
+}
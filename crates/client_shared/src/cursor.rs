@@ -0,0 +1,41 @@
+use std::str::FromStr;
+
+use ambient_core::{
+    transform::translation,
+    window::{cursor_image, cursor_position},
+};
+use ambient_element::{
+    element_component, use_frame, use_state, Element, ElementComponentExt, Hooks,
+};
+use ambient_native_std::asset_url::AbsAssetUrl;
+use ambient_ui_native::{height, width, ImageFromUrl};
+
+/// Draws the `cursor_image` at the position of the cursor, in front of the rest of the UI.
+#[element_component]
+pub fn CustomCursor(hooks: &mut Hooks) -> Element {
+    let (cursor, set_cursor) = use_state(hooks, None);
+    use_frame(hooks, {
+        let cursor = cursor.clone();
+        move |world| {
+            let new_cursor = world
+                .get_cloned(world.resource_entity(), cursor_image())
+                .ok()
+                .map(|image| (image, *world.resource(cursor_position())));
+            if new_cursor != cursor {
+                set_cursor(new_cursor);
+            }
+        }
+    });
+
+    let Some((image, position)) = cursor else {
+        return Element::new();
+    };
+    let Ok(url) = AbsAssetUrl::from_str(&image.url) else {
+        return Element::new();
+    };
+    ImageFromUrl { url }
+        .el()
+        .with(width(), image.size.x)
+        .with(height(), image.size.y)
+        .with(translation(), (position - image.hotspot).extend(-0.99))
+}
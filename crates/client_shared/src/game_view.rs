@@ -14,18 +14,30 @@ use ambient_layout::Docking;
 use ambient_network::client::{ClientState, GameClientRenderTarget, GameClientWorld};
 use ambient_shared_types::CursorIcon;
 use ambient_ui_native::{
-    cb, docking, padding, width, Borders, Button, Dock, MeasureSize, ScrollArea, ScrollAreaSizing,
-    UIExt, STREET,
+    cb, docking, padding, width, Borders, Button, Cb, Dock, MeasureSize, ScrollArea,
+    ScrollAreaSizing, UIExt, STREET,
 };
 use glam::{uvec2, vec4, Vec2};
 
+use crate::loading_screen::LoadingScreen;
+
 const ECS_DEBUGGER: bool = true;
 
+/// Shows the world of the [ClientState], after a loading screen.
+///
+/// If `on_preloaded` is set, the world is being loaded in the background: nothing is shown, and
+/// `on_preloaded` is called once the world has loaded. The world is shown right away once
+/// `on_preloaded` is unset.
 #[element_component]
-pub fn GameView(hooks: &mut Hooks, show_debug: bool) -> Element {
+pub fn GameView(
+    hooks: &mut Hooks,
+    show_debug: bool,
+    on_preloaded: Option<Cb<dyn Fn() + Sync + Send>>,
+) -> Element {
     let (client_state, _) = consume_context::<ClientState>(hooks).unwrap();
     let (render_target, _) = consume_context::<GameClientRenderTarget>(hooks).unwrap();
 
+    let (loaded, set_loaded) = use_state(hooks, false);
     let (show_ecs, set_show_ecs) = use_state(hooks, true);
     let (ecs_size, set_ecs_size) = use_state(hooks, Vec2::ZERO);
     let (debugger_size, set_debugger_size) = use_state(hooks, Vec2::ZERO);
@@ -51,7 +63,12 @@ pub fn GameView(hooks: &mut Hooks, show_debug: bool) -> Element {
         let render_target = render_target.clone();
         let set_w = set_w.clone();
         let set_w_memory = set_w_memory.clone();
+        let preloading = on_preloaded.is_some();
         move |world| {
+            // The window is only passed on to the world once it is shown
+            if preloading {
+                return;
+            }
             let mut state = state.game_state.lock();
 
             let scale_factor = *world.resource(window_scale_factor());
@@ -98,6 +115,27 @@ pub fn GameView(hooks: &mut Hooks, show_debug: bool) -> Element {
         }
     });
 
+    if let Some(on_preloaded) = on_preloaded {
+        return if loaded {
+            Element::new()
+        } else {
+            LoadingScreen::el(
+                cb(move || {
+                    set_loaded(true);
+                    on_preloaded();
+                }),
+                true,
+            )
+        };
+    }
+
+    // The game world is not shown until it has loaded, which also keeps its render target small
+    let world_view = if loaded {
+        GameClientWorld.el()
+    } else {
+        LoadingScreen::el(cb(move || set_loaded(true)), false)
+    };
+
     Dock::el([
         if ECS_DEBUGGER && show_debug {
             MeasureSize::el(
@@ -164,7 +202,7 @@ pub fn GameView(hooks: &mut Hooks, show_debug: bool) -> Element {
             Element::new()
         },
         if show_debug {
-            Dock::el([GameClientWorld.el()])
+            Dock::el([world_view])
                 .with_background(vec4(0.2, 0.2, 0.2, 1.))
                 .with(
                     padding(),
@@ -177,7 +215,7 @@ pub fn GameView(hooks: &mut Hooks, show_debug: bool) -> Element {
                     .into(),
                 )
         } else {
-            GameClientWorld.el()
+            world_view
         },
     ])
 }
@@ -0,0 +1,152 @@
+//! Recording of the raw input of the player to a file, and its replay, so that automated tests
+//! can drive the gameplay of a package.
+//!
+//! A recording is a JSON object per line, with the input of a frame and the time (seconds) since
+//! the first frame of the recording. Frames whose input did not change are not recorded.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+
+use ambient_ecs::{DynSystem, FnSystem};
+use ambient_input::{player_raw_input, PlayerRawInput};
+use ambient_sys::time::Instant;
+use anyhow::Context;
+use glam::Vec2;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedInput {
+    time: f32,
+    input: PlayerRawInput,
+}
+
+/// Writes the input of the player to a recording.
+#[derive(Debug)]
+pub struct InputRecorder {
+    file: File,
+    start: Option<Instant>,
+    last_input: String,
+}
+impl InputRecorder {
+    pub fn create(path: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            file: File::create(path)
+                .with_context(|| format!("Failed to create {}", path.display()))?,
+            start: None,
+            last_input: String::new(),
+        })
+    }
+
+    fn record(&mut self, input: &PlayerRawInput) -> anyhow::Result<()> {
+        let time = self.start.get_or_insert_with(Instant::now).elapsed();
+        let input_json = serde_json::to_string(input)?;
+        // The relative inputs are recorded on every frame they happen, even if they repeat
+        let moved = input.mouse_delta != Vec2::ZERO || input.mouse_wheel != 0.;
+        if !moved && input_json == self.last_input {
+            return Ok(());
+        }
+        let line = serde_json::to_string(&RecordedInput {
+            time: time.as_secs_f32(),
+            input: input.clone(),
+        })?;
+        // Each line is written at once, so that the recording is usable even if the client is
+        // killed
+        self.file.write_all(format!("{line}\n").as_bytes())?;
+        self.last_input = input_json;
+        Ok(())
+    }
+}
+
+/// Replays a recording in place of the input of the player.
+#[derive(Debug)]
+pub struct InputReplay {
+    frames: Vec<RecordedInput>,
+    next: usize,
+    start: Option<Instant>,
+}
+impl InputReplay {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut frames = Vec::new();
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let frame: RecordedInput = serde_json::from_str(&line)
+                .with_context(|| format!("Invalid input at {}:{}", path.display(), i + 1))?;
+            frames.push(frame);
+        }
+        Ok(Self {
+            frames,
+            next: 0,
+            start: None,
+        })
+    }
+
+    /// The time from the first to the last frame of the recording.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f32(self.frames.last().map_or(0., |frame| frame.time.max(0.)))
+    }
+
+    /// Applies the frames recorded up to the current time of the replay to `input`.
+    ///
+    /// When the replay runs at a lower frame rate than the recording, the relative inputs of the
+    /// frames it skips are added up, so that the mouse ends up moving by the same amount.
+    fn apply(&mut self, input: &mut PlayerRawInput) {
+        let time = self
+            .start
+            .get_or_insert_with(Instant::now)
+            .elapsed()
+            .as_secs_f32();
+        let (mut mouse_delta, mut mouse_wheel, mut mouse_motion) = (Vec2::ZERO, 0., Vec::new());
+        let mut latest = None;
+        while let Some(frame) = self
+            .frames
+            .get(self.next)
+            .filter(|frame| frame.time <= time)
+        {
+            mouse_delta += frame.input.mouse_delta;
+            mouse_wheel += frame.input.mouse_wheel;
+            mouse_motion.extend_from_slice(&frame.input.mouse_motion);
+            latest = Some(frame);
+            self.next += 1;
+        }
+        if let Some(frame) = latest {
+            *input = PlayerRawInput {
+                mouse_delta,
+                mouse_wheel,
+                mouse_motion,
+                ..frame.input.clone()
+            };
+        }
+    }
+}
+
+/// Records the input of each frame. Must run before the input is reset at the end of the frame.
+pub fn record_system(recorder: Arc<Mutex<InputRecorder>>) -> DynSystem {
+    let mut failed = false;
+    Box::new(FnSystem::new(move |world, _| {
+        if failed {
+            return;
+        }
+        if let Err(err) = recorder.lock().record(world.resource(player_raw_input())) {
+            tracing::error!("Failed to record input, stopping the recording: {err:#}");
+            failed = true;
+        }
+    }))
+}
+
+/// Replaces the input of the player by the replayed input. Must run before the input is used.
+pub fn replay_system(replay: Arc<Mutex<InputReplay>>) -> DynSystem {
+    Box::new(FnSystem::new(move |world, _| {
+        replay.lock().apply(world.resource_mut(player_raw_input()));
+    }))
+}
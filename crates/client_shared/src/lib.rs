@@ -1,3 +1,7 @@
+pub mod cursor;
 pub mod game_view;
+pub mod input_recording;
+pub mod loading_screen;
 pub mod player;
+pub mod quality_tier;
 pub mod util;
@@ -0,0 +1,195 @@
+//! The loading screen shown after connecting to a server, until the world has been received and
+//! its assets have been downloaded.
+//!
+//! Packages can customize it with the `[loading_screen]` section of their manifest, which sets
+//! the [loading_screen_background] and [loading_screen_tips] of their package entity.
+use std::{str::FromStr, time::Duration};
+
+use ambient_core::asset_cache;
+use ambient_ecs::{
+    generated::{
+        messages::AssetLoadProgress,
+        package::components::{is_package, loading_screen_background, loading_screen_tips},
+    },
+    query, world_events, FnSystem, SystemGroup, WorldEventsExt,
+};
+use ambient_element::{
+    consume_context, element_component, use_frame, use_ref_with, use_state, Element,
+    ElementComponentExt, Hooks,
+};
+use ambient_layout::Docking;
+use ambient_native_std::{
+    asset_cache::SyncAssetKeyExt, asset_url::AbsAssetUrl, download_asset::DownloadProgressKey, Cb,
+};
+use ambient_network::client::ClientState;
+use ambient_sys::time::Instant;
+use ambient_ui_native::{
+    background_color, docking, height,
+    style_constants::{app_background_color, cutout_color, primary_color},
+    width, Dock, FlowColumn, ImageFromUrl, Rectangle, Text, UIExt, STREET,
+};
+
+/// How long no download must be pending before the world is considered loaded, as finished
+/// downloads often start new ones (e.g. a model and then its textures).
+const SETTLE_TIME: Duration = Duration::from_millis(500);
+/// How long each tip is shown for.
+const TIP_DURATION: Duration = Duration::from_secs(6);
+const PROGRESS_BAR_WIDTH: f32 = 400.;
+
+/// Sends an [AssetLoadProgress] message to the client modules whenever the download counts of the
+/// [DownloadProgressKey] change.
+pub fn systems() -> SystemGroup {
+    let mut last_progress = (0, 0);
+    SystemGroup::new(
+        "client_shared/loading_screen",
+        vec![Box::new(FnSystem::new(move |world, _| {
+            let progress = DownloadProgressKey.get(world.resource(asset_cache()));
+            let current = (progress.started(), progress.finished());
+            if current == last_progress {
+                return;
+            }
+            last_progress = current;
+
+            world
+                .resource_mut(world_events())
+                .add_message(AssetLoadProgress {
+                    started: current.0 as u32,
+                    finished: current.1 as u32,
+                });
+        }))],
+    )
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct LoadingState {
+    /// The downloads started since the loading screen was shown
+    started: usize,
+    /// The downloads finished since the loading screen was shown
+    finished: usize,
+    background: Option<String>,
+    tip: Option<String>,
+}
+
+struct LoadingTiming {
+    shown_at: Instant,
+    started_before: usize,
+    finished_before: usize,
+    /// When the last pending download finished
+    idle_since: Option<Instant>,
+}
+
+/// Shows the progress of the asset downloads until the world of the [ClientState] has been
+/// received and no download has been pending for a short while, and then calls `on_loaded`.
+///
+/// If `hidden` is set, the progress is tracked without showing anything, which is used while the
+/// world is preloaded in the background.
+#[element_component]
+pub fn LoadingScreen(
+    hooks: &mut Hooks,
+    on_loaded: Cb<dyn Fn() + Sync + Send>,
+    hidden: bool,
+) -> Element {
+    let (client_state, _) = consume_context::<ClientState>(hooks).unwrap();
+    let progress = DownloadProgressKey.get(hooks.world.resource(asset_cache()));
+    let (state, set_state) = use_state(hooks, LoadingState::default());
+    let timing = use_ref_with(hooks, {
+        let progress = progress.clone();
+        move |_| LoadingTiming {
+            shown_at: Instant::now(),
+            started_before: progress.started(),
+            finished_before: progress.finished(),
+            idle_since: None,
+        }
+    });
+
+    use_frame(hooks, {
+        let state = state.clone();
+        move |_| {
+            let now = Instant::now();
+            let mut timing = timing.lock();
+
+            let (has_packages, background, tips) = {
+                let game_state = client_state.game_state.lock();
+                let world = &game_state.world;
+                let packages = query(is_package())
+                    .iter(world, None)
+                    .map(|(id, _)| id)
+                    .collect::<Vec<_>>();
+                let background = packages
+                    .iter()
+                    .find_map(|id| world.get_cloned(*id, loading_screen_background()).ok());
+                let tips = packages
+                    .iter()
+                    .find_map(|id| world.get_cloned(*id, loading_screen_tips()).ok())
+                    .unwrap_or_default();
+                (!packages.is_empty(), background, tips)
+            };
+
+            if has_packages && progress.pending() == 0 {
+                let idle_since = *timing.idle_since.get_or_insert(now);
+                if now.duration_since(idle_since) >= SETTLE_TIME {
+                    on_loaded();
+                    return;
+                }
+            } else {
+                timing.idle_since = None;
+            }
+
+            let tip_index = (now.duration_since(timing.shown_at).as_secs_f32()
+                / TIP_DURATION.as_secs_f32()) as usize;
+            let new_state = LoadingState {
+                started: progress.started().saturating_sub(timing.started_before),
+                finished: progress.finished().saturating_sub(timing.finished_before),
+                background,
+                tip: (!tips.is_empty()).then(|| tips[tip_index % tips.len()].clone()),
+            };
+            if new_state != state {
+                set_state(new_state);
+            }
+        }
+    });
+
+    if hidden {
+        return Element::new();
+    }
+
+    let LoadingState {
+        started,
+        finished,
+        background,
+        tip,
+    } = state;
+    let fraction = if started == 0 {
+        0.
+    } else {
+        (finished as f32 / started as f32).min(1.)
+    };
+
+    let status = FlowColumn::el([
+        Text::el(if started == 0 {
+            "Loading world".to_string()
+        } else {
+            format!("Loading assets ({finished}/{started})")
+        }),
+        Rectangle
+            .el()
+            .with(width(), PROGRESS_BAR_WIDTH)
+            .with(height(), 4.)
+            .with(background_color(), cutout_color().into())
+            .children(vec![Rectangle
+                .el()
+                .with(width(), PROGRESS_BAR_WIDTH * fraction)
+                .with(height(), 4.)
+                .with(background_color(), primary_color().into())]),
+        tip.map(Text::el).unwrap_or_default(),
+    ])
+    .with(docking(), Docking::Bottom)
+    .with_padding_even(STREET);
+
+    let background = background
+        .and_then(|url| AbsAssetUrl::from_str(&url).ok())
+        .map(|url| ImageFromUrl { url }.el())
+        .unwrap_or_default();
+
+    Dock::el([status, background]).with_background(app_background_color().into())
+}
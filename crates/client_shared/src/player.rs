@@ -2,10 +2,14 @@ use std::str::FromStr;
 
 use ambient_core::window::cursor_position;
 use ambient_ecs::{generated::messages, query_mut, SystemGroup, World};
-use ambient_element::{element_component, use_runtime_message, use_state, Element, Hooks};
-use ambient_input::{player_prev_raw_input, player_raw_input, PlayerRawInput};
+use ambient_element::{
+    element_component, use_frame, use_runtime_message, use_state, Element, Hooks,
+};
+use ambient_input::{
+    gamepads, player_prev_raw_input, player_raw_input, MouseMotion, PlayerRawInput, Touch,
+};
 use ambient_network::client::client_state;
-use ambient_shared_types::VirtualKeyCode;
+use ambient_shared_types::{TouchPhase, VirtualKeyCode};
 use glam::Vec2;
 
 pub fn systems_final() -> SystemGroup {
@@ -16,6 +20,11 @@ pub fn systems_final() -> SystemGroup {
                 |q, world, qs, _| {
                     for (_, (prev, input), ()) in q.iter(world, qs) {
                         input.mouse_delta = glam::Vec2::ZERO;
+                        input.mouse_motion.clear();
+                        // Ended touches are kept for a frame, so that guests can see them end
+                        input.touches.retain(|touch| {
+                            !matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled)
+                        });
                         *prev = input.clone();
                     }
                 },
@@ -65,6 +74,10 @@ pub fn PlayerRawInputHandler(hooks: &mut Hooks) -> Element {
         process_input(world, has_focus, |input, mouse_pos| {
             input.mouse_position = mouse_pos;
             input.mouse_delta += msg.delta;
+            input.mouse_motion.push(MouseMotion {
+                delta: msg.delta,
+                time: msg.time,
+            });
         });
     });
 
@@ -78,6 +91,36 @@ pub fn PlayerRawInputHandler(hooks: &mut Hooks) -> Element {
         });
     });
 
+    use_runtime_message::<messages::WindowTouch>(hooks, move |world, event| {
+        let Ok(phase) = TouchPhase::from_str(&event.phase) else {
+            return;
+        };
+        process_input(world, has_focus, |input, _| {
+            let touch = Touch {
+                id: event.touch_id,
+                phase,
+                position: event.position,
+                pressure: event.pressure,
+            };
+            match input.touches.iter_mut().find(|t| t.id == touch.id) {
+                Some(existing) => *existing = touch,
+                None => input.touches.push(touch),
+            }
+        });
+    });
+
+    // The gamepads are polled by the app, rather than sent as events
+    use_frame(hooks, move |world| {
+        let Some(pads) = world.resource_opt(gamepads()) else {
+            return;
+        };
+        process_input(world, has_focus, |input, _| {
+            if input.gamepads != *pads {
+                input.gamepads = pads.clone();
+            }
+        });
+    });
+
     fn process_input(
         ui_world: &World,
         has_focus: bool,
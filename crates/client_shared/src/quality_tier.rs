@@ -0,0 +1,78 @@
+use std::{str::FromStr, sync::Arc};
+
+use ambient_core::{asset_cache, gpu, quality_tier};
+use ambient_ecs::{
+    generated::package::components::{asset_url, quality_tiers},
+    query, SystemGroup, World,
+};
+use ambient_native_std::{
+    asset_cache::SyncAssetKeyExt,
+    asset_url::{AbsAssetUrl, DownloadUrlRewritesKey},
+};
+use ambient_package::quality_tier::{self as tiers, GpuTier};
+
+/// Returns the quality tier that this client should target, based on its platform and GPU.
+pub fn target_tier(world: &World) -> String {
+    let platform = if cfg!(target_os = "unknown") {
+        "web"
+    } else {
+        "desktop"
+    };
+    let gpu_tier = match world
+        .resource_opt(gpu())
+        .map(|gpu| gpu.adapter.get_info().device_type)
+    {
+        Some(wgpu::DeviceType::DiscreteGpu) => GpuTier::High,
+        _ => GpuTier::Low,
+    };
+
+    tiers::target_tier(platform, gpu_tier)
+}
+
+/// Redirects the asset downloads of each package that has a quality tier matching the
+/// [quality_tier] resource to the assets of that tier.
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "client_shared/quality_tier",
+        vec![query((asset_url(), quality_tiers()))
+            .spawned()
+            .to_system(|q, world, qs, _| {
+                let Some(target) = world.resource_opt(quality_tier()) else {
+                    return;
+                };
+                let assets = world.resource(asset_cache());
+
+                let mut new_rewrites = vec![];
+                for (_, (url, available)) in q.iter(world, qs) {
+                    let Some(tier) = tiers::resolve_tier(target, available) else {
+                        continue;
+                    };
+
+                    let rewrite = |path: &str| {
+                        anyhow::Ok(
+                            AbsAssetUrl::from_str(&format!("{url}/{path}/"))?
+                                .to_download_url(assets)?
+                                .to_string(),
+                        )
+                    };
+                    match rewrite("assets")
+                        .and_then(|from| Ok((from, rewrite(&tiers::assets_path(tier))?)))
+                    {
+                        Ok(rewrite) => {
+                            tracing::debug!(?rewrite, "Using quality tier {tier:?}");
+                            new_rewrites.push(rewrite);
+                        }
+                        Err(err) => {
+                            tracing::warn!("Failed to resolve quality tier {tier:?}: {err:?}")
+                        }
+                    }
+                }
+
+                if !new_rewrites.is_empty() {
+                    let mut rewrites = (*DownloadUrlRewritesKey.get(assets)).clone();
+                    rewrites.extend(new_rewrites);
+                    DownloadUrlRewritesKey.insert(assets, Arc::new(rewrites));
+                }
+            })],
+    )
+}
@@ -47,11 +47,13 @@ pub fn bounding_systems() -> SystemGroup {
                 (local_bounding_aabb().changed(), local_to_world().changed()),
             )
             .to_system(|q, world, qs, _| {
-                for (_, (world_aabb, world_sphere), (aabb, local_to_world)) in q.iter(world, qs) {
-                    let world_box = aabb.transform(local_to_world);
-                    *world_aabb = world_box.to_aabb();
-                    *world_sphere = world_box.to_sphere();
-                }
+                q.par_for_each(
+                    world,
+                    qs,
+                    |_, (world_aabb, world_sphere), (aabb, local_to_world)| {
+                        (*world_aabb, *world_sphere) = aabb.transform_bounds(local_to_world);
+                    },
+                );
             }),
             // Systems for syncing the structured components to the piecewise components, so
             // that the guest can access them
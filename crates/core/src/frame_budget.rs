@@ -0,0 +1,79 @@
+//! The time spent by each subsystem of a server during its ticks, which the server reports
+//! periodically, so that overloaded servers can be diagnosed from their logs.
+//!
+//! Subsystems [measure] their work in the worlds that have a [frame_budget]; in other worlds,
+//! such as the world of the client, nothing is recorded.
+use std::{collections::BTreeMap, time::Duration};
+
+use ambient_ecs::{components, Resource, World};
+use ambient_sys::time::Instant;
+
+use crate::memory;
+
+components!("app", {
+    /// The time spent by each subsystem of the server since the last report
+    @[Resource]
+    frame_budget: FrameBudget,
+});
+
+/// The subsystem of the physics simulation.
+pub const PHYSICS: &str = "physics";
+/// The subsystem that sends the changes of the world to the clients.
+pub const REPLICATION: &str = "replication";
+
+/// The time spent by each subsystem.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameBudget {
+    /// The time spent by each subsystem, such as [PHYSICS]
+    pub subsystems: BTreeMap<&'static str, Duration>,
+    /// The time spent running each WASM module, by module name
+    pub modules: BTreeMap<String, Duration>,
+}
+impl FrameBudget {
+    /// Adds the times of `other` to this budget.
+    pub fn merge(&mut self, other: FrameBudget) {
+        for (subsystem, duration) in other.subsystems {
+            *self.subsystems.entry(subsystem).or_default() += duration;
+        }
+        for (module, duration) in other.modules {
+            *self.modules.entry(module).or_default() += duration;
+        }
+    }
+
+    /// The total time spent running WASM modules.
+    pub fn wasm(&self) -> Duration {
+        self.modules.values().sum()
+    }
+}
+
+/// Runs `f`, and adds the time it took to `subsystem`, if the world keeps a [frame_budget].
+///
+/// The memory that `f` allocates is also attributed to `subsystem`; see [crate::memory].
+pub fn measure<R>(
+    world: &mut World,
+    subsystem: &'static str,
+    f: impl FnOnce(&mut World) -> R,
+) -> R {
+    let start = Instant::now();
+    let result = {
+        let _memory = memory::scope(subsystem);
+        f(world)
+    };
+    if let Some(budget) = world.resource_mut_opt(frame_budget()) {
+        *budget.subsystems.entry(subsystem).or_default() += start.elapsed();
+    }
+    result
+}
+
+/// Adds `duration` to the time spent running the WASM module called `module`, if the world keeps
+/// a [frame_budget].
+pub fn record_module(world: &mut World, module: &str, duration: Duration) {
+    if let Some(budget) = world.resource_mut_opt(frame_budget()) {
+        match budget.modules.get_mut(module) {
+            Some(total) => *total += duration,
+            None => {
+                budget.modules.insert(module.to_string(), duration);
+            }
+        }
+    }
+}
@@ -20,21 +20,28 @@ pub mod async_ecs;
 pub mod bounding;
 pub mod camera;
 
+pub mod frame_budget;
 pub mod hierarchy;
+pub mod memory;
 pub mod player;
+pub mod stable_ids;
+pub mod tag_index;
 pub mod timing;
 pub mod transform;
 pub mod window;
 
 pub use ambient_ecs::generated::{
     app::components::{
-        delta_time, description, epoch_time, game_time, main_package_name, main_scene, map_seed,
-        name, ref_count, selectable, snap_to_ground, tags, ui_scene,
+        delta_time, description, epoch_time, game_time, locale, main_package_name, main_scene,
+        map_seed, name, quality_tier, ref_count, selectable, snap_to_ground, stable_id, tags,
+        ui_scene,
     },
     ecs::components::remove_at_game_time,
 };
 
-/// The time between fixed updates of the server state.
+/// The default time between fixed updates of the server state, used if no tick rate is configured.
+///
+/// The actual tick time of a server is available as `delta_time` in its world.
 pub const FIXED_SERVER_TICK_TIME: Duration = Duration::from_micros((1_000_000. / 60.) as u64);
 
 components!("app", {
@@ -79,6 +86,9 @@ pub fn init_all_components() {
     async_ecs::init_components();
     ambient_gpu_ecs::init_components();
     camera::init_components();
+    frame_budget::init_components();
+    stable_ids::init_components();
+    tag_index::init_components();
     timing::init_components();
     transform::init_components();
     transform::init_gpu_components();
@@ -0,0 +1,202 @@
+//! Tracks the memory allocated by each subsystem, so that the memory growth of long-running servers
+//! can be attributed.
+//!
+//! The tracking is done by [TrackingAllocator], which must be installed as the global allocator;
+//! the `ambient` binary does so when it is built with the `memory-profiling` feature. Code that
+//! runs within a [scope] has its allocations attributed to the subsystem of the scope, and the
+//! memory stays attributed to that subsystem until it is freed, wherever that happens. Everything
+//! else is attributed to [OTHER].
+//!
+//! Without the allocator, [scope] does nothing and [usage] returns nothing.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+/// The subsystem of the allocations that are not made within a [scope].
+pub const OTHER: &str = "other";
+
+/// The maximum number of subsystems; the allocations of any further subsystem are attributed to
+/// [OTHER].
+pub const MAX_SUBSYSTEMS: usize = 256;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU64 = AtomicU64::new(0);
+static BYTES: [AtomicU64; MAX_SUBSYSTEMS] = [ZERO; MAX_SUBSYSTEMS];
+static ALLOCATIONS: [AtomicU64; MAX_SUBSYSTEMS] = [ZERO; MAX_SUBSYSTEMS];
+static TRACKING: AtomicBool = AtomicBool::new(false);
+/// The names of the subsystems, by slot. The first slot is [OTHER].
+static SUBSYSTEMS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+thread_local! {
+    static CURRENT_SLOT: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A global allocator that attributes each allocation to the subsystem of the current [scope].
+///
+/// Each allocation is prefixed with a small header that remembers its subsystem, so this should
+/// only be used when profiling.
+pub struct TrackingAllocator;
+
+const SLOT_SIZE: usize = std::mem::size_of::<usize>();
+
+/// Returns the layout of an allocation of `layout` with its header, and the offset of the data.
+fn with_header(layout: Layout) -> Option<(Layout, usize)> {
+    // The offset is a multiple of the alignment, so the data stays aligned
+    let offset = layout.align().max(SLOT_SIZE);
+    let size = layout.size().checked_add(offset)?;
+    Some((Layout::from_size_align(size, layout.align()).ok()?, offset))
+}
+
+fn current_slot() -> usize {
+    // The slot has no destructor, so it is available while the thread is torn down
+    CURRENT_SLOT.try_with(Cell::get).unwrap_or(0)
+}
+
+impl TrackingAllocator {
+    unsafe fn allocate(&self, layout: Layout, alloc: impl FnOnce(Layout) -> *mut u8) -> *mut u8 {
+        let Some((full_layout, offset)) = with_header(layout) else {
+            return std::ptr::null_mut();
+        };
+        let base = alloc(full_layout);
+        if base.is_null() {
+            return base;
+        }
+        if !TRACKING.load(Ordering::Relaxed) {
+            TRACKING.store(true, Ordering::Relaxed);
+        }
+
+        let slot = current_slot();
+        base.add(offset - SLOT_SIZE)
+            .cast::<usize>()
+            .write_unaligned(slot);
+        BYTES[slot].fetch_add(layout.size() as u64, Ordering::Relaxed);
+        ALLOCATIONS[slot].fetch_add(1, Ordering::Relaxed);
+        base.add(offset)
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.allocate(layout, |layout| System.alloc(layout))
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.allocate(layout, |layout| System.alloc_zeroed(layout))
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let (full_layout, offset) = with_header(layout).expect("Layout was allocated");
+        let base = ptr.sub(offset);
+        let slot = base
+            .add(offset - SLOT_SIZE)
+            .cast::<usize>()
+            .read_unaligned();
+        BYTES[slot].fetch_sub(layout.size() as u64, Ordering::Relaxed);
+        ALLOCATIONS[slot].fetch_sub(1, Ordering::Relaxed);
+        System.dealloc(base, full_layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let (full_layout, offset) = with_header(layout).expect("Layout was allocated");
+        let Some(full_size) = new_size.checked_add(offset) else {
+            return std::ptr::null_mut();
+        };
+        // The header is moved along with the data, so the memory stays attributed to the
+        // subsystem that allocated it
+        let base = System.realloc(ptr.sub(offset), full_layout, full_size);
+        if base.is_null() {
+            return base;
+        }
+        let slot = base
+            .add(offset - SLOT_SIZE)
+            .cast::<usize>()
+            .read_unaligned();
+        BYTES[slot].fetch_add(new_size as u64, Ordering::Relaxed);
+        BYTES[slot].fetch_sub(layout.size() as u64, Ordering::Relaxed);
+        base.add(offset)
+    }
+}
+
+/// Returns whether the [TrackingAllocator] is the global allocator.
+pub fn is_tracking() -> bool {
+    TRACKING.load(Ordering::Relaxed)
+}
+
+/// Attributes the allocations of the current thread to `subsystem`, until the returned guard is
+/// dropped.
+///
+/// Scopes can be nested; the innermost scope is used.
+pub fn scope(subsystem: &str) -> MemoryScope {
+    let slot = if is_tracking() { slot(subsystem) } else { 0 };
+    MemoryScope {
+        previous: CURRENT_SLOT.with(|current| current.replace(slot)),
+        _not_send: PhantomData,
+    }
+}
+
+fn slot(subsystem: &str) -> usize {
+    let mut subsystems = SUBSYSTEMS.lock().unwrap_or_else(|err| err.into_inner());
+    if subsystems.is_empty() {
+        subsystems.push(OTHER.to_string());
+    }
+    match subsystems.iter().position(|name| name == subsystem) {
+        Some(slot) => slot,
+        None if subsystems.len() < MAX_SUBSYSTEMS => {
+            subsystems.push(subsystem.to_string());
+            subsystems.len() - 1
+        }
+        None => 0,
+    }
+}
+
+/// Restores the previous subsystem of the thread when dropped.
+pub struct MemoryScope {
+    previous: usize,
+    // The subsystem is set for the current thread
+    _not_send: PhantomData<*const ()>,
+}
+impl Drop for MemoryScope {
+    fn drop(&mut self) {
+        CURRENT_SLOT.with(|current| current.set(self.previous));
+    }
+}
+
+/// The memory used by a subsystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubsystemMemory {
+    pub subsystem: String,
+    /// The number of bytes that are currently allocated
+    pub bytes: u64,
+    /// The number of allocations that have not been freed yet
+    pub allocations: u64,
+}
+
+/// Returns the memory currently used by each subsystem, or nothing if the [TrackingAllocator] is
+/// not the global allocator.
+pub fn usage() -> Vec<SubsystemMemory> {
+    if !is_tracking() {
+        return Vec::new();
+    }
+    let mut names = SUBSYSTEMS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+    if names.is_empty() {
+        names.push(OTHER.to_string());
+    }
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(slot, subsystem)| SubsystemMemory {
+            subsystem,
+            bytes: BYTES[slot].load(Ordering::Relaxed),
+            allocations: ALLOCATIONS[slot].load(Ordering::Relaxed),
+        })
+        .collect()
+}
@@ -1,6 +1,8 @@
 use ambient_ecs::{query, EntityId, World};
 
-pub use ambient_ecs::generated::player::components::{is_player, local_user_id, user_id};
+pub use ambient_ecs::generated::player::components::{
+    is_player, is_spectator, local_user_id, user_id,
+};
 
 /// Returns the player entity for the given user ID, if it exists
 pub fn get_by_user_id(world: &World, user_id: &str) -> Option<EntityId> {
@@ -11,3 +13,11 @@ pub fn get_by_user_id(world: &World, user_id: &str) -> Option<EntityId> {
         .iter(world, None)
         .find_map(|(id, uid)| if uid == user_id { Some(id) } else { None })
 }
+
+/// Returns the spectator entity for the given user ID, if it exists
+pub fn get_spectator_by_user_id(world: &World, user_id: &str) -> Option<EntityId> {
+    query(self::user_id())
+        .incl(is_spectator())
+        .iter(world, None)
+        .find_map(|(id, uid)| if uid == user_id { Some(id) } else { None })
+}
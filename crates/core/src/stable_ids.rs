@@ -0,0 +1,154 @@
+//! Stable IDs identify entities across saves, loads and server restarts, unlike their [EntityId]s,
+//! which change whenever an entity is spawned again.
+//!
+//! The [stable_id] of an entity is assigned by [get_or_assign] when it is first requested, and is
+//! stored and networked with the rest of the entity, so references kept in persistent storage
+//! can be resolved with [get_by_stable_id] after a restart or a host migration.
+use std::collections::HashMap;
+
+use ambient_ecs::{
+    components, query, EntityId, FnSystem, QueryState, Resource, SystemGroup, World,
+};
+
+use crate::stable_id;
+
+components!("app", {
+    @[Resource]
+    stable_id_index: StableIdIndex,
+});
+
+/// The entity with each stable ID. Kept up to date by [systems], and before each lookup, as
+/// entities are spawned and despawned and their [stable_id] is added, changed or removed.
+#[derive(Debug, Clone)]
+pub struct StableIdIndex {
+    entities: HashMap<String, EntityId>,
+    /// The stable ID each entity is indexed under
+    stable_ids: HashMap<EntityId, String>,
+    spawned: QueryState,
+    changed: QueryState,
+    /// Entities that were despawned or had their [stable_id] removed
+    removed: QueryState,
+}
+impl Default for StableIdIndex {
+    fn default() -> Self {
+        Self {
+            entities: HashMap::new(),
+            stable_ids: HashMap::new(),
+            spawned: QueryState::new(),
+            changed: QueryState::new(),
+            removed: QueryState::new(),
+        }
+    }
+}
+impl StableIdIndex {
+    /// Returns the entity with `stable_id`, as of the last update.
+    pub fn get(&self, stable_id: &str) -> Option<EntityId> {
+        self.entities.get(stable_id).copied()
+    }
+
+    fn update(&mut self, world: &World) {
+        let updated = query(stable_id())
+            .spawned()
+            .collect_cloned(world, Some(&mut self.spawned))
+            .into_iter()
+            .chain(query(stable_id().changed()).collect_cloned(world, Some(&mut self.changed)));
+        for (id, stable_id) in updated.collect::<Vec<_>>() {
+            self.remove(id);
+            // If several entities have the same stable ID, such as copies of the same entity,
+            // the last one to be indexed is found
+            self.entities.insert(stable_id.clone(), id);
+            self.stable_ids.insert(id, stable_id);
+        }
+
+        // Despawned queries also report entities that still exist but no longer match, so this
+        // covers the removal of the stable ID of a live entity as well
+        for id in query(())
+            .incl(stable_id())
+            .despawned()
+            .collect_ids(world, Some(&mut self.removed))
+        {
+            self.remove(id);
+        }
+    }
+
+    fn remove(&mut self, id: EntityId) {
+        if let Some(stable_id) = self.stable_ids.remove(&id) {
+            if self.entities.get(&stable_id) == Some(&id) {
+                self.entities.remove(&stable_id);
+            }
+        }
+    }
+}
+
+/// Updates the [stable_id_index] of the world, creating it if needed.
+fn update(world: &mut World) {
+    if !world.has_component(world.resource_entity(), stable_id_index()) {
+        world.add_resource(stable_id_index(), StableIdIndex::default());
+    }
+    // The index is taken out of the world while it is updated, as it needs to read the world
+    let mut index = std::mem::take(world.resource_mut(stable_id_index()));
+    index.update(world);
+    *world.resource_mut(stable_id_index()) = index;
+}
+
+/// Returns a new stable ID, which is a random UUID.
+pub fn new_stable_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Returns the stable ID of `id`, assigning a new one if it does not have one yet.
+///
+/// Returns `None` if the entity does not exist.
+pub fn get_or_assign(world: &mut World, id: EntityId) -> Option<String> {
+    if !world.exists(id) {
+        return None;
+    }
+    if let Ok(existing) = world.get_ref(id, stable_id()) {
+        return Some(existing.clone());
+    }
+
+    let new = new_stable_id();
+    world.add_component(id, stable_id(), new.clone()).ok()?;
+    Some(new)
+}
+
+/// Returns the entity of the world with `stable_id`, if there is one.
+pub fn get_by_stable_id(world: &mut World, stable_id: &str) -> Option<EntityId> {
+    update(world);
+    world.resource(stable_id_index()).get(stable_id)
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "stable_id",
+        vec![Box::new(FnSystem::new(|world, _| update(world)))],
+    )
+}
+
+#[test]
+fn test_stable_id_index() {
+    ambient_ecs::init_components();
+    crate::init_all_components();
+
+    let mut world = World::new_unknown("test_stable_id_index");
+    let a = world.spawn(ambient_ecs::Entity::new());
+    let b = world.spawn(ambient_ecs::Entity::new());
+    let a_stable_id = get_or_assign(&mut world, a).unwrap();
+    let b_stable_id = get_or_assign(&mut world, b).unwrap();
+    assert_eq!(get_or_assign(&mut world, a), Some(a_stable_id.clone()));
+    assert_eq!(get_by_stable_id(&mut world, &a_stable_id), Some(a));
+    assert_eq!(get_by_stable_id(&mut world, &b_stable_id), Some(b));
+
+    // Removing the stable ID of a live entity removes it from the index
+    world.remove_component(a, stable_id()).unwrap();
+    assert_eq!(get_by_stable_id(&mut world, &a_stable_id), None);
+    assert_eq!(get_by_stable_id(&mut world, &b_stable_id), Some(b));
+
+    // The stable ID can be given to another entity, which is found from then on
+    world.set(b, stable_id(), a_stable_id.clone()).unwrap();
+    assert_eq!(get_by_stable_id(&mut world, &a_stable_id), Some(b));
+    assert_eq!(get_by_stable_id(&mut world, &b_stable_id), None);
+
+    world.despawn(b);
+    assert_eq!(get_by_stable_id(&mut world, &a_stable_id), None);
+}
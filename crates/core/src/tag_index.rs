@@ -0,0 +1,100 @@
+//! An index of the entities with each of the [tags], so that the entities with a tag can be found
+//! without going through all the entities with tags.
+use std::collections::{HashMap, HashSet};
+
+use ambient_ecs::{
+    components, query, EntityId, FnSystem, QueryState, Resource, SystemGroup, World,
+};
+
+use crate::tags;
+
+components!("app", {
+    @[Resource]
+    tag_index: TagIndex,
+});
+
+/// The entities with each tag. Kept up to date by [systems], and by [tagged] before each lookup.
+#[derive(Debug, Clone)]
+pub struct TagIndex {
+    entities: HashMap<String, HashSet<EntityId>>,
+    /// The tags each entity is indexed under
+    tags: HashMap<EntityId, Vec<String>>,
+    spawned: QueryState,
+    changed: QueryState,
+    despawned: QueryState,
+}
+impl Default for TagIndex {
+    fn default() -> Self {
+        Self {
+            entities: HashMap::new(),
+            tags: HashMap::new(),
+            spawned: QueryState::new(),
+            changed: QueryState::new(),
+            despawned: QueryState::new(),
+        }
+    }
+}
+impl TagIndex {
+    /// Returns the entities with `tag`, as of the last update.
+    pub fn get(&self, tag: &str) -> impl Iterator<Item = EntityId> + '_ {
+        self.entities.get(tag).into_iter().flatten().copied()
+    }
+
+    fn update(&mut self, world: &World) {
+        let updated = query(tags())
+            .spawned()
+            .collect_cloned(world, Some(&mut self.spawned))
+            .into_iter()
+            .chain(query(tags().changed()).collect_cloned(world, Some(&mut self.changed)));
+        for (id, tags) in updated.collect::<Vec<_>>() {
+            self.remove(id);
+            for tag in &tags {
+                self.entities.entry(tag.clone()).or_default().insert(id);
+            }
+            self.tags.insert(id, tags);
+        }
+
+        for id in query(())
+            .incl(tags())
+            .despawned()
+            .collect_ids(world, Some(&mut self.despawned))
+        {
+            self.remove(id);
+        }
+    }
+
+    fn remove(&mut self, id: EntityId) {
+        for tag in self.tags.remove(&id).unwrap_or_default() {
+            if let Some(entities) = self.entities.get_mut(&tag) {
+                entities.remove(&id);
+                if entities.is_empty() {
+                    self.entities.remove(&tag);
+                }
+            }
+        }
+    }
+}
+
+/// Updates the [tag_index] of the world, creating it if needed.
+fn update(world: &mut World) {
+    if !world.has_component(world.resource_entity(), tag_index()) {
+        world.add_resource(tag_index(), TagIndex::default());
+    }
+    // The index is taken out of the world while it is updated, as it needs to read the world
+    let mut index = std::mem::take(world.resource_mut(tag_index()));
+    index.update(world);
+    *world.resource_mut(tag_index()) = index;
+}
+
+/// Returns the entities of the world with `tag`.
+pub fn tagged(world: &mut World, tag: &str) -> Vec<EntityId> {
+    update(world);
+    world.resource(tag_index()).get(tag).collect()
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "tag_index",
+        vec![Box::new(FnSystem::new(|world, _| update(world)))],
+    )
+}
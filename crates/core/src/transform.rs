@@ -13,6 +13,7 @@ use crate::{
     player::local_user_id,
 };
 use ambient_gpu_ecs::{ComponentToGpuSystem, GpuComponentFormat, GpuWorldSyncEvent};
+use ambient_native_std::math::simd;
 
 pub use ambient_ecs::generated::transform::components::{
     cylindrical_billboard_z, euler_rotation, inv_local_to_world, local_to_parent, local_to_world,
@@ -50,6 +51,7 @@ pub struct TransformSystem {
     post_parented_systems: SystemGroup,
     parented_state_1: QueryState,
     parented_state_2: QueryState,
+    parented_state_3: QueryState,
 }
 impl TransformSystem {
     pub fn new() -> Self {
@@ -59,9 +61,9 @@ impl TransformSystem {
                 vec![
                     query_mut((rotation(),), (euler_rotation().changed(),)).to_system(
                         |query, world, state, _| {
-                            for (_, (rot,), (&r,)) in query.iter(world, state) {
+                            query.par_for_each(world, state, |_, (rot,), (&r,)| {
                                 *rot = Quat::from_euler(EulerRot::ZYX, r.z, r.y, r.x);
-                            }
+                            });
                         },
                     ),
                     query_mut(
@@ -74,12 +76,7 @@ impl TransformSystem {
                     )
                     .excl(fbx_complex_transform())
                     .to_system(|query, world, state, _| {
-                        for (_, (local_to_parent,), (&translation, &rotation, &scale)) in
-                            query.iter(world, state)
-                        {
-                            *local_to_parent =
-                                Mat4::from_scale_rotation_translation(scale, rotation, translation);
-                        }
+                        query.par_for_each_batch(world, state, scale_rotation_translation_batch);
                     }),
                     query_mut(
                         (local_to_parent(),),
@@ -88,15 +85,17 @@ impl TransformSystem {
                     .excl(rotation())
                     .excl(fbx_complex_transform())
                     .to_system(|query, world, state, _| {
-                        for (_, (local_to_parent,), (&translation, &scale)) in
-                            query.iter(world, state)
-                        {
-                            *local_to_parent = Mat4::from_scale_rotation_translation(
-                                scale,
-                                Quat::IDENTITY,
-                                translation,
-                            );
-                        }
+                        query.par_for_each(
+                            world,
+                            state,
+                            |_, (local_to_parent,), (&translation, &scale)| {
+                                *local_to_parent = Mat4::from_scale_rotation_translation(
+                                    scale,
+                                    Quat::IDENTITY,
+                                    translation,
+                                );
+                            },
+                        );
                     }),
                     query_mut(
                         (local_to_parent(),),
@@ -105,12 +104,14 @@ impl TransformSystem {
                     .excl(scale())
                     .excl(fbx_complex_transform())
                     .to_system(|query, world, state, _| {
-                        for (_, (local_to_parent,), (&translation, &rotation)) in
-                            query.iter(world, state)
-                        {
-                            *local_to_parent =
-                                Mat4::from_rotation_translation(rotation, translation);
-                        }
+                        query.par_for_each(
+                            world,
+                            state,
+                            |_, (local_to_parent,), (&translation, &rotation)| {
+                                *local_to_parent =
+                                    Mat4::from_rotation_translation(rotation, translation);
+                            },
+                        );
                     }),
                     query_mut(
                         (local_to_parent(),),
@@ -119,39 +120,52 @@ impl TransformSystem {
                     .excl(translation())
                     .excl(fbx_complex_transform())
                     .to_system(|query, world, state, _| {
-                        for (_, (local_to_parent,), (&scale, &rotation)) in query.iter(world, state)
-                        {
-                            *local_to_parent =
-                                Mat4::from_scale_rotation_translation(scale, rotation, Vec3::ZERO);
-                        }
+                        query.par_for_each(
+                            world,
+                            state,
+                            |_, (local_to_parent,), (&scale, &rotation)| {
+                                *local_to_parent = Mat4::from_scale_rotation_translation(
+                                    scale,
+                                    rotation,
+                                    Vec3::ZERO,
+                                );
+                            },
+                        );
                     }),
                     query_mut((local_to_parent(),), (translation().changed(),))
                         .excl(scale())
                         .excl(rotation())
                         .excl(fbx_complex_transform())
                         .to_system(|query, world, state, _| {
-                            for (_, (local_to_parent,), (&translation,)) in query.iter(world, state)
-                            {
-                                *local_to_parent = Mat4::from_translation(translation);
-                            }
+                            query.par_for_each(
+                                world,
+                                state,
+                                |_, (local_to_parent,), (&translation,)| {
+                                    *local_to_parent = Mat4::from_translation(translation);
+                                },
+                            );
                         }),
                     query_mut((local_to_parent(),), (rotation().changed(),))
                         .excl(scale())
                         .excl(translation())
                         .excl(fbx_complex_transform())
                         .to_system(|query, world, state, _| {
-                            for (_, (local_to_parent,), (&rotation,)) in query.iter(world, state) {
-                                *local_to_parent = Mat4::from_quat(rotation);
-                            }
+                            query.par_for_each(
+                                world,
+                                state,
+                                |_, (local_to_parent,), (&rotation,)| {
+                                    *local_to_parent = Mat4::from_quat(rotation);
+                                },
+                            );
                         }),
                     query_mut((local_to_parent(),), (scale().changed(),))
                         .excl(rotation())
                         .excl(translation())
                         .excl(fbx_complex_transform())
                         .to_system(|query, world, state, _| {
-                            for (_, (local_to_parent,), (&scale,)) in query.iter(world, state) {
+                            query.par_for_each(world, state, |_, (local_to_parent,), (&scale,)| {
                                 *local_to_parent = Mat4::from_scale(scale);
-                            }
+                            });
                         }),
                     query_mut(
                         (local_to_world(),),
@@ -165,12 +179,7 @@ impl TransformSystem {
                     .excl(lookat_target())
                     .excl(fbx_complex_transform())
                     .to_system(|query, world, state, _| {
-                        for (_, (local_to_world,), (&translation, &rotation, &scale)) in
-                            query.iter(world, state)
-                        {
-                            *local_to_world =
-                                Mat4::from_scale_rotation_translation(scale, rotation, translation);
-                        }
+                        query.par_for_each_batch(world, state, scale_rotation_translation_batch);
                     }),
                     query_mut(
                         (local_to_world(),),
@@ -181,10 +190,14 @@ impl TransformSystem {
                     .excl(scale())
                     .excl(fbx_complex_transform())
                     .to_system(|q, world, qs, _| {
-                        for (_, (local_to_world,), (&translation, &rotation)) in q.iter(world, qs) {
-                            *local_to_world =
-                                Mat4::from_rotation_translation(rotation, translation);
-                        }
+                        q.par_for_each(
+                            world,
+                            qs,
+                            |_, (local_to_world,), (&translation, &rotation)| {
+                                *local_to_world =
+                                    Mat4::from_rotation_translation(rotation, translation);
+                            },
+                        );
                     }),
                     query_mut(
                         (local_to_world(),),
@@ -195,13 +208,17 @@ impl TransformSystem {
                     .excl(rotation())
                     .excl(fbx_complex_transform())
                     .to_system(|q, world, qs, _| {
-                        for (_, (local_to_world,), (&translation, &scale)) in q.iter(world, qs) {
-                            *local_to_world = Mat4::from_scale_rotation_translation(
-                                scale,
-                                Quat::IDENTITY,
-                                translation,
-                            );
-                        }
+                        q.par_for_each(
+                            world,
+                            qs,
+                            |_, (local_to_world,), (&translation, &scale)| {
+                                *local_to_world = Mat4::from_scale_rotation_translation(
+                                    scale,
+                                    Quat::IDENTITY,
+                                    translation,
+                                );
+                            },
+                        );
                     }),
                     query_mut(
                         (local_to_world(),),
@@ -212,10 +229,10 @@ impl TransformSystem {
                     .excl(translation())
                     .excl(fbx_complex_transform())
                     .to_system(|q, world, qs, _| {
-                        for (_, (local_to_world,), (&rotation, &scale)) in q.iter(world, qs) {
+                        q.par_for_each(world, qs, |_, (local_to_world,), (&rotation, &scale)| {
                             *local_to_world =
                                 Mat4::from_scale_rotation_translation(scale, rotation, Vec3::ZERO);
-                        }
+                        });
                     }),
                     query_mut((local_to_world(),), (translation().changed(),))
                         .excl(local_to_parent())
@@ -224,9 +241,9 @@ impl TransformSystem {
                         .excl(rotation())
                         .excl(fbx_complex_transform())
                         .to_system(|q, world, qs, _| {
-                            for (_, (local_to_world,), (&translation,)) in q.iter(world, qs) {
+                            q.par_for_each(world, qs, |_, (local_to_world,), (&translation,)| {
                                 *local_to_world = Mat4::from_translation(translation);
-                            }
+                            });
                         }),
                     query_mut((local_to_world(),), (scale().changed(),))
                         .excl(local_to_parent())
@@ -235,9 +252,9 @@ impl TransformSystem {
                         .excl(rotation())
                         .excl(fbx_complex_transform())
                         .to_system(|q, world, qs, _| {
-                            for (_, (local_to_world,), (&scale,)) in q.iter(world, qs) {
+                            q.par_for_each(world, qs, |_, (local_to_world,), (&scale,)| {
                                 *local_to_world = Mat4::from_scale(scale);
-                            }
+                            });
                         }),
                     query_mut((local_to_world(),), (rotation().changed(),))
                         .excl(local_to_parent())
@@ -246,9 +263,9 @@ impl TransformSystem {
                         .excl(scale())
                         .excl(fbx_complex_transform())
                         .to_system(|q, world, qs, _| {
-                            for (_, (local_to_world,), (&rotation,)) in q.iter(world, qs) {
+                            q.par_for_each(world, qs, |_, (local_to_world,), (&rotation,)| {
                                 *local_to_world = Mat4::from_quat(rotation);
-                            }
+                            });
                         }),
                     // Make sure lookat has all the components
                     ensure_has_component(lookat_target(), local_to_world(), Default::default()),
@@ -266,16 +283,17 @@ impl TransformSystem {
                     .excl(local_to_parent())
                     .excl(fbx_complex_transform())
                     .to_system(|q, world, qs, _| {
-                        for (
-                            _,
-                            (local_to_world, inv_local_to_world),
-                            (&translation, &lookat_target, &lookat_up),
-                        ) in q.iter(world, qs)
-                        {
-                            *inv_local_to_world =
-                                Mat4::look_at_lh(translation, lookat_target, lookat_up);
-                            *local_to_world = inv_local_to_world.inverse();
-                        }
+                        q.par_for_each(
+                            world,
+                            qs,
+                            |_,
+                             (local_to_world, inv_local_to_world),
+                             (&translation, &lookat_target, &lookat_up)| {
+                                *inv_local_to_world =
+                                    Mat4::look_at_lh(translation, lookat_target, lookat_up);
+                                *local_to_world = inv_local_to_world.inverse();
+                            },
+                        );
                     }),
                     // FBX
                     query_mut(
@@ -303,8 +321,6 @@ impl TransformSystem {
                     .optional_changed(scale())
                     .incl(fbx_complex_transform())
                     .to_system(|q, world, qs, _| {
-                        // See: https://help.autodesk.com/view/FBX/2017/ENU/?guid=__files_GUID_10CDD63C_79C1_4F2D_BB28_AD2BE65A02ED_htm
-                        // and: https://github.com/assimp/assimp/blob/add7f1355e96c6ff0df0ba3cec084f25332d154e/code/AssetLib/FBX/FBXConverter.cpp#L687
                         for (
                             _,
                             transform,
@@ -321,14 +337,17 @@ impl TransformSystem {
                             ),
                         ) in q.iter(world, qs)
                         {
-                            let o = pos + rot_offset + rot_pivot;
-                            let r = pre_rot * rot * post_rot.inverse();
-                            let p = scaling_offset + scaling_pivot
-                                - rot_pivot
-                                - scaling * scaling_pivot;
-                            let t = o + r * p;
-
-                            *transform = Mat4::from_scale_rotation_translation(scaling, r, t);
+                            *transform = fbx_transform(
+                                pos,
+                                rot_offset,
+                                rot_pivot,
+                                pre_rot,
+                                rot,
+                                post_rot,
+                                scaling_offset,
+                                scaling_pivot,
+                                scaling,
+                            );
                         }
                     }),
                     query_mut(
@@ -357,8 +376,6 @@ impl TransformSystem {
                     .incl(fbx_complex_transform())
                     .excl(local_to_parent())
                     .to_system(|q, world, qs, _| {
-                        // See: https://help.autodesk.com/view/FBX/2017/ENU/?guid=__files_GUID_10CDD63C_79C1_4F2D_BB28_AD2BE65A02ED_htm
-                        // and: https://github.com/assimp/assimp/blob/add7f1355e96c6ff0df0ba3cec084f25332d154e/code/AssetLib/FBX/FBXConverter.cpp#L687
                         for (
                             _,
                             transform,
@@ -375,14 +392,17 @@ impl TransformSystem {
                             ),
                         ) in q.iter(world, qs)
                         {
-                            let o = pos + rot_offset + rot_pivot;
-                            let r = pre_rot * rot * post_rot.inverse();
-                            let p = scaling_offset + scaling_pivot
-                                - rot_pivot
-                                - scaling * scaling_pivot;
-                            let t = o + r * p;
-
-                            *transform = Mat4::from_scale_rotation_translation(scaling, r, t);
+                            *transform = fbx_transform(
+                                pos,
+                                rot_offset,
+                                rot_pivot,
+                                pre_rot,
+                                rot,
+                                post_rot,
+                                scaling_offset,
+                                scaling_pivot,
+                                scaling,
+                            );
                         }
                     }),
                 ],
@@ -419,44 +439,45 @@ impl TransformSystem {
             ),
             parented_state_1: QueryState::new(),
             parented_state_2: QueryState::new(),
+            parented_state_3: QueryState::new(),
         }
     }
 
+    /// Updates the world transforms of the entities whose local transform or parent has changed,
+    /// and of their descendants. The rest of the hierarchy is left untouched.
     #[profiling::function]
     fn parented(&mut self, world: &mut World) {
-        let mut changed_roots = HashSet::<EntityId>::new();
+        let mut dirty = HashSet::<EntityId>::new();
         for (id, _) in
             query((local_to_parent().changed(),)).iter(world, Some(&mut self.parented_state_1))
         {
-            // TODO: This could be optimized
-            changed_roots.insert(get_transform_root(world, id));
+            dirty.insert(id);
+        }
+        for (id, _) in query((parent().changed(),))
+            .incl(local_to_parent())
+            .iter(world, Some(&mut self.parented_state_3))
+        {
+            dirty.insert(id);
         }
-        for (id, (), (_, _)) in query_mut((), (local_to_world().changed(), children()))
+        for (_, (), (_, children)) in query_mut((), (local_to_world().changed(), children()))
             .excl(local_to_parent())
             .iter(world, Some(&mut self.parented_state_2))
         {
-            changed_roots.insert(id);
+            dirty.extend(children.iter().copied());
         }
 
-        for id in changed_roots.into_iter() {
-            if let Ok(transform) = world.get(id, local_to_parent()) {
-                if world.set(id, local_to_world(), transform).is_err() {
-                    return;
-                }
-                if let Ok(children) = world.get_ref(id, children()).cloned() {
-                    for child in children {
-                        update_transform_recursive(world, child, transform);
-                    }
-                }
-            } else if let Ok(transform) = world.get(id, local_to_world()) {
-                if let Ok(children) = world.get_ref(id, children()).cloned() {
-                    for child in children {
-                        update_transform_recursive(world, child, transform);
-                    }
-                }
-            } else {
-                tracing::warn!("Bad transform hierarchy; bad root: {}", id);
+        for &id in &dirty {
+            // The entity is updated along with its dirty ancestor
+            if has_dirty_ancestor(world, id, &dirty) {
+                continue;
             }
+            let parent_transform = match world.get(id, parent()) {
+                Ok(parent) => world
+                    .get(parent, local_to_world())
+                    .unwrap_or(Mat4::IDENTITY),
+                Err(_) => Mat4::IDENTITY,
+            };
+            update_transform_recursive(world, id, parent_transform);
         }
     }
 }
@@ -502,6 +523,38 @@ pub fn transform_gpu_systems(gpu: Arc<Gpu>) -> SystemGroup<GpuWorldSyncEvent> {
         ))],
     )
 }
+#[allow(clippy::too_many_arguments)]
+fn fbx_transform(
+    pos: Vec3,
+    rot_offset: Vec3,
+    rot_pivot: Vec3,
+    pre_rot: Quat,
+    rot: Quat,
+    post_rot: Quat,
+    scaling_offset: Vec3,
+    scaling_pivot: Vec3,
+    scaling: Vec3,
+) -> Mat4 {
+    // See: https://help.autodesk.com/view/FBX/2017/ENU/?guid=__files_GUID_10CDD63C_79C1_4F2D_BB28_AD2BE65A02ED_htm
+    // and: https://github.com/assimp/assimp/blob/add7f1355e96c6ff0df0ba3cec084f25332d154e/code/AssetLib/FBX/FBXConverter.cpp#L687
+    let o = pos + rot_offset + rot_pivot;
+    let r = pre_rot * rot * post_rot.inverse();
+    let p = scaling_offset + scaling_pivot - rot_pivot - scaling * scaling_pivot;
+    let t = o + r * p;
+
+    Mat4::from_scale_rotation_translation(scaling, r, t)
+}
+
+/// Computes the matrices of a batch of entities that have a translation, rotation and scale, four
+/// at a time.
+fn scale_rotation_translation_batch(batch: &mut [(EntityId, (&mut Mat4,), (&Vec3, &Quat, &Vec3))]) {
+    simd::scale_rotation_translation_batch(
+        batch,
+        |(_, _, (translation, rotation, scale))| (**scale, **rotation, **translation),
+        |(_, (matrix,), _)| &mut **matrix,
+    );
+}
+
 fn update_transform_recursive(world: &mut World, id: EntityId, mut parent_transform: Mat4) {
     if world.has_component(id, reset_scale()) {
         let (_s, r, t) = parent_transform.to_scale_rotation_translation();
@@ -522,15 +575,19 @@ fn update_transform_recursive(world: &mut World, id: EntityId, mut parent_transf
     }
 }
 
-fn get_transform_root(world: &World, id: EntityId) -> EntityId {
-    if let Ok(parent) = world.get_ref(id, parent()) {
-        if world.has_component(id, local_to_parent())
-            && world.has_component(*parent, local_to_world())
-        {
-            return get_transform_root(world, *parent);
+/// Whether the world transform of `id` is updated by the update of one of its ancestors, which
+/// reaches it through entities with a `local_to_parent`.
+fn has_dirty_ancestor(world: &World, mut id: EntityId, dirty: &HashSet<EntityId>) -> bool {
+    while world.has_component(id, local_to_parent()) {
+        let Ok(parent) = world.get(id, parent()) else {
+            break;
+        };
+        if dirty.contains(&parent) {
+            return true;
         }
+        id = parent;
     }
-    id
+    false
 }
 
 fn spherical_billboard_matrix(local_to_world: &mut Mat4, inv_view: &Mat4) {
@@ -601,3 +658,98 @@ pub fn get_world_rotation(world: &World, entity: EntityId) -> Result<Quat, ECSEr
         },
     }
 }
+
+/// The transform of `entity` relative to its parent, or to the world if it has no parent, from its
+/// current `translation`, `rotation` and `scale`. Unlike `local_to_parent`, which is only updated
+/// by the transform systems, this includes the changes made earlier in the frame.
+pub fn get_local_transform(world: &World, entity: EntityId) -> Mat4 {
+    if !world.has_component(entity, translation())
+        && !world.has_component(entity, rotation())
+        && !world.has_component(entity, scale())
+    {
+        return world
+            .get(entity, local_to_parent())
+            .or_else(|_| world.get(entity, local_to_world()))
+            .unwrap_or_default();
+    }
+    let pos = world.get(entity, translation()).unwrap_or(Vec3::ZERO);
+    let rot = world.get(entity, rotation()).unwrap_or(Quat::IDENTITY);
+    let scaling = world.get(entity, scale()).unwrap_or(Vec3::ONE);
+    if world.has_component(entity, fbx_complex_transform()) {
+        fbx_transform(
+            pos,
+            world.get(entity, fbx_rotation_offset()).unwrap_or_default(),
+            world.get(entity, fbx_rotation_pivot()).unwrap_or_default(),
+            world.get(entity, fbx_pre_rotation()).unwrap_or_default(),
+            rot,
+            world.get(entity, fbx_post_rotation()).unwrap_or_default(),
+            world.get(entity, fbx_scaling_offset()).unwrap_or_default(),
+            world.get(entity, fbx_scaling_pivot()).unwrap_or_default(),
+            scaling,
+        )
+    } else {
+        Mat4::from_scale_rotation_translation(scaling, rot, pos)
+    }
+}
+
+/// The transform of `entity` relative to the world, from the current local transforms of it and
+/// of its parents (see [get_local_transform]).
+pub fn get_current_world_transform(world: &World, entity: EntityId) -> Mat4 {
+    let transform = get_local_transform(world, entity);
+    match world.get(entity, parent()) {
+        Ok(parent) if world.has_component(entity, local_to_parent()) => {
+            get_current_world_transform(world, parent) * transform
+        }
+        _ => transform,
+    }
+}
+
+#[test]
+fn test_parented_transforms() {
+    ambient_ecs::init_components();
+    crate::init_all_components();
+
+    let mut world = World::new_unknown("test_parented_transforms");
+    let spawn = |world: &mut World, parent_id: Option<EntityId>, translation: Vec3| {
+        let mut entity = ambient_ecs::Entity::new()
+            .with(crate::transform::translation(), translation)
+            .with(local_to_world(), Mat4::IDENTITY);
+        if let Some(parent_id) = parent_id {
+            entity.set(parent(), parent_id);
+            entity.set(local_to_parent(), Mat4::IDENTITY);
+        }
+        let id = world.spawn(entity.with(children(), Vec::new()));
+        if let Some(parent_id) = parent_id {
+            world.get_mut(parent_id, children()).unwrap().push(id);
+        }
+        id
+    };
+    let root = spawn(&mut world, None, Vec3::X);
+    let a = spawn(&mut world, Some(root), Vec3::Y);
+    let b = spawn(&mut world, Some(a), Vec3::Z);
+    let c = spawn(&mut world, Some(root), Vec3::Y);
+
+    let mut system = TransformSystem::new();
+    let world_translation = |world: &World, id| {
+        world
+            .get(id, local_to_world())
+            .unwrap()
+            .to_scale_rotation_translation()
+            .2
+    };
+    system.run(&mut world, &FrameEvent);
+    assert_eq!(world_translation(&world, b), vec3(1., 1., 1.));
+    assert_eq!(world_translation(&world, c), vec3(1., 1., 0.));
+
+    // Only the changed entity and its descendants are updated
+    world.set(a, translation(), Vec3::Y * 2.).unwrap();
+    world.set(c, local_to_world(), Mat4::IDENTITY).unwrap();
+    system.run(&mut world, &FrameEvent);
+    assert_eq!(world_translation(&world, b), vec3(1., 2., 1.));
+    assert_eq!(world_translation(&world, c), Vec3::ZERO);
+
+    // Reparented entities are updated, from the world transform of `c` that was set above
+    world.set(b, parent(), c).unwrap();
+    system.run(&mut world, &FrameEvent);
+    assert_eq!(world_translation(&world, b), Vec3::Z);
+}
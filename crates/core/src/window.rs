@@ -1,4 +1,4 @@
-use ambient_ecs::{components, Description, Name, Resource, World};
+use ambient_ecs::{components, Debuggable, Description, Name, Resource, World};
 use ambient_native_std::math::interpolate;
 use glam::{uvec2, vec2, UVec2, Vec2, Vec3, Vec3Swizzles};
 use winit::window::{CursorGrabMode, CursorIcon, Window};
@@ -10,6 +10,18 @@ pub use ambient_ecs::generated::app::components::{
 components!("app", {
     @[Resource, Name["Window Control"], Description["Allows controlling the window from afar."]]
     window_ctl: flume::Sender<WindowCtl>,
+    @[
+        Debuggable, Resource,
+        Name["Focus loss behavior"],
+        Description["What the client does when its window loses focus."]
+    ]
+    focus_loss_behavior: FocusLossBehavior,
+    @[
+        Debuggable,
+        Name["Cursor image"],
+        Description["The image drawn in place of the cursor, while the cursor is visible."]
+    ]
+    cursor_image: CursorImage,
 });
 
 pub fn set_cursor(world: &World, cursor: CursorIcon) {
@@ -51,14 +63,45 @@ pub fn get_window_sizes(window: &Window) -> (UVec2, UVec2, f64) {
     (size, (size.as_dvec2() / sf).as_uvec2(), sf)
 }
 
+/// An image that is drawn in place of the OS cursor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CursorImage {
+    pub url: String,
+    /// The size of the image, in logical pixels.
+    pub size: Vec2,
+    /// The point of the image that is at the position of the cursor, in logical pixels from its
+    /// top-left corner.
+    pub hotspot: Vec2,
+}
+
+/// What the client does when its window loses focus. It is set from the user settings, and can be
+/// overridden with [WindowCtl::OverrideFocusLossBehavior].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FocusLossBehavior {
+    /// Minimizes the window if it is fullscreen.
+    pub minimize: bool,
+    pub mute_audio: bool,
+    /// Unlocks and shows the cursor until the window regains focus.
+    pub release_cursor: bool,
+}
+
 /// Allows controlling the window
 #[derive(Debug, Clone)]
 pub enum WindowCtl {
     GrabCursor(CursorGrabMode),
+    /// Keeps the cursor within a rectangle of the window, in logical pixels, or releases it.
+    ConfineCursor(Option<(Vec2, Vec2)>),
     SetCursorIcon(CursorIcon),
+    SetCursorImage(Option<CursorImage>),
     ShowCursor(bool),
     SetTitle(String),
     SetFullscreen(bool),
+    /// Overrides the parts of the [FocusLossBehavior] that are set.
+    OverrideFocusLossBehavior {
+        minimize: Option<bool>,
+        mute_audio: Option<bool>,
+        release_cursor: Option<bool>,
+    },
     ExitProcess(ExitStatus),
 }
 
@@ -17,7 +17,7 @@ use ambient_element::{
 use ambient_gizmos::{gizmos, GizmoPrimitive};
 use ambient_native_std::{asset_cache::AssetCache, color::Color, Cb};
 use ambient_network::{client::ClientState, server::RpcArgs as ServerRpcArgs};
-use ambient_renderer::{RenderTarget, Renderer};
+use ambient_renderer::{DebugView, RenderTarget, Renderer};
 use ambient_rpc::RpcRegistry;
 use ambient_shared_types::{ModifiersState, VirtualKeyCode};
 use ambient_std::line_uid;
@@ -300,7 +300,7 @@ pub fn Debugger(hooks: &mut Hooks, get_state: GetDebuggerState) -> Element {
                     .hotkey(VirtualKeyCode::F6)
                     .style(ButtonStyle::Flat)
                     .el(),
-                    ShaderDebug {
+                    DebugViews {
                         get_state: get_state.clone(),
                     }
                     .el(),
@@ -378,62 +378,36 @@ fn ShadowMapViz(hooks: &mut Hooks, get_state: GetDebuggerState, cascade: u32) ->
 }
 
 #[element_component]
-fn ShaderDebug(hooks: &mut Hooks, get_state: GetDebuggerState) -> Element {
+fn DebugViews(hooks: &mut Hooks, get_state: GetDebuggerState) -> Element {
     let (show, set_show) = use_state(hooks, false);
 
     let (_, upd) = use_state(hooks, ());
 
-    let mut params = Default::default();
+    let mut current_view = DebugView::default();
     get_state(&mut |renderer, _, _| {
-        params = renderer.shader_debug_params;
+        current_view = renderer.debug_view;
     });
-    let metallic_roughness = params.metallic_roughness;
-    let normals = params.normals;
-    let shading = params.shading;
 
     Dropdown {
-        content: Button::new("Shader Debug", move |_| set_show(!show))
-            .toggled(show)
-            .el(),
-        dropdown: FlowColumn::el([
-            Button::new("Show metallic roughness", {
-                let get_state = get_state.clone();
-                let upd = upd.clone();
-                move |_| {
-                    get_state(&mut |renderer, _, _| {
-                        renderer.shader_debug_params.metallic_roughness =
-                            (1.0 - metallic_roughness).round();
-                    });
-                    upd(())
-                }
-            })
-            .toggled(metallic_roughness > 0.0)
-            .el(),
-            Button::new("Show normals", {
-                let get_state = get_state.clone();
-                let upd = upd.clone();
-                move |_| {
-                    get_state(&mut |renderer, _, _| {
-                        renderer.shader_debug_params.normals = (1.0 - normals).round();
-                    });
-                    upd(())
-                }
-            })
-            .toggled(normals > 0.0)
-            .el(),
-            Button::new("Disable shading", {
+        content: Button::new(format!("View: {}", current_view.name()), move |_| {
+            set_show(!show)
+        })
+        .toggled(show)
+        .el(),
+        dropdown: FlowColumn::el(DebugView::ALL.into_iter().map(|view| {
+            Button::new(view.name(), {
                 let get_state = get_state.clone();
                 let upd = upd.clone();
                 move |_| {
                     get_state(&mut |renderer, _, _| {
-                        renderer.shader_debug_params.shading = (1.0 - shading).round();
+                        renderer.debug_view = view;
                     });
                     upd(())
                 }
             })
-            .toggled(shading > 0.0)
-            .el(),
-        ]),
+            .toggled(view == current_view)
+            .el()
+        })),
         show,
     }
     .el()
@@ -80,6 +80,7 @@ impl SyncAssetKey<Arc<RendererShader>> for DecalShaderKey {
             double_sided: true,
             depth_write_enabled: false,
             transparency_group: -100,
+            bindless: false,
         })
     }
 }
@@ -0,0 +1,219 @@
+//! Dragging payloads between UI elements.
+//!
+//! Pressing the left mouse button over a `drag_source` and moving the mouse starts a drag. When the
+//! button is released over a `drop_target`, the payload of the source is dropped on it.
+use ambient_ecs::{
+    generated::{
+        app::components::{cursor_position, window_scale_factor},
+        hierarchy::components::parent,
+        input::components::mouse_over_entity,
+        layout::components::{height, width},
+        messages::WindowMouseInput,
+        rendering::components::scissors,
+        transform::components::local_to_world,
+        ui::{
+            components::{drag_source, drop_target, drop_target_hovered},
+            messages::{DragCancelled, DragDropped, DragStarted},
+        },
+    },
+    query, read_messages, world_events, EntityId, FnSystem, SystemGroup, World, WorldEventReader,
+    WorldEventsExt,
+};
+use glam::Vec2;
+
+/// How far the mouse has to move while the button is held before a drag starts, in logical pixels.
+const DRAG_THRESHOLD: f32 = 4.;
+
+#[derive(Debug, Clone, Default)]
+enum DragState {
+    #[default]
+    Idle,
+    /// The button was pressed over a drag source, but the mouse has not moved far enough yet.
+    Pressed {
+        source: EntityId,
+        payload: String,
+        origin: Vec2,
+    },
+    Dragging {
+        source: EntityId,
+        payload: String,
+        hovered: Option<EntityId>,
+    },
+}
+
+pub fn systems() -> SystemGroup {
+    let mut reader = WorldEventReader::new();
+    let mut state = DragState::Idle;
+    SystemGroup::new(
+        "drag_drop",
+        vec![Box::new(FnSystem::new(move |world, _| {
+            let cursor = world
+                .resource_opt(cursor_position())
+                .copied()
+                .unwrap_or_default();
+
+            for event in
+                read_messages::<WindowMouseInput>(&mut reader, world.resource(world_events()))
+            {
+                if event.button != 0 {
+                    continue;
+                }
+                state = if event.pressed {
+                    match drag_source_under_mouse(world) {
+                        Some((source, payload)) => DragState::Pressed {
+                            source,
+                            payload,
+                            origin: cursor,
+                        },
+                        None => DragState::Idle,
+                    }
+                } else {
+                    release(world, std::mem::take(&mut state), cursor);
+                    DragState::Idle
+                };
+            }
+
+            state = update(world, std::mem::take(&mut state), cursor);
+        }))],
+    )
+}
+
+/// Starts the drag once the mouse has moved far enough, and keeps track of the hovered target.
+fn update(world: &mut World, state: DragState, cursor: Vec2) -> DragState {
+    match state {
+        DragState::Idle => DragState::Idle,
+        DragState::Pressed { source, .. } if !world.exists(source) => DragState::Idle,
+        DragState::Pressed {
+            source,
+            payload,
+            origin,
+        } => {
+            if cursor.distance(origin) < DRAG_THRESHOLD {
+                return DragState::Pressed {
+                    source,
+                    payload,
+                    origin,
+                };
+            }
+            world.resource_mut(world_events()).add_message(DragStarted {
+                source,
+                payload: payload.clone(),
+            });
+            DragState::Dragging {
+                source,
+                payload,
+                hovered: None,
+            }
+        }
+        DragState::Dragging {
+            source,
+            payload,
+            hovered,
+        } => {
+            if !world.exists(source) {
+                set_hovered(world, hovered, None);
+                world
+                    .resource_mut(world_events())
+                    .add_message(DragCancelled { source, payload });
+                return DragState::Idle;
+            }
+            let target = drop_target_at(world, cursor);
+            set_hovered(world, hovered, target);
+            DragState::Dragging {
+                source,
+                payload,
+                hovered: target,
+            }
+        }
+    }
+}
+
+/// Drops the dragged payload on the target under the cursor, if there is one.
+fn release(world: &mut World, state: DragState, cursor: Vec2) {
+    let DragState::Dragging {
+        source,
+        payload,
+        hovered,
+    } = state
+    else {
+        return;
+    };
+    set_hovered(world, hovered, None);
+
+    let target = drop_target_at(world, cursor);
+    let events = world.resource_mut(world_events());
+    match target {
+        Some(target) => events.add_message(DragDropped {
+            source,
+            target,
+            payload,
+        }),
+        None => events.add_message(DragCancelled { source, payload }),
+    }
+}
+
+fn set_hovered(world: &mut World, from: Option<EntityId>, to: Option<EntityId>) {
+    if from == to {
+        return;
+    }
+    if let Some(from) = from {
+        world.remove_component(from, drop_target_hovered()).ok();
+    }
+    if let Some(to) = to {
+        world.add_component(to, drop_target_hovered(), ()).ok();
+    }
+}
+
+/// Returns the drag source that the mouse is over, which is either the entity under the mouse or
+/// one of its ancestors, and its payload.
+fn drag_source_under_mouse(world: &World) -> Option<(EntityId, String)> {
+    let mut current = world.resource_opt(mouse_over_entity()).copied()?;
+    loop {
+        if let Ok(payload) = world.get_cloned(current, drag_source()) {
+            return Some((current, payload));
+        }
+        current = world.get(current, parent()).ok()?;
+    }
+}
+
+/// Returns the frontmost drop target under the cursor.
+///
+/// The bounds of the targets are used instead of the entity under the mouse, as the drag ghost
+/// would otherwise be in the way.
+fn drop_target_at(world: &World, cursor: Vec2) -> Option<EntityId> {
+    let scale_factor = world
+        .resource_opt(window_scale_factor())
+        .copied()
+        .unwrap_or(1.) as f32;
+
+    query(local_to_world())
+        .incl(drop_target())
+        .iter(world, None)
+        .filter_map(|(id, transform)| {
+            let (scale, _, position) = transform.to_scale_rotation_translation();
+            let size = Vec2::new(
+                world.get(id, width()).unwrap_or(0.),
+                world.get(id, height()).unwrap_or(0.),
+            ) * scale.truncate();
+            let min = position.truncate();
+            let inside =
+                contains(min, min + size, cursor) && !is_clipped(world, id, cursor * scale_factor);
+            inside.then_some((id, position.z))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(id, _)| id)
+}
+
+/// Whether `physical_cursor` is outside of the `scissors` of the entity, which it has if it is in
+/// a clipping parent.
+fn is_clipped(world: &World, id: EntityId, physical_cursor: Vec2) -> bool {
+    world.get(id, scissors()).is_ok_and(|scissors| {
+        let min = Vec2::new(scissors.x as f32, scissors.y as f32);
+        let size = Vec2::new(scissors.z as f32, scissors.w as f32);
+        !contains(min, min + size, physical_cursor)
+    })
+}
+
+fn contains(min: Vec2, max: Vec2, point: Vec2) -> bool {
+    point.cmpge(min).all() && point.cmplt(max).all()
+}
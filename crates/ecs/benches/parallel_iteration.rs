@@ -0,0 +1,102 @@
+//! Compares the sequential and parallel iteration of queries over a large world.
+//!
+//! Run with `cargo bench -p ambient_ecs --bench parallel_iteration`.
+
+use std::time::{Duration, Instant};
+
+use ambient_ecs::{components, query, query_mut, Entity, World};
+use ambient_native_std::math::simd;
+use glam::{Mat4, Quat, Vec3};
+
+components!("bench", {
+    translation: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+    local_to_world: Mat4,
+});
+
+const ENTITY_COUNTS: [usize; 3] = [1_000, 10_000, 100_000];
+const ITERATIONS: u32 = 50;
+
+fn create_world(count: usize) -> World {
+    let mut world = World::new_unknown("parallel_iteration");
+    for i in 0..count {
+        world.spawn(
+            Entity::new()
+                .with(translation(), Vec3::splat(i as f32))
+                .with(rotation(), Quat::from_rotation_z(i as f32))
+                .with(scale(), Vec3::ONE)
+                .with(local_to_world(), Mat4::IDENTITY),
+        );
+    }
+    world
+}
+
+fn measure(mut run: impl FnMut()) -> Duration {
+    // Warm up the caches and the thread pool
+    run();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        run();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    init_components();
+
+    let q = query_mut((local_to_world(),), (translation(), rotation(), scale()));
+    let read = query((translation(), rotation(), scale()));
+    for count in ENTITY_COUNTS {
+        let mut world = create_world(count);
+
+        let iter = measure(|| {
+            for (_, (local_to_world,), (&translation, &rotation, &scale)) in
+                q.iter(&mut world, None)
+            {
+                *local_to_world =
+                    Mat4::from_scale_rotation_translation(scale, rotation, translation);
+            }
+        });
+        let par_for_each = measure(|| {
+            q.par_for_each(
+                &mut world,
+                None,
+                |_, (local_to_world,), (&translation, &rotation, &scale)| {
+                    *local_to_world =
+                        Mat4::from_scale_rotation_translation(scale, rotation, translation);
+                },
+            );
+        });
+        let par_for_each_batch = measure(|| {
+            q.par_for_each_batch(&mut world, None, |batch| {
+                simd::scale_rotation_translation_batch(
+                    batch,
+                    |(_, _, (translation, rotation, scale))| (**scale, **rotation, **translation),
+                    |(_, (local_to_world,), _)| &mut **local_to_world,
+                );
+            });
+        });
+        println!(
+            "{count} entities: iter {iter:?}, par_for_each {par_for_each:?}, \
+             par_for_each_batch {par_for_each_batch:?}"
+        );
+
+        let iter = measure(|| {
+            let matrices = read
+                .iter(&world, None)
+                .map(|(_, (&translation, &rotation, &scale))| {
+                    Mat4::from_scale_rotation_translation(scale, rotation, translation)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(matrices.len(), count);
+        });
+        let par_map = measure(|| {
+            let matrices = read.par_map(&world, None, |_, (&translation, &rotation, &scale)| {
+                Mat4::from_scale_rotation_translation(scale, rotation, translation)
+            });
+            assert_eq!(matrices.len(), count);
+        });
+        println!("{count} entities: iter {iter:?}, par_map {par_map:?}");
+    }
+}
@@ -170,6 +170,10 @@ impl Archetype {
             query_markers: Default::default(),
         }
     }
+    /// The components of the entities of this archetype
+    pub fn active_components(&self) -> &ComponentSet {
+        &self.active_components
+    }
     pub fn entity_count(&self) -> usize {
         self.entity_indices_to_ids.len()
     }
@@ -5,6 +5,7 @@ use std::{
     sync::Arc,
 };
 
+pub use ambient_shared_types::Replication;
 use as_any::{AsAny, Downcast};
 use serde::{Deserialize, Serialize};
 
@@ -238,6 +239,13 @@ where
     }
 }
 
+impl ComponentAttribute for Replication {}
+impl<T: ComponentValue> AttributeConstructor<T, Replication> for Replication {
+    fn construct(store: &mut AttributeStore, value: Replication) {
+        store.set(value)
+    }
+}
+
 pub(crate) struct ComponentPath(pub String);
 impl ComponentAttribute for ComponentPath {}
 
@@ -67,12 +67,15 @@ macro_rules! define_external_component_attribute {
         pub struct ExternalComponentAttributes {
             $(pub $field_name: bool,)*
             $(pub $special_field_name: bool,)*
+            #[serde(default)]
+            pub replication: Option<Replication>,
         }
         impl ExternalComponentAttributes {
             pub fn from_existing_component(desc: ComponentDesc) -> Self {
                 Self {
                     $($field_name: desc.has_attribute::<$type_name>(),)*
                     $($special_field_name: desc.has_attribute::<$special_type_name>(),)*
+                    replication: desc.attribute::<Replication>().map(|r| *r),
                 }
             }
 
@@ -95,6 +98,10 @@ macro_rules! define_external_component_attribute {
                 if self.enum_ {
                     <Enum as AttributeConstructor<u32, _>>::construct(store, ());
                 }
+
+                if let Some(replication) = self.replication {
+                    <Replication as AttributeConstructor<T, _>>::construct(store, replication);
+                }
             }
         }
         impl<'a> FromIterator<&'a str> for ExternalComponentAttributes {
@@ -742,6 +742,48 @@ pub fn query_mut<
     TypedReadWriteQuery::new(read_write_components, read_components)
 }
 
+/// The number of entities that are processed together by a thread in the parallel iteration of a
+/// query. Queries that match fewer entities are run on the calling thread, as the work is not
+/// worth distributing.
+pub const PARALLEL_CHUNK_SIZE: usize = 256;
+
+fn par_for_each_chunked<T: Send>(items: Vec<T>, f: impl Fn(T) + Send + Sync) {
+    // There are no threads on the web
+    #[cfg(not(target_os = "unknown"))]
+    if items.len() > PARALLEL_CHUNK_SIZE {
+        use rayon::prelude::*;
+        items
+            .into_par_iter()
+            .with_min_len(PARALLEL_CHUNK_SIZE)
+            .for_each(f);
+        return;
+    }
+    items.into_iter().for_each(f);
+}
+
+fn par_chunks_mut<T: Send>(items: &mut [T], f: impl Fn(&mut [T]) + Send + Sync) {
+    #[cfg(not(target_os = "unknown"))]
+    if items.len() > PARALLEL_CHUNK_SIZE {
+        use rayon::prelude::*;
+        items.par_chunks_mut(PARALLEL_CHUNK_SIZE).for_each(f);
+        return;
+    }
+    items.chunks_mut(PARALLEL_CHUNK_SIZE).for_each(f);
+}
+
+fn par_map_chunked<T: Send, U: Send>(items: Vec<T>, f: impl Fn(T) -> U + Send + Sync) -> Vec<U> {
+    #[cfg(not(target_os = "unknown"))]
+    if items.len() > PARALLEL_CHUNK_SIZE {
+        use rayon::prelude::*;
+        return items
+            .into_par_iter()
+            .with_min_len(PARALLEL_CHUNK_SIZE)
+            .map(f)
+            .collect();
+    }
+    items.into_iter().map(f).collect()
+}
+
 #[derive(Clone)]
 pub struct TypedReadQuery<R> {
     read_components: R,
@@ -818,6 +860,20 @@ impl<'a, R: ComponentQuery<'a> + Clone + 'static> TypedReadQuery<R> {
             .iter(world, state)
             .map(move |acc| (acc.id(), r.get_data(world, &acc)))
     }
+    /// Calls `f` for each entity of the query on the threads of the global thread pool, in chunks
+    /// of [PARALLEL_CHUNK_SIZE] entities, and returns the results in the order of [Self::iter].
+    pub fn par_map<T: Send>(
+        &self,
+        world: &'a World,
+        state: Option<&'a mut QueryState>,
+        f: impl Fn(EntityId, <R as ComponentQuery<'a>>::Data) -> T + Send + Sync,
+    ) -> Vec<T>
+    where
+        <R as ComponentQuery<'a>>::Data: Send,
+    {
+        let items = self.iter(world, state).collect_vec();
+        par_map_chunked(items, |(id, data)| f(id, data))
+    }
     pub fn iter_cloned(
         &self,
         world: &'a World,
@@ -1007,6 +1063,48 @@ impl<'a, RW: ComponentQuery<'a> + Clone + 'static, R: ComponentQuery<'a> + Clone
             )
         })
     }
+    /// Calls `f` for each entity of the query on the threads of the global thread pool, in chunks
+    /// of [PARALLEL_CHUNK_SIZE] entities.
+    ///
+    /// The components are borrowed, and their changes are recorded, on the calling thread before
+    /// `f` is called, so `f` should only access the entity through its arguments.
+    pub fn par_for_each(
+        &self,
+        world: &'a mut World,
+        state: Option<&'a mut QueryState>,
+        f: impl Fn(EntityId, <RW as ComponentQuery<'a>>::DataMut, <R as ComponentQuery<'a>>::Data)
+            + Send
+            + Sync,
+    ) where
+        <RW as ComponentQuery<'a>>::DataMut: Send,
+        <R as ComponentQuery<'a>>::Data: Send,
+    {
+        let items = self.iter(world, state).collect_vec();
+        par_for_each_chunked(items, |(id, rw, r)| f(id, rw, r));
+    }
+    /// Like [Self::par_for_each], but calls `f` with a batch of up to [PARALLEL_CHUNK_SIZE]
+    /// entities at a time, so that their components can be processed together, such as with SIMD
+    /// instructions.
+    #[allow(clippy::type_complexity)]
+    pub fn par_for_each_batch(
+        &self,
+        world: &'a mut World,
+        state: Option<&'a mut QueryState>,
+        f: impl Fn(
+                &mut [(
+                    EntityId,
+                    <RW as ComponentQuery<'a>>::DataMut,
+                    <R as ComponentQuery<'a>>::Data,
+                )],
+            ) + Send
+            + Sync,
+    ) where
+        <RW as ComponentQuery<'a>>::DataMut: Send,
+        <R as ComponentQuery<'a>>::Data: Send,
+    {
+        let mut items = self.iter(world, state).collect_vec();
+        par_chunks_mut(&mut items, f);
+    }
     pub fn to_system<
         F: Fn(&Self, &mut World, Option<&mut QueryState>, &E) + Send + Sync + 'static,
         E: 'static,
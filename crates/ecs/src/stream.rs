@@ -11,7 +11,7 @@ use super::{
     ArchetypeFilter, Component, ComponentValue, Entity, EntityId, FramedEventsReader, Query,
     QueryState, World,
 };
-use crate::{ComponentDesc, ComponentEntry};
+use crate::{ComponentDesc, ComponentEntry, Replication, Serializable};
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct WorldDiff {
@@ -384,12 +384,20 @@ impl Display for WorldChange {
     }
 }
 
+/// Produces the changes to a world since the last diff, respecting the [Replication] of each
+/// component.
 #[derive(Clone)]
 pub struct WorldStream {
     changed_qs: QueryState,
     shape_stream_reader: FramedEventsReader<WorldChange>,
     filter: WorldStreamFilter,
     version: u64,
+    /// The number of diffs produced so far
+    diffs: u64,
+    /// The components with a [Replication::Interval] that have changed, but not been sent yet
+    pending: HashSet<(EntityId, ComponentDesc)>,
+    /// The last serialized value sent of each component with [Replication::OnChange]
+    sent: HashMap<EntityId, HashMap<ComponentDesc, Vec<u8>>>,
 }
 impl WorldStream {
     pub fn new(filter: WorldStreamFilter) -> Self {
@@ -398,6 +406,9 @@ impl WorldStream {
             shape_stream_reader: FramedEventsReader::new(),
             filter,
             version: 0,
+            diffs: 0,
+            pending: HashSet::new(),
+            sent: HashMap::new(),
         }
     }
     pub fn filter(&self) -> &WorldStreamFilter {
@@ -417,19 +428,27 @@ impl WorldStream {
         let mut removed_components = HashSet::new();
         for change in shape_changes.iter() {
             match change {
-                WorldChange::Spawn(id, _) => {
+                WorldChange::Spawn(id, data) => {
                     removed_entities.remove(id);
+                    record_sent(&mut self.sent, *id, data.iter());
                 }
                 WorldChange::Despawn(id) => {
                     removed_entities.insert(*id);
+                    self.sent.remove(id);
                 }
                 WorldChange::AddComponents(id, comps) => {
                     for entry in comps.iter() {
                         removed_components.remove(&(*id, entry.desc()));
                     }
+                    record_sent(&mut self.sent, *id, comps.iter());
                 }
                 WorldChange::RemoveComponents(id, comps) => {
                     removed_components.extend(comps.iter().map(|&desc| (*id, desc)));
+                    if let Some(sent) = self.sent.get_mut(id) {
+                        for desc in comps {
+                            sent.remove(desc);
+                        }
+                    }
                 }
                 _ => {}
             }
@@ -444,6 +463,11 @@ impl WorldStream {
                         arch_comp.component,
                         WorldStreamCompEvent::Set,
                     ) {
+                        let replication = arch_comp
+                            .component
+                            .attribute::<Replication>()
+                            .map(|replication| *replication)
+                            .unwrap_or_default();
                         let reader = self
                             .changed_qs
                             .change_readers
@@ -460,10 +484,23 @@ impl WorldStream {
                                     && !removed_components
                                         .contains(&(entity_id, arch_comp.component))
                                 {
-                                    let entry = sets.entry(entity_id).or_insert_with(Entity::new);
-                                    entry.set_entry(
-                                        world.get_entry(entity_id, arch_comp.component).unwrap(),
-                                    );
+                                    let value =
+                                        world.get_entry(entity_id, arch_comp.component).unwrap();
+                                    match replication {
+                                        Replication::EveryTick => {}
+                                        Replication::Interval(_) => {
+                                            self.pending.insert((entity_id, value.desc()));
+                                            continue;
+                                        }
+                                        Replication::OnChange => {
+                                            if !record_sent(&mut self.sent, entity_id, [&value]) {
+                                                continue;
+                                            }
+                                        }
+                                    }
+                                    sets.entry(entity_id)
+                                        .or_insert_with(Entity::new)
+                                        .set_entry(value);
                                 }
                             }
                         }
@@ -471,6 +508,23 @@ impl WorldStream {
                 }
             }
         }
+        // send the components with an interval that is due
+        self.diffs += 1;
+        let diffs = self.diffs;
+        self.pending.retain(|&(id, desc)| {
+            let Some(Replication::Interval(interval)) = desc.attribute::<Replication>().map(|r| *r)
+            else {
+                return false;
+            };
+            if diffs % interval.max(1) as u64 != 0 {
+                return true;
+            }
+            if let Ok(value) = world.get_entry(id, desc) {
+                sets.entry(id).or_insert_with(Entity::new).set_entry(value);
+            }
+            false
+        });
+
         self.version = world.version();
         let mut changes = shape_changes;
         changes.extend(
@@ -480,3 +534,30 @@ impl WorldStream {
         WorldDiff { changes }
     }
 }
+
+/// Remembers the serialized values of the `entries` of `id` that have [Replication::OnChange].
+/// Returns whether any of them differ from the last values that were sent.
+fn record_sent<'a>(
+    sent: &mut HashMap<EntityId, HashMap<ComponentDesc, Vec<u8>>>,
+    id: EntityId,
+    entries: impl IntoIterator<Item = &'a ComponentEntry>,
+) -> bool {
+    let mut changed = false;
+    for entry in entries {
+        if entry.attribute::<Replication>().map(|r| *r) != Some(Replication::OnChange) {
+            continue;
+        }
+        let Some(serializable) = entry.attribute::<Serializable>() else {
+            continue;
+        };
+        let Ok(value) = serde_json::to_vec(serializable.serialize(entry)) else {
+            continue;
+        };
+        let previous = sent
+            .entry(id)
+            .or_default()
+            .insert(entry.desc(), value.clone());
+        changed |= previous.as_ref() != Some(&value);
+    }
+    changed
+}
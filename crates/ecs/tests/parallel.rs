@@ -0,0 +1,96 @@
+use ambient_ecs::{
+    components, query, query_mut, Entity, EntityId, QueryState, World, PARALLEL_CHUNK_SIZE,
+};
+use itertools::Itertools;
+
+components!("test", {
+    a: f32,
+    b: f32,
+    c: f32,
+});
+
+fn init() {
+    init_components();
+}
+
+fn spawn_entities(world: &mut World, count: usize) -> Vec<EntityId> {
+    (0..count)
+        .map(|i| {
+            let mut entity = Entity::new().with(a(), i as f32).with(b(), 0.);
+            // Spread the entities across archetypes
+            if i % 3 == 0 {
+                entity.set(c(), 0.);
+            }
+            world.spawn(entity)
+        })
+        .collect_vec()
+}
+
+#[test]
+fn par_for_each_writes_all_entities() {
+    init();
+    let mut world = World::new_unknown("par_for_each");
+    let ids = spawn_entities(&mut world, PARALLEL_CHUNK_SIZE * 10 + 1);
+
+    let q = query_mut(b(), a());
+    q.par_for_each(&mut world, None, |_, b, a| *b = a * 2.);
+
+    for (i, id) in ids.into_iter().enumerate() {
+        assert_eq!(world.get(id, b()).unwrap(), i as f32 * 2.);
+    }
+}
+
+#[test]
+fn par_for_each_records_changes() {
+    init();
+    let mut world = World::new_unknown("par_for_each_changes");
+    let ids = spawn_entities(&mut world, PARALLEL_CHUNK_SIZE * 4);
+
+    let changed = query(b().changed());
+    let mut state = QueryState::new();
+    assert_eq!(changed.iter(&world, Some(&mut state)).count(), ids.len());
+    assert_eq!(changed.iter(&world, Some(&mut state)).count(), 0);
+
+    query_mut(b(), a()).par_for_each(&mut world, None, |_, b, a| *b = *a);
+    assert_eq!(
+        changed
+            .iter(&world, Some(&mut state))
+            .map(|(id, _)| id)
+            .sorted()
+            .collect_vec(),
+        ids.into_iter().sorted().collect_vec()
+    );
+}
+
+#[test]
+fn par_for_each_batch_writes_all_entities() {
+    init();
+    let mut world = World::new_unknown("par_for_each_batch");
+    let ids = spawn_entities(&mut world, PARALLEL_CHUNK_SIZE * 10 + 1);
+
+    let q = query_mut(b(), a());
+    q.par_for_each_batch(&mut world, None, |batch| {
+        assert!(batch.len() <= PARALLEL_CHUNK_SIZE);
+        for (_, b, a) in batch {
+            **b = **a * 2.;
+        }
+    });
+
+    for (i, id) in ids.into_iter().enumerate() {
+        assert_eq!(world.get(id, b()).unwrap(), i as f32 * 2.);
+    }
+}
+
+#[test]
+fn par_map_keeps_order() {
+    init();
+    let mut world = World::new_unknown("par_map");
+    spawn_entities(&mut world, PARALLEL_CHUNK_SIZE * 10 + 1);
+
+    let q = query(a());
+    let expected = q
+        .iter(&world, None)
+        .map(|(id, &a)| (id, a + 1.))
+        .collect_vec();
+    assert_eq!(q.par_map(&world, None, |id, &a| (id, a + 1.)), expected);
+}
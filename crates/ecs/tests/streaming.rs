@@ -1,8 +1,8 @@
 use std::sync::Arc;
 
 use ambient_ecs::{
-    components, ArchetypeFilter, Entity, EntityId, FrozenWorldDiff, Serializable, World, WorldDiff,
-    WorldStream, WorldStreamFilter,
+    components, ArchetypeFilter, Entity, EntityId, FrozenWorldDiff, Replication, Serializable,
+    World, WorldDiff, WorldStream, WorldStreamFilter,
 };
 use itertools::Itertools;
 
@@ -12,6 +12,10 @@ components!("test", {
     b: f32,
     c: f32,
     no_sync: (),
+    @[Serializable, Replication[Replication::Interval(2)]]
+    slow: f32,
+    @[Serializable, Replication[Replication::OnChange]]
+    rare: f32,
 });
 
 fn init() {
@@ -88,6 +92,43 @@ fn streaming() {
     assert_eq!(dump_content_string(&source), dump_content_string(&dest));
 }
 
+#[test]
+fn streaming_replication() {
+    init();
+    let mut source = World::new_with_config(
+        "streaming_replication_src",
+        ambient_ecs::WorldContext::Unknown,
+        true,
+    );
+    source.init_shape_change_tracking();
+    let mut dest = World::new_unknown("streaming_replication_dst");
+    let mut stream = WorldStream::new(WorldStreamFilter::new(
+        ArchetypeFilter::new(),
+        Arc::new(|_, _| true),
+    ));
+
+    let x = Entity::new()
+        .with(slow(), 1.)
+        .with(rare(), 1.)
+        .spawn(&mut source);
+    stream.next_diff(&source).apply(&mut dest, Entity::new());
+    stream.next_diff(&source).apply(&mut dest, Entity::new());
+
+    // Interval components are only sent every second diff
+    source.set(x, slow(), 2.).unwrap();
+    stream.next_diff(&source).apply(&mut dest, Entity::new());
+    assert_eq!(dest.get(x, slow()), Ok(1.));
+    stream.next_diff(&source).apply(&mut dest, Entity::new());
+    assert_eq!(dest.get(x, slow()), Ok(2.));
+
+    // On-change components are not sent when they are set to the same value
+    source.set(x, rare(), 1.).unwrap();
+    assert!(stream.next_diff(&source).changes.is_empty());
+    source.set(x, rare(), 3.).unwrap();
+    stream.next_diff(&source).apply(&mut dest, Entity::new());
+    assert_eq!(dump_content_string(&source), dump_content_string(&dest));
+}
+
 fn dump_content_string(world: &World) -> String {
     let mut entities = world.entities();
     entities.sort_unstable_by_key(|(id, _)| *id);
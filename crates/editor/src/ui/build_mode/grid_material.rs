@@ -42,6 +42,7 @@ impl SyncAssetKey<Arc<MaterialShader>> for GridShaderKey {
                 ShaderModule::new("GridMaterial", include_file!("grid_material.wgsl"))
                     .with_binding_desc(grid_shader_layout()),
             ),
+            bindless: false,
         })
     }
 }
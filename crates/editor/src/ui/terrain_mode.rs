@@ -256,6 +256,7 @@ impl SyncAssetKey<Arc<MaterialShader>> for BrushCursorShaderMaterialKey {
                 ShaderModule::new("BrushCursor", [include_str!("brush_cursor.wgsl")].concat())
                     .with_binding_desc(get_brush_cursor_layout()),
             ),
+            bindless: false,
         })
     }
 }
@@ -1,40 +1,267 @@
+use std::cmp::Ordering;
+
 use ambient_ecs::{
     generated::{
         input::components::mouse_over_entity,
-        messages::WindowMouseInput,
+        layout::components::{height, width},
+        messages::{WindowKeyboardInput, WindowMouseInput},
+        transform::components::local_to_world,
         ui::{
-            components::{focus, focusable},
-            messages::FocusChanged,
+            components::{
+                focus, focus_cancel_action, focus_default_action, focus_navigable, focus_order,
+                focusable,
+            },
+            messages::{FocusActivated, FocusChanged},
         },
     },
-    read_messages, world_events, FnSystem, SystemGroup, WorldEventReader, WorldEventsExt,
+    query, read_messages, world_events, FnSystem, SystemGroup, World, WorldEventReader,
+    WorldEventsExt,
 };
+use ambient_shared_types::{ModifiersState, VirtualKeyCode};
+use glam::Vec2;
+
+/// An action that moves or uses the focus without the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusAction {
+    /// Focus the next navigable entity in the tab order.
+    Next,
+    /// Focus the previous navigable entity in the tab order.
+    Previous,
+    /// Focus the nearest navigable entity above the focused one.
+    Up,
+    /// Focus the nearest navigable entity below the focused one.
+    Down,
+    /// Focus the nearest navigable entity to the left of the focused one.
+    Left,
+    /// Focus the nearest navigable entity to the right of the focused one.
+    Right,
+    /// Activate the focused navigable entity, or the default action if none is focused.
+    Activate,
+    /// Activate the cancel action.
+    Cancel,
+}
+impl FocusAction {
+    fn from_key(key: VirtualKeyCode, modifiers: ModifiersState) -> Option<Self> {
+        Some(match key {
+            VirtualKeyCode::Tab if modifiers.shift() => Self::Previous,
+            VirtualKeyCode::Tab => Self::Next,
+            VirtualKeyCode::Up => Self::Up,
+            VirtualKeyCode::Down => Self::Down,
+            VirtualKeyCode::Left => Self::Left,
+            VirtualKeyCode::Right => Self::Right,
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter | VirtualKeyCode::Space => {
+                Self::Activate
+            }
+            VirtualKeyCode::Escape => Self::Cancel,
+            _ => return None,
+        })
+    }
+    fn direction(self) -> Option<Vec2> {
+        match self {
+            Self::Up => Some(-Vec2::Y),
+            Self::Down => Some(Vec2::Y),
+            Self::Left => Some(-Vec2::X),
+            Self::Right => Some(Vec2::X),
+            _ => None,
+        }
+    }
+}
 
 pub fn systems() -> SystemGroup {
-    let mut reader = WorldEventReader::new();
+    let mut mouse_reader = WorldEventReader::new();
+    let mut keyboard_reader = WorldEventReader::new();
     SystemGroup::new(
         "focus",
-        vec![Box::new(FnSystem::new(move |world, _| {
-            for event in
-                read_messages::<WindowMouseInput>(&mut reader, world.resource(world_events()))
-            {
-                if event.button == 0 {
-                    let mouse_over = *world.resource(mouse_over_entity());
-                    let focus_id = world
-                        .get_cloned(mouse_over, focusable())
-                        .unwrap_or_default();
-                    let cur_focus = world.resource(focus()).clone();
-                    if cur_focus != focus_id {
-                        *world.resource_mut(focus()) = focus_id.clone();
-                        world
-                            .resource_mut(world_events())
-                            .add_message(FocusChanged {
-                                from_external: false,
-                                focus: focus_id,
-                            });
+        vec![
+            Box::new(FnSystem::new(move |world, _| {
+                for event in read_messages::<WindowMouseInput>(
+                    &mut mouse_reader,
+                    world.resource(world_events()),
+                ) {
+                    if event.button == 0 {
+                        let mouse_over = *world.resource(mouse_over_entity());
+                        let focus_id = world
+                            .get_cloned(mouse_over, focusable())
+                            .unwrap_or_default();
+                        set_focus(world, focus_id);
                     }
                 }
-            }
-        }))],
+            })),
+            Box::new(FnSystem::new(move |world, _| {
+                for event in read_messages::<WindowKeyboardInput>(
+                    &mut keyboard_reader,
+                    world.resource(world_events()),
+                ) {
+                    if !event.pressed || !accepts_navigation(world) {
+                        continue;
+                    }
+                    let Some(key) = event.keycode.and_then(|key| key.parse().ok()) else {
+                        continue;
+                    };
+                    let modifiers = ModifiersState::from_bits_truncate(event.modifiers);
+                    let Some(action) = FocusAction::from_key(key, modifiers) else {
+                        continue;
+                    };
+                    // Space is often bound to a game action, so it only activates focused entities
+                    if key == VirtualKeyCode::Space && world.resource(focus()).is_empty() {
+                        continue;
+                    }
+                    navigate(world, action);
+                }
+            })),
+        ],
     )
 }
+
+/// Sets the focus to `focus_id`, and notifies all listeners if it changed.
+pub fn set_focus(world: &mut World, focus_id: String) {
+    let cur_focus = world.resource(focus()).clone();
+    if cur_focus != focus_id {
+        *world.resource_mut(focus()) = focus_id.clone();
+        world
+            .resource_mut(world_events())
+            .add_message(FocusChanged {
+                from_external: false,
+                focus: focus_id,
+            });
+    }
+}
+
+/// Performs `action`, moving the focus between the navigable entities or activating one of them.
+pub fn navigate(world: &mut World, action: FocusAction) {
+    let cur_focus = world.resource(focus()).clone();
+    match action {
+        FocusAction::Activate => {
+            let target = if navigables(world).iter().any(|n| n.focus == cur_focus) {
+                Some(cur_focus)
+            } else {
+                find_action(world, true)
+            };
+            if let Some(focus) = target {
+                activate(world, focus);
+            }
+        }
+        FocusAction::Cancel => {
+            if let Some(focus) = find_action(world, false) {
+                activate(world, focus);
+            }
+        }
+        FocusAction::Next | FocusAction::Previous => {
+            let mut navigables = navigables(world);
+            if navigables.is_empty() {
+                return;
+            }
+            navigables.sort_by(Navigable::tab_order);
+            let len = navigables.len();
+            let index = match navigables.iter().position(|n| n.focus == cur_focus) {
+                Some(index) if action == FocusAction::Next => (index + 1) % len,
+                Some(index) => (index + len - 1) % len,
+                None if action == FocusAction::Next => 0,
+                None => len - 1,
+            };
+            let focus = navigables.swap_remove(index).focus;
+            set_focus(world, focus);
+        }
+        FocusAction::Up | FocusAction::Down | FocusAction::Left | FocusAction::Right => {
+            let direction = action.direction().unwrap();
+            let navigables = navigables(world);
+            let target = match navigables.iter().find(|n| n.focus == cur_focus) {
+                Some(current) => nearest_in_direction(current, &navigables, direction),
+                None => navigables.iter().min_by(|a, b| a.tab_order(b)),
+            };
+            if let Some(target) = target {
+                let focus = target.focus.clone();
+                set_focus(world, focus);
+            }
+        }
+    }
+}
+
+/// Keyboard navigation is only used when nothing, or one of the navigable entities, is focused, so
+/// that it does not interfere with text fields or the game.
+fn accepts_navigation(world: &World) -> bool {
+    let cur_focus = world.resource(focus());
+    cur_focus.is_empty() || navigables(world).iter().any(|n| &n.focus == cur_focus)
+}
+
+fn activate(world: &mut World, focus: String) {
+    world
+        .resource_mut(world_events())
+        .add_message(FocusActivated { focus });
+}
+
+/// Returns the focus id of the default action if `default` is set, and of the cancel action
+/// otherwise.
+fn find_action(world: &World, default: bool) -> Option<String> {
+    let q = if default {
+        query(focusable()).incl(focus_default_action())
+    } else {
+        query(focusable()).incl(focus_cancel_action())
+    };
+    q.iter(world, None).map(|(_, focus)| focus.clone()).next()
+}
+
+#[derive(Debug, Clone)]
+struct Navigable {
+    focus: String,
+    order: i32,
+    /// The screen-space bounds of the entity.
+    min: Vec2,
+    max: Vec2,
+}
+impl Navigable {
+    fn center(&self) -> Vec2 {
+        (self.min + self.max) / 2.
+    }
+    fn tab_order(&self, other: &Self) -> Ordering {
+        self.order
+            .cmp(&other.order)
+            .then(self.min.y.total_cmp(&other.min.y))
+            .then(self.min.x.total_cmp(&other.min.x))
+    }
+}
+
+fn navigables(world: &World) -> Vec<Navigable> {
+    query((focusable(), local_to_world()))
+        .incl(focus_navigable())
+        .iter(world, None)
+        .map(|(id, (focus, transform))| {
+            let (scale, _, position) = transform.to_scale_rotation_translation();
+            let size = Vec2::new(
+                world.get(id, width()).unwrap_or(0.),
+                world.get(id, height()).unwrap_or(0.),
+            ) * scale.truncate();
+            let min = position.truncate();
+            Navigable {
+                focus: focus.clone(),
+                order: world.get(id, focus_order()).unwrap_or(0),
+                min,
+                max: min + size,
+            }
+        })
+        .collect()
+}
+
+/// Finds the navigable entity closest to `current` in `direction`. Entities that are further off
+/// the axis of movement are penalized, so that moving down in a grid stays in the same column.
+fn nearest_in_direction<'a>(
+    current: &Navigable,
+    navigables: &'a [Navigable],
+    direction: Vec2,
+) -> Option<&'a Navigable> {
+    let from = current.center();
+    navigables
+        .iter()
+        .filter(|n| n.focus != current.focus)
+        .filter_map(|n| {
+            let delta = n.center() - from;
+            let along = delta.dot(direction);
+            if along <= 0. {
+                return None;
+            }
+            let across = (delta - direction * along).length();
+            Some((n, along + across * 2.))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(n, _)| n)
+}
@@ -146,6 +146,7 @@ impl SubRenderer for GizmoRenderer {
                         write_mask: ColorWrites::ALL,
                     })],
                     cull_mode: None,
+                    sample_count: gpu.sample_count,
                     ..Default::default()
                 },
             )
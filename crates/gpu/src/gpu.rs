@@ -8,10 +8,12 @@ use glam::{uvec2, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
 use wgpu::{InstanceDescriptor, PresentMode, TextureFormat};
 use winit::window::Window;
 
-// #[cfg(debug_assertions)]
-pub const DEFAULT_SAMPLE_COUNT: u32 = 1;
-// #[cfg(not(debug_assertions))]
-// pub const DEFAULT_SAMPLE_COUNT: u32 = 4;
+use crate::{pipeline_cache::PipelineCache, shader_module::DEPTH_FORMAT};
+
+/// The features that let shaders index arrays of textures and samplers with an index that
+/// differs between draws, which the renderer uses to bind the textures of all materials at once.
+pub const BINDLESS_FEATURES: wgpu::Features = wgpu::Features::TEXTURE_BINDING_ARRAY
+    .union(wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING);
 
 #[derive(Debug)]
 pub struct GpuKey;
@@ -25,8 +27,15 @@ pub struct Gpu {
     pub swapchain_format: Option<TextureFormat>,
     pub swapchain_mode: Option<PresentMode>,
     pub adapter: wgpu::Adapter,
+    /// The number of samples per pixel of the render targets of the renderer, and of the
+    /// pipelines that render to them
+    pub sample_count: u32,
     /// If this is true, we don't need to use blocking device.polls, since they are assumed to be polled elsewhere
     pub will_be_polled: bool,
+    /// Whether the device supports [BINDLESS_FEATURES]
+    pub bindless: bool,
+    /// The shaders and pipelines that have been created with this device
+    pub pipeline_cache: PipelineCache,
 }
 
 impl Gpu {
@@ -121,6 +130,15 @@ impl Gpu {
             }
         };
 
+        // Binding arrays are not part of WebGPU, and are only enabled where the adapter supports
+        // indexing them per draw
+        let bindless = adapter.features().contains(BINDLESS_FEATURES);
+        let features = if bindless {
+            features | BINDLESS_FEATURES
+        } else {
+            features
+        };
+
         tracing::info!("Using device features: {features:?}");
 
         let (device, queue) = adapter
@@ -129,12 +147,24 @@ impl Gpu {
                     label: None,
                     features: wgpu::Features::default()
                         | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
-                        // | wgpu::Features::POLYGON_MODE_LINE
+                        // Used by the wireframe debug view, where supported
+                        | (adapter.features() & wgpu::Features::POLYGON_MODE_LINE)
                         | features,
                     limits: wgpu::Limits {
                         max_bind_groups: 8,
                         max_storage_buffer_binding_size: adapter_limits
                             .max_storage_buffer_binding_size,
+                        // The binding arrays count towards these limits, one per element
+                        max_sampled_textures_per_shader_stage: if bindless {
+                            adapter_limits.max_sampled_textures_per_shader_stage
+                        } else {
+                            wgpu::Limits::default().max_sampled_textures_per_shader_stage
+                        },
+                        max_samplers_per_shader_stage: if bindless {
+                            adapter_limits.max_samplers_per_shader_stage
+                        } else {
+                            wgpu::Limits::default().max_samplers_per_shader_stage
+                        },
                         ..Default::default()
                     },
                 },
@@ -167,6 +197,13 @@ impl Gpu {
 
         tracing::debug!("Swapchain present mode: {swapchain_mode:?}");
 
+        let sample_count = Self::supported_sample_count(
+            &adapter,
+            swapchain_format.unwrap_or(TextureFormat::Rgba8UnormSrgb),
+            settings.msaa_sample_count(),
+        );
+        tracing::debug!("Sample count: {sample_count}");
+
         if let (Some(window), Some(surface), Some(mode), Some(format)) =
             (window, &surface, swapchain_mode, swapchain_format)
         {
@@ -184,10 +221,45 @@ impl Gpu {
             swapchain_format,
             swapchain_mode,
             adapter,
+            sample_count,
             will_be_polled,
+            bindless,
+            pipeline_cache: PipelineCache::default(),
         })
     }
 
+    /// The highest sample count up to `requested` that the color, normals and depth formats of
+    /// the render targets all support.
+    fn supported_sample_count(
+        adapter: &wgpu::Adapter,
+        color_format: TextureFormat,
+        requested: u32,
+    ) -> u32 {
+        let formats = [
+            color_format,
+            color_format.remove_srgb_suffix(),
+            DEPTH_FORMAT,
+        ];
+        let supported = [16, 8, 4, 2]
+            .into_iter()
+            .filter(|&count| count <= requested)
+            .find(|&count| {
+                formats.iter().all(|&format| {
+                    adapter
+                        .get_texture_format_features(format)
+                        .flags
+                        .sample_count_supported(count)
+                })
+            })
+            .unwrap_or(1);
+        if supported != requested {
+            tracing::warn!(
+                "{requested}x MSAA is not supported by the GPU; using {supported}x instead"
+            );
+        }
+        supported
+    }
+
     pub fn resize(&self, size: winit::dpi::PhysicalSize<u32>) {
         if let Some(surface) = &self.surface {
             if size.width > 0 && size.height > 0 {
@@ -205,6 +277,25 @@ impl Gpu {
     pub fn sc_desc(&self, size: UVec2) -> wgpu::SurfaceConfiguration {
         Self::create_sc_desc(self.swapchain_format(), self.swapchain_mode(), size)
     }
+    /// Runs `f`, and returns the validation error of the wgpu calls that it makes, if any.
+    ///
+    /// The errors can only be read synchronously on native, so they are still reported to the
+    /// device on the web.
+    pub fn catch_validation_error<T>(&self, f: impl FnOnce() -> T) -> anyhow::Result<T> {
+        #[cfg(not(target_os = "unknown"))]
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let result = f();
+
+        #[cfg(not(target_os = "unknown"))]
+        {
+            use futures::FutureExt;
+            if let Some(Some(err)) = self.device.pop_error_scope().now_or_never() {
+                anyhow::bail!("{err}");
+            }
+        }
+        Ok(result)
+    }
     fn create_sc_desc(
         format: TextureFormat,
         present_mode: PresentMode,
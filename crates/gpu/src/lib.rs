@@ -5,6 +5,7 @@ pub mod gpu_run;
 pub mod mesh_buffer;
 pub mod mipmap;
 pub mod multi_buffer;
+pub mod pipeline_cache;
 pub mod sampler;
 pub mod shader_module;
 pub mod std_assets;
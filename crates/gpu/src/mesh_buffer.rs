@@ -50,6 +50,13 @@ impl GpuMesh {
     pub fn from_mesh(gpu: &Gpu, assets: &AssetCache, mesh: &Mesh) -> Arc<GpuMesh> {
         MeshBufferKey.get(assets).lock().insert(gpu, mesh)
     }
+    /// Replaces the contents of the mesh; see [MeshBuffer::update_mesh].
+    pub fn update(&self, gpu: &Gpu, assets: &AssetCache, mesh: &Mesh) {
+        MeshBufferKey
+            .get(assets)
+            .lock()
+            .update_mesh(gpu, self, mesh)
+    }
     pub fn index(&self) -> GpuMeshIndex {
         self.index
     }
@@ -134,6 +141,9 @@ pub struct MeshBuffer {
     meshes: Vec<Option<InternalMesh>>,
     to_remove: Arc<Mutex<Vec<GpuMeshIndex>>>,
     free_indices: Vec<GpuMeshIndex>,
+    /// The earliest space that was left behind by a mesh that was moved by
+    /// [MeshBuffer::update_mesh], which is reclaimed by the next [MeshBuffer::update]
+    stale: Option<MeshMetadata>,
 }
 
 impl MeshBuffer {
@@ -175,98 +185,24 @@ impl MeshBuffer {
             meshes: Vec::new(),
             to_remove: Arc::new(Mutex::new(Vec::new())),
             free_indices: Vec::new(),
+            stale: None,
         }
     }
 
     pub fn insert(&mut self, gpu: &Gpu, mesh: &Mesh) -> Arc<GpuMesh> {
-        let metadata = MeshMetadata {
-            base_offset: self.base_buffer.front.len() as u32,
-            skinned_offset: self.skinned_buffer.front.len() as u32,
-            index_offset: self.index_buffer.front.len() as u32,
-            index_count: mesh.index_count(),
-        };
-
-        let mut internal_mesh = InternalMesh {
-            metadata,
-            ..Default::default()
-        };
-
-        // Pad all vertex attributes to match vertex positions buffer.
-        {
-            let pos = mesh.positions();
-            let norm = mesh.normals();
-            let tan = mesh.tangents();
-            let uv = mesh.texcoords(0);
-
-            let len = ([pos.len(), norm.len(), tan.len(), uv.len()])
-                .into_iter()
-                .max()
-                .unwrap_or(0);
-
-            let mut data = vec![BaseMesh::default(); len];
-
-            pos.iter()
-                .zip(&mut data)
-                .for_each(|(src, dst)| dst.position = src.extend(0.0));
-            norm.iter()
-                .zip(&mut data)
-                .for_each(|(src, dst)| dst.normal = src.extend(0.0));
-            tan.iter()
-                .zip(&mut data)
-                .for_each(|(src, dst)| dst.tangent = src.extend(0.0));
-            uv.iter()
-                .zip(&mut data)
-                .for_each(|(src, dst)| dst.texcoord0 = *src);
-
-            self.base_buffer
-                .front
-                .set_len(gpu, self.base_buffer.front.len() + data.len());
-
-            self.base_buffer
-                .front
-                .write(gpu, metadata.base_offset as usize, &data);
-
-            internal_mesh.base_count += data.len() as u64;
-        }
-
-        if !mesh.joint_indices().is_empty() && !mesh.joint_weights().is_empty() {
-            let joints = mesh.joint_indices();
-            let weights = mesh.joint_weights();
-
-            let len = joints.len().max(weights.len());
-
-            let mut data = vec![SkinnedMesh::default(); len];
-
-            joints
-                .iter()
-                .zip(&mut data)
-                .for_each(|(src, dst)| dst.joint = *src);
-            weights
-                .iter()
-                .zip(&mut data)
-                .for_each(|(src, dst)| dst.weights = *src);
-
-            self.skinned_buffer
-                .front
-                .set_len(gpu, self.skinned_buffer.front.len() + len);
-
-            self.skinned_buffer
-                .front
-                .write(gpu, metadata.skinned_offset as usize, &data);
-
-            internal_mesh.skinned_count += len as u64;
-        }
-
-        self.index_buffer.front.set_len(
+        let base = Self::base_data(mesh);
+        let skinned = Self::skinned_data(mesh);
+        let mut internal_mesh =
+            self.allocate(gpu, base.len(), skinned.len(), mesh.index_count() as usize);
+        internal_mesh.metadata.index_count = mesh.index_count();
+        self.write_mesh(
             gpu,
-            self.index_buffer.front.len() + mesh.index_count() as usize,
+            &internal_mesh.metadata,
+            &base,
+            &skinned,
+            mesh.indices(),
         );
-
-        self.index_buffer
-            .front
-            .write(gpu, metadata.index_offset as usize, mesh.indices());
-
-        internal_mesh.index_count = mesh.index_count().try_into().unwrap();
+        let metadata = internal_mesh.metadata;
 
         let metadata_offset = if let Some(offset) = self.free_indices.pop() {
             self.meshes[offset as usize] = Some(internal_mesh);
@@ -293,33 +229,168 @@ impl MeshBuffer {
         })
     }
 
+    /// Replaces the contents of `mesh` with `data`, keeping its index, so that the entities that
+    /// use it don't have to be updated.
+    ///
+    /// The mesh is written in place if it fits in the space that was allocated for it. Otherwise
+    /// it's moved to the end of the buffers, and the space that it leaves behind is reclaimed by
+    /// the next [MeshBuffer::update].
+    pub fn update_mesh(&mut self, gpu: &Gpu, mesh: &GpuMesh, data: &Mesh) {
+        let base = Self::base_data(data);
+        let skinned = Self::skinned_data(data);
+        let index_count = data.index_count() as usize;
+
+        let mut internal_mesh = self.meshes[mesh.index as usize].clone().unwrap();
+        let fits = base.len() as u64 <= internal_mesh.base_count
+            && skinned.len() as u64 <= internal_mesh.skinned_count
+            && index_count as u64 <= internal_mesh.index_count;
+        if !fits {
+            let previous = internal_mesh.metadata;
+            self.stale = Some(match self.stale {
+                Some(stale) if stale.base_offset < previous.base_offset => stale,
+                _ => previous,
+            });
+            internal_mesh = self.allocate(gpu, base.len(), skinned.len(), index_count);
+        }
+        internal_mesh.metadata.index_count = data.index_count();
+        self.write_mesh(
+            gpu,
+            &internal_mesh.metadata,
+            &base,
+            &skinned,
+            data.indices(),
+        );
+
+        self.metadata_buffer
+            .write(gpu, mesh.index as usize, &[internal_mesh.metadata]);
+        self.meshes[mesh.index as usize] = Some(internal_mesh);
+
+        MESHES_TOTAL_SIZE.store(self.size() as usize, Ordering::SeqCst);
+    }
+
+    /// The vertex attributes of `mesh`, padded to the length of the longest attribute.
+    fn base_data(mesh: &Mesh) -> Vec<BaseMesh> {
+        let pos = mesh.positions();
+        let norm = mesh.normals();
+        let tan = mesh.tangents();
+        let uv = mesh.texcoords(0);
+
+        let len = ([pos.len(), norm.len(), tan.len(), uv.len()])
+            .into_iter()
+            .max()
+            .unwrap_or(0);
+
+        let mut data = vec![BaseMesh::default(); len];
+
+        pos.iter()
+            .zip(&mut data)
+            .for_each(|(src, dst)| dst.position = src.extend(0.0));
+        norm.iter()
+            .zip(&mut data)
+            .for_each(|(src, dst)| dst.normal = src.extend(0.0));
+        tan.iter()
+            .zip(&mut data)
+            .for_each(|(src, dst)| dst.tangent = src.extend(0.0));
+        uv.iter()
+            .zip(&mut data)
+            .for_each(|(src, dst)| dst.texcoord0 = *src);
+        data
+    }
+
+    /// The joints and weights of `mesh`, or nothing if it isn't skinned.
+    fn skinned_data(mesh: &Mesh) -> Vec<SkinnedMesh> {
+        if mesh.joint_indices().is_empty() || mesh.joint_weights().is_empty() {
+            return Vec::new();
+        }
+        let joints = mesh.joint_indices();
+        let weights = mesh.joint_weights();
+
+        let len = joints.len().max(weights.len());
+
+        let mut data = vec![SkinnedMesh::default(); len];
+
+        joints
+            .iter()
+            .zip(&mut data)
+            .for_each(|(src, dst)| dst.joint = *src);
+        weights
+            .iter()
+            .zip(&mut data)
+            .for_each(|(src, dst)| dst.weights = *src);
+        data
+    }
+
+    /// Allocates space for a mesh at the end of the buffers.
+    fn allocate(
+        &mut self,
+        gpu: &Gpu,
+        base_count: usize,
+        skinned_count: usize,
+        index_count: usize,
+    ) -> InternalMesh {
+        let metadata = MeshMetadata {
+            base_offset: self.base_buffer.front.len() as u32,
+            skinned_offset: self.skinned_buffer.front.len() as u32,
+            index_offset: self.index_buffer.front.len() as u32,
+            index_count: index_count as u32,
+        };
+
+        self.base_buffer
+            .front
+            .set_len(gpu, self.base_buffer.front.len() + base_count);
+        self.skinned_buffer
+            .front
+            .set_len(gpu, self.skinned_buffer.front.len() + skinned_count);
+        self.index_buffer
+            .front
+            .set_len(gpu, self.index_buffer.front.len() + index_count);
+
+        InternalMesh {
+            metadata,
+            base_count: base_count as u64,
+            skinned_count: skinned_count as u64,
+            index_count: index_count as u64,
+        }
+    }
+
+    fn write_mesh(
+        &self,
+        gpu: &Gpu,
+        metadata: &MeshMetadata,
+        base: &[BaseMesh],
+        skinned: &[SkinnedMesh],
+        indices: &[u32],
+    ) {
+        self.base_buffer
+            .front
+            .write(gpu, metadata.base_offset as usize, base);
+        if !skinned.is_empty() {
+            self.skinned_buffer
+                .front
+                .write(gpu, metadata.skinned_offset as usize, skinned);
+        }
+        self.index_buffer
+            .front
+            .write(gpu, metadata.index_offset as usize, indices);
+    }
+
     pub fn update(&mut self, gpu: &Gpu) {
         let to_remove = {
             let mut to_remove = self.to_remove.lock();
             to_remove.drain(..).collect_vec()
         };
 
-        if to_remove.is_empty() {
-            return;
-        }
-
-        // We let the meshes before the first removed mesh just remain; no need to copy them around
-        let first_to_remove_mesh_index = *to_remove
+        // We let the meshes before the first removed or moved mesh just remain; no need to copy
+        // them around
+        let base_metadata = match to_remove
             .iter()
-            .sorted_by_key(|index| {
-                self.meshes[**index as usize]
-                    .as_ref()
-                    .unwrap()
-                    .metadata
-                    .base_offset
-            })
-            .next()
-            .unwrap();
-
-        let base_metadata = self.meshes[first_to_remove_mesh_index as usize]
-            .as_ref()
-            .unwrap()
-            .metadata;
+            .map(|index| self.meshes[*index as usize].as_ref().unwrap().metadata)
+            .chain(self.stale.take())
+            .min_by_key(|metadata| metadata.base_offset)
+        {
+            Some(base_metadata) => base_metadata,
+            None => return,
+        };
 
         let mut encoder = gpu
             .device
@@ -369,7 +440,7 @@ impl MeshBuffer {
         let mut cursor = MeshMetadata::default();
         for (index, mesh) in update_meshes_sorted {
             self.meshes[index].as_mut().unwrap().metadata = MeshMetadata {
-                index_count: mesh.index_count as u32,
+                index_count: mesh.metadata.index_count,
                 base_offset: base_metadata.base_offset + cursor.base_offset,
                 skinned_offset: base_metadata.skinned_offset + cursor.skinned_offset,
                 index_offset: base_metadata.index_offset + cursor.index_offset,
@@ -0,0 +1,322 @@
+//! Caches the shaders and pipelines that have been created, keyed by a hash of what they were
+//! created from, so that identical permutations are only compiled once.
+//!
+//! Once [PipelineCache::prewarm] has been called, the descriptions of the shaders and pipelines
+//! are also recorded on disk, and created again when the next run starts instead of when they are
+//! first drawn with, which would otherwise cause a hitch mid-game. wgpu doesn't expose the
+//! drivers' caches of compiled pipelines, so it's the descriptions that are persisted; most
+//! drivers keep their own cache of the compiled code.
+
+use std::{collections::HashMap, sync::Arc};
+
+use ambient_native_std::{asset_cache::AssetCache, sha256_digest};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    gpu::Gpu,
+    shader_module::{ComputePipeline, GraphicsPipeline, GraphicsPipelineInfo, Shader},
+};
+
+/// The size above which the file is discarded, to keep it from growing forever as shaders change.
+#[cfg(not(target_os = "unknown"))]
+const MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// What a [Shader] is created from, after its modules have been preprocessed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ShaderRecord {
+    pub label: String,
+    pub source: String,
+    /// The label and entries of each bind group layout
+    pub bind_groups: Vec<(String, Vec<wgpu::BindGroupLayoutEntry>)>,
+}
+
+/// The owned version of [GraphicsPipelineInfo], which can be recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct GraphicsPipelineRecord {
+    vs_main: String,
+    fs_main: String,
+    depth: Option<wgpu::DepthStencilState>,
+    targets: Vec<Option<wgpu::ColorTargetState>>,
+    front_face: wgpu::FrontFace,
+    cull_mode: Option<wgpu::Face>,
+    topology: wgpu::PrimitiveTopology,
+    polygon_mode: wgpu::PolygonMode,
+    sample_count: u32,
+}
+
+impl From<&GraphicsPipelineInfo<'_>> for GraphicsPipelineRecord {
+    fn from(info: &GraphicsPipelineInfo) -> Self {
+        Self {
+            vs_main: info.vs_main.to_string(),
+            fs_main: info.fs_main.to_string(),
+            depth: info.depth.clone(),
+            targets: info.targets.to_vec(),
+            front_face: info.front_face,
+            cull_mode: info.cull_mode,
+            topology: info.topology,
+            polygon_mode: info.polygon_mode,
+            sample_count: info.sample_count,
+        }
+    }
+}
+
+impl GraphicsPipelineRecord {
+    fn info(&self) -> GraphicsPipelineInfo {
+        GraphicsPipelineInfo {
+            vs_main: &self.vs_main,
+            fs_main: &self.fs_main,
+            depth: self.depth.clone(),
+            targets: &self.targets,
+            front_face: self.front_face,
+            cull_mode: self.cull_mode,
+            topology: self.topology,
+            polygon_mode: self.polygon_mode,
+            sample_count: self.sample_count,
+        }
+    }
+}
+
+/// A line of the cache file.
+#[derive(Debug, Serialize, Deserialize)]
+enum CacheRecord {
+    /// The first line of the file. The file is discarded if it was written by another version of
+    /// Ambient, or for another adapter.
+    Header {
+        version: String,
+        adapter: String,
+    },
+    Shader(ShaderRecord),
+    Graphics {
+        shader: String,
+        pipeline: GraphicsPipelineRecord,
+    },
+    Compute {
+        shader: String,
+        entry_point: String,
+    },
+}
+
+#[derive(Default)]
+pub struct PipelineCache {
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    shaders: HashMap<String, Arc<Shader>>,
+    graphics: HashMap<String, GraphicsPipeline>,
+    compute: HashMap<String, ComputePipeline>,
+    /// The file that the new shaders and pipelines are recorded to, once the cache has been
+    /// prewarmed
+    #[cfg(not(target_os = "unknown"))]
+    file: Option<std::fs::File>,
+}
+
+impl PipelineCache {
+    /// Returns the shader created from `record`, creating it if it isn't in the cache yet.
+    pub(crate) fn shader(
+        &self,
+        gpu: &Gpu,
+        assets: &AssetCache,
+        record: ShaderRecord,
+    ) -> anyhow::Result<Arc<Shader>> {
+        let key = sha256_digest(&serde_json::to_string(&record)?);
+        if let Some(shader) = self.state.lock().shaders.get(&key) {
+            return Ok(shader.clone());
+        }
+
+        let shader = Shader::from_record(gpu, assets, key.clone(), &record)?;
+        let mut state = self.state.lock();
+        state.shaders.insert(key, shader.clone());
+        state.record(&CacheRecord::Shader(record));
+        Ok(shader)
+    }
+
+    /// Returns the graphics pipeline of `shader` created from `record`, creating it if it isn't in
+    /// the cache yet.
+    pub(crate) fn graphics_pipeline(
+        &self,
+        gpu: &Gpu,
+        shader: &Arc<Shader>,
+        record: GraphicsPipelineRecord,
+    ) -> GraphicsPipeline {
+        let key = pipeline_key(shader, &record);
+        if let Some(pipeline) = self.state.lock().graphics.get(&key) {
+            return pipeline.clone();
+        }
+
+        let pipeline = shader.create_pipeline(gpu, record.info());
+        let mut state = self.state.lock();
+        state.graphics.insert(key, pipeline.clone());
+        state.record(&CacheRecord::Graphics {
+            shader: shader.key().to_string(),
+            pipeline: record,
+        });
+        pipeline
+    }
+
+    /// Returns the compute pipeline of `shader` for `entry_point`, creating it if it isn't in the
+    /// cache yet.
+    pub(crate) fn compute_pipeline(
+        &self,
+        gpu: &Gpu,
+        shader: &Arc<Shader>,
+        entry_point: &str,
+    ) -> ComputePipeline {
+        let key = pipeline_key(shader, entry_point);
+        if let Some(pipeline) = self.state.lock().compute.get(&key) {
+            return pipeline.clone();
+        }
+
+        let pipeline = shader.create_compute_pipeline(gpu, entry_point);
+        let mut state = self.state.lock();
+        state.compute.insert(key, pipeline.clone());
+        state.record(&CacheRecord::Compute {
+            shader: shader.key().to_string(),
+            entry_point: entry_point.to_string(),
+        });
+        pipeline
+    }
+
+    /// Creates the shaders and pipelines that were recorded by the previous runs, and records the
+    /// ones that are created from now on.
+    ///
+    /// The records are stored in `pipeline_cache.jsonl`, in the
+    /// [AssetsCacheDir](ambient_native_std::download_asset::AssetsCacheDir).
+    #[cfg(not(target_os = "unknown"))]
+    pub fn prewarm(&self, gpu: &Gpu, assets: &AssetCache) {
+        use std::io::{BufRead, BufReader, Write};
+
+        use ambient_native_std::{asset_cache::SyncAssetKeyExt, download_asset::AssetsCacheDir};
+
+        let _span = tracing::info_span!("prewarm_pipeline_cache").entered();
+
+        let path = AssetsCacheDir.get(assets).join("pipeline_cache.jsonl");
+        let info = gpu.adapter.get_info();
+        let header = CacheRecord::Header {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            adapter: format!("{:?} {} {}", info.backend, info.name, info.driver_info),
+        };
+        let header = serde_json::to_string(&header).unwrap();
+
+        let mut records = Vec::new();
+        let reusable = std::fs::File::open(&path).ok().filter(|file| {
+            file.metadata()
+                .map_or(false, |metadata| metadata.len() <= MAX_FILE_SIZE)
+        });
+        if let Some(file) = reusable {
+            let mut lines = BufReader::new(file).lines();
+            if matches!(lines.next(), Some(Ok(line)) if line == header) {
+                // A line that was cut short by a crash is skipped
+                records.extend(
+                    lines
+                        .map_while(Result::ok)
+                        .filter_map(|line| serde_json::from_str::<CacheRecord>(&line).ok()),
+                );
+            }
+        }
+
+        let file = if records.is_empty() {
+            std::fs::create_dir_all(path.parent().unwrap())
+                .and_then(|_| std::fs::File::create(&path))
+                .and_then(|mut file| writeln!(file, "{header}").map(|_| file))
+        } else {
+            std::fs::OpenOptions::new().append(true).open(&path)
+        };
+
+        let count = records.len();
+        let mut shaders = HashMap::new();
+        let mut graphics = HashMap::new();
+        let mut compute = HashMap::new();
+        for record in records {
+            match record {
+                CacheRecord::Header { .. } => {}
+                CacheRecord::Shader(record) => {
+                    let Ok(key) = serde_json::to_string(&record).map(|json| sha256_digest(&json))
+                    else {
+                        continue;
+                    };
+                    match Shader::from_record(gpu, assets, key.clone(), &record) {
+                        Ok(shader) => {
+                            shaders.insert(key, shader);
+                        }
+                        Err(err) => {
+                            tracing::debug!("Failed to prewarm shader {}: {err:?}", record.label);
+                        }
+                    }
+                }
+                CacheRecord::Graphics { shader, pipeline } => {
+                    if let Some(shader) = shaders.get(&shader) {
+                        let key = pipeline_key(shader, &pipeline);
+                        graphics.insert(key, shader.create_pipeline(gpu, pipeline.info()));
+                    }
+                }
+                CacheRecord::Compute {
+                    shader,
+                    entry_point,
+                } => {
+                    if let Some(shader) = shaders.get(&shader) {
+                        let key = pipeline_key(shader, &entry_point);
+                        compute.insert(key, shader.create_compute_pipeline(gpu, &entry_point));
+                    }
+                }
+            }
+        }
+        tracing::debug!(
+            "Prewarmed {} shaders and {} pipelines from {count} records",
+            shaders.len(),
+            graphics.len() + compute.len()
+        );
+
+        let mut state = self.state.lock();
+        state.shaders.extend(shaders);
+        state.graphics.extend(graphics);
+        state.compute.extend(compute);
+        state.file = match file {
+            Ok(file) => Some(file),
+            Err(err) => {
+                tracing::warn!("Failed to open the pipeline cache at {path:?}: {err}");
+                None
+            }
+        };
+    }
+}
+
+impl CacheState {
+    #[cfg(not(target_os = "unknown"))]
+    fn record(&mut self, record: &CacheRecord) {
+        use std::io::Write;
+
+        let Some(file) = &mut self.file else {
+            return;
+        };
+        let line = serde_json::to_string(record).unwrap();
+        if let Err(err) = writeln!(file, "{line}") {
+            tracing::warn!("Failed to write to the pipeline cache: {err}");
+            self.file = None;
+        }
+    }
+
+    #[cfg(target_os = "unknown")]
+    fn record(&mut self, _record: &CacheRecord) {}
+}
+
+impl std::fmt::Debug for PipelineCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock();
+        f.debug_struct("PipelineCache")
+            .field("shaders", &state.shaders.len())
+            .field("graphics", &state.graphics.len())
+            .field("compute", &state.compute.len())
+            .finish()
+    }
+}
+
+fn pipeline_key(shader: &Shader, record: &impl Serialize) -> String {
+    sha256_digest(&format!(
+        "{}{}",
+        shader.key(),
+        serde_json::to_string(record).unwrap()
+    ))
+}
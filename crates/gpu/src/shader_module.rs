@@ -9,7 +9,8 @@ use wgpu::{
     BindGroupLayout, BindGroupLayoutEntry, ComputePipelineDescriptor, DepthBiasState, TextureFormat,
 };
 
-use super::gpu::{Gpu, GpuKey, DEFAULT_SAMPLE_COUNT};
+use super::gpu::{Gpu, GpuKey};
+use crate::pipeline_cache::{GraphicsPipelineRecord, ShaderRecord};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum WgslValue {
@@ -223,6 +224,8 @@ pub struct Shader {
     // Ordered sets
     bind_group_layouts: Vec<Arc<wgpu::BindGroupLayout>>,
     label: CowStr,
+    /// The key of the shader in the [PipelineCache](crate::pipeline_cache::PipelineCache)
+    key: String,
 }
 
 impl std::ops::Deref for Shader {
@@ -273,15 +276,10 @@ impl Shader {
             }
         }
 
-        // Now for the fun part: constructing the binding group layout descriptors
-        let bind_group_layouts = bind_groups
-            .iter()
-            .map(|desc| desc.get(assets))
-            .collect_vec();
-        if bind_group_layouts.len() > 4 {
+        if bind_groups.len() > 4 {
             anyhow::bail!(
                 "Maximum bind group layout count exceeded. Expected a maximum of 4, found {}: {bind_group_names:?}",
-                bind_group_layouts.len()
+                bind_groups.len()
             );
         }
 
@@ -342,17 +340,54 @@ impl Shader {
             std::fs::write(path, source.as_bytes()).unwrap();
         }
 
+        // Identical shaders are shared, and compiled ahead of time if a previous run recorded them
+        let record = ShaderRecord {
+            label: label.to_string(),
+            source,
+            bind_groups: bind_groups
+                .into_iter()
+                .map(|desc| (desc.label.into_owned(), desc.entries))
+                .collect(),
+        };
+        gpu.pipeline_cache.shader(&gpu, assets, record)
+    }
+
+    pub(crate) fn from_record(
+        gpu: &Gpu,
+        assets: &AssetCache,
+        key: String,
+        record: &ShaderRecord,
+    ) -> anyhow::Result<Arc<Self>> {
+        let label = record.label.clone();
+
+        // Now for the fun part: constructing the binding group layout descriptors
+        let bind_group_layouts = record
+            .bind_groups
+            .iter()
+            .map(|(label, entries)| {
+                BindGroupDesc {
+                    label: label.clone().into(),
+                    entries: entries.clone(),
+                }
+                .get(assets)
+            })
+            .collect_vec();
+
         let module = gpu
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some(&label),
-                source: wgpu::ShaderSource::Wgsl(source.into()),
-            });
+            .catch_validation_error(|| {
+                gpu.device
+                    .create_shader_module(wgpu::ShaderModuleDescriptor {
+                        label: Some(&label),
+                        source: wgpu::ShaderSource::Wgsl(record.source.as_str().into()),
+                    })
+            })
+            .map_err(|err| anyhow::anyhow!("Failed to compile shader {label}: {err}"))?;
 
         Ok(Arc::new(Self {
             module,
             bind_group_layouts,
-            label,
+            label: label.into(),
+            key,
         }))
     }
 
@@ -367,10 +402,31 @@ impl Shader {
         &self.module
     }
 
+    pub(crate) fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the graphics pipeline of the shader for `info`, which is shared with the other
+    /// identical pipelines.
     pub fn to_pipeline(
         self: &Arc<Self>,
         gpu: &Gpu,
         info: GraphicsPipelineInfo,
+    ) -> GraphicsPipeline {
+        gpu.pipeline_cache
+            .graphics_pipeline(gpu, self, GraphicsPipelineRecord::from(&info))
+    }
+
+    /// Returns the compute pipeline of the shader for `entry_point`, which is shared with the
+    /// other identical pipelines.
+    pub fn to_compute_pipeline(self: &Arc<Self>, gpu: &Gpu, entry_point: &str) -> ComputePipeline {
+        gpu.pipeline_cache.compute_pipeline(gpu, self, entry_point)
+    }
+
+    pub(crate) fn create_pipeline(
+        self: &Arc<Self>,
+        gpu: &Gpu,
+        info: GraphicsPipelineInfo,
     ) -> GraphicsPipeline {
         let layout = gpu
             .device
@@ -394,6 +450,7 @@ impl Shader {
                     front_face: info.front_face,
                     cull_mode: info.cull_mode,
                     topology: info.topology,
+                    polygon_mode: info.polygon_mode,
                     ..Default::default()
                 },
                 fragment: Some(wgpu::FragmentState {
@@ -403,7 +460,7 @@ impl Shader {
                 }),
                 depth_stencil: info.depth,
                 multisample: wgpu::MultisampleState {
-                    count: DEFAULT_SAMPLE_COUNT,
+                    count: info.sample_count,
                     mask: !0,
                     alpha_to_coverage_enabled: false,
                 },
@@ -411,13 +468,17 @@ impl Shader {
             });
 
         GraphicsPipeline {
-            pipeline,
+            pipeline: Arc::new(pipeline),
             name: self.label.deref().into(),
             shader: self.clone(),
         }
     }
 
-    pub fn to_compute_pipeline(self: &Arc<Self>, gpu: &Gpu, entry_point: &str) -> ComputePipeline {
+    pub(crate) fn create_compute_pipeline(
+        self: &Arc<Self>,
+        gpu: &Gpu,
+        entry_point: &str,
+    ) -> ComputePipeline {
         let layout = gpu
             .device
             .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -436,7 +497,7 @@ impl Shader {
             });
 
         ComputePipeline {
-            pipeline,
+            pipeline: Arc::new(pipeline),
             shader: self.clone(),
             name: self.label.deref().into(),
         }
@@ -452,6 +513,10 @@ pub struct GraphicsPipelineInfo<'a> {
     pub front_face: wgpu::FrontFace,
     pub cull_mode: Option<wgpu::Face>,
     pub topology: wgpu::PrimitiveTopology,
+    /// Anything but [wgpu::PolygonMode::Fill] requires a feature of the device
+    pub polygon_mode: wgpu::PolygonMode,
+    /// Must match the sample count of the render targets; see [Gpu::sample_count]
+    pub sample_count: u32,
 }
 
 impl<'a> Default for GraphicsPipelineInfo<'a> {
@@ -464,6 +529,8 @@ impl<'a> Default for GraphicsPipelineInfo<'a> {
             front_face: wgpu::FrontFace::Cw,
             cull_mode: None,
             topology: wgpu::PrimitiveTopology::TriangleList,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            sample_count: 1,
         }
     }
 }
@@ -472,11 +539,21 @@ pub type GraphicsPipeline = Pipeline<wgpu::RenderPipeline>;
 pub type ComputePipeline = Pipeline<wgpu::ComputePipeline>;
 
 pub struct Pipeline<P> {
-    pipeline: P,
+    pipeline: Arc<P>,
     shader: Arc<Shader>,
     name: String,
 }
 
+impl<P> Clone for Pipeline<P> {
+    fn clone(&self) -> Self {
+        Self {
+            pipeline: self.pipeline.clone(),
+            shader: self.shader.clone(),
+            name: self.name.clone(),
+        }
+    }
+}
+
 impl<P> Pipeline<P> {
     /// Get a reference to the graphics pipeline's pipeline.
     pub fn pipeline(&self) -> &P {
@@ -0,0 +1,231 @@
+//! Gamepad input.
+//!
+//! On native platforms, the gamepads are polled with [gilrs] on their own thread, as its state
+//! can't be moved between threads on all platforms. The [GamepadSystem] applies the events to the
+//! [gamepads] of the world each frame.
+use std::collections::HashSet;
+
+pub use ambient_shared_types::GamepadButton;
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_os = "unknown"))]
+pub use native::GamepadSystem;
+
+/// The state of a connected gamepad.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GamepadState {
+    /// Identifies the gamepad for as long as it is connected
+    pub id: u32,
+    pub name: String,
+    pub buttons: HashSet<GamepadButton>,
+    /// From -1 to 1 on each axis, with positive Y being up
+    pub left_stick: Vec2,
+    pub right_stick: Vec2,
+    /// From 0 (released) to 1 (fully pressed)
+    pub left_trigger: f32,
+    pub right_trigger: f32,
+}
+
+#[cfg(not(target_os = "unknown"))]
+mod native {
+    use std::time::Duration;
+
+    use ambient_ecs::{generated::messages, world_events, System, World, WorldEventsExt};
+    use gilrs::{Axis, Button, EventType, Gilrs};
+
+    use super::{GamepadButton, GamepadState};
+    use crate::gamepads;
+
+    #[derive(Debug)]
+    enum GamepadEvent {
+        Connected {
+            id: u32,
+            name: String,
+        },
+        Disconnected {
+            id: u32,
+        },
+        Button {
+            id: u32,
+            button: GamepadButton,
+            pressed: bool,
+        },
+        Axis {
+            id: u32,
+            axis: Axis,
+            value: f32,
+        },
+        Trigger {
+            id: u32,
+            right: bool,
+            value: f32,
+        },
+    }
+
+    /// Updates the [gamepads] resource from the events of the gamepads, and sends the
+    /// [GamepadConnected](messages::GamepadConnected) and
+    /// [GamepadDisconnected](messages::GamepadDisconnected) messages.
+    #[derive(Debug)]
+    pub struct GamepadSystem {
+        events: flume::Receiver<GamepadEvent>,
+    }
+    impl GamepadSystem {
+        pub fn new() -> Self {
+            let (events_tx, events) = flume::unbounded();
+            let spawned = std::thread::Builder::new()
+                .name("gamepads".to_string())
+                .spawn(move || poll_gamepads(events_tx));
+            if let Err(err) = spawned {
+                tracing::warn!("Failed to start polling the gamepads: {err}");
+            }
+            Self { events }
+        }
+    }
+    impl System for GamepadSystem {
+        fn run(&mut self, world: &mut World, _event: &ambient_ecs::FrameEvent) {
+            for event in self.events.try_iter() {
+                let pads = world.resource_mut(gamepads());
+                match event {
+                    GamepadEvent::Connected { id, name } => {
+                        pads.retain(|pad| pad.id != id);
+                        pads.push(GamepadState {
+                            id,
+                            name: name.clone(),
+                            ..Default::default()
+                        });
+                        pads.sort_by_key(|pad| pad.id);
+                        world
+                            .resource_mut(world_events())
+                            .add_message(messages::GamepadConnected::new(id, name));
+                    }
+                    GamepadEvent::Disconnected { id } => {
+                        pads.retain(|pad| pad.id != id);
+                        world
+                            .resource_mut(world_events())
+                            .add_message(messages::GamepadDisconnected::new(id));
+                    }
+                    GamepadEvent::Button {
+                        id,
+                        button,
+                        pressed,
+                    } => {
+                        if let Some(pad) = pads.iter_mut().find(|pad| pad.id == id) {
+                            if pressed {
+                                pad.buttons.insert(button);
+                            } else {
+                                pad.buttons.remove(&button);
+                            }
+                        }
+                    }
+                    GamepadEvent::Axis { id, axis, value } => {
+                        if let Some(pad) = pads.iter_mut().find(|pad| pad.id == id) {
+                            match axis {
+                                Axis::LeftStickX => pad.left_stick.x = value,
+                                Axis::LeftStickY => pad.left_stick.y = value,
+                                Axis::RightStickX => pad.right_stick.x = value,
+                                Axis::RightStickY => pad.right_stick.y = value,
+                                _ => {}
+                            }
+                        }
+                    }
+                    GamepadEvent::Trigger { id, right, value } => {
+                        if let Some(pad) = pads.iter_mut().find(|pad| pad.id == id) {
+                            if right {
+                                pad.right_trigger = value;
+                            } else {
+                                pad.left_trigger = value;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sends the events of the gamepads to `events_tx` until it is disconnected.
+    fn poll_gamepads(events_tx: flume::Sender<GamepadEvent>) {
+        let mut gilrs = match Gilrs::new() {
+            Ok(gilrs) => gilrs,
+            Err(err) => {
+                tracing::warn!("Gamepads are not supported: {err}");
+                return;
+            }
+        };
+
+        // The gamepads that are already connected don't send a `Connected` event
+        for (id, gamepad) in gilrs.gamepads() {
+            let event = GamepadEvent::Connected {
+                id: usize::from(id) as u32,
+                name: gamepad.name().to_string(),
+            };
+            if events_tx.send(event).is_err() {
+                return;
+            }
+        }
+
+        while !events_tx.is_disconnected() {
+            let Some(event) = gilrs.next_event_blocking(Some(Duration::from_millis(100))) else {
+                continue;
+            };
+            let id = usize::from(event.id) as u32;
+            let event = match event.event {
+                EventType::Connected => GamepadEvent::Connected {
+                    id,
+                    name: gilrs.gamepad(event.id).name().to_string(),
+                },
+                EventType::Disconnected => GamepadEvent::Disconnected { id },
+                EventType::ButtonPressed(button, _) | EventType::ButtonReleased(button, _) => {
+                    let Some(button) = convert_button(button) else {
+                        continue;
+                    };
+                    GamepadEvent::Button {
+                        id,
+                        button,
+                        pressed: matches!(event.event, EventType::ButtonPressed(..)),
+                    }
+                }
+                EventType::ButtonChanged(Button::LeftTrigger2, value, _) => GamepadEvent::Trigger {
+                    id,
+                    right: false,
+                    value,
+                },
+                EventType::ButtonChanged(Button::RightTrigger2, value, _) => {
+                    GamepadEvent::Trigger {
+                        id,
+                        right: true,
+                        value,
+                    }
+                }
+                EventType::AxisChanged(axis, value, _) => GamepadEvent::Axis { id, axis, value },
+                _ => continue,
+            };
+            if events_tx.send(event).is_err() {
+                return;
+            }
+        }
+    }
+
+    fn convert_button(button: Button) -> Option<GamepadButton> {
+        Some(match button {
+            Button::South => GamepadButton::South,
+            Button::East => GamepadButton::East,
+            Button::North => GamepadButton::North,
+            Button::West => GamepadButton::West,
+            Button::LeftTrigger => GamepadButton::LeftShoulder,
+            Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+            Button::RightTrigger => GamepadButton::RightShoulder,
+            Button::RightTrigger2 => GamepadButton::RightTrigger,
+            Button::Select => GamepadButton::Select,
+            Button::Start => GamepadButton::Start,
+            Button::Mode => GamepadButton::Mode,
+            Button::LeftThumb => GamepadButton::LeftStick,
+            Button::RightThumb => GamepadButton::RightStick,
+            Button::DPadUp => GamepadButton::DPadUp,
+            Button::DPadDown => GamepadButton::DPadDown,
+            Button::DPadLeft => GamepadButton::DPadLeft,
+            Button::DPadRight => GamepadButton::DPadRight,
+            Button::C | Button::Z | Button::Unknown => return None,
+        })
+    }
+}
@@ -1,33 +1,67 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, path::Path};
 
+use ambient_core::{last_frame_time, window::window_scale_factor};
 use ambient_ecs::{
     components, generated::messages, world_events, Debuggable, Entity, FnSystem, Resource, System,
     SystemGroup, WorldEventsExt,
 };
+use ambient_settings::MouseMode;
 use glam::{vec2, Vec2};
 use serde::{Deserialize, Serialize};
-use winit::event::ModifiersState;
 pub use winit::event::{
-    DeviceEvent, ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode,
-    WindowEvent,
+    DeviceEvent, ElementState, Event, Ime, KeyboardInput, MouseButton, MouseScrollDelta,
+    VirtualKeyCode, WindowEvent,
 };
+use winit::{dpi::PhysicalPosition, event::ModifiersState};
 
+pub mod gamepad;
 pub mod picking;
 
+use gamepad::GamepadState;
+
+/// A finger or stylus touching the window.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Touch {
+    /// Identifies the touch until it has ended
+    pub id: u64,
+    pub phase: ambient_shared_types::TouchPhase,
+    /// In logical pixels, like the mouse position
+    pub position: Vec2,
+    /// From 0 to 1, if the device supports it
+    pub pressure: Option<f32>,
+}
+
+/// A movement of the mouse.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MouseMotion {
+    pub delta: Vec2,
+    /// When the movement happened, in seconds since the start of the frame it happened in
+    pub time: f32,
+}
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct PlayerRawInput {
     pub keys: HashSet<ambient_shared_types::VirtualKeyCode>,
     pub mouse_position: Vec2,
     pub mouse_delta: Vec2,
+    /// The movements that add up to `mouse_delta`, in the order they happened
+    #[serde(default)]
+    pub mouse_motion: Vec<MouseMotion>,
     pub mouse_wheel: f32,
     pub mouse_buttons: HashSet<ambient_shared_types::MouseButton>,
+    pub gamepads: Vec<GamepadState>,
+    /// The touches, including the ones that have ended since the last frame
+    pub touches: Vec<Touch>,
 }
 impl PlayerRawInput {
     pub fn clear(&mut self) {
         self.keys.clear();
         self.mouse_delta = vec2(0.0, 0.0);
+        self.mouse_motion.clear();
         self.mouse_wheel = 0.0;
         self.mouse_buttons.clear();
+        self.gamepads.clear();
+        self.touches.clear();
     }
 }
 
@@ -38,6 +72,9 @@ components!("input", {
     player_raw_input: PlayerRawInput,
     @[Debuggable, Resource]
     player_prev_raw_input: PlayerRawInput,
+    /// The connected gamepads, by ID
+    @[Debuggable, Resource]
+    gamepads: Vec<GamepadState>,
 });
 
 pub fn init_all_components() {
@@ -45,8 +82,8 @@ pub fn init_all_components() {
     init_components();
 }
 
-pub fn event_systems() -> SystemGroup<Event<'static, ()>> {
-    SystemGroup::new("inputs", vec![Box::new(InputSystem::new())])
+pub fn event_systems(mouse_mode: MouseMode) -> SystemGroup<Event<'static, ()>> {
+    SystemGroup::new("inputs", vec![Box::new(InputSystem::new(mouse_mode))])
 }
 
 pub fn cursor_lock_system(cursor_lock_rx: flume::Receiver<bool>) -> Box<dyn System + Send + Sync> {
@@ -63,23 +100,40 @@ pub fn resources() -> Entity {
     Entity::new()
         .with(player_raw_input(), Default::default())
         .with(player_prev_raw_input(), Default::default())
+        .with(gamepads(), Default::default())
 }
 
 #[derive(Debug)]
 pub struct InputSystem {
     modifiers: ModifiersState,
     is_focused: bool,
+    mouse_mode: MouseMode,
+    /// The last position of the system pointer, which [MouseMode::Pointer] reads the motion from.
+    pointer_position: Option<PhysicalPosition<f64>>,
 }
 
 impl InputSystem {
-    pub fn new() -> Self {
+    pub fn new(mouse_mode: MouseMode) -> Self {
         Self {
             modifiers: ModifiersState::empty(),
             is_focused: true,
+            mouse_mode,
+            pointer_position: None,
         }
     }
 }
 
+/// Sends a mouse motion, timestamped within the current frame, so that the motions of a frame
+/// can be told apart however long it is.
+fn send_mouse_motion(world: &mut ambient_ecs::World, delta: Vec2) {
+    let time = world
+        .resource_opt(last_frame_time())
+        .map_or(0.0, |frame_start| frame_start.elapsed().as_secs_f32());
+    world
+        .resource_mut(world_events())
+        .add_message(messages::WindowMouseMotion::new(delta, time));
+}
+
 impl System<Event<'static, ()>> for InputSystem {
     fn run(&mut self, world: &mut ambient_ecs::World, event: &Event<'static, ()>) {
         match event {
@@ -106,9 +160,20 @@ impl System<Event<'static, ()>> for InputSystem {
                         return;
                     }
 
-                    world
-                        .resource_mut(world_events())
-                        .add_message(messages::WindowKeyboardCharacter::new(c.to_string()));
+                    let events = world.resource_mut(world_events());
+                    events.add_message(messages::WindowKeyboardCharacter::new(c.to_string()));
+                    events.add_message(messages::TextInput::new(c.to_string(), false));
+                }
+
+                WindowEvent::Ime(ime) => {
+                    let message = match ime {
+                        Ime::Preedit(text, _) => messages::TextInput::new(text.clone(), true),
+                        Ime::Commit(text) => messages::TextInput::new(text.clone(), false),
+                        // Ends the composition, if there was one
+                        Ime::Disabled => messages::TextInput::new(String::new(), true),
+                        Ime::Enabled => return,
+                    };
+                    world.resource_mut(world_events()).add_message(message);
                 }
 
                 WindowEvent::ModifiersChanged(mods) => {
@@ -152,6 +217,23 @@ impl System<Event<'static, ()>> for InputSystem {
                     );
                 }
 
+                WindowEvent::Touch(touch) => {
+                    let scale_factor = world
+                        .resource_opt(window_scale_factor())
+                        .copied()
+                        .unwrap_or(1.0);
+                    let position = vec2(touch.location.x as f32, touch.location.y as f32)
+                        / scale_factor as f32;
+                    world
+                        .resource_mut(world_events())
+                        .add_message(messages::WindowTouch::new(
+                            touch.id,
+                            ambient_shared_types::TouchPhase::from(touch.phase).to_string(),
+                            position,
+                            touch.force.map(|force| force.normalized() as f32),
+                        ));
+                }
+
                 WindowEvent::MouseWheel { delta, .. } => {
                     world.resource_mut(world_events()).add_message(
                         messages::WindowMouseWheel::new(
@@ -164,25 +246,72 @@ impl System<Event<'static, ()>> for InputSystem {
                     );
                 }
 
+                &WindowEvent::CursorMoved { position, .. } => {
+                    let previous = self.pointer_position.replace(position);
+                    if let (MouseMode::Pointer, Some(previous)) = (self.mouse_mode, previous) {
+                        send_mouse_motion(
+                            world,
+                            vec2(
+                                (position.x - previous.x) as f32,
+                                (position.y - previous.y) as f32,
+                            ),
+                        );
+                    }
+                }
+
+                WindowEvent::CursorLeft { .. } => {
+                    self.pointer_position = None;
+                }
+
+                WindowEvent::DroppedFile(path) => {
+                    world
+                        .resource_mut(world_events())
+                        .add_message(file_dropped_message(path));
+                }
+
                 _ => {}
             },
 
             Event::DeviceEvent {
                 event: DeviceEvent::MouseMotion { delta },
                 ..
-            } => {
-                world
-                    .resource_mut(world_events())
-                    .add_message(messages::WindowMouseMotion::new(vec2(
-                        delta.0 as f32,
-                        delta.1 as f32,
-                    )));
+            } if self.mouse_mode == MouseMode::Raw => {
+                send_mouse_motion(world, vec2(delta.0 as f32, delta.1 as f32));
             }
             _ => {}
         }
     }
 }
 
+/// The largest dropped file that is read and sent to modules.
+pub const MAX_DROPPED_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Reads a file that was dropped onto the window. Only the name of the file is sent, so that
+/// modules don't learn anything else about the user's file system.
+fn file_dropped_message(path: &Path) -> messages::WindowFileDropped {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let contents = match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => Err("Folders can't be dropped".to_string()),
+        Ok(metadata) if metadata.len() > MAX_DROPPED_FILE_SIZE => Err(format!(
+            "The file is larger than {} MiB",
+            MAX_DROPPED_FILE_SIZE / 1024 / 1024
+        )),
+        Ok(_) => std::fs::read(path).map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+    };
+
+    match contents {
+        Ok(contents) => messages::WindowFileDropped::new(name, contents, None),
+        Err(error) => {
+            tracing::warn!("Failed to read the dropped file {path:?}: {error}");
+            messages::WindowFileDropped::new(name, vec![], Some(error))
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct MouseInput {
     pub state: ElementState,
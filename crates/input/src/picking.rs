@@ -1,5 +1,7 @@
 use ambient_core::{
     camera::{clip_position_to_world_ray, get_active_camera},
+    hierarchy::parent,
+    main_scene,
     player::{local_user_id, user_id},
     transform::local_to_world,
     ui_scene,
@@ -7,14 +9,17 @@ use ambient_core::{
 };
 use ambient_ecs::{
     components,
-    generated::input::{
-        components::{mouse_over_distance, mouse_over_entity},
-        messages::MouseOverChanged,
+    generated::{
+        input::{
+            components::{mouse_over_distance, mouse_over_entity},
+            messages::MouseOverChanged,
+        },
+        ui::components::{world_panel, world_panel_cursor},
     },
-    query, world_events, Debuggable, Entity, EntityId, SystemGroup, WorldEventsExt,
+    query, world_events, Debuggable, Entity, EntityId, SystemGroup, World, WorldEventsExt,
 };
-use ambient_native_std::shapes::{RayIntersectable, AABB};
-use glam::Vec2;
+use ambient_native_std::shapes::{Ray, RayIntersectable, AABB};
+use glam::{Vec2, Vec3Swizzles};
 
 pub use ambient_ecs::generated::input::components::{
     is_mouse_over, mouse_pickable_max, mouse_pickable_min,
@@ -52,39 +57,55 @@ pub fn frame_systems() -> SystemGroup {
                     let mut mouse_origin =
                         -Vec2::ONE + (mouse_position / window_size.as_vec2()) * 2.;
                     mouse_origin.y = -mouse_origin.y;
-                    let camera = match get_active_camera(
-                        world,
-                        ui_scene(),
-                        world
-                            .get_ref(id, user_id())
-                            .ok()
-                            .or_else(|| world.resource_opt(local_user_id())),
-                    ) {
+                    let user = world
+                        .get_ref(id, user_id())
+                        .ok()
+                        .or_else(|| world.resource_opt(local_user_id()));
+                    let camera = match get_active_camera(world, ui_scene(), user) {
                         Some(cam) => cam,
                         None => return,
                     };
                     let ray =
                         clip_position_to_world_ray(world, camera, mouse_origin).unwrap_or_default();
+                    // The UI elements of world panels are in the main scene, and are picked with
+                    // the main camera instead
+                    let world_ray =
+                        get_active_camera(world, main_scene(), user).and_then(|camera| {
+                            clip_position_to_world_ray(world, camera, mouse_origin).ok()
+                        });
 
                     let prev_intersecting_entity =
                         world.get(id, mouse_over_entity()).unwrap_or_default();
 
-                    let mut intersecting_entity = EntityId::null();
-                    let mut intersecting_dist = 0.;
+                    let mut screen_hit: Option<(EntityId, f32)> = None;
+                    let mut world_hit: Option<(EntityId, f32)> = None;
                     for (id2, (pickable, local_to_world)) in
                         query((mouse_pickable(), local_to_world())).iter(world, None)
                     {
                         if local_to_world.is_nan() {
                             continue;
                         }
+                        let (ray, hit) = if world.has_component(id2, main_scene()) {
+                            match world_ray {
+                                Some(world_ray) => (world_ray, &mut world_hit),
+                                None => continue,
+                            }
+                        } else {
+                            (ray, &mut screen_hit)
+                        };
                         let ray = ray.transform(local_to_world.inverse());
                         if let Some(dist) = pickable.ray_intersect(ray) {
-                            if intersecting_entity.is_null() || dist < intersecting_dist {
-                                intersecting_entity = id2;
-                                intersecting_dist = dist;
+                            if hit.map_or(true, |(_, hit_dist)| dist < hit_dist) {
+                                *hit = Some((id2, dist));
                             }
                         }
                     }
+
+                    // The UI on the screen is in front of the world
+                    let world_hit = world_hit.filter(|_| screen_hit.is_none());
+                    update_world_panel_cursor(world, world_ray.zip(world_hit));
+                    let (intersecting_entity, intersecting_dist) =
+                        screen_hit.or(world_hit).unwrap_or((EntityId::null(), 0.));
                     if prev_intersecting_entity != intersecting_entity {
                         if !prev_intersecting_entity.is_null() {
                             if let Ok(prev_mouse_over) =
@@ -132,3 +153,34 @@ pub fn frame_systems() -> SystemGroup {
         ],
     )
 }
+
+/// Sets the `world_panel_cursor` of the world panel that `hit` is in, at the point where `ray`
+/// hits it, and removes it from the other world panels.
+fn update_world_panel_cursor(world: &mut World, hit: Option<(Ray, (EntityId, f32))>) {
+    let cursor = hit.and_then(|(ray, (id, dist))| {
+        let panel = world_panel_ancestor(world, id)?;
+        let point = ray.origin + ray.dir * dist;
+        let local_to_world = world.get(panel, local_to_world()).ok()?;
+        Some((panel, local_to_world.inverse().transform_point3(point).xy()))
+    });
+
+    for (id, _) in query(world_panel_cursor()).collect_cloned(world, None) {
+        if cursor.map_or(true, |(panel, _)| panel != id) {
+            world.remove_component(id, world_panel_cursor()).ok();
+        }
+    }
+    if let Some((panel, cursor)) = cursor {
+        world
+            .add_component(panel, world_panel_cursor(), cursor)
+            .ok();
+    }
+}
+
+fn world_panel_ancestor(world: &World, mut id: EntityId) -> Option<EntityId> {
+    loop {
+        if world.has_component(id, world_panel()) {
+            return Some(id);
+        }
+        id = world.get(id, parent()).ok()?;
+    }
+}
@@ -0,0 +1,102 @@
+//! Clipping of UI elements to the bounds of their ancestors.
+//!
+//! Every descendant of an entity with [clip_children] gets a [scissors] rect that covers the
+//! bounds of that entity, in physical pixels. Clips of nested entities are intersected.
+use ambient_core::{
+    hierarchy::{children, parent},
+    transform::local_to_world,
+    window::window_scale_factor,
+};
+use ambient_ecs::{
+    generated::rendering::components::scissors, query, EntityId, SystemGroup, World,
+};
+use glam::{uvec4, vec2, Vec2};
+
+use crate::{clip_children, height, width};
+
+/// A rect in physical pixels, as its minimum and maximum corners.
+type Rect = (Vec2, Vec2);
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "layout/clip",
+        vec![query(()).incl(clip_children()).to_system_with_name(
+            "layout/clip/update",
+            |q, world, qs, _| {
+                let scale_factor = world
+                    .resource_opt(window_scale_factor())
+                    .copied()
+                    .unwrap_or(1.) as f32;
+
+                // Nested clips are handled while traversing their outermost clipping ancestor
+                let roots = q
+                    .collect_ids(world, qs)
+                    .into_iter()
+                    .filter(|&id| !has_clipping_ancestor(world, id))
+                    .collect::<Vec<_>>();
+
+                for id in roots {
+                    if let Some(rect) = bounds(world, id, scale_factor) {
+                        clip_descendants(world, id, rect, scale_factor);
+                    }
+                }
+            },
+        )],
+    )
+}
+
+fn has_clipping_ancestor(world: &World, id: EntityId) -> bool {
+    let mut current = world.get(id, parent()).ok();
+    while let Some(id) = current {
+        if world.has_component(id, clip_children()) {
+            return true;
+        }
+        current = world.get(id, parent()).ok();
+    }
+    false
+}
+
+fn bounds(world: &World, id: EntityId, scale_factor: f32) -> Option<Rect> {
+    let (scale, _, position) = world
+        .get(id, local_to_world())
+        .ok()?
+        .to_scale_rotation_translation();
+    let size = vec2(
+        world.get(id, width()).unwrap_or(0.),
+        world.get(id, height()).unwrap_or(0.),
+    ) * scale.truncate();
+
+    let min = position.truncate() * scale_factor;
+    Some((min, min + size * scale_factor))
+}
+
+fn clip_descendants(world: &mut World, id: EntityId, rect: Rect, scale_factor: f32) {
+    let Ok(children) = world.get_cloned(id, children()) else {
+        return;
+    };
+
+    let (min, max) = rect;
+    let min = min.max(Vec2::ZERO);
+    let size = (max - min).max(Vec2::ZERO);
+    let value = uvec4(min.x as u32, min.y as u32, size.x as u32, size.y as u32);
+
+    for child in children {
+        if world.get(child, scissors()).ok() != Some(value) {
+            if world.has_component(child, scissors()) {
+                world.set(child, scissors(), value).ok();
+            } else {
+                world.add_component(child, scissors(), value).ok();
+            }
+        }
+
+        let child_rect = if world.has_component(child, clip_children()) {
+            match bounds(world, child, scale_factor) {
+                Some((child_min, child_max)) => (rect.0.max(child_min), rect.1.min(child_max)),
+                None => rect,
+            }
+        } else {
+            rect
+        };
+        clip_descendants(world, child, child_rect, scale_factor);
+    }
+}
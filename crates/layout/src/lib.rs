@@ -2,7 +2,7 @@ use ambient_core::{
     hierarchy::{children, parent},
     transform::{local_to_parent, local_to_world, mesh_to_local, translation},
 };
-use ambient_ecs::{query, query_mut, DynSystem, EntityId, SystemGroup, World};
+use ambient_ecs::{components, query, query_mut, DynSystem, EntityId, SystemGroup, World};
 use ambient_gpu_ecs::{
     gpu_components, ComponentToGpuSystem, GpuComponentFormat, GpuWorldSyncEvent,
 };
@@ -12,8 +12,8 @@ use itertools::Itertools;
 
 pub use ambient_ecs::generated::layout::{
     components::{
-        align_horizontal, align_vertical, docking, fit_horizontal, fit_vertical, gpu_ui_size,
-        height, is_book_file, is_screen, layout, margin, max_height, max_width,
+        align_horizontal, align_vertical, clip_children, docking, fit_horizontal, fit_vertical,
+        gpu_ui_size, height, is_book_file, is_screen, layout, margin, max_height, max_width,
         mesh_to_local_from_size, min_height, min_width, orientation, padding, space_between_items,
         width,
     },
@@ -22,10 +22,17 @@ pub use ambient_ecs::generated::layout::{
 use ambient_gpu::gpu::Gpu;
 use std::sync::Arc;
 
+pub mod clip;
+pub mod transition;
+
 gpu_components! {
     gpu_ui_size() => ui_size: GpuComponentFormat::Vec4,
 }
 
+components!("layout", {
+    transition_state: transition::TransitionState,
+});
+
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Borders {
     pub top: f32,
@@ -218,6 +225,9 @@ pub fn layout_systems() -> SystemGroup {
                         *size = vec4(*width, *height, 0., 0.);
                     }
                 }),
+            Box::new(clip::systems()),
+            // Runs after the layout, so that elements enter at their final position
+            Box::new(transition::systems()),
         ],
     )
 }
@@ -0,0 +1,372 @@
+//! Transitions of UI element properties, and enter/exit animations.
+//!
+//! Entities with a [transition_duration] get a [transition_state](crate::transition_state), which
+//! remembers the values last written by these systems. When another system (or a guest) changes
+//! one of the transitioned properties to a different value, the property is reset to its displayed
+//! value and a tween towards the new value is started.
+use ambient_core::{
+    delta_time,
+    hierarchy::{children, despawn_recursive, parent},
+    transform::{local_to_parent, local_to_world, scale, translation},
+};
+use ambient_ecs::{
+    generated::{
+        rect::components::background_color,
+        rendering::components::color,
+        ui::{
+            components::{
+                transition_duration, transition_easing, transition_enter_offset,
+                transition_enter_opacity, transition_enter_scale, transition_exit_offset,
+                transition_exit_opacity, transition_exit_scale, transition_exiting,
+            },
+            types::Easing,
+        },
+    },
+    query, EntityId, SystemGroup, World,
+};
+use glam::{Vec3, Vec4};
+
+use crate::transition_state;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Property {
+    Translation,
+    Scale,
+    Color,
+    BackgroundColor,
+}
+impl Property {
+    const ALL: [Property; 4] = [
+        Property::Translation,
+        Property::Scale,
+        Property::Color,
+        Property::BackgroundColor,
+    ];
+
+    fn get(self, world: &World, id: EntityId) -> Option<Vec4> {
+        match self {
+            Property::Translation => world.get(id, translation()).ok().map(|v| v.extend(0.)),
+            Property::Scale => world.get(id, scale()).ok().map(|v| v.extend(0.)),
+            Property::Color => world.get(id, color()).ok(),
+            Property::BackgroundColor => world.get(id, background_color()).ok(),
+        }
+    }
+
+    fn set(self, world: &mut World, id: EntityId, value: Vec4) {
+        fn set_or_add<T: ambient_ecs::ComponentValue>(
+            world: &mut World,
+            id: EntityId,
+            component: ambient_ecs::Component<T>,
+            value: T,
+        ) {
+            if world.has_component(id, component) {
+                world.set(id, component, value).ok();
+            } else {
+                world.add_component(id, component, value).ok();
+            }
+        }
+
+        match self {
+            Property::Translation => set_or_add(world, id, translation(), value.truncate()),
+            Property::Scale => set_or_add(world, id, scale(), value.truncate()),
+            Property::Color => set_or_add(world, id, color(), value),
+            Property::BackgroundColor => set_or_add(world, id, background_color(), value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TransitionState {
+    /// The values of each [Property] last written by the transition systems.
+    displayed: [Option<Vec4>; 4],
+    tweens: Vec<Tween>,
+    /// Despawn the entity once all tweens have finished.
+    exiting: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Tween {
+    /// The entity being animated; enter and exit animations also fade the descendants of an entity.
+    target: EntityId,
+    property: Property,
+    from: Vec4,
+    to: Vec4,
+    elapsed: f32,
+    duration: f32,
+    easing: Easing,
+}
+impl Tween {
+    fn value(&self) -> Vec4 {
+        let t = if self.duration > 0. {
+            (self.elapsed / self.duration).min(1.)
+        } else {
+            1.
+        };
+        self.from.lerp(self.to, ease(self.easing, t))
+    }
+    fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+/// Maps the linear progress `t` (from 0 to 1) of a transition with the given easing function.
+pub fn ease(easing: Easing, t: f32) -> f32 {
+    match easing {
+        Easing::Linear => t,
+        Easing::EaseIn => t * t * t,
+        Easing::EaseOut => 1. - (1. - t).powi(3),
+        Easing::EaseInOut => {
+            if t < 0.5 {
+                4. * t * t * t
+            } else {
+                1. - (-2. * t + 2.).powi(3) / 2.
+            }
+        }
+    }
+}
+
+/// Sets the property, and records it as displayed if the entity is transitioned, so that it is
+/// not mistaken for a new target value.
+fn write(world: &mut World, id: EntityId, property: Property, value: Vec4) {
+    property.set(world, id, value);
+    if let Ok(state) = world.get_mut(id, transition_state()) {
+        state.displayed[property as usize] = Some(value);
+    }
+}
+
+fn tween(
+    world: &World,
+    id: EntityId,
+    target: EntityId,
+    property: Property,
+    from: Vec4,
+    to: Vec4,
+) -> Tween {
+    Tween {
+        target,
+        property,
+        from,
+        to,
+        elapsed: 0.,
+        duration: world.get(id, transition_duration()).unwrap_or(0.),
+        easing: world
+            .get(id, transition_easing())
+            .unwrap_or(Easing::EaseOut),
+    }
+}
+
+fn with_descendants(world: &World, id: EntityId, out: &mut Vec<EntityId>) {
+    out.push(id);
+    if let Ok(children) = world.get_ref(id, children()) {
+        for &child in children {
+            with_descendants(world, child, out);
+        }
+    }
+}
+
+/// Creates the tweens between the current state of `id` and the state offset by `offset`,
+/// multiplied by `scale_factor` and faded to `opacity`. When `entering`, the entity starts at the
+/// modified state; otherwise, it ends there.
+fn presence_tweens(
+    world: &mut World,
+    id: EntityId,
+    offset: Option<Vec3>,
+    scale_factor: Option<f32>,
+    opacity: Option<f32>,
+    entering: bool,
+) -> Vec<Tween> {
+    let mut tweens = Vec::new();
+    let mut add =
+        |world: &World, target: EntityId, property: Property, current: Vec4, modified: Vec4| {
+            let (from, to) = if entering {
+                (modified, current)
+            } else {
+                (current, modified)
+            };
+            tweens.push(tween(world, id, target, property, from, to));
+        };
+
+    if let Some(offset) = offset {
+        let current = Property::Translation.get(world, id).unwrap_or(Vec4::ZERO);
+        add(
+            world,
+            id,
+            Property::Translation,
+            current,
+            current + offset.extend(0.),
+        );
+    }
+    if let Some(scale_factor) = scale_factor {
+        let current = Property::Scale
+            .get(world, id)
+            .unwrap_or(Vec3::ONE.extend(0.));
+        add(world, id, Property::Scale, current, current * scale_factor);
+    }
+    if let Some(opacity) = opacity {
+        let mut targets = Vec::new();
+        with_descendants(world, id, &mut targets);
+        for target in targets {
+            let current = Property::Color.get(world, target).unwrap_or(Vec4::ONE);
+            add(
+                world,
+                target,
+                Property::Color,
+                current,
+                current.truncate().extend(current.w * opacity),
+            );
+        }
+    }
+
+    if entering {
+        for tween in &tweens {
+            write(world, tween.target, tween.property, tween.from);
+        }
+    }
+    tweens
+}
+
+/// Makes the entity a transform root, keeping its current world transform, so that it can exit
+/// independently of its former parent.
+fn detach(world: &mut World, id: EntityId) {
+    let Ok(transform) = world.get(id, local_to_world()) else {
+        return;
+    };
+    let (world_scale, _, world_translation) = transform.to_scale_rotation_translation();
+    world
+        .remove_components(id, vec![parent().desc(), local_to_parent().desc()])
+        .ok();
+    write(
+        world,
+        id,
+        Property::Translation,
+        world_translation.extend(0.),
+    );
+    if world.has_component(id, scale()) {
+        write(world, id, Property::Scale, world_scale.extend(0.));
+    }
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "layout/transition",
+        vec![
+            query((transition_duration(),))
+                .spawned()
+                .to_system_with_name("layout/transition/enter", |q, world, qs, _| {
+                    for (id, _) in q.collect_cloned(world, qs) {
+                        let displayed = Property::ALL.map(|property| property.get(world, id));
+                        world
+                            .add_component(
+                                id,
+                                transition_state(),
+                                TransitionState {
+                                    displayed,
+                                    ..Default::default()
+                                },
+                            )
+                            .ok();
+
+                        let offset = world.get(id, transition_enter_offset()).ok();
+                        let scale_factor = world.get(id, transition_enter_scale()).ok();
+                        let opacity = world.get(id, transition_enter_opacity()).ok();
+                        let tweens =
+                            presence_tweens(world, id, offset, scale_factor, opacity, true);
+                        if let Ok(state) = world.get_mut(id, transition_state()) {
+                            state.tweens = tweens;
+                        }
+                    }
+                }),
+            query((transition_exiting(),))
+                .spawned()
+                .to_system_with_name("layout/transition/exit", |q, world, qs, _| {
+                    for (id, _) in q.collect_cloned(world, qs) {
+                        if !world.has_component(id, transition_state()) {
+                            despawn_recursive(world, id);
+                            continue;
+                        }
+
+                        detach(world, id);
+                        let offset = world.get(id, transition_exit_offset()).ok();
+                        let scale_factor = world.get(id, transition_exit_scale()).ok();
+                        let opacity = world.get(id, transition_exit_opacity()).ok();
+                        let tweens =
+                            presence_tweens(world, id, offset, scale_factor, opacity, false);
+                        if let Ok(state) = world.get_mut(id, transition_state()) {
+                            state.tweens = tweens;
+                            state.exiting = true;
+                        }
+                    }
+                }),
+            query((transition_state(),))
+                .optional_changed(translation())
+                .optional_changed(scale())
+                .optional_changed(color())
+                .optional_changed(background_color())
+                .to_system_with_name("layout/transition/changed", |q, world, qs, _| {
+                    for (id, (state,)) in q.collect_cloned(world, qs) {
+                        if state.exiting {
+                            continue;
+                        }
+
+                        for property in Property::ALL {
+                            let Some(value) = property.get(world, id) else {
+                                continue;
+                            };
+                            let Some(displayed) = state.displayed[property as usize] else {
+                                if let Ok(state) = world.get_mut(id, transition_state()) {
+                                    state.displayed[property as usize] = Some(value);
+                                }
+                                continue;
+                            };
+                            let retargeted = state
+                                .tweens
+                                .iter()
+                                .any(|t| t.target == id && t.property == property && t.to == value);
+                            if value == displayed || retargeted {
+                                continue;
+                            }
+
+                            let new_tween = tween(world, id, id, property, displayed, value);
+                            write(world, id, property, displayed);
+                            if let Ok(state) = world.get_mut(id, transition_state()) {
+                                state
+                                    .tweens
+                                    .retain(|t| !(t.target == id && t.property == property));
+                                state.tweens.push(new_tween);
+                            }
+                        }
+                    }
+                }),
+            query(()).incl(transition_state()).to_system_with_name(
+                "layout/transition/update",
+                |q, world, qs, _| {
+                    let dt = *world.resource(delta_time());
+                    for (id, _) in q.collect_cloned(world, qs) {
+                        let Ok(state) = world.get_mut(id, transition_state()) else {
+                            continue;
+                        };
+                        if state.tweens.is_empty() && !state.exiting {
+                            continue;
+                        }
+                        let exiting = state.exiting;
+                        let mut tweens = std::mem::take(&mut state.tweens);
+
+                        for tween in &mut tweens {
+                            tween.elapsed += dt;
+                            if world.exists(tween.target) {
+                                write(world, tween.target, tween.property, tween.value());
+                            }
+                        }
+                        tweens.retain(|tween| !tween.finished());
+
+                        if exiting && tweens.is_empty() {
+                            despawn_recursive(world, id);
+                        } else if let Ok(state) = world.get_mut(id, transition_state()) {
+                            state.tweens = tweens;
+                        }
+                    }
+                },
+            ),
+        ],
+    )
+}
@@ -132,26 +132,11 @@ async fn internal_spawn_models_from_defs(
         .into_iter()
         .map(|(url, ids)| async move {
             tracing::debug!("Loading model: {url:#?}");
-            let mut url = match TypedAssetUrl::from_str(&url).context("Failed to parse url") {
-                Ok(url) => url,
+            let key = match ModelFromUrl::from_model_from_url(&url) {
+                Ok(key) => key,
                 Err(e) => return (ids, Err(e)),
             };
-            if !url.0.path().contains("/models/") {
-                url = match url
-                    .0
-                    .as_directory()
-                    .join("models/main.json")
-                    .context("Failed to join url")
-                {
-                    Ok(url) => url.into(),
-                    Err(e) => return (ids, Err(e)),
-                };
-            };
-            match ModelFromUrl(url)
-                .get(assets)
-                .await
-                .context("Failed to load model")
-            {
+            match key.get(assets).await.context("Failed to load model") {
                 Ok(v) => (ids, Ok(v)),
                 Err(e) => (ids, Err(e)),
             }
@@ -277,6 +262,20 @@ impl ModelFromUrl {
     pub fn new(url: impl AsRef<str>) -> anyhow::Result<Self> {
         Ok(Self(TypedAssetUrl::from_str(url.as_ref())?))
     }
+    /// Resolves the value of a `model_from_url` component, which may be the URL of a model
+    /// or of the model crate containing it.
+    pub fn from_model_from_url(url: &str) -> anyhow::Result<Self> {
+        let mut url = TypedAssetUrl::from_str(url).context("Failed to parse url")?;
+        if !url.0.path().contains("/models/") {
+            url = url
+                .0
+                .as_directory()
+                .join("models/main.json")
+                .context("Failed to join url")?
+                .into();
+        }
+        Ok(Self(url))
+    }
 }
 #[async_trait]
 impl AsyncAssetKey<Result<Arc<Model>, AssetError>> for ModelFromUrl {
@@ -40,6 +40,7 @@ impl SyncAssetKey<Arc<MaterialShader>> for LoadingShaderKey {
                 ShaderModule::new("LoadingMaterial", include_file!("loading_material.wgsl"))
                     .with_binding_desc(get_loading_layout()),
             ),
+            bindless: false,
         })
     }
 }
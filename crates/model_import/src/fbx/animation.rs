@@ -119,6 +119,7 @@ pub(crate) fn get_animations(
                         max_time
                     }
                 },
+                events: Vec::new(),
             };
             clip.merge_field_tracks();
             (stack.name.clone(), clip)
@@ -22,6 +22,7 @@ pub mod assimp;
 pub mod fbx;
 pub mod gltf;
 pub mod model_crate;
+pub mod usd;
 
 pub type TextureResolver =
     Arc<dyn Fn(String) -> futures::future::BoxFuture<'static, Option<RgbaImage>> + Sync + Send>;
@@ -323,7 +324,7 @@ fn material_filter_matches(filter: &MaterialFilter, mat: &PbrMaterialDesc) -> bo
 //     }
 // }
 
-pub const MODEL_EXTENSIONS: &[&str] = &["glb", "gltf", "fbx", "obj", "x"];
+pub const MODEL_EXTENSIONS: &[&str] = &["glb", "gltf", "fbx", "obj", "x", "usd", "usda", "usdz"];
 
 /// `../[path]`
 pub fn dotdot_path(path: impl Into<RelativePathBuf>) -> RelativePathBuf {
@@ -1,6 +1,6 @@
 use std::{collections::HashMap, io::Cursor, path::PathBuf, sync::Arc};
 
-use ambient_animation::AnimationClip;
+use ambient_animation::{AnimationClip, AnimationClipEvent};
 use ambient_core::{
     bounding::local_bounding_aabb,
     hierarchy::children,
@@ -260,6 +260,10 @@ impl ModelCrate {
 
         let is_fbx = ext == Some("fbx");
         let is_glb = ext == Some("glb") || ext == Some("gltf");
+        let is_usd = matches!(ext, Some("usd" | "usda" | "usdz"));
+
+        // The transform from the units and up axis of a USD stage to meters and Y-up
+        let mut usd_transform = None;
 
         if force_assimp {
             crate::assimp::import_url(assets, url, self, resolve_texture).await?;
@@ -285,10 +289,15 @@ impl ModelCrate {
             }
         } else if is_glb {
             crate::gltf::import_url(assets, url, self).await?;
+        } else if is_usd {
+            usd_transform = Some(crate::usd::import_url(assets, url, self).await?);
         } else {
             crate::assimp::import_url(assets, url, self, resolve_texture).await?;
         }
         if normalize {
+            if let Some(transform) = usd_transform {
+                self.model_mut().transform(transform);
+            }
             self.model_mut().rotate_yup_to_zup();
             if is_fbx {
                 self.model_mut()
@@ -562,6 +571,39 @@ impl ModelCrate {
             cap_texture_size(image, max_size);
         }
     }
+    /// Limits LOD groups to their `max_levels` least detailed levels by dropping the most
+    /// detailed ones. The meshes of the dropped levels are left in the crate, but are no longer
+    /// referenced.
+    pub fn limit_lod_levels(&mut self, max_levels: usize) {
+        let max_levels = max_levels.max(1);
+        let world = self.model_world_mut();
+        for (_, (cutoffs, primitives)) in
+            query_mut((lod_cutoffs(), pbr_renderer_primitives_from_url()), ()).iter(world, None)
+        {
+            let levels = primitives
+                .iter()
+                .map(|p| p.lod + 1)
+                .max()
+                .unwrap_or_default();
+            if levels <= max_levels {
+                continue;
+            }
+
+            let skip = levels - max_levels;
+            primitives.retain(|p| p.lod >= skip);
+            for primitive in primitives.iter_mut() {
+                primitive.lod -= skip;
+            }
+            *cutoffs = cutoffs.skip_levels(skip);
+        }
+    }
+    /// Adds a named time to the animation `animation`, which is sent to the modules when the
+    /// playback of the animation passes it. Nothing is added if the crate has no such animation.
+    pub fn add_animation_event(&mut self, animation: &str, name: String, time: f32) {
+        if let Some(clip) = self.animations.content.get_mut(animation) {
+            clip.events.push(AnimationClipEvent { name, time });
+        }
+    }
     pub fn update_transforms(&mut self) {
         TransformSystem::new().run(self.model_world_mut(), &FrameEvent);
     }
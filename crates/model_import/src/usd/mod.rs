@@ -0,0 +1,864 @@
+//! Importer for USD scenes, in text layers (`.usda`) or USDZ packages (`.usdz`).
+//!
+//! The stage is composed (sublayers, references, payloads, inherits and variants), and its prims
+//! are converted to entities with their transforms and hierarchy. Meshes are triangulated, and
+//! `UsdPreviewSurface` materials are converted to PBR materials.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use ambient_core::{
+    bounding::local_bounding_aabb,
+    hierarchy::{children, parent},
+    name,
+    transform::{local_to_parent, local_to_world, rotation, scale, translation},
+};
+use ambient_ecs::{Entity, EntityId, World};
+use ambient_gpu::sampler::SamplerKey;
+use ambient_model::{pbr_renderer_primitives_from_url, Model, PbrRenderPrimitiveFromUrl};
+use ambient_native_std::{
+    asset_cache::AssetCache,
+    asset_url::AbsAssetUrl,
+    mesh::{generate_tangents, MeshBuilder},
+    shapes::AABB,
+};
+use ambient_renderer::materials::pbr_material::PbrMaterialDesc;
+use anyhow::Context;
+use glam::{Mat4, Quat, Vec2, Vec3, Vec4};
+use image::RgbaImage;
+use itertools::Itertools;
+use relative_path::RelativePathBuf;
+
+use self::{
+    stage::{AssetLocation, Files, Stage},
+    usda::{PrimSpec, Specifier, Value},
+};
+use crate::{dotdot_path, model_crate::ModelCrate};
+
+mod stage;
+mod usda;
+mod usdz;
+
+/// Imports the stage at `url`, and returns the transform from the units and up axis of the stage
+/// to meters and Y-up, which is applied when the model is normalized.
+pub async fn import_url(
+    assets: &AssetCache,
+    url: &AbsAssetUrl,
+    asset_crate: &mut ModelCrate,
+) -> anyhow::Result<Mat4> {
+    let (files, root) = Files::open(assets, url).await?;
+    let stage = stage::load(&files, &root).await?;
+
+    let mut textures = HashMap::new();
+    for location in texture_locations(&stage) {
+        let image = match files.read(&location).await {
+            Ok(data) => image::load_from_memory(&data).map_err(anyhow::Error::from),
+            Err(err) => Err(err),
+        };
+        match image {
+            Ok(image) => {
+                textures.insert(location, image.into_rgba8());
+            }
+            Err(err) => tracing::warn!("Skipping the texture {location}: {err:?}"),
+        }
+    }
+
+    let mut converter = Converter {
+        stage: &stage,
+        source: url.to_string(),
+        textures,
+        asset_crate,
+        world: World::new("usd", ambient_ecs::WorldContext::Prefab),
+        meshes: HashMap::new(),
+        materials: HashMap::new(),
+        images: HashMap::new(),
+    };
+    let roots = stage
+        .prims
+        .iter()
+        .filter_map(|prim| converter.prim(prim, &format!("/{}", prim.name), None))
+        .collect_vec();
+    let mut world = converter.world;
+    world.add_resource(children(), roots);
+    world.add_resource(
+        name(),
+        url.decoded_path()
+            .file_name()
+            .unwrap_or_default()
+            .to_string(),
+    );
+    asset_crate.models.insert(ModelCrate::MAIN, Model(world));
+
+    // Stages without a unit are in centimeters
+    let meters_per_unit = stage
+        .metadata
+        .get("metersPerUnit")
+        .and_then(Value::as_f32)
+        .unwrap_or(0.01);
+    let scale = Mat4::from_scale(Vec3::splat(meters_per_unit));
+    if stage.metadata.get("upAxis").and_then(Value::as_str) == Some("Z") {
+        Ok(Mat4::from_cols(Vec4::X, Vec4::Z, Vec4::Y, Vec4::W) * scale)
+    } else {
+        Ok(scale)
+    }
+}
+
+/// The files of the texture shaders of the stage.
+fn texture_locations(stage: &Stage) -> Vec<AssetLocation> {
+    fn visit(prim: &PrimSpec, locations: &mut Vec<AssetLocation>) {
+        if prim.type_name.as_deref() == Some("Shader") {
+            if let Some(Value::Asset(asset)) = prim.value("inputs:file") {
+                locations.extend(asset.resolved.clone());
+            }
+        }
+        prim.children
+            .iter()
+            .for_each(|child| visit(child, locations));
+    }
+    let mut locations = Vec::new();
+    stage
+        .prims
+        .iter()
+        .for_each(|prim| visit(prim, &mut locations));
+    let mut seen = HashSet::new();
+    locations.retain(|location| seen.insert(location.clone()));
+    locations
+}
+
+struct Converter<'a> {
+    stage: &'a Stage,
+    source: String,
+    textures: HashMap<AssetLocation, RgbaImage>,
+    asset_crate: &'a mut ModelCrate,
+    world: World,
+    /// The meshes by the hash of their content, as instanced prims have the same meshes.
+    meshes: HashMap<u64, RelativePathBuf>,
+    /// The materials by their serialized description.
+    materials: HashMap<String, RelativePathBuf>,
+    images: HashMap<Vec<Option<(AssetLocation, usize)>>, RelativePathBuf>,
+}
+impl<'a> Converter<'a> {
+    /// Converts the prim at `path` and its descendants to entities. `material` is the material
+    /// bound to its closest ancestor.
+    fn prim(&mut self, prim: &PrimSpec, path: &str, material: Option<String>) -> Option<EntityId> {
+        let type_name = prim.type_name.as_deref().unwrap_or_default();
+        if prim.specifier != Specifier::Def
+            || prim.metadata.get("active").and_then(Value::as_bool) == Some(false)
+            || prim.value("visibility").and_then(Value::as_str) == Some("invisible")
+            || matches!(
+                prim.value("purpose").and_then(Value::as_str),
+                Some("guide" | "proxy")
+            )
+            || matches!(
+                type_name,
+                "Material" | "Shader" | "NodeGraph" | "GeomSubset"
+            )
+        {
+            return None;
+        }
+        let material = material_binding(prim, path).or(material);
+
+        let (prim_scale, prim_rotation, prim_translation) =
+            local_transform(prim).to_scale_rotation_translation();
+        let mut entity = Entity::new()
+            .with(name(), prim.name.clone())
+            .with(translation(), prim_translation)
+            .with(rotation(), prim_rotation)
+            .with(scale(), prim_scale)
+            .with(local_to_world(), Default::default());
+        if type_name == "Mesh" {
+            match self.mesh(prim, path, material.as_deref()) {
+                Ok(Some((primitives, aabb))) => {
+                    entity.set(pbr_renderer_primitives_from_url(), primitives);
+                    entity.set(local_bounding_aabb(), aabb);
+                }
+                Ok(None) => {}
+                Err(err) => tracing::warn!("Skipping the mesh {path}: {err:?}"),
+            }
+        }
+        let id = entity.spawn(&mut self.world);
+
+        let child_ids = prim
+            .children
+            .iter()
+            .filter_map(|child| {
+                self.prim(child, &format!("{path}/{}", child.name), material.clone())
+            })
+            .collect_vec();
+        if !child_ids.is_empty() {
+            for &child in &child_ids {
+                self.world.add_component(child, parent(), id).unwrap();
+                self.world
+                    .add_component(child, local_to_parent(), Default::default())
+                    .unwrap();
+            }
+            self.world.add_component(id, children(), child_ids).unwrap();
+        }
+        Some(id)
+    }
+
+    /// Triangulates the mesh at `path`, with a primitive for each of its material subsets.
+    fn mesh(
+        &mut self,
+        prim: &PrimSpec,
+        path: &str,
+        material: Option<&str>,
+    ) -> anyhow::Result<Option<(Vec<PbrRenderPrimitiveFromUrl>, AABB)>> {
+        let points = prim
+            .value("points")
+            .and_then(vec3s)
+            .context("The mesh has no points")?;
+        let counts = prim
+            .value("faceVertexCounts")
+            .and_then(indices)
+            .context("The mesh has no faceVertexCounts")?;
+        let vertex_indices = prim
+            .value("faceVertexIndices")
+            .and_then(indices)
+            .context("The mesh has no faceVertexIndices")?;
+        if points.is_empty() || counts.is_empty() {
+            return Ok(None);
+        }
+        if counts.iter().sum::<usize>() != vertex_indices.len() {
+            anyhow::bail!("The faceVertexCounts don't add up to the number of faceVertexIndices");
+        }
+        if vertex_indices.iter().any(|&index| index >= points.len()) {
+            anyhow::bail!("A faceVertexIndex is out of range of the points");
+        }
+        let left_handed = prim.value("orientation").and_then(Value::as_str) == Some("leftHanded");
+        let double_sided = prim.value("doubleSided").and_then(Value::as_bool) == Some(true);
+
+        let sizes = (points.len(), vertex_indices.len(), counts.len());
+        let normals = Primvar::read(prim, "primvars:normals", vec3s, sizes)
+            .or_else(|| Primvar::read(prim, "normals", vec3s, sizes));
+        let texcoord_name = if prim.properties.contains_key("primvars:st") {
+            Some("primvars:st")
+        } else {
+            prim.properties
+                .iter()
+                .find(|(name, property)| {
+                    name.starts_with("primvars:")
+                        && matches!(property.type_name.as_str(), "texCoord2f[]" | "float2[]")
+                })
+                .map(|(name, _)| name.as_str())
+        };
+        let texcoords = texcoord_name.and_then(|name| Primvar::read(prim, name, vec2s, sizes));
+        let display_color = prim
+            .value("primvars:displayColor")
+            .and_then(vec3s)
+            .and_then(|colors| colors.first().copied());
+
+        // Each subset of faces bound to a material is a primitive, and the remaining faces are
+        // another
+        let mut in_subset = vec![false; counts.len()];
+        let mut groups = Vec::new();
+        for subset in &prim.children {
+            if subset.type_name.as_deref() != Some("GeomSubset")
+                || subset.value("elementType").and_then(Value::as_str) != Some("face")
+                || !matches!(
+                    subset.value("familyName").and_then(Value::as_str),
+                    Some("materialBind") | None
+                )
+            {
+                continue;
+            }
+            let subset_path = format!("{path}/{}", subset.name);
+            let faces = subset
+                .value("indices")
+                .and_then(indices)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|&face| face < counts.len() && !in_subset[face])
+                .collect_vec();
+            for &face in &faces {
+                in_subset[face] = true;
+            }
+            let subset_material =
+                material_binding(subset, &subset_path).or(material.map(|m| m.to_string()));
+            groups.push((subset_material, faces));
+        }
+        let remaining = (0..counts.len())
+            .filter(|&face| !in_subset[face])
+            .collect_vec();
+        groups.push((material.map(|material| material.to_string()), remaining));
+
+        let mut face_starts = Vec::with_capacity(counts.len());
+        let mut start = 0;
+        for &count in &counts {
+            face_starts.push(start);
+            start += count;
+        }
+
+        let mut primitives = Vec::new();
+        let mut aabbs = Vec::new();
+        for (group_material, faces) in groups {
+            let mut positions = Vec::new();
+            let mut mesh_normals = Vec::new();
+            let mut mesh_texcoords = Vec::new();
+            let mut triangles = Vec::new();
+            for face in faces {
+                let (count, start) = (counts[face], face_starts[face]);
+                if count < 3 {
+                    continue;
+                }
+                let corners = start..start + count;
+                let base = positions.len() as u32;
+                // Newell's method gives the normal of non-planar polygons too
+                let face_normal = {
+                    let mut normal = Vec3::ZERO;
+                    for corner in corners.clone() {
+                        let next = start + (corner - start + 1) % count;
+                        let (a, b) = (points[vertex_indices[corner]], points[vertex_indices[next]]);
+                        normal += newell_term(a, b);
+                    }
+                    let normal = normal.normalize_or_zero();
+                    if left_handed {
+                        -normal
+                    } else {
+                        normal
+                    }
+                };
+                for corner in corners {
+                    let point = vertex_indices[corner];
+                    positions.push(points[point]);
+                    mesh_normals.push(match &normals {
+                        Some(normals) => normals
+                            .get(face, corner, point)
+                            .context("The normals don't cover every vertex")?
+                            .normalize_or_zero(),
+                        None => face_normal,
+                    });
+                    if let Some(texcoords) = &texcoords {
+                        // USD's texture coordinates start at the bottom of the image
+                        let uv = texcoords
+                            .get(face, corner, point)
+                            .context("The texture coordinates don't cover every vertex")?;
+                        mesh_texcoords.push(Vec2::new(uv.x, 1. - uv.y));
+                    }
+                }
+                // Ambient's front faces are clockwise, and USD's are counter-clockwise unless the
+                // mesh is left-handed
+                for k in 1..count as u32 - 1 {
+                    if left_handed {
+                        triangles.extend([base, base + k, base + k + 1]);
+                    } else {
+                        triangles.extend([base, base + k + 1, base + k]);
+                    }
+                }
+            }
+            if triangles.is_empty() {
+                continue;
+            }
+
+            let tangents = if mesh_texcoords.is_empty() {
+                Vec::new()
+            } else {
+                generate_tangents(&positions, &mesh_texcoords, &mesh_normals, &triangles)
+            };
+            let mut hasher = DefaultHasher::new();
+            for value in positions
+                .iter()
+                .chain(&mesh_normals)
+                .flat_map(|v| v.to_array())
+                .chain(mesh_texcoords.iter().flat_map(|v| v.to_array()))
+            {
+                value.to_bits().hash(&mut hasher);
+            }
+            triangles.hash(&mut hasher);
+            let key = hasher.finish();
+
+            let mesh = MeshBuilder {
+                positions,
+                normals: mesh_normals,
+                tangents,
+                texcoords: if mesh_texcoords.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![mesh_texcoords]
+                },
+                indices: triangles,
+                ..MeshBuilder::default()
+            }
+            .build()?;
+            aabbs.push(mesh.aabb());
+            let mesh_path = match self.meshes.get(&key) {
+                Some(mesh_path) => mesh_path.clone(),
+                None => {
+                    let id = format!("{}_{}", asset_name(&prim.name), self.meshes.len());
+                    let mesh_path = self.asset_crate.meshes.insert(id, mesh).path;
+                    self.meshes.insert(key, mesh_path.clone());
+                    mesh_path
+                }
+            };
+
+            let material = match &group_material {
+                Some(material_path) => self.material(material_path, double_sided),
+                None => display_color.map(|color| {
+                    self.insert_material(PbrMaterialDesc {
+                        name: Some(prim.name.clone()),
+                        source: Some(self.source.clone()),
+                        base_color_factor: Some(color.extend(1.)),
+                        metallic_factor: 0.,
+                        roughness_factor: 0.5,
+                        double_sided: Some(double_sided),
+                        ..Default::default()
+                    })
+                }),
+            };
+            primitives.push(PbrRenderPrimitiveFromUrl {
+                mesh: dotdot_path(mesh_path).into(),
+                material: material.map(|material| dotdot_path(material).into()),
+                lod: 0,
+            });
+        }
+        Ok(AABB::unions(&aabbs).map(|aabb| (primitives, aabb)))
+    }
+
+    /// Converts the `UsdPreviewSurface` of the material at `path`.
+    fn material(&mut self, path: &str, double_sided: bool) -> Option<RelativePathBuf> {
+        let Some(material) = self.stage.prim(path) else {
+            tracing::warn!("The material {path} is missing");
+            return None;
+        };
+        let surface = material
+            .properties
+            .get("outputs:surface")
+            .and_then(|property| connection_target(property.connection.as_ref()?))
+            .map(|target| absolute_path(path, target.split('.').next().unwrap_or_default()));
+        let shader_path = surface.or_else(|| {
+            material
+                .children
+                .iter()
+                .find(|child| shader_id(child) == Some("UsdPreviewSurface"))
+                .map(|child| format!("{path}/{}", child.name))
+        })?;
+        let shader = self.stage.prim(&shader_path)?;
+        if shader_id(shader) != Some("UsdPreviewSurface") {
+            tracing::warn!(
+                "Skipping the material {path}, as only UsdPreviewSurface shaders are supported"
+            );
+            return None;
+        }
+
+        let input = |name: &str| self.input(shader, &shader_path, name);
+        let diffuse = input("diffuseColor");
+        let opacity = input("opacity");
+        let metallic = input("metallic");
+        let roughness = input("roughness");
+        let emissive = input("emissiveColor");
+        let normal = input("normal");
+        let opacity_threshold = input("opacityThreshold").and_then(|input| input.as_f32());
+
+        let mut desc = PbrMaterialDesc {
+            name: Some(material.name.clone()),
+            source: Some(self.source.clone()),
+            double_sided: Some(double_sided),
+            sampler: Some(SamplerKey::LINEAR_REPEAT),
+            ..Default::default()
+        };
+
+        let color = diffuse
+            .as_ref()
+            .and_then(Input::as_vec3)
+            .unwrap_or(Vec3::splat(0.18));
+        let alpha = opacity.as_ref().and_then(Input::as_f32).unwrap_or(1.);
+        desc.base_color_factor = Some(color.extend(alpha));
+        let [red, green, blue] = diffuse
+            .as_ref()
+            .and_then(Input::texture_channels)
+            .map_or([None, None, None], |channels| channels.map(Some));
+        let opacity_channel = opacity
+            .as_ref()
+            .and_then(Input::texture_channels)
+            .map(|[channel, ..]| channel);
+        if red.is_some() || opacity_channel.is_some() {
+            if red.is_some() {
+                desc.base_color_factor = Some(Vec3::ONE.extend(alpha));
+            }
+            desc.base_color = self
+                .image([red, green, blue, opacity_channel])
+                .map(|image| dotdot_path(image).into());
+        }
+        if alpha < 1. || opacity_channel.is_some() {
+            match opacity_threshold {
+                Some(threshold) if threshold > 0. => desc.alpha_cutoff = Some(threshold),
+                _ => desc.transparent = Some(true),
+            }
+        }
+
+        desc.metallic_factor = metallic.as_ref().and_then(Input::as_f32).unwrap_or(0.);
+        desc.roughness_factor = roughness.as_ref().and_then(Input::as_f32).unwrap_or(0.5);
+        let metallic_channel = metallic
+            .as_ref()
+            .and_then(Input::texture_channels)
+            .map(|[channel, ..]| channel);
+        let roughness_channel = roughness
+            .as_ref()
+            .and_then(Input::texture_channels)
+            .map(|[channel, ..]| channel);
+        if metallic_channel.is_some() || roughness_channel.is_some() {
+            if metallic_channel.is_some() {
+                desc.metallic_factor = 1.;
+            }
+            if roughness_channel.is_some() {
+                desc.roughness_factor = 1.;
+            }
+            desc.metallic_roughness = self
+                .image([metallic_channel, roughness_channel, None, None])
+                .map(|image| dotdot_path(image).into());
+        }
+
+        if let Some(emissive) = emissive.as_ref().and_then(Input::as_vec3) {
+            desc.emissive_factor = Some(emissive.extend(0.));
+        }
+        if let Some([red, green, blue]) = normal.as_ref().and_then(Input::texture_channels) {
+            desc.normalmap = self
+                .image([Some(red), Some(green), Some(blue), None])
+                .map(|image| dotdot_path(image).into());
+        }
+
+        Some(self.insert_material(desc))
+    }
+
+    fn insert_material(&mut self, desc: PbrMaterialDesc) -> RelativePathBuf {
+        let key = serde_json::to_string(&desc).unwrap_or_default();
+        if let Some(path) = self.materials.get(&key) {
+            return path.clone();
+        }
+        let id = format!(
+            "{}_{}",
+            asset_name(desc.name.as_deref().unwrap_or("material")),
+            self.materials.len()
+        );
+        let path = self.asset_crate.materials.insert(id, desc).path;
+        self.materials.insert(key, path.clone());
+        path
+    }
+
+    /// Combines the channels of textures into an image. The missing channels are white.
+    fn image(&mut self, channels: [Option<(AssetLocation, usize)>; 4]) -> Option<RelativePathBuf> {
+        let key = channels.to_vec();
+        if let Some(path) = self.images.get(&key) {
+            return Some(path.clone());
+        }
+        let sources = channels
+            .iter()
+            .map(|channel| {
+                let (location, index) = channel.as_ref()?;
+                Some((self.textures.get(location)?, *index))
+            })
+            .collect_vec();
+        let (width, height) = sources
+            .iter()
+            .flatten()
+            .map(|(texture, _)| texture.dimensions())
+            .next()?;
+        let sources = sources
+            .into_iter()
+            .map(|source| {
+                source.map(|(texture, index)| {
+                    let texture = if texture.dimensions() == (width, height) {
+                        texture.clone()
+                    } else {
+                        image::imageops::resize(
+                            texture,
+                            width,
+                            height,
+                            image::imageops::FilterType::Triangle,
+                        )
+                    };
+                    (texture, index)
+                })
+            })
+            .collect_vec();
+        let mut image = RgbaImage::from_pixel(width, height, image::Rgba([255; 4]));
+        for (channel, source) in sources.iter().enumerate() {
+            if let Some((texture, index)) = source {
+                for (pixel, source) in image.pixels_mut().zip(texture.pixels()) {
+                    pixel[channel] = source[*index];
+                }
+            }
+        }
+        let id = self.images.len().to_string();
+        let path = self.asset_crate.images.insert(id, image).path;
+        self.images.insert(key, path.clone());
+        Some(path)
+    }
+
+    /// The value of the input `name` of `shader`, following its connections to textures and to
+    /// the inputs of materials and node graphs.
+    fn input(&self, shader: &PrimSpec, shader_path: &str, name: &str) -> Option<Input> {
+        let mut property = shader.properties.get(&format!("inputs:{name}"))?;
+        let mut prim_path = shader_path.to_string();
+        for _ in 0..8 {
+            let Some(connection) = property.connection.as_ref().and_then(connection_target) else {
+                return property.value.clone().map(Input::Value);
+            };
+            let (source_path, attribute) = connection.split_once('.')?;
+            let source_path = absolute_path(&prim_path, source_path);
+            let source = self.stage.prim(&source_path)?;
+            if shader_id(source) == Some("UsdUVTexture") {
+                let Some(Value::Asset(file)) = source.value("inputs:file") else {
+                    return None;
+                };
+                let output = attribute.strip_prefix("outputs:")?.to_string();
+                return Some(Input::Texture(file.resolved.clone()?, output));
+            }
+            property = source.properties.get(attribute)?;
+            prim_path = source_path;
+        }
+        None
+    }
+}
+
+/// The value of a shader input.
+enum Input {
+    Value(Value),
+    /// A texture and the output of the texture used, such as `rgb` or `r`.
+    Texture(AssetLocation, String),
+}
+impl Input {
+    fn as_f32(&self) -> Option<f32> {
+        match self {
+            Input::Value(value) => value.as_f32(),
+            Input::Texture(..) => None,
+        }
+    }
+    fn as_vec3(&self) -> Option<Vec3> {
+        match self {
+            Input::Value(value) => value.as_floats().and_then(|v| vec3(&v)),
+            Input::Texture(..) => None,
+        }
+    }
+    /// The texture and channels used for red, green and blue; a single channel output is used
+    /// for all of them.
+    fn texture_channels(&self) -> Option<[(AssetLocation, usize); 3]> {
+        let Input::Texture(location, output) = self else {
+            return None;
+        };
+        let channel = |name: &str| ["r", "g", "b", "a"].iter().position(|c| *c == name);
+        let channels = match output.as_str() {
+            "rgb" | "rgba" => [0, 1, 2],
+            output => [channel(output)?; 3],
+        };
+        Some(channels.map(|channel| (location.clone(), channel)))
+    }
+}
+
+/// A primvar, or an attribute with an interpolation such as the `normals` of a mesh.
+struct Primvar<T> {
+    values: Vec<T>,
+    indices: Option<Vec<usize>>,
+    interpolation: Interpolation,
+}
+#[derive(Clone, Copy)]
+enum Interpolation {
+    Constant,
+    Uniform,
+    Vertex,
+    FaceVarying,
+}
+impl<T: Copy> Primvar<T> {
+    /// Reads the primvar `name`; `sizes` are the number of points, face vertices and faces of the
+    /// mesh, which are used to guess its interpolation when it is not authored.
+    fn read(
+        prim: &PrimSpec,
+        name: &str,
+        values: impl Fn(&Value) -> Option<Vec<T>>,
+        (points, corners, faces): (usize, usize, usize),
+    ) -> Option<Self> {
+        let property = prim.properties.get(name)?;
+        let values = values(property.value.as_ref()?)?;
+        let indices = prim.value(&format!("{name}:indices")).and_then(indices);
+        let count = indices
+            .as_ref()
+            .map_or(values.len(), |indices| indices.len());
+        let interpolation = match property
+            .metadata
+            .get("interpolation")
+            .and_then(Value::as_str)
+        {
+            Some("constant") => Interpolation::Constant,
+            Some("uniform") => Interpolation::Uniform,
+            Some("vertex" | "varying") => Interpolation::Vertex,
+            Some("faceVarying") => Interpolation::FaceVarying,
+            _ if count == corners => Interpolation::FaceVarying,
+            _ if count == points => Interpolation::Vertex,
+            _ if count == faces => Interpolation::Uniform,
+            _ => Interpolation::Constant,
+        };
+        Some(Self {
+            values,
+            indices,
+            interpolation,
+        })
+    }
+    fn get(&self, face: usize, corner: usize, point: usize) -> Option<T> {
+        let index = match self.interpolation {
+            Interpolation::Constant => 0,
+            Interpolation::Uniform => face,
+            Interpolation::Vertex => point,
+            Interpolation::FaceVarying => corner,
+        };
+        let index = match &self.indices {
+            Some(indices) => *indices.get(index)?,
+            None => index,
+        };
+        self.values.get(index).copied()
+    }
+}
+
+/// The local transform of a prim, from its `xformOpOrder`.
+fn local_transform(prim: &PrimSpec) -> Mat4 {
+    let Some(order) = prim.value("xformOpOrder").and_then(Value::as_slice) else {
+        return Mat4::IDENTITY;
+    };
+    let mut transform = Mat4::IDENTITY;
+    for op in order.iter().filter_map(Value::as_str) {
+        let (invert, op) = match op.strip_prefix("!invert!") {
+            Some(op) => (true, op),
+            None => (false, op),
+        };
+        let Some(values) = prim.value(op).and_then(Value::as_floats) else {
+            continue;
+        };
+        let kind = op.split(':').nth(1).unwrap_or_default();
+        let matrix = match kind {
+            "translate" => vec3(&values).map(Mat4::from_translation),
+            "scale" => vec3(&values).map(Mat4::from_scale),
+            "orient" => (values.len() == 4).then(|| {
+                Mat4::from_quat(
+                    Quat::from_xyzw(values[1], values[2], values[3], values[0]).normalize(),
+                )
+            }),
+            "transform" => (values.len() == 16).then(|| Mat4::from_cols_slice(&values)),
+            _ => kind.strip_prefix("rotate").and_then(|axes| {
+                let axis = |name: char| match name {
+                    'X' => Some((Vec3::X, 0)),
+                    'Y' => Some((Vec3::Y, 1)),
+                    'Z' => Some((Vec3::Z, 2)),
+                    _ => None,
+                };
+                let mut rotation = Quat::IDENTITY;
+                for name in axes.chars() {
+                    let (axis, index) = axis(name)?;
+                    // A single axis rotation has a single angle
+                    let angle = if axes.len() == 1 {
+                        values.first()?
+                    } else {
+                        values.get(index)?
+                    };
+                    // The first axis is applied first
+                    rotation = Quat::from_axis_angle(axis, angle.to_radians()) * rotation;
+                }
+                Some(Mat4::from_quat(rotation))
+            }),
+        };
+        let Some(matrix) = matrix else {
+            tracing::warn!(
+                "Skipping the unsupported transform operation {op} of {}",
+                prim.name
+            );
+            continue;
+        };
+        transform *= if invert { matrix.inverse() } else { matrix };
+    }
+    transform
+}
+
+/// The path of the material bound to `prim`, at `path`.
+fn material_binding(prim: &PrimSpec, path: &str) -> Option<String> {
+    let value = prim.properties.get("material:binding")?.value.as_ref()?;
+    let target = match value {
+        Value::Path(target) => target,
+        Value::List(targets) => match targets.first()? {
+            Value::Path(target) => target,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(absolute_path(path, target))
+}
+
+/// The path a connection points to.
+fn connection_target(value: &Value) -> Option<&str> {
+    match value {
+        Value::Path(path) => Some(path),
+        Value::List(paths) => match paths.first()? {
+            Value::Path(path) => Some(path),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Resolves `target` relative to the prim at `base`, if it is relative.
+fn absolute_path(base: &str, target: &str) -> String {
+    if target.starts_with('/') {
+        return target.to_string();
+    }
+    let mut segments = base.split('/').filter(|s| !s.is_empty()).collect_vec();
+    for segment in target.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    format!("/{}", segments.join("/"))
+}
+
+fn shader_id(prim: &PrimSpec) -> Option<&str> {
+    prim.value("info:id").and_then(Value::as_str)
+}
+
+fn asset_name(name: &str) -> String {
+    name.replace(['/', '\\'], "-")
+}
+
+fn vec3(values: &[f32]) -> Option<Vec3> {
+    (values.len() == 3).then(|| Vec3::from_slice(values))
+}
+
+/// The contribution of the edge from `a` to `b` to the normal computed by Newell's method.
+fn newell_term(a: Vec3, b: Vec3) -> Vec3 {
+    Vec3::new(
+        (a.y - b.y) * (a.z + b.z),
+        (a.z - b.z) * (a.x + b.x),
+        (a.x - b.x) * (a.y + b.y),
+    )
+}
+
+fn vec3s(value: &Value) -> Option<Vec<Vec3>> {
+    value
+        .as_slice()?
+        .iter()
+        .map(|value| vec3(&value.as_floats()?))
+        .collect()
+}
+
+fn vec2s(value: &Value) -> Option<Vec<Vec2>> {
+    value
+        .as_slice()?
+        .iter()
+        .map(|value| match value.as_floats()?.as_slice() {
+            &[x, y] => Some(Vec2::new(x, y)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn indices(value: &Value) -> Option<Vec<usize>> {
+    value
+        .as_slice()?
+        .iter()
+        .map(|value| match value {
+            Value::Number(number) if *number >= 0. => Some(*number as usize),
+            _ => None,
+        })
+        .collect()
+}
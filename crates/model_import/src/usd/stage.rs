@@ -0,0 +1,676 @@
+//! Loading and composition of USD stages.
+//!
+//! A stage is composed from its root layer, the layers it sublayers, and the layers its prims
+//! reference. The composition follows the USD strength ordering in a simplified form: local
+//! opinions are stronger than variants, which are stronger than inherits, references, payloads and
+//! specializes, in that order. Payloads are always loaded.
+
+use std::{collections::HashMap, fmt::Display};
+
+use ambient_native_std::{asset_cache::AssetCache, asset_url::AbsAssetUrl};
+use anyhow::Context;
+use indexmap::{map::Entry, IndexMap};
+
+use super::{
+    usda::{self, Layer, Metadata, PrimSpec, Specifier, Value},
+    usdz::Usdz,
+};
+
+/// How deeply references can be nested, which stops cyclic references.
+const MAX_REFERENCE_DEPTH: usize = 32;
+
+const ARCS: [&str; 4] = ["inherits", "references", "payload", "specializes"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AssetLocation {
+    Url(AbsAssetUrl),
+    /// A file of the USDZ archive being imported.
+    Archive(String),
+}
+impl Display for AssetLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetLocation::Url(url) => write!(f, "{url}"),
+            AssetLocation::Archive(path) => write!(f, "{path}"),
+        }
+    }
+}
+
+/// The files of the stage being imported: either the files next to the root layer, or the files of
+/// a USDZ archive.
+pub struct Files<'a> {
+    assets: &'a AssetCache,
+    url: AbsAssetUrl,
+    archive: Option<Usdz>,
+}
+impl<'a> Files<'a> {
+    /// Opens the stage at `url`, and returns the location of its root layer.
+    pub async fn open(
+        assets: &'a AssetCache,
+        url: &AbsAssetUrl,
+    ) -> anyhow::Result<(Self, AssetLocation)> {
+        let mut files = Self {
+            assets,
+            url: url.clone(),
+            archive: None,
+        };
+        if !url.extension_is("usdz") {
+            return Ok((files, AssetLocation::Url(url.clone())));
+        }
+        let archive = Usdz::parse(url.download_bytes(assets).await?)?;
+        let root = archive
+            .root_layer()
+            .context("The USDZ archive has no USD layer")?
+            .to_string();
+        files.archive = Some(archive);
+        Ok((files, AssetLocation::Archive(root)))
+    }
+
+    /// Resolves the asset `path` relative to the layer at `from`.
+    pub fn resolve(&self, from: &AssetLocation, path: &str) -> Option<AssetLocation> {
+        if path.is_empty() {
+            return None;
+        }
+        if let (AssetLocation::Archive(from), Some(archive)) = (from, &self.archive) {
+            let dir = from.rsplit_once('/').map_or("", |(dir, _)| dir);
+            let mut segments = Vec::new();
+            for segment in dir.split('/').chain(path.split('/')) {
+                match segment {
+                    "" | "." => {}
+                    ".." => {
+                        segments.pop();
+                    }
+                    segment => segments.push(segment),
+                }
+            }
+            let path_in_archive = segments.join("/");
+            if archive.contains(&path_in_archive) {
+                return Some(AssetLocation::Archive(path_in_archive));
+            }
+        }
+        // Files outside the archive are relative to the archive itself
+        let base = match from {
+            AssetLocation::Url(url) => url,
+            AssetLocation::Archive(_) => &self.url,
+        };
+        base.join(path).ok().map(AssetLocation::Url)
+    }
+
+    pub async fn read(&self, location: &AssetLocation) -> anyhow::Result<Vec<u8>> {
+        match location {
+            AssetLocation::Url(url) => url.download_bytes(self.assets).await,
+            AssetLocation::Archive(path) => self
+                .archive
+                .as_ref()
+                .and_then(|archive| archive.get(path))
+                .map(|data| data.to_vec())
+                .with_context(|| format!("{path} is not in the USDZ archive")),
+        }
+    }
+}
+
+/// A composed stage, whose prims have all their opinions merged.
+pub struct Stage {
+    pub metadata: Metadata,
+    pub prims: Vec<PrimSpec>,
+}
+impl Stage {
+    /// Finds the prim at the absolute `path`.
+    pub fn prim(&self, path: &str) -> Option<&PrimSpec> {
+        find_prim(&self.prims, path)
+    }
+}
+
+/// Loads the layers of the stage whose root layer is at `root`, and composes them.
+pub async fn load(files: &Files<'_>, root: &AssetLocation) -> anyhow::Result<Stage> {
+    let mut layers = HashMap::new();
+    let mut pending = vec![root.clone()];
+    while let Some(location) = pending.pop() {
+        if layers.contains_key(&location) {
+            continue;
+        }
+        let layer = match load_layer(files, &location).await {
+            Ok(layer) => layer,
+            Err(err) if &location != root => {
+                tracing::warn!("Skipping the USD layer {location}: {err:?}");
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        pending.extend(dependencies(&layer));
+        layers.insert(location, layer);
+    }
+    compose(&layers, root)
+}
+
+/// Composes the stage whose root layer is at `root` from its loaded `layers`.
+fn compose(layers: &HashMap<AssetLocation, Layer>, root: &AssetLocation) -> anyhow::Result<Stage> {
+    let stacks = layers
+        .keys()
+        .map(|location| Ok((location.clone(), layer_stack(layers, location, 0)?)))
+        .collect::<anyhow::Result<HashMap<_, _>>>()?;
+    let composer = Composer {
+        layers,
+        stacks: &stacks,
+    };
+    let scope = &stacks[root];
+    let prims = scope
+        .iter()
+        .map(|prim| {
+            composer.compose(
+                prim.clone(),
+                &format!("/{}", prim.name),
+                scope,
+                &IndexMap::new(),
+                0,
+            )
+        })
+        .collect::<anyhow::Result<_>>()?;
+    Ok(Stage {
+        metadata: layers[root].metadata.clone(),
+        prims,
+    })
+}
+
+async fn load_layer(files: &Files<'_>, location: &AssetLocation) -> anyhow::Result<Layer> {
+    let data = files.read(location).await?;
+    if data.starts_with(b"PXR-USDC") {
+        anyhow::bail!(
+            "{location} is a binary (USDC) layer, which is not supported; convert it to a text \
+             (USDA) layer, e.g. with `usdcat input.usdc -o output.usda`"
+        );
+    }
+    let text = std::str::from_utf8(&data).with_context(|| format!("{location} is not UTF-8"))?;
+    let mut layer =
+        usda::parse(text).with_context(|| format!("Failed to parse the USD layer {location}"))?;
+    layer.for_each_asset(&mut |asset| asset.resolved = files.resolve(location, &asset.path));
+    Ok(layer)
+}
+
+/// The layers that `layer` sublayers or references.
+fn dependencies(layer: &Layer) -> Vec<AssetLocation> {
+    fn arc_locations(value: &Value, locations: &mut Vec<AssetLocation>) {
+        match value {
+            Value::Asset(asset) | Value::Reference(asset, _) => {
+                locations.extend(asset.resolved.clone())
+            }
+            Value::List(values) => values
+                .iter()
+                .for_each(|value| arc_locations(value, locations)),
+            _ => {}
+        }
+    }
+    fn prim(spec: &PrimSpec, locations: &mut Vec<AssetLocation>) {
+        for arc in ARCS {
+            if let Some(value) = spec.metadata.get(arc) {
+                arc_locations(value, locations);
+            }
+        }
+        spec.children
+            .iter()
+            .for_each(|child| prim(child, locations));
+        for variants in spec.variant_sets.values() {
+            variants
+                .values()
+                .for_each(|variant| prim(variant, locations));
+        }
+    }
+
+    let mut locations = Vec::new();
+    if let Some(sublayers) = layer.metadata.get("subLayers") {
+        arc_locations(sublayers, &mut locations);
+    }
+    layer
+        .prims
+        .iter()
+        .for_each(|spec| prim(spec, &mut locations));
+    locations
+}
+
+/// The root prims of the layer at `location`, merged with the weaker prims of its sublayers.
+fn layer_stack(
+    layers: &HashMap<AssetLocation, Layer>,
+    location: &AssetLocation,
+    depth: usize,
+) -> anyhow::Result<Vec<PrimSpec>> {
+    if depth > MAX_REFERENCE_DEPTH {
+        anyhow::bail!("The sublayers of {location} are nested too deeply; they may be cyclic");
+    }
+    let Some(layer) = layers.get(location) else {
+        return Ok(Vec::new());
+    };
+    let mut prims = layer.prims.clone();
+    let sublayers = match layer.metadata.get("subLayers") {
+        Some(Value::List(sublayers)) => sublayers.as_slice(),
+        _ => &[],
+    };
+    for sublayer in sublayers {
+        if let Value::Asset(usda::AssetPath {
+            resolved: Some(sublayer),
+            ..
+        }) = sublayer
+        {
+            for prim in layer_stack(layers, sublayer, depth + 1)? {
+                merge_into(&mut prims, prim);
+            }
+        }
+    }
+    Ok(prims)
+}
+
+struct Composer<'a> {
+    layers: &'a HashMap<AssetLocation, Layer>,
+    stacks: &'a HashMap<AssetLocation, Vec<PrimSpec>>,
+}
+impl<'a> Composer<'a> {
+    /// Composes the prim `spec` at `path`, whose internal references are found in `scope`.
+    /// `selections` are variant selections of stronger opinions, such as the prim that references
+    /// this one.
+    fn compose(
+        &self,
+        mut spec: PrimSpec,
+        path: &str,
+        scope: &'a [PrimSpec],
+        selections: &IndexMap<String, Value>,
+        depth: usize,
+    ) -> anyhow::Result<PrimSpec> {
+        if depth > MAX_REFERENCE_DEPTH {
+            anyhow::bail!("The references of {path} are nested too deeply; they may be cyclic");
+        }
+
+        // Variants can add variant sets of their own, so the selections are applied until none
+        // are left
+        for _ in 0..MAX_REFERENCE_DEPTH {
+            if spec.variant_sets.is_empty() {
+                break;
+            }
+            let selections = variant_selections(&spec, selections);
+            for (set, mut variants) in std::mem::take(&mut spec.variant_sets) {
+                let selected = selections.get(&set).and_then(Value::as_str);
+                if let Some(variant) = selected.and_then(|selected| variants.shift_remove(selected))
+                {
+                    merge(&mut spec, variant);
+                }
+            }
+        }
+
+        let selections = variant_selections(&spec, selections);
+        for arc in ARCS {
+            let Some(value) = spec.metadata.shift_remove(arc) else {
+                continue;
+            };
+            let targets = match value {
+                Value::List(values) => values,
+                value => vec![value],
+            };
+            for target in targets {
+                let Some((target_path, target_scope)) = self.arc_target(&target, scope) else {
+                    tracing::warn!("Skipping the unresolved {arc} {target:?} of {path}");
+                    continue;
+                };
+                let Some(target) = find_prim(target_scope, &target_path) else {
+                    tracing::warn!(
+                        "Skipping the {arc} of {path} to {target_path}, which is missing"
+                    );
+                    continue;
+                };
+                let mut target = self.compose(
+                    target.clone(),
+                    &target_path,
+                    target_scope,
+                    &selections,
+                    depth + 1,
+                )?;
+                remap_paths(&mut target, &target_path, path);
+                merge(&mut spec, target);
+            }
+        }
+
+        spec.children = std::mem::take(&mut spec.children)
+            .into_iter()
+            .map(|child| {
+                let child_path = format!("{path}/{}", child.name);
+                self.compose(child, &child_path, scope, &IndexMap::new(), depth)
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(spec)
+    }
+
+    /// The path of the prim targeted by a composition arc, and the prims to find it in.
+    fn arc_target(
+        &self,
+        target: &Value,
+        scope: &'a [PrimSpec],
+    ) -> Option<(String, &'a [PrimSpec])> {
+        let (location, path) = match target {
+            Value::Path(path) => return Some((path.clone(), scope)),
+            Value::Asset(asset) => (asset.resolved.as_ref()?, None),
+            Value::Reference(asset, path) => (asset.resolved.as_ref()?, Some(path.clone())),
+            _ => return None,
+        };
+        let prims = self.stacks.get(location)?;
+        let path = match path {
+            Some(path) => path,
+            None => {
+                let default_prim = self.layers[location]
+                    .metadata
+                    .get("defaultPrim")
+                    .and_then(Value::as_str)
+                    .map(|name| name.to_string());
+                let name = default_prim.or_else(|| {
+                    prims
+                        .iter()
+                        .find(|prim| prim.specifier == Specifier::Def)
+                        .map(|prim| prim.name.clone())
+                })?;
+                format!("/{name}")
+            }
+        };
+        Some((path, prims))
+    }
+}
+
+/// The variant selections of `spec`, overridden by the stronger `selections`.
+fn variant_selections(
+    spec: &PrimSpec,
+    selections: &IndexMap<String, Value>,
+) -> IndexMap<String, Value> {
+    let mut result = match spec.metadata.get("variants") {
+        Some(Value::Dictionary(own)) => own.clone(),
+        _ => IndexMap::new(),
+    };
+    result.extend(selections.clone());
+    result
+}
+
+/// Finds the prim at the absolute `path` among the root prims `prims`.
+pub fn find_prim<'a>(prims: &'a [PrimSpec], path: &str) -> Option<&'a PrimSpec> {
+    let mut names = path.trim_start_matches('/').split('/');
+    let root = names.next()?;
+    let mut prim = prims.iter().find(|prim| prim.name == root)?;
+    for name in names {
+        prim = prim.child(name)?;
+    }
+    Some(prim)
+}
+
+/// Merges the weaker opinions of `weaker` into `stronger`.
+fn merge(stronger: &mut PrimSpec, weaker: PrimSpec) {
+    if weaker.specifier == Specifier::Def && stronger.specifier == Specifier::Over {
+        stronger.specifier = Specifier::Def;
+    }
+    if stronger.type_name.is_none() {
+        stronger.type_name = weaker.type_name;
+    }
+    merge_metadata(&mut stronger.metadata, weaker.metadata);
+    for (name, property) in weaker.properties {
+        match stronger.properties.entry(name) {
+            Entry::Occupied(entry) => {
+                let stronger = entry.into_mut();
+                if stronger.value.is_none() {
+                    stronger.value = property.value;
+                }
+                if stronger.connection.is_none() {
+                    stronger.connection = property.connection;
+                }
+                merge_metadata(&mut stronger.metadata, property.metadata);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(property);
+            }
+        }
+    }
+    for child in weaker.children {
+        merge_into(&mut stronger.children, child);
+    }
+    for (set, variants) in weaker.variant_sets {
+        let stronger = stronger.variant_sets.entry(set).or_default();
+        for (name, variant) in variants {
+            merge_into_map(stronger, name, variant);
+        }
+    }
+}
+
+fn merge_metadata(stronger: &mut Metadata, weaker: Metadata) {
+    let into_list = |value: Value| match value {
+        Value::List(values) => values,
+        value => vec![value],
+    };
+    for (key, value) in weaker {
+        match (stronger.get_mut(&key), value) {
+            (None, value) => {
+                stronger.insert(key, value);
+            }
+            // The arcs of both are kept, with the stronger ones first
+            (Some(arcs), weaker) if ARCS.contains(&key.as_str()) => {
+                let mut values = into_list(std::mem::replace(arcs, Value::None));
+                values.extend(into_list(weaker));
+                *arcs = Value::List(values);
+            }
+            (Some(Value::Dictionary(stronger)), Value::Dictionary(weaker)) => {
+                for (key, value) in weaker {
+                    stronger.entry(key).or_insert(value);
+                }
+            }
+            (Some(_), _) => {}
+        }
+    }
+}
+
+/// Merges `weaker` into the prim of the same name in `prims`, or adds it.
+fn merge_into(prims: &mut Vec<PrimSpec>, weaker: PrimSpec) {
+    match prims.iter_mut().find(|prim| prim.name == weaker.name) {
+        Some(stronger) => merge(stronger, weaker),
+        None => prims.push(weaker),
+    }
+}
+
+fn merge_into_map(prims: &mut IndexMap<String, PrimSpec>, name: String, weaker: PrimSpec) {
+    match prims.get_mut(&name) {
+        Some(stronger) => merge(stronger, weaker),
+        None => {
+            prims.insert(name, weaker);
+        }
+    }
+}
+
+/// Rewrites the paths to `from` and its descendants, e.g. relationships to materials, to point to
+/// `to` instead, as a referenced prim is moved to the path of the prim that references it.
+fn remap_paths(spec: &mut PrimSpec, from: &str, to: &str) {
+    fn remap(value: &mut Value, from: &str, to: &str) {
+        match value {
+            Value::Path(path) => {
+                if let Some(rest) = path.strip_prefix(from) {
+                    if rest.is_empty() || rest.starts_with('/') || rest.starts_with('.') {
+                        *path = format!("{to}{rest}");
+                    }
+                }
+            }
+            Value::List(values) | Value::Tuple(values) => {
+                values.iter_mut().for_each(|value| remap(value, from, to))
+            }
+            _ => {}
+        }
+    }
+    if from == to {
+        return;
+    }
+    for property in spec.properties.values_mut() {
+        for value in [&mut property.value, &mut property.connection]
+            .into_iter()
+            .flatten()
+        {
+            remap(value, from, to);
+        }
+    }
+    spec.children
+        .iter_mut()
+        .for_each(|child| remap_paths(child, from, to));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Composes a stage from in-memory layers, whose asset paths are resolved as paths of a USDZ
+    /// archive. The first layer is the root layer.
+    fn stage(files: &[(&str, &str)]) -> anyhow::Result<Stage> {
+        let layers = files
+            .iter()
+            .map(|(path, text)| {
+                let mut layer = usda::parse(text)?;
+                layer.for_each_asset(&mut |asset| {
+                    let path = asset.path.trim_start_matches("./").to_string();
+                    asset.resolved = Some(AssetLocation::Archive(path));
+                });
+                Ok((AssetLocation::Archive(path.to_string()), layer))
+            })
+            .collect::<anyhow::Result<HashMap<_, _>>>()?;
+        compose(&layers, &AssetLocation::Archive(files[0].0.to_string()))
+    }
+
+    const SCENE: &str = r#"#usda 1.0
+(
+    defaultPrim = "World"
+    subLayers = [@./lighting.usda@]
+)
+
+def Xform "World"
+{
+    def "Chair" (
+        prepend references = @./chair.usda@
+        variants = { string color = "red" }
+    )
+    {
+        double3 xformOp:translate = (1, 0, 0)
+    }
+
+    def "Stool" (
+        inherits = </_class_Seat>
+    )
+    {
+    }
+}
+
+class "_class_Seat"
+{
+    double height = 0.5
+}
+"#;
+
+    const LIGHTING: &str = r#"#usda 1.0
+over "World"
+{
+    def SphereLight "Lamp"
+    {
+        float inputs:intensity = 10
+    }
+
+    over "Chair"
+    {
+        double3 xformOp:translate = (5, 0, 0)
+        float weight = 2
+    }
+}
+"#;
+
+    const CHAIR: &str = r#"#usda 1.0
+(
+    defaultPrim = "Chair"
+)
+
+def Xform "Chair" (
+    variantSets = "color"
+)
+{
+    rel material:binding = </Chair/Looks/Wood>
+
+    variantSet "color" = {
+        "red" {
+            color3f[] primvars:displayColor = [(1, 0, 0)]
+        }
+        "blue" {
+            color3f[] primvars:displayColor = [(0, 0, 1)]
+        }
+    }
+
+    def Scope "Looks"
+    {
+        def Material "Wood" {}
+    }
+}
+"#;
+
+    #[test]
+    fn composition() {
+        let stage = stage(&[
+            ("scene.usda", SCENE),
+            ("lighting.usda", LIGHTING),
+            ("chair.usda", CHAIR),
+        ])
+        .unwrap();
+        assert_eq!(stage.metadata["defaultPrim"].as_str(), Some("World"));
+
+        // Sublayers add prims and properties, but are weaker than the root layer
+        let lamp = stage.prim("/World/Lamp").unwrap();
+        assert_eq!(lamp.type_name.as_deref(), Some("SphereLight"));
+        let chair = stage.prim("/World/Chair").unwrap();
+        assert_eq!(
+            chair.value("xformOp:translate").unwrap().as_floats(),
+            Some(vec![1., 0., 0.])
+        );
+        assert_eq!(chair.value("weight").unwrap().as_f32(), Some(2.));
+
+        // The referenced prim is moved to the path of the chair, with the selected variant
+        assert_eq!(chair.specifier, Specifier::Def);
+        assert_eq!(chair.type_name.as_deref(), Some("Xform"));
+        assert!(!chair.metadata.contains_key("references"));
+        assert_eq!(
+            chair.value("primvars:displayColor").unwrap().as_floats(),
+            Some(vec![1., 0., 0.])
+        );
+        assert_eq!(
+            chair.value("material:binding"),
+            Some(&Value::Path("/World/Chair/Looks/Wood".to_string()))
+        );
+        assert!(stage.prim("/World/Chair/Looks/Wood").is_some());
+
+        let stool = stage.prim("/World/Stool").unwrap();
+        assert_eq!(stool.value("height").unwrap().as_f32(), Some(0.5));
+    }
+
+    #[test]
+    fn missing_and_cyclic_references() {
+        // Missing layers and prims are skipped
+        let stage = stage(&[(
+            "scene.usda",
+            r#"#usda 1.0
+def "Chair" (
+    references = [@./missing.usda@, </Missing>]
+)
+{
+}
+"#,
+        )])
+        .unwrap();
+        assert!(stage.prim("/Chair").is_some());
+
+        let result = stage(&[(
+            "scene.usda",
+            r#"#usda 1.0
+def "A" (
+    references = </B>
+)
+{
+}
+
+def "B" (
+    references = </A>
+)
+{
+}
+"#,
+        )]);
+        assert!(result.is_err());
+    }
+}
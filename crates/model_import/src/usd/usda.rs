@@ -0,0 +1,823 @@
+//! A parser for USDA, the text format of USD layers.
+//!
+//! This covers the parts of the format that describe scenes: layer and prim metadata, prims and
+//! their properties, connections, relationships and variant sets. Time samples are reduced to
+//! their first sample, as animation is not imported.
+
+use anyhow::Context;
+use indexmap::IndexMap;
+
+use super::stage::AssetLocation;
+
+/// A layer, i.e. a single USD file.
+#[derive(Debug, Clone, Default)]
+pub struct Layer {
+    pub metadata: Metadata,
+    pub prims: Vec<PrimSpec>,
+}
+
+pub type Metadata = IndexMap<String, Value>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Specifier {
+    Def,
+    Over,
+    Class,
+}
+
+/// The opinions of a layer about a prim.
+#[derive(Debug, Clone)]
+pub struct PrimSpec {
+    pub specifier: Specifier,
+    pub type_name: Option<String>,
+    pub name: String,
+    pub metadata: Metadata,
+    pub properties: IndexMap<String, Property>,
+    pub children: Vec<PrimSpec>,
+    /// The variants of each variant set, by name. The body of a variant is a prim spec that is
+    /// merged into the prim when the variant is selected.
+    pub variant_sets: IndexMap<String, IndexMap<String, PrimSpec>>,
+}
+impl PrimSpec {
+    fn new(specifier: Specifier, type_name: Option<String>, name: String) -> Self {
+        Self {
+            specifier,
+            type_name,
+            name,
+            metadata: Metadata::new(),
+            properties: IndexMap::new(),
+            children: Vec::new(),
+            variant_sets: IndexMap::new(),
+        }
+    }
+    pub fn child(&self, name: &str) -> Option<&PrimSpec> {
+        self.children.iter().find(|child| child.name == name)
+    }
+    /// The value of the property `name`.
+    pub fn value(&self, name: &str) -> Option<&Value> {
+        self.properties.get(name)?.value.as_ref()
+    }
+}
+
+/// An attribute or a relationship (with a `type_name` of `rel`).
+#[derive(Debug, Clone, Default)]
+pub struct Property {
+    pub type_name: String,
+    pub value: Option<Value>,
+    /// The attribute this attribute is connected to.
+    pub connection: Option<Value>,
+    pub metadata: Metadata,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    None,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    /// A bare identifier.
+    Ident(String),
+    Asset(AssetPath),
+    /// A prim or property path, such as `</World/Mesh.points>`.
+    Path(String),
+    /// An asset path followed by a prim path, as used by references and payloads.
+    Reference(AssetPath, String),
+    Tuple(Vec<Value>),
+    List(Vec<Value>),
+    Dictionary(IndexMap<String, Value>),
+}
+impl Value {
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Value::Number(value) => Some(*value as f32),
+            Value::Bool(value) => Some(*value as u8 as f32),
+            _ => None,
+        }
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            Value::Number(value) => Some(*value != 0.),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) | Value::Ident(value) => Some(value),
+            _ => None,
+        }
+    }
+    pub fn as_slice(&self) -> Option<&[Value]> {
+        match self {
+            Value::Tuple(values) | Value::List(values) => Some(values),
+            _ => None,
+        }
+    }
+    /// The numbers of a tuple, including those of nested tuples such as the rows of a matrix, or
+    /// a single number.
+    pub fn as_floats(&self) -> Option<Vec<f32>> {
+        match self {
+            Value::Number(value) => Some(vec![*value as f32]),
+            _ => Some(
+                self.as_slice()?
+                    .iter()
+                    .map(Value::as_floats)
+                    .collect::<Option<Vec<_>>>()?
+                    .concat(),
+            ),
+        }
+    }
+    fn for_each_asset(&mut self, f: &mut impl FnMut(&mut AssetPath)) {
+        match self {
+            Value::Asset(asset) | Value::Reference(asset, _) => f(asset),
+            Value::Tuple(values) | Value::List(values) => {
+                values.iter_mut().for_each(|value| value.for_each_asset(f))
+            }
+            Value::Dictionary(values) => values
+                .values_mut()
+                .for_each(|value| value.for_each_asset(f)),
+            _ => {}
+        }
+    }
+}
+
+/// An asset path, such as `@./textures/wood.png@`, and where it resolves to relative to the layer
+/// it is written in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetPath {
+    pub path: String,
+    pub resolved: Option<AssetLocation>,
+}
+
+impl Layer {
+    /// Calls `f` on every asset path of the layer, e.g. to resolve them.
+    pub fn for_each_asset(&mut self, f: &mut impl FnMut(&mut AssetPath)) {
+        fn prim(spec: &mut PrimSpec, f: &mut impl FnMut(&mut AssetPath)) {
+            spec.metadata
+                .values_mut()
+                .for_each(|value| value.for_each_asset(f));
+            for property in spec.properties.values_mut() {
+                for value in [&mut property.value, &mut property.connection]
+                    .into_iter()
+                    .flatten()
+                {
+                    value.for_each_asset(f);
+                }
+            }
+            spec.children.iter_mut().for_each(|child| prim(child, f));
+            for variants in spec.variant_sets.values_mut() {
+                variants.values_mut().for_each(|variant| prim(variant, f));
+            }
+        }
+        self.metadata
+            .values_mut()
+            .for_each(|value| value.for_each_asset(f));
+        self.prims.iter_mut().for_each(|spec| prim(spec, f));
+    }
+}
+
+/// Parses a USDA layer.
+pub fn parse(text: &str) -> anyhow::Result<Layer> {
+    if !text.starts_with("#usda") {
+        anyhow::bail!("Not a USDA layer; USDA layers start with `#usda 1.0`");
+    }
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        position: 0,
+    };
+
+    let mut layer = Layer::default();
+    if parser.eat(&Token::Punct('(')) {
+        layer.metadata = parser.metadata()?;
+    }
+    while parser.peek().is_some() {
+        layer.prims.push(parser.prim()?);
+    }
+    Ok(layer)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Punct(char),
+    Ident(String),
+    String(String),
+    Number(f64),
+    Asset(String),
+    Path(String),
+}
+
+fn tokenize(text: &str) -> anyhow::Result<Vec<(Token, usize)>> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    let is_ident_start = |c: char| c.is_ascii_alphabetic() || c == '_';
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    while i < chars.len() {
+        let c = chars[i];
+        let start_line = line;
+        match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+            }
+            c if c.is_whitespace() => i += 1,
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '"' | '\'' => {
+                let triple = chars.get(i + 1) == Some(&c) && chars.get(i + 2) == Some(&c);
+                i += if triple { 3 } else { 1 };
+                let mut value = String::new();
+                loop {
+                    let Some(&next) = chars.get(i) else {
+                        anyhow::bail!("line {start_line}: unterminated string");
+                    };
+                    if next == c
+                        && (!triple
+                            || (chars.get(i + 1) == Some(&c) && chars.get(i + 2) == Some(&c)))
+                    {
+                        i += if triple { 3 } else { 1 };
+                        break;
+                    }
+                    match next {
+                        '\\' => {
+                            let escaped = chars.get(i + 1).copied().unwrap_or('\\');
+                            value.push(match escaped {
+                                'n' => '\n',
+                                't' => '\t',
+                                other => other,
+                            });
+                            i += 2;
+                        }
+                        '\n' if !triple => anyhow::bail!("line {start_line}: unterminated string"),
+                        _ => {
+                            if next == '\n' {
+                                line += 1;
+                            }
+                            value.push(next);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push((Token::String(value), start_line));
+            }
+            '@' => {
+                // Asset paths containing `@` are delimited by `@@@`
+                let delimiter = if chars[i..].starts_with(&['@', '@', '@']) {
+                    3
+                } else {
+                    1
+                };
+                i += delimiter;
+                let start = i;
+                while i < chars.len() && !chars[i..].starts_with(&['@'; 3][..delimiter]) {
+                    if chars[i] == '\n' {
+                        anyhow::bail!("line {start_line}: unterminated asset path");
+                    }
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    anyhow::bail!("line {start_line}: unterminated asset path");
+                }
+                tokens.push((Token::Asset(chars[start..i].iter().collect()), start_line));
+                i += delimiter;
+            }
+            '<' => {
+                let start = i + 1;
+                while i < chars.len() && chars[i] != '>' {
+                    if chars[i] == '\n' {
+                        anyhow::bail!("line {start_line}: unterminated path");
+                    }
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    anyhow::bail!("line {start_line}: unterminated path");
+                }
+                tokens.push((Token::Path(chars[start..i].iter().collect()), start_line));
+                i += 1;
+            }
+            c if c.is_ascii_digit()
+                || (c == '.' && chars.get(i + 1).map_or(false, char::is_ascii_digit))
+                || ((c == '-' || c == '+')
+                    && chars.get(i + 1).map_or(false, |&next| {
+                        next.is_ascii_digit() || matches!(next, '.' | 'i' | 'n')
+                    })) =>
+            {
+                let start = i;
+                i += 1;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric()
+                        || chars[i] == '.'
+                        || ((chars[i] == '-' || chars[i] == '+')
+                            && matches!(chars[i - 1], 'e' | 'E')))
+                {
+                    i += 1;
+                }
+                let text = chars[start..i].iter().collect::<String>();
+                let number = match text.trim_start_matches('+') {
+                    "inf" => f64::INFINITY,
+                    "-inf" => f64::NEG_INFINITY,
+                    "nan" | "-nan" => f64::NAN,
+                    text => text
+                        .parse::<f64>()
+                        .with_context(|| format!("line {start_line}: invalid number {text}"))?,
+                };
+                tokens.push((Token::Number(number), start_line));
+            }
+            c if is_ident_start(c) => {
+                let start = i;
+                while i < chars.len()
+                    && (is_ident(chars[i])
+                        || (chars[i] == ':'
+                            && chars.get(i + 1).map_or(false, |&next| is_ident_start(next))))
+                {
+                    i += 1;
+                }
+                tokens.push((Token::Ident(chars[start..i].iter().collect()), start_line));
+            }
+            '(' | ')' | '[' | ']' | '{' | '}' | '=' | ',' | ';' | ':' | '.' => {
+                tokens.push((Token::Punct(c), start_line));
+                i += 1;
+            }
+            _ => anyhow::bail!("line {line}: unexpected character {c:?}"),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+}
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+    fn next(&mut self) -> anyhow::Result<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .map(|(token, _)| token.clone())
+            .with_context(|| format!("line {}: unexpected end of layer", self.line()))?;
+        self.position += 1;
+        Ok(token)
+    }
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+    fn expect(&mut self, punct: char) -> anyhow::Result<()> {
+        let line = self.line();
+        match self.next()? {
+            Token::Punct(c) if c == punct => Ok(()),
+            token => anyhow::bail!("line {line}: expected `{punct}`, found {token:?}"),
+        }
+    }
+    fn ident(&mut self) -> anyhow::Result<String> {
+        let line = self.line();
+        match self.next()? {
+            Token::Ident(ident) => Ok(ident),
+            token => anyhow::bail!("line {line}: expected an identifier, found {token:?}"),
+        }
+    }
+    fn string(&mut self) -> anyhow::Result<String> {
+        let line = self.line();
+        match self.next()? {
+            Token::String(string) => Ok(string),
+            token => anyhow::bail!("line {line}: expected a string, found {token:?}"),
+        }
+    }
+    fn skip_separators(&mut self) {
+        while self.eat(&Token::Punct(';')) {}
+    }
+
+    /// Parses the entries of a metadata block, after its opening `(`.
+    fn metadata(&mut self) -> anyhow::Result<Metadata> {
+        let mut metadata = Metadata::new();
+        loop {
+            self.skip_separators();
+            if self.eat(&Token::Punct(')')) {
+                return Ok(metadata);
+            }
+            let line = self.line();
+            match self.next()? {
+                Token::String(doc) => {
+                    metadata.insert("doc".to_string(), Value::String(doc));
+                }
+                Token::Ident(ident) => {
+                    let list_op = matches!(
+                        ident.as_str(),
+                        "prepend" | "append" | "add" | "delete" | "reorder"
+                    );
+                    let key = if list_op {
+                        self.ident()?
+                    } else {
+                        ident.clone()
+                    };
+                    self.expect('=')?;
+                    let value = self.value()?;
+                    match ident.as_str() {
+                        "delete" | "reorder" => {}
+                        // List edits add to the items of the list
+                        _ if list_op => {
+                            let mut items = match metadata.remove(&key) {
+                                Some(Value::List(items)) => items,
+                                Some(value) => vec![value],
+                                None => Vec::new(),
+                            };
+                            match value {
+                                Value::List(values) => items.extend(values),
+                                value => items.push(value),
+                            }
+                            metadata.insert(key, Value::List(items));
+                        }
+                        _ => {
+                            metadata.insert(key, value);
+                        }
+                    }
+                }
+                token => anyhow::bail!("line {line}: unexpected {token:?} in metadata"),
+            }
+        }
+    }
+
+    fn value(&mut self) -> anyhow::Result<Value> {
+        let line = self.line();
+        Ok(match self.next()? {
+            Token::Number(number) => Value::Number(number),
+            Token::String(string) => Value::String(string),
+            Token::Path(path) => Value::Path(path),
+            Token::Asset(path) => {
+                let asset = AssetPath {
+                    path,
+                    resolved: None,
+                };
+                match self.peek() {
+                    Some(Token::Path(prim)) => {
+                        let prim = prim.clone();
+                        self.position += 1;
+                        Value::Reference(asset, prim)
+                    }
+                    _ => Value::Asset(asset),
+                }
+            }
+            Token::Ident(ident) => match ident.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "None" => Value::None,
+                _ => Value::Ident(ident),
+            },
+            Token::Punct(open @ ('(' | '[')) => {
+                let close = if open == '(' { ')' } else { ']' };
+                let mut values = Vec::new();
+                loop {
+                    if self.eat(&Token::Punct(close)) {
+                        break;
+                    }
+                    values.push(self.value()?);
+                    if !self.eat(&Token::Punct(',')) {
+                        self.expect(close)?;
+                        break;
+                    }
+                }
+                if open == '(' {
+                    Value::Tuple(values)
+                } else {
+                    Value::List(values)
+                }
+            }
+            Token::Punct('{') => Value::Dictionary(self.dictionary()?),
+            token => anyhow::bail!("line {line}: expected a value, found {token:?}"),
+        })
+    }
+
+    /// Parses the entries of a dictionary, such as `{ string shadingVariant = "red" }`, after its
+    /// opening `{`.
+    fn dictionary(&mut self) -> anyhow::Result<IndexMap<String, Value>> {
+        let mut entries = IndexMap::new();
+        loop {
+            self.skip_separators();
+            if self.eat(&Token::Punct('}')) {
+                return Ok(entries);
+            }
+            // The type of the value
+            self.ident()?;
+            if self.eat(&Token::Punct('[')) {
+                self.expect(']')?;
+            }
+            let line = self.line();
+            let key = match self.next()? {
+                Token::Ident(key) | Token::String(key) => key,
+                token => anyhow::bail!("line {line}: expected a dictionary key, found {token:?}"),
+            };
+            self.expect('=')?;
+            entries.insert(key, self.value()?);
+        }
+    }
+
+    fn prim(&mut self) -> anyhow::Result<PrimSpec> {
+        let line = self.line();
+        let specifier = match self.ident()?.as_str() {
+            "def" => Specifier::Def,
+            "over" => Specifier::Over,
+            "class" => Specifier::Class,
+            other => anyhow::bail!("line {line}: expected `def`, `over` or `class`, found {other}"),
+        };
+        let type_name = match self.peek() {
+            Some(Token::Ident(_)) => Some(self.ident()?),
+            _ => None,
+        };
+        let mut spec = PrimSpec::new(specifier, type_name, self.string()?);
+        if self.eat(&Token::Punct('(')) {
+            spec.metadata = self.metadata()?;
+        }
+        self.expect('{')?;
+        self.prim_body(&mut spec)?;
+        Ok(spec)
+    }
+
+    /// Parses the properties, children and variant sets of a prim, after its opening `{`.
+    fn prim_body(&mut self, spec: &mut PrimSpec) -> anyhow::Result<()> {
+        loop {
+            self.skip_separators();
+            if self.eat(&Token::Punct('}')) {
+                return Ok(());
+            }
+            match self.peek() {
+                Some(Token::Ident(ident)) if matches!(ident.as_str(), "def" | "over" | "class") => {
+                    spec.children.push(self.prim()?);
+                }
+                // The order of children and properties doesn't matter when importing
+                Some(Token::Ident(ident)) if ident == "reorder" => {
+                    self.position += 1;
+                    self.ident()?;
+                    self.expect('=')?;
+                    self.value()?;
+                }
+                Some(Token::Ident(ident)) if ident == "variantSet" => {
+                    self.position += 1;
+                    let set = self.string()?;
+                    self.expect('=')?;
+                    self.expect('{')?;
+                    let mut variants = IndexMap::new();
+                    loop {
+                        self.skip_separators();
+                        if self.eat(&Token::Punct('}')) {
+                            break;
+                        }
+                        let name = self.string()?;
+                        let mut variant = PrimSpec::new(Specifier::Over, None, name.clone());
+                        if self.eat(&Token::Punct('(')) {
+                            variant.metadata = self.metadata()?;
+                        }
+                        self.expect('{')?;
+                        self.prim_body(&mut variant)?;
+                        variants.insert(name, variant);
+                    }
+                    spec.variant_sets.insert(set, variants);
+                }
+                _ => self.property(spec)?,
+            }
+        }
+    }
+
+    fn property(&mut self, spec: &mut PrimSpec) -> anyhow::Result<()> {
+        let mut type_name = self.ident()?;
+        while matches!(
+            type_name.as_str(),
+            "custom" | "uniform" | "varying" | "config" | "prepend" | "append" | "add" | "delete"
+        ) {
+            type_name = self.ident()?;
+        }
+        if self.eat(&Token::Punct('[')) {
+            self.expect(']')?;
+            type_name.push_str("[]");
+        }
+        let name = self.ident()?;
+
+        let mut connection = false;
+        let mut time_samples = false;
+        if self.eat(&Token::Punct('.')) {
+            match self.ident()?.as_str() {
+                "connect" => connection = true,
+                "timeSamples" => time_samples = true,
+                other => anyhow::bail!("line {}: unsupported property field {other}", self.line()),
+            }
+        }
+
+        let property = spec.properties.entry(name).or_default();
+        property.type_name = type_name;
+        if self.eat(&Token::Punct('=')) {
+            if time_samples {
+                self.expect('{')?;
+                let mut first = None;
+                loop {
+                    self.skip_separators();
+                    if self.eat(&Token::Punct('}')) {
+                        break;
+                    }
+                    let line = self.line();
+                    let time = match self.next()? {
+                        Token::Number(time) => time,
+                        token => anyhow::bail!("line {line}: expected a time, found {token:?}"),
+                    };
+                    self.expect(':')?;
+                    let value = self.value()?;
+                    if first.as_ref().map_or(true, |(first, _)| time < *first) {
+                        first = Some((time, value));
+                    }
+                    self.eat(&Token::Punct(','));
+                }
+                if property.value.is_none() {
+                    property.value = first.map(|(_, value)| value);
+                }
+            } else if connection {
+                property.connection = Some(self.value()?);
+            } else {
+                property.value = Some(self.value()?);
+            }
+        }
+        if self.eat(&Token::Punct('(')) {
+            let metadata = self.metadata()?;
+            property.metadata.extend(metadata);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn layers() {
+        let layer = parse(
+            r#"#usda 1.0
+(
+    """A layer
+    with a long doc"""
+    upAxis = "Z"
+    metersPerUnit = 0.01
+    customLayerData = { string creator = "test"; int[] versions = [1, 2] }
+)
+
+# A comment
+def Xform "World" (
+    prepend apiSchemas = ["MaterialBindingAPI"]
+    append apiSchemas = "ShadowAPI"
+    kind = "component"
+)
+{
+    custom uniform token purpose = "render"
+    double3 xformOp:translate.timeSamples = {
+        10: (1, 1, 1),
+        0: (0, -2.5, 1e3),
+    }
+    uniform token[] xformOpOrder = ["xformOp:translate"]
+    rel material:binding = </World/Looks/Wood>
+    asset texture = @@@textures/a@b.png@@@
+    point3f[] points = [(0, 0, 0), (1, 0, 0)] (
+        interpolation = "vertex"
+    )
+
+    def Shader "Shader"
+    {
+        token outputs:surface.connect = </World/Looks/Wood.outputs:surface>;
+    }
+    over "Empty" {}
+}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            layer.metadata["doc"],
+            Value::String("A layer\n    with a long doc".to_string())
+        );
+        assert_eq!(layer.metadata["upAxis"].as_str(), Some("Z"));
+        assert_eq!(layer.metadata["metersPerUnit"].as_f32(), Some(0.01));
+        let Value::Dictionary(data) = &layer.metadata["customLayerData"] else {
+            panic!("{:?}", layer.metadata["customLayerData"]);
+        };
+        assert_eq!(data["creator"].as_str(), Some("test"));
+        assert_eq!(data["versions"].as_floats(), Some(vec![1., 2.]));
+
+        let world = &layer.prims[0];
+        assert_eq!(world.specifier, Specifier::Def);
+        assert_eq!(world.type_name.as_deref(), Some("Xform"));
+        assert_eq!(world.name, "World");
+        // List edits are combined
+        assert_eq!(
+            world.metadata["apiSchemas"],
+            Value::List(vec![
+                Value::String("MaterialBindingAPI".to_string()),
+                Value::String("ShadowAPI".to_string()),
+            ])
+        );
+        assert_eq!(world.properties["purpose"].type_name, "token");
+        // Only the first time sample is kept
+        assert_eq!(
+            world.value("xformOp:translate").unwrap().as_floats(),
+            Some(vec![0., -2.5, 1000.])
+        );
+        assert_eq!(world.properties["xformOpOrder"].type_name, "token[]");
+        assert_eq!(world.properties["material:binding"].type_name, "rel");
+        assert_eq!(
+            world.value("material:binding"),
+            Some(&Value::Path("/World/Looks/Wood".to_string()))
+        );
+        assert_eq!(
+            world.value("texture"),
+            Some(&Value::Asset(AssetPath {
+                path: "textures/a@b.png".to_string(),
+                resolved: None,
+            }))
+        );
+        let points = &world.properties["points"];
+        assert_eq!(
+            points.value.as_ref().unwrap().as_floats(),
+            Some(vec![0., 0., 0., 1., 0., 0.])
+        );
+        assert_eq!(points.metadata["interpolation"].as_str(), Some("vertex"));
+
+        let shader = world.child("Shader").unwrap();
+        let output = &shader.properties["outputs:surface"];
+        assert_eq!(output.value, None);
+        assert_eq!(
+            output.connection,
+            Some(Value::Path("/World/Looks/Wood.outputs:surface".to_string()))
+        );
+        assert_eq!(world.child("Empty").unwrap().specifier, Specifier::Over);
+    }
+
+    #[test]
+    fn variant_sets() {
+        let layer = parse(
+            r#"#usda 1.0
+def "Chair" (
+    variants = { string color = "red" }
+    prepend variantSets = "color"
+)
+{
+    variantSet "color" = {
+        "red" (doc = "Red") {
+            color3f[] primvars:displayColor = [(1, 0, 0)]
+        }
+        "blue" {
+            def "Cushion" {}
+        }
+    }
+}
+"#,
+        )
+        .unwrap();
+
+        let chair = &layer.prims[0];
+        let Value::Dictionary(selections) = &chair.metadata["variants"] else {
+            panic!("{:?}", chair.metadata["variants"]);
+        };
+        assert_eq!(selections["color"].as_str(), Some("red"));
+        let colors = &chair.variant_sets["color"];
+        assert_eq!(colors.keys().collect::<Vec<_>>(), ["red", "blue"]);
+        assert_eq!(colors["red"].metadata["doc"].as_str(), Some("Red"));
+        assert_eq!(
+            colors["red"]
+                .value("primvars:displayColor")
+                .unwrap()
+                .as_floats(),
+            Some(vec![1., 0., 0.])
+        );
+        assert!(colors["blue"].child("Cushion").is_some());
+    }
+
+    #[test]
+    fn errors() {
+        assert!(parse("def \"World\" {}").is_err());
+
+        let error = parse("#usda 1.0\ndef \"World\" {\n    float radius = \n}\n").unwrap_err();
+        assert!(error.to_string().starts_with("line 4:"), "{error}");
+        let error = parse("#usda 1.0\n\ndef \"World\" (doc = \"unterminated\n) {}\n").unwrap_err();
+        assert!(error.to_string().starts_with("line 3:"), "{error}");
+        let error = parse("#usda 1.0\ndef \"World\" {\n").unwrap_err();
+        assert!(
+            error.to_string().contains("unexpected end of layer"),
+            "{error}"
+        );
+        let error =
+            parse("#usda 1.0\ndef \"World\" {\n    float radius.spline = 1\n}").unwrap_err();
+        assert!(error.to_string().contains("spline"), "{error}");
+    }
+}
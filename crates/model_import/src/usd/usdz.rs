@@ -0,0 +1,142 @@
+use std::ops::Range;
+
+use indexmap::IndexMap;
+
+/// A USDZ package: a zip archive whose first file is the root layer.
+///
+/// The USDZ specification requires the files of the archive to be stored uncompressed, so they
+/// are read in place.
+pub struct Usdz {
+    data: Vec<u8>,
+    files: IndexMap<String, Range<usize>>,
+}
+impl Usdz {
+    pub fn parse(data: Vec<u8>) -> anyhow::Result<Self> {
+        const LOCAL_FILE_HEADER: u32 = 0x04034b50;
+
+        let u16_at = |offset: usize| -> anyhow::Result<usize> {
+            let bytes = data
+                .get(offset..offset + 2)
+                .ok_or_else(|| anyhow::anyhow!("Truncated USDZ archive"))?;
+            Ok(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+        };
+        let u32_at = |offset: usize| -> anyhow::Result<u32> {
+            let bytes = data
+                .get(offset..offset + 4)
+                .ok_or_else(|| anyhow::anyhow!("Truncated USDZ archive"))?;
+            Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        };
+
+        let mut files = IndexMap::new();
+        let mut offset = 0;
+        // The local file headers are followed by the central directory, which is not needed
+        while offset + 4 <= data.len() && u32_at(offset)? == LOCAL_FILE_HEADER {
+            let flags = u16_at(offset + 6)?;
+            let method = u16_at(offset + 8)?;
+            let size = u32_at(offset + 18)? as usize;
+            let name_length = u16_at(offset + 26)?;
+            let extra_length = u16_at(offset + 28)?;
+            let name_start = offset + 30;
+            let name = data
+                .get(name_start..name_start + name_length)
+                .ok_or_else(|| anyhow::anyhow!("Truncated USDZ archive"))?;
+            let name = String::from_utf8_lossy(name).to_string();
+            if method != 0 {
+                anyhow::bail!(
+                    "The file {name} of the USDZ archive is compressed; USDZ archives must be \
+                     stored uncompressed"
+                );
+            }
+            if flags & 0x8 != 0 {
+                anyhow::bail!("The file {name} of the USDZ archive has no size in its header");
+            }
+            let start = name_start + name_length + extra_length;
+            if start + size > data.len() {
+                anyhow::bail!("Truncated USDZ archive");
+            }
+            files.insert(name, start..start + size);
+            offset = start + size;
+        }
+        if files.is_empty() {
+            anyhow::bail!("Not a USDZ archive");
+        }
+        Ok(Self { data, files })
+    }
+
+    /// The path of the root layer, which is the first USD file of the archive.
+    pub fn root_layer(&self) -> Option<&str> {
+        self.files
+            .keys()
+            .find(|name| {
+                let name = name.to_lowercase();
+                name.ends_with(".usda") || name.ends_with(".usdc") || name.ends_with(".usd")
+            })
+            .map(|name| name.as_str())
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+
+    pub fn get(&self, path: &str) -> Option<&[u8]> {
+        self.files.get(path).map(|range| &self.data[range.clone()])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Writes a zip archive that stores `files` with the compression `method`.
+    fn archive(files: &[(&str, &[u8])], method: u16) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (name, contents) in files {
+            data.extend(0x04034b50u32.to_le_bytes());
+            data.extend(20u16.to_le_bytes()); // Version needed to extract
+            data.extend(0u16.to_le_bytes()); // Flags
+            data.extend(method.to_le_bytes());
+            data.extend([0; 8]); // Modification time, date and CRC-32
+            data.extend((contents.len() as u32).to_le_bytes());
+            data.extend((contents.len() as u32).to_le_bytes());
+            data.extend((name.len() as u16).to_le_bytes());
+            data.extend(0u16.to_le_bytes()); // Extra field length
+            data.extend(name.as_bytes());
+            data.extend(*contents);
+        }
+        // The start of the central directory, which ends the files
+        data.extend(0x02014b50u32.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn files() {
+        let usdz = Usdz::parse(archive(
+            &[
+                ("textures/wood.png", b"png"),
+                ("scene.usda", b"#usda 1.0\n"),
+                ("props/chair.usda", b"#usda 1.0\n"),
+            ],
+            0,
+        ))
+        .unwrap();
+        assert_eq!(usdz.root_layer(), Some("scene.usda"));
+        assert!(usdz.contains("props/chair.usda"));
+        assert!(!usdz.contains("chair.usda"));
+        assert_eq!(usdz.get("textures/wood.png"), Some(&b"png"[..]));
+        assert_eq!(usdz.get("scene.usda"), Some(&b"#usda 1.0\n"[..]));
+        assert_eq!(usdz.get("missing.usda"), None);
+    }
+
+    #[test]
+    fn invalid_archives() {
+        assert!(Usdz::parse(b"#usda 1.0\n".to_vec()).is_err());
+        assert!(Usdz::parse(archive(&[("scene.usda", b"#usda 1.0\n")], 8)).is_err());
+
+        let mut truncated = archive(&[("scene.usda", b"#usda 1.0\n")], 0);
+        truncated.truncate(40);
+        assert!(Usdz::parse(truncated).is_err());
+
+        let usdz = Usdz::parse(archive(&[("textures/wood.png", b"png")], 0)).unwrap();
+        assert_eq!(usdz.root_layer(), None);
+    }
+}
@@ -17,7 +17,7 @@ use url::Url;
 
 use crate::{
     asset_cache::{AssetCache, SyncAssetKey, SyncAssetKeyExt},
-    download_asset::{download, AssetsCacheDir},
+    download_asset::{download, fetch_from_stream, AssetsCacheDir},
     Cb,
 };
 
@@ -42,6 +42,16 @@ impl SyncAssetKey<AbsAssetUrl> for ContentBaseUrlKey {
     }
 }
 
+/// Prefix substitutions applied to URLs when they are downloaded (e.g. to download the assets of
+/// a package's quality tier instead of its default assets). The first matching prefix is used.
+#[derive(Debug, Clone)]
+pub struct DownloadUrlRewritesKey;
+impl SyncAssetKey<Arc<Vec<(String, String)>>> for DownloadUrlRewritesKey {
+    fn load(&self, _assets: AssetCache) -> Arc<Vec<(String, String)>> {
+        Default::default()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct UsingLocalDebugAssetsKey;
 impl SyncAssetKey<bool> for UsingLocalDebugAssetsKey {
@@ -251,8 +261,16 @@ impl AbsAssetUrl {
     }
     fn to_download_raw_url(&self, assets: &AssetCache) -> Result<Url, url::ParseError> {
         let content_url = ContentBaseUrlKey.get(assets);
-
-        self.to_download_url_with_base(&content_url)
+        let url = self.to_download_url_with_base(&content_url)?;
+
+        let rewrites = DownloadUrlRewritesKey.get(assets);
+        match rewrites
+            .iter()
+            .find_map(|(from, to)| Some((url.as_str().strip_prefix(from.as_str())?, to)))
+        {
+            Some((rest, to)) => Url::parse(&format!("{to}{rest}")),
+            None => Ok(url),
+        }
     }
     pub async fn download_bytes(&self, assets: &AssetCache) -> anyhow::Result<Vec<u8>> {
         if let Some(path) = self.to_file_path()? {
@@ -260,12 +278,14 @@ impl AbsAssetUrl {
                 .await
                 .context(format!("Failed to read file at: {:}", self.0))?)
         } else {
+            let url = self.to_download_raw_url(assets)?;
+            if let Some(bytes) = fetch_from_stream(assets, &url).await {
+                return bytes;
+            }
             Ok(
-                download(assets, self.to_download_raw_url(assets)?, |resp| async {
-                    Ok(resp.bytes().await?)
-                })
-                .await?
-                .to_vec(),
+                download(assets, url, |resp| async { Ok(resp.bytes().await?) })
+                    .await?
+                    .to_vec(),
             )
         }
     }
@@ -275,12 +295,11 @@ impl AbsAssetUrl {
                 .await
                 .context(format!("Failed to read file at: {:}", self.0))?)
         } else {
-            Ok(
-                download(assets, self.to_download_raw_url(assets)?, |resp| async {
-                    Ok(resp.text().await?)
-                })
-                .await?,
-            )
+            let url = self.to_download_raw_url(assets)?;
+            if let Some(bytes) = fetch_from_stream(assets, &url).await {
+                return Ok(String::from_utf8(bytes?)?);
+            }
+            Ok(download(assets, url, |resp| async { Ok(resp.text().await?) }).await?)
         }
     }
     pub async fn download_json<T: 'static + Send + DeserializeOwned>(
@@ -295,12 +314,11 @@ impl AbsAssetUrl {
             let res = serde_path_to_error::deserialize(de)?;
             Ok(res)
         } else {
-            Ok(
-                download(assets, self.to_download_raw_url(assets)?, |resp| async {
-                    Ok(resp.json::<T>().await?)
-                })
-                .await?,
-            )
+            let url = self.to_download_raw_url(assets)?;
+            if let Some(bytes) = fetch_from_stream(assets, &url).await {
+                return Ok(serde_json::from_slice(&bytes?)?);
+            }
+            Ok(download(assets, url, |resp| async { Ok(resp.json::<T>().await?) }).await?)
         }
     }
     pub async fn download_toml<T: DeserializeOwned>(
@@ -1,4 +1,12 @@
-use std::{marker::PhantomData, path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    marker::PhantomData,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use crate::{
     asset_cache::{AssetCache, AsyncAssetKey, AsyncAssetKeyExt, SyncAssetKey, SyncAssetKeyExt},
@@ -9,7 +17,7 @@ use crate::{
 use ambient_sys::task::wasm_nonsend;
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
-use futures::Future;
+use futures::{future::BoxFuture, Future};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::str::FromStr;
 use thiserror::Error;
@@ -77,6 +85,79 @@ impl SyncAssetKey<reqwest::Client> for ReqwestClientKey {
     }
 }
 
+/// Counts the downloads made through an asset cache, so that loading screens can show how far
+/// along loading is.
+#[derive(Debug, Default)]
+pub struct DownloadProgress {
+    started: AtomicUsize,
+    finished: AtomicUsize,
+}
+impl DownloadProgress {
+    /// The number of downloads that have been started, including the finished ones.
+    pub fn started(&self) -> usize {
+        self.started.load(Ordering::Relaxed)
+    }
+    /// The number of downloads that have finished, whether they succeeded or not.
+    pub fn finished(&self) -> usize {
+        self.finished.load(Ordering::Relaxed)
+    }
+    /// The number of downloads that are waiting for their turn or in flight.
+    pub fn pending(&self) -> usize {
+        self.started().saturating_sub(self.finished())
+    }
+}
+
+#[derive(Debug)]
+pub struct DownloadProgressKey;
+impl SyncAssetKey<Arc<DownloadProgress>> for DownloadProgressKey {
+    fn load(&self, _assets: AssetCache) -> Arc<DownloadProgress> {
+        Arc::new(DownloadProgress::default())
+    }
+}
+
+/// Marks a download as finished when dropped, so that failed and cancelled downloads are counted.
+struct DownloadProgressGuard(Arc<DownloadProgress>);
+impl DownloadProgressGuard {
+    fn start(progress: Arc<DownloadProgress>) -> Self {
+        progress.started.fetch_add(1, Ordering::Relaxed);
+        Self(progress)
+    }
+}
+impl Drop for DownloadProgressGuard {
+    fn drop(&mut self) {
+        self.0.finished.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Delivers assets through something other than HTTP, such as a stream of the connection to the
+/// server that hosts them.
+pub trait AssetStream: std::fmt::Debug + Sync + Send {
+    /// Starts fetching the asset at the download URL `url`, or returns `None` if this stream does
+    /// not serve it, in which case it is downloaded over HTTP.
+    fn fetch(&self, url: &url::Url) -> Option<BoxFuture<'static, anyhow::Result<Vec<u8>>>>;
+}
+
+/// The [AssetStream] that assets are fetched through before falling back to HTTP, if any.
+#[derive(Debug, Clone)]
+pub struct AssetStreamKey;
+impl SyncAssetKey<Option<Arc<dyn AssetStream>>> for AssetStreamKey {
+    fn load(&self, _assets: AssetCache) -> Option<Arc<dyn AssetStream>> {
+        None
+    }
+}
+
+/// Fetches the asset at the download URL `url` through the [AssetStream] of `assets`, if there is
+/// one and it serves the asset.
+pub(crate) async fn fetch_from_stream(
+    assets: &AssetCache,
+    url: &url::Url,
+) -> Option<anyhow::Result<Vec<u8>>> {
+    let fetch = AssetStreamKey.get(assets)?.fetch(url)?;
+    let _progress = DownloadProgressGuard::start(DownloadProgressKey.get(assets));
+    tracing::debug!("Download [stream  ] {url}");
+    Some(fetch.await)
+}
+
 /// Download with retries and a global rate limiting sempahore
 pub(crate) async fn download<T: 'static + Send, F: Future<Output = anyhow::Result<T>>>(
     assets: &AssetCache,
@@ -89,6 +170,7 @@ pub(crate) async fn download<T: 'static + Send, F: Future<Output = anyhow::Resul
 
     // reqwest::Client is not Send on wasm
     wasm_nonsend(move || async move {
+        let _progress = DownloadProgressGuard::start(DownloadProgressKey.get(&assets));
         let client = ReqwestClientKey.get(&assets);
         let url_short = if url_str.len() > 200 {
             format!("{}...", &url_str[..200])
@@ -167,13 +249,13 @@ pub async fn download_uncached_bytes(
             .context(format!("Failed to read file at: {:}", url.0))?);
     }
 
-    let body = download(
-        assets,
-        url.to_download_url(assets).map_err(anyhow::Error::new)?.0,
-        |resp| async { Ok(resp.bytes().await?) },
-    )
-    .await?
-    .to_vec();
+    let url = url.to_download_url(assets).map_err(anyhow::Error::new)?.0;
+    let body = match fetch_from_stream(assets, &url).await {
+        Some(body) => body?,
+        None => download(assets, url, |resp| async { Ok(resp.bytes().await?) })
+            .await?
+            .to_vec(),
+    };
     assert!(!body.is_empty());
     Ok(body)
 }
@@ -243,13 +325,17 @@ impl AsyncAssetKey<AssetResult<Arc<PathBuf>>> for BytesFromUrlCachedPath {
             std::fs::create_dir_all(&dir)
                 .context(format!("Failed to create asset dir: {dir:?}"))?;
             let tmp_path = path.with_extension(".downloading");
-            download(
-                &assets,
-                self.url
-                    .to_download_url(&assets)
-                    .map_err(anyhow::Error::new)?
-                    .0,
-                {
+            let url = self
+                .url
+                .to_download_url(&assets)
+                .map_err(anyhow::Error::new)?
+                .0;
+            if let Some(body) = fetch_from_stream(&assets, &url).await {
+                tokio::fs::write(&tmp_path, body?)
+                    .await
+                    .context(format!("Failed to write to tmp file: {tmp_path:?}"))?;
+            } else {
+                download(&assets, url, {
                     let tmp_path = tmp_path.clone();
                     move |mut resp| {
                         let tmp_path = tmp_path.clone();
@@ -269,9 +355,9 @@ impl AsyncAssetKey<AssetResult<Arc<PathBuf>>> for BytesFromUrlCachedPath {
                             Ok(())
                         }
                     }
-                },
-            )
-            .await?;
+                })
+                .await?;
+            }
             std::fs::rename(&tmp_path, &path).context(format!(
                 "Failed to rename tmp file, from: {tmp_path:?}, to: {path:?}"
             ))?;
@@ -1,4 +1,4 @@
-use glam::{vec3, DVec3, Mat4, Vec3};
+use glam::{vec3, vec3a, DVec3, Mat4, Vec3, Vec3A};
 
 mod culling;
 mod shape;
@@ -100,6 +100,35 @@ impl Cuboid {
         self.to_box().transform(mat)
     }
 
+    /// The same as `self.transform(mat).to_aabb()` and `self.transform(mat).to_sphere()`, but the
+    /// corners are kept on the stack and transformed with SIMD instructions, which is much faster
+    /// for the bounds of many entities.
+    pub fn transform_bounds(&self, mat: &Mat4) -> (AABB, Sphere) {
+        let corners: [Vec3A; 8] = std::array::from_fn(|i| {
+            let pick = |bit: usize, min: f32, max: f32| if i & bit != 0 { max } else { min };
+            mat.project_point3a(vec3a(
+                pick(4, self.min.x, self.max.x),
+                pick(2, self.min.y, self.max.y),
+                pick(1, self.min.z, self.max.z),
+            ))
+        });
+
+        let (min, max) = corners[1..]
+            .iter()
+            .fold((corners[0], corners[0]), |(min, max), &corner| {
+                (min.min(corner), max.max(corner))
+            });
+        let center = corners.iter().sum::<Vec3A>() / corners.len() as f32;
+        let radius = corners
+            .iter()
+            .map(|&corner| (corner - center).length())
+            .fold(0., f32::max);
+        (
+            AABB::new(min.into(), max.into()),
+            Sphere::new(center.into(), radius),
+        )
+    }
+
     pub fn size(&self) -> Vec3 {
         self.max - self.min
     }
@@ -396,6 +425,25 @@ mod tests {
         assert_eq!(p, Vec3::Z);
     }
 
+    #[test]
+    fn test_transform_bounds() {
+        let aabb = AABB::new(vec3(-1., -2., 0.), vec3(3., 1., 0.5));
+        let mat = Mat4::from_scale_rotation_translation(
+            vec3(2., 0.5, 1.),
+            glam::Quat::from_rotation_z(0.7) * glam::Quat::from_rotation_x(-0.3),
+            vec3(10., -4., 2.),
+        );
+        let (world_aabb, world_sphere) = aabb.transform_bounds(&mat);
+        let world_box = aabb.transform(&mat);
+        let (expected_aabb, expected_sphere) = (world_box.to_aabb(), world_box.to_sphere());
+        assert!(world_aabb.min.abs_diff_eq(expected_aabb.min, 1e-5));
+        assert!(world_aabb.max.abs_diff_eq(expected_aabb.max, 1e-5));
+        assert!(world_sphere
+            .center
+            .abs_diff_eq(expected_sphere.center, 1e-5));
+        assert!((world_sphere.radius - expected_sphere.radius).abs() < 1e-5);
+    }
+
     #[test]
     fn test_ray_aabb_intersection() {
         let ray = Ray {
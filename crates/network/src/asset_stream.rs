@@ -0,0 +1,325 @@
+//! Streams the build assets of a locally-hosted server to its clients over the game connection,
+//! instead of having the clients download them over HTTP.
+//!
+//! Each asset is requested on its own bidirectional stream, with the path of the asset relative
+//! to the content base URL. The server sends a few assets at once, and picks the waiting request
+//! with the highest priority next: the assets that entities do not refer to (such as WASM modules
+//! and metadata) come first, followed by the models closest to the requesting player.
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    fmt::Debug,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use ambient_ecs::{
+    generated::{
+        model::components::model_from_url, player::components::user_id as player_user_id,
+        prefab::components::prefab_from_url, transform::components::translation,
+    },
+    query, World,
+};
+use ambient_native_std::{asset_url::AbsAssetUrl, download_asset::AssetStream, log_result};
+use ambient_sys::time::Instant;
+use anyhow::Context;
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use glam::Vec3;
+use parking_lot::Mutex;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::{
+    client::NetworkTransport, frame_budget, server::BiStreamHandlers, NetworkError,
+    ASSET_STREAM_BISTREAM_ID,
+};
+
+/// The number of assets that are sent at once by a server.
+const MAX_CONCURRENT_SENDS: usize = 4;
+/// The longest asset path that can be requested.
+const MAX_PATH_LENGTH: u64 = 4096;
+/// The extensions of the model files whose build output is prioritized by distance.
+const MODEL_EXTENSIONS: &[&str] = &["glb", "gltf", "fbx", "obj", "usd", "usda", "usdz"];
+
+const RESPONSE_OK: u8 = 0;
+const RESPONSE_ERROR: u8 = 1;
+
+/// Serves the files in `root`, the build directory of the server, to the clients that request
+/// them through a [ClientAssetStream].
+pub fn register_server_bi_stream_handler(handlers: &mut BiStreamHandlers, root: PathBuf) {
+    let queue = Arc::new(SendQueue::default());
+    handlers.insert(
+        ASSET_STREAM_BISTREAM_ID,
+        (
+            "asset_stream",
+            Arc::new(move |state, _assets, user_id, mut send, recv| {
+                let user_id = user_id.to_string();
+                let root = root.clone();
+                let queue = queue.clone();
+                ambient_sys::task::spawn(async move {
+                    let try_block = || async {
+                        let mut path = String::new();
+                        recv.take(MAX_PATH_LENGTH).read_to_string(&mut path).await?;
+
+                        let priority = state
+                            .lock()
+                            .get_player_world(&user_id)
+                            .map_or(0., |world| asset_priority(world, &user_id, &path));
+                        let _permit = queue.acquire(priority).await;
+
+                        // The time spent waiting for a slot is not part of the serving time
+                        let start = Instant::now();
+                        let response = match read_asset(&root, &path).await {
+                            Ok(bytes) => [&[RESPONSE_OK][..], &bytes].concat(),
+                            Err(err) => {
+                                [&[RESPONSE_ERROR][..], format!("{err:?}").as_bytes()].concat()
+                            }
+                        };
+                        send.write_all(&response).await?;
+                        frame_budget::record_asset_served(start.elapsed(), response.len());
+                        Ok(()) as Result<(), NetworkError>
+                    };
+                    log_result!(try_block().await);
+                });
+            }),
+        ),
+    );
+}
+
+async fn read_asset(root: &Path, path: &str) -> anyhow::Result<Vec<u8>> {
+    let path = Path::new(path);
+    anyhow::ensure!(
+        path.components()
+            .all(|component| matches!(component, Component::Normal(_))),
+        "Invalid asset path {path:?}"
+    );
+    ambient_sys::fs::read(root.join(path))
+        .await
+        .with_context(|| format!("Failed to read asset {path:?}"))
+}
+
+/// Returns the priority of the asset at `path` for the given user; lower values are sent first.
+fn asset_priority(world: &World, user_id: &str, path: &str) -> f32 {
+    let Some(root) = model_root(path) else {
+        return 0.;
+    };
+
+    // The player entity may not have a position, in which case it is at its spawn point
+    let origin = query((player_user_id(), translation()))
+        .iter(world, None)
+        .find(|(_, (id, _))| id.as_str() == user_id)
+        .map_or(Vec3::ZERO, |(_, (_, position))| *position);
+    let distance = |id| {
+        world
+            .get(id, translation())
+            .unwrap_or(Vec3::ZERO)
+            .distance(origin)
+    };
+
+    let models = query(model_from_url())
+        .iter(world, None)
+        .filter(|(_, url)| url.contains(root))
+        .map(|(id, _)| distance(id));
+    let prefabs = query(prefab_from_url())
+        .iter(world, None)
+        .filter(|(_, url)| url.contains(root))
+        .map(|(id, _)| distance(id));
+    models
+        .chain(prefabs)
+        .reduce(f32::min)
+        // Models that are not used yet come last
+        .map_or(f32::MAX, |distance| 1. + distance)
+}
+
+/// Returns the part of `path` up to the model file it was built from, if any (e.g.
+/// `package/assets/Cube.glb` for `package/assets/Cube.glb/models/main.json`).
+fn model_root(path: &str) -> Option<&str> {
+    let mut end = 0;
+    for segment in path.split('/') {
+        end += segment.len();
+        let extension = segment.rsplit_once('.').map(|(_, extension)| extension);
+        if extension.map_or(false, |extension| {
+            MODEL_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+        }) {
+            return Some(&path[..end]);
+        }
+        end += 1;
+    }
+    None
+}
+
+/// Hands out the slots for sending assets, to the waiting requests with the lowest priority values
+/// first.
+#[derive(Debug, Default)]
+struct SendQueue {
+    state: Mutex<SendQueueState>,
+}
+
+#[derive(Debug, Default)]
+struct SendQueueState {
+    sending: usize,
+    requests: u64,
+    waiting: BinaryHeap<WaitingSend>,
+}
+
+#[derive(Debug)]
+struct WaitingSend {
+    priority: f32,
+    /// The order the requests were made in, to send requests of the same priority in order
+    order: u64,
+    ready: flume::Sender<()>,
+}
+impl PartialEq for WaitingSend {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for WaitingSend {}
+impl PartialOrd for WaitingSend {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for WaitingSend {
+    // The heap pops the greatest element, which must be the one with the lowest priority value
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .total_cmp(&self.priority)
+            .then(other.order.cmp(&self.order))
+    }
+}
+
+impl SendQueue {
+    async fn acquire(self: &Arc<Self>, priority: f32) -> SendPermit {
+        if let Some(ready) = self.enqueue(priority) {
+            // The sender is only dropped with the queue
+            ready.recv_async().await.ok();
+        }
+        SendPermit(self.clone())
+    }
+
+    /// Takes a free slot, or returns a receiver that is notified when a slot is handed over.
+    fn enqueue(&self, priority: f32) -> Option<flume::Receiver<()>> {
+        let mut state = self.state.lock();
+        state.requests += 1;
+        if state.sending < MAX_CONCURRENT_SENDS {
+            state.sending += 1;
+            return None;
+        }
+
+        let (ready, receiver) = flume::bounded(1);
+        let order = state.requests;
+        state.waiting.push(WaitingSend {
+            priority,
+            order,
+            ready,
+        });
+        Some(receiver)
+    }
+
+    fn release(&self) {
+        let mut state = self.state.lock();
+        while let Some(waiting) = state.waiting.pop() {
+            // The slot is handed over, unless the request was dropped
+            if waiting.ready.send(()).is_ok() {
+                return;
+            }
+        }
+        state.sending -= 1;
+    }
+}
+
+struct SendPermit(Arc<SendQueue>);
+impl Drop for SendPermit {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+/// Fetches the assets under the content base URL of a server from it, over the game connection.
+pub struct ClientAssetStream {
+    transport: Arc<dyn NetworkTransport>,
+    content_base_url: String,
+}
+impl ClientAssetStream {
+    pub fn new(transport: Arc<dyn NetworkTransport>, content_base_url: &AbsAssetUrl) -> Self {
+        Self {
+            transport,
+            content_base_url: content_base_url.to_string(),
+        }
+    }
+}
+impl Debug for ClientAssetStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientAssetStream")
+            .field("content_base_url", &self.content_base_url)
+            .finish_non_exhaustive()
+    }
+}
+impl AssetStream for ClientAssetStream {
+    fn fetch(&self, url: &url::Url) -> Option<BoxFuture<'static, anyhow::Result<Vec<u8>>>> {
+        let path = url
+            .as_str()
+            .strip_prefix(&self.content_base_url)?
+            .to_string();
+        let transport = self.transport.clone();
+        Some(Box::pin(async move {
+            let response = transport
+                .request_bi(ASSET_STREAM_BISTREAM_ID, Bytes::from(path.clone()))
+                .await?;
+            match response.split_first() {
+                Some((&RESPONSE_OK, bytes)) => Ok(bytes.to_vec()),
+                Some((&RESPONSE_ERROR, message)) => {
+                    anyhow::bail!("{}", String::from_utf8_lossy(message))
+                }
+                _ => anyhow::bail!("Invalid response when streaming asset {path:?}"),
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_root_is_the_model_file() {
+        assert_eq!(
+            model_root("package/assets/Cube.glb/models/main.json"),
+            Some("package/assets/Cube.glb")
+        );
+        assert_eq!(
+            model_root("package/assets/props/Barrel.FBX/textures/0.png"),
+            Some("package/assets/props/Barrel.FBX")
+        );
+        assert_eq!(model_root("package/build/client/main.wasm"), None);
+    }
+
+    #[test]
+    fn waiting_sends_are_ordered_by_priority() {
+        let queue = SendQueue::default();
+        for _ in 0..MAX_CONCURRENT_SENDS {
+            assert!(queue.enqueue(0.).is_none());
+        }
+
+        let mut far = queue.enqueue(10.).unwrap();
+        let mut near = queue.enqueue(1.).unwrap();
+        let mut also_near = queue.enqueue(1.).unwrap();
+
+        queue.release();
+        assert!(near.try_recv().is_ok());
+        assert!(also_near.try_recv().is_err());
+
+        queue.release();
+        assert!(also_near.try_recv().is_ok());
+        assert!(far.try_recv().is_err());
+
+        queue.release();
+        assert!(far.try_recv().is_ok());
+
+        // Once nothing is waiting, the slots are freed
+        queue.release();
+        assert_eq!(queue.state.lock().sending, MAX_CONCURRENT_SENDS - 1);
+    }
+}
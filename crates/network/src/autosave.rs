@@ -0,0 +1,293 @@
+//! Periodic saves of the main world of a server, so that persistent worlds survive restarts and
+//! crashes.
+//!
+//! Autosaving is configured in the `[autosave]` section of the [ServerConfig], which is read from
+//! `server.toml` in the working directory of the server. The saves are written to the `saves`
+//! directory next to it, and rotated: the most recent saves are kept, along with the last save of
+//! each of the most recent hours and days. When the server starts, it can restore the most recent
+//! save that can be read.
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use ambient_core::player::is_player;
+use ambient_ecs::{
+    dont_store,
+    generated::{package::components::is_package, wasm::components::is_module},
+    query, World, WorldContext,
+};
+use ambient_sys::time::SystemTime;
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::{is_persistent_resources, ServerWorldExt};
+
+/// The name of the configuration file of a server, in its working directory.
+pub const CONFIG_FILE: &str = "server.toml";
+/// The name of the directory the saves are written to, in the working directory of the server.
+pub const SAVES_DIR: &str = "saves";
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+/// The configuration of a server that is read from its [CONFIG_FILE].
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ServerConfig {
+    /// Autosaving is disabled if this is not set
+    #[serde(default)]
+    pub autosave: Option<AutosaveConfig>,
+}
+impl ServerConfig {
+    /// Loads the configuration at `path`, or returns the default configuration if the file does
+    /// not exist.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents)
+                .with_context(|| format!("Failed to parse the server config at {path:?}")),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => {
+                Err(err).with_context(|| format!("Failed to read the server config at {path:?}"))
+            }
+        }
+    }
+}
+
+/// How often the world is saved, and which saves are kept.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AutosaveConfig {
+    /// The time between saves, in seconds
+    pub interval: u64,
+    /// The number of most recent saves to keep
+    pub keep: usize,
+    /// The number of most recent hours to keep the last save of
+    pub hourly: usize,
+    /// The number of most recent days to keep the last save of
+    pub daily: usize,
+    /// Whether the server restores the most recent save when it starts
+    pub load_on_start: bool,
+}
+impl Default for AutosaveConfig {
+    fn default() -> Self {
+        Self {
+            interval: 5 * 60,
+            keep: 5,
+            hourly: 24,
+            daily: 7,
+            load_on_start: true,
+        }
+    }
+}
+impl AutosaveConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval.max(1))
+    }
+}
+
+/// Copies the entities of `world` that should be saved, with their stored components.
+///
+/// Players, packages and modules are not saved, as they are recreated when the server starts and
+/// the players reconnect. The persistent resources are saved with the entities.
+pub fn capture(world: &World) -> World {
+    let mut save = World::new_with_config("autosave", WorldContext::Unknown, false);
+    let entities = query(())
+        .excl(dont_store())
+        .excl(is_player())
+        .excl(is_package())
+        .excl(is_module())
+        .iter(world, None)
+        .map(|(id, _)| id)
+        .filter(|id| *id != world.resource_entity())
+        .collect::<Vec<_>>();
+    for id in entities {
+        let Ok(mut entity) = world.clone_entity(id) else {
+            continue;
+        };
+        entity.filter(&|desc| {
+            desc.has_attribute::<ambient_ecs::Store>() || desc == is_persistent_resources().desc()
+        });
+        save.spawn_with_id(id, entity);
+    }
+    save
+}
+
+/// Spawns the entities of `save` into `world`, keeping their ids, and merges the saved persistent
+/// resources into those of `world`.
+pub fn restore(world: &mut World, save: World) {
+    let entities = save.entities();
+    tracing::info!("Restoring {} entities from the save", entities.len());
+    for (id, entity) in entities {
+        if entity.contains(is_persistent_resources()) {
+            if let Some(resources) = world.persisted_resource_entity() {
+                world.add_components(resources, entity).ok();
+            }
+        } else if !world.spawn_with_id(id, entity) {
+            tracing::warn!(?id, "Entity already exists; skipping");
+        }
+    }
+}
+
+/// The saves of a server, in a directory.
+#[derive(Debug, Clone)]
+pub struct Autosaves {
+    dir: PathBuf,
+    config: AutosaveConfig,
+}
+impl Autosaves {
+    pub fn new(dir: impl Into<PathBuf>, config: AutosaveConfig) -> Self {
+        Self {
+            dir: dir.into(),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &AutosaveConfig {
+        &self.config
+    }
+
+    /// Writes `save`, as captured by [capture], and deletes the saves that are no longer kept.
+    pub fn save(&self, save: &World) -> anyhow::Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create the saves directory {:?}", self.dir))?;
+        let time = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_secs();
+        let path = self.path(time);
+
+        // The save is written to a temporary file first, so that a crash while saving does not
+        // leave a partial save behind
+        let contents = serde_json::to_vec(save)?;
+        let temporary = path.with_extension("json.tmp");
+        std::fs::write(&temporary, contents)
+            .with_context(|| format!("Failed to write the save {temporary:?}"))?;
+        std::fs::rename(&temporary, &path)
+            .with_context(|| format!("Failed to write the save {path:?}"))?;
+
+        let times = self.times()?;
+        let kept = saves_to_keep(&times, &self.config);
+        for time in times.into_iter().filter(|time| !kept.contains(time)) {
+            if let Err(err) = std::fs::remove_file(self.path(time)) {
+                tracing::warn!(?err, "Failed to remove an old save");
+            }
+        }
+        Ok(path)
+    }
+
+    /// Loads the most recent save that can be read, skipping the ones that are corrupted.
+    pub fn load_latest(&self) -> Option<(PathBuf, World)> {
+        let mut times = self.times().ok()?;
+        times.sort_unstable();
+        times.into_iter().rev().find_map(|time| {
+            let path = self.path(time);
+            let result = std::fs::read(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|contents| World::from_slice(&contents));
+            match result {
+                Ok(world) => Some((path, world)),
+                Err(err) => {
+                    tracing::warn!(?path, ?err, "Skipping a save that could not be read");
+                    None
+                }
+            }
+        })
+    }
+
+    fn path(&self, time: u64) -> PathBuf {
+        self.dir.join(format!("autosave-{time}.json"))
+    }
+
+    /// The times of the saves in the directory, in seconds since the Unix epoch.
+    fn times(&self) -> anyhow::Result<Vec<u64>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(entries
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                name.strip_prefix("autosave-")?
+                    .strip_suffix(".json")?
+                    .parse()
+                    .ok()
+            })
+            .collect())
+    }
+}
+
+/// Returns the saves to keep among the saves made at `times`: the most recent ones, and the last
+/// save of each of the most recent hours and days.
+pub fn saves_to_keep(times: &[u64], config: &AutosaveConfig) -> BTreeSet<u64> {
+    let mut times = times.to_vec();
+    times.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut kept: BTreeSet<u64> = times.iter().take(config.keep).copied().collect();
+    for (period, count) in [(HOUR, config.hourly), (DAY, config.daily)] {
+        let mut periods = 0;
+        let mut last_period = None;
+        for &time in &times {
+            let time_period = time / period;
+            if last_period == Some(time_period) {
+                continue;
+            }
+            if periods == count {
+                break;
+            }
+            kept.insert(time);
+            periods += 1;
+            last_period = Some(time_period);
+        }
+    }
+    kept
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rotation() {
+        let config = AutosaveConfig {
+            keep: 2,
+            hourly: 2,
+            daily: 2,
+            ..Default::default()
+        };
+        // Saves every 30 minutes over three days
+        let times: Vec<u64> = (0..3 * 48).map(|i| i * 30 * 60).collect();
+        let last = *times.last().unwrap();
+
+        let kept = saves_to_keep(&times, &config);
+        let expected: BTreeSet<u64> = [
+            // The most recent saves, the first of which is also the last of its hour and day
+            last,
+            last - 30 * 60,
+            // The last save of the previous hour
+            last - HOUR,
+            // The last save of the previous day
+            last - DAY,
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(kept, expected);
+
+        // Nothing is removed while there are fewer saves than are kept
+        assert_eq!(saves_to_keep(&times[..1], &config).len(), 1);
+    }
+
+    #[test]
+    fn config() {
+        let config: ServerConfig = toml::from_str("[autosave]\ninterval = 60\nkeep = 3\n").unwrap();
+        assert_eq!(
+            config.autosave,
+            Some(AutosaveConfig {
+                interval: 60,
+                keep: 3,
+                ..Default::default()
+            })
+        );
+        assert_eq!(toml::from_str::<ServerConfig>("").unwrap().autosave, None);
+    }
+}
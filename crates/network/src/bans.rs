@@ -0,0 +1,189 @@
+//! The users that are not allowed to connect to the server.
+//!
+//! The [BanList] is consulted when a client connects, and can be changed by server-side code
+//! through the [ban_list](crate::server::ban_list) resource. If it was loaded from a file, every
+//! change is saved back to it in the background, so that the bans outlive the server without
+//! blocking the code that changes them.
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// The [BanList] of a server, shared between all of its world instances.
+pub type SharedBanList = Arc<Mutex<BanList>>;
+
+/// The banned users of a server, with the reasons of their bans.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BanList {
+    /// The reason of each ban, by user ID
+    users: BTreeMap<String, String>,
+    #[serde(skip)]
+    file: Option<BanListFile>,
+}
+impl BanList {
+    /// Loads the ban list stored at `path`, or creates an empty one if the file does not exist.
+    /// Changes to the list are saved to `path`.
+    pub fn load(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let mut list = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str::<Self>(&contents)
+                .with_context(|| format!("Failed to parse the ban list at {path:?}"))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to read the ban list at {path:?}"))
+            }
+        };
+        let writer = spawn_writer(path.clone())
+            .with_context(|| format!("Failed to start saving the ban list to {path:?}"))?;
+        list.file = Some(BanListFile { path, writer });
+        Ok(list)
+    }
+
+    /// The file the list is saved to, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.file.as_ref().map(|file| file.path.as_path())
+    }
+
+    /// Blocks until every change made so far has been written to the file of the list.
+    pub fn flush(&self) {
+        let Some(file) = &self.file else {
+            return;
+        };
+        let (done_tx, done_rx) = flume::bounded(1);
+        if file.writer.send(WriteRequest::Flush(done_tx)).is_ok() {
+            done_rx.recv().ok();
+        }
+    }
+
+    /// Bans the given user, replacing the reason of any previous ban.
+    pub fn ban(&mut self, user_id: impl Into<String>, reason: impl Into<String>) {
+        self.users.insert(user_id.into(), reason.into());
+        self.save();
+    }
+
+    /// Lifts the ban of the given user. Returns `false` if they were not banned.
+    pub fn unban(&mut self, user_id: &str) -> bool {
+        let was_banned = self.users.remove(user_id).is_some();
+        if was_banned {
+            self.save();
+        }
+        was_banned
+    }
+
+    /// Returns the reason the given user is banned for, if they are banned.
+    pub fn reason(&self, user_id: &str) -> Option<&str> {
+        self.users.get(user_id).map(|reason| reason.as_str())
+    }
+
+    /// The banned users and the reasons of their bans, ordered by user ID.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.users
+            .iter()
+            .map(|(user_id, reason)| (user_id.as_str(), reason.as_str()))
+    }
+
+    /// Queues the current contents of the list to be written to its file.
+    fn save(&self) {
+        let Some(file) = &self.file else {
+            return;
+        };
+        match serde_json::to_string_pretty(self) {
+            Ok(contents) => {
+                // The writer only stops once all of its senders are dropped, so it is running
+                file.writer.send(WriteRequest::Save(contents)).ok();
+            }
+            Err(err) => tracing::error!("Failed to serialize the ban list: {err:?}"),
+        }
+    }
+}
+
+/// The file a [BanList] is saved to, and the thread that writes it.
+#[derive(Debug, Clone)]
+struct BanListFile {
+    path: PathBuf,
+    writer: flume::Sender<WriteRequest>,
+}
+impl PartialEq for BanListFile {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+impl Eq for BanListFile {}
+
+#[derive(Debug)]
+enum WriteRequest {
+    /// Writes these contents to the file, unless newer ones were queued after them
+    Save(String),
+    /// Reports back once everything queued before it has been written
+    Flush(flume::Sender<()>),
+}
+
+/// Starts the thread that writes a ban list to `path`, which stops when the returned sender and
+/// all of its clones are dropped.
+fn spawn_writer(path: PathBuf) -> std::io::Result<flume::Sender<WriteRequest>> {
+    let (writer, requests) = flume::unbounded::<WriteRequest>();
+    std::thread::Builder::new()
+        .name("ban_list_writer".to_string())
+        .spawn(move || {
+            let mut pending = None;
+            while let Ok(request) = requests.recv() {
+                // Changes made while the file was being written are saved together
+                for request in std::iter::once(request).chain(requests.try_iter()) {
+                    match request {
+                        WriteRequest::Save(contents) => pending = Some(contents),
+                        WriteRequest::Flush(done) => {
+                            write(&path, pending.take());
+                            done.send(()).ok();
+                        }
+                    }
+                }
+                write(&path, pending.take());
+            }
+        })?;
+    Ok(writer)
+}
+
+/// Writes `contents` to a temporary file first, then moves it to `path`, so that the ban list is
+/// not lost if the server stops while it is being written.
+fn write(path: &Path, contents: Option<String>) {
+    let Some(contents) = contents else {
+        return;
+    };
+    let temporary_path = path.with_extension("json.tmp");
+    let result = std::fs::write(&temporary_path, contents)
+        .and_then(|_| std::fs::rename(&temporary_path, path))
+        .with_context(|| format!("Failed to write the ban list to {path:?}"));
+    if let Err(err) = result {
+        tracing::error!("{err:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bans_are_saved_to_the_file() {
+        let path = std::env::temp_dir().join(format!("ambient_bans_{}.json", uuid::Uuid::new_v4()));
+
+        let mut list = BanList::load(&path).unwrap();
+        assert_eq!(list.iter().count(), 0);
+        list.ban("griefer", "Griefing");
+        list.ban("cheater", "Cheating");
+        assert!(list.unban("cheater"));
+        assert!(!list.unban("cheater"));
+        list.flush();
+
+        let loaded = BanList::load(&path).unwrap();
+        assert_eq!(loaded.reason("griefer"), Some("Griefing"));
+        assert_eq!(loaded.reason("cheater"), None);
+        assert_eq!(loaded, list);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -36,12 +36,25 @@ components!("network::client", {
     /// The most recent server performance statistics
     @[Resource]
     client_network_stats: NetworkStats,
+    /// Connects the client to the server at the given address, replacing its current world.
+    /// Only set by clients that can switch servers.
+    @[Resource]
+    connect_to_server: Cb<dyn Fn(String) + Sync + Send>,
+    /// Pauses or resumes the server this client is hosting in the same process.
+    /// Only set by clients that can host a server.
+    @[Resource]
+    pause_server: Cb<dyn Fn(bool) -> anyhow::Result<()> + Sync + Send>,
+    /// Set in the worlds of clients that are not connected to a server, such as the world of a
+    /// main menu
+    @[Resource]
+    is_local_world: (),
 });
 
+/// A stream of a QUIC or an in-memory connection
 #[cfg(not(target_os = "unknown"))]
-pub type PlatformSendStream = quinn::SendStream;
+pub type PlatformSendStream = std::pin::Pin<Box<dyn tokio::io::AsyncWrite + Send + Sync>>;
 #[cfg(not(target_os = "unknown"))]
-pub type PlatformRecvStream = quinn::RecvStream;
+pub type PlatformRecvStream = std::pin::Pin<Box<dyn tokio::io::AsyncRead + Send + Sync>>;
 
 #[cfg(target_os = "unknown")]
 pub type PlatformSendStream = crate::webtransport::SendStream;
@@ -0,0 +1,260 @@
+//! Optional zstd compression of world diffs and datagrams.
+//!
+//! Compression is negotiated with ALPN during the QUIC handshake: native clients offer
+//! [ALPN_ZSTD] in addition to [ALPN], and servers that support compression prefer it.
+//! WebTransport and proxied connections are never compressed, and neither are connections to a
+//! server on the same machine. Set `AMBIENT_DISABLE_COMPRESSION` to disable it on either side.
+//!
+//! When compression is in use, every payload starts with a tag byte that says whether the rest of
+//! it is compressed. This lets the sender skip compression for individual messages: payloads that
+//! are too small to benefit from it, that do not get any smaller, or that have one of the types
+//! listed in [UNCOMPRESSED_DATAGRAM_IDS] are sent as-is. Datagrams keep their id in front of the
+//! tag byte, so that it can be read without decompressing the payload.
+use bytes::{BufMut, Bytes, BytesMut};
+
+#[cfg(not(target_os = "unknown"))]
+use bytes::Buf;
+
+use crate::{NetworkError, PLAYER_INPUT_DATAGRAM_ID, VOICE_DATAGRAM_ID};
+
+/// The ALPN protocol of uncompressed native connections.
+pub const ALPN: &[u8] = b"ambient-02";
+/// The ALPN protocol of native connections that compress world diffs and datagrams with zstd.
+pub const ALPN_ZSTD: &[u8] = b"ambient-02-zstd";
+
+/// Datagram types that are never compressed, as their payloads are either tiny and
+/// latency-sensitive or already compressed.
+pub const UNCOMPRESSED_DATAGRAM_IDS: &[u32] = &[PLAYER_INPUT_DATAGRAM_ID, VOICE_DATAGRAM_ID];
+
+/// Payloads smaller than this are not worth compressing.
+#[cfg(not(target_os = "unknown"))]
+const MIN_COMPRESSED_SIZE: usize = 128;
+#[cfg(not(target_os = "unknown"))]
+const ZSTD_LEVEL: i32 = 3;
+
+#[cfg(not(target_os = "unknown"))]
+const TAG_RAW: u8 = 0;
+#[cfg(not(target_os = "unknown"))]
+const TAG_ZSTD: u8 = 1;
+
+/// Returns the ALPN protocols that this side supports, in order of preference.
+pub fn supported_alpn_protocols() -> Vec<Vec<u8>> {
+    let mut protocols = vec![ALPN.to_vec()];
+    if cfg!(not(target_os = "unknown")) && std::env::var("AMBIENT_DISABLE_COMPRESSION").is_err() {
+        protocols.insert(0, ALPN_ZSTD.to_vec());
+    }
+    protocols
+}
+
+/// The compression used by a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    #[cfg(not(target_os = "unknown"))]
+    Zstd,
+}
+impl Compression {
+    /// Returns the compression for a connection that negotiated the given ALPN protocol.
+    pub fn from_alpn(protocol: &[u8]) -> Self {
+        match protocol {
+            #[cfg(not(target_os = "unknown"))]
+            ALPN_ZSTD => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+
+    #[cfg(not(target_os = "unknown"))]
+    pub fn of_connection(conn: &quinn::Connection) -> Self {
+        conn.handshake_data()
+            .and_then(|data| data.downcast::<quinn::crypto::rustls::HandshakeData>().ok())
+            .and_then(|data| data.protocol)
+            .map(|protocol| Self::from_alpn(&protocol))
+            .unwrap_or_default()
+    }
+
+    /// Compresses `data`, unless that would not make it any smaller.
+    pub fn compress(self, data: Bytes) -> Bytes {
+        match self {
+            Self::None => data,
+            #[cfg(not(target_os = "unknown"))]
+            Self::Zstd => {
+                if data.len() >= MIN_COMPRESSED_SIZE {
+                    match zstd::bulk::compress(&data, ZSTD_LEVEL) {
+                        Ok(compressed) if compressed.len() < data.len() => {
+                            return tagged(TAG_ZSTD, &compressed);
+                        }
+                        Ok(_) => {}
+                        Err(err) => tracing::warn!("Failed to compress payload: {err:?}"),
+                    }
+                }
+                tagged(TAG_RAW, &data)
+            }
+        }
+    }
+
+    /// Prepares `data` to be sent without compressing it, e.g. because it is already compressed.
+    pub fn uncompressed(self, data: Bytes) -> Bytes {
+        match self {
+            Self::None => data,
+            #[cfg(not(target_os = "unknown"))]
+            Self::Zstd => tagged(TAG_RAW, &data),
+        }
+    }
+
+    /// Builds a datagram with the given `id`, compressing its payload unless the type has opted
+    /// out.
+    pub fn encode_datagram(self, id: u32, data: Bytes) -> Bytes {
+        let data = if UNCOMPRESSED_DATAGRAM_IDS.contains(&id) {
+            self.uncompressed(data)
+        } else {
+            self.compress(data)
+        };
+
+        let mut bytes = BytesMut::with_capacity(4 + data.len());
+        bytes.put_u32(id);
+        bytes.put(data);
+        bytes.freeze()
+    }
+
+    /// Reverses [Self::encode_datagram], returning the id followed by the decompressed payload.
+    pub fn decode_datagram(self, datagram: Bytes) -> Result<Bytes, NetworkError> {
+        if self == Self::None {
+            return Ok(datagram);
+        }
+        if datagram.len() < 4 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Missing datagram id",
+            )
+            .into());
+        }
+
+        let payload = self.decompress(datagram.slice(4..))?;
+        let mut bytes = BytesMut::with_capacity(4 + payload.len());
+        bytes.put_slice(&datagram[..4]);
+        bytes.put(payload);
+        Ok(bytes.freeze())
+    }
+
+    /// Reverses [Self::compress] and [Self::uncompressed].
+    pub fn decompress(self, data: Bytes) -> Result<Bytes, NetworkError> {
+        match self {
+            Self::None => Ok(data),
+            #[cfg(not(target_os = "unknown"))]
+            Self::Zstd => {
+                use std::io::{ErrorKind, Read};
+
+                let mut data = data;
+                if data.is_empty() {
+                    return Err(std::io::Error::new(
+                        ErrorKind::InvalidData,
+                        "Missing compression tag",
+                    )
+                    .into());
+                }
+
+                match data.get_u8() {
+                    TAG_RAW => Ok(data),
+                    TAG_ZSTD => {
+                        let mut decompressed = Vec::new();
+                        zstd::stream::read::Decoder::new(&data[..])?
+                            .take(crate::MAX_FRAME_SIZE as u64 + 1)
+                            .read_to_end(&mut decompressed)?;
+                        if decompressed.len() > crate::MAX_FRAME_SIZE {
+                            return Err(NetworkError::FrameTooLarge);
+                        }
+                        Ok(decompressed.into())
+                    }
+                    tag => Err(std::io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Unknown compression tag {tag}"),
+                    )
+                    .into()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "unknown"))]
+fn tagged(tag: u8, data: &[u8]) -> Bytes {
+    let mut bytes = BytesMut::with_capacity(1 + data.len());
+    bytes.put_u8(tag);
+    bytes.put_slice(data);
+    bytes.freeze()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let data = Bytes::from("Hello, World! ".repeat(100));
+
+        let compressed = Compression::Zstd.compress(data.clone());
+        assert!(compressed.len() < data.len());
+        assert_eq!(Compression::Zstd.decompress(compressed).unwrap(), data);
+    }
+
+    #[test]
+    fn small_payloads_are_not_compressed() {
+        let data = Bytes::from_static(b"small");
+
+        let compressed = Compression::Zstd.compress(data.clone());
+        assert_eq!(compressed.len(), data.len() + 1);
+        assert_eq!(Compression::Zstd.decompress(compressed).unwrap(), data);
+    }
+
+    #[test]
+    fn opted_out_datagrams_are_not_compressed() {
+        let data = Bytes::from(vec![0u8; 1024]);
+
+        let datagram = Compression::Zstd.encode_datagram(PLAYER_INPUT_DATAGRAM_ID, data.clone());
+        assert_eq!(datagram.len(), 4 + 1 + data.len());
+        assert_eq!(&datagram[5..], &data[..]);
+
+        let datagram = Compression::Zstd.encode_datagram(crate::WASM_DATAGRAM_ID, data.clone());
+        assert!(datagram.len() < data.len());
+    }
+
+    #[test]
+    fn datagram_roundtrip() {
+        use crate::bytes_ext::BufExt;
+
+        let payloads = [
+            (
+                crate::WASM_DATAGRAM_ID,
+                Bytes::from("Hello, World! ".repeat(100)),
+            ),
+            (crate::WASM_DATAGRAM_ID, Bytes::from_static(b"small")),
+            (PLAYER_INPUT_DATAGRAM_ID, Bytes::from(vec![0u8; 1024])),
+        ];
+        for compression in [Compression::None, Compression::Zstd] {
+            for (id, data) in payloads.clone() {
+                let datagram = compression.encode_datagram(id, data.clone());
+
+                // `process_datagram` reads the id from the decoded datagram
+                let mut decoded = compression.decode_datagram(datagram).unwrap();
+                assert_eq!(decoded.try_get_u32().unwrap(), id);
+                assert_eq!(decoded, data);
+            }
+        }
+
+        assert!(Compression::Zstd
+            .decode_datagram(Bytes::from_static(b"id"))
+            .is_err());
+    }
+
+    #[test]
+    fn negotiation() {
+        assert_eq!(Compression::from_alpn(ALPN_ZSTD), Compression::Zstd);
+        assert_eq!(Compression::from_alpn(ALPN), Compression::None);
+        assert_eq!(Compression::from_alpn(b"h3"), Compression::None);
+        assert_eq!(
+            Compression::None.compress(Bytes::from_static(b"data")),
+            "data"
+        );
+    }
+}
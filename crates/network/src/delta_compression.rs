@@ -0,0 +1,240 @@
+//! Delta compression of component values sent in world diffs.
+//!
+//! Both ends of a diff stream keep the last serialized value of every component that was sent
+//! with `WorldChange::SetComponents` (see [`ComponentValueCache`]). When a component is set again,
+//! only the byte ranges that differ from the previous value are sent (see [`encode`] and
+//! [`decode`]). This means that a struct with one changed field, or a `Vec3` where only one axis
+//! has changed, costs only a few bytes on the wire.
+//!
+//! To make this more effective for transforms, which change constantly, [`quantize_transforms`]
+//! rounds the mantissa of their floats so that small changes leave most of the bytes untouched.
+//!
+//! Encoding can be disabled for debugging with the `debug-disable-delta-compression` feature;
+//! decoding is always supported.
+use std::collections::HashMap;
+
+use ambient_core::transform::{rotation, scale, translation};
+use ambient_ecs::{EntityId, WorldChange, WorldDiff};
+use bytes::Bytes;
+use glam::{Quat, Vec3};
+
+/// Whether or not delta compression should be used when serializing diffs.
+pub const ENABLED: bool = cfg!(not(feature = "debug-disable-delta-compression"));
+
+/// The number of mantissa bits kept when quantizing transforms. This gives a relative error of
+/// at most 2^-17, or ~7.6mm at 1km from the origin.
+pub const TRANSFORM_MANTISSA_BITS: u32 = 16;
+
+/// Changed byte ranges that are separated by fewer unchanged bytes than this are merged, as
+/// the overhead of starting a new range would outweigh the savings.
+const MIN_GAP: usize = 3;
+
+/// The last serialized value of each component, keyed by entity.
+#[derive(Clone, Debug, Default)]
+pub struct ComponentValueCache(HashMap<EntityId, HashMap<u32, Bytes>>);
+impl ComponentValueCache {
+    pub fn get(&self, id: EntityId, component: u32) -> Option<&Bytes> {
+        self.0
+            .get(&id)
+            .and_then(|components| components.get(&component))
+    }
+
+    pub fn insert(&mut self, id: EntityId, component: u32, value: Bytes) {
+        self.0.entry(id).or_default().insert(component, value);
+    }
+
+    pub fn remove(&mut self, id: EntityId, component: u32) {
+        if let Some(components) = self.0.get_mut(&id) {
+            components.remove(&component);
+            if components.is_empty() {
+                self.0.remove(&id);
+            }
+        }
+    }
+
+    pub fn remove_entity(&mut self, id: EntityId) {
+        self.0.remove(&id);
+    }
+
+    /// Invalidates any cached values that are affected by a change that does not go through
+    /// delta compression (i.e. anything other than `SetComponents`).
+    pub fn invalidate(&mut self, change: &WorldChange) {
+        match change {
+            WorldChange::Spawn(id, _) | WorldChange::Despawn(id) => self.remove_entity(*id),
+            WorldChange::AddComponents(id, entity) => {
+                for entry in entity.iter() {
+                    self.remove(*id, entry.desc().index());
+                }
+            }
+            WorldChange::RemoveComponents(id, components) => {
+                for desc in components {
+                    self.remove(*id, desc.index());
+                }
+            }
+            WorldChange::SetComponents(_, _) => {}
+        }
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn read_varint(input: &mut &[u8]) -> Option<usize> {
+    let mut value = 0usize;
+    for shift in (0..usize::BITS).step_by(7) {
+        let (&byte, rest) = input.split_first()?;
+        *input = rest;
+        value |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Encodes `next` relative to `previous`.
+///
+/// Returns `None` if the values have different lengths, or if the delta would not be smaller
+/// than `next` itself. Otherwise, the delta is a sequence of `(skip, len, bytes)` ranges.
+pub fn encode(previous: &[u8], next: &[u8]) -> Option<Vec<u8>> {
+    if previous.len() != next.len() {
+        return None;
+    }
+
+    let mut ranges: Vec<(usize, usize)> = vec![];
+    for (i, (a, b)) in previous.iter().zip(next).enumerate() {
+        if a == b {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some((_, end)) if i - *end < MIN_GAP => *end = i + 1,
+            _ => ranges.push((i, i + 1)),
+        }
+    }
+
+    let mut out = vec![];
+    let mut position = 0;
+    for (start, end) in ranges {
+        write_varint(&mut out, start - position);
+        write_varint(&mut out, end - start);
+        out.extend_from_slice(&next[start..end]);
+        position = end;
+    }
+
+    (out.len() < next.len()).then_some(out)
+}
+
+/// Applies a delta produced by [`encode`] to `previous`.
+pub fn decode(previous: &[u8], mut delta: &[u8]) -> Option<Vec<u8>> {
+    let mut out = previous.to_vec();
+    let mut position = 0usize;
+    while !delta.is_empty() {
+        let start = position.checked_add(read_varint(&mut delta)?)?;
+        let len = read_varint(&mut delta)?;
+        let end = start.checked_add(len)?;
+        if end > out.len() || len > delta.len() {
+            return None;
+        }
+        out[start..end].copy_from_slice(&delta[..len]);
+        delta = &delta[len..];
+        position = end;
+    }
+    Some(out)
+}
+
+/// Rounds `value` to the nearest float with only `bits` bits of mantissa.
+pub fn quantize_f32(value: f32, bits: u32) -> f32 {
+    if !value.is_finite() || bits >= 23 {
+        return value;
+    }
+    let dropped = 23 - bits;
+    let half = 1u32 << (dropped - 1);
+    let mask = !((1u32 << dropped) - 1);
+    f32::from_bits((value.to_bits().wrapping_add(half)) & mask)
+}
+
+fn quantize_vec3(value: Vec3) -> Vec3 {
+    Vec3::from(
+        value
+            .to_array()
+            .map(|v| quantize_f32(v, TRANSFORM_MANTISSA_BITS)),
+    )
+}
+
+fn quantize_quat(value: Quat) -> Quat {
+    Quat::from_array(
+        value
+            .to_array()
+            .map(|v| quantize_f32(v, TRANSFORM_MANTISSA_BITS)),
+    )
+}
+
+/// Quantizes the transform components of all `SetComponents` changes in `diff`.
+pub fn quantize_transforms(diff: &mut WorldDiff) {
+    for change in &mut diff.changes {
+        let WorldChange::SetComponents(_, entity) = change else {
+            continue;
+        };
+        if let Some(value) = entity.get_mut(translation()) {
+            *value = quantize_vec3(*value);
+        }
+        if let Some(value) = entity.get_mut(scale()) {
+            *value = quantize_vec3(*value);
+        }
+        if let Some(value) = entity.get_mut(rotation()) {
+            *value = quantize_quat(*value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delta_roundtrips() {
+        let previous = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+        let next = [1, 2, 3, 40, 5, 6, 7, 8, 9, 10, 11, 120];
+        let delta = encode(&previous, &next).unwrap();
+        assert!(delta.len() < next.len());
+        assert_eq!(decode(&previous, &delta).unwrap(), next);
+    }
+
+    #[test]
+    fn unchanged_value_encodes_to_empty_delta() {
+        let value = [1, 2, 3, 4];
+        assert_eq!(encode(&value, &value), Some(vec![]));
+        assert_eq!(decode(&value, &[]).unwrap(), value);
+    }
+
+    #[test]
+    fn delta_is_skipped_when_not_smaller() {
+        assert_eq!(encode(&[1, 2, 3], &[4, 5, 6]), None);
+        assert_eq!(encode(&[1, 2, 3], &[1, 2, 3, 4]), None);
+    }
+
+    #[test]
+    fn decode_rejects_out_of_bounds_delta() {
+        assert_eq!(decode(&[1, 2, 3], &[2, 2, 9, 9]), None);
+        assert_eq!(decode(&[1, 2, 3], &[0, 5, 9]), None);
+    }
+
+    #[test]
+    fn quantization_is_bounded() {
+        for value in [0.0, 1.0, -1.0, 0.1, 123.456, -9876.54321, 1e-20] {
+            let quantized = quantize_f32(value, TRANSFORM_MANTISSA_BITS);
+            assert!((quantized - value).abs() <= value.abs() * 2f32.powi(-17));
+        }
+        assert!(quantize_f32(f32::NAN, TRANSFORM_MANTISSA_BITS).is_nan());
+        assert_eq!(quantize_f32(f32::INFINITY, 8), f32::INFINITY);
+    }
+}
@@ -1,5 +1,5 @@
 //! Utilities for `WorldDiff` serialization like `WorldDiffDeduplicator` or `DiffSerializer`.
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap};
 
 use ambient_ecs::{
     with_component_registry, ComponentDesc, ComponentEntry, ComponentRegistry, Entity, EntityId,
@@ -12,6 +12,8 @@ use serde::{
     Deserialize, Deserializer,
 };
 
+use crate::delta_compression::{self, ComponentValueCache};
+
 /// Bincode options used for diff serialization
 pub fn bincode_options() -> impl Options {
     bincode::DefaultOptions::new()
@@ -91,6 +93,7 @@ enum WorldChangeTag {
     AddComponents = 2,
     RemoveComponents = 3,
     SetComponents = 4,
+    SetComponentsDelta = 5,
 }
 impl TryFrom<u8> for WorldChangeTag {
     type Error = ();
@@ -102,6 +105,7 @@ impl TryFrom<u8> for WorldChangeTag {
             2 => Ok(Self::AddComponents),
             3 => Ok(Self::RemoveComponents),
             4 => Ok(Self::SetComponents),
+            5 => Ok(Self::SetComponentsDelta),
             _ => Err(()),
         }
     }
@@ -125,6 +129,7 @@ impl<'a> From<&NetworkedWorldChange<'a>> for WorldChangeTag {
             NetworkedWorldChange::AddComponents(_, _) => WorldChangeTag::AddComponents,
             NetworkedWorldChange::RemoveComponents(_, _) => WorldChangeTag::RemoveComponents,
             NetworkedWorldChange::SetComponents(_, _) => WorldChangeTag::SetComponents,
+            NetworkedWorldChange::SetComponentsDelta(_, _) => WorldChangeTag::SetComponentsDelta,
         }
     }
 }
@@ -211,6 +216,7 @@ impl From<&ComponentDesc> for UnknownComponent {
 /// - serializing EntityId in binary
 /// - using variable length integer encoding
 /// - indexing of Component paths to avoid duplicating the path string in the serialized format
+/// - delta compression of values set with `WorldChange::SetComponents` (see `delta_compression`)
 ///
 /// It's supposed to be used on both sides of serialization, that is a diff serialized with `DiffSerializer` has to be
 /// deserialized with another `DiffSerializer` instance. On top of that it assumes that it can keep its internal stat
@@ -240,6 +246,12 @@ impl From<&ComponentDesc> for UnknownComponent {
 ///     - `Entity` (for `Spawn`, `AddComponents`, `SetComponents`)
 ///     - collection of `ComponentDesc` (for `RemoveComponents`)
 ///     - `0` byte filler (for `Despawn`)
+///     - collection of `(u32, bool, Vec<u8>)` (for `SetComponentsDelta`)
+///
+/// `SetComponentsDelta` is used instead of `SetComponents` when delta compression is enabled. Each element is the
+/// component index, whether the bytes are a delta against the last value of that component sent for that entity, and
+/// either the delta or the full serialized value. Both sides keep the last values in a `ComponentValueCache`, which
+/// is invalidated by any other change that touches the same components.
 ///
 /// `Entity` is serialized as a collection of `ComponentEntry`.
 ///
@@ -265,14 +277,14 @@ impl From<&ComponentDesc> for UnknownComponent {
 /// let diff = WorldDiff { changes: vec![WorldChange::SetComponents(id, entity)] };
 /// let serialized = serializer.serialize(&diff).unwrap();
 ///
-/// assert_eq!(serialized.as_ref(), b"\x01\0\0\x18ambient_core::test::text\x01\x04\xfd\xef\xbe\xad\xde\xef\xbe\xad\xde\x01\0\x03foo");
+/// assert_eq!(serialized.as_ref(), b"\x01\0\0\x18ambient_core::test::text\x01\x05\xfd\xef\xbe\xad\xde\xef\xbe\xad\xde\x01\0\0\x04\x03foo");
 ///
 /// // note that when the same component is seen again, it won't be included in the index
 /// let new_entity = Entity::new().with(text(), "bar".to_string());
 /// let new_diff = WorldDiff { changes: vec![WorldChange::SetComponents(id, new_entity)] };
 /// let new_serialized = serializer.serialize(&new_diff).unwrap();
 ///
-/// assert_eq!(new_serialized.as_ref(), b"\0\x01\x04\xfd\xef\xbe\xad\xde\xef\xbe\xad\xde\x01\0\x03bar");
+/// assert_eq!(new_serialized.as_ref(), b"\0\x01\x05\xfd\xef\xbe\xad\xde\xef\xbe\xad\xde\x01\0\0\x04\x03bar");
 ///
 /// // that's why it's crucial for deserializer to see all payloads
 /// let mut deserializer = DiffSerializer::default();
@@ -281,6 +293,7 @@ impl From<&ComponentDesc> for UnknownComponent {
 #[derive(Clone, Default)]
 pub struct DiffSerializer {
     known_component_paths: HashMap<u32, String>,
+    component_values: ComponentValueCache,
 }
 
 impl std::fmt::Debug for DiffSerializer {
@@ -310,10 +323,8 @@ impl DiffSerializer {
     pub fn serialize_external_components(&mut self) -> Result<Bytes, bincode::Error> {
         // get all external components that we haven't seen before
         let unknown_components = self.collect_all_unknown_external_components();
-        // create dummy diff
-        let diff = Default::default();
-        // serialize everything
-        self.serialize_parts(unknown_components, &NetworkedWorldDiff(&diff))
+        // serialize everything with an empty diff
+        self.serialize_parts(unknown_components, &NetworkedWorldDiff::default())
     }
 
     fn collect_all_unknown_external_components(&self) -> HashMap<u32, UnknownComponent> {
@@ -329,8 +340,50 @@ impl DiffSerializer {
     pub fn serialize(&mut self, diff: &WorldDiff) -> Result<Bytes, bincode::Error> {
         // get all component that we haven't seen before
         let unknown_components = self.collect_unknown_components(diff.changes.iter());
+        // convert the changes, delta compressing values where possible
+        let changes = diff
+            .changes
+            .iter()
+            .map(|change| self.networked_change(change, delta_compression::ENABLED))
+            .collect::<Result<Vec<_>, _>>()?;
         // serialize everything
-        self.serialize_parts(unknown_components, &NetworkedWorldDiff(diff))
+        self.serialize_parts(unknown_components, &NetworkedWorldDiff(changes))
+    }
+
+    fn networked_change<'a>(
+        &mut self,
+        change: &'a WorldChange,
+        use_delta: bool,
+    ) -> Result<NetworkedWorldChange<'a>, bincode::Error> {
+        let WorldChange::SetComponents(id, entity) = change else {
+            self.component_values.invalidate(change);
+            return Ok(change.into());
+        };
+        if !use_delta {
+            return Ok(change.into());
+        }
+
+        let mut entries = Vec::with_capacity(entity.len());
+        for entry in entity.iter() {
+            let index = entry.desc().index();
+            let Some(ser) = entry.attribute::<Serializable>() else {
+                return Err(serde::ser::Error::custom(format!(
+                    "tried to serialize non-serializable component {:?}",
+                    entry
+                )));
+            };
+            let bytes: Bytes = bincode_options().serialize(ser.serialize(entry))?.into();
+            let delta = self
+                .component_values
+                .get(*id, index)
+                .and_then(|previous| delta_compression::encode(previous, &bytes));
+            entries.push(match delta {
+                Some(delta) => (index, true, delta),
+                None => (index, false, bytes.to_vec()),
+            });
+            self.component_values.insert(*id, index, bytes);
+        }
+        Ok(NetworkedWorldChange::SetComponentsDelta(id.0, entries))
     }
 
     fn collect_unknown_components<'a, I>(&self, changes: I) -> HashMap<u32, UnknownComponent>
@@ -382,20 +435,20 @@ impl DiffSerializer {
                 .map(|(k, v)| (k, v.into_path())),
         );
         // deserialize the actual changes
-        deserializer.deserialize_seq(NetworkedChangesVisitor::from(&*self))
+        let component_values = RefCell::new(std::mem::take(&mut self.component_values));
+        let result = deserializer.deserialize_seq(NetworkedChangesVisitor {
+            known_component_paths: &self.known_component_paths,
+            component_values: &component_values,
+        });
+        self.component_values = component_values.into_inner();
+        result
     }
 }
 
 #[derive(Clone, Copy, Debug)]
 struct NetworkedChangesVisitor<'a> {
     known_component_paths: &'a HashMap<u32, String>,
-}
-impl<'a> From<&'a DiffSerializer> for NetworkedChangesVisitor<'a> {
-    fn from(value: &'a DiffSerializer) -> Self {
-        Self {
-            known_component_paths: &value.known_component_paths,
-        }
-    }
+    component_values: &'a RefCell<ComponentValueCache>,
 }
 impl<'a, 'de> serde::de::Visitor<'de> for NetworkedChangesVisitor<'a> {
     type Value = WorldDiff;
@@ -423,6 +476,56 @@ impl<'a, 'de> serde::de::Visitor<'de> for NetworkedChangesVisitor<'a> {
 #[derive(Clone, Copy, Debug)]
 struct NetworkedChangeVisitor<'a> {
     known_component_paths: &'a HashMap<u32, String>,
+    component_values: &'a RefCell<ComponentValueCache>,
+}
+impl<'a> From<NetworkedChangesVisitor<'a>> for NetworkedChangeVisitor<'a> {
+    fn from(value: NetworkedChangesVisitor<'a>) -> Self {
+        Self {
+            known_component_paths: value.known_component_paths,
+            component_values: value.component_values,
+        }
+    }
+}
+impl<'a> NetworkedChangeVisitor<'a> {
+    fn apply_deltas<E: serde::de::Error>(
+        &self,
+        id: EntityId,
+        entries: Vec<(u32, bool, Vec<u8>)>,
+    ) -> Result<Entity, E> {
+        let mut component_values = self.component_values.borrow_mut();
+        let mut entity = Entity::new();
+        for (index, is_delta, data) in entries {
+            let desc = serde::de::Visitor::visit_u32::<E>(
+                NetworkedComponentDescVisitor {
+                    known_component_paths: self.known_component_paths,
+                },
+                index,
+            )?;
+            let Some(ser) = desc.attribute::<Serializable>() else {
+                return Err(E::custom(format!(
+                    "tried to deserialize non-serializable component {:?}",
+                    desc
+                )));
+            };
+
+            let bytes = if is_delta {
+                let previous = component_values.get(id, index).ok_or_else(|| {
+                    E::custom(format!("received delta for unknown value of {desc:?}"))
+                })?;
+                delta_compression::decode(previous, &data)
+                    .ok_or_else(|| E::custom(format!("invalid delta for {desc:?}")))?
+            } else {
+                data
+            };
+
+            let entry = bincode_options()
+                .deserialize_seed(ser.deserializer(desc), &bytes)
+                .map_err(E::custom)?;
+            entity.set_entry(entry);
+            component_values.insert(id, index, bytes.into());
+        }
+        Ok(entity)
+    }
 }
 impl<'a, 'de> serde::de::Visitor<'de> for NetworkedChangeVisitor<'a> {
     type Value = WorldChange;
@@ -447,7 +550,7 @@ impl<'a, 'de> serde::de::Visitor<'de> for NetworkedChangeVisitor<'a> {
             .next_element_seed(NetworkedEntityIdVisitor)?
             .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
 
-        Ok(match tag {
+        let change = match tag {
             WorldChangeTag::Spawn => {
                 let entity = seq
                     .next_element_seed(NetworkedEntityVisitor::from(self))?
@@ -479,7 +582,19 @@ impl<'a, 'de> serde::de::Visitor<'de> for NetworkedChangeVisitor<'a> {
                     .ok_or_else(|| serde::de::Error::invalid_length(2, &self))?;
                 WorldChange::SetComponents(id, entity)
             }
-        })
+            WorldChangeTag::SetComponentsDelta => {
+                let entries = seq
+                    .next_element::<Vec<(u32, bool, Vec<u8>)>>()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(2, &self))?;
+                // returned directly, as the values have already been recorded
+                return Ok(WorldChange::SetComponents(
+                    id,
+                    self.apply_deltas(id, entries)?,
+                ));
+            }
+        };
+        self.component_values.borrow_mut().invalidate(&change);
+        Ok(change)
     }
 }
 impl<'a, 'de> serde::de::DeserializeSeed<'de> for NetworkedChangeVisitor<'a> {
@@ -691,16 +806,16 @@ impl<'a, 'de> serde::de::DeserializeSeed<'de> for NetworkedComponentEntryVisitor
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-struct NetworkedWorldDiff<'a>(&'a WorldDiff);
+#[derive(Clone, Debug, Default)]
+struct NetworkedWorldDiff<'a>(Vec<NetworkedWorldChange<'a>>);
 impl<'a> serde::Serialize for NetworkedWorldDiff<'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        let mut seq = serializer.serialize_seq(Some(self.0.changes.len()))?;
-        for change in self.0.changes.iter() {
-            seq.serialize_element(&NetworkedWorldChange::from(change))?;
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for change in self.0.iter() {
+            seq.serialize_element(change)?;
         }
         seq.end()
     }
@@ -713,6 +828,7 @@ enum NetworkedWorldChange<'a> {
     AddComponents(u128, NetworkedEntity<'a>),
     RemoveComponents(u128, Vec<NetworkedComponentDesc>),
     SetComponents(u128, NetworkedEntity<'a>),
+    SetComponentsDelta(u128, Vec<(u32, bool, Vec<u8>)>),
 }
 impl<'a> NetworkedWorldChange<'a> {
     fn id(&self) -> u128 {
@@ -721,13 +837,16 @@ impl<'a> NetworkedWorldChange<'a> {
             | NetworkedWorldChange::Despawn(id)
             | NetworkedWorldChange::AddComponents(id, _)
             | NetworkedWorldChange::RemoveComponents(id, _)
-            | NetworkedWorldChange::SetComponents(id, _) => *id,
+            | NetworkedWorldChange::SetComponents(id, _)
+            | NetworkedWorldChange::SetComponentsDelta(id, _) => *id,
         }
     }
 
     fn entity(&self) -> Option<NetworkedEntity<'a>> {
         match self {
-            NetworkedWorldChange::Despawn(_) | NetworkedWorldChange::RemoveComponents(_, _) => None,
+            NetworkedWorldChange::Despawn(_)
+            | NetworkedWorldChange::RemoveComponents(_, _)
+            | NetworkedWorldChange::SetComponentsDelta(_, _) => None,
             NetworkedWorldChange::Spawn(_, e)
             | NetworkedWorldChange::AddComponents(_, e)
             | NetworkedWorldChange::SetComponents(_, e) => Some(*e),
@@ -773,6 +892,8 @@ impl<'a> serde::Serialize for NetworkedWorldChange<'a> {
             seq.serialize_element(components)?;
         } else if let NetworkedWorldChange::Despawn(_) = self {
             seq.serialize_element(&0u8)?;
+        } else if let NetworkedWorldChange::SetComponentsDelta(_, entries) = self {
+            seq.serialize_element(entries)?;
         } else {
             unreachable!();
         }
@@ -829,7 +950,6 @@ macro_rules! impl_from_kcp {
         }
     };
 }
-impl_from_kcp!(NetworkedChangesVisitor<'a>, NetworkedChangeVisitor<'a>);
 impl_from_kcp!(NetworkedChangeVisitor<'a>, NetworkedEntityVisitor<'a>);
 impl_from_kcp!(
     NetworkedChangeVisitor<'a>,
@@ -1021,6 +1141,66 @@ mod tests {
         assert_same_diffs(&diff, &second_diff);
     }
 
+    #[test]
+    fn repeated_set_components_are_delta_compressed() {
+        // Arrange
+        init_components();
+        let id = EntityId::new();
+        let text_value = "a long string that will only change slightly".to_string();
+        let diff = |n: usize| WorldDiff {
+            changes: vec![WorldChange::SetComponents(
+                id,
+                Entity::new()
+                    .with(text(), text_value.clone())
+                    .with(counter(), n),
+            )],
+        };
+        let mut serializer = DiffSerializer::default();
+        let mut deserializer = DiffSerializer::default();
+
+        // Act
+        let first_message = serializer.serialize(&diff(1)).unwrap();
+        let second_message = serializer.serialize(&diff(2)).unwrap();
+
+        // Assert
+        assert!(second_message.len() + text_value.len() < first_message.len());
+        assert_same_diffs(&diff(1), &deserializer.deserialize(first_message).unwrap());
+        assert_same_diffs(&diff(2), &deserializer.deserialize(second_message).unwrap());
+    }
+
+    #[test]
+    fn delta_compression_is_reset_by_other_changes() {
+        init_components();
+        let id = EntityId::new();
+        let entity = Entity::new().with(text(), "foo".to_string());
+        let diffs = [
+            WorldDiff {
+                changes: vec![WorldChange::SetComponents(id, entity.clone())],
+            },
+            WorldDiff {
+                changes: vec![
+                    WorldChange::Despawn(id),
+                    WorldChange::Spawn(id, entity.clone()),
+                    WorldChange::SetComponents(id, entity.clone()),
+                ],
+            },
+            WorldDiff {
+                changes: vec![
+                    WorldChange::RemoveComponents(id, entity.components()),
+                    WorldChange::AddComponents(id, entity.clone()),
+                    WorldChange::SetComponents(id, entity.clone()),
+                ],
+            },
+        ];
+        let mut serializer = DiffSerializer::default();
+        let mut deserializer = DiffSerializer::default();
+
+        for diff in diffs {
+            let message = serializer.serialize(&diff).unwrap();
+            assert_same_diffs(&diff, &deserializer.deserialize(message).unwrap());
+        }
+    }
+
     #[test]
     fn world_change_tag_to_and_from_u8_matches() {
         for tag in [
@@ -1029,6 +1209,7 @@ mod tests {
             WorldChangeTag::AddComponents,
             WorldChangeTag::RemoveComponents,
             WorldChangeTag::SetComponents,
+            WorldChangeTag::SetComponentsDelta,
         ] {
             assert_eq!(WorldChangeTag::try_from(tag as u8).unwrap(), tag);
         }
@@ -0,0 +1,187 @@
+//! Periodic reports of how a server spends the time of its ticks, broken down by subsystem, and
+//! warnings for the ticks that take longer than the tick time, so that overloaded servers can be
+//! diagnosed from their logs.
+//!
+//! The reports are logged with the `ambient::frame_budget` target, with one event for the server
+//! and one for each WASM module. The times are averaged over the ticks of the report.
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use ambient_core::frame_budget::{FrameBudget, PHYSICS, REPLICATION};
+use ambient_sys::time::Instant;
+
+/// How often the report is logged.
+pub const REPORT_INTERVAL: Duration = Duration::from_secs(30);
+
+// Assets are served outside of the ticks, by the connections, so they are counted separately
+static ASSET_SERVING_MICROS: AtomicU64 = AtomicU64::new(0);
+static ASSETS_SERVED: AtomicU64 = AtomicU64::new(0);
+static ASSET_BYTES_SERVED: AtomicU64 = AtomicU64::new(0);
+
+/// Records that an asset of `bytes` bytes was served to a client, which took `duration`.
+pub fn record_asset_served(duration: Duration, bytes: usize) {
+    ASSET_SERVING_MICROS.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    ASSETS_SERVED.fetch_add(1, Ordering::Relaxed);
+    ASSET_BYTES_SERVED.fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+/// How the ticks of a server spent their time since the previous report.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameBudgetReport {
+    pub ticks: u32,
+    /// The time taken by all of the ticks
+    pub total: Duration,
+    pub slowest_tick: Duration,
+    /// The number of ticks that took longer than the tick time
+    pub over_budget: u32,
+    /// The time spent by each subsystem during the ticks
+    pub budget: FrameBudget,
+    pub asset_serving: Duration,
+    pub assets_served: u64,
+    pub asset_bytes_served: u64,
+}
+impl FrameBudgetReport {
+    /// The average time spent per tick, in milliseconds.
+    fn per_tick_ms(&self, duration: Duration) -> f64 {
+        duration.as_secs_f64() * 1000.0 / self.ticks.max(1) as f64
+    }
+
+    fn log(&self, tick_time: Duration) {
+        let subsystem = |name| {
+            self.per_tick_ms(
+                self.budget
+                    .subsystems
+                    .get(name)
+                    .copied()
+                    .unwrap_or_default(),
+            )
+        };
+        tracing::info!(
+            target: "ambient::frame_budget",
+            ticks = self.ticks,
+            budget_ms = tick_time.as_secs_f64() * 1000.0,
+            mean_tick_ms = self.per_tick_ms(self.total),
+            slowest_tick_ms = self.slowest_tick.as_secs_f64() * 1000.0,
+            over_budget = self.over_budget,
+            physics_ms = subsystem(PHYSICS),
+            replication_ms = subsystem(REPLICATION),
+            wasm_ms = self.per_tick_ms(self.budget.wasm()),
+            asset_serving_ms = self.asset_serving.as_secs_f64() * 1000.0,
+            assets_served = self.assets_served,
+            asset_bytes_served = self.asset_bytes_served,
+            "Server frame budget"
+        );
+        for (module, duration) in &self.budget.modules {
+            tracing::info!(
+                target: "ambient::frame_budget",
+                module = %module,
+                wasm_ms = self.per_tick_ms(*duration),
+                "WASM module frame budget"
+            );
+        }
+    }
+}
+
+/// Accumulates the time spent by the ticks of a server, and logs a [FrameBudgetReport] every
+/// [REPORT_INTERVAL].
+#[derive(Debug)]
+pub struct FrameBudgetReporter {
+    tick_time: Duration,
+    period_start: Instant,
+    report: FrameBudgetReport,
+}
+impl FrameBudgetReporter {
+    pub fn new(tick_time: Duration) -> Self {
+        Self {
+            tick_time,
+            period_start: Instant::now(),
+            report: FrameBudgetReport::default(),
+        }
+    }
+
+    /// Records a tick that took `duration`, during which the subsystems spent `budget`.
+    ///
+    /// Warns if the tick took longer than the tick time. Only the first such tick of each report
+    /// is logged, so that an overloaded server does not flood its logs.
+    pub fn tick(&mut self, duration: Duration, budget: FrameBudget) {
+        let report = &mut self.report;
+        report.ticks += 1;
+        report.total += duration;
+        report.slowest_tick = report.slowest_tick.max(duration);
+        if duration > self.tick_time {
+            report.over_budget += 1;
+            if report.over_budget == 1 {
+                let ms = |duration: Option<&Duration>| {
+                    duration.copied().unwrap_or_default().as_secs_f64() * 1000.0
+                };
+                tracing::warn!(
+                    target: "ambient::frame_budget",
+                    tick_ms = duration.as_secs_f64() * 1000.0,
+                    budget_ms = self.tick_time.as_secs_f64() * 1000.0,
+                    physics_ms = ms(budget.subsystems.get(PHYSICS)),
+                    replication_ms = ms(budget.subsystems.get(REPLICATION)),
+                    wasm_ms = budget.wasm().as_secs_f64() * 1000.0,
+                    "Server tick took longer than its budget"
+                );
+            }
+        }
+        report.budget.merge(budget);
+
+        if self.period_start.elapsed() >= REPORT_INTERVAL {
+            self.take_report().log(self.tick_time);
+        }
+    }
+
+    /// Returns the report of the ticks since the previous one, and starts a new one.
+    pub fn take_report(&mut self) -> FrameBudgetReport {
+        self.period_start = Instant::now();
+        FrameBudgetReport {
+            asset_serving: Duration::from_micros(ASSET_SERVING_MICROS.swap(0, Ordering::Relaxed)),
+            assets_served: ASSETS_SERVED.swap(0, Ordering::Relaxed),
+            asset_bytes_served: ASSET_BYTES_SERVED.swap(0, Ordering::Relaxed),
+            ..std::mem::take(&mut self.report)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn budget(physics: u64, modules: &[(&str, u64)]) -> FrameBudget {
+        FrameBudget {
+            subsystems: [(PHYSICS, Duration::from_millis(physics))]
+                .into_iter()
+                .collect(),
+            modules: modules
+                .iter()
+                .map(|(name, ms)| (name.to_string(), Duration::from_millis(*ms)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn report() {
+        let mut reporter = FrameBudgetReporter::new(Duration::from_millis(16));
+        reporter.tick(Duration::from_millis(10), budget(4, &[("game", 2)]));
+        reporter.tick(
+            Duration::from_millis(20),
+            budget(6, &[("game", 3), ("ui", 1)]),
+        );
+
+        let report = reporter.take_report();
+        assert_eq!(report.ticks, 2);
+        assert_eq!(report.total, Duration::from_millis(30));
+        assert_eq!(report.slowest_tick, Duration::from_millis(20));
+        assert_eq!(report.over_budget, 1);
+        assert_eq!(report.budget, budget(10, &[("game", 5), ("ui", 1)]));
+        assert_eq!(report.per_tick_ms(report.budget.wasm()), 3.0);
+
+        // The next report starts from scratch
+        let report = reporter.take_report();
+        assert_eq!(report.ticks, 0);
+        assert_eq!(report.budget, FrameBudget::default());
+    }
+}
@@ -0,0 +1,179 @@
+//! Host migration: when the player hosting a session quits, one of the remaining clients takes
+//! over as the server.
+//!
+//! The old host picks a successor among the players that are connected directly over QUIC, and
+//! sends a [ServerPush::HostMigration] to every client. The successor captures a [HostSnapshot] of
+//! the networked state of its client world and starts a server from it, while the other clients
+//! reconnect to the successor's address.
+use std::net::SocketAddr;
+
+use ambient_core::{
+    player::{is_player, user_id},
+    stable_id,
+};
+use ambient_ecs::{
+    generated::{
+        network::components::host_migrated, package::components::is_package,
+        wasm::components::is_module,
+    },
+    query, Entity, EntityId, Networked, World,
+};
+
+use crate::{
+    is_remote_entity, is_synced_resources,
+    proto::{HostMigration, ServerPush},
+    server::{is_spectator_connection, player_connection_id, ServerState, MAIN_INSTANCE_ID},
+};
+
+/// How long the new host waits for the players of the old host to reconnect before their player
+/// entities are despawned.
+pub const RECONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// The networked state of the world as seen by a client, which the new host restores.
+#[derive(Debug, Clone, Default)]
+pub struct HostSnapshot {
+    pub entities: Vec<(EntityId, Entity)>,
+}
+impl HostSnapshot {
+    /// Captures the entities that were synchronized from the server, with their networked
+    /// components. The synchronized resources, packages and modules are not included, as the new
+    /// server recreates them when it starts.
+    pub fn capture(world: &World) -> Self {
+        let entities = query(())
+            .incl(is_remote_entity())
+            .excl(is_synced_resources())
+            .excl(is_package())
+            .excl(is_module())
+            .iter(world, None)
+            .filter_map(|(id, _)| {
+                let mut entity = world.clone_entity(id).ok()?;
+                entity.filter(&|desc| {
+                    desc.has_attribute::<Networked>() && desc != is_remote_entity().desc()
+                });
+                Some((id, entity))
+            })
+            .collect();
+
+        Self { entities }
+    }
+
+    /// Spawns the captured entities into the world of the new server, keeping their ids.
+    ///
+    /// If an id is already taken, an entity with a [stable_id] is spawned with a new id instead,
+    /// as it can still be found by its stable ID.
+    pub fn restore(self, world: &mut World) {
+        tracing::info!(
+            "Restoring {} entities from the previous host",
+            self.entities.len()
+        );
+        for (id, entity) in self.entities {
+            if world.exists(id) && entity.contains(stable_id()) {
+                tracing::warn!(?id, "Entity already exists; spawning it with a new id");
+                entity.spawn(world);
+            } else if !world.spawn_with_id(id, entity) {
+                tracing::warn!(?id, "Entity already exists; skipping");
+            }
+        }
+        world.add_resource(host_migrated(), ());
+    }
+}
+
+/// Picks the successor among the `candidates`, which are the user IDs and addresses of the
+/// connected players. The new server listens on `port`.
+///
+/// The user hosting the session is skipped as it is leaving, and so are players connected over
+/// loopback, as they run on the host's machine and cannot be reached by the other players.
+pub fn choose_successor<'a>(
+    candidates: impl IntoIterator<Item = (&'a str, SocketAddr)>,
+    host_user_id: &str,
+    port: u16,
+) -> Option<HostMigration> {
+    let (successor, addr) = candidates
+        .into_iter()
+        .filter(|(user_id, addr)| *user_id != host_user_id && !addr.ip().is_loopback())
+        .min_by_key(|(user_id, _)| *user_id)?;
+
+    Some(HostMigration {
+        successor: successor.to_string(),
+        host_name: addr.ip().to_string(),
+        addr: SocketAddr::new(addr.ip(), port),
+    })
+}
+
+/// Picks a successor among the players connected to the session hosted by `host_user_id` and
+/// tells every client about it.
+///
+/// Returns `None` if no player can take over as the host.
+pub fn start_migration(
+    state: &mut ServerState,
+    host_user_id: &str,
+    port: u16,
+) -> Option<HostMigration> {
+    let world = &state.instances.get(MAIN_INSTANCE_ID)?.world;
+    let candidates = state.players.iter().filter_map(|(user_id, player)| {
+        let addr = player.remote_addr?;
+        (!is_spectator_connection(world, user_id)).then_some((user_id.as_str(), addr))
+    });
+
+    let migration = choose_successor(candidates, host_user_id, port)?;
+    tracing::info!(?migration, "Migrating the host");
+    for player in state.players.values() {
+        player.push(ServerPush::HostMigration(migration.clone()));
+    }
+
+    Some(migration)
+}
+
+/// Despawns the restored player entities that have not been taken over by their users.
+pub fn despawn_unclaimed_players(world: &mut World) {
+    let unclaimed = query(user_id())
+        .incl(is_player())
+        .excl(player_connection_id())
+        .iter(world, None)
+        .map(|(id, user_id)| (id, user_id.clone()))
+        .collect::<Vec<_>>();
+
+    for (id, user_id) in unclaimed {
+        tracing::info!(%user_id, "Player did not rejoin after host migration");
+        ambient_core::hierarchy::despawn_recursive(world, id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn successor() {
+        let candidates = [
+            ("bob", SocketAddr::from(([192, 168, 0, 3], 21000))),
+            ("alice", SocketAddr::from(([192, 168, 0, 2], 22000))),
+        ];
+
+        let migration = choose_successor(candidates, "host", 9000).unwrap();
+        assert_eq!(migration.successor, "alice");
+        assert_eq!(migration.host_name, "192.168.0.2");
+        assert_eq!(migration.addr, SocketAddr::from(([192, 168, 0, 2], 9000)));
+
+        assert_eq!(choose_successor([], "host", 9000), None);
+    }
+
+    #[test]
+    fn host_and_loopback_players_are_not_successors() {
+        let candidates = [
+            ("alice", SocketAddr::from(([192, 168, 0, 2], 22000))),
+            ("bob", SocketAddr::from(([127, 0, 0, 1], 21000))),
+            ("carol", SocketAddr::from(([192, 168, 0, 4], 23000))),
+        ];
+
+        // alice is hosting, and bob is playing on the same machine
+        let migration = choose_successor(candidates, "alice", 9001).unwrap();
+        assert_eq!(migration.successor, "carol");
+        assert_eq!(migration.addr, SocketAddr::from(([192, 168, 0, 4], 9001)));
+
+        assert_eq!(
+            choose_successor(candidates[..2].iter().copied(), "alice", 9001),
+            None
+        );
+    }
+}
@@ -16,12 +16,21 @@ pub use ambient_ecs::generated::network::components::{
 
 pub type AsyncMutex<T> = tokio::sync::Mutex<T>;
 
+#[cfg(not(target_os = "unknown"))]
+pub mod asset_stream;
+pub mod autosave;
+pub mod bans;
 pub mod bytes_ext;
 pub mod client;
 pub mod client_game_state;
 pub mod codec;
+pub mod compression;
+pub mod delta_compression;
 pub mod diff_serialization;
+pub mod frame_budget;
 pub mod hooks;
+pub mod host_migration;
+pub mod memory_report;
 pub mod proto;
 pub mod rpc;
 pub mod serialization;
@@ -47,6 +56,11 @@ pub const WASM_UNISTREAM_ID: u32 = 11;
 
 pub const PLAYER_INPUT_DATAGRAM_ID: u32 = 12;
 pub const WASM_DATAGRAM_ID: u32 = 13;
+pub const VOICE_DATAGRAM_ID: u32 = 14;
+
+pub const ASSET_STREAM_BISTREAM_ID: u32 = 15;
+
+pub const MESSAGE_INTERESTS_UNISTREAM_ID: u32 = 16;
 
 const MAX_FRAME_SIZE: usize = 1024 * 1024 * 1024;
 
@@ -166,6 +180,10 @@ pub enum NetworkError {
     FrameError(#[from] FrameError),
     #[error("Frame or stream exceeds maximum allowed size")]
     FrameTooLarge,
+    #[error("Connection rejected by server: {0}")]
+    ConnectionRejected(#[from] proto::ConnectionRejection),
+    #[error("Incompatible server: {0}")]
+    VersionMismatch(#[from] proto::VersionMismatch),
 
     #[error("Backpressure")]
     Backpressure,
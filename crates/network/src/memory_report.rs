@@ -0,0 +1,360 @@
+//! Periodic reports of the memory used by a server and of the number of entities and assets it
+//! keeps, with how they have grown over the last hours, so that leaks in long-running servers can
+//! be found from their logs.
+//!
+//! The reports are logged with the `ambient::memory` target. The memory used by each subsystem is
+//! only reported if the server tracks its allocations; see [ambient_core::memory]. Quantities that
+//! have grown steadily for at least [LEAK_WINDOW] are logged as warnings.
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Duration,
+};
+
+use ambient_core::memory;
+use ambient_ecs::with_component_registry;
+use ambient_native_std::asset_cache::AssetCacheStats;
+use ambient_sys::time::Instant;
+
+use crate::server::ServerState;
+
+/// How often the server is sampled and the report is logged.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// How far back the trends are computed.
+pub const TREND_WINDOW: Duration = Duration::from_secs(6 * 60 * 60);
+/// How long a quantity must have grown at every sample to be reported as a possible leak.
+pub const LEAK_WINDOW: Duration = Duration::from_secs(60 * 60);
+/// How many of the components with the fastest growing entity counts are reported.
+const REPORTED_COMPONENTS: usize = 5;
+
+/// The memory used by a server, and the number of entities and assets it keeps, at a given time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemorySample {
+    /// The time since the server started
+    pub time: Duration,
+    /// The bytes allocated by each subsystem, if the server tracks its allocations
+    pub subsystems: BTreeMap<String, u64>,
+    /// The number of entities of all the instances
+    pub entities: u64,
+    /// The number of entities that have each component, by component path
+    pub components: BTreeMap<String, u64>,
+    pub assets: AssetCacheStats,
+}
+impl MemorySample {
+    pub fn collect(state: &ServerState, time: Duration) -> Self {
+        let mut entities = 0;
+        let mut counts = BTreeMap::<usize, u64>::new();
+        for instance in state.instances.values() {
+            for archetype in instance.world.archetypes() {
+                let count = archetype.entity_count() as u64;
+                entities += count;
+                for index in archetype.active_components().0.iter() {
+                    *counts.entry(index).or_default() += count;
+                }
+            }
+        }
+        let components = with_component_registry(|cr| {
+            counts
+                .into_iter()
+                .filter_map(|(index, count)| Some((cr.get_by_index(index as u32)?.path(), count)))
+                .collect()
+        });
+
+        Self {
+            time,
+            subsystems: memory::usage()
+                .into_iter()
+                .map(|usage| (usage.subsystem, usage.bytes))
+                .collect(),
+            entities,
+            components,
+            assets: state.assets.stats(),
+        }
+    }
+}
+
+/// How a quantity has changed over the samples of the trend window.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Trend {
+    pub current: u64,
+    /// The average growth per hour over the window
+    pub per_hour: f64,
+    /// Whether the quantity has grown at every sample of the window, and the window is at least
+    /// [LEAK_WINDOW] long
+    pub steady_growth: bool,
+}
+impl Trend {
+    fn new(samples: &VecDeque<MemorySample>, value: impl Fn(&MemorySample) -> u64) -> Self {
+        let (Some(first), Some(last)) = (samples.front(), samples.back()) else {
+            return Self::default();
+        };
+        let window = last.time.saturating_sub(first.time);
+        let hours = window.as_secs_f64() / 3600.0;
+        let (start, current) = (value(first), value(last));
+        Self {
+            current,
+            per_hour: if hours > 0.0 {
+                (current as f64 - start as f64) / hours
+            } else {
+                0.0
+            },
+            steady_growth: window >= LEAK_WINDOW
+                && samples
+                    .iter()
+                    .zip(samples.iter().skip(1))
+                    .all(|(a, b)| value(b) > value(a)),
+        }
+    }
+}
+
+/// The trends of the memory, entities and assets of a server.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryReport {
+    /// The time covered by the trends
+    pub window: Duration,
+    /// The total bytes allocated, if the server tracks its allocations
+    pub total_bytes: Option<Trend>,
+    /// The bytes allocated by each subsystem
+    pub subsystems: BTreeMap<String, Trend>,
+    pub entities: Trend,
+    /// The components with the fastest growing entity counts, fastest first
+    pub components: Vec<(String, Trend)>,
+    pub async_assets: Trend,
+    pub sync_assets: Trend,
+    pub asset_timeline_entries: Trend,
+}
+impl MemoryReport {
+    fn log(&self) {
+        let mb = |bytes: f64| bytes / (1024.0 * 1024.0);
+        if let Some(total) = &self.total_bytes {
+            tracing::info!(
+                target: "ambient::memory",
+                window_hours = self.window.as_secs_f64() / 3600.0,
+                allocated_mb = mb(total.current as f64),
+                growth_mb_per_hour = mb(total.per_hour),
+                "Server memory"
+            );
+        }
+        for (subsystem, trend) in &self.subsystems {
+            tracing::info!(
+                target: "ambient::memory",
+                subsystem = %subsystem,
+                allocated_mb = mb(trend.current as f64),
+                growth_mb_per_hour = mb(trend.per_hour),
+                "Subsystem memory"
+            );
+            if trend.steady_growth {
+                tracing::warn!(
+                    target: "ambient::memory",
+                    subsystem = %subsystem,
+                    allocated_mb = mb(trend.current as f64),
+                    growth_mb_per_hour = mb(trend.per_hour),
+                    "The memory of a subsystem keeps growing, which may be a leak"
+                );
+            }
+        }
+
+        tracing::info!(
+            target: "ambient::memory",
+            entities = self.entities.current,
+            entity_growth_per_hour = self.entities.per_hour,
+            async_assets = self.async_assets.current,
+            async_asset_growth_per_hour = self.async_assets.per_hour,
+            sync_assets = self.sync_assets.current,
+            sync_asset_growth_per_hour = self.sync_assets.per_hour,
+            asset_timeline_entries = self.asset_timeline_entries.current,
+            "Server entities and assets"
+        );
+        for (component, trend) in &self.components {
+            tracing::info!(
+                target: "ambient::memory",
+                component = %component,
+                entities = trend.current,
+                entity_growth_per_hour = trend.per_hour,
+                "Fastest growing component"
+            );
+        }
+
+        let leaks = [
+            ("entities".to_string(), &self.entities),
+            ("async assets".to_string(), &self.async_assets),
+            ("sync assets".to_string(), &self.sync_assets),
+            (
+                "asset timeline entries".to_string(),
+                &self.asset_timeline_entries,
+            ),
+        ]
+        .into_iter()
+        .chain(
+            self.components
+                .iter()
+                .map(|(component, trend)| (format!("entities with {component}"), trend)),
+        );
+        for (name, trend) in leaks {
+            if trend.steady_growth {
+                tracing::warn!(
+                    target: "ambient::memory",
+                    count = trend.current,
+                    growth_per_hour = trend.per_hour,
+                    "The number of {name} keeps growing, which may be a leak"
+                );
+            }
+        }
+    }
+}
+
+/// Samples a server every [SAMPLE_INTERVAL], and logs a [MemoryReport] of the samples of the last
+/// [TREND_WINDOW].
+#[derive(Debug)]
+pub struct MemoryReporter {
+    start: Instant,
+    last_sample: Option<Instant>,
+    samples: VecDeque<MemorySample>,
+}
+impl MemoryReporter {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            last_sample: None,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Samples `state` and logs a report, if it has not been sampled for [SAMPLE_INTERVAL].
+    pub fn tick(&mut self, state: &ServerState) {
+        if self
+            .last_sample
+            .map_or(false, |last| last.elapsed() < SAMPLE_INTERVAL)
+        {
+            return;
+        }
+        self.last_sample = Some(Instant::now());
+        let sample = MemorySample::collect(state, self.start.elapsed());
+        self.record(sample).log();
+    }
+
+    /// Adds `sample` to the samples of the trend window, and returns the report of the window.
+    pub fn record(&mut self, sample: MemorySample) -> MemoryReport {
+        let now = sample.time;
+        self.samples.push_back(sample);
+        while self
+            .samples
+            .front()
+            .map_or(false, |first| now.saturating_sub(first.time) > TREND_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+
+        let samples = &self.samples;
+        let last = samples.back().expect("A sample was just added");
+        let window = now.saturating_sub(samples.front().map_or(now, |first| first.time));
+
+        let subsystems: BTreeMap<_, _> = last
+            .subsystems
+            .keys()
+            .map(|subsystem| {
+                let trend = Trend::new(samples, |sample| {
+                    sample
+                        .subsystems
+                        .get(subsystem)
+                        .copied()
+                        .unwrap_or_default()
+                });
+                (subsystem.clone(), trend)
+            })
+            .collect();
+
+        let mut components = last
+            .components
+            .keys()
+            .map(|component| {
+                let trend = Trend::new(samples, |sample| {
+                    sample
+                        .components
+                        .get(component)
+                        .copied()
+                        .unwrap_or_default()
+                });
+                (component.clone(), trend)
+            })
+            .filter(|(_, trend)| trend.per_hour > 0.0)
+            .collect::<Vec<_>>();
+        components.sort_by(|(_, a), (_, b)| b.per_hour.total_cmp(&a.per_hour));
+        components.truncate(REPORTED_COMPONENTS);
+
+        MemoryReport {
+            window,
+            total_bytes: (!last.subsystems.is_empty())
+                .then(|| Trend::new(samples, |sample| sample.subsystems.values().sum())),
+            subsystems,
+            entities: Trend::new(samples, |sample| sample.entities),
+            components,
+            async_assets: Trend::new(samples, |sample| sample.assets.async_assets as u64),
+            sync_assets: Trend::new(samples, |sample| sample.assets.sync_assets as u64),
+            asset_timeline_entries: Trend::new(samples, |sample| {
+                sample.assets.timeline_entries as u64
+            }),
+        }
+    }
+}
+impl Default for MemoryReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample(minutes: u64, entities: u64, bullets: u64) -> MemorySample {
+        MemorySample {
+            time: Duration::from_secs(minutes * 60),
+            entities,
+            components: [
+                ("game::bullet".to_string(), bullets),
+                ("game::player".to_string(), 2),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn trends() {
+        let mut reporter = MemoryReporter::new();
+        reporter.record(sample(0, 100, 10));
+        let report = reporter.record(sample(30, 150, 60));
+        assert_eq!(report.window, Duration::from_secs(30 * 60));
+        assert_eq!(report.entities.current, 150);
+        assert_eq!(report.entities.per_hour, 100.0);
+        // The window is too short to call it a leak
+        assert!(!report.entities.steady_growth);
+        assert_eq!(report.total_bytes, None);
+
+        let report = reporter.record(sample(60, 200, 110));
+        assert!(report.entities.steady_growth);
+        // Only the components that grew are reported
+        assert_eq!(report.components.len(), 1);
+        assert_eq!(report.components[0].0, "game::bullet");
+        assert_eq!(report.components[0].1.per_hour, 100.0);
+
+        // Growth that stops is not a leak
+        let report = reporter.record(sample(90, 200, 110));
+        assert!(!report.entities.steady_growth);
+        assert_eq!(report.entities.current, 200);
+        assert!((report.entities.per_hour - 100.0 / 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn old_samples_leave_the_window() {
+        let mut reporter = MemoryReporter::new();
+        reporter.record(sample(0, 1000, 0));
+        let window_minutes = TREND_WINDOW.as_secs() / 60;
+        reporter.record(sample(window_minutes, 100, 0));
+        let report = reporter.record(sample(window_minutes + 60, 200, 0));
+        assert_eq!(report.window, Duration::from_secs(60 * 60));
+        assert_eq!(report.entities.per_hour, 100.0);
+        assert!(report.entities.steady_growth);
+    }
+}
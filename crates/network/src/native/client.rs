@@ -1,10 +1,20 @@
 use crate::{
-    client::{CleanupFunc, ClientState, Control, GameClientRenderTarget, LoadedFunc, NetworkStats},
+    asset_stream::ClientAssetStream,
+    client::{
+        CleanupFunc, ClientState, Control, GameClientRenderTarget, LoadedFunc, NetworkStats,
+        NetworkTransport,
+    },
     client_game_state::{game_screen_render_target, ClientGameState},
-    native::load_root_certs,
+    compression::{self, Compression},
+    host_migration::HostSnapshot,
+    native::{
+        load_root_certs,
+        local_connection::{LocalConnection, LocalConnector},
+        simulation::{self, NetworkConditions},
+    },
     proto::{
         client::{ClientProtoState, SharedClientGameState},
-        ClientRequest,
+        ClientRequest, HostMigration,
     },
     server::RpcArgs,
     stream::{FramedRecvStream, FramedSendStream, RawFramedRecvStream},
@@ -17,16 +27,18 @@ use ambient_element::{
     consume_context, provide_context, use_frame, use_runtime_message, use_state, use_state_with,
     use_task, Element, ElementComponent, ElementComponentExt, Hooks,
 };
+use ambient_gpu::gpu::Gpu;
 use ambient_native_std::{
     asset_cache::{AssetCache, SyncAssetKeyExt},
     asset_url::ContentBaseUrlKey,
+    download_asset::{AssetStream, AssetStreamKey},
     Cb,
 };
 use ambient_renderer::RenderTarget;
 use ambient_rpc::RpcRegistry;
 use ambient_ui_native::{Centered, Dock, FlowColumn, FlowRow, StylesExt, Text, Throbber};
 use anyhow::Context;
-use futures::{SinkExt, StreamExt};
+use futures::{future::BoxFuture, SinkExt, StreamExt};
 use glam::uvec2;
 use parking_lot::Mutex;
 use quinn::{ClientConfig, Connection, Endpoint, TransportConfig};
@@ -71,12 +83,29 @@ impl ResolvedAddr {
 #[derive(Debug, Clone)]
 pub struct ClientView {
     pub server_addr: ResolvedAddr,
+    /// Connects to the server in-memory rather than over QUIC, if it runs in the same process.
+    /// Only used for the first connection, as a new host is always connected to over QUIC.
+    pub local_server: Option<LocalConnector>,
     pub cert: Option<Vec<u8>>,
     pub user_id: String,
+    /// Whether to join as a spectator, which receives the world state without a player entity
+    pub spectator: bool,
     pub fail_on_version_mismatch: bool,
+    /// The network conditions to simulate on the connection to the server, for testing
+    pub network_conditions: NetworkConditions,
     pub systems_and_resources: Cb<dyn Fn() -> (SystemGroup, Entity) + Sync + Send>,
     pub on_loaded: LoadedFunc,
     pub create_rpc_registry: Cb<dyn Fn() -> RpcRegistry<RpcArgs> + Sync + Send>,
+    /// Starts a server from the snapshot when this client is chosen to take over as the host.
+    /// The returned future completes once the server is listening on the given port.
+    pub on_host_migration: Option<
+        Cb<dyn Fn(HostSnapshot, u16) -> BoxFuture<'static, anyhow::Result<()>> + Sync + Send>,
+    >,
+    /// Whether the world is being loaded in the background while another view is shown.
+    ///
+    /// A background view does not receive input or set the window title, and shows nothing but
+    /// `inner` once connected; errors are only logged.
+    pub background: bool,
     pub inner: Element,
 }
 
@@ -84,11 +113,16 @@ impl ElementComponent for ClientView {
     fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
         let Self {
             server_addr,
+            local_server,
             user_id,
+            spectator,
             fail_on_version_mismatch,
+            network_conditions,
             systems_and_resources,
             create_rpc_registry,
             on_loaded,
+            on_host_migration,
+            background,
             inner,
             cert,
         } = *self;
@@ -117,36 +151,19 @@ impl ElementComponent for ClientView {
         });
 
         // Run game logic
-        {
-            let gpu = gpu.clone();
-            let render_target = render_target.clone();
-            let world_event_reader = Mutex::new(hooks.world.resource(world_events()).reader());
-
-            let client_state = client_state.clone();
-            use_frame(hooks, move |app_world| {
-                if let Some(client_state) = &client_state {
-                    let mut game_state = client_state.game_state.lock();
-                    // Pipe events from app world to game world
-                    for (_, event) in world_event_reader
-                        .lock()
-                        .iter(app_world.resource(world_events()))
-                    {
-                        game_state
-                            .world
-                            .resource_mut(world_events())
-                            .add_event(event.clone());
-                    }
-
-                    game_state.on_frame(&gpu, &render_target.0);
-                } else {
-                    tracing::debug!("No game state");
-                }
-            });
-        }
+        use_game_logic(
+            hooks,
+            client_state.clone(),
+            gpu.clone(),
+            render_target.clone(),
+            !background,
+        );
 
         // Set the window title to the package name
         let (window_title_state, set_window_title) = use_state(hooks, "Ambient".to_string());
-        *hooks.world.resource_mut(window_title()) = window_title_state;
+        if !background {
+            *hooks.world.resource_mut(window_title()) = window_title_state;
+        }
 
         let (err, set_error) = use_state(hooks, None);
 
@@ -154,22 +171,29 @@ impl ElementComponent for ClientView {
             let local_resources = world_instance_resources(AppResources::from_world(ui_world))
                 .with(game_screen_render_target(), render_target.0.clone());
             let task = async move {
-                let conn = open_connection(server_addr.clone(), cert.map(Certificate))
-                    .await
-                    .with_context(|| {
-                        format!("Failed to connect to endpoint \"{server_addr:?}\"")
-                    })?;
-
-                handle_connection(
-                    conn.clone(),
-                    &assets,
-                    user_id,
-                    fail_on_version_mismatch,
-                    move |args| {
+                let cert = cert.map(Certificate);
+                let mut server_addr = server_addr;
+                let mut local_server = local_server;
+                let mut attempts = 1;
+
+                loop {
+                    let set_window_title = set_window_title.clone();
+                    let systems_and_resources = systems_and_resources.clone();
+                    let local_resources = local_resources.clone();
+                    let gpu = gpu.clone();
+                    let create_rpc_registry = create_rpc_registry.clone();
+                    let on_loaded = on_loaded.clone();
+                    let set_client_state = set_client_state.clone();
+
+                    let on_connected = move |args: OnConnectionState| -> anyhow::Result<(
+                        SharedClientGameState,
+                        CleanupFunc,
+                    )> {
                         let OnConnectionState {
                             assets,
                             user_id,
                             main_package_name,
+                            transport,
                         } = args;
 
                         set_window_title(main_package_name.to_string());
@@ -190,7 +214,7 @@ impl ElementComponent for ClientView {
 
                         // Create a handle for the game client
                         let client_state = ClientState::new(
-                            Arc::new(conn.clone()),
+                            transport,
                             Arc::new(create_rpc_registry()),
                             Arc::new(Mutex::new(game_state)),
                             user_id.into(),
@@ -217,11 +241,68 @@ impl ElementComponent for ClientView {
                         set_client_state(Some(client_state.clone()));
 
                         Ok((game_state.clone(), cleanup))
-                    },
-                    // game_state,
-                    control_rx,
-                )
-                .await?;
+                    };
+
+                    let migration = match local_server.take() {
+                        Some(local_server) => {
+                            tracing::debug!("Connecting to the server in this process");
+                            handle_local_connection(
+                                local_server.connect()?,
+                                &assets,
+                                user_id.clone(),
+                                spectator,
+                                fail_on_version_mismatch,
+                                on_connected,
+                                control_rx.clone(),
+                            )
+                            .await?
+                        }
+                        None => {
+                            let conn = open_connection_with_retries(
+                                server_addr.clone(),
+                                cert.clone(),
+                                attempts,
+                                network_conditions,
+                            )
+                            .await
+                            .with_context(|| {
+                                format!("Failed to connect to endpoint \"{server_addr:?}\"")
+                            })?;
+
+                            handle_connection(
+                                conn,
+                                &assets,
+                                user_id.clone(),
+                                spectator,
+                                fail_on_version_mismatch,
+                                on_connected,
+                                control_rx.clone(),
+                            )
+                            .await?
+                        }
+                    };
+
+                    let Some((migration, snapshot)) = migration else {
+                        break;
+                    };
+                    set_client_state(None);
+
+                    server_addr = match snapshot {
+                        Some(snapshot) => {
+                            let on_host_migration = on_host_migration
+                                .as_ref()
+                                .context("This client cannot take over as the host")?;
+                            on_host_migration(snapshot, migration.addr.port()).await?;
+                            ResolvedAddr::localhost_with_port(migration.addr.port())
+                        }
+                        None => ResolvedAddr {
+                            host_name: migration.host_name,
+                            addr: migration.addr,
+                        },
+                    };
+                    // The new host may still be starting
+                    attempts = MIGRATION_CONNECT_ATTEMPTS;
+                }
 
                 Ok(()) as anyhow::Result<()>
             };
@@ -232,14 +313,22 @@ impl ElementComponent for ClientView {
                         tracing::info!("Client disconnected");
                     }
                     Err(err) => {
-                        if let Some(err) = err.downcast_ref::<NetworkError>() {
-                            if let NetworkError::ConnectionClosed = err {
+                        match err.downcast_ref::<NetworkError>() {
+                            Some(NetworkError::ConnectionClosed) => {
                                 tracing::info!("Connection closed by peer");
-                            } else {
-                                tracing::error!("Network error: {:?}", err);
                             }
-                        } else {
-                            tracing::error!("Game failed: {:?}", err);
+                            Some(NetworkError::ConnectionRejected(reason)) => {
+                                tracing::info!("Connection rejected: {reason}");
+                                set_error(Some(reason.to_string()));
+                                return;
+                            }
+                            Some(NetworkError::VersionMismatch(mismatch)) => {
+                                tracing::info!("Incompatible server: {mismatch}");
+                                set_error(Some(mismatch.to_string()));
+                                return;
+                            }
+                            Some(err) => tracing::error!("Network error: {:?}", err),
+                            None => tracing::error!("Game failed: {:?}", err),
                         }
                         set_error(Some(format!("{err:?}")));
                     }
@@ -248,17 +337,24 @@ impl ElementComponent for ClientView {
         });
 
         if let Some(err) = err {
+            if background {
+                return Element::new();
+            }
             return Dock(vec![Text::el("Error").header_style(), Text::el(err)]).el();
         }
 
         if let Some(client_state) = &client_state {
             // Provide the context
             provide_context(hooks, || client_state.clone());
-            hooks
-                .world
-                .add_resource(crate::client::client_state(), Some(client_state.clone()));
+            if !background {
+                hooks
+                    .world
+                    .add_resource(crate::client::client_state(), Some(client_state.clone()));
+            }
 
             inner
+        } else if background {
+            Element::new()
         } else {
             Centered(vec![FlowColumn::el([FlowRow::el([
                 Text::el("Connecting"),
@@ -269,22 +365,62 @@ impl ElementComponent for ClientView {
     }
 }
 
+/// Runs a frame of the world of `client_state` for each frame of the UI world, once it is set.
+///
+/// The events of the UI world, such as input, are passed on to the world if `forward_events` is
+/// set.
+pub(crate) fn use_game_logic(
+    hooks: &mut Hooks,
+    client_state: Option<ClientState>,
+    gpu: Arc<Gpu>,
+    render_target: GameClientRenderTarget,
+    forward_events: bool,
+) {
+    let world_event_reader = Mutex::new(hooks.world.resource(world_events()).reader());
+
+    use_frame(hooks, move |app_world| {
+        if let Some(client_state) = &client_state {
+            let mut game_state = client_state.game_state.lock();
+            // Pipe events from app world to game world. The events are read even if they are not
+            // forwarded, so that the world does not receive old input once they are
+            for (_, event) in world_event_reader
+                .lock()
+                .iter(app_world.resource(world_events()))
+            {
+                if !forward_events {
+                    continue;
+                }
+                game_state
+                    .world
+                    .resource_mut(world_events())
+                    .add_event(event.clone());
+            }
+
+            game_state.on_frame(&gpu, &render_target.0);
+        } else {
+            tracing::debug!("No game state");
+        }
+    });
+}
+
 struct OnConnectionState<'a> {
     assets: &'a AssetCache,
     user_id: &'a str,
     main_package_name: &'a str,
+    transport: Arc<dyn NetworkTransport>,
 }
 
 async fn handle_connection(
     conn: quinn::Connection,
     assets: &AssetCache,
     user_id: String,
+    spectator: bool,
     fail_on_version_mismatch: bool,
     mut on_loaded: impl FnMut(OnConnectionState) -> anyhow::Result<(SharedClientGameState, CleanupFunc)>
         + Send
         + Sync,
     control_rx: flume::Receiver<Control>,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Option<(HostMigration, Option<HostSnapshot>)>> {
     let mut request_send = FramedSendStream::new(conn.open_uni().await?);
 
     // Accept the diff and stat stream
@@ -293,9 +429,12 @@ async fn handle_connection(
     // Send a connection request
     tracing::info!("Attempting to connect using {user_id:?}");
 
-    request_send
-        .send(ClientRequest::Connect(user_id.clone()))
-        .await?;
+    let request = if spectator {
+        ClientRequest::ConnectSpectator(user_id.clone())
+    } else {
+        ClientRequest::Connect(user_id.clone())
+    };
+    request_send.send(request).await?;
 
     let mut client = ClientProtoState::Pending(user_id.clone());
 
@@ -309,28 +448,48 @@ async fn handle_connection(
 
     assert!(ContentBaseUrlKey.exists(assets));
 
-    let main_package_name = if let ClientProtoState::Connected(connected) = &client {
-        connected.main_package_name.clone()
-    } else {
-        tracing::warn!("Connection failed or was denied");
-        return Ok(());
-    };
+    let (main_package_name, streams_assets) =
+        if let ClientProtoState::Connected(connected) = &client {
+            (
+                connected.main_package_name.clone(),
+                connected.streams_assets,
+            )
+        } else {
+            tracing::warn!("Connection failed or was denied");
+            return Ok(None);
+        };
+
+    // Assets served by the server are fetched over this connection, rather than over HTTP
+    let asset_stream = streams_assets.then(|| {
+        Arc::new(ClientAssetStream::new(
+            Arc::new(conn.clone()),
+            &ContentBaseUrlKey.get(assets),
+        )) as Arc<dyn AssetStream>
+    });
+    AssetStreamKey.insert(assets, asset_stream);
 
     tracing::info!("Connection successfully established");
 
     // Create the game client
 
     let mut diff_stream = RawFramedRecvStream::new(conn.accept_uni().await?);
+    let compression = Compression::of_connection(&conn);
+    tracing::debug!(?compression, "Using compression");
 
     let (shared_client_state, cleanup) = on_loaded(OnConnectionState {
         assets,
         user_id: &user_id,
         main_package_name: &main_package_name,
+        transport: Arc::new(conn.clone()),
     })?;
 
-    let on_disconnect = move || {
-        tracing::debug!("Running connection cleanup");
-        cleanup()
+    let on_disconnect = {
+        let assets = assets.clone();
+        move || {
+            tracing::debug!("Running connection cleanup");
+            AssetStreamKey.insert(&assets, None);
+            cleanup()
+        }
     };
 
     scopeguard::defer!(on_disconnect());
@@ -371,21 +530,169 @@ async fn handle_connection(
             }
 
             Ok(datagram) = conn.read_datagram() => {
+                let datagram = compression.decode_datagram(datagram)?;
                 connected.process_datagram(&shared_client_state, datagram)?;
             }
             Ok((send, recv)) = conn.accept_bi() => {
-                connected.process_bi(&shared_client_state, send, recv);
+                connected.process_bi(&shared_client_state, Box::pin(send), Box::pin(recv));
             }
             Ok(recv) = conn.accept_uni() => {
-                connected.process_uni(&shared_client_state, recv);
+                connected.process_uni(&shared_client_state, Box::pin(recv));
             }
             Some(diff) = diff_stream.next() => {
-                connected.process_diff(&shared_client_state, diff?)?;
+                connected.process_diff(&shared_client_state, compression.decompress(diff?)?)?;
+            }
+        }
+    }
+
+    let Some(migration) = client.into_migration() else {
+        return Ok(None);
+    };
+    // The successor restores the world as it was last synchronized
+    let snapshot = (migration.successor == user_id)
+        .then(|| HostSnapshot::capture(&shared_client_state.lock().world));
+
+    Ok(Some((migration, snapshot)))
+}
+
+/// Runs the same protocol as [handle_connection] over an in-memory connection to a server in the
+/// same process. There are no network statistics to report, and world diffs are received as they
+/// are rather than over a stream.
+async fn handle_local_connection(
+    conn: LocalConnection,
+    assets: &AssetCache,
+    user_id: String,
+    spectator: bool,
+    fail_on_version_mismatch: bool,
+    mut on_loaded: impl FnMut(OnConnectionState) -> anyhow::Result<(SharedClientGameState, CleanupFunc)>
+        + Send
+        + Sync,
+    control_rx: flume::Receiver<Control>,
+) -> anyhow::Result<Option<(HostMigration, Option<HostSnapshot>)>> {
+    let mut request_send = FramedSendStream::new(conn.open_uni()?);
+
+    tracing::info!("Attempting to connect using {user_id:?}");
+
+    let request = if spectator {
+        ClientRequest::ConnectSpectator(user_id.clone())
+    } else {
+        ClientRequest::Connect(user_id.clone())
+    };
+    request_send.send(request).await?;
+
+    let mut client = ClientProtoState::Pending(user_id.clone());
+
+    let mut push_recv = FramedRecvStream::new(conn.accept_uni().await?);
+
+    while client.is_pending() {
+        if let Some(frame) = push_recv.next().await {
+            client.process_push(assets, fail_on_version_mismatch, frame?)?;
+        }
+    }
+
+    assert!(ContentBaseUrlKey.exists(assets));
+
+    let (main_package_name, streams_assets) =
+        if let ClientProtoState::Connected(connected) = &client {
+            (
+                connected.main_package_name.clone(),
+                connected.streams_assets,
+            )
+        } else {
+            tracing::warn!("Connection failed or was denied");
+            return Ok(None);
+        };
+
+    let asset_stream = streams_assets.then(|| {
+        Arc::new(ClientAssetStream::new(
+            Arc::new(conn.clone()),
+            &ContentBaseUrlKey.get(assets),
+        )) as Arc<dyn AssetStream>
+    });
+    AssetStreamKey.insert(assets, asset_stream);
+
+    tracing::info!("Connection successfully established");
+
+    let (shared_client_state, cleanup) = on_loaded(OnConnectionState {
+        assets,
+        user_id: &user_id,
+        main_package_name: &main_package_name,
+        transport: Arc::new(conn.clone()),
+    })?;
+
+    let on_disconnect = {
+        let assets = assets.clone();
+        move || {
+            tracing::debug!("Running connection cleanup");
+            AssetStreamKey.insert(&assets, None);
+            cleanup()
+        }
+    };
+
+    scopeguard::defer!(on_disconnect());
+
+    let mut control_rx = control_rx.into_stream();
+
+    while let ClientProtoState::Connected(connected) = &mut client {
+        tokio::select! {
+            Some(frame) = push_recv.next() => {
+                client.process_push(assets, fail_on_version_mismatch, frame?)?;
+            }
+            Some(control) = control_rx.next() => {
+                match control {
+                    Control::Disconnect => {
+                        tracing::debug!("Disconnecting manually");
+                        request_send.send(ClientRequest::Disconnect).await?;
+                    }
+                }
+            }
+            datagram = conn.read_datagram() => {
+                connected.process_datagram(&shared_client_state, datagram?)?;
+            }
+            stream = conn.accept_bi() => {
+                let (send, recv) = stream?;
+                connected.process_bi(&shared_client_state, Box::pin(send), Box::pin(recv));
+            }
+            stream = conn.accept_uni() => {
+                connected.process_uni(&shared_client_state, Box::pin(stream?));
+            }
+            diff = conn.read_diff() => {
+                connected.process_local_diff(&shared_client_state, diff?);
             }
         }
     }
 
-    Ok(())
+    let Some(migration) = client.into_migration() else {
+        return Ok(None);
+    };
+    let snapshot = (migration.successor == user_id)
+        .then(|| HostSnapshot::capture(&shared_client_state.lock().world));
+
+    Ok(Some((migration, snapshot)))
+}
+
+/// How many times to try to connect to the new host during host migration, as it may still be
+/// starting.
+const MIGRATION_CONNECT_ATTEMPTS: u32 = 30;
+
+async fn open_connection_with_retries(
+    server_addr: ResolvedAddr,
+    cert: Option<Certificate>,
+    attempts: u32,
+    conditions: NetworkConditions,
+) -> anyhow::Result<Connection> {
+    let mut attempt = 1;
+    loop {
+        match open_connection(server_addr.clone(), cert.clone(), conditions).await {
+            Ok(conn) => return Ok(conn),
+            Err(err) if attempt < attempts => {
+                tracing::debug!("Failed to connect (attempt {attempt}/{attempts}): {err:?}");
+                attempt += 1;
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            Err(err) => return Err(err),
+        }
+    }
 }
 
 /// Connnect to the server endpoint.
@@ -393,11 +700,15 @@ async fn handle_connection(
 async fn open_connection(
     server_addr: ResolvedAddr,
     cert: Option<Certificate>,
+    conditions: NetworkConditions,
 ) -> anyhow::Result<Connection> {
     tracing::debug!("Connecting to world instance: {server_addr:?}");
 
-    let endpoint =
-        create_client_endpoint_random_port(cert).context("Failed to create client endpoint")?;
+    // Compressing the traffic to a server on the same machine, such as the server of a
+    // single-player game, costs more time than it saves
+    let compress = !server_addr.addr.ip().is_loopback();
+    let endpoint = create_client_endpoint_random_port(cert, conditions, compress)
+        .context("Failed to create client endpoint")?;
 
     tracing::debug!("Got endpoint");
     let conn = endpoint
@@ -408,7 +719,13 @@ async fn open_connection(
     Ok(conn)
 }
 
-pub fn create_client_endpoint_random_port(cert: Option<Certificate>) -> anyhow::Result<Endpoint> {
+/// Creates a client endpoint on a random port, which simulates the given network `conditions`
+/// unless they are ideal. The endpoint only offers compression to servers if `compress` is set.
+pub fn create_client_endpoint_random_port(
+    cert: Option<Certificate>,
+    conditions: NetworkConditions,
+    compress: bool,
+) -> anyhow::Result<Endpoint> {
     let mut roots = load_root_certs();
 
     if let Some(cert) = cert {
@@ -425,7 +742,13 @@ pub fn create_client_endpoint_random_port(cert: Option<Certificate>) -> anyhow::
 
         let client_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), client_port);
 
-        if let Ok(mut endpoint) = Endpoint::client(client_addr) {
+        let endpoint = if conditions.is_ideal() {
+            Endpoint::client(client_addr)
+        } else {
+            simulation::client_endpoint(client_addr, conditions)
+        };
+
+        if let Ok(mut endpoint) = endpoint {
             let mut tls_config = rustls::ClientConfig::builder()
                 .with_safe_default_cipher_suites()
                 .with_safe_default_kx_groups()
@@ -435,7 +758,11 @@ pub fn create_client_endpoint_random_port(cert: Option<Certificate>) -> anyhow::
                 .with_no_client_auth();
 
             // tls_config.enable_early_data = true;
-            tls_config.alpn_protocols = vec!["ambient-02".into()];
+            tls_config.alpn_protocols = if compress {
+                compression::supported_alpn_protocols()
+            } else {
+                vec![compression::ALPN.to_vec()]
+            };
 
             let mut transport = TransportConfig::default();
             transport.keep_alive_interval(Some(Duration::from_secs_f32(1.)));
@@ -1,8 +1,10 @@
+use std::net::SocketAddr;
+
 use ambient_proxy::client::ProxiedConnection;
 use bytes::Bytes;
 use quinn::{Connection, RecvStream, SendStream};
 
-use crate::NetworkError;
+use crate::{compression::Compression, NetworkError};
 
 /// Incoming quinn connection from the client that can be either direct or proxied
 #[derive(Debug, Clone)]
@@ -24,6 +26,22 @@ impl From<ProxiedConnection> for ConnectionKind {
 }
 
 impl ConnectionKind {
+    /// The compression negotiated for this connection. Proxied connections are not compressed.
+    pub fn compression(&self) -> Compression {
+        match self {
+            ConnectionKind::Direct(conn) => Compression::of_connection(conn),
+            ConnectionKind::Proxied(_) => Compression::None,
+        }
+    }
+
+    /// The address of the client, if it is connected directly.
+    pub fn remote_address(&self) -> Option<SocketAddr> {
+        match self {
+            ConnectionKind::Direct(conn) => Some(conn.remote_address()),
+            ConnectionKind::Proxied(_) => None,
+        }
+    }
+
     pub fn is_local(&self) -> bool {
         match self {
             ConnectionKind::Direct(conn) => conn.remote_address().ip().is_loopback(),
@@ -1,10 +1,10 @@
-use bytes::{BufMut, Bytes, BytesMut};
+use bytes::{BufMut, Bytes};
 use futures::future::BoxFuture;
 use h3::quic::BidiStream;
 use h3_webtransport::server::WebTransportSession;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-use crate::{client::NetworkTransport, NetworkError, MAX_FRAME_SIZE};
+use crate::{client::NetworkTransport, compression::Compression, NetworkError, MAX_FRAME_SIZE};
 
 impl NetworkTransport for WebTransportSession<h3_quinn::Connection, Bytes> {
     fn request_bi(&self, id: u32, data: Bytes) -> BoxFuture<Result<Bytes, NetworkError>> {
@@ -81,11 +81,8 @@ impl NetworkTransport for quinn::Connection {
     }
 
     fn send_datagram(&self, id: u32, data: Bytes) -> BoxFuture<Result<(), NetworkError>> {
-        let mut bytes = bytes::BytesMut::with_capacity(4 + data.len());
-        bytes.put_u32(id);
-        bytes.put(data);
-
-        let res = self.send_datagram(bytes.freeze()).map_err(Into::into);
+        let datagram = Compression::of_connection(self).encode_datagram(id, data);
+        let res = self.send_datagram(datagram).map_err(Into::into);
 
         Box::pin(futures::future::ready(res))
     }
@@ -119,11 +116,8 @@ impl NetworkTransport for crate::native::client_connection::ConnectionKind {
     }
 
     fn send_datagram(&self, id: u32, data: Bytes) -> BoxFuture<Result<(), NetworkError>> {
-        let mut bytes = BytesMut::with_capacity(4 + data.len());
-        bytes.put_u32(id);
-        bytes.put(data);
-
-        let res = self.send_datagram(bytes.freeze()).map_err(Into::into);
+        let datagram = self.compression().encode_datagram(id, data);
+        let res = self.send_datagram(datagram).map_err(Into::into);
 
         Box::pin(futures::future::ready(res))
     }
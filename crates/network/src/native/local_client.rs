@@ -0,0 +1,132 @@
+//! A client world that is not connected to a server, such as the world of a main menu.
+use std::sync::Arc;
+
+use ambient_app::{world_instance_resources, AppResources};
+use ambient_core::{asset_cache, gpu};
+use ambient_ecs::{Entity, SystemGroup};
+use ambient_element::{
+    consume_context, provide_context, use_spawn, use_state, Element, ElementComponent, Hooks,
+};
+use ambient_native_std::Cb;
+use ambient_renderer::RenderTarget;
+use ambient_rpc::RpcRegistry;
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use glam::uvec2;
+use parking_lot::Mutex;
+
+use crate::{
+    client::{ClientState, GameClientRenderTarget, LoadedFunc, NetworkTransport},
+    client_game_state::{game_screen_render_target, ClientGameState},
+    native::client::use_game_logic,
+    NetworkError,
+};
+
+/// Runs a client world without connecting to a server. The world has the same systems and
+/// resources as the world of a [ClientView](super::client::ClientView), and is provided to
+/// `inner` through a [ClientState] whose requests to the server always fail.
+#[derive(Debug, Clone)]
+pub struct LocalClientView {
+    pub user_id: String,
+    pub systems_and_resources: Cb<dyn Fn() -> (SystemGroup, Entity) + Sync + Send>,
+    pub on_loaded: LoadedFunc,
+    pub inner: Element,
+}
+
+impl ElementComponent for LocalClientView {
+    fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
+        let Self {
+            user_id,
+            systems_and_resources,
+            on_loaded,
+            inner,
+        } = *self;
+
+        let gpu = hooks.world.resource(gpu()).clone();
+
+        provide_context(hooks, || {
+            GameClientRenderTarget(Arc::new(RenderTarget::new(&gpu, uvec2(1, 1), None)))
+        });
+
+        let (render_target, _) = consume_context::<GameClientRenderTarget>(hooks).unwrap();
+
+        let (client_state, set_client_state) = use_state(hooks, None as Option<ClientState>);
+
+        use_game_logic(
+            hooks,
+            client_state.clone(),
+            gpu.clone(),
+            render_target.clone(),
+            true,
+        );
+
+        use_spawn(hooks, move |ui_world| {
+            let assets = ui_world.resource(asset_cache()).clone();
+            let (systems, resources) = systems_and_resources();
+            let resources = world_instance_resources(AppResources::from_world(ui_world))
+                .with(game_screen_render_target(), render_target.0.clone())
+                .with(ambient_core::player::local_user_id(), user_id.clone())
+                .with(crate::client::is_local_world(), ())
+                .with_merge(resources);
+
+            let game_state =
+                ClientGameState::new(&gpu, assets, user_id.clone(), systems, resources);
+            let client_state = ClientState::new(
+                Arc::new(DisconnectedTransport),
+                Arc::new(RpcRegistry::new()),
+                Arc::new(Mutex::new(game_state)),
+                user_id,
+            );
+
+            let cleanup = {
+                let game_state = &mut client_state.game_state.lock();
+                game_state
+                    .world
+                    .add_resource(crate::client::client_state(), Some(client_state.clone()));
+
+                match on_loaded(&client_state, game_state) {
+                    Ok(cleanup) => Some(cleanup),
+                    Err(err) => {
+                        tracing::error!("Failed to load the local world: {err:?}");
+                        None
+                    }
+                }
+            };
+
+            set_client_state(Some(client_state));
+
+            move |_| {
+                if let Some(cleanup) = cleanup {
+                    cleanup();
+                }
+            }
+        });
+
+        let Some(client_state) = client_state else {
+            return Element::new();
+        };
+
+        provide_context(hooks, || client_state.clone());
+        hooks
+            .world
+            .add_resource(crate::client::client_state(), Some(client_state));
+
+        inner
+    }
+}
+
+/// The transport of a [LocalClientView], which has no server to send requests to.
+struct DisconnectedTransport;
+impl NetworkTransport for DisconnectedTransport {
+    fn request_bi(&self, _id: u32, _data: Bytes) -> BoxFuture<Result<Bytes, NetworkError>> {
+        Box::pin(async { Err(NetworkError::ConnectionClosed) })
+    }
+
+    fn request_uni(&self, _id: u32, _data: Bytes) -> BoxFuture<Result<(), NetworkError>> {
+        Box::pin(async { Err(NetworkError::ConnectionClosed) })
+    }
+
+    fn send_datagram(&self, _id: u32, _data: Bytes) -> BoxFuture<Result<(), NetworkError>> {
+        Box::pin(async { Err(NetworkError::ConnectionClosed) })
+    }
+}
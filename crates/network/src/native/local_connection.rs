@@ -0,0 +1,256 @@
+//! In-memory connections between a client and a server running in the same process, such as the
+//! server of a single-player game.
+//!
+//! A [LocalConnection] carries the same streams and datagrams as a QUIC connection, but over
+//! in-process pipes: nothing is encrypted, compressed or subject to congestion control. World
+//! diffs are passed to the client as they are, rather than being serialized.
+use ambient_ecs::FrozenWorldDiff;
+use bytes::{BufMut, Bytes, BytesMut};
+use futures::future::BoxFuture;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+use crate::{client::NetworkTransport, NetworkError, MAX_FRAME_SIZE};
+
+/// The size of the buffer of each in-memory stream.
+const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
+/// One side of an in-memory connection; see [LocalConnection::pair].
+#[derive(Debug, Clone)]
+pub struct LocalConnection {
+    uni_tx: flume::Sender<DuplexStream>,
+    uni_rx: flume::Receiver<DuplexStream>,
+    bi_tx: flume::Sender<(DuplexStream, DuplexStream)>,
+    bi_rx: flume::Receiver<(DuplexStream, DuplexStream)>,
+    datagram_tx: flume::Sender<Bytes>,
+    datagram_rx: flume::Receiver<Bytes>,
+    diff_tx: flume::Sender<FrozenWorldDiff>,
+    diff_rx: flume::Receiver<FrozenWorldDiff>,
+}
+
+impl LocalConnection {
+    /// Creates both sides of a connection. Each side accepts what the other one opens or sends.
+    pub fn pair() -> (Self, Self) {
+        let (uni_tx_a, uni_rx_b) = flume::unbounded();
+        let (uni_tx_b, uni_rx_a) = flume::unbounded();
+        let (bi_tx_a, bi_rx_b) = flume::unbounded();
+        let (bi_tx_b, bi_rx_a) = flume::unbounded();
+        let (datagram_tx_a, datagram_rx_b) = flume::unbounded();
+        let (datagram_tx_b, datagram_rx_a) = flume::unbounded();
+        let (diff_tx_a, diff_rx_b) = flume::unbounded();
+        let (diff_tx_b, diff_rx_a) = flume::unbounded();
+
+        (
+            Self {
+                uni_tx: uni_tx_a,
+                uni_rx: uni_rx_a,
+                bi_tx: bi_tx_a,
+                bi_rx: bi_rx_a,
+                datagram_tx: datagram_tx_a,
+                datagram_rx: datagram_rx_a,
+                diff_tx: diff_tx_a,
+                diff_rx: diff_rx_a,
+            },
+            Self {
+                uni_tx: uni_tx_b,
+                uni_rx: uni_rx_b,
+                bi_tx: bi_tx_b,
+                bi_rx: bi_rx_b,
+                datagram_tx: datagram_tx_b,
+                datagram_rx: datagram_rx_b,
+                diff_tx: diff_tx_b,
+                diff_rx: diff_rx_b,
+            },
+        )
+    }
+
+    /// Opens a stream that the other side reads from once it accepts it.
+    pub fn open_uni(&self) -> Result<DuplexStream, NetworkError> {
+        let (send, recv) = tokio::io::duplex(STREAM_BUFFER_SIZE);
+        self.uni_tx
+            .send(recv)
+            .map_err(|_| NetworkError::ConnectionClosed)?;
+        Ok(send)
+    }
+
+    /// Opens a pair of send and receive streams.
+    pub fn open_bi(&self) -> Result<(DuplexStream, DuplexStream), NetworkError> {
+        let (send, remote_recv) = tokio::io::duplex(STREAM_BUFFER_SIZE);
+        let (remote_send, recv) = tokio::io::duplex(STREAM_BUFFER_SIZE);
+        self.bi_tx
+            .send((remote_send, remote_recv))
+            .map_err(|_| NetworkError::ConnectionClosed)?;
+        Ok((send, recv))
+    }
+
+    pub async fn accept_uni(&self) -> Result<DuplexStream, NetworkError> {
+        self.uni_rx
+            .recv_async()
+            .await
+            .map_err(|_| NetworkError::ConnectionClosed)
+    }
+
+    pub async fn accept_bi(&self) -> Result<(DuplexStream, DuplexStream), NetworkError> {
+        self.bi_rx
+            .recv_async()
+            .await
+            .map_err(|_| NetworkError::ConnectionClosed)
+    }
+
+    pub async fn read_datagram(&self) -> Result<Bytes, NetworkError> {
+        self.datagram_rx
+            .recv_async()
+            .await
+            .map_err(|_| NetworkError::ConnectionClosed)
+    }
+
+    /// The sender of the world diffs for the other side, which the server registers for its
+    /// player instead of a diff stream.
+    pub fn diff_sender(&self) -> flume::Sender<FrozenWorldDiff> {
+        self.diff_tx.clone()
+    }
+
+    /// Receives the next world diff sent by the other side.
+    pub async fn read_diff(&self) -> Result<FrozenWorldDiff, NetworkError> {
+        self.diff_rx
+            .recv_async()
+            .await
+            .map_err(|_| NetworkError::ConnectionClosed)
+    }
+}
+
+impl NetworkTransport for LocalConnection {
+    fn request_bi(&self, id: u32, data: Bytes) -> BoxFuture<Result<Bytes, NetworkError>> {
+        Box::pin(async move {
+            let (mut send, recv) = self.open_bi()?;
+
+            send.write_u32(id).await?;
+            send.write_all(&data).await?;
+
+            drop(send);
+
+            let mut buf = Vec::new();
+
+            let read = recv
+                .take(MAX_FRAME_SIZE as u64 + 1)
+                .read_to_end(&mut buf)
+                .await?;
+            if read > MAX_FRAME_SIZE {
+                return Err(NetworkError::FrameTooLarge);
+            }
+
+            Ok(buf.into())
+        })
+    }
+
+    fn request_uni(&self, id: u32, data: Bytes) -> BoxFuture<Result<(), NetworkError>> {
+        Box::pin(async move {
+            let mut send = self.open_uni()?;
+
+            send.write_u32(id).await?;
+            send.write_all(&data).await?;
+
+            Ok(())
+        })
+    }
+
+    fn send_datagram(&self, id: u32, data: Bytes) -> BoxFuture<Result<(), NetworkError>> {
+        let mut bytes = BytesMut::with_capacity(4 + data.len());
+        bytes.put_u32(id);
+        bytes.put(data);
+
+        let res = self
+            .datagram_tx
+            .send(bytes.freeze())
+            .map_err(|_| NetworkError::ConnectionClosed);
+
+        Box::pin(futures::future::ready(res))
+    }
+}
+
+/// Connects clients in the same process to a [GameServer](super::server::GameServer) in-memory.
+#[derive(Debug, Clone)]
+pub struct LocalConnector {
+    pub(crate) tx: flume::Sender<LocalConnection>,
+}
+
+impl LocalConnector {
+    /// Opens a connection to the server, returning the client's side of it.
+    pub fn connect(&self) -> Result<LocalConnection, NetworkError> {
+        let (client, server) = LocalConnection::pair();
+        self.tx
+            .send(server)
+            .map_err(|_| NetworkError::ConnectionClosed)?;
+        Ok(client)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn streams_and_datagrams() {
+        let (client, server) = LocalConnection::pair();
+
+        let mut send = client.open_uni().unwrap();
+        send.write_all(b"uni").await.unwrap();
+        drop(send);
+        let mut buf = Vec::new();
+        server
+            .accept_uni()
+            .await
+            .unwrap()
+            .read_to_end(&mut buf)
+            .await
+            .unwrap();
+        assert_eq!(buf, b"uni");
+
+        let responder = tokio::spawn({
+            let server = server.clone();
+            async move {
+                let (mut send, mut recv) = server.accept_bi().await.unwrap();
+                let id = recv.read_u32().await.unwrap();
+                let mut request = Vec::new();
+                recv.read_to_end(&mut request).await.unwrap();
+                send.write_all(format!("{id}: ").as_bytes()).await.unwrap();
+                send.write_all(&request).await.unwrap();
+            }
+        });
+        let response = client
+            .request_bi(7, Bytes::from_static(b"request"))
+            .await
+            .unwrap();
+        assert_eq!(response, "7: request");
+        responder.await.unwrap();
+
+        server
+            .send_datagram(3, Bytes::from_static(b"datagram"))
+            .await
+            .unwrap();
+        assert_eq!(
+            client.read_datagram().await.unwrap(),
+            [&3u32.to_be_bytes()[..], b"datagram"].concat()
+        );
+    }
+
+    #[tokio::test]
+    async fn closed_connections() {
+        let (client, server) = LocalConnection::pair();
+        drop(server);
+
+        assert!(client.open_uni().is_err());
+        assert!(client.accept_bi().await.is_err());
+        assert!(client.read_diff().await.is_err());
+
+        let (tx, rx) = flume::unbounded();
+        let connector = LocalConnector { tx };
+        let client = connector.connect().unwrap();
+        let server = rx.recv().unwrap();
+        server.open_uni().unwrap();
+        assert!(client.accept_uni().await.is_ok());
+
+        // The server has stopped accepting connections
+        drop(rx);
+        assert!(connector.connect().is_err());
+    }
+}
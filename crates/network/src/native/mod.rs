@@ -4,7 +4,10 @@
 pub mod client;
 pub mod client_connection;
 pub mod common;
+pub mod local_client;
+pub mod local_connection;
 pub mod server;
+pub mod simulation;
 mod webtransport;
 
 #[cfg(feature = "tls-native-roots")]
@@ -6,7 +6,7 @@ use std::{
     time::Duration,
 };
 
-use ambient_core::{asset_cache, FIXED_SERVER_TICK_TIME};
+use ambient_core::asset_cache;
 use ambient_ecs::{
     generated::network::components::no_sync, ArchetypeFilter, ComponentDesc, System, SystemGroup,
     World, WorldStream, WorldStreamCompEvent, WorldStreamFilter,
@@ -29,16 +29,22 @@ use tokio::time::{interval, MissedTickBehavior};
 use uuid::Uuid;
 
 use crate::{
+    compression::{self, Compression},
+    frame_budget::FrameBudgetReporter,
+    memory_report::MemoryReporter,
     native::{
-        client_connection::ConnectionKind, load_root_certs, webtransport::handle_h3_connection,
+        client_connection::ConnectionKind,
+        load_root_certs,
+        local_connection::{LocalConnection, LocalConnector},
+        webtransport::handle_h3_connection,
     },
     proto::{
         server::{handle_diffs, ConnectionData, ServerProtoState},
         ServerInfo, ServerPush,
     },
     server::{
-        server_stats, ForkingEvent, ProxySettings, ServerState, SharedServerState, ShutdownEvent,
-        WorldInstance, MAIN_INSTANCE_ID,
+        server_stats, world_instance_id, ForkingEvent, ProxySettings, ServerState,
+        SharedServerState, ShutdownEvent, WorldInstance, MAIN_INSTANCE_ID,
     },
     stream::{FramedRecvStream, FramedSendStream},
     ServerWorldExt,
@@ -56,6 +62,10 @@ pub struct GameServer {
     /// Shuts down the server if there are no players
     pub inactivity_shutdown: Option<Duration>,
     proxy_settings: Option<ProxySettings>,
+    local_connections: (
+        flume::Sender<LocalConnection>,
+        flume::Receiver<LocalConnection>,
+    ),
 }
 
 impl GameServer {
@@ -72,6 +82,7 @@ impl GameServer {
             endpoint,
             inactivity_shutdown,
             proxy_settings,
+            local_connections: flume::unbounded(),
         })
     }
 
@@ -115,10 +126,12 @@ impl GameServer {
         let Self {
             endpoint,
             proxy_settings,
+            local_connections: (_, local_connections),
             ..
         } = self;
 
         let assets = world.resource(asset_cache()).clone();
+        world.add_resource(world_instance_id(), MAIN_INSTANCE_ID.to_string());
         let world_stream_filter =
             WorldStreamFilter::new(ArchetypeFilter::new().excl(no_sync()), is_sync_component);
         let state = Arc::new(Mutex::new(ServerState::new(
@@ -140,7 +153,11 @@ impl GameServer {
         on_server_state_created(state.clone());
 
         let mut fps_counter = FpsCounter::new();
-        let mut sim_interval = interval(FIXED_SERVER_TICK_TIME);
+        let tick_time = state.lock().tick_time;
+        let mut frame_budget_reporter = FrameBudgetReporter::new(tick_time);
+        let mut memory_reporter = MemoryReporter::new();
+        tracing::debug!(?tick_time, "Starting simulation");
+        let mut sim_interval = interval(tick_time);
         sim_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
         let mut inactivity_interval = interval(Duration::from_secs_f32(5.));
@@ -172,14 +189,22 @@ impl GameServer {
                     let fut = resolve_connection(conn, state.clone(), world_stream_filter.clone(), ServerBaseUrlKey.get(&assets));
                     tokio::spawn(async move {  log_result!(fut.await) });
                 }
+                Ok(conn) = local_connections.recv_async() => {
+                    let fut = handle_local_connection(conn, state.clone(), world_stream_filter.clone(), ServerBaseUrlKey.get(&assets));
+                    tokio::spawn(async move {  log_result!(fut.await) });
+                }
                 _ = sim_interval.tick() => {
                     fps_counter.frame_start();
                     let mut state = state.lock();
                     tokio::task::block_in_place(|| {
                         profiling::finish_frame!();
                         profiling::scope!("sim_tick");
+                        let tick_start = Instant::now();
                         state.step();
                         state.broadcast_diffs();
+                        let budget = state.take_frame_budget();
+                        frame_budget_reporter.tick(tick_start.elapsed(), budget);
+                        memory_reporter.tick(&state);
                         if let Some(sample) = fps_counter.frame_end() {
                             for instance in state.instances.values_mut() {
                                 let id = instance.world.synced_resource_entity().unwrap();
@@ -217,6 +242,14 @@ impl GameServer {
         state
     }
 
+    /// Returns a connector for clients in the same process, which connect to this server
+    /// in-memory rather than over QUIC.
+    pub fn local_connector(&self) -> LocalConnector {
+        LocalConnector {
+            tx: self.local_connections.0.clone(),
+        }
+    }
+
     /// Returns the local socket address of the endpoint
     pub fn local_addr(&self) -> SocketAddr {
         self.endpoint
@@ -261,7 +294,7 @@ async fn resolve_connection(
     };
 
     tracing::debug!("Accepted connection");
-    if protocol == b"ambient-02" {
+    if protocol == compression::ALPN || protocol == compression::ALPN_ZSTD {
         handle_quinn_connection(
             conn.into(),
             state.clone(),
@@ -318,6 +351,7 @@ async fn handle_quinn_connection(
         diff_tx: diffs_tx,
         connection_id: Uuid::new_v4(),
         world_stream_filter,
+        remote_addr: conn.remote_address(),
     };
 
     while server.is_pending_connection() {
@@ -326,9 +360,19 @@ async fn handle_quinn_connection(
         }
     }
 
+    if let ServerProtoState::Rejected(reason) = &server {
+        tracing::info!(?reason, "Connection rejected");
+        push_send.send(ServerPush::Rejected(reason.clone())).await?;
+        return Ok(());
+    }
+
+    let compression = conn.compression();
+    tracing::debug!(?compression, "Using compression");
+
     tokio::spawn(handle_diffs(
         FramedSendStream::new(conn.open_uni().await?),
         diffs_rx,
+        compression,
     ));
 
     let mut server = scopeguard::guard(server, |mut server| {
@@ -339,6 +383,86 @@ async fn handle_quinn_connection(
     });
 
     // Before a connection has been established, only process the control stream
+    while let ServerProtoState::Connected(connected) = &mut *server {
+        tokio::select! {
+            Some(frame) = request_recv.next() => {
+                server.process_control(&data, frame?)?;
+            }
+            stream = conn.accept_uni() => {
+                connected.process_uni(&data, stream?);
+            }
+            stream = conn.accept_bi() => {
+                let (send, recv) = stream?;
+                connected.process_bi(&data, send, recv);
+            }
+            datagram = conn.read_datagram() => {
+                connected.process_datagram(&data, compression.decode_datagram(datagram?)?)?;
+            }
+            Some(msg) = connected.control_rx.next() => {
+                push_send.send(&msg).await?;
+                if let ServerPush::Rejected(reason) = msg {
+                    tracing::info!(?reason, "Disconnecting client");
+                    server.process_disconnect(&data);
+                }
+            }
+        }
+    }
+
+    tracing::info!("Client disconnected");
+
+    Ok(())
+}
+
+/// Setup the protocol and enter the update loop for a client in the same process, which is
+/// connected in-memory
+#[tracing::instrument(level = "info", skip_all)]
+async fn handle_local_connection(
+    conn: LocalConnection,
+    state: SharedServerState,
+    world_stream_filter: WorldStreamFilter,
+    content_base_url: AbsAssetUrl,
+) -> anyhow::Result<()> {
+    tracing::debug!("Handling local server connection");
+
+    let server_info = ServerInfo::new(&mut state.lock(), content_base_url);
+
+    let mut server = ServerProtoState::default();
+
+    let mut request_recv = FramedRecvStream::new(conn.accept_uni().await?);
+    let mut push_send = FramedSendStream::new(conn.open_uni()?);
+
+    // Send who we are
+    push_send.send(ServerPush::ServerInfo(server_info)).await?;
+
+    // The world diffs are passed on to the client as they are, so no diff stream is opened
+    let data = ConnectionData {
+        conn: Arc::new(conn.clone()),
+        state,
+        diff_tx: conn.diff_sender(),
+        connection_id: Uuid::new_v4(),
+        world_stream_filter,
+        remote_addr: None,
+    };
+
+    while server.is_pending_connection() {
+        if let Some(frame) = request_recv.next().await {
+            server.process_control(&data, frame?)?;
+        }
+    }
+
+    if let ServerProtoState::Rejected(reason) = &server {
+        tracing::info!(?reason, "Connection rejected");
+        push_send.send(ServerPush::Rejected(reason.clone())).await?;
+        return Ok(());
+    }
+
+    let mut server = scopeguard::guard(server, |mut server| {
+        if !server.is_disconnected() {
+            tracing::info!("Connection closed abruptly from {server:?}");
+            server.process_disconnect(&data);
+        }
+    });
+
     while let ServerProtoState::Connected(connected) = &mut *server {
         tokio::select! {
             Some(frame) = request_recv.next() => {
@@ -356,6 +480,10 @@ async fn handle_quinn_connection(
             }
             Some(msg) = connected.control_rx.next() => {
                 push_send.send(&msg).await?;
+                if let ServerPush::Rejected(reason) = msg {
+                    tracing::info!(?reason, "Disconnecting client");
+                    server.process_disconnect(&data);
+                }
             }
         }
     }
@@ -474,14 +602,15 @@ fn create_server(server_addr: SocketAddr, crypto: &Crypto) -> anyhow::Result<End
         )?;
 
     tls_config.max_early_data_size = u32::MAX;
-    let alpn: Vec<Vec<u8>> = vec![
+    let mut alpn: Vec<Vec<u8>> = vec![
         b"h3".to_vec(),
         b"h3-32".to_vec(),
         b"h3-31".to_vec(),
         b"h3-30".to_vec(),
         b"h3-29".to_vec(),
-        b"ambient-02".to_vec(),
     ];
+    // The server's order of preference decides, so compression is used if the client supports it
+    alpn.extend(compression::supported_alpn_protocols());
 
     tls_config.alpn_protocols = alpn;
 
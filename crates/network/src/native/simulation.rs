@@ -0,0 +1,233 @@
+//! Simulation of bad network conditions on the client, to test how a package behaves on a slow or
+//! unreliable network without needing one.
+//!
+//! The simulation sits between quinn and the UDP socket, so it affects all the traffic of the
+//! connection, and QUIC recovers from the lost packets like it would on a real network.
+use std::{
+    io::{self, IoSliceMut},
+    net::SocketAddr,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use parking_lot::Mutex;
+use quinn::{
+    udp::{RecvMeta, Transmit, UdpState},
+    AsyncUdpSocket, Endpoint, EndpointConfig,
+};
+use rand::Rng;
+use tokio::{
+    net::UdpSocket,
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    time::Instant,
+};
+
+/// The network conditions to simulate.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NetworkConditions {
+    /// Added to the round-trip time: half of it delays the packets in each direction.
+    pub latency: Duration,
+    /// The packets in each direction are delayed by a random duration up to this on top of the
+    /// latency. Packets are not reordered, so a delayed packet also delays the ones behind it.
+    pub jitter: Duration,
+    /// The fraction of the packets in each direction that are dropped, from 0 to 1.
+    pub loss: f32,
+}
+impl NetworkConditions {
+    /// Whether these conditions do not change anything, so nothing needs to be simulated.
+    pub fn is_ideal(&self) -> bool {
+        self.latency.is_zero() && self.jitter.is_zero() && self.loss <= 0.
+    }
+
+    /// Returns when a packet sent now should arrive, or `None` if it is lost.
+    fn arrival(&self) -> Option<Instant> {
+        let mut rng = rand::thread_rng();
+        if rng.gen::<f32>() < self.loss {
+            return None;
+        }
+        let jitter = self.jitter.mul_f32(rng.gen());
+        Some(Instant::now() + self.latency / 2 + jitter)
+    }
+}
+
+/// Creates a client endpoint bound to `addr` whose traffic goes through the simulation.
+pub(crate) fn client_endpoint(
+    addr: SocketAddr,
+    conditions: NetworkConditions,
+) -> io::Result<Endpoint> {
+    let runtime = quinn::default_runtime()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "no async runtime found"))?;
+    let socket = std::net::UdpSocket::bind(addr)?;
+    socket.set_nonblocking(true)?;
+    let socket = SimulatedSocket::new(UdpSocket::from_std(socket)?, conditions);
+    Endpoint::new_with_abstract_socket(EndpointConfig::default(), None, socket, runtime)
+}
+
+#[derive(Debug)]
+struct SimulatedSocket {
+    socket: Arc<UdpSocket>,
+    conditions: NetworkConditions,
+    /// The outgoing packets, with when they should be sent
+    outgoing: UnboundedSender<(Instant, SocketAddr, Bytes)>,
+    /// The incoming packets, once they have been delayed
+    incoming: Mutex<UnboundedReceiver<(SocketAddr, Bytes)>>,
+}
+impl SimulatedSocket {
+    fn new(socket: UdpSocket, conditions: NetworkConditions) -> Self {
+        let socket = Arc::new(socket);
+
+        let (outgoing, mut outgoing_rx) = mpsc::unbounded_channel::<(_, _, Bytes)>();
+        tokio::spawn({
+            let socket = socket.clone();
+            async move {
+                while let Some((send_at, destination, packet)) = outgoing_rx.recv().await {
+                    tokio::time::sleep_until(send_at).await;
+                    if let Err(err) = socket.send_to(&packet, destination).await {
+                        tracing::debug!("Failed to send simulated packet: {err:?}");
+                    }
+                }
+            }
+        });
+
+        let (received, mut received_rx) = mpsc::unbounded_channel();
+        tokio::spawn({
+            let socket = socket.clone();
+            async move {
+                let mut buf = vec![0; u16::MAX as usize];
+                loop {
+                    let (len, addr) = tokio::select! {
+                        result = socket.recv_from(&mut buf) => match result {
+                            Ok(result) => result,
+                            Err(err) => {
+                                tracing::debug!("Failed to receive simulated packet: {err:?}");
+                                continue;
+                            }
+                        },
+                        _ = received.closed() => break,
+                    };
+                    if let Some(arrival) = conditions.arrival() {
+                        let packet = Bytes::copy_from_slice(&buf[..len]);
+                        if received.send((arrival, addr, packet)).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        let (incoming, incoming_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some((arrival, addr, packet)) = received_rx.recv().await {
+                tokio::time::sleep_until(arrival).await;
+                if incoming.send((addr, packet)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            socket,
+            conditions,
+            outgoing,
+            incoming: Mutex::new(incoming_rx),
+        }
+    }
+}
+impl AsyncUdpSocket for SimulatedSocket {
+    fn poll_send(
+        &self,
+        _state: &UdpState,
+        _cx: &mut Context,
+        transmits: &[Transmit],
+    ) -> Poll<io::Result<usize>> {
+        for transmit in transmits {
+            // Transmits can contain several packets when segmentation offload is supported
+            let segment_size = transmit.segment_size.unwrap_or(transmit.contents.len());
+            for start in (0..transmit.contents.len()).step_by(segment_size.max(1)) {
+                let end = (start + segment_size).min(transmit.contents.len());
+                if let Some(send_at) = self.conditions.arrival() {
+                    let packet = transmit.contents.slice(start..end);
+                    self.outgoing
+                        .send((send_at, transmit.destination, packet))
+                        .ok();
+                }
+            }
+        }
+        Poll::Ready(Ok(transmits.len()))
+    }
+
+    fn poll_recv(
+        &self,
+        cx: &mut Context,
+        bufs: &mut [IoSliceMut<'_>],
+        meta: &mut [RecvMeta],
+    ) -> Poll<io::Result<usize>> {
+        let mut incoming = self.incoming.lock();
+        let mut count = 0;
+        while count < bufs.len() {
+            match incoming.poll_recv(cx) {
+                Poll::Ready(Some((addr, packet))) => {
+                    let len = packet.len().min(bufs[count].len());
+                    bufs[count][..len].copy_from_slice(&packet[..len]);
+                    meta[count] = RecvMeta {
+                        addr,
+                        len,
+                        stride: len,
+                        ecn: None,
+                        dst_ip: None,
+                    };
+                    count += 1;
+                }
+                Poll::Ready(None) if count == 0 => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::BrokenPipe,
+                        "the simulated network has stopped",
+                    )))
+                }
+                Poll::Ready(None) | Poll::Pending => break,
+            }
+        }
+        if count == 0 {
+            Poll::Pending
+        } else {
+            Poll::Ready(Ok(count))
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn arrival() {
+        let ideal = NetworkConditions::default();
+        assert!(ideal.is_ideal());
+        assert!(ideal.arrival().is_some());
+
+        let lossy = NetworkConditions {
+            loss: 1.,
+            ..Default::default()
+        };
+        assert!(!lossy.is_ideal());
+        assert!(lossy.arrival().is_none());
+
+        let slow = NetworkConditions {
+            latency: Duration::from_millis(100),
+            jitter: Duration::from_millis(20),
+            loss: 0.,
+        };
+        let now = Instant::now();
+        for _ in 0..100 {
+            let delay = slow.arrival().unwrap() - now;
+            assert!(delay >= Duration::from_millis(50));
+            assert!(delay <= Duration::from_millis(71));
+        }
+    }
+}
@@ -14,6 +14,7 @@ use http::Method;
 use uuid::Uuid;
 
 use crate::{
+    compression::Compression,
     proto::{
         self,
         server::{handle_diffs, ConnectionData},
@@ -113,6 +114,7 @@ async fn handle_webtransport_session(
         diff_tx: diffs_tx,
         connection_id: Uuid::new_v4(),
         world_stream_filter,
+        remote_addr: None,
     };
 
     while server.is_pending_connection() {
@@ -121,9 +123,16 @@ async fn handle_webtransport_session(
         }
     }
 
+    if let proto::server::ServerProtoState::Rejected(reason) = &server {
+        tracing::info!(?reason, "Connection rejected");
+        push_send.send(ServerPush::Rejected(reason.clone())).await?;
+        return Ok(());
+    }
+
     tokio::spawn(handle_diffs(
         FramedSendStream::new(conn.open_uni(sid).await?),
         diffs_rx,
+        Compression::None,
     ));
 
     let mut server = scopeguard::guard(server, |mut server| {
@@ -155,6 +164,10 @@ async fn handle_webtransport_session(
             }
             Some(msg) = connected.control_rx.next() => {
                 push_send.send(&msg).await?;
+                if let ServerPush::Rejected(reason) = msg {
+                    tracing::info!(?reason, "Disconnecting client");
+                    server.process_disconnect(&data);
+                }
             }
         }
     }
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
 use ambient_ecs::{generated::network::components::is_remote_entity, Entity};
+#[cfg(not(target_os = "unknown"))]
+use ambient_ecs::{FrozenWorldDiff, WorldDiff};
 use ambient_native_std::{
     asset_cache::{AssetCache, SyncAssetKeyExt},
     asset_url::ContentBaseUrlKey,
@@ -21,6 +23,7 @@ use crate::{
     diff_serialization::DiffSerializer,
     log_task_result,
     proto::*,
+    NetworkError,
 };
 
 /// The client logic handler in a connected state
@@ -30,12 +33,16 @@ use crate::{
 pub(crate) struct ConnectedClient {
     diff_serializer: DiffSerializer,
     pub main_package_name: String,
+    /// Whether the server streams its assets over the connection
+    pub streams_assets: bool,
 }
 
 #[derive(Debug)]
 pub(crate) enum ClientProtoState {
     Pending(String),
     Connected(ConnectedClient),
+    /// The server is shutting down, and the client should reconnect to the new host
+    Migrating(HostMigration),
     Disconnected,
 }
 
@@ -59,19 +66,12 @@ impl ClientProtoState {
     ) -> anyhow::Result<()> {
         match (frame, &self) {
             (ServerPush::ServerInfo(server_info), Self::Pending(_user_id)) => {
-                let current_version = ambient_version().to_string();
-
-                if server_info.version != current_version {
-                    let msg = format!(
-                        "Client version does not match server version.\n\nServer version: {}\nClient version: {}",
-                        server_info.version,
-                        current_version
-                    );
-
+                if let Err(mismatch) = server_info.check_version() {
                     if fail_on_version_mismatch {
-                        anyhow::bail!(msg);
+                        self.process_disconnect();
+                        return Err(NetworkError::VersionMismatch(mismatch).into());
                     } else {
-                        tracing::error!("{}", msg);
+                        tracing::error!("{mismatch}");
                     }
                 }
 
@@ -81,6 +81,7 @@ impl ClientProtoState {
                 *self = Self::Connected(ConnectedClient {
                     diff_serializer: Default::default(),
                     main_package_name: server_info.main_package_name,
+                    streams_assets: server_info.streams_assets,
                 });
 
                 Ok(())
@@ -93,6 +94,19 @@ impl ClientProtoState {
                 self.process_disconnect();
                 Ok(())
             }
+            (ServerPush::Rejected(reason), _) => {
+                self.process_disconnect();
+                Err(NetworkError::ConnectionRejected(reason).into())
+            }
+            (ServerPush::HostMigration(migration), Self::Connected(_)) => {
+                tracing::info!(?migration, "The host is migrating");
+                *self = Self::Migrating(migration);
+                Ok(())
+            }
+            (ServerPush::HostMigration(_), _) => {
+                tracing::warn!("Received host migration while not connected");
+                Ok(())
+            }
         }
     }
 
@@ -125,6 +139,16 @@ impl ClientProtoState {
     pub(crate) fn is_connected(&self) -> bool {
         matches!(self, Self::Connected(..))
     }
+
+    /// Returns the host migration if the client state is [`Migrating`].
+    ///
+    /// [`Migrating`]: ClientProtoState::Migrating
+    pub(crate) fn into_migration(self) -> Option<HostMigration> {
+        match self {
+            Self::Migrating(migration) => Some(migration),
+            _ => None,
+        }
+    }
 }
 
 impl ConnectedClient {
@@ -140,6 +164,18 @@ impl ConnectedClient {
         Ok(())
     }
 
+    /// Applies a diff from a server in the same process, which is passed on without being
+    /// serialized.
+    #[cfg(not(target_os = "unknown"))]
+    pub fn process_local_diff(&mut self, state: &SharedClientGameState, diff: FrozenWorldDiff) {
+        let diff = WorldDiff {
+            changes: diff.into_iter().cloned().collect(),
+        };
+        let mut gs = state.lock();
+        tracing::trace!(diff=?diff.len(), "Applying diff");
+        diff.apply(&mut gs.world, Entity::new().with(is_remote_entity(), ()));
+    }
+
     /// Processes an incoming datagram
     pub fn process_datagram(
         &mut self,
@@ -1,5 +1,11 @@
+use std::sync::OnceLock;
+
 use ambient_core::main_package_name;
 use ambient_native_std::{ambient_version, asset_url::AbsAssetUrl};
+use ambient_package::Manifest;
+use sha2::{Digest, Sha256};
+
+use crate::{server::bi_stream_handlers, ASSET_STREAM_BISTREAM_ID};
 
 pub mod client;
 pub mod server;
@@ -11,6 +17,10 @@ pub enum ClientRequest {
     Connect(String),
     /// Client wants to disconnect
     Disconnect,
+    /// Connect to the server as a spectator with the specified user id
+    ///
+    /// Spectators receive the world state, but do not get a player entity.
+    ConnectSpectator(String),
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -19,6 +29,33 @@ pub enum ServerPush {
     ServerInfo(ServerInfo),
     /// Graceful disconnect
     Disconnect,
+    /// The server has refused the connection request
+    Rejected(ConnectionRejection),
+    /// The host is shutting down, and another client takes over as the server
+    HostMigration(HostMigration),
+}
+
+/// Tells the clients which of them becomes the new host, and where to reconnect to.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct HostMigration {
+    /// The user ID of the client that starts the new server
+    pub successor: String,
+    /// The name to use when verifying the certificate of the new server
+    pub host_name: String,
+    /// The address of the new server
+    pub addr: std::net::SocketAddr,
+}
+
+/// The reason that the server has refused a connection request.
+/// Note: Like [ServerInfo], this should deserialize correctly between versions so that the client can show why it was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, thiserror::Error)]
+pub enum ConnectionRejection {
+    #[error("The server is full (maximum of {max_players} players)")]
+    ServerFull { max_players: u32 },
+    #[error("You have been kicked from the server: {reason}")]
+    Kicked { reason: String },
+    #[error("You are banned from the server: {reason}")]
+    Banned { reason: String },
 }
 
 /// Miscellaneous information about the server that needs to be sent to the client during the handshake.
@@ -35,6 +72,15 @@ pub struct ServerInfo {
     /// Defaults to the version of the crate.
     /// TODO: use semver
     pub version: String,
+
+    // These fields were added after the fields above, so that older clients can still read them.
+    /// The [PROTOCOL_VERSION] of the server.
+    pub protocol_version: u32,
+    /// The [schema_hash] of the server.
+    pub schema_hash: u64,
+    /// Whether the server streams the assets under the content base URL over the connection.
+    /// See [asset_stream](crate::asset_stream).
+    pub streams_assets: bool,
 }
 
 impl ServerInfo {
@@ -49,6 +95,140 @@ impl ServerInfo {
             main_package_name: world.resource(main_package_name()).clone(),
             content_base_url,
             version: ambient_version().to_string(),
+            protocol_version: PROTOCOL_VERSION,
+            schema_hash: schema_hash(),
+            streams_assets: world
+                .resource(bi_stream_handlers())
+                .contains_key(&ASSET_STREAM_BISTREAM_ID),
         }
     }
+
+    /// Checks whether this client can connect to the server, returning why not if it can't.
+    pub fn check_version(&self) -> Result<(), VersionMismatch> {
+        VersionMismatch::check(
+            (&self.version, self.protocol_version, self.schema_hash),
+            (
+                &ambient_version().to_string(),
+                PROTOCOL_VERSION,
+                schema_hash(),
+            ),
+        )
+    }
+}
+
+/// The version of the protocol used between the client and the server.
+///
+/// Increment this whenever the handshake, the framing or the serialization of the messages
+/// exchanged by the client and the server change.
+pub const PROTOCOL_VERSION: u32 = 4;
+
+/// Returns a hash of the components, messages and enums defined by the core schema of this
+/// version of Ambient. The client and the server must agree on these, as they are used to
+/// serialize the world and the messages sent between them.
+pub fn schema_hash() -> u64 {
+    static HASH: OnceLock<u64> = OnceLock::new();
+    *HASH.get_or_init(|| {
+        let mut definitions = Vec::new();
+        for (path, contents) in ambient_schema::FILES {
+            let manifest = match Manifest::parse(contents) {
+                Ok(manifest) => manifest,
+                Err(err) => {
+                    tracing::warn!("Failed to parse schema file {path}: {err:?}");
+                    continue;
+                }
+            };
+            for (id, component) in &manifest.components {
+                definitions.push(format!(
+                    "{path}/{id}: {:?} {:?}",
+                    component.type_, component.attributes
+                ));
+            }
+            for (id, message) in &manifest.messages {
+                definitions.push(format!("{path}/{id}: {:?}", message.fields));
+            }
+            for (id, enum_) in &manifest.enums {
+                definitions.push(format!("{path}/{id}: {:?}", enum_.members.keys()));
+            }
+        }
+        definitions.sort();
+
+        let digest = Sha256::digest(definitions.join("\n"));
+        u64::from_le_bytes(digest[..8].try_into().unwrap())
+    })
+}
+
+/// The reason that a client cannot connect to a server that runs a different version of Ambient.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum VersionMismatch {
+    #[error("The server runs Ambient {server_version}, which uses version {server_protocol} of the network protocol, but you have Ambient {client_version}, which uses version {client_protocol}. Install Ambient {server_version} to join this server.")]
+    Protocol {
+        server_version: String,
+        client_version: String,
+        server_protocol: u32,
+        client_protocol: u32,
+    },
+    #[error("The server runs Ambient {server_version}, which defines different components and messages than your version (Ambient {client_version}). Install Ambient {server_version} to join this server.")]
+    Schema {
+        server_version: String,
+        client_version: String,
+    },
+    #[error("The server runs Ambient {server_version}, but you have Ambient {client_version}. Install Ambient {server_version} to join this server.")]
+    Version {
+        server_version: String,
+        client_version: String,
+    },
+}
+impl VersionMismatch {
+    /// Compares the version, protocol version and schema hash of the server and the client.
+    pub fn check(server: (&str, u32, u64), client: (&str, u32, u64)) -> Result<(), Self> {
+        let (server_version, server_protocol, server_schema) = server;
+        let (client_version, client_protocol, client_schema) = client;
+        let versions = (server_version.to_string(), client_version.to_string());
+
+        if server_protocol != client_protocol {
+            Err(Self::Protocol {
+                server_version: versions.0,
+                client_version: versions.1,
+                server_protocol,
+                client_protocol,
+            })
+        } else if server_schema != client_schema {
+            Err(Self::Schema {
+                server_version: versions.0,
+                client_version: versions.1,
+            })
+        } else if server_version != client_version {
+            Err(Self::Version {
+                server_version: versions.0,
+                client_version: versions.1,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn version_mismatch() {
+        let current = ("0.3.2", PROTOCOL_VERSION, schema_hash());
+        assert_eq!(VersionMismatch::check(current, current), Ok(()));
+
+        assert!(matches!(
+            VersionMismatch::check(("0.3.1", PROTOCOL_VERSION - 1, 1), current),
+            Err(VersionMismatch::Protocol { server_protocol, .. })
+                if server_protocol == PROTOCOL_VERSION - 1
+        ));
+        assert!(matches!(
+            VersionMismatch::check(("0.3.1", PROTOCOL_VERSION, 1), current),
+            Err(VersionMismatch::Schema { .. })
+        ));
+        assert!(matches!(
+            VersionMismatch::check(("0.3.1", PROTOCOL_VERSION, schema_hash()), current),
+            Err(VersionMismatch::Version { .. })
+        ));
+    }
 }
@@ -1,8 +1,10 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
 
-use ambient_core::player::get_by_user_id;
+use ambient_core::player::{get_by_user_id, is_player, is_spectator};
 use ambient_ecs::{
-    ComponentRegistry, Entity, EntityId, FrozenWorldDiff, WorldChange, WorldDiff, WorldStreamFilter,
+    generated::network::{components::max_players, messages::ServerFull},
+    world_events, ComponentRegistry, Entity, EntityId, FrozenWorldDiff, WorldChange, WorldDiff,
+    WorldEventsExt, WorldStreamFilter,
 };
 use ambient_native_std::{fps_counter::FpsSample, log_result};
 use anyhow::Context;
@@ -15,11 +17,14 @@ use uuid::Uuid;
 use crate::{
     bytes_ext::BufExt,
     client::NetworkTransport,
+    compression::Compression,
+    delta_compression,
     diff_serialization::{DiffSerializer, WorldDiffDeduplicator},
     log_network_result, log_task_result,
-    proto::ServerPush,
+    proto::{ConnectionRejection, ServerPush},
     server::{
-        bi_stream_handlers, create_player_entity_data, datagram_handlers, uni_stream_handlers,
+        ban_list, bi_stream_handlers, create_player_entity_data, create_spectator_entity_data,
+        datagram_handlers, get_connection_entity, player_connection_id, uni_stream_handlers,
     },
     server::{SharedServerState, MAIN_INSTANCE_ID},
     stream,
@@ -36,6 +41,8 @@ pub enum ServerProtoState {
     #[default]
     PendingConnection,
     Connected(ConnectedClient),
+    /// The connection request was refused; the reason should be sent to the client before closing the connection.
+    Rejected(ConnectionRejection),
     Disconnected,
 }
 
@@ -68,6 +75,8 @@ pub struct ConnectionData {
     pub(crate) connection_id: Uuid,
     pub(crate) conn: Arc<dyn NetworkTransport>,
     pub(crate) world_stream_filter: WorldStreamFilter,
+    /// The address of the client, if it is connected directly over QUIC
+    pub(crate) remote_addr: Option<SocketAddr>,
 }
 
 impl std::fmt::Debug for ConnectionData {
@@ -76,6 +85,7 @@ impl std::fmt::Debug for ConnectionData {
             .field("diff_tx", &self.diff_tx)
             .field("diff_tx", &self.diff_tx)
             .field("connection_id", &self.connection_id)
+            .field("remote_addr", &self.remote_addr)
             .finish_non_exhaustive()
     }
 }
@@ -85,6 +95,9 @@ pub struct Player {
     pub instance: String,
     control_tx: flume::Sender<ServerPush>,
     connection_id: Uuid,
+    /// The address of the client, if it is connected directly over QUIC. Only these clients can
+    /// take over as the host.
+    pub remote_addr: Option<SocketAddr>,
 }
 
 impl Player {
@@ -95,6 +108,7 @@ impl Player {
             instance: instance.into(),
             control_tx,
             connection_id: Uuid::new_v4(),
+            remote_addr: None,
         }
     }
 
@@ -102,6 +116,11 @@ impl Player {
     pub fn abort(&self) {
         self.control_tx.send(ServerPush::Disconnect).ok();
     }
+
+    /// Sends a control frame to the client
+    pub(crate) fn push(&self, frame: ServerPush) {
+        self.control_tx.send(frame).ok();
+    }
 }
 
 impl ServerProtoState {
@@ -119,10 +138,18 @@ impl ServerProtoState {
             (ClientRequest::Connect(user_id), Self::PendingConnection) => {
                 // Connect the user
                 tracing::debug!("User connected");
-                self.process_connect(data, user_id);
+                self.process_connect(data, user_id, false);
+                Ok(())
+            }
+            (ClientRequest::ConnectSpectator(user_id), Self::PendingConnection) => {
+                tracing::debug!("Spectator connected");
+                self.process_connect(data, user_id, true);
                 Ok(())
             }
-            (ClientRequest::Connect(_), Self::Connected(_)) => {
+            (
+                ClientRequest::Connect(_) | ClientRequest::ConnectSpectator(_),
+                Self::Connected(_) | Self::Rejected(_),
+            ) => {
                 tracing::warn!("Client already connected");
                 Ok(())
             }
@@ -134,9 +161,44 @@ impl ServerProtoState {
     }
 
     #[tracing::instrument(level = "debug")]
-    fn process_connect(&mut self, data: &ConnectionData, user_id: String) {
+    fn process_connect(&mut self, data: &ConnectionData, user_id: String, spectator: bool) {
         let mut state = data.state.lock();
 
+        let ban_reason = state.instances[MAIN_INSTANCE_ID]
+            .world
+            .resource_opt(ban_list())
+            .and_then(|bans| {
+                bans.lock()
+                    .reason(&user_id)
+                    .map(|reason| reason.to_string())
+            });
+        if let Some(reason) = ban_reason {
+            tracing::info!(%user_id, %reason, "Rejecting player: banned");
+            *self = Self::Rejected(ConnectionRejection::Banned { reason });
+            return;
+        }
+
+        // Spectators do not take up a slot, and players that are reconnecting take over their existing slot
+        let player_count = state.player_count();
+        let world = &mut state.instances.get_mut(MAIN_INSTANCE_ID).unwrap().world;
+        if !spectator && get_by_user_id(world, &user_id).is_none() {
+            let max_players = world
+                .resource_opt(max_players())
+                .copied()
+                .unwrap_or_default();
+
+            if max_players > 0 && player_count >= max_players as usize {
+                tracing::info!(%user_id, max_players, "Rejecting player: server is full");
+                world.resource_mut(world_events()).add_message(ServerFull {
+                    user_id,
+                    max_players,
+                });
+
+                *self = Self::Rejected(ConnectionRejection::ServerFull { max_players });
+                return;
+            }
+        }
+
         let (control_tx, control_rx) = flume::unbounded();
 
         let old_player = state.players.insert(
@@ -145,6 +207,7 @@ impl ServerProtoState {
                 instance: MAIN_INSTANCE_ID.to_string(),
                 control_tx,
                 connection_id: data.connection_id,
+                remote_addr: data.remote_addr,
             },
         );
 
@@ -160,7 +223,12 @@ impl ServerProtoState {
         log_result!(data.diff_tx.send(diff.into()));
         tracing::debug!("[{}] Init diff sent", user_id);
 
-        let entity_data = create_player_entity_data(
+        let create_entity_data = if spectator {
+            create_spectator_entity_data
+        } else {
+            create_player_entity_data
+        };
+        let entity_data = create_entity_data(
             data.conn.clone(),
             user_id.clone(),
             data.diff_tx.clone(),
@@ -170,14 +238,36 @@ impl ServerProtoState {
         if let Some(old_player) = old_player {
             old_player.control_tx.send(ServerPush::Disconnect).ok();
 
-            let id = get_by_user_id(&instance.world, &user_id).unwrap();
+            let id = get_connection_entity(&instance.world, &user_id).unwrap();
 
+            // The user may have reconnected in a different mode
+            let old_marker = if spectator {
+                is_player()
+            } else {
+                is_spectator()
+            };
+            instance.world.remove_component(id, old_marker).unwrap();
             instance.world.add_components(id, entity_data).unwrap();
 
-            tracing::debug!(user_id, ?id, "Player reconnected");
+            tracing::debug!(user_id, ?id, spectator, "Player reconnected");
+        } else if let Some(id) = get_by_user_id(&instance.world, &user_id)
+            .filter(|id| !instance.world.has_component(*id, player_connection_id()))
+        {
+            // The player entity was restored by host migration, and is taken over by its user
+            instance.world.add_components(id, entity_data).unwrap();
+            if spectator {
+                instance.world.remove_component(id, is_player()).ok();
+            }
+
+            tracing::debug!(
+                user_id,
+                ?id,
+                spectator,
+                "Player rejoined after host migration"
+            );
         } else {
             let id = instance.spawn_player(entity_data);
-            tracing::debug!(user_id, ?id, "Player connected");
+            tracing::debug!(user_id, ?id, spectator, "Player connected");
         }
 
         *self = Self::Connected(ConnectedClient {
@@ -357,10 +447,11 @@ pub async fn handle_stats<S>(
     log_network_result!(stats.map(Ok).forward(stream).await);
 }
 
-/// Sends the world diffs over the network
+/// Sends the world diffs over the network, compressing them if the connection supports it
 pub async fn handle_diffs<S>(
     stream: stream::FramedSendStream<WorldDiff, S>,
     diffs_rx: flume::Receiver<FrozenWorldDiff>,
+    compression: Compression,
 ) where
     S: Unpin + AsyncWrite,
 {
@@ -426,6 +517,9 @@ pub async fn handle_diffs<S>(
                 let input_changes_count = intermediate_diff.changes_merged;
                 let mut diff = intermediate_diff.take();
                 let merged_changes_count = diff.changes.len();
+                if delta_compression::ENABLED {
+                    delta_compression::quantize_transforms(&mut diff);
+                }
                 deduplicator.deduplicate(&mut diff);
                 let msg = serializer.serialize(&diff).unwrap();
                 tracing::trace!(
@@ -450,6 +544,8 @@ pub async fn handle_diffs<S>(
                 );
             }
 
+            let msg = compression.compress(msg);
+
             let span = tracing::debug_span!("send_world_diff");
             sending_future = Some(Box::pin(async move {
                 stream
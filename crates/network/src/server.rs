@@ -1,23 +1,29 @@
 use std::{collections::HashMap, fmt::Debug, sync::Arc, time::Duration};
 
 use crate::{
-    client::NetworkTransport, proto::server::Player, DynRecv, DynSend, NetworkError,
-    RPC_BISTREAM_ID,
+    bans::SharedBanList,
+    client::NetworkTransport,
+    proto::{server::Player, ConnectionRejection, ServerPush},
+    DynRecv, DynSend, NetworkError, ServerWorldExt, RPC_BISTREAM_ID,
 };
 use ambient_core::{
-    app_start_time, name,
-    player::{get_by_user_id, is_player, user_id},
+    app_start_time,
+    frame_budget::{frame_budget, FrameBudget, REPLICATION},
+    game_time, name,
+    player::{get_by_user_id, is_player, is_spectator, user_id},
     FIXED_SERVER_TICK_TIME,
 };
 use ambient_ecs::{
-    components, dont_store, query, ArchetypeFilter, Entity, EntityId, FrameEvent, FrozenWorldDiff,
-    Networked, Resource, System, SystemGroup, World, WorldStream, WorldStreamFilter,
+    components, dont_store, generated::network::components::tick_rate, query, ArchetypeFilter,
+    Entity, EntityId, FrameEvent, FrozenWorldDiff, Networked, Resource, System, SystemGroup, World,
+    WorldDiff, WorldStream, WorldStreamFilter,
 };
 use ambient_native_std::{
     asset_cache::AssetCache, asset_url::AbsAssetUrl, fps_counter::FpsSample, log_result,
 };
 use ambient_rpc::RpcRegistry;
 use ambient_sys::time::Instant;
+use anyhow::Context;
 use bytes::Bytes;
 use flume::Sender;
 use parking_lot::Mutex;
@@ -38,6 +44,19 @@ components!("network::server", {
     // synced resource
     @[Networked]
     server_stats: FpsSample,
+    @[Networked, Description["The seed of the random numbers that are shared by the server and the clients of this world."]]
+    random_seed: u64,
+    @[Networked, Description["The number of ticks the server has simulated in this world."]]
+    server_tick: u64,
+
+    @[Resource, Description["The users that are not allowed to connect to the server."]]
+    ban_list: SharedBanList,
+    @[Resource, Description["Players to disconnect after the current tick, by user ID, with the reason."]]
+    kick_requests: Vec<(String, String)>,
+    @[Resource, Description["The ID of the world instance this world belongs to."]]
+    world_instance_id: String,
+    @[Resource, Description["Changes to the world instances of the server requested from this world, applied after the current tick."]]
+    instance_requests: Vec<InstanceRequest>,
 });
 
 pub type BiStreamHandler =
@@ -58,6 +77,40 @@ pub struct ForkedEvent;
 #[derive(Debug, Clone, Copy)]
 pub struct ShutdownEvent;
 
+/// A change to the world instances of the server, requested from inside one of them.
+///
+/// The instances cannot change while they are being stepped, so the requests are queued in the
+/// [instance_requests] resource of the requesting world, and applied after the tick.
+#[derive(Debug, Clone)]
+pub enum InstanceRequest {
+    /// Creates an instance by copying the requesting instance; see [ServerState::fork_instance].
+    Create { id: String },
+    /// Moves a player, by user ID, to another instance; see [ServerState::join_instance].
+    Join {
+        user_id: String,
+        instance_id: String,
+    },
+    /// Removes an instance; see [ServerState::close_instance].
+    Remove { instance_id: String },
+}
+
+/// Disconnects the given user after the current tick, telling their client that they were kicked
+/// for `reason`.
+pub fn request_kick(world: &mut World, user_id: String, reason: String) {
+    match world.resource_mut_opt(kick_requests()) {
+        Some(requests) => requests.push((user_id, reason)),
+        None => world.add_resource(kick_requests(), vec![(user_id, reason)]),
+    }
+}
+
+/// Queues a change to the world instances of the server, to be applied after the current tick.
+pub fn request_instance_change(world: &mut World, request: InstanceRequest) {
+    match world.resource_mut_opt(instance_requests()) {
+        Some(requests) => requests.push(request),
+        None => world.add_resource(instance_requests(), vec![request]),
+    }
+}
+
 pub struct WorldInstance {
     pub world: World,
     pub world_stream: WorldStream,
@@ -91,6 +144,50 @@ pub fn create_player_entity_data(
         .with(dont_store(), ())
 }
 
+/// Like [create_player_entity_data], but for a spectator: the entity receives world diffs, but is
+/// not a player.
+pub fn create_spectator_entity_data(
+    transport: Arc<dyn NetworkTransport>,
+    new_user_id: String,
+    entities_tx: Sender<FrozenWorldDiff>,
+    connection_id: Uuid,
+) -> Entity {
+    create_player_entity_data(transport, new_user_id.clone(), entities_tx, connection_id)
+        .remove(is_player())
+        .with(name(), format!("Spectator {}", new_user_id))
+        .with(is_spectator(), ())
+}
+
+/// Returns the entity of the connection for the given user ID, which is either a player or a spectator.
+pub fn get_connection_entity(world: &World, user_id: &str) -> Option<EntityId> {
+    query(self::user_id())
+        .incl(player_connection_id())
+        .iter(world, None)
+        .find_map(|(id, uid)| if uid == user_id { Some(id) } else { None })
+}
+
+/// Returns `true` if the given user is connected as a spectator.
+pub fn is_spectator_connection(world: &World, user_id: &str) -> bool {
+    get_connection_entity(world, user_id).is_some_and(|id| world.has_component(id, is_spectator()))
+}
+
+/// Turns the spectator with the given user ID into a player, returning their entity.
+///
+/// Returns `None` if the user is not connected as a spectator.
+pub fn promote_spectator(world: &mut World, user_id: &str) -> Option<EntityId> {
+    let id = ambient_core::player::get_spectator_by_user_id(world, user_id)?;
+    world.remove_component(id, is_spectator()).ok()?;
+    world
+        .add_components(
+            id,
+            Entity::new()
+                .with(is_player(), ())
+                .with(name(), format!("Player {}", user_id)),
+        )
+        .ok()?;
+    Some(id)
+}
+
 pub fn register_rpc_bi_stream_handler(
     handlers: &mut BiStreamHandlers,
     rpc_registry: RpcRegistry<RpcArgs>,
@@ -128,29 +225,55 @@ impl WorldInstance {
     pub fn spawn_player(&mut self, ed: Entity) -> EntityId {
         ed.spawn(&mut self.world)
     }
+    /// Despawns the entity of the given user's connection, whether they are a player or a spectator.
     pub fn despawn_player(&mut self, user_id: &str) -> Option<Entity> {
-        let id = get_by_user_id(&self.world, user_id)?;
+        let id = get_connection_entity(&self.world, user_id)?;
         ambient_core::hierarchy::despawn_recursive(&mut self.world, id)
     }
     pub fn broadcast_diffs(&mut self) {
-        let diff = self.world_stream.next_diff(&self.world);
-        if diff.is_empty() {
-            return;
-        }
-        let diff: FrozenWorldDiff = diff.into();
+        let Self {
+            world,
+            world_stream,
+            ..
+        } = self;
+        ambient_core::frame_budget::measure(world, REPLICATION, |world| {
+            let diff = world_stream.next_diff(world);
+            if diff.is_empty() {
+                return;
+            }
+            let diff: FrozenWorldDiff = diff.into();
 
-        profiling::scope!("Send MsgEntities");
+            profiling::scope!("Send MsgEntities");
 
-        for (_, (entity_stream,)) in query((player_entity_stream(),)).iter(&self.world, None) {
-            if let Err(err) = entity_stream.send(diff.clone()) {
-                tracing::warn!("Failed to broadcast diff to player: {err:?}");
+            for (_, (entity_stream,)) in query((player_entity_stream(),)).iter(world, None) {
+                if let Err(err) = entity_stream.send(diff.clone()) {
+                    tracing::warn!("Failed to broadcast diff to player: {err:?}");
+                }
             }
-        }
+        });
+    }
+    /// Returns the time spent by the subsystems of this instance since the last call.
+    pub fn take_frame_budget(&mut self) -> FrameBudget {
+        self.world
+            .resource_mut_opt(frame_budget())
+            .map(std::mem::take)
+            .unwrap_or_default()
     }
     pub fn player_count(&self) -> usize {
         query((is_player(),)).iter(&self.world, None).count()
     }
+    /// Steps the simulation by one tick of `delta_time`.
+    ///
+    /// The server's time advances in fixed steps: `delta_time` is always the tick time, and
+    /// `game_time` is the sum of all previous ticks, regardless of how long each tick actually
+    /// took. This keeps the simulation independent of the load on the server.
     pub fn step(&mut self, frame_time: Instant, delta_time: Duration) {
+        // The time spent by the subsystems is only recorded in the worlds of servers
+        if self.world.resource_opt(frame_budget()).is_none() {
+            self.world
+                .add_resource(frame_budget(), FrameBudget::default());
+        }
+        let next_game_time = *self.world.resource(game_time()) + delta_time;
         self.world
             .set_components(
                 self.world.resource_entity(),
@@ -158,22 +281,64 @@ impl WorldInstance {
                     frame_time,
                     *self.world.resource(app_start_time()),
                     delta_time,
-                ),
+                )
+                .with(game_time(), next_game_time),
             )
             .unwrap();
         self.systems.run(&mut self.world, &FrameEvent);
+        self.advance_server_tick();
         self.world.next_frame();
     }
+    /// Advances the [server_tick] of the synced resources, which are seeded on the first tick.
+    fn advance_server_tick(&mut self) {
+        let Some(id) = self.world.synced_resource_entity() else {
+            return;
+        };
+        if !self.world.has_component(id, random_seed()) {
+            self.world
+                .add_components(
+                    id,
+                    Entity::new()
+                        .with(random_seed(), rand::random())
+                        .with(server_tick(), 0),
+                )
+                .unwrap();
+        }
+        let tick = self.world.get_mut(id, server_tick()).unwrap();
+        *tick += 1;
+    }
 }
 
 pub const MAIN_INSTANCE_ID: &str = "main";
 
+/// The number of times per second the server is stepped, if no [tick_rate] is configured.
+pub const DEFAULT_TICK_RATE: u32 = 60;
+
+/// Returns the time between ticks for a server with the given tick rate.
+pub fn tick_time_from_rate(tick_rate: u32) -> Duration {
+    if tick_rate == 0 {
+        return FIXED_SERVER_TICK_TIME;
+    }
+    Duration::from_secs_f64(1.0 / tick_rate as f64)
+}
+
+/// Returns the time between ticks for the server that owns `world`, based on its [tick_rate] resource.
+pub fn tick_time(world: &World) -> Duration {
+    world
+        .resource_opt(tick_rate())
+        .map_or(FIXED_SERVER_TICK_TIME, |rate| tick_time_from_rate(*rate))
+}
+
 pub type SharedServerState = Arc<Mutex<ServerState>>;
 
 pub struct ServerState {
     pub assets: AssetCache,
     pub instances: HashMap<String, WorldInstance>,
     pub players: HashMap<String, Player>,
+    /// The time between ticks of the simulation; see [tick_time]
+    pub tick_time: Duration,
+    /// Whether the simulation is paused; see [ServerState::step]
+    pub paused: bool,
     pub create_server_systems: Arc<dyn Fn(&mut World) -> SystemGroup + Sync + Send>,
     pub create_on_forking_systems: Arc<dyn Fn() -> SystemGroup<ForkingEvent> + Sync + Send>,
     pub create_shutdown_systems: Arc<dyn Fn() -> SystemGroup<ShutdownEvent> + Sync + Send>,
@@ -188,13 +353,20 @@ impl ServerState {
             instances: [(
                 MAIN_INSTANCE_ID.to_string(),
                 WorldInstance {
-                    world: World::new("main_server", ambient_ecs::WorldContext::Server),
+                    world: {
+                        let mut world =
+                            World::new("main_server", ambient_ecs::WorldContext::Server);
+                        world.add_resource(world_instance_id(), MAIN_INSTANCE_ID.to_string());
+                        world
+                    },
                     world_stream: WorldStream::new(world_stream_filter),
                     systems: SystemGroup::new("", vec![]),
                 },
             )]
             .into(),
             players: Default::default(),
+            tick_time: FIXED_SERVER_TICK_TIME,
+            paused: false,
             create_server_systems: Arc::new(|_| SystemGroup::new("", vec![])),
             create_on_forking_systems: Arc::new(|| SystemGroup::new("", vec![])),
             create_shutdown_systems: Arc::new(|| SystemGroup::new("", vec![])),
@@ -207,26 +379,55 @@ impl ServerState {
         create_on_forking_systems: Arc<dyn Fn() -> SystemGroup<ForkingEvent> + Sync + Send>,
         create_shutdown_systems: Arc<dyn Fn() -> SystemGroup<ShutdownEvent> + Sync + Send>,
     ) -> Self {
+        let tick_time = instances
+            .get(MAIN_INSTANCE_ID)
+            .map_or(FIXED_SERVER_TICK_TIME, |instance| {
+                tick_time(&instance.world)
+            });
+
         Self {
             assets,
             instances,
             players: Default::default(),
+            tick_time,
+            paused: false,
             create_server_systems,
             create_on_forking_systems,
             create_shutdown_systems,
         }
     }
 
+    /// Steps all the instances by one tick, then applies the [InstanceRequest]s made during it.
+    ///
+    /// While the server is [paused](ServerState::paused), the instances are not stepped, but the
+    /// requests are still applied. A paused server resumes as soon as a second player joins, as
+    /// only single-player games can be paused.
     pub fn step(&mut self) {
-        for instance in self.instances.values_mut() {
-            instance.step(Instant::now(), FIXED_SERVER_TICK_TIME);
+        if self.paused && self.player_count() > 1 {
+            tracing::info!("Resuming the simulation, as another player joined");
+            self.paused = false;
+        }
+        if !self.paused {
+            for instance in self.instances.values_mut() {
+                instance.step(Instant::now(), self.tick_time);
+            }
         }
+        self.apply_instance_requests();
+        self.apply_kick_requests();
     }
     pub fn broadcast_diffs(&mut self) {
         for instance in self.instances.values_mut() {
             instance.broadcast_diffs();
         }
     }
+    /// Returns the time spent by the subsystems of all the instances since the last call.
+    pub fn take_frame_budget(&mut self) -> FrameBudget {
+        let mut budget = FrameBudget::default();
+        for instance in self.instances.values_mut() {
+            budget.merge(instance.take_frame_budget());
+        }
+        budget
+    }
     pub fn player_count(&self) -> usize {
         self.instances.values().map(|i| i.player_count()).sum()
     }
@@ -247,6 +448,209 @@ impl ServerState {
     pub fn get_player_world(&self, user_id: &str) -> Option<&World> {
         self.get_player_world_instance(user_id).map(|i| &i.world)
     }
+    /// Creates the instance `id` as a copy of the instance `source_id`, without its players.
+    ///
+    /// The `resources` and `synced_res` are added to the resources of the copy, and the
+    /// [ForkingEvent] systems are run on it; the packages of the copy are restarted by them.
+    /// Does nothing if the instance `id` already exists.
+    pub fn fork_instance(
+        &mut self,
+        source_id: &str,
+        id: &str,
+        resources: Entity,
+        synced_res: Entity,
+    ) -> anyhow::Result<()> {
+        if self.instances.contains_key(id) {
+            return Ok(());
+        }
+        let instance = self
+            .instances
+            .get(source_id)
+            .with_context(|| format!("No instance with ID {source_id:?}"))?;
+        let mut world = instance.world.clone();
+
+        for (player_id, _) in query(user_id()).collect_cloned(&world, None) {
+            world.despawn(player_id);
+        }
+        world.add_components(
+            world.resource_entity(),
+            resources
+                .with_merge(ambient_core::async_ecs::async_ecs_resources())
+                .with(world_instance_id(), id.to_string())
+                .with(instance_requests(), Vec::new()),
+        )?;
+        if let Some(synced_res_id) = world.synced_resource_entity() {
+            world.add_components(synced_res_id, synced_res)?;
+        }
+
+        let mut on_forking = (self.create_on_forking_systems)();
+        on_forking.run(&mut world, &ForkingEvent);
+
+        world.reset_events();
+
+        let new_instance = WorldInstance {
+            systems: (self.create_server_systems)(&mut world),
+            world,
+            world_stream: instance.world_stream.clone(),
+        };
+        self.instances.insert(id.to_string(), new_instance);
+        Ok(())
+    }
+    /// Moves the player with the given user ID to the instance `instance_id`.
+    ///
+    /// The client of the player receives the difference between the two worlds. If the player was
+    /// the last one in their previous instance, and it is not the main instance, it is removed.
+    pub fn join_instance(&mut self, user_id: &str, instance_id: &str) -> anyhow::Result<()> {
+        let old_instance_id = self
+            .players
+            .get(user_id)
+            .with_context(|| format!("No player with user ID {user_id:?}"))?
+            .instance
+            .clone();
+        if old_instance_id == instance_id {
+            return Ok(());
+        }
+        anyhow::ensure!(
+            self.instances.contains_key(instance_id),
+            "No instance with ID {instance_id:?}"
+        );
+
+        let instances = &mut self.instances;
+
+        // Borrow the new world mutably to broadcast its diffs.
+        instances.get_mut(instance_id).unwrap().broadcast_diffs();
+
+        // Borrow both worlds immutably to extract the old world's player count and the diff
+        // between the two.
+        let (old_player_count, diff) = {
+            let (old_instance, new_instance) = instances
+                .get(&old_instance_id)
+                .zip(instances.get(instance_id))
+                .unwrap();
+            (
+                old_instance.player_count(),
+                WorldDiff::from_a_to_b(
+                    old_instance.world_stream.filter().clone(),
+                    &old_instance.world,
+                    &new_instance.world,
+                ),
+            )
+        };
+
+        // Borrow the old world mutably to remove the player and their streams.
+        let mut ed = instances
+            .get_mut(&old_instance_id)
+            .unwrap()
+            .despawn_player(user_id)
+            .context("The player has no entity in their instance")?;
+        let entities_tx = ed.remove_self(player_entity_stream()).unwrap();
+        let connection_id = ed.remove_self(player_connection_id()).unwrap();
+        let conn = ed.remove_self(player_transport()).unwrap();
+
+        // Borrow the new world mutably to spawn the player in with their old streams.
+        instances
+            .get_mut(instance_id)
+            .unwrap()
+            .spawn_player(create_player_entity_data(
+                conn,
+                user_id.to_string(),
+                entities_tx.clone(),
+                connection_id,
+            ));
+        self.players.get_mut(user_id).unwrap().instance = instance_id.to_string();
+
+        entities_tx.send(diff.into()).ok();
+
+        // Remove old instance
+        if old_player_count == 1 && old_instance_id != MAIN_INSTANCE_ID {
+            self.remove_instance(&old_instance_id);
+        }
+        Ok(())
+    }
+    /// Moves the players of the instance `instance_id` to the main instance, and removes it.
+    pub fn close_instance(&mut self, instance_id: &str) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            instance_id != MAIN_INSTANCE_ID,
+            "The main instance cannot be removed"
+        );
+        anyhow::ensure!(
+            self.instances.contains_key(instance_id),
+            "No instance with ID {instance_id:?}"
+        );
+
+        let user_ids = self
+            .players
+            .iter()
+            .filter(|(_, player)| player.instance == instance_id)
+            .map(|(user_id, _)| user_id.clone())
+            .collect::<Vec<_>>();
+        for user_id in user_ids {
+            self.join_instance(&user_id, MAIN_INSTANCE_ID)?;
+        }
+
+        // Moving the last player out removes the instance
+        if self.instances.contains_key(instance_id) {
+            self.remove_instance(instance_id);
+        }
+        Ok(())
+    }
+    fn apply_instance_requests(&mut self) {
+        let mut requests = Vec::new();
+        for (id, instance) in &mut self.instances {
+            if let Some(instance_requests) = instance.world.resource_mut_opt(instance_requests()) {
+                requests.extend(
+                    std::mem::take(instance_requests)
+                        .into_iter()
+                        .map(|request| (id.clone(), request)),
+                );
+            }
+        }
+
+        for (source_id, request) in requests {
+            let result = match &request {
+                InstanceRequest::Create { id } => {
+                    self.fork_instance(&source_id, id, Entity::new(), Entity::new())
+                }
+                InstanceRequest::Join {
+                    user_id,
+                    instance_id,
+                } => self.join_instance(user_id, instance_id),
+                InstanceRequest::Remove { instance_id } => self.close_instance(instance_id),
+            };
+            if let Err(err) = result {
+                tracing::warn!(
+                    ?request,
+                    "Failed to apply request from instance {source_id:?}: {err:?}"
+                );
+            }
+        }
+    }
+    /// Disconnects the given user, telling their client that they were kicked for `reason`.
+    /// Returns `false` if the user is not connected.
+    pub fn kick(&mut self, user_id: &str, reason: &str) -> bool {
+        let Some(player) = self.players.get(user_id) else {
+            return false;
+        };
+        tracing::info!(user_id, reason, "Kicking player");
+        player.push(ServerPush::Rejected(ConnectionRejection::Kicked {
+            reason: reason.to_string(),
+        }));
+        true
+    }
+    fn apply_kick_requests(&mut self) {
+        let mut requests = Vec::new();
+        for instance in self.instances.values_mut() {
+            if let Some(kick_requests) = instance.world.resource_mut_opt(kick_requests()) {
+                requests.append(kick_requests);
+            }
+        }
+
+        for (user_id, reason) in requests {
+            if !self.kick(&user_id, &reason) {
+                tracing::warn!(%user_id, "Failed to kick player: they are not connected");
+            }
+        }
+    }
     pub fn remove_instance(&mut self, instance_id: &str) {
         tracing::debug!("Removing server instance id={}", instance_id);
         let mut sys = (self.create_shutdown_systems)();
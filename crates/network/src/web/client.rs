@@ -48,6 +48,8 @@ pub struct GameClientView {
     /// The url to connect to
     pub url: String,
     pub user_id: String,
+    /// Whether to join as a spectator, which receives the world state without a player entity
+    pub spectator: bool,
     pub fail_on_version_mismatch: bool,
     pub systems_and_resources: Cb<dyn Fn() -> (SystemGroup, Entity) + Sync + Send>,
     /// Invoked when the game client is loaded
@@ -64,6 +66,7 @@ impl ElementComponent for GameClientView {
         let Self {
             url,
             user_id,
+            spectator,
             fail_on_version_mismatch,
             systems_and_resources,
             on_loaded,
@@ -124,6 +127,7 @@ impl ElementComponent for GameClientView {
                     conn,
                     &assets,
                     user_id,
+                    spectator,
                     fail_on_version_mismatch,
                     move |assets, user_id| {
                         let (systems, resources) = systems_and_resources();
@@ -369,6 +373,7 @@ async fn handle_connection(
     mut conn: Connection,
     assets: &AssetCache,
     user_id: String,
+    spectator: bool,
     fail_on_version_mismatch: bool,
     mut on_loaded: impl FnMut(&AssetCache, &str) -> anyhow::Result<(SharedClientGameState, CleanupFunc)>,
     control_rx: flume::Receiver<Control>,
@@ -384,9 +389,12 @@ async fn handle_connection(
     // Send a connection request
     tracing::debug!("Attempting to connect using {user_id:?}");
 
-    request_send
-        .send(ClientRequest::Connect(user_id.clone()))
-        .await?;
+    let request = if spectator {
+        ClientRequest::ConnectSpectator(user_id.clone())
+    } else {
+        ClientRequest::Connect(user_id.clone())
+    };
+    request_send.send(request).await?;
 
     let mut client = ClientProtoState::Pending(user_id.clone());
 
@@ -462,6 +470,14 @@ async fn handle_connection(
         }
     }
 
+    // The new host is only reachable by native clients
+    if let Some(migration) = client.into_migration() {
+        anyhow::bail!(
+            "The host has left the session, and {} has taken over as the host",
+            migration.successor
+        );
+    }
+
     tracing::debug!("Client entered disconnected state");
     Ok(())
 }
@@ -58,14 +58,20 @@ pub async fn initialize(
     world: &mut World,
     main_package_path: &AbsAssetUrl,
     wasm_spawn: WasmSpawn,
+) -> anyhow::Result<()> {
+    let semantic = ambient_package_semantic::Semantic::new(false).await?;
+    initialize_with_semantic(world, semantic, main_package_path, wasm_spawn)
+}
+
+/// Like [initialize], for when the [Semantic] has to be created before the world can be accessed.
+pub fn initialize_with_semantic(
+    world: &mut World,
+    semantic: Semantic,
+    main_package_path: &AbsAssetUrl,
+    wasm_spawn: WasmSpawn,
 ) -> anyhow::Result<()> {
     world.add_resource(self::wasm_spawn(), wasm_spawn);
-    world.add_resource(
-        self::semantic(),
-        Arc::new(Mutex::new(
-            ambient_package_semantic::Semantic::new(false).await?,
-        )),
-    );
+    world.add_resource(self::semantic(), Arc::new(Mutex::new(semantic)));
 
     add(
         world,
@@ -250,6 +256,25 @@ fn sync_semantic_to_world(
         if let PackageContent::Mod { for_playables } = &manifest.package.content {
             entity.set(self::for_playables(), for_playables.clone());
         }
+        if let Some(endpoint) = &manifest.analytics.endpoint {
+            entity.set(self::analytics_endpoint(), endpoint.clone());
+        }
+        if let Some(background) = &manifest.loading_screen.background {
+            let url = AbsAssetUrl(base_asset_url.clone()).push(format!("assets/{background}"))?;
+            entity.set(self::loading_screen_background(), url.to_string());
+        }
+        if !manifest.loading_screen.tips.is_empty() {
+            entity.set(
+                self::loading_screen_tips(),
+                manifest.loading_screen.tips.clone(),
+            );
+        }
+        if !manifest.http.allowed_domains.is_empty() {
+            entity.set(
+                self::http_allowed_domains(),
+                manifest.http.allowed_domains.clone(),
+            );
+        }
         let entity = entity.spawn(world);
         world
             .synced_resource_mut(package_id_to_package_entity())
@@ -257,6 +282,17 @@ fn sync_semantic_to_world(
             .insert(package_id.clone(), entity);
 
         if let Some(metadata) = &package.build_metadata {
+            world.add_component(
+                entity,
+                self::localized_assets(),
+                metadata.localized_assets.clone(),
+            )?;
+            world.add_component(
+                entity,
+                self::quality_tiers(),
+                metadata.quality_tiers.clone(),
+            )?;
+
             let asset_url = AbsAssetUrl(base_asset_url.clone());
 
             let wasm_spawn = world.resource(self::wasm_spawn()).clone();
@@ -401,9 +437,12 @@ fn all_defined_components(semantic: &Semantic) -> anyhow::Result<Vec<ExternalCom
                     })],
                     name: component.name.clone(),
                     description: component.description.clone(),
-                    attributes: ExternalComponentAttributes::from_iter(
-                        attributes.iter().map(|s| s.as_str()),
-                    ),
+                    attributes: ExternalComponentAttributes {
+                        replication: component.replication,
+                        ..ExternalComponentAttributes::from_iter(
+                            attributes.iter().map(|s| s.as_str()),
+                        )
+                    },
                 });
             }
             Ok(())
@@ -20,7 +20,7 @@ use ambient_native_std::{
 use anyhow::Context;
 use async_trait::async_trait;
 use futures::future::try_join_all;
-use glam::{vec3, Mat4, Quat, Vec3};
+use glam::{Mat4, Quat, Vec3};
 use itertools::Itertools;
 use physxx::{
     AsPxActor, AsPxRigidActor, PxActor, PxActorFlag, PxBase, PxBoxGeometry, PxControllerDesc,
@@ -32,6 +32,7 @@ use physxx::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    helpers::set_ccd_flags,
     main_controller_manager,
     mesh::{PhysxGeometry, PhysxGeometryFromUrl},
     physx::{character_controller, physics, physics_shape, rigid_actor, Physics},
@@ -186,6 +187,10 @@ pub fn server_systems() -> SystemGroup {
                 character_controller_height().changed(),
                 character_controller_radius().changed(),
             ))
+            .optional_changed(character_controller_step_offset())
+            .optional_changed(character_controller_slope_limit())
+            .optional_changed(character_controller_skin_width())
+            .optional_changed(character_controller_up())
             .to_system(|q, world, qs, _| {
                 let all = changed_or_missing(q, world, qs, character_controller());
 
@@ -204,9 +209,23 @@ pub fn server_systems() -> SystemGroup {
                         },
                         physics_material,
                     );
+                    desc.position = pos.as_dvec3();
+                    desc.up_direction = world
+                        .get(id, character_controller_up())
+                        .ok()
+                        .and_then(|up| up.try_normalize())
+                        .unwrap_or(Vec3::Z);
+                    if let Ok(step_offset) = world.get(id, character_controller_step_offset()) {
+                        desc.step_offset = step_offset;
+                    }
+                    if let Ok(slope_limit) = world.get(id, character_controller_slope_limit()) {
+                        // PhysX takes the cosine of the steepest walkable slope
+                        desc.slope_limit = slope_limit.clamp(0., PI / 2.).cos();
+                    }
+                    if let Ok(skin_width) = world.get(id, character_controller_skin_width()) {
+                        desc.contact_offset = skin_width;
+                    }
                     if desc.is_valid() {
-                        desc.position = pos.as_dvec3();
-                        desc.up_direction = vec3(0., 0., 1.);
                         let controller = controller_manager.create_controller(&desc);
                         for shape in controller.get_actor().get_shapes() {
                             shape.set_flag(PxShapeFlag::VISUALIZATION, false);
@@ -308,6 +327,8 @@ pub fn server_systems() -> SystemGroup {
             ))
             .optional_changed(collider_type())
             .optional_changed(kinematic())
+            .optional_changed(ccd_enabled())
+            .optional_changed(trigger())
             .to_system(|q, world, qs, _| {
                 let physics = world.resource(physics()).clone();
                 let force_static = world
@@ -330,6 +351,7 @@ pub fn server_systems() -> SystemGroup {
                         }
                         let is_dynamic = collider_type == ColliderType::Dynamic;
                         let is_kinematic = world.has_component(id, kinematic());
+                        let is_trigger = world.has_component(id, trigger());
                         let actor = if is_dynamic && !force_static {
                             let body = PxRigidDynamicRef::new(
                                 physics.physics,
@@ -352,7 +374,11 @@ pub fn server_systems() -> SystemGroup {
                         };
                         if let Some(actor) = actor.to_rigid_body() {
                             actor.set_rigid_body_flag(PxRigidBodyFlag::KINEMATIC, is_kinematic);
-                            actor.set_rigid_body_flag(PxRigidBodyFlag::ENABLE_CCD, !is_kinematic);
+                            set_ccd_flags(
+                                &actor,
+                                world.get(id, ccd_enabled()).unwrap_or(false),
+                                is_kinematic,
+                            );
                         }
                         actor
                             .as_actor()
@@ -360,7 +386,8 @@ pub fn server_systems() -> SystemGroup {
                         for shape in actor.get_shapes() {
                             actor.detach_shape(&shape, false);
                         }
-                        let shapes = if is_dynamic && !is_kinematic {
+                        // Triangle meshes can't be triggers
+                        let shapes = if (is_dynamic && !is_kinematic) || is_trigger {
                             &mut shapes_convex
                         } else {
                             &mut shapes_concave
@@ -381,6 +408,15 @@ pub fn server_systems() -> SystemGroup {
                             if let Some(roff) = roff {
                                 shape.set_rest_offset(roff);
                             }
+                            // A shape can't be a simulation shape and a trigger at the same time,
+                            // so the flag that is set is cleared first
+                            if is_trigger {
+                                shape.set_flag(PxShapeFlag::SIMULATION_SHAPE, false);
+                                shape.set_flag(PxShapeFlag::TRIGGER_SHAPE, true);
+                            } else {
+                                shape.set_flag(PxShapeFlag::TRIGGER_SHAPE, false);
+                                shape.set_flag(PxShapeFlag::SIMULATION_SHAPE, true);
+                            }
                             shape.update_user_data::<PxShapeUserData>(&|ud| ud.entity = id);
                         }
                         if let Some(actor) = actor.to_rigid_dynamic() {
@@ -460,6 +496,11 @@ pub enum ColliderDef {
         center: Vec3,
     },
     Plane,
+    /// A set of boxes, such as the solid tiles of a tilemap.
+    Boxes {
+        /// The size and center of each box
+        boxes: Vec<(Vec3, Vec3)>,
+    },
 }
 
 type ColliderSpawner = Box<dyn Fn(&Physics, Vec3) -> (Vec<PxShape>, Vec<PxShape>) + Sync + Send>;
@@ -489,31 +530,26 @@ impl ColliderDef {
         let material = PxWoodMaterialKey.get(&assets);
         match self.clone() {
             ColliderDef::Box { size, center } => {
-                if !(size.x > 0. && size.y > 0. && size.z > 0.) {
-                    return Err(anyhow::anyhow!(
-                        "Box collider size has to be more than zero in each dimension. size={}",
-                        size
-                    )
-                    .into());
-                }
+                validate_box_size(size)?;
                 Ok(Box::new(move |physics, scale| {
-                    let size = size * scale;
-                    let geometry = PxBoxGeometry::new(size.x / 2., size.y / 2., size.z / 2.);
-                    let shape =
-                        PxShape::new(physics.physics, &geometry, &[&material], Some(true), None);
-                    shape.set_local_pose(&PxTransform::from_translation(center * scale));
-                    shape.set_user_data(PxShapeUserData {
-                        entity: EntityId::null(),
-                        density,
-                        base_pose: Mat4::from_scale_rotation_translation(
-                            size / scale,
-                            Default::default(),
-                            center * scale,
-                        ),
-                    });
+                    let shape = box_shape(physics, &material, density, size, center, scale);
                     (vec![shape.clone()], vec![shape])
                 }))
             }
+            ColliderDef::Boxes { boxes } => {
+                for (size, _) in &boxes {
+                    validate_box_size(*size)?;
+                }
+                Ok(Box::new(move |physics, scale| {
+                    let shapes = boxes
+                        .iter()
+                        .map(|&(size, center)| {
+                            box_shape(physics, &material, density, size, center, scale)
+                        })
+                        .collect_vec();
+                    (shapes.clone(), shapes)
+                }))
+            }
             ColliderDef::Sphere { radius, center } => {
                 if radius <= 0. {
                     return Err(anyhow::anyhow!("Collider radius must be more than zero").into());
@@ -566,6 +602,37 @@ impl ColliderDef {
     }
 }
 
+fn validate_box_size(size: Vec3) -> Result<(), AssetError> {
+    if !(size.x > 0. && size.y > 0. && size.z > 0.) {
+        return Err(anyhow::anyhow!(
+            "Box collider size has to be more than zero in each dimension. size={}",
+            size
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn box_shape(
+    physics: &Physics,
+    material: &PxMaterial,
+    density: f32,
+    size: Vec3,
+    center: Vec3,
+    scale: Vec3,
+) -> PxShape {
+    let scaled_size = size * scale;
+    let geometry = PxBoxGeometry::new(scaled_size.x / 2., scaled_size.y / 2., scaled_size.z / 2.);
+    let shape = PxShape::new(physics.physics, &geometry, &[material], Some(true), None);
+    shape.set_local_pose(&PxTransform::from_translation(center * scale));
+    shape.set_user_data(PxShapeUserData {
+        entity: EntityId::null(),
+        density,
+        base_pose: Mat4::from_scale_rotation_translation(size, Default::default(), center * scale),
+    });
+    shape
+}
+
 impl Default for ColliderDef {
     fn default() -> Self {
         Self::Sphere {
@@ -1,10 +1,10 @@
 use std::collections::HashSet;
 
 use ambient_core::{
+    delta_time,
     transform::{get_world_position, rotation, translation},
-    FIXED_SERVER_TICK_TIME,
 };
-use ambient_ecs::{query, ECSError, EntityId, World};
+use ambient_ecs::{query, Component, ComponentValue, ECSError, EntityId, World};
 use anyhow::{bail, Context};
 use glam::{vec3, Mat4, Vec3};
 use itertools::Itertools;
@@ -17,6 +17,7 @@ use physxx::{
 };
 
 use crate::{
+    ccd_enabled,
     collider::{collider_shapes_convex, collider_type, kinematic},
     main_physics_scene,
     physx::{
@@ -59,7 +60,11 @@ pub fn convert_rigid_static_dynamic(world: &mut World, id: EntityId, to_dynamic:
     let new_actor = if to_dynamic {
         let actor = PxRigidDynamicRef::new(physics.physics, &old_actor.get_global_pose());
         actor.set_rigid_body_flag(PxRigidBodyFlag::KINEMATIC, is_kinematic);
-        actor.set_rigid_body_flag(PxRigidBodyFlag::ENABLE_CCD, !is_kinematic);
+        set_ccd_flags(
+            &actor,
+            world.get(id, ccd_enabled()).unwrap_or(false),
+            is_kinematic,
+        );
         actor.as_rigid_actor()
     } else {
         PxRigidStaticRef::new(physics.physics, &old_actor.get_global_pose()).as_rigid_actor()
@@ -99,6 +104,16 @@ pub fn convert_rigid_static_dynamic(world: &mut World, id: EntityId, to_dynamic:
     scene.add_actor(&new_actor);
 }
 
+/// Enables continuous collision detection on a body: swept CCD if it is dynamic, and speculative
+/// CCD if it is kinematic, as swept CCD does not support kinematic bodies.
+pub(crate) fn set_ccd_flags(body: &impl PxRigidBody, enabled: bool, is_kinematic: bool) {
+    body.set_rigid_body_flag(PxRigidBodyFlag::ENABLE_CCD, enabled && !is_kinematic);
+    body.set_rigid_body_flag(
+        PxRigidBodyFlag::ENABLE_SPECULATIVE_CCD,
+        enabled && is_kinematic,
+    );
+}
+
 pub fn update_physics_controlled(world: &mut World, actor: PxRigidActorRef) {
     let is_physics_controlled = match actor.to_rigid_dynamic() {
         Some(body) => !body
@@ -572,13 +587,13 @@ impl PhysicsObjectCollection {
                 }
             }
         }
+        let dt = *world.resource(delta_time());
         for &id in &self.units {
             let mass = world.get(id, unit_mass()).unwrap_or(1.);
             let pos = get_world_position(world, id).unwrap();
             let force = get_force(pos);
             let a = force / mass;
-            *world.get_mut(id, unit_velocity()).unwrap() +=
-                a * FIXED_SERVER_TICK_TIME.as_secs_f32();
+            *world.get_mut(id, unit_velocity()).unwrap() += a * dt;
         }
     }
     pub fn add_radial_impulse(
@@ -671,6 +686,21 @@ pub fn random_position_in_actor(world: &World, id: EntityId) -> Option<Vec3> {
     None
 }
 
+/// Sets a component that is updated by the simulation, only marking it as changed when its value
+/// does.
+pub(crate) fn set_state<T: ComponentValue + PartialEq>(
+    world: &mut World,
+    id: EntityId,
+    component: Component<T>,
+    value: T,
+) {
+    if world.has_component(id, component) {
+        world.set_if_changed(id, component, value).unwrap();
+    } else {
+        world.add_component(id, component, value).unwrap();
+    }
+}
+
 pub fn release_px_scene(scene: PxSceneRef) {
     for actor in scene.get_actors(PxActorTypeFlag::all()) {
         actor.release();
@@ -1,21 +1,25 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, f32::consts::FRAC_PI_2};
 
 use ambient_core::{asset_cache, transform::translation};
 use ambient_ecs::{query, ArchetypeFilter, EntityId, World};
 use ambient_meshes::cuboid::CuboidMesh;
 use ambient_native_std::{asset_cache::SyncAssetKeyExt, mesh::Mesh, shapes::Ray};
 use ambient_network::server;
-use glam::Vec3;
+use glam::{Quat, Vec3};
 use itertools::Itertools;
 use ordered_float::OrderedFloat;
 use physxx::{
-    PxConvexFlag, PxConvexMesh, PxConvexMeshDesc, PxConvexMeshGeometry, PxOverlapCallback,
-    PxQueryFilterData, PxRaycastCallback, PxRigidActor, PxShape, PxTransform, PxUserData,
+    PxBoxGeometry, PxCapsuleGeometry, PxConvexFlag, PxConvexMesh, PxConvexMeshDesc,
+    PxConvexMeshGeometry, PxGeometry, PxOverlapCallback, PxQueryFilterData, PxRaycastCallback,
+    PxRigidActor, PxShape, PxSphereGeometry, PxTransform, PxUserData,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{main_physics_scene, physx::PhysicsKey, ColliderScene, PxShapeUserData};
 
+/// The maximum number of shapes that an overlap query ([overlap]) considers.
+pub const MAX_OVERLAP_HITS: usize = 1024;
+
 pub fn get_entities_in_radius(world: &World, center: Vec3, radius: f32) -> Vec<EntityId> {
     query((translation(),))
         .iter(world, None)
@@ -124,6 +128,174 @@ pub fn raycast_collider_type_px(
     Vec::new()
 }
 
+/// The first shape hit by a sweep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepHit {
+    pub entity: EntityId,
+    /// The distance the swept shape travelled before the hit
+    pub distance: f32,
+    /// The point of contact
+    pub position: Vec3,
+    /// The normal of the surface that was hit, at the point of contact
+    pub normal: Vec3,
+}
+
+/// Sweeps `geometry` from `pose` along `direction`, which must be normalized, for up to
+/// `max_distance`, and returns the first shape of the physics scene that it hits.
+///
+/// A sweep that starts out overlapping a shape hits it at a distance of zero.
+pub fn sweep_first(
+    world: &World,
+    geometry: &dyn PxGeometry,
+    pose: &PxTransform,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<SweepHit> {
+    let scene = ColliderScene::Physics.get_scene(world);
+    let hits = scene.sweep(
+        geometry,
+        pose,
+        direction,
+        max_distance,
+        PxQueryFilterData::new(),
+    );
+    hits.block()
+        .into_iter()
+        .chain(hits.touches())
+        .filter_map(|hit| {
+            let entity = hit.shape?.get_user_data::<PxShapeUserData>()?.entity;
+            Some(SweepHit {
+                entity,
+                distance: hit.distance,
+                position: hit.position,
+                normal: hit.normal,
+            })
+        })
+        .min_by_key(|hit| OrderedFloat(hit.distance))
+}
+
+/// Sweeps a sphere of `radius` centered on `origin`; see [sweep_first].
+pub fn sweep_sphere(
+    world: &World,
+    radius: f32,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<SweepHit> {
+    sweep_first(
+        world,
+        &PxSphereGeometry::new(radius),
+        &PxTransform::from_translation(origin),
+        direction,
+        max_distance,
+    )
+}
+
+/// Sweeps a box of `half_extents`, rotated by `rotation`, centered on `origin`; see
+/// [sweep_first].
+pub fn sweep_box(
+    world: &World,
+    half_extents: Vec3,
+    rotation: Quat,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<SweepHit> {
+    sweep_first(
+        world,
+        &PxBoxGeometry::new(half_extents.x, half_extents.y, half_extents.z),
+        &PxTransform::new(origin, rotation),
+        direction,
+        max_distance,
+    )
+}
+
+/// Sweeps a capsule centered on `origin`; see [sweep_first].
+///
+/// Before it is rotated by `rotation`, the capsule is upright: its axis is along Z, and it is
+/// `2 * (half_height + radius)` tall.
+pub fn sweep_capsule(
+    world: &World,
+    radius: f32,
+    half_height: f32,
+    rotation: Quat,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<SweepHit> {
+    // The axis of PhysX capsules is along X
+    let x_to_z = Quat::from_rotation_y(-FRAC_PI_2);
+    sweep_first(
+        world,
+        &PxCapsuleGeometry::new(radius, half_height),
+        &PxTransform::new(origin, rotation * x_to_z),
+        direction,
+        max_distance,
+    )
+}
+
+/// Returns the entities of the shapes of the physics scene that overlap `geometry` at `pose`, each
+/// once.
+///
+/// At most [MAX_OVERLAP_HITS] shapes are considered.
+pub fn overlap(world: &World, geometry: &dyn PxGeometry, pose: PxTransform) -> Vec<EntityId> {
+    let mut hit_call = PxOverlapCallback::new(MAX_OVERLAP_HITS);
+    let scene = ColliderScene::Physics.get_scene(world);
+    if !scene.overlap(geometry, pose, &mut hit_call, &PxQueryFilterData::new()) {
+        return Vec::new();
+    }
+    hit_call
+        .block()
+        .into_iter()
+        .chain(hit_call.touches())
+        .filter_map(|hit| Some(hit.shape.get_user_data::<PxShapeUserData>()?.entity))
+        .unique()
+        .collect()
+}
+
+/// Returns the entities that overlap a sphere of `radius` centered on `origin`; see [overlap].
+pub fn overlap_sphere(world: &World, origin: Vec3, radius: f32) -> Vec<EntityId> {
+    overlap(
+        world,
+        &PxSphereGeometry::new(radius),
+        PxTransform::from_translation(origin),
+    )
+}
+
+/// Returns the entities that overlap a box of `half_extents`, rotated by `rotation`, centered on
+/// `origin`; see [overlap].
+pub fn overlap_box(
+    world: &World,
+    origin: Vec3,
+    half_extents: Vec3,
+    rotation: Quat,
+) -> Vec<EntityId> {
+    overlap(
+        world,
+        &PxBoxGeometry::new(half_extents.x, half_extents.y, half_extents.z),
+        PxTransform::new(origin, rotation),
+    )
+}
+
+/// Returns the entities that overlap a capsule centered on `origin`; see [overlap].
+///
+/// The capsule is oriented like the capsules of [sweep_capsule].
+pub fn overlap_capsule(
+    world: &World,
+    origin: Vec3,
+    radius: f32,
+    half_height: f32,
+    rotation: Quat,
+) -> Vec<EntityId> {
+    // The axis of PhysX capsules is along X
+    let x_to_z = Quat::from_rotation_y(-FRAC_PI_2);
+    overlap(
+        world,
+        &PxCapsuleGeometry::new(radius, half_height),
+        PxTransform::new(origin, rotation * x_to_z),
+    )
+}
+
 pub fn intersect_frustum(world: &World, frustum_corners: &[Vec3; 8]) -> Vec<EntityId> {
     let mut hit_call = PxOverlapCallback::new(1000);
     let filter_data = PxQueryFilterData::new();
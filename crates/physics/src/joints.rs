@@ -0,0 +1,256 @@
+use ambient_ecs::{
+    components, generated::physics::types::JointKind, query, EntityId, FnSystem, SystemGroup, World,
+};
+use glam::{Quat, UVec3, Vec3};
+use physxx::{
+    AsPxJoint, PxBase, PxConstraintFlags, PxD6Axis, PxD6JointRef, PxD6Motion, PxFixedJointRef,
+    PxJoint, PxJointAngularLimitPair, PxJointLimitCone, PxJointLinearLimit, PxJointLinearLimitPair,
+    PxJointRef, PxPhysicsRef, PxPrismaticJointFlag, PxPrismaticJointRef, PxRevoluteJointFlag,
+    PxRevoluteJointRef, PxRigidActor, PxRigidActorRef, PxSphericalJointFlag, PxSphericalJointRef,
+    PxTransform,
+};
+
+use crate::{
+    helpers::get_actor, joint_angular_limits, joint_body_a, joint_body_b, joint_break_force,
+    joint_break_torque, joint_breaks, joint_broken, joint_collision_enabled, joint_cone_limits,
+    joint_d6_angular_motion, joint_d6_linear_motion, joint_distance_limit, joint_drive_velocity,
+    joint_kind, joint_linear_limits, joint_local_anchor_a, joint_local_anchor_b,
+    joint_local_rotation_a, joint_local_rotation_b,
+};
+
+components!("physics", {
+    /// The PhysX joint created from the `joint_*` components of this entity.
+    physics_joint: PxJointRef,
+});
+
+pub fn server_systems() -> SystemGroup {
+    SystemGroup::new(
+        "physics/joints/server",
+        vec![
+            // Joints are recreated when they are reconfigured
+            query(())
+                .incl(physics_joint())
+                .optional_changed(joint_kind())
+                .optional_changed(joint_body_a())
+                .optional_changed(joint_body_b())
+                .optional_changed(joint_local_anchor_a())
+                .optional_changed(joint_local_anchor_b())
+                .optional_changed(joint_local_rotation_a())
+                .optional_changed(joint_local_rotation_b())
+                .optional_changed(joint_linear_limits())
+                .optional_changed(joint_angular_limits())
+                .optional_changed(joint_cone_limits())
+                .optional_changed(joint_distance_limit())
+                .optional_changed(joint_d6_linear_motion())
+                .optional_changed(joint_d6_angular_motion())
+                .optional_changed(joint_break_force())
+                .optional_changed(joint_break_torque())
+                .optional_changed(joint_collision_enabled())
+                .optional_changed(joint_drive_velocity())
+                .to_system(|q, world, qs, _| {
+                    for (id, _) in q.collect_cloned(world, qs) {
+                        release_joint(world, id);
+                    }
+                }),
+            Box::new(FnSystem::new(|world, _| {
+                profiling::scope!("joints");
+                for (id, joint) in query(physics_joint()).collect_cloned(world, None) {
+                    if !world.has_component(id, joint_kind())
+                        || !world.has_component(id, joint_body_a())
+                    {
+                        release_joint(world, id);
+                        continue;
+                    }
+                    // The actors of the bodies are replaced when e.g. their collider changes
+                    let (body_a, body_b) = joint_actors(world, id);
+                    if (body_a, body_b) != joint.get_actors() {
+                        release_joint(world, id);
+                        continue;
+                    }
+                    if joint
+                        .get_constraint_flags()
+                        .contains(PxConstraintFlags::BROKEN)
+                    {
+                        release_joint(world, id);
+                        world.add_component(id, joint_broken(), ()).unwrap();
+                        world.resource_mut(joint_breaks()).push(id);
+                    }
+                }
+
+                // The joints are created once the actors of their bodies exist
+                for (id, kind) in query(joint_kind())
+                    .incl(joint_body_a())
+                    .excl(physics_joint())
+                    .excl(joint_broken())
+                    .collect_cloned(world, None)
+                {
+                    if let Some(joint) = create_joint(world, id, kind) {
+                        world.add_component(id, physics_joint(), joint).unwrap();
+                    }
+                }
+            })),
+            query(physics_joint())
+                .despawned()
+                .to_system(|q, world, qs, _| {
+                    for (id, joint) in q.collect_cloned(world, qs) {
+                        // The joint has already been released if only the component was removed
+                        if !world.exists(id) {
+                            joint.release();
+                        }
+                    }
+                }),
+        ],
+    )
+}
+
+fn release_joint(world: &mut World, id: EntityId) {
+    if let Ok(joint) = world.get(id, physics_joint()) {
+        joint.release();
+        world.remove_component(id, physics_joint()).unwrap();
+    }
+}
+
+/// The actors of the bodies of the joint `id`; the second body is `None` when the joint is
+/// attached to the world.
+fn joint_actors(world: &World, id: EntityId) -> (Option<PxRigidActorRef>, Option<PxRigidActorRef>) {
+    let actor = |body: Option<EntityId>| {
+        body.and_then(|body| get_actor(world, body))
+            .and_then(|actor| actor.to_rigid_actor())
+    };
+    (
+        actor(world.get(id, joint_body_a()).ok()),
+        actor(world.get(id, joint_body_b()).ok()),
+    )
+}
+
+/// Creates the joint `id` from its components, or returns `None` if its bodies don't have actors
+/// yet, or if none of them is dynamic.
+fn create_joint(world: &World, id: EntityId, kind: JointKind) -> Option<PxJointRef> {
+    let body_a = world.get(id, joint_body_a()).ok()?;
+    let body_b = world.get(id, joint_body_b()).ok();
+    let actor_a = get_actor(world, body_a)?;
+    let actor_b = match body_b {
+        Some(body_b) => Some(get_actor(world, body_b)?),
+        None => None,
+    };
+    if actor_a.to_rigid_dynamic().is_none()
+        && !actor_b.map_or(false, |actor| actor.to_rigid_dynamic().is_some())
+    {
+        return None;
+    }
+    let actor_a = actor_a.to_rigid_actor()?;
+    let actor_b = match actor_b {
+        Some(actor_b) => Some(actor_b.to_rigid_actor()?),
+        None => None,
+    };
+
+    let frame_a = PxTransform::new(
+        world.get(id, joint_local_anchor_a()).unwrap_or(Vec3::ZERO),
+        world
+            .get(id, joint_local_rotation_a())
+            .unwrap_or(Quat::IDENTITY)
+            .normalize(),
+    );
+    // Without a second body, the joint is attached to the world where the frame of the first body
+    // currently is, unless the frame is given in world space
+    let frame_b = match (
+        actor_b,
+        world.get(id, joint_local_anchor_b()),
+        world.get(id, joint_local_rotation_b()),
+    ) {
+        (None, Err(_), Err(_)) => {
+            let pose = actor_a.get_global_pose();
+            PxTransform::new(
+                pose.translation() + pose.rotation() * frame_a.translation(),
+                pose.rotation() * frame_a.rotation(),
+            )
+        }
+        (_, anchor, rotation) => PxTransform::new(
+            anchor.unwrap_or(Vec3::ZERO),
+            rotation.unwrap_or(Quat::IDENTITY).normalize(),
+        ),
+    };
+
+    let physics = PxPhysicsRef::get();
+    let actor_a = Some(actor_a);
+    let joint = match kind {
+        JointKind::Fixed => {
+            PxFixedJointRef::new(physics, actor_a, &frame_a, actor_b, &frame_b).as_joint()
+        }
+        JointKind::Revolute => {
+            let joint = PxRevoluteJointRef::new(physics, actor_a, &frame_a, actor_b, &frame_b);
+            if let Ok(limits) = world.get(id, joint_angular_limits()) {
+                joint.set_limit(&PxJointAngularLimitPair::new(limits.x, limits.y, 0.01));
+                joint.set_revolute_flag(PxRevoluteJointFlag::LIMIT_ENABLED, true);
+            }
+            if let Ok(velocity) = world.get(id, joint_drive_velocity()) {
+                joint.set_drive_velocity(velocity, true);
+                joint.set_revolute_flag(PxRevoluteJointFlag::DRIVE_ENABLED, true);
+            }
+            joint.as_joint()
+        }
+        JointKind::Prismatic => {
+            let joint = PxPrismaticJointRef::new(physics, actor_a, &frame_a, actor_b, &frame_b);
+            if let Ok(limits) = world.get(id, joint_linear_limits()) {
+                joint.set_limit(&PxJointLinearLimitPair::new(limits.x, limits.y, 0.01));
+                joint.set_prismatic_flag(PxPrismaticJointFlag::LIMIT_ENABLED, true);
+            }
+            joint.as_joint()
+        }
+        JointKind::Spherical => {
+            let joint = PxSphericalJointRef::new(physics, actor_a, &frame_a, actor_b, &frame_b);
+            if let Ok(limits) = world.get(id, joint_cone_limits()) {
+                joint.set_limit_cone(&PxJointLimitCone::new(limits.x, limits.y, 0.01));
+                joint.set_spherical_flag(PxSphericalJointFlag::LIMIT_ENABLED, true);
+            }
+            joint.as_joint()
+        }
+        JointKind::D6 => {
+            let joint = PxD6JointRef::new(physics, actor_a, &frame_a, actor_b, &frame_b);
+            let linear = world
+                .get(id, joint_d6_linear_motion())
+                .unwrap_or(UVec3::ZERO);
+            let angular = world
+                .get(id, joint_d6_angular_motion())
+                .unwrap_or(UVec3::ZERO);
+            for (axis, motion) in [
+                (PxD6Axis::X, linear.x),
+                (PxD6Axis::Y, linear.y),
+                (PxD6Axis::Z, linear.z),
+                (PxD6Axis::Twist, angular.x),
+                (PxD6Axis::Swing1, angular.y),
+                (PxD6Axis::Swing2, angular.z),
+            ] {
+                joint.set_motion(axis, d6_motion(motion));
+            }
+            if let Ok(distance) = world.get(id, joint_distance_limit()) {
+                joint.set_distance_limit(&PxJointLinearLimit::new(distance, 0.01));
+            }
+            if let Ok(limits) = world.get(id, joint_angular_limits()) {
+                joint.set_twist_limit(&PxJointAngularLimitPair::new(limits.x, limits.y, 0.01));
+            }
+            if let Ok(limits) = world.get(id, joint_cone_limits()) {
+                joint.set_swing_limit(&PxJointLimitCone::new(limits.x, limits.y, 0.01));
+            }
+            joint.as_joint()
+        }
+    };
+
+    joint.set_break_force(
+        world.get(id, joint_break_force()).unwrap_or(f32::MAX),
+        world.get(id, joint_break_torque()).unwrap_or(f32::MAX),
+    );
+    if world.has_component(id, joint_collision_enabled()) {
+        joint.set_constraint_flag(PxConstraintFlags::COLLISION_ENABLED, true);
+    }
+    Some(joint)
+}
+
+/// The motion of a D6 axis: 0 is locked, 1 is limited and anything else is free.
+fn d6_motion(motion: u32) -> PxD6Motion {
+    match motion {
+        0 => PxD6Motion::Locked,
+        1 => PxD6Motion::Limited,
+        _ => PxD6Motion::Free,
+    }
+}
@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
-use ambient_core::{asset_cache, FIXED_SERVER_TICK_TIME};
+use ambient_core::{
+    asset_cache, delta_time,
+    frame_budget::{self, PHYSICS},
+};
 use ambient_ecs::{
     components, query, Debuggable, DynSystem, Entity, EntityId, FnSystem, Resource, SystemGroup,
     World,
@@ -10,6 +13,7 @@ use ambient_network::server::{ForkingEvent, ShutdownEvent};
 use collider::{collider_shapes, collider_shapes_convex};
 use glam::{vec3, Mat4, Vec3};
 use helpers::release_px_scene;
+use material::{DEFAULT_FRICTION, DEFAULT_RESTITUTION};
 use parking_lot::Mutex;
 use physx::{
     actor_aggregate, articulation_cache, articulation_link, articulation_reduce_coordinate,
@@ -18,8 +22,8 @@ use physx::{
 };
 use physxx::{
     AsPxActor, PxContactPairHeader, PxContactPoint, PxControllerManagerRef, PxMaterial,
-    PxPvdSceneFlag, PxRigidActor, PxSceneDesc, PxSceneFlags, PxSceneRef, PxSimulationEventCallback,
-    PxUserData,
+    PxPvdSceneFlag, PxRigidActor, PxSceneDesc, PxSceneFlags, PxSceneRef, PxShape,
+    PxSimulationEventCallback, PxTriggerPair, PxUserData,
 };
 use serde::{Deserialize, Serialize};
 
@@ -28,9 +32,15 @@ use crate::physx::PhysicsKey;
 pub mod collider;
 pub mod helpers;
 pub mod intersection;
+pub mod joints;
+pub mod material;
 pub mod mesh;
+pub mod navigation;
 pub mod physx;
+pub mod platforms;
+pub mod ragdoll;
 pub mod rc_asset;
+pub mod vehicles;
 pub mod visualization;
 
 pub use ambient_ecs::generated::physics::components::*;
@@ -48,11 +58,22 @@ components!("physics", {
     wood_physics_material: PxMaterial,
     @[Debuggable, Resource]
     collisions: Arc<Mutex<Vec<(EntityId, EntityId, Vec<Vec3>, Vec<Vec3>)>>>,
+    @[Debuggable, Resource]
+    trigger_events: Arc<Mutex<Vec<TriggerEvent>>>,
+    /// Whether a step of the [main_physics_scene] has been started, and its results have not been
+    /// fetched yet.
+    @[Debuggable, Resource]
+    simulation_running: bool,
 });
 pub fn init_all_components() {
     init_components();
     physx::init_components();
     collider::init_components();
+    joints::init_components();
+    material::init_components();
+    navigation::init_components();
+    platforms::init_components();
+    ragdoll::init_components();
     visualization::init_components();
 }
 
@@ -67,8 +88,10 @@ pub fn create_server_resources(assets: &AssetCache, server_resources: &mut Entit
     main_scene_desc.update_flags(|flags| flags | PxSceneFlags::ENABLE_CCD);
     main_scene_desc.set_filter_shader(main_physx_scene_filter_shader, true);
     let collisions = Arc::new(Mutex::new(Vec::new()));
+    let trigger_events = Arc::new(Mutex::new(Vec::new()));
     {
         let collisions = collisions.clone();
+        let trigger_events = trigger_events.clone();
         main_scene_desc.set_simulation_event_callbacks(PxSimulationEventCallback {
             collision_callback: Some(Box::new(
                 move |header: &PxContactPairHeader, contacts: Vec<PxContactPoint>| {
@@ -96,11 +119,35 @@ pub fn create_server_resources(assets: &AssetCache, server_resources: &mut Entit
                     }
                 },
             )),
+            trigger_callback: Some(Box::new(move |pairs: &[PxTriggerPair]| {
+                let mut trigger_events = trigger_events.lock();
+                for pair in pairs {
+                    let entity = |shape: &Option<PxShape>| {
+                        shape
+                            .as_ref()
+                            .and_then(|s| s.get_user_data::<PxShapeUserData>())
+                            .map(|ud| ud.entity)
+                    };
+                    // The pairs of removed shapes are skipped, as their entities can't be known
+                    if let (Some(trigger), Some(other)) =
+                        (entity(&pair.trigger_shape), entity(&pair.other_shape))
+                    {
+                        trigger_events.push(TriggerEvent {
+                            trigger,
+                            other,
+                            entered: pair.touch_found,
+                        });
+                    }
+                }
+            })),
         });
     }
     let main_scene = PxSceneRef::new(&physics.physics, &main_scene_desc);
     server_resources.set(self::collisions(), collisions);
+    server_resources.set(self::simulation_running(), false);
+    server_resources.set(self::trigger_events(), trigger_events);
     server_resources.set(self::collider_loads(), vec![]);
+    server_resources.set(self::joint_breaks(), vec![]);
 
     main_scene.get_scene_pvd_client().set_scene_pvd_flags(
         PxPvdSceneFlag::TRANSMIT_CONSTRAINTS
@@ -124,8 +171,15 @@ pub fn create_server_resources(assets: &AssetCache, server_resources: &mut Entit
     server_resources.set(self::main_controller_manager(), main_controller_manager);
     server_resources.set(
         self::wood_physics_material(),
-        PxMaterial::new(physics.physics, 0.5, 0.5, 0.6),
+        PxMaterial::new(
+            physics.physics,
+            DEFAULT_FRICTION,
+            DEFAULT_FRICTION,
+            DEFAULT_RESTITUTION,
+        ),
     );
+    server_resources.set(material::physics_materials(), Default::default());
+    server_resources.set(navigation::navmeshes(), Default::default());
 }
 
 #[derive(Debug, Clone)]
@@ -146,6 +200,14 @@ impl Default for PxShapeUserData {
     }
 }
 
+/// A collider that started or stopped overlapping a `trigger` during the last physics step.
+#[derive(Debug, Clone, Copy)]
+pub struct TriggerEvent {
+    pub trigger: EntityId,
+    pub other: EntityId,
+    pub entered: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct PxActorUserData {
     pub serialize: bool,
@@ -187,13 +249,24 @@ pub struct PxWoodMaterialKey;
 impl SyncAssetKey<PxMaterial> for PxWoodMaterialKey {
     fn load(&self, assets: AssetCache) -> PxMaterial {
         let physics = PhysicsKey.get(&assets);
-        PxMaterial::new(physics.physics, 0.5, 0.5, 0.6)
+        PxMaterial::new(
+            physics.physics,
+            DEFAULT_FRICTION,
+            DEFAULT_FRICTION,
+            DEFAULT_RESTITUTION,
+        )
     }
 }
 
 unsafe extern "C" fn main_physx_scene_filter_shader(
     info: *mut physxx::sys::FilterShaderCallbackInfo,
 ) -> u16 {
+    // The pairs of triggers keep the flags of the default filter shader, as they can't have
+    // contacts
+    let trigger = physxx::sys::PxFilterObjectFlag::eTRIGGER as u32;
+    if ((*info).attributes0 | (*info).attributes1) & trigger != 0 {
+        return (physxx::sys::PxFilterFlag::eDEFAULT) as u16;
+    }
     (*(*info).pairFlags).mBits |= (physxx::sys::PxPairFlag::eSOLVE_CONTACT
         | physxx::sys::PxPairFlag::eDETECT_DISCRETE_CONTACT
         | physxx::sys::PxPairFlag::eDETECT_CCD_CONTACT
@@ -234,34 +307,58 @@ pub fn server_systems() -> SystemGroup {
                     }
                 }),
             Box::new(collider::server_systems()),
+            Box::new(material::server_systems()),
+            Box::new(joints::server_systems()),
+            Box::new(platforms::server_systems()),
+            Box::new(navigation::server_systems()),
+            Box::new(vehicles::server_systems()),
+            Box::new(ragdoll::server_systems()),
             Box::new(visualization::server_systems()),
         ],
     )
 }
 
-/// Starts the physx simulation step concurrently.
+/// Starts the physx simulation step on the worker threads of the physx dispatcher.
+///
+/// This should be the last system of the tick, so that the step runs while the server replicates
+/// the world and waits for the next tick, and while the other instances are stepped. No system
+/// may access the physx scene until the results have been fetched by [`fetch_simulation_system`]
+/// at the start of the next tick.
 ///
-/// Results will be available after [`fetch_simulation_system`]
+/// There is no separate physics thread or second copy of the results, so a step that takes longer
+/// than the rest of the tick still delays the next one.
 pub fn run_simulation_system() -> DynSystem {
     Box::new(FnSystem::new(|world, _| {
         profiling::scope!("run_simulation_system");
-        let scene = world.resource(main_physics_scene());
-        scene.simulate(FIXED_SERVER_TICK_TIME.as_secs_f32());
+        frame_budget::measure(world, PHYSICS, |world| {
+            let scene = world.resource(main_physics_scene());
+            // The server's delta time is always its fixed tick time
+            scene.simulate(*world.resource(delta_time()));
+        });
+        *world.resource_mut(simulation_running()) = true;
     }))
 }
 
-/// Ensures the physx simulation data is available.
+/// Waits for the physx simulation step started by [`run_simulation_system`] in the previous tick,
+/// and makes its results available to the rest of the tick.
 ///
-/// Must only be called once per [`run_simulation_system`]
+/// This should be the first system of the tick.
 pub fn fetch_simulation_system() -> DynSystem {
     Box::new(FnSystem::new(|world, _| {
         profiling::scope!("fetch_simulation_system");
 
         world.resource(collisions()).lock().clear();
+        world.resource(trigger_events()).lock().clear();
         world.resource_mut(collider_loads()).clear();
-        let scene = world.resource(main_physics_scene());
-        // Ensure the previous simulation has completed
-        scene.fetch_results(true);
+        world.resource_mut(joint_breaks()).clear();
+        if !*world.resource(simulation_running()) {
+            return;
+        }
+        frame_budget::measure(world, PHYSICS, |world| {
+            let scene = world.resource(main_physics_scene());
+            scene.fetch_results(true);
+        });
+        *world.resource_mut(simulation_running()) = false;
     }))
 }
 
@@ -297,6 +394,12 @@ pub fn on_forking_systems() -> SystemGroup<ForkingEvent> {
             for (id, _) in query(()).incl(revolute_joint()).collect_cloned(world, None) {
                 world.remove_component(id, revolute_joint()).unwrap();
             }
+            for (id, _) in query(())
+                .incl(joints::physics_joint())
+                .collect_cloned(world, None)
+            {
+                world.remove_component(id, joints::physics_joint()).unwrap();
+            }
             for (id, _) in query(())
                 .incl(articulation_reduce_coordinate())
                 .collect_cloned(world, None)
@@ -317,6 +420,14 @@ pub fn on_forking_systems() -> SystemGroup<ForkingEvent> {
             {
                 world.remove_component(id, articulation_cache()).unwrap();
             }
+            for (id, _) in query(())
+                .incl(ragdoll::ragdoll_articulation())
+                .collect_cloned(world, None)
+            {
+                world
+                    .remove_component(id, ragdoll::ragdoll_articulation())
+                    .unwrap();
+            }
             for (id, _) in query(())
                 .incl(character_controller())
                 .collect_cloned(world, None)
@@ -344,7 +455,9 @@ pub fn on_shutdown_systems() -> SystemGroup<ShutdownEvent> {
     SystemGroup::new(
         "physics/on_shutdown_systems",
         vec![Box::new(FnSystem::new(|world, _| {
-            world.resource(main_physics_scene()).fetch_results(true);
+            if *world.resource(simulation_running()) {
+                world.resource(main_physics_scene()).fetch_results(true);
+            }
             release_px_scene(*world.resource(main_physics_scene()));
             release_px_scene(*world.resource(picking_scene()));
             release_px_scene(*world.resource(trigger_areas_scene()));
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use ambient_ecs::{
+    components, generated::physics::types::CombineMode, query, EntityId, Resource, SystemGroup,
+    World,
+};
+use ordered_float::OrderedFloat;
+use physxx::{PxCombineMode, PxMaterial, PxPhysicsRef};
+
+use crate::{
+    collider::{collider_shapes, collider_shapes_convex},
+    friction, friction_combine_mode,
+    physx::physics,
+    restitution, restitution_combine_mode, static_friction, wood_physics_material,
+};
+
+pub const DEFAULT_FRICTION: f32 = 0.5;
+pub const DEFAULT_RESTITUTION: f32 = 0.6;
+
+components!("physics", {
+    /// The materials created for the entities with material components, which are shared by the
+    /// entities with the same values.
+    @[Resource]
+    physics_materials: HashMap<PhysicsMaterialKey, PxMaterial>,
+});
+
+pub fn server_systems() -> SystemGroup {
+    SystemGroup::new(
+        "physics/material/server",
+        vec![query(collider_shapes().changed())
+            .optional_changed(collider_shapes_convex())
+            .optional_changed(friction())
+            .optional_changed(static_friction())
+            .optional_changed(restitution())
+            .optional_changed(friction_combine_mode())
+            .optional_changed(restitution_combine_mode())
+            .to_system(|q, world, qs, _| {
+                for (id, shapes) in q.collect_cloned(world, qs) {
+                    let material = entity_material(world, id);
+                    let convex = world
+                        .get_ref(id, collider_shapes_convex())
+                        .cloned()
+                        .unwrap_or_default();
+                    for shape in shapes.iter().chain(&convex) {
+                        shape.set_materials(&[&material]);
+                    }
+                }
+            })],
+    )
+}
+
+/// The values of the material components of an entity, with their defaults filled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PhysicsMaterialKey {
+    static_friction: OrderedFloat<f32>,
+    dynamic_friction: OrderedFloat<f32>,
+    restitution: OrderedFloat<f32>,
+    friction_combine_mode: PxCombineMode,
+    restitution_combine_mode: PxCombineMode,
+}
+impl PhysicsMaterialKey {
+    fn get(world: &World, id: EntityId) -> Self {
+        let dynamic_friction = world
+            .get(id, friction())
+            .unwrap_or(DEFAULT_FRICTION)
+            .max(0.);
+        Self {
+            static_friction: OrderedFloat(
+                world
+                    .get(id, static_friction())
+                    .unwrap_or(dynamic_friction)
+                    .max(0.),
+            ),
+            dynamic_friction: OrderedFloat(dynamic_friction),
+            restitution: OrderedFloat(
+                world
+                    .get(id, restitution())
+                    .unwrap_or(DEFAULT_RESTITUTION)
+                    .clamp(0., 1.),
+            ),
+            friction_combine_mode: world
+                .get(id, friction_combine_mode())
+                .map_or(PxCombineMode::Average, px_combine_mode),
+            restitution_combine_mode: world
+                .get(id, restitution_combine_mode())
+                .map_or(PxCombineMode::Average, px_combine_mode),
+        }
+    }
+
+    fn is_default(&self) -> bool {
+        *self
+            == Self {
+                static_friction: OrderedFloat(DEFAULT_FRICTION),
+                dynamic_friction: OrderedFloat(DEFAULT_FRICTION),
+                restitution: OrderedFloat(DEFAULT_RESTITUTION),
+                friction_combine_mode: PxCombineMode::Average,
+                restitution_combine_mode: PxCombineMode::Average,
+            }
+    }
+
+    fn create(&self, physics: PxPhysicsRef) -> PxMaterial {
+        let material = PxMaterial::new(
+            physics,
+            self.static_friction.0,
+            self.dynamic_friction.0,
+            self.restitution.0,
+        );
+        material.set_friction_combine_mode(self.friction_combine_mode);
+        material.set_restitution_combine_mode(self.restitution_combine_mode);
+        material
+    }
+}
+
+/// The material of the colliders of an entity. Entities without material components use the
+/// default material.
+pub fn entity_material(world: &mut World, id: EntityId) -> PxMaterial {
+    let key = PhysicsMaterialKey::get(world, id);
+    if key.is_default() {
+        return world.resource(wood_physics_material()).clone();
+    }
+    let physics = world.resource(physics()).physics;
+    world
+        .resource_mut(physics_materials())
+        .entry(key)
+        .or_insert_with(|| key.create(physics))
+        .clone()
+}
+
+fn px_combine_mode(mode: CombineMode) -> PxCombineMode {
+    match mode {
+        CombineMode::Average => PxCombineMode::Average,
+        CombineMode::Min => PxCombineMode::Min,
+        CombineMode::Multiply => PxCombineMode::Multiply,
+        CombineMode::Max => PxCombineMode::Max,
+    }
+}
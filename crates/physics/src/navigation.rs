@@ -0,0 +1,894 @@
+//! Navigation meshes, which are baked from the static colliders within the bounds of the entities
+//! with a `navmesh`, and the agents that follow the paths found on them.
+//!
+//! A navigation mesh is a heightfield in the style of Recast: its bounds are divided into columns
+//! of `navmesh_cell_size`, and each column holds a cell for every walkable surface that a ray cast
+//! down through it hits. Neighbouring cells are linked when an agent can step from one to the
+//! other, and paths are found with A* over the links, then shortened by skipping the waypoints
+//! that are in line of sight.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    f32::consts::{FRAC_PI_2, FRAC_PI_4, SQRT_2},
+    sync::Arc,
+};
+
+use ambient_core::{delta_time, transform::translation};
+use ambient_ecs::{
+    components, generated::physics::types::NavAgentStatus, query, EntityId, FnSystem, Resource,
+    SystemGroup, World,
+};
+use glam::{Vec2, Vec3, Vec3Swizzles};
+use ordered_float::OrderedFloat;
+use physxx::{
+    PxControllerFilters, PxQueryFilterData, PxQueryFlag, PxRaycastCallback, PxSceneRef, PxUserData,
+};
+
+use crate::{
+    collider::collider_shapes, dynamic, helpers::set_state, main_physics_scene, nav_agent_path,
+    nav_agent_speed, nav_agent_status, nav_agent_target, nav_agent_velocity, navmesh,
+    navmesh_agent_height, navmesh_agent_radius, navmesh_baked, navmesh_bounds_max,
+    navmesh_bounds_min, navmesh_cell_size, navmesh_ignored, navmesh_max_slope, navmesh_step_height,
+    navmesh_walkable, physx::character_controller, PxShapeUserData, GRAVITY,
+};
+
+pub const DEFAULT_CELL_SIZE: f32 = 0.25;
+pub const DEFAULT_AGENT_RADIUS: f32 = 0.5;
+pub const DEFAULT_AGENT_HEIGHT: f32 = 2.0;
+pub const DEFAULT_MAX_SLOPE: f32 = FRAC_PI_4;
+pub const DEFAULT_STEP_HEIGHT: f32 = 0.3;
+pub const DEFAULT_AGENT_SPEED: f32 = 3.;
+
+/// How long (seconds) the colliders must stay unchanged before a navigation mesh is baked, so
+/// that the colliders of a level which load over several frames only cause one bake.
+const BAKE_DELAY: f32 = 0.25;
+/// The maximum number of columns of a navigation mesh.
+const MAX_COLUMNS: usize = 1 << 22;
+/// The maximum number of shapes that the ray of a column considers.
+const MAX_COLUMN_HITS: usize = 32;
+/// How far above a surface the ray that checks the headroom of the agents starts.
+const HEADROOM_OFFSET: f32 = 0.01;
+
+/// The value of a link to a cell that doesn't exist.
+const NO_LINK: u32 = u32::MAX;
+/// The offsets of the columns that the links of a cell lead to, in the order of [Cell::links].
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+components!("physics", {
+    /// The baked navigation meshes, by the entities that they were baked for.
+    @[Resource]
+    navmeshes: NavMeshes,
+    /// The time (seconds) left before the navigation mesh of this entity is baked.
+    navmesh_bake_timer: f32,
+    /// The target and navigation meshes that the path of an agent was found with.
+    nav_agent_plan: NavAgentPlan,
+});
+
+pub fn server_systems() -> SystemGroup {
+    SystemGroup::new(
+        "physics/navigation/server",
+        vec![
+            query(navmesh().changed())
+                .optional_changed(navmesh_bounds_min())
+                .optional_changed(navmesh_bounds_max())
+                .optional_changed(navmesh_cell_size())
+                .optional_changed(navmesh_agent_radius())
+                .optional_changed(navmesh_agent_height())
+                .optional_changed(navmesh_max_slope())
+                .optional_changed(navmesh_step_height())
+                .to_system(|q, world, qs, _| {
+                    for (id, _) in q.collect_cloned(world, qs) {
+                        world
+                            .add_component(id, navmesh_bake_timer(), BAKE_DELAY)
+                            .unwrap();
+                    }
+                }),
+            query(collider_shapes().changed()).to_system(|q, world, qs, _| {
+                let changed = q
+                    .iter(world, qs)
+                    .any(|(id, _)| !world.get(id, dynamic()).unwrap_or(false));
+                if changed {
+                    schedule_bakes(world);
+                }
+            }),
+            query(collider_shapes())
+                .excl(dynamic())
+                .despawned()
+                .to_system(|q, world, qs, _| {
+                    if q.iter(world, qs).next().is_some() {
+                        schedule_bakes(world);
+                    }
+                }),
+            query(navmesh()).despawned().to_system(|q, world, qs, _| {
+                for (id, _) in q.collect_cloned(world, qs) {
+                    let navmeshes = world.resource_mut(navmeshes());
+                    if navmeshes.meshes.remove(&id).is_some() {
+                        navmeshes.generation += 1;
+                    }
+                }
+            }),
+            Box::new(FnSystem::new(|world, _| {
+                let dt = *world.resource(delta_time());
+                for (id, timer) in query(navmesh_bake_timer()).collect_cloned(world, None) {
+                    if timer > 0. {
+                        world.set(id, navmesh_bake_timer(), timer - dt).unwrap();
+                        continue;
+                    }
+                    world.remove_component(id, navmesh_bake_timer()).unwrap();
+                    bake(world, id);
+                }
+            })),
+            Box::new(FnSystem::new(|world, _| {
+                profiling::scope!("nav_agents");
+                update_agents(world);
+            })),
+        ],
+    )
+}
+
+/// The navigation meshes of a world.
+#[derive(Debug, Clone, Default)]
+pub struct NavMeshes {
+    meshes: HashMap<EntityId, Arc<NavMesh>>,
+    /// Incremented whenever a navigation mesh is baked or removed, so that the agents find their
+    /// paths again.
+    generation: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavAgentPlan {
+    target: Vec3,
+    generation: u64,
+}
+
+/// Finds a path from `start` to `end` on the first navigation mesh that both are on.
+///
+/// The path starts at `start` and ends at `end`, both moved onto the navigation mesh.
+pub fn find_path(world: &World, start: Vec3, end: Vec3) -> Option<Vec<Vec3>> {
+    world
+        .resource(navmeshes())
+        .meshes
+        .values()
+        .find_map(|mesh| mesh.find_path(start, end))
+}
+
+/// The point of the navigation meshes which is the closest to `point`, within `max_distance`.
+pub fn nearest_point(world: &World, point: Vec3, max_distance: f32) -> Option<Vec3> {
+    world
+        .resource(navmeshes())
+        .meshes
+        .values()
+        .filter_map(|mesh| mesh.nearest_point(point, max_distance))
+        .min_by_key(|nearest| OrderedFloat(nearest.distance_squared(point)))
+}
+
+/// Makes all of the navigation meshes be baked again, once the colliders stop changing.
+fn schedule_bakes(world: &mut World) {
+    for (id, _) in query(navmesh()).collect_cloned(world, None) {
+        world
+            .add_component(id, navmesh_bake_timer(), BAKE_DELAY)
+            .unwrap();
+    }
+}
+
+fn bake(world: &mut World, id: EntityId) {
+    profiling::scope!("bake_navmesh");
+    let Some(config) = NavMeshConfig::get(world, id) else {
+        tracing::warn!("Navmesh {id} has no navmesh_bounds_min and navmesh_bounds_max");
+        return;
+    };
+    let mesh = match NavMesh::bake(world, config) {
+        Ok(mesh) => mesh,
+        Err(err) => {
+            tracing::warn!("Failed to bake navmesh {id}: {err:#}");
+            return;
+        }
+    };
+    tracing::debug!("Baked navmesh {id} with {} cells", mesh.cells.len());
+    let navmeshes = world.resource_mut(navmeshes());
+    navmeshes.meshes.insert(id, Arc::new(mesh));
+    navmeshes.generation += 1;
+    if !world.has_component(id, navmesh_baked()) {
+        world.add_component(id, navmesh_baked(), ()).unwrap();
+    }
+}
+
+/// Moves the agents along their paths, which are found again when their target or the navigation
+/// meshes change.
+fn update_agents(world: &mut World) {
+    let dt = *world.resource(delta_time());
+    let generation = world.resource(navmeshes()).generation;
+    for (id, target) in query(nav_agent_target()).collect_cloned(world, None) {
+        let controller = world.get_ref(id, character_controller()).ok().cloned();
+        let position = match &controller {
+            Some(controller) => controller.get_foot_position().as_vec3(),
+            None => match world.get(id, translation()) {
+                Ok(position) => position,
+                Err(_) => continue,
+            },
+        };
+
+        let plan = NavAgentPlan { target, generation };
+        if world.get(id, nav_agent_plan()).ok() != Some(plan) {
+            let path = find_path(world, position, target);
+            let status = if path.is_some() {
+                NavAgentStatus::Moving
+            } else {
+                NavAgentStatus::Unreachable
+            };
+            // The first waypoint is where the agent already is
+            let waypoints = path.map(|path| path[1..].to_vec()).unwrap_or_default();
+            set_state(world, id, nav_agent_path(), waypoints);
+            set_state(world, id, nav_agent_status(), status);
+            set_state(world, id, nav_agent_plan(), plan);
+        }
+
+        let mut path = world.get_cloned(id, nav_agent_path()).unwrap_or_default();
+        if path.is_empty() {
+            set_state(world, id, nav_agent_velocity(), Vec3::ZERO);
+            if world.get(id, nav_agent_status()).ok() == Some(NavAgentStatus::Moving) {
+                set_state(world, id, nav_agent_status(), NavAgentStatus::Arrived);
+            }
+            continue;
+        }
+
+        // The waypoints are reached when the agent is above or below them, as the feet of
+        // character controllers hover slightly above the ground
+        let mut remaining = world
+            .get(id, nav_agent_speed())
+            .unwrap_or(DEFAULT_AGENT_SPEED)
+            .max(0.)
+            * dt;
+        let mut goal = position;
+        while let Some(&waypoint) = path.first() {
+            let distance = (waypoint - goal).xy().length();
+            if distance > remaining {
+                goal += (waypoint - goal) * (remaining / distance);
+                break;
+            }
+            remaining -= distance;
+            goal = waypoint;
+            path.remove(0);
+        }
+
+        let new_position = match &controller {
+            Some(controller) => {
+                // Character controllers keep to the ground, and slide along the obstacles that
+                // weren't there when the path was found
+                let displacement = (goal - position).xy().extend(-GRAVITY * dt);
+                controller.move_controller(displacement, 0., dt, &PxControllerFilters::new(), None);
+                controller.get_foot_position().as_vec3()
+            }
+            None => goal,
+        };
+        if world.has_component(id, translation()) {
+            world.set(id, translation(), new_position).unwrap();
+        }
+        if dt > 0. {
+            set_state(
+                world,
+                id,
+                nav_agent_velocity(),
+                (new_position - position) / dt,
+            );
+        }
+        if path.is_empty() {
+            set_state(world, id, nav_agent_status(), NavAgentStatus::Arrived);
+        }
+        set_state(world, id, nav_agent_path(), path);
+    }
+}
+
+/// The settings of a navigation mesh, from the components of its entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavMeshConfig {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub cell_size: f32,
+    pub agent_radius: f32,
+    pub agent_height: f32,
+    pub max_slope: f32,
+    pub step_height: f32,
+}
+impl NavMeshConfig {
+    fn get(world: &World, id: EntityId) -> Option<Self> {
+        let (a, b) = (
+            world.get(id, navmesh_bounds_min()).ok()?,
+            world.get(id, navmesh_bounds_max()).ok()?,
+        );
+        Some(Self {
+            min: a.min(b),
+            max: a.max(b),
+            cell_size: world
+                .get(id, navmesh_cell_size())
+                .unwrap_or(DEFAULT_CELL_SIZE)
+                .max(0.01),
+            agent_radius: world
+                .get(id, navmesh_agent_radius())
+                .unwrap_or(DEFAULT_AGENT_RADIUS)
+                .max(0.),
+            agent_height: world
+                .get(id, navmesh_agent_height())
+                .unwrap_or(DEFAULT_AGENT_HEIGHT)
+                .max(0.),
+            max_slope: world
+                .get(id, navmesh_max_slope())
+                .unwrap_or(DEFAULT_MAX_SLOPE)
+                .clamp(0., FRAC_PI_2 - 0.01),
+            step_height: world
+                .get(id, navmesh_step_height())
+                .unwrap_or(DEFAULT_STEP_HEIGHT)
+                .max(0.),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Cell {
+    column: u32,
+    height: f32,
+    /// The cells that an agent can step to in the +X, +Y, -X and -Y directions.
+    links: [u32; 4],
+}
+
+#[derive(Debug)]
+pub struct NavMesh {
+    config: NavMeshConfig,
+    width: usize,
+    length: usize,
+    /// The cells of each column, by increasing height.
+    columns: Vec<Vec<u32>>,
+    cells: Vec<Cell>,
+}
+impl NavMesh {
+    /// Bakes a navigation mesh from the static colliders of the main physics scene.
+    ///
+    /// The surfaces of the colliders with a `navmesh_walkable` are the only walkable ones if there
+    /// are any, and the colliders with a `navmesh_ignored` are skipped.
+    pub fn bake(world: &World, config: NavMeshConfig) -> anyhow::Result<Self> {
+        let size = (config.max - config.min).xy() / config.cell_size;
+        let (width, length) = (size.x.ceil() as usize, size.y.ceil() as usize);
+        if width * length > MAX_COLUMNS {
+            anyhow::bail!(
+                "The navmesh has {} columns, more than the maximum of {MAX_COLUMNS}; increase its \
+                 cell size or reduce its bounds",
+                width * length
+            );
+        }
+        let walkable: HashSet<EntityId> = query(navmesh_walkable())
+            .iter(world, None)
+            .map(|(id, _)| id)
+            .collect();
+        let ignored: HashSet<EntityId> = query(navmesh_ignored())
+            .iter(world, None)
+            .map(|(id, _)| id)
+            .collect();
+        let scene = world.resource(main_physics_scene());
+        let min_normal_z = config.max_slope.cos();
+
+        let mut heights = vec![Vec::new(); width * length];
+        for (column, column_heights) in heights.iter_mut().enumerate() {
+            let center = config.min.xy() + column_center(column, width) * config.cell_size;
+            let mut surfaces = static_raycast(
+                scene,
+                &ignored,
+                center.extend(config.max.z),
+                -Vec3::Z,
+                config.max.z - config.min.z,
+            );
+            surfaces.sort_by_key(|surface| OrderedFloat(surface.position.z));
+            for surface in surfaces {
+                // Rays that start inside of a shape hit it at their origin
+                if surface.distance <= 0. || surface.normal.z < min_normal_z {
+                    continue;
+                }
+                if !walkable.is_empty() && !surface.entity.is_some_and(|e| walkable.contains(&e)) {
+                    continue;
+                }
+                let headroom = static_raycast(
+                    scene,
+                    &ignored,
+                    surface.position + Vec3::Z * HEADROOM_OFFSET,
+                    Vec3::Z,
+                    config.agent_height,
+                );
+                if !headroom.is_empty() {
+                    continue;
+                }
+                column_heights.push(surface.position.z);
+            }
+        }
+        Ok(Self::from_heights(config, width, length, heights))
+    }
+
+    /// Builds a navigation mesh from the heights of the walkable surfaces of each column of a
+    /// `width` by `length` grid, by increasing height.
+    fn from_heights(
+        config: NavMeshConfig,
+        width: usize,
+        length: usize,
+        heights: Vec<Vec<f32>>,
+    ) -> Self {
+        let mut cells = Vec::new();
+        let columns = heights
+            .into_iter()
+            .enumerate()
+            .map(|(column, column_heights)| {
+                column_heights
+                    .into_iter()
+                    .map(|height| {
+                        cells.push(Cell {
+                            column: column as u32,
+                            height,
+                            links: [NO_LINK; 4],
+                        });
+                        cells.len() as u32 - 1
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut mesh = Self {
+            config,
+            width,
+            length,
+            columns,
+            cells,
+        };
+        mesh.link_cells();
+        mesh.erode();
+        mesh
+    }
+
+    /// Links the cells to the cells of the neighbouring columns that are within a step or slope of
+    /// them.
+    fn link_cells(&mut self) {
+        let max_climb = self
+            .config
+            .step_height
+            .max(self.config.cell_size * self.config.max_slope.tan());
+        for index in 0..self.cells.len() {
+            let cell = self.cells[index];
+            for (direction, offset) in DIRECTIONS.into_iter().enumerate() {
+                let Some(column) = self.offset_column(cell.column, offset) else {
+                    continue;
+                };
+                let link = self.columns[column]
+                    .iter()
+                    .copied()
+                    .map(|other| {
+                        (
+                            other,
+                            (self.cells[other as usize].height - cell.height).abs(),
+                        )
+                    })
+                    .filter(|&(_, climb)| climb <= max_climb)
+                    .min_by_key(|&(_, climb)| OrderedFloat(climb));
+                if let Some((other, _)) = link {
+                    self.cells[index].links[direction] = other;
+                }
+            }
+        }
+    }
+
+    /// Removes the cells that are closer than the radius of the agents to an edge of the
+    /// navigation mesh, so that the agents which follow the paths don't run into walls.
+    fn erode(&mut self) {
+        let mut distances = vec![f32::INFINITY; self.cells.len()];
+        let mut queue = BinaryHeap::new();
+        for (index, cell) in self.cells.iter().enumerate() {
+            if cell.links.contains(&NO_LINK) {
+                distances[index] = 0.;
+                queue.push(Reverse((OrderedFloat(0.), index as u32)));
+            }
+        }
+        while let Some(Reverse((OrderedFloat(distance), index))) = queue.pop() {
+            if distance > distances[index as usize] {
+                continue;
+            }
+            for (neighbour, steps) in self.neighbours(index) {
+                let neighbour_distance = distance + steps;
+                if neighbour_distance < distances[neighbour as usize] {
+                    distances[neighbour as usize] = neighbour_distance;
+                    queue.push(Reverse((OrderedFloat(neighbour_distance), neighbour)));
+                }
+            }
+        }
+
+        // The distances are from the centers of the edge cells, which are half a cell away from
+        // the edge
+        let keep = distances
+            .iter()
+            .map(|distance| (distance + 0.5) * self.config.cell_size >= self.config.agent_radius)
+            .collect::<Vec<_>>();
+        let mut new_indices = vec![NO_LINK; self.cells.len()];
+        let mut cells = Vec::new();
+        for (index, cell) in self.cells.iter().enumerate() {
+            if keep[index] {
+                new_indices[index] = cells.len() as u32;
+                cells.push(*cell);
+            }
+        }
+        for cell in &mut cells {
+            for link in &mut cell.links {
+                if *link != NO_LINK {
+                    *link = new_indices[*link as usize];
+                }
+            }
+        }
+        for column in &mut self.columns {
+            column.retain(|&index| keep[index as usize]);
+            for index in column.iter_mut() {
+                *index = new_indices[*index as usize];
+            }
+        }
+        self.cells = cells;
+    }
+
+    /// The cells that an agent can move to from a cell, and the distances (in cells) to them.
+    ///
+    /// Diagonal moves must be possible both ways around the corner, so that they don't cut
+    /// through obstacles.
+    fn neighbours(&self, index: u32) -> impl Iterator<Item = (u32, f32)> {
+        let links = self.cells[index as usize].links;
+        let mut neighbours = [(NO_LINK, 0.); 8];
+        for direction in 0..4 {
+            neighbours[direction] = (links[direction], 1.);
+            let next = (direction + 1) % 4;
+            let diagonal = self.link(links[direction], next);
+            if diagonal != NO_LINK && diagonal == self.link(links[next], direction) {
+                neighbours[4 + direction] = (diagonal, SQRT_2);
+            }
+        }
+        neighbours
+            .into_iter()
+            .filter(|&(neighbour, _)| neighbour != NO_LINK)
+    }
+
+    fn link(&self, index: u32, direction: usize) -> u32 {
+        if index == NO_LINK {
+            return NO_LINK;
+        }
+        self.cells[index as usize].links[direction]
+    }
+
+    fn offset_column(&self, column: u32, (dx, dy): (i32, i32)) -> Option<usize> {
+        let (x, y) = self.column_coords(column);
+        let (x, y) = (x + dx, y + dy);
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.length as i32 {
+            return None;
+        }
+        Some(y as usize * self.width + x as usize)
+    }
+
+    fn column_coords(&self, column: u32) -> (i32, i32) {
+        (
+            (column as usize % self.width) as i32,
+            (column as usize / self.width) as i32,
+        )
+    }
+
+    /// The coordinates of a point in the grid of columns, in cells.
+    fn grid_position(&self, point: Vec3) -> Vec2 {
+        (point.xy() - self.config.min.xy()) / self.config.cell_size
+    }
+
+    fn cell_position(&self, index: u32) -> Vec3 {
+        let cell = self.cells[index as usize];
+        let center = self.config.min.xy()
+            + column_center(cell.column as usize, self.width) * self.config.cell_size;
+        center.extend(cell.height)
+    }
+
+    /// The cell which is the closest to `point`, within `max_distance` horizontally and the height
+    /// of the agents vertically, and the point of that cell which is the closest to `point`.
+    fn nearest_cell(&self, point: Vec3, max_distance: f32) -> Option<(u32, Vec3)> {
+        let grid = self.grid_position(point);
+        let (x, y) = (grid.x.floor() as i32, grid.y.floor() as i32);
+        let radius = (max_distance / self.config.cell_size).ceil() as i32;
+        let mut nearest = None;
+        for y in (y - radius).max(0)..=(y + radius).min(self.length as i32 - 1) {
+            for x in (x - radius).max(0)..=(x + radius).min(self.width as i32 - 1) {
+                for &index in &self.columns[y as usize * self.width + x as usize] {
+                    let position = self.cell_position(index);
+                    let height = self.cells[index as usize].height;
+                    // Slightly less than half a cell, so that the point stays in the column of
+                    // the cell
+                    let half_cell = Vec2::splat(self.config.cell_size * 0.49);
+                    let closest = point
+                        .xy()
+                        .clamp(position.xy() - half_cell, position.xy() + half_cell)
+                        .extend(height);
+                    if (closest - point).xy().length() > max_distance
+                        || (height - point.z).abs() > self.config.agent_height
+                    {
+                        continue;
+                    }
+                    let distance = closest.distance_squared(point);
+                    if nearest.map_or(true, |(_, _, nearest)| distance < nearest) {
+                        nearest = Some((index, closest, distance));
+                    }
+                }
+            }
+        }
+        nearest.map(|(index, closest, _)| (index, closest))
+    }
+
+    /// The distance within which the start and end of paths are moved onto the navigation mesh,
+    /// which covers the edges that were eroded away.
+    fn snap_distance(&self) -> f32 {
+        self.config.agent_radius * 2. + self.config.cell_size
+    }
+
+    pub fn nearest_point(&self, point: Vec3, max_distance: f32) -> Option<Vec3> {
+        self.nearest_cell(point, max_distance)
+            .map(|(_, nearest)| nearest)
+    }
+
+    pub fn find_path(&self, start: Vec3, end: Vec3) -> Option<Vec<Vec3>> {
+        let (start_cell, start) = self.nearest_cell(start, self.snap_distance())?;
+        let (end_cell, end) = self.nearest_cell(end, self.snap_distance())?;
+        let cells = self.find_cells(start_cell, end_cell)?;
+        Some(self.smooth(&cells, start, end))
+    }
+
+    /// Finds the shortest path between two cells with A*.
+    fn find_cells(&self, start: u32, end: u32) -> Option<Vec<u32>> {
+        let end_position = self.cell_position(end);
+        let mut came_from = HashMap::new();
+        let mut costs = HashMap::from([(start, 0.)]);
+        let mut queue = BinaryHeap::from([Reverse((OrderedFloat(0.), start))]);
+        let mut visited = HashSet::new();
+        while let Some(Reverse((_, index))) = queue.pop() {
+            if index == end {
+                let mut cells = vec![end];
+                while let Some(&previous) = came_from.get(cells.last().unwrap()) {
+                    cells.push(previous);
+                }
+                cells.reverse();
+                return Some(cells);
+            }
+            if !visited.insert(index) {
+                continue;
+            }
+            let position = self.cell_position(index);
+            let cost = costs[&index];
+            for (neighbour, _) in self.neighbours(index) {
+                let neighbour_position = self.cell_position(neighbour);
+                let neighbour_cost = cost + position.distance(neighbour_position);
+                if costs
+                    .get(&neighbour)
+                    .map_or(true, |&previous| neighbour_cost < previous)
+                {
+                    costs.insert(neighbour, neighbour_cost);
+                    came_from.insert(neighbour, index);
+                    let estimate = neighbour_cost + neighbour_position.distance(end_position);
+                    queue.push(Reverse((OrderedFloat(estimate), neighbour)));
+                }
+            }
+        }
+        None
+    }
+
+    /// Turns a path of cells into waypoints, skipping the cells that the waypoint before them can
+    /// see the cell after them from.
+    fn smooth(&self, cells: &[u32], start: Vec3, end: Vec3) -> Vec<Vec3> {
+        let waypoint = |i: usize| {
+            if i == cells.len() - 1 {
+                end
+            } else {
+                self.cell_position(cells[i])
+            }
+        };
+        let mut waypoints = vec![start];
+        let (mut anchor, mut anchor_cell) = (start, cells[0]);
+        for i in 1..cells.len().saturating_sub(1) {
+            if !self.line_of_sight(anchor_cell, anchor, waypoint(i + 1)) {
+                anchor = waypoint(i);
+                anchor_cell = cells[i];
+                waypoints.push(anchor);
+            }
+        }
+        waypoints.push(end);
+        waypoints
+    }
+
+    /// Whether an agent can walk in a straight line from `from`, which is in the cell `index`,
+    /// to `to`, by following the links of the cells under the line.
+    fn line_of_sight(&self, mut index: u32, from: Vec3, to: Vec3) -> bool {
+        let (from, to) = (self.grid_position(from), self.grid_position(to));
+        // Two samples per cell, so that the line moves by at most one column at a time
+        let samples = ((to - from).abs().max_element() * 2.).ceil().max(1.) as usize;
+        let (mut x, mut y) = self.column_coords(self.cells[index as usize].column);
+        for sample in 1..=samples {
+            let point = from.lerp(to, sample as f32 / samples as f32);
+            let (target_x, target_y) = (point.x.floor() as i32, point.y.floor() as i32);
+            while (x, y) != (target_x, target_y) {
+                let (dx, dy) = ((target_x - x).signum(), (target_y - y).signum());
+                index = match (dx, dy) {
+                    (_, 0) | (0, _) => self.link(index, direction((dx, dy))),
+                    _ => {
+                        let (along_x, along_y) = (direction((dx, 0)), direction((0, dy)));
+                        let diagonal = self.link(self.link(index, along_x), along_y);
+                        if diagonal == self.link(self.link(index, along_y), along_x) {
+                            diagonal
+                        } else {
+                            NO_LINK
+                        }
+                    }
+                };
+                if index == NO_LINK {
+                    return false;
+                }
+                (x, y) = (x + dx, y + dy);
+            }
+        }
+        true
+    }
+}
+
+/// The center of a column in the grid of columns, in cells.
+fn column_center(column: usize, width: usize) -> Vec2 {
+    Vec2::new((column % width) as f32, (column / width) as f32) + 0.5
+}
+
+/// The index of a direction of [DIRECTIONS].
+fn direction(offset: (i32, i32)) -> usize {
+    DIRECTIONS.iter().position(|&o| o == offset).unwrap()
+}
+
+struct StaticHit {
+    entity: Option<EntityId>,
+    position: Vec3,
+    normal: Vec3,
+    distance: f32,
+}
+
+/// Casts a ray against the static colliders of the scene, skipping the `ignored` entities.
+fn static_raycast(
+    scene: &PxSceneRef,
+    ignored: &HashSet<EntityId>,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Vec<StaticHit> {
+    let mut hit = PxRaycastCallback::new(MAX_COLUMN_HITS);
+    let mut filter_data = PxQueryFilterData::new();
+    filter_data.set_flags(PxQueryFlag::STATIC | PxQueryFlag::NO_BLOCK);
+    if !scene.raycast(
+        origin,
+        direction,
+        max_distance,
+        &mut hit,
+        None,
+        &filter_data,
+    ) {
+        return Vec::new();
+    }
+    hit.touches()
+        .into_iter()
+        .map(|hit| StaticHit {
+            entity: hit
+                .shape
+                .as_ref()
+                .and_then(|shape| shape.get_user_data::<PxShapeUserData>())
+                .map(|ud| ud.entity),
+            position: hit.position,
+            normal: hit.normal,
+            distance: hit.distance,
+        })
+        .filter(|hit| !hit.entity.is_some_and(|entity| ignored.contains(&entity)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A navigation mesh with a cell per character of `rows`, the first of which is at Y = 0: `#`
+    /// is a column without cells, `.` a cell at a height of 0 and a digit a cell at a height of
+    /// a quarter of that digit.
+    fn mesh(agent_radius: f32, rows: &[&str]) -> NavMesh {
+        let (width, length) = (rows[0].len(), rows.len());
+        let heights = rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .map(|c| match c {
+                '#' => vec![],
+                '.' => vec![0.],
+                digit => vec![digit.to_digit(10).unwrap() as f32 * 0.25],
+            })
+            .collect();
+        let config = NavMeshConfig {
+            min: Vec3::ZERO,
+            max: Vec3::new(width as f32, length as f32, 5.),
+            cell_size: 1.,
+            agent_radius,
+            agent_height: 2.,
+            max_slope: 0.2,
+            step_height: 0.3,
+        };
+        NavMesh::from_heights(config, width, length, heights)
+    }
+
+    #[test]
+    fn paths_go_around_walls() {
+        let mesh = mesh(
+            0.,
+            &[
+                "..........",
+                "..........",
+                "..........",
+                "#########.",
+                "..........",
+            ],
+        );
+        let (start, end) = (Vec3::new(0.5, 0.5, 0.), Vec3::new(0.5, 4.5, 0.));
+        let path = mesh.find_path(start, end).unwrap();
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&end));
+        assert!(path.iter().any(|point| point.x > 9.), "{path:?}");
+
+        // Points that are in line of sight of each other are joined directly
+        let end = Vec3::new(8.5, 2.5, 0.);
+        assert_eq!(mesh.find_path(start, end), Some(vec![start, end]));
+    }
+
+    #[test]
+    fn steps_and_slopes() {
+        let mesh = mesh(0., &[".1234", "....."]);
+        let end = Vec3::new(4.5, 0.5, 1.);
+        let path = mesh.find_path(Vec3::new(0.5, 1.5, 0.), end).unwrap();
+        assert_eq!(path.last(), Some(&end));
+
+        // Nearest points are only found within the height of the agents
+        assert!(mesh.nearest_point(Vec3::new(4.5, 0.5, 3.5), 1.).is_none());
+        assert_eq!(
+            mesh.nearest_point(Vec3::new(4.5, 0.5, 2.5), 1.),
+            Some(Vec3::new(4.5, 0.5, 1.))
+        );
+    }
+
+    #[test]
+    fn unreachable_targets() {
+        // A cliff that is higher than a step
+        let cliff = mesh(0., &["...888", "...888", "...888"]);
+        let start = Vec3::new(0.5, 1.5, 0.);
+        assert!(cliff.find_path(start, Vec3::new(5.5, 1.5, 2.)).is_none());
+        assert!(cliff.find_path(start, Vec3::new(2.5, 1.5, 0.)).is_some());
+        // Targets away from the mesh
+        assert!(cliff.find_path(start, Vec3::new(50., 1.5, 0.)).is_none());
+
+        // Diagonal moves don't squeeze between two walls
+        let (start, end) = (Vec3::new(0.5, 0.5, 0.), Vec3::new(1.5, 1.5, 0.));
+        assert!(mesh(0., &[".#", "#."]).find_path(start, end).is_none());
+        assert!(mesh(0., &[".#", ".."]).find_path(start, end).is_some());
+    }
+
+    #[test]
+    fn gaps_narrower_than_the_agents_are_closed() {
+        let rooms = |gap: &str| {
+            [
+                "...........",
+                "...........",
+                "...........",
+                gap,
+                "...........",
+                "...........",
+                "...........",
+            ]
+        };
+        let (start, end) = (Vec3::new(5.5, 1.5, 0.), Vec3::new(5.5, 5.5, 0.));
+
+        assert!(mesh(0., &rooms("#####.#####"))
+            .find_path(start, end)
+            .is_some());
+        assert!(mesh(1., &rooms("#####.#####"))
+            .find_path(start, end)
+            .is_none());
+        assert!(mesh(1., &rooms("####...####"))
+            .find_path(start, end)
+            .is_some());
+    }
+}
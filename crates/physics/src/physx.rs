@@ -68,7 +68,7 @@ impl Physics {
             pvd,
             pvd_transport,
             physics,
-            dispatcher: PxDefaultCpuDispatcherRef::new(2),
+            dispatcher: PxDefaultCpuDispatcherRef::new(worker_thread_count()),
         }
     }
     pub fn release(self) {
@@ -82,6 +82,16 @@ impl Physics {
     }
 }
 
+/// The number of worker threads of the physx dispatcher. The simulation islands of a scene (the
+/// groups of actors that interact with each other), and the scenes of different instances, are
+/// stepped in parallel on these threads.
+fn worker_thread_count() -> u32 {
+    // One core is left for the server tick
+    std::thread::available_parallelism()
+        .map_or(2, |count| count.get().saturating_sub(1))
+        .clamp(2, 8) as u32
+}
+
 fn vec3_changed(old: Vec3, new: Vec3) -> bool {
     (new - old).length() > 0.001
 }
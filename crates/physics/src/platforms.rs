@@ -0,0 +1,125 @@
+//! Kinematic bodies are moved by setting their `translation` and `rotation`, but PhysX does not
+//! make them collide with character controllers, which are kinematic too. The systems of this
+//! module carry the characters standing on kinematic bodies with them (e.g. elevators and moving
+//! platforms), and push the characters they move into.
+
+use std::collections::HashMap;
+
+use ambient_core::{
+    delta_time,
+    transform::{rotation, translation},
+};
+use ambient_ecs::{components, query, EntityId, FnSystem, SystemGroup, World};
+use ambient_native_std::shapes::Ray;
+use glam::{Mat4, Quat, Vec3};
+use physxx::PxControllerFilters;
+
+use crate::{
+    character_controller_height, character_controller_radius, character_controller_up,
+    intersection::{overlap_capsule, raycast},
+    kinematic,
+    physx::{character_controller, rigid_dynamic},
+};
+
+/// How far below the feet of a character the ground is looked for.
+const GROUND_DISTANCE: f32 = 0.1;
+
+components!("physics", {
+    /// The pose of a kinematic body when its riders were last moved.
+    kinematic_last_pose: Mat4,
+});
+
+pub fn server_systems() -> SystemGroup {
+    SystemGroup::new(
+        "physics/platforms/server",
+        vec![Box::new(FnSystem::new(|world, _| {
+            profiling::scope!("platforms");
+            let deltas = update_kinematic_poses(world);
+            if deltas.is_empty() {
+                return;
+            }
+            let dt = *world.resource(delta_time());
+            for (id, controller) in query(character_controller()).collect_cloned(world, None) {
+                let foot = controller.get_foot_position().as_vec3();
+                let up = world
+                    .get(id, character_controller_up())
+                    .ok()
+                    .and_then(|up| up.try_normalize())
+                    .unwrap_or(Vec3::Z);
+                let new_foot = if let Some(delta) =
+                    ground(world, id, foot, up).and_then(|ground| deltas.get(&ground))
+                {
+                    // Riders are moved rigidly, as the body has not been moved in the physics
+                    // scene yet and would block them when it moves down
+                    let new_foot = delta.transform_point3(foot);
+                    controller.set_foot_position(new_foot.as_dvec3());
+                    new_foot
+                } else if let Some(delta) =
+                    pusher(world, id, foot, up).and_then(|pusher| deltas.get(&pusher))
+                {
+                    // Pushed characters slide along the obstacles behind them
+                    controller.move_controller(
+                        delta.transform_point3(foot) - foot,
+                        0.,
+                        dt,
+                        &PxControllerFilters::new(),
+                        None,
+                    );
+                    controller.get_foot_position().as_vec3()
+                } else {
+                    continue;
+                };
+                if world.has_component(id, translation()) {
+                    world.set(id, translation(), new_foot).unwrap();
+                }
+            }
+        }))],
+    )
+}
+
+/// Records the poses of the kinematic bodies, and returns the transforms by which the bodies
+/// that moved since the last frame did so.
+fn update_kinematic_poses(world: &mut World) -> HashMap<EntityId, Mat4> {
+    let mut deltas = HashMap::new();
+    for (id, (pos, rot)) in query((translation(), rotation()))
+        .incl(kinematic())
+        .incl(rigid_dynamic())
+        .collect_cloned(world, None)
+    {
+        let pose = Mat4::from_rotation_translation(rot, pos);
+        if let Ok(last_pose) = world.get(id, kinematic_last_pose()) {
+            if last_pose == pose {
+                continue;
+            }
+            deltas.insert(id, pose * last_pose.inverse());
+        }
+        world
+            .add_component(id, kinematic_last_pose(), pose)
+            .unwrap();
+    }
+    deltas
+}
+
+/// The entity that the character `id` stands on.
+fn ground(world: &World, id: EntityId, foot: Vec3, up: Vec3) -> Option<EntityId> {
+    raycast(world, Ray::new(foot + up * GROUND_DISTANCE, -up))
+        .into_iter()
+        .find(|&(hit, _)| hit != id)
+        .filter(|&(_, distance)| distance <= GROUND_DISTANCE * 2.)
+        .map(|(hit, _)| hit)
+}
+
+/// A kinematic body that overlaps the character `id`.
+fn pusher(world: &World, id: EntityId, foot: Vec3, up: Vec3) -> Option<EntityId> {
+    let height = world.get(id, character_controller_height()).ok()?;
+    let radius = world.get(id, character_controller_radius()).ok()?;
+    overlap_capsule(
+        world,
+        foot + up * height / 2.,
+        radius,
+        (height / 2. - radius).max(0.),
+        Quat::from_rotation_arc(Vec3::Z, up),
+    )
+    .into_iter()
+    .find(|&hit| hit != id && world.has_component(hit, kinematic()))
+}
@@ -0,0 +1,418 @@
+use std::{
+    collections::{HashMap, HashSet},
+    f32::consts::PI,
+    sync::Arc,
+};
+
+use ambient_core::{
+    asset_cache,
+    async_ecs::async_run,
+    delta_time,
+    hierarchy::children,
+    runtime,
+    transform::{get_world_transform, rotation, scale, translation},
+};
+use ambient_ecs::{
+    components, generated::animation::components::bind_id, query, EntityId, FnSystem, SystemGroup,
+    World,
+};
+use ambient_model::{model_from_url, Model, ModelFromUrl};
+use ambient_native_std::asset_cache::AsyncAssetKeyExt;
+use anyhow::Context;
+use glam::{Mat4, Quat, Vec3};
+use itertools::Itertools;
+use physxx::{
+    articulation_reduced_coordinate::{PxArticulationJointRef, PxArticulationRef},
+    PxArticulationAxis, PxArticulationJointBase, PxArticulationJointType, PxArticulationLinkRef,
+    PxArticulationMotion, PxCapsuleGeometry, PxRigidActor, PxRigidBody, PxShape, PxTransform,
+    PxUserData,
+};
+
+use crate::{
+    helpers::set_state, main_physics_scene, physx::physics, ragdoll, ragdoll_active,
+    ragdoll_blend_time, ragdoll_bones, ragdoll_rotations, ragdoll_translations, ragdoll_weight,
+    wood_physics_material, PxShapeUserData,
+};
+
+pub const DEFAULT_BLEND_TIME: f32 = 0.2;
+
+/// Bones shorter than this (meters) are not simulated, and follow their parent instead.
+const MIN_BONE_LENGTH: f32 = 0.05;
+/// The radius of the capsule of a bone, relative to its length.
+const BONE_RADIUS: f32 = 0.2;
+const BONE_DENSITY: f32 = 1000.;
+const TWIST_LIMIT: f32 = PI / 6.;
+const SWING_LIMIT: f32 = PI / 3.;
+
+components!("physics", {
+    /// The simulated bones of a ragdoll, loaded from its model.
+    ragdoll_skeleton: Arc<RagdollSkeleton>,
+    /// The articulation simulating an active ragdoll.
+    ragdoll_articulation: RagdollArticulation,
+});
+
+pub fn server_systems() -> SystemGroup {
+    SystemGroup::new(
+        "physics/ragdoll/server",
+        vec![
+            query((ragdoll().changed(), model_from_url().changed())).to_system(
+                |q, world, qs, _| {
+                    let ragdolls = q
+                        .iter(world, qs)
+                        .map(|(id, (_, url))| (id, url.clone()))
+                        .collect_vec();
+                    if ragdolls.is_empty() {
+                        return;
+                    }
+                    let assets = world.resource(asset_cache()).clone();
+                    let runtime = world.resource(runtime()).clone();
+                    let async_run = world.resource(async_run()).clone();
+                    for (id, url) in ragdolls {
+                        let assets = assets.clone();
+                        let async_run = async_run.clone();
+                        runtime.spawn(async move {
+                            let model = match ModelFromUrl::from_model_from_url(&url) {
+                                Ok(key) => key.get(&assets).await.context("Failed to load model"),
+                                Err(err) => Err(err),
+                            };
+                            let skeleton = match model {
+                                Ok(model) => RagdollSkeleton::from_model(&model),
+                                Err(err) => {
+                                    tracing::warn!("Failed to load ragdoll {url:?}: {err:#}");
+                                    return;
+                                }
+                            };
+                            async_run.run(move |world| {
+                                if !world.has_component(id, ragdoll())
+                                    || world.get_ref(id, model_from_url()).ok() != Some(&url)
+                                {
+                                    return;
+                                }
+                                release_articulation(world, id);
+                                let bones = skeleton
+                                    .bones
+                                    .iter()
+                                    .map(|bone| bone.bind_id.clone())
+                                    .collect_vec();
+                                set_state(world, id, ragdoll_bones(), bones);
+                                world
+                                    .add_component(id, ragdoll_skeleton(), Arc::new(skeleton))
+                                    .unwrap();
+                            });
+                        });
+                    }
+                },
+            ),
+            Box::new(FnSystem::new(|world, _| {
+                profiling::scope!("ragdolls");
+                for (id, _) in query(())
+                    .incl(ragdoll_skeleton())
+                    .excl(ragdoll())
+                    .collect_cloned(world, None)
+                {
+                    release_articulation(world, id);
+                    world.remove_component(id, ragdoll_skeleton()).unwrap();
+                }
+
+                let dt = *world.resource(delta_time());
+                for (id, skeleton) in query(ragdoll_skeleton()).collect_cloned(world, None) {
+                    let active = world.get(id, ragdoll_active()).unwrap_or(false);
+                    let articulation = world.get_ref(id, ragdoll_articulation()).ok().cloned();
+                    let articulation = match (active, articulation) {
+                        (true, None) => {
+                            let articulation = create_articulation(world, id, &skeleton);
+                            world
+                                .add_component(id, ragdoll_articulation(), articulation.clone())
+                                .unwrap();
+                            Some(articulation)
+                        }
+                        (false, Some(_)) => {
+                            // The ragdoll keeps its last pose while it blends back to its
+                            // animations
+                            release_articulation(world, id);
+                            None
+                        }
+                        (_, articulation) => articulation,
+                    };
+
+                    let blend_time = world
+                        .get(id, ragdoll_blend_time())
+                        .unwrap_or(DEFAULT_BLEND_TIME);
+                    let target = if active { 1. } else { 0. };
+                    let weight = world.get(id, ragdoll_weight()).unwrap_or(0.);
+                    let weight = if blend_time > 0. {
+                        weight + (target - weight).clamp(-dt / blend_time, dt / blend_time)
+                    } else {
+                        target
+                    };
+                    set_state(world, id, ragdoll_weight(), weight);
+
+                    if let Some(articulation) = articulation {
+                        let (rotations, translations) =
+                            articulation.local_poses(world, id, &skeleton);
+                        set_state(world, id, ragdoll_rotations(), rotations);
+                        set_state(world, id, ragdoll_translations(), translations);
+                    }
+                }
+            })),
+            query(ragdoll_articulation())
+                .despawned()
+                .to_system(|q, world, qs, _| {
+                    for (id, articulation) in q.collect_cloned(world, qs) {
+                        // The articulation has already been released if only the component was
+                        // removed
+                        if !world.exists(id) {
+                            articulation.release(world);
+                        }
+                    }
+                }),
+        ],
+    )
+}
+
+/// The bones of a skeleton that are simulated as a ragdoll, ordered so that parents come
+/// before their children.
+#[derive(Debug, Clone)]
+pub struct RagdollSkeleton {
+    bones: Vec<RagdollBone>,
+}
+#[derive(Debug, Clone)]
+struct RagdollBone {
+    bind_id: String,
+    /// The index of the closest simulated ancestor of this bone, which is `None` for the root
+    /// of the ragdoll.
+    parent: Option<usize>,
+    /// The rest transform of the bone, relative to the model.
+    transform: Mat4,
+    /// The rest transform of the parent node of the bone, relative to the `parent` bone, or to
+    /// the model for the root of the ragdoll.
+    parent_offset: Mat4,
+    /// The end of the capsule of the bone, relative to the bone.
+    end: Vec3,
+}
+impl RagdollSkeleton {
+    /// Builds the ragdoll of the first skin of `model`.
+    ///
+    /// Each bone is a capsule that extends to the child bone with the most descendants; leaf
+    /// bones, and bones that are too short, are not simulated.
+    pub fn from_model(model: &Model) -> Self {
+        let world = &model.0;
+        let joints = model
+            .skins()
+            .and_then(|skins| skins.first())
+            .map(|skin| skin.joints.clone())
+            .unwrap_or_default();
+        let joint_set = joints.iter().copied().collect::<HashSet<_>>();
+
+        let mut parents = HashMap::new();
+        for (id, node_children) in query(children()).iter(world, None) {
+            for &child in node_children {
+                parents.insert(child, id);
+            }
+        }
+        let model_transform = model.get_transform().unwrap_or_default();
+        let transform = |node: Option<EntityId>| {
+            let mut transform = Mat4::IDENTITY;
+            let mut node = node;
+            while let Some(id) = node {
+                transform = Mat4::from_scale_rotation_translation(
+                    world.get(id, scale()).unwrap_or(Vec3::ONE),
+                    world.get(id, rotation()).unwrap_or(Quat::IDENTITY),
+                    world.get(id, translation()).unwrap_or(Vec3::ZERO),
+                ) * transform;
+                node = parents.get(&id).copied();
+            }
+            model_transform * transform
+        };
+        let joint_ancestors = |joint: EntityId| {
+            std::iter::successors(parents.get(&joint).copied(), |id| parents.get(id).copied())
+                .filter(|id| joint_set.contains(id))
+        };
+
+        let mut descendants = HashMap::<EntityId, usize>::new();
+        for &joint in &joints {
+            for ancestor in joint_ancestors(joint) {
+                *descendants.entry(ancestor).or_default() += 1;
+            }
+        }
+        let joints = joints
+            .iter()
+            .copied()
+            .sorted_by_key(|&joint| joint_ancestors(joint).count())
+            .collect_vec();
+
+        let mut bones = Vec::<RagdollBone>::new();
+        let mut bone_indices = HashMap::new();
+        for &joint in &joints {
+            let parent_joint = joint_ancestors(joint).next();
+            let parent = match parent_joint {
+                Some(parent_joint) => match bone_indices.get(&parent_joint) {
+                    Some(&parent) => Some(parent),
+                    // The descendants of bones that are not simulated are not simulated either
+                    None => continue,
+                },
+                None => None,
+            };
+            let Ok(bind_id) = world.get_ref(joint, bind_id()).cloned() else {
+                continue;
+            };
+            let Some(main_child) = joints
+                .iter()
+                .copied()
+                .filter(|&child| joint_ancestors(child).next() == Some(joint))
+                .max_by_key(|child| descendants.get(child).copied().unwrap_or(0))
+            else {
+                continue;
+            };
+            let bone_transform = transform(Some(joint));
+            let start = bone_transform.transform_point3(Vec3::ZERO);
+            let end = transform(Some(main_child)).transform_point3(Vec3::ZERO);
+            if start.distance(end) < MIN_BONE_LENGTH {
+                continue;
+            }
+            let parent_transform = transform(parents.get(&joint).copied());
+            bone_indices.insert(joint, bones.len());
+            bones.push(RagdollBone {
+                bind_id,
+                parent,
+                transform: bone_transform,
+                parent_offset: match parent {
+                    Some(parent) => bones[parent].transform.inverse() * parent_transform,
+                    None => parent_transform,
+                },
+                end: bone_transform.inverse().transform_point3(end),
+            });
+        }
+        Self { bones }
+    }
+}
+
+/// The articulation of an active ragdoll, with one link for each of the bones of its skeleton.
+#[derive(Clone)]
+pub struct RagdollArticulation {
+    articulation: PxArticulationRef,
+    links: Vec<PxArticulationLinkRef>,
+    /// The world space scale of each bone, which is not simulated.
+    scales: Vec<Vec3>,
+}
+impl RagdollArticulation {
+    /// The transforms of the bones relative to their parent nodes.
+    fn local_poses(
+        &self,
+        world: &World,
+        id: EntityId,
+        skeleton: &RagdollSkeleton,
+    ) -> (Vec<Quat>, Vec<Vec3>) {
+        let root = get_world_transform(world, id).unwrap_or_default();
+        let transforms = self
+            .links
+            .iter()
+            .zip(&self.scales)
+            .map(|(link, &scale)| {
+                let pose = link.get_global_pose();
+                Mat4::from_scale_rotation_translation(scale, pose.rotation(), pose.translation())
+            })
+            .collect_vec();
+        skeleton
+            .bones
+            .iter()
+            .zip(&transforms)
+            .map(|(bone, transform)| {
+                let parent = bone.parent.map_or(root, |parent| transforms[parent]);
+                let (_, rotation, translation) = ((parent * bone.parent_offset).inverse()
+                    * *transform)
+                    .to_scale_rotation_translation();
+                (rotation, translation)
+            })
+            .unzip()
+    }
+
+    fn release(mut self, world: &World) {
+        world
+            .resource(main_physics_scene())
+            .remove_articulation(&self.articulation, true);
+        self.articulation.release();
+    }
+}
+
+/// Creates the articulation of the ragdoll `id` in the rest pose of its skeleton.
+fn create_articulation(
+    world: &World,
+    id: EntityId,
+    skeleton: &RagdollSkeleton,
+) -> RagdollArticulation {
+    let physics = world.resource(physics());
+    let material = world.resource(wood_physics_material());
+    let root = get_world_transform(world, id).unwrap_or_default();
+    let articulation = PxArticulationRef::new(&physics.physics);
+
+    let mut links = Vec::<PxArticulationLinkRef>::new();
+    let mut scales = Vec::new();
+    for bone in &skeleton.bones {
+        let transform = root * bone.transform;
+        let (scale, rotation, position) = transform.to_scale_rotation_translation();
+        let link = PxArticulationLinkRef::new(
+            &articulation,
+            bone.parent.map(|parent| &links[parent]),
+            &PxTransform::new(position, rotation),
+        );
+
+        // The capsule extends along the X axis of the joint, from the bone to its end
+        let axis = rotation.inverse() * (transform.transform_point3(bone.end) - position);
+        let length = axis.length();
+        let radius = length * BONE_RADIUS;
+        let joint_rotation = Quat::from_rotation_arc(Vec3::X, axis / length);
+        let shape = PxShape::new(
+            physics.physics,
+            &PxCapsuleGeometry::new(radius, (length / 2. - radius).max(0.)),
+            &[material],
+            Some(true),
+            None,
+        );
+        shape.set_local_pose(&PxTransform::new(axis / 2., joint_rotation));
+        shape.set_user_data(PxShapeUserData {
+            entity: id,
+            density: BONE_DENSITY,
+            base_pose: Mat4::IDENTITY,
+        });
+        link.attach_shape(&shape);
+        link.update_mass_and_inertia_uniform(BONE_DENSITY, None, None);
+
+        if let (Some(parent), Some(mut joint)) =
+            (bone.parent, PxArticulationJointRef::from_link(&link))
+        {
+            let parent_pose = links[parent].get_global_pose();
+            let parent_rotation = parent_pose.rotation().inverse();
+            joint.set_parent_pose(&PxTransform::new(
+                parent_rotation * (position - parent_pose.translation()),
+                parent_rotation * rotation * joint_rotation,
+            ));
+            joint.set_child_pose(&PxTransform::new(Vec3::ZERO, joint_rotation));
+            joint.set_joint_type(PxArticulationJointType::Spherical);
+            joint.set_motion(PxArticulationAxis::Twist, PxArticulationMotion::Limited);
+            joint.set_motion(PxArticulationAxis::Swing1, PxArticulationMotion::Limited);
+            joint.set_motion(PxArticulationAxis::Swing2, PxArticulationMotion::Limited);
+            joint.set_limit(PxArticulationAxis::Twist, -TWIST_LIMIT, TWIST_LIMIT);
+            joint.set_limit(PxArticulationAxis::Swing1, -SWING_LIMIT, SWING_LIMIT);
+            joint.set_limit(PxArticulationAxis::Swing2, -SWING_LIMIT, SWING_LIMIT);
+        }
+        links.push(link);
+        scales.push(scale);
+    }
+    world
+        .resource(main_physics_scene())
+        .add_articulation(&articulation);
+
+    RagdollArticulation {
+        articulation,
+        links,
+        scales,
+    }
+}
+
+fn release_articulation(world: &mut World, id: EntityId) {
+    if let Ok(articulation) = world.get_ref(id, ragdoll_articulation()).cloned() {
+        articulation.release(world);
+        world.remove_component(id, ragdoll_articulation()).unwrap();
+    }
+}
@@ -0,0 +1,277 @@
+use std::{collections::HashMap, f32::consts::TAU};
+
+use ambient_core::{
+    delta_time,
+    hierarchy::parent,
+    transform::{rotation, translation},
+};
+use ambient_ecs::{query, EntityId, FnSystem, SystemGroup, World};
+use glam::{Quat, Vec2, Vec3};
+use itertools::Itertools;
+use ordered_float::OrderedFloat;
+use physxx::{
+    PxQueryFilterData, PxQueryFlag, PxRaycastCallback, PxRigidActor, PxRigidActorRef, PxRigidBody,
+    PxSceneRef,
+};
+
+use crate::{
+    helpers::set_state, main_physics_scene, physx::rigid_dynamic, vehicle_brake,
+    vehicle_brake_torque, vehicle_engine_torque, vehicle_max_speed, vehicle_speed, vehicle_steer,
+    vehicle_throttle, wheel_driven, wheel_friction, wheel_grounded, wheel_local_position,
+    wheel_max_steer_angle, wheel_radius, wheel_rotation, wheel_steer_angle,
+    wheel_suspension_damping, wheel_suspension_length, wheel_suspension_offset,
+    wheel_suspension_stiffness, wheel_vehicle,
+};
+
+pub const DEFAULT_ENGINE_TORQUE: f32 = 1000.;
+pub const DEFAULT_BRAKE_TORQUE: f32 = 3000.;
+pub const DEFAULT_MAX_SPEED: f32 = 30.;
+pub const DEFAULT_WHEEL_RADIUS: f32 = 0.4;
+pub const DEFAULT_SUSPENSION_LENGTH: f32 = 0.3;
+pub const DEFAULT_SUSPENSION_STIFFNESS: f32 = 30000.;
+pub const DEFAULT_SUSPENSION_DAMPING: f32 = 3000.;
+pub const DEFAULT_WHEEL_FRICTION: f32 = 1.5;
+
+/// The maximum number of shapes that the raycast of a suspension considers, including those of
+/// its vehicle.
+const MAX_SUSPENSION_HITS: usize = 16;
+
+pub fn server_systems() -> SystemGroup {
+    SystemGroup::new(
+        "physics/vehicles/server",
+        vec![Box::new(FnSystem::new(|world, _| {
+            profiling::scope!("vehicles");
+            let dt = *world.resource(delta_time());
+            if dt <= 0. {
+                return;
+            }
+            let mut vehicles = HashMap::<EntityId, Vec<EntityId>>::new();
+            for (wheel, vehicle) in query(wheel_vehicle()).collect_cloned(world, None) {
+                vehicles.entry(vehicle).or_default().push(wheel);
+            }
+            for (vehicle, wheels) in vehicles {
+                update_vehicle(world, vehicle, &wheels, dt);
+            }
+        }))],
+    )
+}
+
+/// The configuration of a wheel, from its components.
+struct Wheel {
+    id: EntityId,
+    position: Vec3,
+    radius: f32,
+    suspension_length: f32,
+    stiffness: f32,
+    damping: f32,
+    max_steer_angle: f32,
+    driven: bool,
+    friction: f32,
+}
+impl Wheel {
+    fn get(world: &World, id: EntityId) -> Self {
+        Self {
+            id,
+            position: world.get(id, wheel_local_position()).unwrap_or_default(),
+            radius: world
+                .get(id, wheel_radius())
+                .unwrap_or(DEFAULT_WHEEL_RADIUS)
+                .max(0.01),
+            suspension_length: world
+                .get(id, wheel_suspension_length())
+                .unwrap_or(DEFAULT_SUSPENSION_LENGTH)
+                .max(0.),
+            stiffness: world
+                .get(id, wheel_suspension_stiffness())
+                .unwrap_or(DEFAULT_SUSPENSION_STIFFNESS),
+            damping: world
+                .get(id, wheel_suspension_damping())
+                .unwrap_or(DEFAULT_SUSPENSION_DAMPING),
+            max_steer_angle: world.get(id, wheel_max_steer_angle()).unwrap_or(0.),
+            driven: world.has_component(id, wheel_driven()),
+            friction: world
+                .get(id, wheel_friction())
+                .unwrap_or(DEFAULT_WHEEL_FRICTION),
+        }
+    }
+}
+
+/// The state of a wheel after a step, which is synchronized to the clients.
+struct WheelState {
+    steer_angle: f32,
+    suspension_offset: f32,
+    grounded: bool,
+    /// The angle the wheel rolled by during the step.
+    roll: f32,
+}
+
+/// Applies the forces of the suspension, engine, brakes and tires of the wheels to the chassis.
+///
+/// The chassis is in its local space, where +X is forward, +Y is left and +Z is up.
+fn update_vehicle(world: &mut World, vehicle: EntityId, wheel_ids: &[EntityId], dt: f32) {
+    let Ok(body) = world.get(vehicle, rigid_dynamic()) else {
+        return;
+    };
+    let pose = body.get_global_pose();
+    let (position, orientation) = (pose.translation(), pose.rotation());
+    let up = orientation * Vec3::Z;
+
+    let throttle = world
+        .get(vehicle, vehicle_throttle())
+        .unwrap_or(0.)
+        .clamp(-1., 1.);
+    let brake = world
+        .get(vehicle, vehicle_brake())
+        .unwrap_or(0.)
+        .clamp(0., 1.);
+    let steer = world
+        .get(vehicle, vehicle_steer())
+        .unwrap_or(0.)
+        .clamp(-1., 1.);
+    let engine_torque = world
+        .get(vehicle, vehicle_engine_torque())
+        .unwrap_or(DEFAULT_ENGINE_TORQUE);
+    let brake_torque = world
+        .get(vehicle, vehicle_brake_torque())
+        .unwrap_or(DEFAULT_BRAKE_TORQUE);
+    let max_speed = world
+        .get(vehicle, vehicle_max_speed())
+        .unwrap_or(DEFAULT_MAX_SPEED);
+
+    let wheels = wheel_ids
+        .iter()
+        .map(|&id| Wheel::get(world, id))
+        .collect_vec();
+    let speed = body.get_linear_velocity().dot(orientation * Vec3::X);
+    // The engine's torque fades out as the vehicle approaches its maximum speed, but not when it
+    // slows the vehicle down
+    let engine_factor = if speed * throttle > 0. && max_speed > 0. {
+        (1. - speed.abs() / max_speed).clamp(0., 1.)
+    } else {
+        1.
+    };
+    let driven_wheels = wheels.iter().filter(|wheel| wheel.driven).count().max(1) as f32;
+    let wheel_mass = body.get_mass() / wheels.len().max(1) as f32;
+
+    let scene = world.resource(main_physics_scene());
+    let actor = body.as_rigid_actor();
+    let states = wheels
+        .iter()
+        .map(|wheel| {
+            let steer_angle = steer * wheel.max_steer_angle;
+            let mount = position + orientation * wheel.position;
+            let Some(distance) = suspension_raycast(
+                scene,
+                actor,
+                mount,
+                -up,
+                wheel.suspension_length + wheel.radius,
+            ) else {
+                return WheelState {
+                    steer_angle,
+                    suspension_offset: wheel.suspension_length,
+                    grounded: false,
+                    roll: 0.,
+                };
+            };
+            let suspension_offset = (distance - wheel.radius).max(0.);
+
+            // The suspension is a spring and damper between the mount and the wheel
+            let compression = wheel.suspension_length - suspension_offset;
+            let suspension_velocity = body.get_velocity_at_pos(mount).dot(up);
+            let load =
+                (wheel.stiffness * compression - wheel.damping * suspension_velocity).max(0.);
+            body.add_force_at_pos(up * load, mount, None, None);
+
+            // The tires push the wheel forward or backward, and cancel its sliding, within the
+            // limit of their grip
+            let steering = orientation * Quat::from_rotation_z(steer_angle);
+            let (forward, side) = (steering * Vec3::X, steering * Vec3::Y);
+            let center = mount - up * suspension_offset;
+            let velocity = body.get_velocity_at_pos(center);
+            let (forward_velocity, side_velocity) = (velocity.dot(forward), velocity.dot(side));
+            let mut longitudinal = 0.;
+            if wheel.driven {
+                longitudinal +=
+                    throttle * engine_torque * engine_factor / driven_wheels / wheel.radius;
+            }
+            if brake > 0. {
+                // The brakes stop the wheel, but never push it backwards
+                let stopping_force = forward_velocity.abs() * wheel_mass / dt;
+                let brake_force = brake * brake_torque / wheels.len() as f32 / wheel.radius;
+                longitudinal -= forward_velocity.signum() * brake_force.min(stopping_force);
+            }
+            let lateral = -side_velocity * wheel_mass / dt;
+            let force = Vec2::new(longitudinal, lateral).clamp_length_max(wheel.friction * load);
+            body.add_force_at_pos(forward * force.x + side * force.y, center, None, None);
+
+            WheelState {
+                steer_angle,
+                suspension_offset,
+                grounded: true,
+                roll: forward_velocity / wheel.radius * dt,
+            }
+        })
+        .collect_vec();
+
+    set_state(world, vehicle, vehicle_speed(), speed);
+    for (wheel, state) in wheels.iter().zip(states) {
+        let id = wheel.id;
+        let wheel_angle = (world.get(id, wheel_rotation()).unwrap_or(0.) + state.roll) % TAU;
+        set_state(world, id, wheel_rotation(), wheel_angle);
+        set_state(world, id, wheel_steer_angle(), state.steer_angle);
+        set_state(
+            world,
+            id,
+            wheel_suspension_offset(),
+            state.suspension_offset,
+        );
+        set_state(world, id, wheel_grounded(), state.grounded);
+
+        // Wheels that are children of their vehicle are placed where they are rendered
+        if world.get(id, parent()).ok() == Some(vehicle) {
+            set_state(
+                world,
+                id,
+                translation(),
+                wheel.position - Vec3::Z * state.suspension_offset,
+            );
+            set_state(
+                world,
+                id,
+                rotation(),
+                Quat::from_rotation_z(state.steer_angle) * Quat::from_rotation_y(wheel_angle),
+            );
+        }
+    }
+}
+
+/// The distance from `origin` to the closest shape along `direction`, ignoring the shapes of the
+/// vehicle itself.
+fn suspension_raycast(
+    scene: &PxSceneRef,
+    vehicle: PxRigidActorRef,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<f32> {
+    let mut hit = PxRaycastCallback::new(MAX_SUSPENSION_HITS);
+    let mut filter_data = PxQueryFilterData::new();
+    // All of the hits are touches, so that the shapes of the vehicle can be skipped
+    filter_data.set_flags(PxQueryFlag::STATIC | PxQueryFlag::DYNAMIC | PxQueryFlag::NO_BLOCK);
+    if !scene.raycast(
+        origin,
+        direction,
+        max_distance,
+        &mut hit,
+        None,
+        &filter_data,
+    ) {
+        return None;
+    }
+    hit.touches()
+        .into_iter()
+        .filter(|hit| hit.actor != Some(vehicle))
+        .map(|hit| hit.distance)
+        .min_by_key(|&distance| OrderedFloat(distance))
+}
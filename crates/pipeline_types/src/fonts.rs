@@ -0,0 +1,113 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::is_default;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FontsPipeline {
+    /// The character sets to keep in the font. Glyphs that are not used by any of these
+    /// character sets (or by `characters`) are removed from the font, reducing its size.
+    ///
+    /// If this and `characters` are both empty, the font is not subset.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub character_sets: Vec<CharacterSet>,
+    /// Additional characters to keep in the font.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub characters: String,
+    /// If set, a signed distance field atlas of the kept characters will be generated
+    /// alongside the font.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sdf_atlas: Option<SdfAtlas>,
+}
+impl FontsPipeline {
+    /// Returns the characters to keep in the font, or `None` if the font should not be subset.
+    pub fn characters(&self) -> Option<BTreeSet<char>> {
+        if self.character_sets.is_empty() && self.characters.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.character_sets
+                .iter()
+                .flat_map(|set| set.characters())
+                .chain(self.characters.chars())
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// A named set of characters.
+pub enum CharacterSet {
+    /// Printable ASCII characters (U+0020 to U+007E).
+    Ascii,
+    /// The Latin-1 Supplement (U+00A0 to U+00FF), which covers most Western European languages.
+    /// Does not include ASCII.
+    Latin1,
+    /// Latin Extended-A (U+0100 to U+017F), which covers most Central European languages.
+    LatinExtendedA,
+    /// Greek and Coptic (U+0370 to U+03FF).
+    Greek,
+    /// Cyrillic (U+0400 to U+04FF).
+    Cyrillic,
+}
+impl CharacterSet {
+    /// Returns the characters in this set.
+    pub fn characters(&self) -> impl Iterator<Item = char> {
+        match self {
+            CharacterSet::Ascii => '\u{20}'..='\u{7e}',
+            CharacterSet::Latin1 => '\u{a0}'..='\u{ff}',
+            CharacterSet::LatinExtendedA => '\u{100}'..='\u{17f}',
+            CharacterSet::Greek => '\u{370}'..='\u{3ff}',
+            CharacterSet::Cyrillic => '\u{400}'..='\u{4ff}',
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SdfAtlas {
+    /// The size of the glyphs in the atlas, in pixels.
+    #[serde(default = "default_glyph_size")]
+    #[serde(skip_serializing_if = "is_default_glyph_size")]
+    pub glyph_size: u32,
+    /// The distance from the edge of a glyph, in pixels, that is covered by the distance field.
+    /// Larger values allow for wider outlines and glows, at the cost of atlas space.
+    #[serde(default = "default_spread")]
+    #[serde(skip_serializing_if = "is_default_spread")]
+    pub spread: u32,
+    /// The width of the atlas, in pixels. The height is determined by the number of glyphs.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default")]
+    pub width: Option<u32>,
+}
+impl Default for SdfAtlas {
+    fn default() -> Self {
+        Self {
+            glyph_size: default_glyph_size(),
+            spread: default_spread(),
+            width: None,
+        }
+    }
+}
+
+fn default_glyph_size() -> u32 {
+    32
+}
+
+fn is_default_glyph_size(value: &u32) -> bool {
+    *value == default_glyph_size()
+}
+
+fn default_spread() -> u32 {
+    4
+}
+
+fn is_default_spread(value: &u32) -> bool {
+    *value == default_spread()
+}
@@ -1,11 +1,17 @@
 pub mod audio;
+pub mod fonts;
 pub mod materials;
 pub mod models;
+pub mod scenes;
+pub mod tilemaps;
 pub use audio::AudioPipeline;
+pub use fonts::FontsPipeline;
 pub use materials::{MaterialsImporter, MaterialsPipeline, PipelinePbrMaterial};
 pub use models::{Collider, ModelImporter, ModelsPipeline};
+pub use scenes::ScenesPipeline;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
+pub use tilemaps::TilemapsPipeline;
 
 fn is_false(value: &bool) -> bool {
     !*value
@@ -51,8 +57,18 @@ pub enum PipelineProcessor {
     /// Will import specific materials without needing to be part of a model.
     Materials(MaterialsPipeline),
     /// The audio asset pipeline.
-    /// Will import supported audio file formats and produce Ogg Vorbis or WAV files to be used by the runtime.
+    /// Will import supported audio file formats and produce Ogg Vorbis or WAV files to be used by the runtime,
+    /// along with audio event banks (`.audio_bank.toml`), which can be played by the name of their events.
     Audio(AudioPipeline),
+    /// The fonts asset pipeline.
+    /// Will import TrueType and OpenType fonts, optionally subsetting them and generating a signed distance field atlas.
+    Fonts(FontsPipeline),
+    /// The tilemaps asset pipeline.
+    /// Will import Tiled maps (`.tmx`) and generate prefabs with their tile layers, collision and objects.
+    Tilemaps(TilemapsPipeline),
+    /// The scenes asset pipeline.
+    /// Will import scenes in the scene interchange format (`.scene.json` and `.scene.toml`) and generate prefabs for them.
+    Scenes(ScenesPipeline),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,6 +61,24 @@ pub struct ModelsPipeline {
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub transforms: Vec<ModelTransform>,
+    /// If specified, a list of named times in the animations of this model, such as footsteps.
+    /// An `AnimationEvent` message is sent when the playback of an animation passes one of them.
+    /// The events of animations that a model doesn't have are ignored, so that they can be
+    /// specified for pipelines with several models.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub animation_events: Vec<AnimationEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnimationEvent {
+    /// The animation to add the event to, as listed in the build output of the model.
+    pub animation: String,
+    /// The name of the event, which is sent in the message.
+    pub name: String,
+    /// The time of the event in seconds, from the start of the animation.
+    pub time: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+use crate::is_false;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenesPipeline {
+    /// Skip the components of the scenes that are not known, with a warning, instead of failing.
+    ///
+    /// This is useful for scenes that were exported with components of packages that this
+    /// package does not depend on.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub skip_unknown_components: bool,
+}
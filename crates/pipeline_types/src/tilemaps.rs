@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TilemapsPipeline {
+    /// The size of a tile in world units. Tiled's pixel coordinates are scaled so that one tile
+    /// of the map is this large.
+    #[serde(default = "default_tile_size")]
+    #[serde(skip_serializing_if = "is_default_tile_size")]
+    pub tile_size: f32,
+    /// The name of the boolean custom property that marks layers and tiles as solid.
+    ///
+    /// The tiles of the layers that have this property, and the tiles that have it in their
+    /// tileset, are merged into box colliders.
+    #[serde(default = "default_collision_property")]
+    #[serde(skip_serializing_if = "is_default_collision_property")]
+    pub collision_property: String,
+    /// The height of the box colliders of the solid tiles, in world units. The boxes go from the
+    /// plane of the map upwards.
+    #[serde(default = "default_collision_height")]
+    #[serde(skip_serializing_if = "is_default_collision_height")]
+    pub collision_height: f32,
+}
+impl Default for TilemapsPipeline {
+    fn default() -> Self {
+        Self {
+            tile_size: default_tile_size(),
+            collision_property: default_collision_property(),
+            collision_height: default_collision_height(),
+        }
+    }
+}
+
+fn default_tile_size() -> f32 {
+    1.
+}
+
+fn is_default_tile_size(value: &f32) -> bool {
+    *value == default_tile_size()
+}
+
+fn default_collision_property() -> String {
+    "collision".to_string()
+}
+
+fn is_default_collision_property(value: &String) -> bool {
+    *value == default_collision_property()
+}
+
+fn default_collision_height() -> f32 {
+    1.
+}
+
+fn is_default_collision_height(value: &f32) -> bool {
+    *value == default_collision_height()
+}
@@ -1,8 +1,16 @@
 use std::{collections::HashMap, str::FromStr, sync::Arc};
 
-use ambient_core::{asset_cache, async_ecs::async_run, hierarchy::children, runtime};
+use ambient_core::{
+    asset_cache,
+    async_ecs::async_run,
+    hierarchy::{add_child, children, parent},
+    runtime,
+};
 use ambient_decals::decal;
-use ambient_ecs::{query, query_mut, DeserWorldWithWarnings, EntityId, SystemGroup, World};
+use ambient_ecs::{
+    generated::tilemap::components::tilemap_tileset_images, query, query_mut,
+    DeserWorldWithWarnings, EntityId, SystemGroup, World,
+};
 use ambient_model::model_from_url;
 use ambient_native_std::{
     asset_cache::{AssetCache, AsyncAssetKey, AsyncAssetKeyExt},
@@ -39,8 +47,7 @@ pub fn systems() -> SystemGroup {
                     runtime.spawn(async move {
                         let obj = unwrap_log_err!(url.get(&assets).await);
                         let base_ent_id = obj.resource(children())[0];
-                        // TODO: This only handles prefabs with a single entity
-                        let entity = obj.clone_entity(base_ent_id).unwrap();
+                        let entity = obj.clone_entity(base_ent_id).unwrap().remove(children());
                         async_run.run(move |world| {
                             for id in ids {
                                 if !world.exists(id) {
@@ -49,6 +56,7 @@ pub fn systems() -> SystemGroup {
                                 }
 
                                 world.add_components(id, entity.clone()).unwrap();
+                                spawn_children(world, &obj, base_ent_id, id);
                                 world.add_component(id, spawned(), ()).unwrap();
                             }
                         });
@@ -58,6 +66,21 @@ pub fn systems() -> SystemGroup {
     )
 }
 
+/// Spawns the children of `prefab_id` in `prefab`, and their descendants, as children of `id`.
+fn spawn_children(world: &mut World, prefab: &World, prefab_id: EntityId, id: EntityId) {
+    let Ok(prefab_children) = prefab.get_ref(prefab_id, children()) else {
+        return;
+    };
+    for &prefab_child in prefab_children {
+        let Ok(entity) = prefab.clone_entity(prefab_child) else {
+            continue;
+        };
+        let child = entity.remove(children()).with(parent(), id).spawn(world);
+        add_child(world, id, child).unwrap();
+        spawn_children(world, prefab, prefab_child, child);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PrefabFromUrl(pub AssetUrl);
 
@@ -91,6 +114,24 @@ impl AsyncAssetKey<Result<Arc<World>, AssetError>> for PrefabFromUrl {
             def.resolve(&obj_url)
                 .context("Failed to resolve collider")?;
         }
+        // Prefabs can spawn other prefabs on their entities, such as the models of a scene
+        for (_id, (url,), _) in query_mut((prefab_from_url(),), ()).iter(&mut world, None) {
+            *url = AssetUrl::from_str(url)
+                .context("Invalid prefab URL")?
+                .resolve(&obj_url)
+                .context("Failed to resolve prefab URL")?
+                .into();
+        }
+        for (_id, (images,), _) in query_mut((tilemap_tileset_images(),), ()).iter(&mut world, None)
+        {
+            for image in images.iter_mut().filter(|image| !image.is_empty()) {
+                *image = AssetUrl::from_str(image)
+                    .context("Invalid tileset image URL")?
+                    .resolve(&obj_url)
+                    .context("Failed to resolve tileset image URL")?
+                    .into();
+            }
+        }
         for (_id, (def,), _) in query_mut((decal(),), ()).iter(&mut world, None) {
             *def = def
                 .resolve(&obj_url)
@@ -11,22 +11,33 @@ use ambient_ecs::{
     generated::procedurals::components::{procedural_material, procedural_mesh},
     query, Entity, Resource, SystemGroup,
 };
-use ambient_gpu::{mesh_buffer::GpuMesh, texture::TextureView};
-use ambient_native_std::{cb, mesh::Mesh};
+use ambient_gpu::{gpu::Gpu, mesh_buffer::GpuMesh, texture::TextureView};
+use ambient_native_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    cb,
+    mesh::Mesh,
+    shapes::AABB,
+};
 use ambient_renderer::{
+    custom_material::{CustomMaterial, CustomMaterialConfig},
     gpu_primitives_lod, gpu_primitives_mesh,
     pbr_material::{get_pbr_shader, PbrMaterial, PbrMaterialConfig},
-    primitives, renderer_shader, SharedMaterial,
+    primitives, renderer_shader,
+    skinning::{joint_matrices, joints, skin, SkinsBufferKey},
+    RendererShaderProducer, SharedMaterial,
 };
 use ambient_shared_types::{
-    procedural_storage_handle_definitions, ProceduralMaterialHandle, ProceduralMeshHandle,
-    ProceduralSamplerHandle, ProceduralTextureHandle,
+    procedural_storage_handle_definitions, ProceduralBufferHandle, ProceduralComputeHandle,
+    ProceduralMaterialHandle, ProceduralMeshHandle, ProceduralSamplerHandle,
+    ProceduralTextureHandle,
 };
 use paste::paste;
 
 components!("procedurals", {
     @[Resource]
     procedural_storage: ProceduralStorage,
+    /// The number of joint matrices that the skin of a skinned procedural mesh has room for
+    procedural_skin_capacity: u32,
 });
 
 pub fn client_systems() -> SystemGroup {
@@ -34,16 +45,12 @@ pub fn client_systems() -> SystemGroup {
         "procedurals",
         vec![
             query(procedural_mesh().changed()).to_system(|query, world, query_state, _| {
-                let assets = world.resource(asset_cache()).clone();
-                let gpu = world.resource(gpu()).clone();
                 for (id, mesh_handle) in query.collect_cloned(world, query_state) {
-                    let (gpu_mesh, mesh_aabb) = {
-                        let storage = world.resource(procedural_storage());
-                        let mesh = storage.meshes.get(mesh_handle);
-                        let mesh_aabb = mesh.aabb();
-                        let gpu_mesh = GpuMesh::from_mesh(&gpu, &assets, mesh);
-                        (gpu_mesh, mesh_aabb)
-                    };
+                    let ProceduralMesh { gpu_mesh, aabb } = world
+                        .resource(procedural_storage())
+                        .meshes
+                        .get(mesh_handle)
+                        .clone();
                     world
                         .add_components(
                             id,
@@ -53,9 +60,9 @@ pub fn client_systems() -> SystemGroup {
                                 .with(gpu_primitives_mesh(), Default::default())
                                 .with(gpu_primitives_lod(), Default::default())
                                 .with(primitives(), Default::default())
-                                .with(local_bounding_aabb(), mesh_aabb)
-                                .with(world_bounding_aabb(), mesh_aabb)
-                                .with(world_bounding_sphere(), mesh_aabb.to_sphere()),
+                                .with(local_bounding_aabb(), aabb)
+                                .with(world_bounding_aabb(), aabb)
+                                .with(world_bounding_sphere(), aabb.to_sphere()),
                         )
                         .unwrap();
 
@@ -65,20 +72,55 @@ pub fn client_systems() -> SystemGroup {
                         world.add_component_if_required(id, mesh_to_world(), Default::default());
                 }
             }),
+            // Skinned procedural meshes are posed with the joint matrices that are set on them,
+            // instead of with the joints of a model
+            query((procedural_mesh(), joint_matrices().changed()))
+                .excl(joints())
+                .to_system(|query, world, query_state, _| {
+                    let assets = world.resource(asset_cache()).clone();
+                    let gpu = world.resource(gpu()).clone();
+                    let skins = SkinsBufferKey.get(&assets);
+                    let mut skins = skins.lock();
+                    for (id, (_, matrices)) in query.collect_cloned(world, query_state) {
+                        let capacity = world.get(id, procedural_skin_capacity()).unwrap_or(0);
+                        if matrices.len() > capacity as usize || !world.has_component(id, skin()) {
+                            // The skins buffer can't reuse the space of the previous skin yet
+                            let capacity = matrices.len() as u32;
+                            world
+                                .add_components(
+                                    id,
+                                    Entity::new()
+                                        .with(skin(), skins.create(&gpu, capacity))
+                                        .with(procedural_skin_capacity(), capacity),
+                                )
+                                .unwrap();
+                        }
+                        skins.update(&gpu, world.get_ref(id, skin()).unwrap(), &matrices);
+                    }
+                }),
             query(procedural_material().changed()).to_system(|query, world, query_state, _| {
                 let assets = world.resource(asset_cache()).clone();
                 let gpu = world.resource(gpu()).clone();
                 for (id, material_handle) in query.collect_cloned(world, query_state) {
                     let storage = world.resource(procedural_storage());
-                    let material = storage.materials.get(material_handle).clone();
-                    let material = PbrMaterial::new(&gpu, &assets, material);
-                    let material = SharedMaterial::new(material);
+                    let (material, shader) = match storage.materials.get(material_handle).clone() {
+                        ProceduralMaterial::Pbr(config) => {
+                            let material = PbrMaterial::new(&gpu, &assets, config);
+                            let shader: RendererShaderProducer = cb(get_pbr_shader);
+                            (SharedMaterial::new(material), shader)
+                        }
+                        ProceduralMaterial::Custom(config) => {
+                            let material = CustomMaterial::new(&gpu, &assets, config);
+                            let shader = material.renderer_shader();
+                            (SharedMaterial::new(material), shader)
+                        }
+                    };
                     world
                         .add_components(
                             id,
                             Entity::new()
                                 .with(ambient_renderer::material(), material)
-                                .with(renderer_shader(), cb(get_pbr_shader)),
+                                .with(renderer_shader(), shader),
                         )
                         .unwrap();
                 }
@@ -98,10 +140,40 @@ macro_rules! make_procedural_storage_new_fns {
 
 procedural_storage_handle_definitions!(make_procedural_storage_new_fns);
 
-pub type ProceduralMesh = Mesh;
+/// A mesh that has been uploaded to the GPU, which is shared by all the entities that use it.
+#[derive(Clone)]
+pub struct ProceduralMesh {
+    pub gpu_mesh: Arc<GpuMesh>,
+    pub aabb: AABB,
+}
+
+impl ProceduralMesh {
+    pub fn new(gpu: &Gpu, assets: &AssetCache, mesh: &Mesh) -> Self {
+        Self {
+            gpu_mesh: GpuMesh::from_mesh(gpu, assets, mesh),
+            aabb: mesh.aabb(),
+        }
+    }
+
+    /// Replaces the contents of the mesh, reusing its GPU buffers if the new contents fit in them.
+    pub fn update(&mut self, gpu: &Gpu, assets: &AssetCache, mesh: &Mesh) {
+        self.gpu_mesh.update(gpu, assets, mesh);
+        self.aabb = mesh.aabb();
+    }
+}
+
 pub type ProceduralTexture = Arc<TextureView>;
 pub type ProceduralSampler = Arc<wgpu::Sampler>;
-pub type ProceduralMaterial = PbrMaterialConfig;
+pub type ProceduralBuffer = Arc<wgpu::Buffer>;
+/// A compute pipeline, whose bind group layout is derived from its shader
+pub type ProceduralCompute = Arc<wgpu::ComputePipeline>;
+
+#[derive(Clone)]
+pub enum ProceduralMaterial {
+    Pbr(PbrMaterialConfig),
+    /// A material with a WGSL shader, which has been validated when it was created
+    Custom(CustomMaterialConfig),
+}
 
 #[derive(Clone)]
 pub struct ProceduralMap<Handle, Resource>(HashMap<Handle, Resource>);
@@ -120,6 +192,12 @@ where
             .unwrap_or_else(|| panic!("Procedural resource {handle} must exist"))
     }
 
+    pub fn get_mut(&mut self, handle: Handle) -> &mut Resource {
+        self.0
+            .get_mut(&handle)
+            .unwrap_or_else(|| panic!("Procedural resource {handle} must exist"))
+    }
+
     pub fn remove(&mut self, handle: Handle) -> Resource {
         self.0
             .remove(&handle)
@@ -139,6 +217,8 @@ pub struct ProceduralStorage {
     pub textures: ProceduralMap<ProceduralTextureHandle, ProceduralTexture>,
     pub samplers: ProceduralMap<ProceduralSamplerHandle, ProceduralSampler>,
     pub materials: ProceduralMap<ProceduralMaterialHandle, ProceduralMaterial>,
+    pub buffers: ProceduralMap<ProceduralBufferHandle, ProceduralBuffer>,
+    pub compute_pipelines: ProceduralMap<ProceduralComputeHandle, ProceduralCompute>,
 }
 
 impl ProceduralStorage {
@@ -149,6 +229,8 @@ impl ProceduralStorage {
             textures: Default::default(),
             samplers: Default::default(),
             materials: Default::default(),
+            buffers: Default::default(),
+            compute_pipelines: Default::default(),
         }
     }
 }
@@ -36,12 +36,13 @@ use ambient_renderer::{
     MATERIAL_BIND_GROUP,
 };
 use async_trait::async_trait;
-use glam::{uvec4, vec4, Quat, UVec3, UVec4, Vec3, Vec3Swizzles, Vec4};
+use glam::{uvec4, vec3, vec4, Mat4, Quat, UVec3, UVec4, Vec2, Vec3, Vec3Swizzles, Vec4};
 use wgpu::{BindGroup, BindGroupLayoutEntry, Extent3d};
 
 pub use ambient_ecs::generated::rect::components::{
-    background_color, background_url, border_color, border_radius, border_thickness, line_from,
-    line_to, line_width, pixel_line_from, pixel_line_to, rect, size_from_background_image,
+    background_color, background_slice, background_url, border_color, border_radius,
+    border_thickness, line_from, line_to, line_width, pixel_line_from, pixel_line_to, rect,
+    shadow_blur, shadow_color, shadow_offset, size_from_background_image,
 };
 
 #[repr(C)]
@@ -149,7 +150,63 @@ pub fn systems() -> SystemGroup {
             ensure_has_component_with_default(rect(), mesh_to_world()),
             ensure_has_component_with_default(rect(), local_to_world()),
             ensure_has_component(rect(), scale(), Vec3::ONE),
-            ensure_has_component_with_default(rect(), mesh_to_local_from_size()),
+            // Rects with a shadow manage their own `mesh_to_local`, as the mesh needs to extend
+            // beyond the rect to make room for the shadow
+            query(())
+                .incl(rect())
+                .excl(mesh_to_local_from_size())
+                .excl(shadow_color())
+                .to_system(|q, world, qs, _| {
+                    for (id, _) in q.collect_cloned(world, qs) {
+                        world
+                            .add_component(id, mesh_to_local_from_size(), ())
+                            .unwrap();
+
+                        // Undo any margin left over from a shadow that has been removed
+                        if let (Ok(width), Ok(height)) =
+                            (world.get(id, width()), world.get(id, height()))
+                        {
+                            let size = Mat4::from_scale(vec3(width, height, 1.));
+                            world.set(id, mesh_to_local(), size).ok();
+                        }
+                    }
+                }),
+            query(())
+                .incl(rect())
+                .incl(shadow_color())
+                .incl(mesh_to_local_from_size())
+                .to_system(|q, world, qs, _| {
+                    for (id, _) in q.collect_cloned(world, qs) {
+                        world
+                            .remove_component(id, mesh_to_local_from_size())
+                            .unwrap();
+                    }
+                }),
+            query((width().changed(), height().changed()))
+                .incl(rect())
+                .incl(shadow_color())
+                .optional_changed(shadow_offset())
+                .optional_changed(shadow_blur())
+                .to_system(|q, world, qs, _| {
+                    for (id, (width, height)) in q.collect_cloned(world, qs) {
+                        let margin = shadow_margin(
+                            world.get(id, shadow_offset()).unwrap_or_default(),
+                            world.get(id, shadow_blur()).unwrap_or_default(),
+                        );
+                        world
+                            .set_if_changed(
+                                id,
+                                mesh_to_local(),
+                                Mat4::from_translation(vec3(-margin, -margin, 0.))
+                                    * Mat4::from_scale(vec3(
+                                        width + margin * 2.,
+                                        height + margin * 2.,
+                                        1.,
+                                    )),
+                            )
+                            .ok();
+                    }
+                }),
             ensure_has_component_with_default(rect(), gpu_primitives_mesh()),
             ensure_has_component_with_default(rect(), gpu_primitives_lod()),
             query(())
@@ -182,11 +239,17 @@ pub fn systems() -> SystemGroup {
                 .optional_changed(border_color())
                 .optional_changed(border_radius())
                 .optional_changed(border_thickness())
+                .optional_changed(background_slice())
+                .optional_changed(shadow_color())
+                .optional_changed(shadow_offset())
+                .optional_changed(shadow_blur())
                 .to_system(|q, world, qs, _| {
                     let runtime = world.resource(runtime()).clone();
                     for (id, _) in q.collect_cloned(world, qs) {
                         let assets = world.resource(asset_cache()).clone();
                         let async_run = world.resource(async_run()).clone();
+                        let shadow_offset = world.get(id, shadow_offset()).unwrap_or_default();
+                        let shadow_blur = world.get(id, shadow_blur()).unwrap_or_default();
                         let mat_key = RectMaterialKey {
                             params: RectMaterialParams {
                                 background_color: world
@@ -199,6 +262,17 @@ pub fn systems() -> SystemGroup {
                                     .get(id, border_radius())
                                     .unwrap_or_default()
                                     .into(),
+                                background_slice: world
+                                    .get(id, background_slice())
+                                    .unwrap_or_default(),
+                                shadow_color: world.get(id, shadow_color()).unwrap_or_default(),
+                                shadow_offset,
+                                shadow_blur,
+                                shadow_margin: if world.has_component(id, shadow_color()) {
+                                    shadow_margin(shadow_offset, shadow_blur)
+                                } else {
+                                    0.
+                                },
                                 border_thickness: world.get(id, border_thickness()).unwrap_or(0.),
                                 _padding: Default::default(),
                             },
@@ -250,6 +324,11 @@ pub fn systems() -> SystemGroup {
     )
 }
 
+/// Returns how far the mesh of a rect needs to extend beyond the rect to fit its shadow.
+fn shadow_margin(offset: Vec2, blur: f32) -> f32 {
+    blur.max(0.) + offset.abs().max_element()
+}
+
 #[derive(Debug)]
 pub struct RectMaterialShaderKey;
 impl SyncAssetKey<Arc<MaterialShader>> for RectMaterialShaderKey {
@@ -261,6 +340,7 @@ impl SyncAssetKey<Arc<MaterialShader>> for RectMaterialShaderKey {
             ),
 
             id: "rect_material_shader".to_string(),
+            bindless: false,
         })
     }
 }
@@ -360,6 +440,13 @@ pub struct RectMaterialParams {
     pub background_color: Vec4,
     pub border_color: Vec4,
     pub border_radius: Corners,
+    /// Nine-slice insets of the background image: left, top, right, bottom
+    pub background_slice: Vec4,
+    pub shadow_color: Vec4,
+    pub shadow_offset: Vec2,
+    pub shadow_blur: f32,
+    /// How far the mesh extends beyond the rect on each side, to make room for the shadow
+    pub shadow_margin: f32,
     pub border_thickness: f32,
     pub _padding: UVec3,
 }
@@ -3,6 +3,11 @@ struct RectMaterialParams {
     background_color: vec4<f32>,
     border_color: vec4<f32>,
     border_radius: vec4<f32>,
+    background_slice: vec4<f32>,
+    shadow_color: vec4<f32>,
+    shadow_offset: vec2<f32>,
+    shadow_blur: f32,
+    shadow_margin: f32,
     border_thickness: f32,
 }
 @group(MATERIAL_BIND_GROUP)
@@ -17,15 +22,34 @@ var background_sampler: sampler;
 @binding(2)
 var background: texture_2d<f32>;
 
-fn get_corner_from_tc(tc: vec2<f32>) -> u32 {
+// Signed distance from `p` to the edge of a rect of `size` with rounded corners; `p` is relative to the top left of the rect.
+fn rounded_rect_distance(p: vec2<f32>, size: vec2<f32>) -> f32 {
+    let half_size = size * 0.5;
+    let centered = p - half_size;
+
     var corner = 0u;
-    if tc.x >= 0.5 {
+    if centered.x >= 0. {
         corner += 1u;
     }
-    if tc.y >= 0.5 {
+    if centered.y >= 0. {
         corner += 2u;
     }
-    return corner;
+    let radius = min(rect_params.border_radius[corner], min(half_size.x, half_size.y));
+
+    let q = abs(centered) - half_size + radius;
+    return length(max(q, vec2<f32>(0.))) + min(max(q.x, q.y), 0.) - radius;
+}
+
+// Maps a position along one axis of the rect to a texture coordinate, keeping the `start` and `end` insets at their original size.
+fn nine_slice_axis(p: f32, size: f32, texture_size: f32, start: f32, end: f32) -> f32 {
+    if p < start {
+        return p / texture_size;
+    }
+    if p > size - end {
+        return (texture_size - (size - p)) / texture_size;
+    }
+    let middle = max(size - start - end, 0.0001);
+    return (start + (p - start) / middle * (texture_size - start - end)) / texture_size;
 }
 
 fn get_material(in: MaterialInput) -> MaterialOutput {
@@ -33,25 +57,48 @@ fn get_material(in: MaterialInput) -> MaterialOutput {
     out.roughness = 0.4;
     out.metallic = 0.5;
     let size = get_entity_ui_size(in.entity_loc).xy;
-    let p = (0.5 - abs(in.texcoord - 0.5)) * size; // Normalized to top left
-    let corner = get_corner_from_tc(in.texcoord);
-    let border_radius = rect_params.border_radius[corner];
-    let d = distance(vec2(border_radius), p);
+    let margin = rect_params.shadow_margin;
+    // Position relative to the top left of the rect; the mesh extends beyond the rect by the shadow margin
+    let p = in.texcoord * (size + 2. * margin) - margin;
+
+    var uv = p / size;
+    let slice = rect_params.background_slice;
+    if any(slice != vec4<f32>(0.)) {
+        let texture_size = vec2<f32>(textureDimensions(background));
+        uv = vec2<f32>(
+            nine_slice_axis(p.x, size.x, texture_size.x, slice.x, slice.z),
+            nine_slice_axis(p.y, size.y, texture_size.y, slice.y, slice.w),
+        );
+    }
+    let image = textureSample(background, background_sampler, uv);
 
     let entity_color = get_entity_color_or(in.entity_loc, vec4<f32>(1., 1., 1., 1.));
     let border_color = rect_params.border_color * entity_color;
-    let image = textureSample(background, background_sampler, in.texcoord);
     let back_color = vec4(mix(rect_params.background_color.rgb, image.rgb, image.a), image.a + rect_params.background_color.a);
+
+    let d = rounded_rect_distance(p, size);
     var color = back_color * entity_color;
-    if max(p.x, p.y) <= border_radius {
-        if d > border_radius {
-            color.a = 0.;
-        } else if d > border_radius - rect_params.border_thickness {
-            color = border_color;
-        }
-    } else if min(p.x, p.y) < rect_params.border_thickness {
+    if d > 0. {
+        color.a = 0.;
+    } else if d > -rect_params.border_thickness {
         color = border_color;
     }
+
+    if margin > 0. {
+        let shadow_d = rounded_rect_distance(p - rect_params.shadow_offset, size);
+        let blur = max(rect_params.shadow_blur, 0.0001);
+        let shadow_alpha = rect_params.shadow_color.a * entity_color.a * (1. - smoothstep(-blur, blur, shadow_d));
+
+        // Draw the rect over its shadow
+        let alpha = color.a + shadow_alpha * (1. - color.a);
+        if alpha > 0. {
+            color = vec4(
+                (color.rgb * color.a + rect_params.shadow_color.rgb * shadow_alpha * (1. - color.a)) / alpha,
+                alpha
+            );
+        }
+    }
+
     out.opacity = color.a;
     out.alpha_cutoff = 0.;
     out.base_color = from_srgb_to_linear(color.rgb);
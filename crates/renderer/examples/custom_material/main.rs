@@ -44,6 +44,7 @@ impl SyncAssetKey<Arc<MaterialShader>> for CustomMaterialShaderKey {
                 ShaderModule::new("CustomMaterial", include_str!("material.wgsl"))
                     .with_binding_desc(get_custom_material_layout()),
             ),
+            bindless: false,
         })
     }
 }
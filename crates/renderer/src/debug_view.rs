@@ -0,0 +1,105 @@
+use ambient_gpu::{gpu::Gpu, shader_module::GraphicsPipelineInfo};
+use serde::{Deserialize, Serialize};
+
+/// What the forward and transparent passes draw instead of the lit scene, to diagnose content
+/// issues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum DebugView {
+    #[default]
+    Lit,
+    /// The base color of the materials, without lighting
+    BaseColor,
+    /// The world space normals of the surfaces, after normal mapping
+    Normals,
+    /// The metallic (red) and roughness (green) of the materials
+    MetallicRoughness,
+    /// The texture coordinates of the surfaces, wrapped to 0..1
+    Uvs,
+    /// A heatmap of the number of fragments drawn per pixel, from dark red to white
+    Overdraw,
+    /// The edges of the triangles, without lighting
+    Wireframe,
+}
+impl DebugView {
+    pub const ALL: [Self; 7] = [
+        Self::Lit,
+        Self::BaseColor,
+        Self::Normals,
+        Self::MetallicRoughness,
+        Self::Uvs,
+        Self::Overdraw,
+        Self::Wireframe,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Lit => "Lit",
+            Self::BaseColor => "Base color",
+            Self::Normals => "Normals",
+            Self::MetallicRoughness => "Metallic roughness",
+            Self::Uvs => "UVs",
+            Self::Overdraw => "Overdraw",
+            Self::Wireframe => "Wireframe",
+        }
+    }
+
+    /// The value of `debug_view` in the global parameters of the shaders.
+    pub(crate) fn shader_index(self) -> u32 {
+        self as u32
+    }
+
+    /// Whether the view replaces the whole frame, so that the renderers which don't support debug
+    /// views (such as the sky and water) are skipped, and the frame is cleared to black.
+    pub fn replaces_frame(self) -> bool {
+        matches!(self, Self::Overdraw | Self::Wireframe)
+    }
+
+    /// Changes a pipeline of the forward or transparent passes for this view. `targets` must be
+    /// the targets of `info`, and are changed in place.
+    pub(crate) fn apply_to_pipeline<'a>(
+        self,
+        gpu: &Gpu,
+        info: GraphicsPipelineInfo<'a>,
+        targets: &'a mut [Option<wgpu::ColorTargetState>],
+    ) -> GraphicsPipelineInfo<'a> {
+        match self {
+            Self::Overdraw => {
+                // Every fragment is drawn and added up, including the hidden ones
+                for target in targets.iter_mut().flatten() {
+                    target.blend = Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent::REPLACE,
+                    });
+                }
+                GraphicsPipelineInfo {
+                    depth: info.depth.map(|depth| wgpu::DepthStencilState {
+                        depth_write_enabled: false,
+                        depth_compare: wgpu::CompareFunction::Always,
+                        ..depth
+                    }),
+                    targets,
+                    cull_mode: None,
+                    ..info
+                }
+            }
+            Self::Wireframe if supports_wireframe(gpu) => GraphicsPipelineInfo {
+                polygon_mode: wgpu::PolygonMode::Line,
+                cull_mode: None,
+                ..info
+            },
+            _ => info,
+        }
+    }
+}
+
+/// Whether the GPU can draw the edges of triangles, which the web doesn't support. The
+/// [DebugView::Wireframe] view draws flat triangles otherwise.
+pub fn supports_wireframe(gpu: &Gpu) -> bool {
+    gpu.device
+        .features()
+        .contains(wgpu::Features::POLYGON_MODE_LINE)
+}
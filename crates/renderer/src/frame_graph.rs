@@ -0,0 +1,227 @@
+//! Describes the passes of a frame, and what they read and write, so that the order of the passes,
+//! which of them can be skipped, and where the target has to be resolved can be worked out from
+//! the descriptions instead of being hardcoded in [Renderer::render](crate::Renderer::render).
+
+use std::fmt::Debug;
+
+/// A resource that the passes of a frame read or write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameResource {
+    /// The shadow map cascades
+    ShadowMap,
+    /// The color attachment of the target, which is multisampled if MSAA is enabled
+    Color,
+    /// The depth attachment of the target, which is multisampled if MSAA is enabled
+    Depth,
+    /// The normals attachment of the target, which is multisampled if MSAA is enabled
+    Normals,
+    /// The single-sampled color, depth and normals of the target. The frame graph resolves the
+    /// attachments into them before the passes that read them, if they have been written since
+    /// they were last resolved.
+    Resolved,
+    /// The copy of the opaque parts of the frame, which transparent materials can sample
+    SolidsFrame,
+}
+
+impl FrameResource {
+    /// The resources that have to be written before this resource can be read.
+    fn sources(self) -> &'static [FrameResource] {
+        match self {
+            FrameResource::Resolved => &[
+                FrameResource::Color,
+                FrameResource::Depth,
+                FrameResource::Normals,
+            ],
+            FrameResource::ShadowMap => &[FrameResource::ShadowMap],
+            FrameResource::Color => &[FrameResource::Color],
+            FrameResource::Depth => &[FrameResource::Depth],
+            FrameResource::Normals => &[FrameResource::Normals],
+            FrameResource::SolidsFrame => &[FrameResource::SolidsFrame],
+        }
+    }
+
+    fn is_attachment(self) -> bool {
+        matches!(
+            self,
+            FrameResource::Color | FrameResource::Depth | FrameResource::Normals
+        )
+    }
+}
+
+/// A step of a compiled [FrameGraph].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameStep<P> {
+    /// Resolve the attachments of the target into [FrameResource::Resolved]
+    Resolve,
+    /// Run the pass
+    Pass(P),
+}
+
+#[derive(Debug, Clone)]
+struct FramePass<P> {
+    pass: P,
+    reads: Vec<FrameResource>,
+    writes: Vec<FrameResource>,
+}
+
+/// The passes of a frame, in the order that they are added.
+///
+/// A pass only runs if something that is needed for the outputs of the frame reads what it
+/// writes, so passes whose results are unused are skipped.
+#[derive(Debug, Clone)]
+pub struct FrameGraph<P> {
+    passes: Vec<FramePass<P>>,
+}
+
+impl<P: Copy + Debug> FrameGraph<P> {
+    pub fn new() -> Self {
+        Self { passes: Vec::new() }
+    }
+
+    /// Adds a pass after the passes that have already been added.
+    pub fn add_pass(&mut self, pass: P, reads: &[FrameResource], writes: &[FrameResource]) {
+        self.passes.push(FramePass {
+            pass,
+            reads: reads.to_vec(),
+            writes: writes.to_vec(),
+        });
+    }
+
+    /// Returns the steps that produce `outputs`: the passes that contribute to them, and the
+    /// resolves of the target that those passes need.
+    pub fn compile(&self, outputs: &[FrameResource]) -> Vec<FrameStep<P>> {
+        // Walk the passes backwards, keeping those that write a resource that is needed later on
+        let mut needed = outputs
+            .iter()
+            .flat_map(|output| output.sources())
+            .copied()
+            .collect::<Vec<_>>();
+        let mut kept = vec![false; self.passes.len()];
+        for (index, pass) in self.passes.iter().enumerate().rev() {
+            if pass.writes.iter().any(|write| needed.contains(write)) {
+                kept[index] = true;
+                needed.extend(pass.reads.iter().flat_map(|read| read.sources()));
+            }
+        }
+
+        let mut steps = Vec::new();
+        let mut resolved = true;
+        for (pass, _) in self.passes.iter().zip(kept).filter(|(_, kept)| *kept) {
+            if !resolved && pass.reads.contains(&FrameResource::Resolved) {
+                steps.push(FrameStep::Resolve);
+                resolved = true;
+            }
+            steps.push(FrameStep::Pass(pass.pass));
+            if pass.writes.iter().any(|write| write.is_attachment()) {
+                resolved = false;
+            }
+        }
+        if !resolved && outputs.contains(&FrameResource::Resolved) {
+            steps.push(FrameStep::Resolve);
+        }
+        steps
+    }
+}
+
+impl<P: Copy + Debug> Default for FrameGraph<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use FrameResource::*;
+
+    fn graph(
+        passes: &[(&'static str, &[FrameResource], &[FrameResource])],
+    ) -> FrameGraph<&'static str> {
+        let mut graph = FrameGraph::new();
+        for &(pass, reads, writes) in passes {
+            graph.add_pass(pass, reads, writes);
+        }
+        graph
+    }
+
+    fn passes(names: &[&'static str]) -> Vec<FrameStep<&'static str>> {
+        names.iter().map(|&name| FrameStep::Pass(name)).collect()
+    }
+
+    #[test]
+    fn unused_passes_are_culled() {
+        let frame = graph(&[
+            ("shadows", &[], &[ShadowMap]),
+            ("solids", &[Resolved], &[SolidsFrame]),
+            ("forward", &[ShadowMap], &[Color, Depth]),
+        ]);
+
+        assert_eq!(frame.compile(&[Color]), passes(&["shadows", "forward"]));
+        assert_eq!(frame.compile(&[ShadowMap]), passes(&["shadows"]));
+        assert_eq!(frame.compile(&[]), passes(&[]));
+    }
+
+    #[test]
+    fn resolves_are_inserted_before_readers_after_attachment_writes() {
+        let frame = graph(&[
+            ("shadows", &[], &[ShadowMap]),
+            ("forward", &[ShadowMap], &[Color, Depth, Normals]),
+            ("solids", &[Resolved], &[SolidsFrame]),
+            ("transparent", &[SolidsFrame, Depth], &[Color]),
+            ("outlines", &[Resolved], &[Color]),
+            ("ui", &[], &[Color]),
+        ]);
+        assert_eq!(
+            frame.compile(&[Color]),
+            vec![
+                FrameStep::Pass("shadows"),
+                FrameStep::Pass("forward"),
+                FrameStep::Resolve,
+                FrameStep::Pass("solids"),
+                FrameStep::Pass("transparent"),
+                FrameStep::Resolve,
+                FrameStep::Pass("outlines"),
+                FrameStep::Pass("ui"),
+            ]
+        );
+
+        // Nothing has been written since the last resolve
+        let frame = graph(&[
+            ("forward", &[], &[Color, Depth, Normals]),
+            ("ssao", &[Resolved], &[SolidsFrame]),
+            ("solids", &[Resolved, SolidsFrame], &[SolidsFrame]),
+        ]);
+        assert_eq!(
+            frame.compile(&[SolidsFrame]),
+            vec![
+                FrameStep::Pass("forward"),
+                FrameStep::Resolve,
+                FrameStep::Pass("ssao"),
+                FrameStep::Pass("solids"),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolved_outputs_are_resolved_at_the_end() {
+        let frame = graph(&[
+            ("forward", &[], &[Color, Depth, Normals]),
+            ("solids", &[Resolved], &[SolidsFrame]),
+        ]);
+
+        assert_eq!(
+            frame.compile(&[Resolved]),
+            vec![FrameStep::Pass("forward"), FrameStep::Resolve]
+        );
+        assert_eq!(frame.compile(&[Color]), passes(&["forward"]));
+        // The resolve before `solids` is still up to date
+        assert_eq!(
+            frame.compile(&[Resolved, SolidsFrame]),
+            vec![
+                FrameStep::Pass("forward"),
+                FrameStep::Resolve,
+                FrameStep::Pass("solids"),
+            ]
+        );
+    }
+}
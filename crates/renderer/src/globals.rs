@@ -25,16 +25,6 @@ use crate::{
     MESH_BASE_BINDING, MESH_METADATA_BINDING, MESH_SKIN_BINDING, SKINS_BINDING,
 };
 
-#[repr(C)]
-#[derive(Default, Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
-#[allow(dead_code)]
-pub struct ShaderDebugParams {
-    pub metallic_roughness: f32,
-    pub normals: f32,
-    pub shading: f32,
-    padding: f32,
-}
-
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 pub(crate) struct GlobalParams {
@@ -52,7 +42,9 @@ pub(crate) struct GlobalParams {
     pub time: f32,
     pub fog_height_falloff: f32,
     pub fog_density: f32,
-    pub debug_params: ShaderDebugParams,
+    /// See [crate::DebugView]
+    pub debug_view: u32,
+    _padding: [u32; 3],
 }
 
 impl Default for GlobalParams {
@@ -72,7 +64,8 @@ impl Default for GlobalParams {
             time: 0.,
             fog_height_falloff: 0.5,
             fog_density: 0.5,
-            debug_params: Default::default(),
+            debug_view: 0,
+            _padding: Default::default(),
         }
     }
 }
@@ -413,7 +406,7 @@ impl ShadowAndUIGlobals {
         });
 
         let shadow_texture = create_dummy_shadow_texture(gpu);
-        let dummy_prev_frame = RenderTarget::new(gpu, UVec2::ONE, None);
+        let dummy_prev_frame = RenderTarget::with_sample_count(gpu, UVec2::ONE, None, 1);
         let shadow_view = shadow_texture.create_view(&wgpu::TextureViewDescriptor {
             aspect: wgpu::TextureAspect::DepthOnly,
             ..Default::default()
@@ -19,9 +19,7 @@ struct ForwardGlobalParams {
     fog_height_falloff: f32,
     fog_density: f32,
 
-    debug_metallic_roughness: f32,
-    debug_normals: f32,
-    debug_shading: f32,
+    debug_view: u32,
 };
 
 struct ShadowCamera {
@@ -229,8 +227,29 @@ fn geometry_smith(normal: vec3<f32>, v: vec3<f32>, l: vec3<f32>, roughness: f32)
 }
 
 fn shading(material: MaterialOutput, world_position: vec4<f32>) -> vec4<f32> {
-    if global_params.debug_shading > 0.0 {
-        return vec4(material.base_color.rgb, material.opacity);
+    switch global_params.debug_view {
+        // Base color
+        case 1u: {
+            return vec4(material.base_color.rgb, material.opacity);
+        }
+        // Normals
+        case 2u: {
+            return vec4(material.normal * 0.5 + 0.5, 1.0);
+        }
+        // Metallic roughness
+        case 3u: {
+            return vec4(material.metallic, material.roughness, 0.0, 1.0);
+        }
+        // Overdraw, where each fragment is added to the pixel; the channels saturate after 4,
+        // 12 and 32 fragments
+        case 5u: {
+            return vec4(1.0 / 4.0, 1.0 / 12.0, 1.0 / 32.0, 1.0);
+        }
+        // Wireframe
+        case 6u: {
+            return vec4(0.9, 0.9, 0.9, 1.0);
+        }
+        default: {}
     }
 
     let v = normalize(global_params.camera_position.xyz - world_position.xyz);
@@ -304,9 +323,6 @@ fn shading(material: MaterialOutput, world_position: vec4<f32>) -> vec4<f32> {
 
     var color = mix(material.base_color.rgb, lum, material.shading) + material.emissive_factor;
 
-    color = mix(color, vec3(metallic, roughness, 0.0), global_params.debug_metallic_roughness);
-    color = mix(color, normal, global_params.debug_normals);
-
     if global_params.fog != 0 {
         color = apply_fog(color, global_params.camera_position.xyz, world_position.xyz);
     }
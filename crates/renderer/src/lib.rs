@@ -31,11 +31,15 @@ use serde::{Deserialize, Serialize};
 pub mod bind_groups;
 mod collect;
 mod culling;
+mod debug_view;
+pub mod frame_graph;
 mod globals;
 pub mod lod;
 pub mod materials;
 mod outlines;
 mod overlay_renderer;
+pub mod particles;
+pub mod post_processing;
 mod renderer;
 mod shaders;
 mod shadow_renderer;
@@ -46,6 +50,7 @@ mod tree_renderer;
 use ambient_ecs::{query, Component};
 pub use collect::*;
 pub use culling::*;
+pub use debug_view::*;
 pub use globals::*;
 use materials::pbr_material::PbrMaterialFromUrl;
 pub use materials::*;
@@ -95,6 +100,7 @@ pub fn init_all_components() {
     culling::init_gpu_components();
     lod::init_components();
     lod::init_gpu_components();
+    particles::init_components();
     skinning::init_components();
     skinning::init_gpu_components();
 }
@@ -180,6 +186,7 @@ pub fn systems() -> SystemGroup {
                 }
             }),
             Box::new(outlines::systems()),
+            Box::new(particles::systems()),
         ],
     )
 }
@@ -342,33 +349,35 @@ pub fn get_mesh_data_module(bind_group_offset: u32) -> Arc<ShaderModule> {
 }
 
 pub fn primitives_layout() -> BindGroupDesc<'static> {
-    BindGroupDesc {
-        entries: vec![wgpu::BindGroupLayoutEntry {
-            binding: 0,
-            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
-            ty: wgpu::BindingType::Buffer {
-                ty: wgpu::BufferBindingType::Storage { read_only: true },
-                has_dynamic_offset: false,
-                min_binding_size: None,
-            },
-            count: None,
-        }],
-        label: PRIMITIVES_BIND_GROUP.into(),
-    }
+    get_common_layout()
 }
 
 pub fn get_common_layout() -> BindGroupDesc<'static> {
     BindGroupDesc {
-        entries: vec![wgpu::BindGroupLayoutEntry {
-            binding: 0,
-            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
-            ty: wgpu::BindingType::Buffer {
-                ty: wgpu::BufferBindingType::Storage { read_only: true },
-                has_dynamic_offset: false,
-                min_binding_size: None,
+        entries: vec![
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            // The slots of the materials in [BindlessMaterials], by the material index of the
+            // primitives
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
             },
-            count: None,
-        }],
+        ],
         label: PRIMITIVES_BIND_GROUP.into(),
     }
 }
@@ -412,6 +421,9 @@ pub fn get_overlay_modules(assets: &AssetCache, shadow_cascades: u32) -> Vec<Arc
 pub struct MaterialShader {
     pub id: String,
     pub shader: Arc<ShaderModule>,
+    /// Whether the shader reads its materials from the shared [BindlessMaterials] bind group
+    /// instead of the bind group of each material
+    pub bindless: bool,
 }
 
 pub trait Material: Debug + Sync + Send + as_any::AsAny {
@@ -425,6 +437,12 @@ pub trait Material: Debug + Sync + Send + as_any::AsAny {
 
     fn bind_group(&self) -> &wgpu::BindGroup;
 
+    /// The slot of the material in [BindlessMaterials], which shaders that read their materials
+    /// from it use instead of [Material::bind_group]
+    fn bindless_slot(&self) -> Option<u32> {
+        None
+    }
+
     fn transparent(&self) -> Option<bool> {
         None
     }
@@ -473,6 +491,8 @@ pub struct RendererShader {
     /// TODO: Apply to tree renderer too (only applies to transparent now)
     pub depth_write_enabled: bool,
     pub transparency_group: i32,
+    /// See [MaterialShader::bindless]
+    pub bindless: bool,
 }
 impl RendererShader {
     fn get_fs_main_name(&self, main: FSMain) -> &str {
@@ -41,6 +41,18 @@ impl LodCutoffs {
 
         Self(val)
     }
+
+    /// Removes the `count` most detailed levels after the first, so that the remaining levels
+    /// shift down and the least detailed of the removed levels takes the place of level 0.
+    pub fn skip_levels(&self, count: usize) -> Self {
+        let mut val = [0.0; MAX_LOD_LEVELS];
+        val[0] = self.0[0];
+        let count = count.min(MAX_LOD_LEVELS - 1);
+        let len = MAX_LOD_LEVELS - 1 - count;
+        val[1..1 + len].copy_from_slice(&self.0[1 + count..]);
+
+        Self(val)
+    }
 }
 
 components!("rendering", {
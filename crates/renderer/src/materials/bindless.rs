@@ -0,0 +1,338 @@
+//! The materials of the bindless path of [PbrMaterial](super::pbr_material::PbrMaterial).
+//!
+//! Instead of one bind group per material, the parameters of all materials are kept in one
+//! storage buffer, and their textures and samplers in binding arrays, so that a single bind group
+//! serves all of them. The shader finds the material of a primitive through the material slots
+//! of the renderer, and indexes the arrays with the indices stored in the material.
+
+use std::{collections::HashMap, num::NonZeroU32, sync::Arc};
+
+use ambient_gpu::{
+    gpu::{Gpu, GpuKey},
+    sampler::SamplerKey,
+    shader_module::BindGroupDesc,
+    std_assets::{DefaultNormalMapViewKey, PixelTextureViewKey},
+    texture::TextureView,
+    typed_buffer::TypedBuffer,
+};
+use ambient_native_std::asset_cache::{AssetCache, SyncAssetKey, SyncAssetKeyExt};
+use glam::{uvec4, UVec4};
+use parking_lot::Mutex;
+
+use super::pbr_material::{default_metallic_roughness, PbrMaterialParams};
+use crate::MATERIAL_BIND_GROUP;
+
+/// The number of textures that the materials can use between them.
+pub const BINDLESS_TEXTURE_CAPACITY: u32 = 4096;
+/// The number of samplers that the materials can use between them.
+pub const BINDLESS_SAMPLER_CAPACITY: u32 = 8;
+
+/// The sampled textures and samplers that the other bind groups of the standard shaders use,
+/// which count towards the same limits as the binding arrays.
+const RESERVED_BINDINGS: u32 = 32;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct BindlessMaterial {
+    params: PbrMaterialParams,
+    /// The indices of the base color, normal map and metallic roughness textures, and of the
+    /// sampler
+    textures: UVec4,
+}
+
+/// The textures and sampler of a material that is added to [BindlessMaterials].
+pub struct BindlessTextures<'a> {
+    pub base_color: &'a Arc<TextureView>,
+    pub normalmap: &'a Arc<TextureView>,
+    pub metallic_roughness: &'a Arc<TextureView>,
+    pub sampler: &'a Arc<wgpu::Sampler>,
+}
+
+pub fn get_bindless_material_layout() -> BindGroupDesc<'static> {
+    BindGroupDesc {
+        label: MATERIAL_BIND_GROUP.into(),
+        entries: vec![
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: NonZeroU32::new(BINDLESS_SAMPLER_CAPACITY),
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: NonZeroU32::new(BINDLESS_TEXTURE_CAPACITY),
+            },
+        ],
+    }
+}
+
+/// Returns the bindless materials, or `None` if the GPU can't index binding arrays per draw, in
+/// which case each material is bound with its own bind group.
+#[derive(Debug)]
+pub struct BindlessMaterialsKey;
+impl SyncAssetKey<Option<Arc<BindlessMaterials>>> for BindlessMaterialsKey {
+    fn load(&self, assets: AssetCache) -> Option<Arc<BindlessMaterials>> {
+        let gpu = GpuKey.get(&assets);
+        let limits = gpu.device.limits();
+        if !gpu.bindless
+            || limits.max_sampled_textures_per_shader_stage
+                < BINDLESS_TEXTURE_CAPACITY + RESERVED_BINDINGS
+            || limits.max_samplers_per_shader_stage < BINDLESS_SAMPLER_CAPACITY + RESERVED_BINDINGS
+        {
+            tracing::debug!("Bindless materials are not supported; binding materials one by one");
+            return None;
+        }
+        Some(Arc::new(BindlessMaterials::new(&gpu, &assets)))
+    }
+}
+
+/// The parameters, textures and samplers of the materials that are bound through one bind group.
+pub struct BindlessMaterials {
+    layout: Arc<wgpu::BindGroupLayout>,
+    defaults: [Arc<TextureView>; 3],
+    default_sampler: Arc<wgpu::Sampler>,
+    state: Mutex<BindlessState>,
+}
+
+struct BindlessState {
+    materials: Vec<BindlessMaterial>,
+    free_materials: Vec<u32>,
+    buffer: TypedBuffer<BindlessMaterial>,
+    textures: BindingTable<TextureView>,
+    samplers: BindingTable<wgpu::Sampler>,
+    /// Cleared when the buffer or the contents of the binding arrays change
+    bind_group: Option<Arc<wgpu::BindGroup>>,
+}
+
+impl BindlessMaterials {
+    fn new(gpu: &Gpu, assets: &AssetCache) -> Self {
+        let defaults = [
+            PixelTextureViewKey::white().get(assets),
+            DefaultNormalMapViewKey.get(assets),
+            default_metallic_roughness(assets),
+        ];
+        let default_sampler = SamplerKey::LINEAR_CLAMP_TO_EDGE.get(assets);
+
+        // The defaults are never released, so they can always be used in place of the
+        // textures and samplers that don't fit
+        let mut textures = BindingTable::new(BINDLESS_TEXTURE_CAPACITY);
+        for texture in &defaults {
+            textures.acquire(texture);
+        }
+        let mut samplers = BindingTable::new(BINDLESS_SAMPLER_CAPACITY);
+        samplers.acquire(&default_sampler);
+
+        Self {
+            layout: get_bindless_material_layout().get(assets),
+            defaults,
+            default_sampler,
+            state: Mutex::new(BindlessState {
+                materials: Vec::new(),
+                free_materials: Vec::new(),
+                buffer: TypedBuffer::new(
+                    gpu,
+                    Some("BindlessMaterials.buffer"),
+                    1,
+                    wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+                ),
+                textures,
+                samplers,
+                bind_group: None,
+            }),
+        }
+    }
+
+    /// Adds a material, and returns its slot.
+    pub fn insert(&self, gpu: &Gpu, params: PbrMaterialParams, textures: BindlessTextures) -> u32 {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+
+        let [base_color, normalmap, metallic_roughness] = [
+            (textures.base_color, &self.defaults[0]),
+            (textures.normalmap, &self.defaults[1]),
+            (textures.metallic_roughness, &self.defaults[2]),
+        ]
+        .map(|(texture, default)| {
+            state.textures.acquire(texture).unwrap_or_else(|| {
+                tracing::warn!(
+                    "More than {BINDLESS_TEXTURE_CAPACITY} textures are used by materials; \
+                     using a default texture instead"
+                );
+                state.textures.acquire(default).unwrap()
+            })
+        });
+        let sampler = state.samplers.acquire(textures.sampler).unwrap_or_else(|| {
+            tracing::warn!(
+                "More than {BINDLESS_SAMPLER_CAPACITY} samplers are used by materials; using \
+                 the default sampler instead"
+            );
+            state.samplers.acquire(&self.default_sampler).unwrap()
+        });
+        state.bind_group = None;
+
+        let material = BindlessMaterial {
+            params,
+            textures: uvec4(base_color, normalmap, metallic_roughness, sampler),
+        };
+        let slot = match state.free_materials.pop() {
+            Some(slot) => {
+                state.materials[slot as usize] = material;
+                slot
+            }
+            None => {
+                state.materials.push(material);
+                (state.materials.len() - 1) as u32
+            }
+        };
+
+        if state.buffer.set_len(gpu, state.materials.len()) {
+            state.buffer.write(gpu, 0, &state.materials);
+        } else {
+            state.buffer.write(gpu, slot as usize, &[material]);
+        }
+        slot
+    }
+
+    /// Replaces the parameters of the material in `slot`.
+    pub fn update_params(&self, gpu: &Gpu, slot: u32, params: PbrMaterialParams) {
+        let mut state = self.state.lock();
+        let material = &mut state.materials[slot as usize];
+        material.params = params;
+        let material = *material;
+        state.buffer.write(gpu, slot as usize, &[material]);
+    }
+
+    /// Removes the material in `slot`, and releases its textures and sampler.
+    pub fn remove(&self, slot: u32) {
+        let mut state = self.state.lock();
+        let textures = state.materials[slot as usize].textures;
+        for index in [textures.x, textures.y, textures.z] {
+            state.textures.release(index);
+        }
+        state.samplers.release(textures.w);
+        state.free_materials.push(slot);
+        state.bind_group = None;
+    }
+
+    /// The bind group of all the materials, which is recreated if they have changed since it was
+    /// last created.
+    pub fn bind_group(&self, gpu: &Gpu) -> Arc<wgpu::BindGroup> {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+        state
+            .bind_group
+            .get_or_insert_with(|| {
+                // The unused elements of the arrays are filled with the defaults
+                let textures = state
+                    .textures
+                    .values()
+                    .map(|texture| &texture.unwrap_or(&self.defaults[0]).handle)
+                    .collect::<Vec<_>>();
+                let samplers = state
+                    .samplers
+                    .values()
+                    .map(|sampler| &**sampler.unwrap_or(&self.default_sampler))
+                    .collect::<Vec<_>>();
+
+                Arc::new(gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &self.layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: state.buffer.buffer().as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::SamplerArray(&samplers),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::TextureViewArray(&textures),
+                        },
+                    ],
+                    label: Some("BindlessMaterials.bind_group"),
+                }))
+            })
+            .clone()
+    }
+}
+
+impl std::fmt::Debug for BindlessMaterials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock();
+        f.debug_struct("BindlessMaterials")
+            .field("materials", &state.materials.len())
+            .field("free_materials", &state.free_materials.len())
+            .finish()
+    }
+}
+
+/// The elements of a binding array, which are shared by the materials that use the same
+/// resource.
+struct BindingTable<T> {
+    /// The resource in each element, and the number of materials that use it
+    elements: Vec<Option<(Arc<T>, usize)>>,
+    /// The element of each resource, by its address
+    lookup: HashMap<usize, u32>,
+    free: Vec<u32>,
+}
+
+impl<T> BindingTable<T> {
+    fn new(capacity: u32) -> Self {
+        Self {
+            elements: (0..capacity).map(|_| None).collect(),
+            lookup: HashMap::new(),
+            free: (0..capacity).rev().collect(),
+        }
+    }
+
+    /// Returns the element of `value`, adding it if it isn't in the table yet, or `None` if the
+    /// table is full.
+    fn acquire(&mut self, value: &Arc<T>) -> Option<u32> {
+        let key = Arc::as_ptr(value) as usize;
+        let index = match self.lookup.get(&key) {
+            Some(&index) => index,
+            None => {
+                let index = self.free.pop()?;
+                self.elements[index as usize] = Some((value.clone(), 0));
+                self.lookup.insert(key, index);
+                index
+            }
+        };
+        self.elements[index as usize].as_mut().unwrap().1 += 1;
+        Some(index)
+    }
+
+    fn release(&mut self, index: u32) {
+        let element = &mut self.elements[index as usize];
+        let (value, count) = element.as_mut().unwrap();
+        *count -= 1;
+        if *count == 0 {
+            self.lookup.remove(&(Arc::as_ptr(value) as usize));
+            *element = None;
+            self.free.push(index);
+        }
+    }
+
+    fn values(&self) -> impl Iterator<Item = Option<&Arc<T>>> {
+        self.elements
+            .iter()
+            .map(|element| element.as_ref().map(|(value, _)| value))
+    }
+}
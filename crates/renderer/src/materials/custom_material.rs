@@ -0,0 +1,274 @@
+//! Materials with a WGSL shader provided by a package, which run in the standard vertex pipeline.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt::Write,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use ambient_gpu::{
+    gpu::Gpu,
+    shader_module::{BindGroupDesc, ShaderModule},
+    texture::TextureView,
+};
+use ambient_native_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    cb, friendly_id,
+};
+use glam::Vec4;
+use wgpu::{util::DeviceExt, BindGroup};
+
+use super::super::{Material, MaterialShader, MATERIAL_BIND_GROUP};
+use crate::{RendererConfig, RendererShaderProducer, StandardShaderKey};
+
+/// The maximum number of textures of a custom material.
+pub const MAX_CUSTOM_MATERIAL_TEXTURES: usize = 8;
+/// The maximum number of parameters of a custom material.
+pub const MAX_CUSTOM_MATERIAL_PARAMS: usize = 64;
+
+fn get_material_layout(texture_count: usize) -> BindGroupDesc<'static> {
+    let texture = |binding| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    };
+
+    BindGroupDesc {
+        label: MATERIAL_BIND_GROUP.into(),
+        entries: [
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ]
+        .into_iter()
+        .chain((0..texture_count).map(|i| texture(i as u32 + 2)))
+        .collect(),
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CustomMaterialConfig {
+    pub name: String,
+    /// The WGSL code of the material, which defines
+    /// `fn get_material(in: MaterialInput) -> MaterialOutput`
+    pub shader: String,
+    /// Available to the shader as the `vec4<f32>` fields of `material_params`
+    pub params: Vec<(String, Vec4)>,
+    /// Available to the shader as `texture_2d<f32>` variables with their name
+    pub textures: Vec<(String, Arc<TextureView>)>,
+    /// Available to the shader as `material_sampler`
+    pub sampler: Arc<wgpu::Sampler>,
+    pub transparent: Option<bool>,
+}
+
+impl CustomMaterialConfig {
+    /// Checks the parameters and textures, and compiles the shader with the standard vertex
+    /// pipeline.
+    pub fn validate(&self, assets: &AssetCache) -> anyhow::Result<()> {
+        if self.params.len() > MAX_CUSTOM_MATERIAL_PARAMS {
+            anyhow::bail!(
+                "A custom material can have at most {MAX_CUSTOM_MATERIAL_PARAMS} parameters"
+            );
+        }
+        if self.textures.len() > MAX_CUSTOM_MATERIAL_TEXTURES {
+            anyhow::bail!(
+                "A custom material can have at most {MAX_CUSTOM_MATERIAL_TEXTURES} textures"
+            );
+        }
+
+        let mut names = vec!["material_params", "material_sampler"];
+        let params = self.params.iter().map(|(name, _)| name);
+        let textures = self.textures.iter().map(|(name, _)| name);
+        for name in params.chain(textures) {
+            let mut chars = name.chars();
+            let valid = chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+                && !name.starts_with("__");
+            if !valid {
+                anyhow::bail!("{name:?} is not a valid WGSL identifier");
+            }
+            if names.contains(&name.as_str()) {
+                anyhow::bail!("The name {name:?} is used more than once");
+            }
+            names.push(name);
+        }
+
+        if self.shader.contains("@group") || self.shader.contains("@binding") {
+            anyhow::bail!(
+                "The shader can't declare bindings, as the parameters and textures are bound for it"
+            );
+        }
+        if !self.shader.contains("fn get_material(") {
+            anyhow::bail!(
+                "The shader must define `fn get_material(in: MaterialInput) -> MaterialOutput`"
+            );
+        }
+
+        // The material shader doesn't depend on the number of shadow cascades
+        StandardShaderKey {
+            material_shader: self.material_shader(),
+            lit: true,
+            shadow_cascades: RendererConfig::default().shadow_cascades,
+        }
+        .create(assets)?;
+
+        Ok(())
+    }
+
+    /// The shader of the material, which is shared by the materials with the same shader and
+    /// the same parameter and texture names.
+    pub fn material_shader(&self) -> Arc<MaterialShader> {
+        let mut source = "struct CustomMaterialParams {\n".to_string();
+        for (name, _) in &self.params {
+            writeln!(source, "    {name}: vec4<f32>,").unwrap();
+        }
+        // Structs can't be empty
+        if self.params.is_empty() {
+            source += "    _unused: vec4<f32>,\n";
+        }
+        source += "};\n\n";
+        source += "@group(MATERIAL_BIND_GROUP)\n@binding(0)\n";
+        source += "var<uniform> material_params: CustomMaterialParams;\n\n";
+        source += "@group(MATERIAL_BIND_GROUP)\n@binding(1)\nvar material_sampler: sampler;\n\n";
+        for (i, (name, _)) in self.textures.iter().enumerate() {
+            writeln!(
+                source,
+                "@group(MATERIAL_BIND_GROUP)\n@binding({})\nvar {name}: texture_2d<f32>;\n",
+                i + 2
+            )
+            .unwrap();
+        }
+        source += &self.shader;
+
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        Arc::new(MaterialShader {
+            id: format!("custom_material_shader_{:x}", hasher.finish()),
+            shader: Arc::new(
+                ShaderModule::new("CustomMaterial", source)
+                    .with_binding_desc(get_material_layout(self.textures.len())),
+            ),
+            bindless: false,
+        })
+    }
+}
+
+pub struct CustomMaterial {
+    id: String,
+    pub config: CustomMaterialConfig,
+    material_shader: Arc<MaterialShader>,
+    _buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl CustomMaterial {
+    /// Creates the material from a config that has been validated with
+    /// [CustomMaterialConfig::validate].
+    pub fn new(gpu: &Gpu, assets: &AssetCache, config: CustomMaterialConfig) -> Self {
+        let layout = get_material_layout(config.textures.len()).get(assets);
+
+        let mut params = config
+            .params
+            .iter()
+            .map(|(_, value)| *value)
+            .collect::<Vec<_>>();
+        if params.is_empty() {
+            params.push(Vec4::ZERO);
+        }
+        let buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("CustomMaterial.buffer"),
+                usage: wgpu::BufferUsages::UNIFORM,
+                contents: bytemuck::cast_slice(&params),
+            });
+
+        let mut entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(buffer.as_entire_buffer_binding()),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&config.sampler),
+            },
+        ];
+        for (i, (_, texture)) in config.textures.iter().enumerate() {
+            entries.push(wgpu::BindGroupEntry {
+                binding: i as u32 + 2,
+                resource: wgpu::BindingResource::TextureView(&texture.handle),
+            });
+        }
+
+        Self {
+            id: friendly_id(),
+            bind_group: gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &layout,
+                entries: &entries,
+                label: Some("CustomMaterial.bind_group"),
+            }),
+            _buffer: buffer,
+            material_shader: config.material_shader(),
+            config,
+        }
+    }
+
+    /// The shader to render the material with.
+    pub fn renderer_shader(&self) -> RendererShaderProducer {
+        let material_shader = self.material_shader.clone();
+        cb(move |assets: &AssetCache, config: &RendererConfig| {
+            StandardShaderKey {
+                material_shader: material_shader.clone(),
+                lit: true,
+                shadow_cascades: config.shadow_cascades,
+            }
+            .get(assets)
+        })
+    }
+}
+
+impl std::fmt::Debug for CustomMaterial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomMaterial")
+            .field("id", &self.id)
+            .field("name", &self.config.name)
+            .field("shader", &self.material_shader.id)
+            .finish()
+    }
+}
+
+impl Material for CustomMaterial {
+    fn bind_group(&self) -> &BindGroup {
+        &self.bind_group
+    }
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+    fn transparent(&self) -> Option<bool> {
+        self.config.transparent
+    }
+}
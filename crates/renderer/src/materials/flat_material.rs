@@ -40,6 +40,7 @@ impl SyncAssetKey<Arc<MaterialShader>> for FlatMaterialShaderKey {
                     .with_binding_desc(get_material_layout()),
             ),
             id: "flat_material_shader".to_string(),
+            bindless: false,
         })
     }
 }
@@ -1,2 +1,6 @@
+pub mod bindless;
+pub mod custom_material;
 pub mod flat_material;
 pub mod pbr_material;
+
+pub use bindless::{BindlessMaterials, BindlessMaterialsKey};
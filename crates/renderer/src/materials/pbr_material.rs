@@ -19,7 +19,11 @@ use glam::{uvec4, Vec4};
 use serde::{Deserialize, Serialize};
 use wgpu::{util::DeviceExt, BindGroup};
 
-use super::super::{Material, MaterialShader, RendererShader, MATERIAL_BIND_GROUP};
+use super::{
+    super::{Material, MaterialShader, RendererShader, MATERIAL_BIND_GROUP},
+    bindless::{get_bindless_material_layout, BindlessMaterials, BindlessTextures},
+    BindlessMaterialsKey,
+};
 use crate::{RendererConfig, StandardShaderKey};
 
 fn get_material_layout() -> BindGroupDesc<'static> {
@@ -86,13 +90,42 @@ impl SyncAssetKey<Arc<MaterialShader>> for PbrMaterialShaderKey {
                 ShaderModule::new("PbrMaterial", include_file!("pbr_material.wgsl"))
                     .with_binding_desc(get_material_layout()),
             ),
+            bindless: false,
+        })
+    }
+}
+
+/// The PBR material shader that reads the materials from [BindlessMaterials].
+#[derive(Debug)]
+pub struct PbrBindlessMaterialShaderKey;
+impl SyncAssetKey<Arc<MaterialShader>> for PbrBindlessMaterialShaderKey {
+    fn load(&self, _assets: AssetCache) -> Arc<MaterialShader> {
+        Arc::new(MaterialShader {
+            id: "pbr_bindless_material_shader".to_string(),
+            shader: Arc::new(
+                ShaderModule::new(
+                    "PbrBindlessMaterial",
+                    include_file!("pbr_material_bindless.wgsl"),
+                )
+                .with_binding_desc(get_bindless_material_layout()),
+            ),
+            bindless: true,
         })
     }
 }
 
+/// The material shader of [PbrMaterial]s, which is the bindless one where it's supported.
+fn get_pbr_material_shader(assets: &AssetCache) -> Arc<MaterialShader> {
+    if BindlessMaterialsKey.get(assets).is_some() {
+        PbrBindlessMaterialShaderKey.get(assets)
+    } else {
+        PbrMaterialShaderKey.get(assets)
+    }
+}
+
 pub fn get_pbr_shader(assets: &AssetCache, config: &RendererConfig) -> Arc<RendererShader> {
     StandardShaderKey {
-        material_shader: PbrMaterialShaderKey.get(assets),
+        material_shader: get_pbr_material_shader(assets),
         lit: true,
         shadow_cascades: config.shadow_cascades,
     }
@@ -101,7 +134,7 @@ pub fn get_pbr_shader(assets: &AssetCache, config: &RendererConfig) -> Arc<Rende
 
 pub fn get_pbr_shader_unlit(assets: &AssetCache, config: &RendererConfig) -> Arc<RendererShader> {
     StandardShaderKey {
-        material_shader: PbrMaterialShaderKey.get(assets),
+        material_shader: get_pbr_material_shader(assets),
         lit: false,
         shadow_cascades: config.shadow_cascades,
     }
@@ -150,7 +183,11 @@ pub struct PbrMaterial {
     id: String,
     pub config: PbrMaterialConfig,
     buffer: wgpu::Buffer,
+    /// The bind group of the material, which shaders that don't read their materials from
+    /// [BindlessMaterials] use
     bind_group: wgpu::BindGroup,
+    /// The slot of the material, if it has been added to [BindlessMaterials]
+    bindless: Option<(Arc<BindlessMaterials>, u32)>,
 }
 
 impl PbrMaterial {
@@ -165,6 +202,20 @@ impl PbrMaterial {
                 contents: bytemuck::cast_slice(&[config.params]),
             });
 
+        let bindless = BindlessMaterialsKey.get(assets).map(|bindless| {
+            let slot = bindless.insert(
+                gpu,
+                config.params,
+                BindlessTextures {
+                    base_color: &config.base_color,
+                    normalmap: &config.normalmap,
+                    metallic_roughness: &config.metallic_roughness,
+                    sampler: &config.sampler,
+                },
+            );
+            (bindless, slot)
+        });
+
         Self {
             id: friendly_id(),
             bind_group: gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -197,6 +248,7 @@ impl PbrMaterial {
             }),
             buffer,
             config,
+            bindless,
         }
     }
     pub fn base_color_from_file(gpu: &Gpu, assets: &AssetCache, url: &str) -> Self {
@@ -228,6 +280,9 @@ impl PbrMaterial {
     pub fn upload_params(&self, gpu: &Gpu) {
         gpu.queue
             .write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.config.params]));
+        if let Some((bindless, slot)) = &self.bindless {
+            bindless.update_params(gpu, *slot, self.config.params);
+        }
     }
     pub fn gpu_size(&self) -> u64 {
         self.config.base_color.texture.size_in_bytes
@@ -237,7 +292,7 @@ impl PbrMaterial {
 }
 
 // Mimics the flat_material standard material
-fn default_metallic_roughness(assets: &AssetCache) -> Arc<TextureView> {
+pub(crate) fn default_metallic_roughness(assets: &AssetCache) -> Arc<TextureView> {
     PixelTextureViewKey {
         color: uvec4(0, (0.4f32 * 255.0) as u32, 0, 0),
     }
@@ -253,11 +308,23 @@ impl std::fmt::Debug for PbrMaterial {
             .finish()
     }
 }
+impl Drop for PbrMaterial {
+    fn drop(&mut self) {
+        if let Some((bindless, slot)) = &self.bindless {
+            bindless.remove(*slot);
+        }
+    }
+}
+
 impl Material for PbrMaterial {
     fn bind_group(&self) -> &BindGroup {
         &self.bind_group
     }
 
+    fn bindless_slot(&self) -> Option<u32> {
+        self.bindless.as_ref().map(|(_, slot)| *slot)
+    }
+
     fn id(&self) -> &str {
         &self.id
     }
@@ -0,0 +1,48 @@
+struct PbrMaterialParams {
+    base_color_factor: vec4<f32>,
+    emissive_factor: vec4<f32>,
+    alpha_cutoff: f32,
+    metallic: f32,
+    roughness: f32,
+};
+
+struct BindlessPbrMaterial {
+    params: PbrMaterialParams,
+    // The indices of the base color, normal map and metallic roughness textures, and of the sampler
+    textures: vec4<u32>,
+};
+
+@group(MATERIAL_BIND_GROUP)
+@binding(0)
+var<storage> pbr_materials: array<BindlessPbrMaterial>;
+
+@group(MATERIAL_BIND_GROUP)
+@binding(1)
+var pbr_samplers: binding_array<sampler>;
+
+@group(MATERIAL_BIND_GROUP)
+@binding(2)
+var pbr_textures: binding_array<texture_2d<f32>>;
+
+fn get_material(in: MaterialInput) -> MaterialOutput {
+    var out: MaterialOutput;
+    let material = pbr_materials[material_slots[primitives.data[in.instance_index].w]];
+    let pbr_params = material.params;
+
+    let base_color_texture_sample = textureSample(pbr_textures[material.textures.x], pbr_samplers[material.textures.w], in.texcoord);
+    let mr = textureSample(pbr_textures[material.textures.z], pbr_samplers[material.textures.w], in.texcoord);
+    let color = base_color_texture_sample * pbr_params.base_color_factor * get_entity_color_or(in.entity_loc, vec4<f32>(1., 1., 1., 1.));
+    out.opacity = color.a;
+
+    out.metallic = mr.r * pbr_params.metallic;
+    out.roughness = max(mr.g * pbr_params.roughness, 0.1);
+
+    out.alpha_cutoff = pbr_params.alpha_cutoff;
+    out.base_color = color.rgb;
+    out.emissive_factor = pbr_params.emissive_factor.rgb;
+    out.shading = 1.;
+
+    let normal = textureSample(pbr_textures[material.textures.y], pbr_samplers[material.textures.w], in.texcoord).xyz * 2. - 1.;
+    out.normal = in.normal_matrix * normal;
+    return out;
+}
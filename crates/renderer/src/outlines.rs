@@ -81,6 +81,7 @@ impl Outlines {
             GraphicsPipelineInfo {
                 targets: &[Some(gpu.swapchain_format().into())],
                 topology: PrimitiveTopology::TriangleStrip,
+                sample_count: gpu.sample_count,
                 ..Default::default()
             },
         );
@@ -119,6 +120,7 @@ impl Outlines {
                         .render_mode
                         .unwrap_or_else(RenderMode::instrinsic_render_mode),
                     software_culling: settings.software_culling,
+                    sample_count: 1,
                 },
             ),
             _config: config,
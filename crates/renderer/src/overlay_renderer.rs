@@ -127,6 +127,7 @@ impl OverlayRenderer {
                         vs_main: &shader.vs_main,
                         fs_main: shader.get_fs_main_name(config.fs_main),
                         targets: &config.targets,
+                        sample_count: gpu.sample_count,
                         ..Default::default()
                     }
                     .with_depth(),
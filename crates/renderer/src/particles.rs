@@ -0,0 +1,684 @@
+//! GPU particles. The particles of all emitters live in a single buffer, in which each emitter
+//! has a range that it emits into like a ring buffer. New particles are initialized, and all of
+//! them are simulated, by compute shaders, and they are then drawn as instanced quads facing the
+//! camera.
+
+use std::{collections::HashMap, num::NonZeroU64, str::FromStr, sync::Arc};
+
+use ambient_core::{
+    asset_cache, async_ecs::async_run, main_scene, runtime, transform::local_to_world,
+};
+use ambient_ecs::{
+    components,
+    generated::particles::components::{
+        particle_additive, particle_color_end, particle_color_start, particle_drag,
+        particle_emitter, particle_gravity, particle_lifetime, particle_max_count, particle_rate,
+        particle_size_end, particle_size_start, particle_spawn_radius, particle_texture_from_url,
+        particle_velocity, particle_velocity_spread,
+    },
+    query, Entity, EntityId, SystemGroup, World,
+};
+use ambient_gpu::{
+    gpu::Gpu,
+    sampler::SamplerKey,
+    shader_module::{
+        BindGroupDesc, ComputePipeline, GraphicsPipeline, GraphicsPipelineInfo, Shader,
+        ShaderModule,
+    },
+    std_assets::PixelTextureViewKey,
+    texture::TextureView,
+    texture_loaders::TextureFromUrl,
+    typed_buffer::TypedBuffer,
+};
+use ambient_native_std::{
+    asset_cache::{AssetCache, AsyncAssetKeyExt, SyncAssetKeyExt},
+    asset_url::AbsAssetUrl,
+    include_file,
+};
+use ambient_sys::time::Instant;
+use glam::{Mat4, UVec2, Vec3, Vec4};
+use wgpu::{BindGroupLayout, BindGroupLayoutEntry, BindingType, BufferBindingType, ShaderStages};
+
+use crate::{bind_groups::BindGroups, get_overlay_modules, RendererConfig, RendererTarget};
+
+components!("rendering", {
+    /// The texture of the particles of an emitter, loaded from `particle_texture_from_url`
+    particle_texture: Arc<TextureView>,
+    /// The number of particles that an emitter emits on the next frame, in addition to its rate
+    particle_pending_burst: u32,
+});
+
+const PARTICLES_SIMULATION_BIND_GROUP: &str = "PARTICLES_SIMULATION_BIND_GROUP";
+const PARTICLES_BIND_GROUP: &str = "PARTICLES_BIND_GROUP";
+
+const WORKGROUP_SIZE: u32 = 64;
+const MIN_MAX_COUNT: u32 = 256;
+/// Frames longer than this are simulated as this long, so that the particles don't jump after a
+/// hitch
+const MAX_DT: f32 = 0.1;
+
+/// Emits `count` particles from `emitter` on the next frame, on top of its `particle_rate`.
+pub fn burst(world: &mut World, emitter: EntityId, count: u32) -> anyhow::Result<()> {
+    match world.get_mut(emitter, particle_pending_burst()) {
+        Ok(pending) => *pending = pending.saturating_add(count),
+        Err(_) => world.add_component(emitter, particle_pending_burst(), count)?,
+    }
+    Ok(())
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "particles",
+        vec![
+            query(particle_emitter())
+                .spawned()
+                .to_system(|q, world, qs, _| {
+                    for (id, _) in q.collect_cloned(world, qs) {
+                        let mut data = Entity::new().with(main_scene(), ());
+                        if !world.has_component(id, local_to_world()) {
+                            data.set(local_to_world(), Default::default());
+                        }
+                        world.add_components(id, data).ok();
+                    }
+                }),
+            query(particle_texture_from_url().changed()).to_system(|q, world, qs, _| {
+                for (id, url) in q.collect_cloned(world, qs) {
+                    let url = match AbsAssetUrl::from_str(&url) {
+                        Ok(value) => value,
+                        Err(err) => {
+                            tracing::warn!(
+                                "Failed to parse particle_texture_from_url url: {err:?}"
+                            );
+                            continue;
+                        }
+                    };
+                    let assets = world.resource(asset_cache()).clone();
+                    let async_run = world.resource(async_run()).clone();
+                    world.resource(runtime()).spawn(async move {
+                        let texture = TextureFromUrl {
+                            url,
+                            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                        }
+                        .get(&assets)
+                        .await;
+                        match texture {
+                            Ok(texture) => async_run.run(move |world| {
+                                let view = Arc::new(texture.create_view(&Default::default()));
+                                // The emitter may have been despawned while the texture loaded
+                                world.add_component(id, particle_texture(), view).ok();
+                            }),
+                            Err(err) => {
+                                tracing::warn!("Failed to load particle texture: {err:?}")
+                            }
+                        }
+                    });
+                }
+            }),
+        ],
+    )
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct Particle {
+    position: Vec3,
+    age: f32,
+    velocity: Vec3,
+    lifetime: f32,
+    emitter: u32,
+    seed: u32,
+    _padding: UVec2,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct GpuEmitter {
+    transform: Mat4,
+    velocity: Vec3,
+    velocity_spread: f32,
+    gravity: Vec3,
+    drag: f32,
+    color_start: Vec4,
+    color_end: Vec4,
+    size_start: f32,
+    size_end: f32,
+    lifetime: f32,
+    spawn_radius: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct NewParticle {
+    slot: u32,
+    emitter: u32,
+    seed: u32,
+    _padding: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct SimulationParams {
+    dt: f32,
+    particle_count: u32,
+    new_count: u32,
+    _padding: u32,
+}
+
+/// The range of the particle buffer that an emitter emits into.
+struct EmitterState {
+    id: EntityId,
+    offset: u32,
+    capacity: u32,
+    /// The slot of the range that the next particle is emitted into
+    cursor: u32,
+    /// The fraction of a particle that is left over from emitting at the rate of the emitter
+    accumulated: f32,
+    additive: bool,
+    texture: Option<Arc<TextureView>>,
+}
+
+struct Pipelines {
+    emit: ComputePipeline,
+    simulate: ComputePipeline,
+    blended: GraphicsPipeline,
+    additive: GraphicsPipeline,
+}
+
+pub struct ParticlesRenderer {
+    config: RendererConfig,
+    /// The emitters by their index in the emitter buffer, which their particles refer to
+    emitters: Vec<Option<EmitterState>>,
+    indices: HashMap<EntityId, usize>,
+    particles: TypedBuffer<Particle>,
+    gpu_emitters: TypedBuffer<GpuEmitter>,
+    new_particles: TypedBuffer<NewParticle>,
+    params: TypedBuffer<SimulationParams>,
+    simulation_layout: Arc<BindGroupLayout>,
+    layout: Arc<BindGroupLayout>,
+    pipelines: Option<Pipelines>,
+    last_update: Option<Instant>,
+    seed: u32,
+}
+
+impl ParticlesRenderer {
+    pub fn new(gpu: &Gpu, assets: &AssetCache, config: RendererConfig) -> Self {
+        let storage = wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST;
+        Self {
+            config,
+            emitters: Vec::new(),
+            indices: HashMap::new(),
+            particles: TypedBuffer::new(
+                gpu,
+                Some("Particles.particles"),
+                1,
+                storage | wgpu::BufferUsages::COPY_SRC,
+            ),
+            gpu_emitters: TypedBuffer::new(gpu, Some("Particles.emitters"), 1, storage),
+            new_particles: TypedBuffer::new(gpu, Some("Particles.new_particles"), 1, storage),
+            params: TypedBuffer::new(
+                gpu,
+                Some("Particles.params"),
+                1,
+                wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            ),
+            simulation_layout: get_simulation_layout().get(assets),
+            layout: get_particles_layout().get(assets),
+            pipelines: None,
+            last_update: None,
+            seed: 0,
+        }
+    }
+
+    /// Emits and simulates the particles of the emitters of the scene, and draws them over the
+    /// target.
+    pub fn render(
+        &mut self,
+        gpu: &Gpu,
+        assets: &AssetCache,
+        world: &mut World,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &RendererTarget,
+        bind_groups: &BindGroups,
+    ) {
+        let now = Instant::now();
+        let dt = self
+            .last_update
+            .map_or(0., |last| (now - last).as_secs_f32().min(MAX_DT));
+        self.last_update = Some(now);
+
+        let new_particles = self.update(gpu, world, encoder, dt);
+        if self.indices.is_empty() {
+            return;
+        }
+
+        let pipelines = self
+            .pipelines
+            .get_or_insert_with(|| create_pipelines(gpu, assets, &self.config));
+
+        self.new_particles.fill(gpu, &new_particles, |_| {});
+        let particle_count = self.particles.len() as u32;
+        self.params.fill(
+            gpu,
+            &[SimulationParams {
+                dt,
+                particle_count,
+                new_count: new_particles.len() as u32,
+                _padding: 0,
+            }],
+            |_| {},
+        );
+
+        let simulation_bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Particles.simulation"),
+            layout: &self.simulation_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.particles.buffer().as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.gpu_emitters.buffer().as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.new_particles.buffer().as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: self.params.buffer().as_entire_binding(),
+                },
+            ],
+        });
+
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Particles.simulate"),
+            });
+            cpass.set_bind_group(0, &simulation_bind_group, &[]);
+            // New particles are simulated from the frame they are emitted in
+            if !new_particles.is_empty() {
+                cpass.set_pipeline(pipelines.emit.pipeline());
+                cpass.dispatch_workgroups(workgroups(new_particles.len() as u32), 1, 1);
+            }
+            cpass.set_pipeline(pipelines.simulate.pipeline());
+            cpass.dispatch_workgroups(workgroups(particle_count), 1, 1);
+        }
+
+        let white = PixelTextureViewKey::white().get(assets);
+        let sampler = SamplerKey::LINEAR_CLAMP_TO_EDGE.get(assets);
+        let draws = self
+            .emitters
+            .iter()
+            .flatten()
+            .map(|emitter| {
+                let texture = emitter.texture.as_ref().unwrap_or(&white);
+                let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Particles.emitter"),
+                    layout: &self.layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: self.particles.buffer().as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: self.gpu_emitters.buffer().as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::TextureView(&texture.handle),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 3,
+                            resource: wgpu::BindingResource::Sampler(&sampler),
+                        },
+                    ],
+                });
+                (emitter, bind_group)
+            })
+            .collect::<Vec<_>>();
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Particles"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target.color(),
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: target.depth_stencil(),
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        });
+        render_pass.set_bind_group(0, bind_groups.globals, &[]);
+        for (emitter, bind_group) in &draws {
+            let pipeline = if emitter.additive {
+                &pipelines.additive
+            } else {
+                &pipelines.blended
+            };
+            render_pass.set_pipeline(pipeline.pipeline());
+            render_pass.set_bind_group(1, bind_group, &[]);
+            render_pass.draw(0..6, emitter.offset..emitter.offset + emitter.capacity);
+        }
+    }
+
+    /// Updates the emitters from the world, and returns the particles to emit this frame.
+    fn update(
+        &mut self,
+        gpu: &Gpu,
+        world: &mut World,
+        encoder: &mut wgpu::CommandEncoder,
+        dt: f32,
+    ) -> Vec<NewParticle> {
+        let emitters = query((particle_emitter(), local_to_world()))
+            .incl(self.config.scene)
+            .iter(world, None)
+            .map(|(id, (_, transform))| (id, *transform))
+            .collect::<Vec<_>>();
+
+        // Emitters that were despawned or stopped emitting give their range back
+        let removed = self
+            .indices
+            .keys()
+            .filter(|id| !emitters.iter().any(|(emitter, _)| emitter == *id))
+            .copied()
+            .collect::<Vec<_>>();
+        for id in removed {
+            self.remove(id);
+        }
+
+        let mut gpu_emitters = Vec::with_capacity(self.emitters.len());
+        let mut new_particles = Vec::new();
+        let mut cleared = Vec::new();
+        for (id, transform) in emitters {
+            let rate = world.get(id, particle_rate()).unwrap_or(0.).max(0.);
+            let lifetime = world.get(id, particle_lifetime()).unwrap_or(1.).max(0.);
+            let capacity = world
+                .get(id, particle_max_count())
+                .unwrap_or_else(|_| ((rate * lifetime).ceil() as u32).max(MIN_MAX_COUNT));
+            if capacity == 0 {
+                self.remove(id);
+                continue;
+            }
+
+            let existing = self.indices.get(&id).copied().filter(|&index| {
+                self.emitters[index]
+                    .as_ref()
+                    .is_some_and(|emitter| emitter.capacity == capacity)
+            });
+            let index = match existing {
+                Some(index) => index,
+                None => {
+                    self.remove(id);
+                    let index = self.insert(id, capacity);
+                    cleared.push(index);
+                    index
+                }
+            };
+
+            let size_start = world.get(id, particle_size_start()).unwrap_or(0.1);
+            let gpu_emitter = GpuEmitter {
+                transform,
+                velocity: world.get(id, particle_velocity()).unwrap_or(Vec3::Z),
+                velocity_spread: world.get(id, particle_velocity_spread()).unwrap_or(0.5),
+                gravity: world.get(id, particle_gravity()).unwrap_or_default(),
+                drag: world.get(id, particle_drag()).unwrap_or_default(),
+                color_start: world.get(id, particle_color_start()).unwrap_or(Vec4::ONE),
+                color_end: world
+                    .get(id, particle_color_end())
+                    .unwrap_or(Vec4::new(1., 1., 1., 0.)),
+                size_start,
+                size_end: world.get(id, particle_size_end()).unwrap_or(size_start),
+                lifetime,
+                spawn_radius: world.get(id, particle_spawn_radius()).unwrap_or_default(),
+            };
+            if gpu_emitters.len() <= index {
+                gpu_emitters.resize(index + 1, GpuEmitter::default());
+            }
+            gpu_emitters[index] = gpu_emitter;
+
+            let burst = match world.get_mut(id, particle_pending_burst()) {
+                Ok(pending) => std::mem::take(pending),
+                Err(_) => 0,
+            };
+            let additive = world.has_component(id, particle_additive());
+            let texture = world.get_ref(id, particle_texture()).ok().cloned();
+
+            let emitter = self.emitters[index].as_mut().unwrap();
+            emitter.additive = additive;
+            emitter.texture = texture;
+            emitter.accumulated += rate * dt;
+            let count = (emitter.accumulated as u32).saturating_add(burst);
+            emitter.accumulated = emitter.accumulated.fract();
+            // Emitting more than the capacity would only overwrite the particles of this frame
+            for _ in 0..count.min(emitter.capacity) {
+                self.seed = self.seed.wrapping_add(1);
+                new_particles.push(NewParticle {
+                    slot: emitter.offset + emitter.cursor,
+                    emitter: index as u32,
+                    seed: self.seed,
+                    _padding: 0,
+                });
+                emitter.cursor = (emitter.cursor + 1) % emitter.capacity;
+            }
+        }
+
+        let len = self
+            .emitters
+            .iter()
+            .flatten()
+            .map(|emitter| emitter.offset + emitter.capacity)
+            .max()
+            .unwrap_or_default();
+        self.particles.set_len(gpu, len as usize);
+        gpu_emitters.resize(self.emitters.len(), GpuEmitter::default());
+        self.gpu_emitters.fill(gpu, &gpu_emitters, |_| {});
+
+        // The particles that were left in a range by its previous emitter are removed
+        for index in cleared {
+            let emitter = self.emitters[index].as_ref().unwrap();
+            let size = std::mem::size_of::<Particle>() as u64;
+            encoder.clear_buffer(
+                self.particles.buffer(),
+                emitter.offset as u64 * size,
+                NonZeroU64::new(emitter.capacity as u64 * size),
+            );
+        }
+
+        new_particles
+    }
+
+    /// Adds an emitter with a free range of `capacity` particles, and returns its index.
+    fn insert(&mut self, id: EntityId, capacity: u32) -> usize {
+        let mut ranges = self
+            .emitters
+            .iter()
+            .flatten()
+            .map(|emitter| (emitter.offset, emitter.offset + emitter.capacity))
+            .collect::<Vec<_>>();
+        ranges.sort_unstable();
+        // The first gap between the ranges that fits, or the end of the buffer
+        let mut offset = 0;
+        for (start, end) in ranges {
+            if start >= offset + capacity {
+                break;
+            }
+            offset = offset.max(end);
+        }
+
+        let emitter = EmitterState {
+            id,
+            offset,
+            capacity,
+            cursor: 0,
+            accumulated: 0.,
+            additive: false,
+            texture: None,
+        };
+        let index = match self.emitters.iter().position(Option::is_none) {
+            Some(index) => {
+                self.emitters[index] = Some(emitter);
+                index
+            }
+            None => {
+                self.emitters.push(Some(emitter));
+                self.emitters.len() - 1
+            }
+        };
+        self.indices.insert(id, index);
+        index
+    }
+
+    fn remove(&mut self, id: EntityId) {
+        if let Some(index) = self.indices.remove(&id) {
+            debug_assert_eq!(self.emitters[index].as_ref().map(|e| e.id), Some(id));
+            self.emitters[index] = None;
+        }
+    }
+}
+
+impl std::fmt::Debug for ParticlesRenderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParticlesRenderer")
+            .field("emitters", &self.indices.len())
+            .field("particles", &self.particles.len())
+            .finish()
+    }
+}
+
+fn workgroups(count: u32) -> u32 {
+    (count + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE
+}
+
+fn storage_entry(binding: u32, visibility: ShaderStages, read_only: bool) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+fn get_simulation_layout() -> BindGroupDesc<'static> {
+    BindGroupDesc {
+        entries: vec![
+            storage_entry(0, ShaderStages::COMPUTE, false),
+            storage_entry(1, ShaderStages::COMPUTE, true),
+            storage_entry(2, ShaderStages::COMPUTE, true),
+            BindGroupLayoutEntry {
+                binding: 3,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+        label: PARTICLES_SIMULATION_BIND_GROUP.into(),
+    }
+}
+
+fn get_particles_layout() -> BindGroupDesc<'static> {
+    BindGroupDesc {
+        entries: vec![
+            storage_entry(0, ShaderStages::VERTEX, true),
+            storage_entry(1, ShaderStages::VERTEX, true),
+            BindGroupLayoutEntry {
+                binding: 2,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 3,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+        label: PARTICLES_BIND_GROUP.into(),
+    }
+}
+
+fn create_pipelines(gpu: &Gpu, assets: &AssetCache, config: &RendererConfig) -> Pipelines {
+    let particle = Arc::new(ShaderModule::new(
+        "Particle",
+        include_file!("particles.wgsl"),
+    ));
+
+    let simulation = Shader::new(
+        assets,
+        "particles_simulate",
+        &[PARTICLES_SIMULATION_BIND_GROUP],
+        &ShaderModule::new(
+            "ParticlesSimulate",
+            include_file!("particles_simulate.wgsl"),
+        )
+        .with_binding_desc(get_simulation_layout())
+        .with_dependency(particle.clone()),
+    )
+    .unwrap();
+
+    let draw = Shader::new(
+        assets,
+        "particles",
+        &[crate::GLOBALS_BIND_GROUP, PARTICLES_BIND_GROUP],
+        &ShaderModule::new("Particles", include_file!("particles_draw.wgsl"))
+            .with_binding_desc(get_particles_layout())
+            .with_dependencies(get_overlay_modules(assets, config.shadow_cascades))
+            .with_dependency(particle),
+    )
+    .unwrap();
+
+    let draw_pipeline = |blend| {
+        let targets = [Some(wgpu::ColorTargetState {
+            format: gpu.swapchain_format(),
+            blend: Some(blend),
+            write_mask: wgpu::ColorWrites::ALL,
+        })];
+        let mut info = GraphicsPipelineInfo {
+            targets: &targets,
+            sample_count: gpu.sample_count,
+            ..Default::default()
+        }
+        .with_depth();
+        // The particles are tested against the depth of the scene, but don't occlude each other
+        if let Some(depth) = &mut info.depth {
+            depth.depth_write_enabled = false;
+        }
+        draw.to_pipeline(gpu, info)
+    };
+
+    Pipelines {
+        emit: simulation.to_compute_pipeline(gpu, "emit"),
+        simulate: simulation.to_compute_pipeline(gpu, "simulate"),
+        blended: draw_pipeline(wgpu::BlendState::ALPHA_BLENDING),
+        additive: draw_pipeline(wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::OVER,
+        }),
+    }
+}
@@ -0,0 +1,27 @@
+struct Particle {
+    position: vec3<f32>,
+    age: f32,
+    velocity: vec3<f32>,
+    lifetime: f32,
+    emitter: u32,
+    seed: u32,
+    _padding: vec2<u32>,
+};
+
+struct Emitter {
+    transform: mat4x4<f32>,
+    velocity: vec3<f32>,
+    velocity_spread: f32,
+    gravity: vec3<f32>,
+    drag: f32,
+    color_start: vec4<f32>,
+    color_end: vec4<f32>,
+    size_start: f32,
+    size_end: f32,
+    lifetime: f32,
+    spawn_radius: f32,
+};
+
+fn particle_alive(particle: Particle) -> bool {
+    return particle.age < particle.lifetime;
+}
@@ -0,0 +1,66 @@
+@group(PARTICLES_BIND_GROUP)
+@binding(0)
+var<storage> particles: array<Particle>;
+
+@group(PARTICLES_BIND_GROUP)
+@binding(1)
+var<storage> emitters: array<Emitter>;
+
+@group(PARTICLES_BIND_GROUP)
+@binding(2)
+var particle_texture: texture_2d<f32>;
+
+@group(PARTICLES_BIND_GROUP)
+@binding(3)
+var particle_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(instance_index) instance_index: u32, @builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let particle = particles[instance_index];
+    // Dead particles are collapsed to a point, so that nothing is drawn
+    if !particle_alive(particle) {
+        out.position = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+        return out;
+    }
+    let emitter = emitters[particle.emitter];
+    let t = particle.age / particle.lifetime;
+
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+    let corner = corners[vertex_index];
+
+    // The quads face the camera, and are kept upright (+Z)
+    let to_camera = normalize(global_params.camera_position.xyz - particle.position);
+    var right = cross(vec3<f32>(0.0, 0.0, 1.0), to_camera);
+    if length(right) < 0.001 {
+        right = vec3<f32>(1.0, 0.0, 0.0);
+    }
+    right = normalize(right);
+    let up = cross(to_camera, right);
+
+    let size = mix(emitter.size_start, emitter.size_end, t);
+    let position = particle.position + (right * corner.x + up * corner.y) * size * 0.5;
+
+    out.position = global_params.projection_view * vec4<f32>(position, 1.0);
+    out.uv = corner * vec2<f32>(0.5, -0.5) + 0.5;
+    out.color = mix(emitter.color_start, emitter.color_end, t);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(particle_texture, particle_sampler, in.uv) * in.color;
+}
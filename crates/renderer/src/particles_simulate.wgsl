@@ -0,0 +1,90 @@
+struct NewParticle {
+    slot: u32,
+    emitter: u32,
+    seed: u32,
+    _padding: u32,
+};
+
+struct SimulationParams {
+    dt: f32,
+    particle_count: u32,
+    new_count: u32,
+    _padding: u32,
+};
+
+@group(PARTICLES_SIMULATION_BIND_GROUP)
+@binding(0)
+var<storage, read_write> particles: array<Particle>;
+
+@group(PARTICLES_SIMULATION_BIND_GROUP)
+@binding(1)
+var<storage> emitters: array<Emitter>;
+
+@group(PARTICLES_SIMULATION_BIND_GROUP)
+@binding(2)
+var<storage> new_particles: array<NewParticle>;
+
+@group(PARTICLES_SIMULATION_BIND_GROUP)
+@binding(3)
+var<uniform> params: SimulationParams;
+
+// PCG hash; see https://www.reedbeta.com/blog/hash-functions-for-gpu-rendering/
+fn hash(value: u32) -> u32 {
+    let state = value * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn random(seed: ptr<function, u32>) -> f32 {
+    *seed = hash(*seed);
+    return f32(*seed) / 4294967295.0;
+}
+
+fn random_in_sphere(seed: ptr<function, u32>) -> vec3<f32> {
+    let z = random(seed) * 2.0 - 1.0;
+    let angle = random(seed) * 6.2831853;
+    let r = sqrt(1.0 - z * z);
+    return vec3<f32>(r * cos(angle), r * sin(angle), z) * pow(random(seed), 1.0 / 3.0);
+}
+
+@compute
+@workgroup_size(64)
+fn emit(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.new_count {
+        return;
+    }
+    let new_particle = new_particles[id.x];
+    let emitter = emitters[new_particle.emitter];
+    var seed = new_particle.seed;
+
+    let offset = random_in_sphere(&seed) * emitter.spawn_radius;
+    let velocity = emitter.velocity + random_in_sphere(&seed) * emitter.velocity_spread;
+
+    var particle: Particle;
+    particle.position = (emitter.transform * vec4<f32>(offset, 1.0)).xyz;
+    particle.velocity = (emitter.transform * vec4<f32>(velocity, 0.0)).xyz;
+    particle.age = 0.0;
+    particle.lifetime = emitter.lifetime;
+    particle.emitter = new_particle.emitter;
+    particle.seed = seed;
+    particles[new_particle.slot] = particle;
+}
+
+@compute
+@workgroup_size(64)
+fn simulate(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.particle_count {
+        return;
+    }
+    var particle = particles[id.x];
+    if !particle_alive(particle) {
+        return;
+    }
+    let emitter = emitters[particle.emitter];
+    let dt = params.dt;
+
+    particle.age += dt;
+    particle.velocity = (particle.velocity + emitter.gravity * dt) * max(1.0 - emitter.drag * dt, 0.0);
+    particle.position += particle.velocity * dt;
+    particles[id.x] = particle;
+}
@@ -0,0 +1,434 @@
+//! Post-processing of the image rendered by a camera. The effects are applied by fullscreen passes
+//! after the scene has been rendered: the bright parts of the image are extracted and blurred at a
+//! quarter of its resolution for bloom, and are then composited with the image along with
+//! chromatic aberration, tonemapping and the vignette, before FXAA is applied to the result.
+
+use std::sync::Arc;
+
+use ambient_ecs::{
+    generated::post_processing::{
+        components::{
+            bloom_intensity, bloom_threshold, chromatic_aberration, fxaa, tonemapping,
+            vignette_intensity,
+        },
+        types::Tonemapping,
+    },
+    ComponentDesc, Entity, EntityId, World,
+};
+use ambient_gpu::{
+    gpu::Gpu,
+    sampler::SamplerKey,
+    shader_module::{BindGroupDesc, GraphicsPipeline, GraphicsPipelineInfo, Shader, ShaderModule},
+    texture::{Texture, TextureView},
+    typed_buffer::TypedBuffer,
+};
+use ambient_native_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    include_file,
+};
+use wgpu::{BindGroupLayout, BindGroupLayoutEntry, BindingType, ShaderStages};
+
+use crate::RenderTarget;
+
+const POST_PROCESSING_BIND_GROUP: &str = "POST_PROCESSING_BIND_GROUP";
+
+const BLOOM_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// The bloom textures are this many times smaller than the image in each dimension
+const BLOOM_DOWNSCALE: u32 = 4;
+
+pub const DEFAULT_BLOOM_THRESHOLD: f32 = 0.8;
+
+/// The post-processing of a camera, which is stored in its components. Effects that are disabled
+/// don't have a component.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PostProcessingSettings {
+    pub bloom_threshold: f32,
+    /// Bloom is disabled when this is 0
+    pub bloom_intensity: f32,
+    pub tonemapping: Tonemapping,
+    pub vignette_intensity: f32,
+    pub chromatic_aberration: f32,
+    pub fxaa: bool,
+}
+impl Default for PostProcessingSettings {
+    fn default() -> Self {
+        Self {
+            bloom_threshold: DEFAULT_BLOOM_THRESHOLD,
+            bloom_intensity: 0.,
+            tonemapping: Tonemapping::Clamp,
+            vignette_intensity: 0.,
+            chromatic_aberration: 0.,
+            fxaa: false,
+        }
+    }
+}
+impl PostProcessingSettings {
+    pub fn get(world: &World, camera: EntityId) -> Self {
+        let default = Self::default();
+        Self {
+            bloom_threshold: world
+                .get(camera, bloom_threshold())
+                .unwrap_or(default.bloom_threshold),
+            bloom_intensity: world
+                .get(camera, bloom_intensity())
+                .unwrap_or(default.bloom_intensity),
+            tonemapping: world
+                .get(camera, tonemapping())
+                .unwrap_or(default.tonemapping),
+            vignette_intensity: world
+                .get(camera, vignette_intensity())
+                .unwrap_or(default.vignette_intensity),
+            chromatic_aberration: world
+                .get(camera, chromatic_aberration())
+                .unwrap_or(default.chromatic_aberration),
+            fxaa: world.has_component(camera, fxaa()),
+        }
+    }
+
+    /// Sets the components of `camera` to these settings, and removes those of the effects that
+    /// are disabled.
+    pub fn set(&self, world: &mut World, camera: EntityId) -> anyhow::Result<()> {
+        let mut data = Entity::new();
+        let mut removed: Vec<ComponentDesc> = Vec::new();
+
+        if self.bloom_intensity > 0. {
+            data.set(bloom_intensity(), self.bloom_intensity);
+        } else {
+            removed.push(bloom_intensity().desc());
+        }
+        if self.bloom_threshold != DEFAULT_BLOOM_THRESHOLD {
+            data.set(bloom_threshold(), self.bloom_threshold);
+        } else {
+            removed.push(bloom_threshold().desc());
+        }
+        if self.tonemapping != Tonemapping::Clamp {
+            data.set(tonemapping(), self.tonemapping);
+        } else {
+            removed.push(tonemapping().desc());
+        }
+        if self.vignette_intensity > 0. {
+            data.set(vignette_intensity(), self.vignette_intensity);
+        } else {
+            removed.push(vignette_intensity().desc());
+        }
+        if self.chromatic_aberration > 0. {
+            data.set(chromatic_aberration(), self.chromatic_aberration);
+        } else {
+            removed.push(chromatic_aberration().desc());
+        }
+        if self.fxaa {
+            data.set(fxaa(), ());
+        } else {
+            removed.push(fxaa().desc());
+        }
+
+        removed.retain(|&desc| world.has_component(camera, desc));
+        world.add_components(camera, data)?;
+        if !removed.is_empty() {
+            world.remove_components(camera, removed)?;
+        }
+        Ok(())
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.bloom_intensity > 0.
+            || self.tonemapping != Tonemapping::Clamp
+            || self.vignette_intensity > 0.
+            || self.chromatic_aberration > 0.
+            || self.fxaa
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostProcessingParams {
+    bloom_threshold: f32,
+    bloom_intensity: f32,
+    vignette_intensity: f32,
+    chromatic_aberration: f32,
+    tonemapping: u32,
+    fxaa: u32,
+    _padding: [u32; 2],
+}
+impl From<PostProcessingSettings> for PostProcessingParams {
+    fn from(settings: PostProcessingSettings) -> Self {
+        Self {
+            bloom_threshold: settings.bloom_threshold,
+            bloom_intensity: settings.bloom_intensity,
+            vignette_intensity: settings.vignette_intensity,
+            chromatic_aberration: settings.chromatic_aberration,
+            tonemapping: match settings.tonemapping {
+                Tonemapping::Clamp => 0,
+                Tonemapping::Reinhard => 1,
+                Tonemapping::Aces => 2,
+            },
+            fxaa: settings.fxaa as u32,
+            _padding: Default::default(),
+        }
+    }
+}
+
+struct Pipelines {
+    bright: GraphicsPipeline,
+    blur_horizontal: GraphicsPipeline,
+    blur_vertical: GraphicsPipeline,
+    composite: GraphicsPipeline,
+    output: GraphicsPipeline,
+}
+
+/// The intermediate textures, which have the size of the target.
+struct Textures {
+    size: wgpu::Extent3d,
+    /// A copy of the image, as it can't be sampled while it is rendered to
+    source: TextureView,
+    /// The result of the composite pass, which FXAA is applied to
+    composite: TextureView,
+    bloom: [TextureView; 2],
+}
+
+pub struct PostProcessing {
+    layout: Arc<BindGroupLayout>,
+    params: TypedBuffer<PostProcessingParams>,
+    pipelines: Option<Pipelines>,
+    textures: Option<Textures>,
+}
+
+impl PostProcessing {
+    pub fn new(gpu: &Gpu, assets: &AssetCache) -> Self {
+        Self {
+            layout: get_post_processing_layout().get(assets),
+            params: TypedBuffer::new(
+                gpu,
+                Some("PostProcessing.params"),
+                1,
+                wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            ),
+            pipelines: None,
+            textures: None,
+        }
+    }
+
+    /// Applies the post-processing of `camera` to the target, which must have been resolved.
+    pub fn render(
+        &mut self,
+        gpu: &Gpu,
+        assets: &AssetCache,
+        world: &World,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &RenderTarget,
+        camera: Option<EntityId>,
+    ) {
+        let Some(settings) = camera
+            .map(|camera| PostProcessingSettings::get(world, camera))
+            .filter(|settings| settings.is_enabled())
+        else {
+            return;
+        };
+        profiling::scope!("PostProcessing");
+
+        let pipelines = self
+            .pipelines
+            .get_or_insert_with(|| create_pipelines(gpu, assets));
+        let size = target.color_buffer.size;
+        if self.textures.as_ref().map(|t| t.size) != Some(size) {
+            self.textures = Some(Textures::new(gpu, size));
+        }
+        let textures = self.textures.as_ref().unwrap();
+
+        self.params.fill(gpu, &[settings.into()], |_| {});
+        encoder.copy_texture_to_texture(
+            target.color_buffer.handle.as_image_copy(),
+            textures.source.texture.handle.as_image_copy(),
+            size,
+        );
+
+        let sampler = SamplerKey::LINEAR_CLAMP_TO_EDGE.get(assets);
+        let bind_group = |source: &TextureView, bloom: &TextureView| {
+            gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("PostProcessing"),
+                layout: &self.layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&source.handle),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&bloom.handle),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: self.params.buffer().as_entire_binding(),
+                    },
+                ],
+            })
+        };
+        let mut pass = |label,
+                        pipeline: &GraphicsPipeline,
+                        bind_group: wgpu::BindGroup,
+                        output: &wgpu::TextureView| {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(label),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: output,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(pipeline.pipeline());
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        };
+
+        let [bloom_0, bloom_1] = &textures.bloom;
+        if settings.bloom_intensity > 0. {
+            pass(
+                "PostProcessing.bright",
+                &pipelines.bright,
+                bind_group(&textures.source, bloom_1),
+                &bloom_0.handle,
+            );
+            pass(
+                "PostProcessing.blur_horizontal",
+                &pipelines.blur_horizontal,
+                bind_group(bloom_0, bloom_0),
+                &bloom_1.handle,
+            );
+            pass(
+                "PostProcessing.blur_vertical",
+                &pipelines.blur_vertical,
+                bind_group(bloom_1, bloom_1),
+                &bloom_0.handle,
+            );
+        }
+        pass(
+            "PostProcessing.composite",
+            &pipelines.composite,
+            bind_group(&textures.source, bloom_0),
+            &textures.composite.handle,
+        );
+        // The result is written to all of the samples of a multisampled target, so that it is
+        // kept when the target is resolved
+        let output = match &target.multisampled {
+            Some(multisampled) => &multisampled.color_buffer_view,
+            None => &target.color_buffer_view,
+        };
+        pass(
+            "PostProcessing.output",
+            &pipelines.output,
+            bind_group(&textures.composite, bloom_0),
+            &output.handle,
+        );
+    }
+}
+
+impl Textures {
+    fn new(gpu: &Gpu, size: wgpu::Extent3d) -> Self {
+        let create_view = |label, size, format| {
+            Arc::new(Texture::new(
+                gpu,
+                &wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::COPY_DST,
+                    view_formats: &[],
+                },
+            ))
+            .create_view(&Default::default())
+        };
+        let bloom_size = wgpu::Extent3d {
+            width: (size.width / BLOOM_DOWNSCALE).max(1),
+            height: (size.height / BLOOM_DOWNSCALE).max(1),
+            depth_or_array_layers: 1,
+        };
+        Self {
+            size,
+            source: create_view("PostProcessing.source", size, gpu.swapchain_format()),
+            composite: create_view("PostProcessing.composite", size, gpu.swapchain_format()),
+            bloom: [
+                create_view("PostProcessing.bloom_0", bloom_size, BLOOM_FORMAT),
+                create_view("PostProcessing.bloom_1", bloom_size, BLOOM_FORMAT),
+            ],
+        }
+    }
+}
+
+fn get_post_processing_layout() -> BindGroupDesc<'static> {
+    let texture = |binding| BindGroupLayoutEntry {
+        binding,
+        visibility: ShaderStages::FRAGMENT,
+        ty: BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    };
+    BindGroupDesc {
+        entries: vec![
+            texture(0),
+            texture(1),
+            BindGroupLayoutEntry {
+                binding: 2,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 3,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+        label: POST_PROCESSING_BIND_GROUP.into(),
+    }
+}
+
+fn create_pipelines(gpu: &Gpu, assets: &AssetCache) -> Pipelines {
+    let shader = Shader::new(
+        assets,
+        "post_processing",
+        &[POST_PROCESSING_BIND_GROUP],
+        &ShaderModule::new("PostProcessing", include_file!("post_processing.wgsl"))
+            .with_binding_desc(get_post_processing_layout()),
+    )
+    .unwrap();
+
+    let pipeline = |fs_main, format: wgpu::TextureFormat, sample_count| {
+        shader.to_pipeline(
+            gpu,
+            GraphicsPipelineInfo {
+                fs_main,
+                targets: &[Some(format.into())],
+                sample_count,
+                ..Default::default()
+            },
+        )
+    };
+    Pipelines {
+        bright: pipeline("fs_bright", BLOOM_FORMAT, 1),
+        blur_horizontal: pipeline("fs_blur_horizontal", BLOOM_FORMAT, 1),
+        blur_vertical: pipeline("fs_blur_vertical", BLOOM_FORMAT, 1),
+        composite: pipeline("fs_composite", gpu.swapchain_format(), 1),
+        output: pipeline("fs_output", gpu.swapchain_format(), gpu.sample_count),
+    }
+}
@@ -0,0 +1,174 @@
+struct PostProcessingParams {
+    bloom_threshold: f32,
+    bloom_intensity: f32,
+    vignette_intensity: f32,
+    chromatic_aberration: f32,
+    tonemapping: u32,
+    fxaa: u32,
+};
+
+@group(POST_PROCESSING_BIND_GROUP)
+@binding(0)
+var source: texture_2d<f32>;
+
+@group(POST_PROCESSING_BIND_GROUP)
+@binding(1)
+var bloom: texture_2d<f32>;
+
+@group(POST_PROCESSING_BIND_GROUP)
+@binding(2)
+var source_sampler: sampler;
+
+@group(POST_PROCESSING_BIND_GROUP)
+@binding(3)
+var<uniform> params: PostProcessingParams;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// A triangle that covers the screen
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+fn sample_source(uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(source, source_sampler, uv, 0.0);
+}
+
+fn source_texel() -> vec2<f32> {
+    return 1.0 / vec2<f32>(textureDimensions(source));
+}
+
+// Downsamples the image with a 4x4 box filter, and keeps the parts that are brighter than the
+// threshold
+@fragment
+fn fs_bright(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = source_texel();
+    let color = (
+        sample_source(in.uv + vec2<f32>(-1.0, -1.0) * texel).rgb +
+        sample_source(in.uv + vec2<f32>(1.0, -1.0) * texel).rgb +
+        sample_source(in.uv + vec2<f32>(-1.0, 1.0) * texel).rgb +
+        sample_source(in.uv + vec2<f32>(1.0, 1.0) * texel).rgb
+    ) * 0.25;
+    let brightness = max(color.r, max(color.g, color.b));
+    let weight = max(brightness - params.bloom_threshold, 0.0) / max(brightness, 0.0001);
+    return vec4<f32>(color * weight, 1.0);
+}
+
+fn blur(uv: vec2<f32>, direction: vec2<f32>) -> vec4<f32> {
+    var weights = array<f32, 5>(0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+    let texel_step = direction * source_texel();
+    var color = sample_source(uv).rgb * weights[0];
+    for (var i = 1; i < 5; i = i + 1) {
+        let offset = texel_step * f32(i);
+        color += (sample_source(uv + offset).rgb + sample_source(uv - offset).rgb) * weights[i];
+    }
+    return vec4<f32>(color, 1.0);
+}
+
+@fragment
+fn fs_blur_horizontal(in: VertexOutput) -> @location(0) vec4<f32> {
+    return blur(in.uv, vec2<f32>(1.0, 0.0));
+}
+
+@fragment
+fn fs_blur_vertical(in: VertexOutput) -> @location(0) vec4<f32> {
+    return blur(in.uv, vec2<f32>(0.0, 1.0));
+}
+
+fn tonemap(color: vec3<f32>) -> vec3<f32> {
+    switch params.tonemapping {
+        // Reinhard
+        case 1u: {
+            return color / (1.0 + color);
+        }
+        // Narkowicz's fit of the ACES filmic curve
+        case 2u: {
+            let x = color;
+            let mapped = (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+            return clamp(mapped, vec3<f32>(0.0), vec3<f32>(1.0));
+        }
+        default: {
+            return clamp(color, vec3<f32>(0.0), vec3<f32>(1.0));
+        }
+    }
+}
+
+@fragment
+fn fs_composite(in: VertexOutput) -> @location(0) vec4<f32> {
+    let center = in.uv - 0.5;
+    let original = sample_source(in.uv);
+
+    // The red and blue channels are shifted apart, by the chromatic aberration at the corners
+    let offset = center * 2.0 * params.chromatic_aberration;
+    var color = vec3<f32>(
+        sample_source(in.uv + offset).r,
+        original.g,
+        sample_source(in.uv - offset).b
+    );
+
+    color += textureSampleLevel(bloom, source_sampler, in.uv, 0.0).rgb * params.bloom_intensity;
+    color = tonemap(color);
+
+    // The distance from the center is 1 at the corners
+    let corner_distance = length(center) * 1.41421356;
+    color *= 1.0 - params.vignette_intensity * smoothstep(0.4, 1.0, corner_distance);
+
+    return vec4<f32>(color, original.a);
+}
+
+fn luma(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.299, 0.587, 0.114));
+}
+
+// Copies the result to the target, and applies FXAA if it is enabled. This is the FXAA of
+// Timothy Lottes, which blurs along the edges that it detects from the contrast of the luma.
+@fragment
+fn fs_output(in: VertexOutput) -> @location(0) vec4<f32> {
+    let original = sample_source(in.uv);
+    if params.fxaa == 0u {
+        return original;
+    }
+
+    let texel = source_texel();
+    let luma_m = luma(original.rgb);
+    let luma_nw = luma(sample_source(in.uv + vec2<f32>(-1.0, -1.0) * texel).rgb);
+    let luma_ne = luma(sample_source(in.uv + vec2<f32>(1.0, -1.0) * texel).rgb);
+    let luma_sw = luma(sample_source(in.uv + vec2<f32>(-1.0, 1.0) * texel).rgb);
+    let luma_se = luma(sample_source(in.uv + vec2<f32>(1.0, 1.0) * texel).rgb);
+    let luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    let luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+    if luma_max - luma_min < max(0.0312, luma_max * 0.125) {
+        return original;
+    }
+
+    // The direction along the edge
+    var dir = vec2<f32>(
+        -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+        (luma_nw + luma_sw) - (luma_ne + luma_se)
+    );
+    let dir_reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * (0.25 / 8.0), 1.0 / 128.0);
+    let rcp_dir_min = 1.0 / (min(abs(dir.x), abs(dir.y)) + dir_reduce);
+    dir = clamp(dir * rcp_dir_min, vec2<f32>(-8.0), vec2<f32>(8.0)) * texel;
+
+    let color_a = 0.5 * (
+        sample_source(in.uv + dir * (1.0 / 3.0 - 0.5)).rgb +
+        sample_source(in.uv + dir * (2.0 / 3.0 - 0.5)).rgb
+    );
+    let color_b = color_a * 0.5 + 0.25 * (
+        sample_source(in.uv - dir * 0.5).rgb +
+        sample_source(in.uv + dir * 0.5).rgb
+    );
+    let luma_b = luma(color_b);
+    if luma_b < luma_min || luma_b > luma_max {
+        return vec4<f32>(color_a, original.a);
+    }
+    return vec4<f32>(color_b, original.a);
+}
@@ -1,12 +1,16 @@
 use super::{
     overlay_renderer::{OverlayConfig, OverlayRenderer},
+    particles::ParticlesRenderer,
+    post_processing::PostProcessing,
     shadow_renderer::ShadowsRenderer,
     Culling, FSMain, ForwardGlobals, Outlines, OutlinesConfig, RenderTarget, RendererCollect,
     RendererCollectState, TransparentRenderer, TransparentRendererConfig, TreeRenderer,
     TreeRendererConfig,
 };
 use crate::{
-    bind_groups::BindGroups, get_common_layout, globals_layout, to_linear_format, ShaderDebugParams,
+    bind_groups::BindGroups,
+    frame_graph::{FrameGraph, FrameResource, FrameStep},
+    get_common_layout, globals_layout, to_linear_format, DebugView,
 };
 use ambient_core::{asset_cache, camera::*, gpu, player::local_user_id, ui_scene};
 use ambient_ecs::{ArchetypeFilter, Component, World};
@@ -105,6 +109,7 @@ impl Default for RendererConfig {
 
 pub enum RendererTarget<'a> {
     Target(&'a RenderTarget),
+    /// Attachments that are rendered to directly. They must have the sample count of the GPU
     Direct {
         color: &'a TextureView,
         depth: &'a TextureView,
@@ -114,13 +119,18 @@ pub enum RendererTarget<'a> {
 }
 
 impl<'a> RendererTarget<'a> {
+    /// The color attachment, which is multisampled if MSAA is enabled
     pub fn color(&self) -> &'a TextureView {
         match self {
-            RendererTarget::Target(target) => &target.color_buffer_view,
+            RendererTarget::Target(target) => match &target.multisampled {
+                Some(multisampled) => &multisampled.color_buffer_view,
+                None => &target.color_buffer_view,
+            },
             RendererTarget::Direct { color, .. } => color,
         }
     }
 
+    /// The depth of the target, which can be sampled
     pub fn depth(&self) -> &'a TextureView {
         match self {
             RendererTarget::Target(target) => &target.depth_buffer_view,
@@ -128,20 +138,34 @@ impl<'a> RendererTarget<'a> {
         }
     }
 
+    /// The depth attachment, which is multisampled if MSAA is enabled
     pub fn depth_stencil(&self) -> &'a TextureView {
         match self {
-            RendererTarget::Target(target) => &target.depth_stencil_view,
+            RendererTarget::Target(target) => match &target.multisampled {
+                Some(multisampled) => &multisampled.depth_buffer_view,
+                None => &target.depth_stencil_view,
+            },
             RendererTarget::Direct { depth, .. } => depth,
         }
     }
 
+    /// The normals attachment, which is multisampled if MSAA is enabled
     pub fn normals(&self) -> &'a TextureView {
         match self {
-            RendererTarget::Target(target) => &target.normals_quat_buffer_view,
+            RendererTarget::Target(target) => match &target.multisampled {
+                Some(multisampled) => &multisampled.normals_quat_buffer_view,
+                None => &target.normals_quat_buffer_view,
+            },
             RendererTarget::Direct { normals, .. } => normals,
         }
     }
 
+    fn resolve(&self, gpu: &Gpu, assets: &AssetCache, encoder: &mut wgpu::CommandEncoder) {
+        if let RendererTarget::Target(target) = self {
+            target.resolve(gpu, assets, encoder);
+        }
+    }
+
     pub fn size(&self) -> wgpu::Extent3d {
         match self {
             RendererTarget::Target(target) => target.color_buffer.size,
@@ -165,9 +189,26 @@ pub trait SubRenderer: std::fmt::Debug + Send + Sync {
     );
 }
 
+/// The passes of the [Renderer], which are ordered and culled by its [FrameGraph] each frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RendererPass {
+    Shadows,
+    Forward,
+    PostForward,
+    Overlays,
+    /// Copies the opaque parts of the frame, for the transparent materials that sample them
+    CopySolids,
+    Transparent,
+    Particles,
+    PostTransparent,
+    Outlines,
+    PostProcessing,
+}
+
 pub struct Renderer {
     pub config: RendererConfig,
-    pub shader_debug_params: ShaderDebugParams,
+    /// What the forward and transparent passes draw; see [DebugView]
+    pub debug_view: DebugView,
     mesh_meta_layout: Arc<BindGroupLayout>,
 
     culling: Culling,
@@ -177,10 +218,14 @@ pub struct Renderer {
     forward: Option<TreeRenderer>,
     overlays: OverlayRenderer,
     transparent: TransparentRenderer,
+    particles: ParticlesRenderer,
     solids_frame: RenderTarget,
     outlines: Outlines,
+    post_processing: PostProcessing,
     pub post_forward: Option<Box<dyn SubRenderer>>,
     pub post_transparent: Option<Box<dyn SubRenderer>>,
+    /// The steps of the last rendered frame
+    frame_steps: Vec<FrameStep<RendererPass>>,
 }
 
 impl Renderer {
@@ -254,6 +299,7 @@ impl Renderer {
                             .render_mode
                             .unwrap_or_else(RenderMode::instrinsic_render_mode),
                         software_culling: settings.software_culling,
+                        sample_count: gpu.sample_count,
                     },
                 ))
             } else {
@@ -275,7 +321,8 @@ impl Renderer {
                     settings,
                 },
             ),
-            solids_frame: RenderTarget::new(
+            particles: ParticlesRenderer::new(gpu, assets, config.clone()),
+            solids_frame: RenderTarget::with_sample_count(
                 gpu,
                 uvec2(1, 1),
                 Some(
@@ -283,6 +330,7 @@ impl Renderer {
                         | wgpu::TextureUsages::TEXTURE_BINDING
                         | wgpu::TextureUsages::COPY_DST,
                 ),
+                1,
             ),
             outlines: Outlines::new(
                 gpu,
@@ -293,11 +341,13 @@ impl Renderer {
                 },
                 config.clone(),
             ),
+            post_processing: PostProcessing::new(gpu, assets),
             mesh_meta_layout: renderer_resources.mesh_meta_layout,
             config,
-            shader_debug_params: Default::default(),
+            debug_view: Default::default(),
             post_forward: Default::default(),
             post_transparent: Default::default(),
+            frame_steps: Vec::new(),
         }
     }
 
@@ -313,9 +363,17 @@ impl Renderer {
         let _span = debug_span!("Renderer.render", label = world.name()).entered();
         profiling::scope!("Renderer.render", world.name());
 
+        // The views that replace the whole frame are drawn over black
+        let replaces_frame = self.debug_view.replaces_frame();
+        let clear = if replaces_frame {
+            Some(Color::BLACK)
+        } else {
+            clear
+        };
+
         if let RendererTarget::Target(target) = &target {
             if self.solids_frame.color_buffer.size != target.color_buffer.size {
-                self.solids_frame = RenderTarget::new(
+                self.solids_frame = RenderTarget::with_sample_count(
                     gpu,
                     uvec2(
                         target.color_buffer.size.width,
@@ -326,6 +384,7 @@ impl Renderer {
                             | wgpu::TextureUsages::TEXTURE_BINDING
                             | wgpu::TextureUsages::COPY_DST,
                     ),
+                    1,
                 );
             }
         }
@@ -360,6 +419,7 @@ impl Renderer {
             self.overlays.update(gpu, &assets, world);
 
             if let Some(forward) = &mut self.forward {
+                forward.set_debug_view(gpu, self.debug_view);
                 forward.update(gpu, &assets, world);
                 forward.run_collect(
                     gpu,
@@ -374,6 +434,7 @@ impl Renderer {
                 );
             }
 
+            self.transparent.set_debug_view(gpu, self.debug_view);
             self.transparent.update(
                 gpu,
                 &assets,
@@ -387,7 +448,7 @@ impl Renderer {
             shadows.update(gpu, &assets, world);
         }
 
-        self.forward_globals.params.debug_params = self.shader_debug_params;
+        self.forward_globals.params.debug_view = self.debug_view.shader_index();
         // tracing::debug!("Updating forward globals");
         self.forward_globals.update(
             gpu,
@@ -413,177 +474,309 @@ impl Renderer {
             mesh_meta: &mesh_meta_bind_group,
         };
 
-        if let Some(shadows) = &mut self.shadows {
-            shadows.render(
-                world,
-                gpu,
-                &assets,
-                &mesh_buffer,
-                encoder,
-                &bind_groups,
-                post_submit,
-            );
-        }
-
-        {
-            profiling::scope!("Forward");
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Forward"),
-                color_attachments: &[
-                    Some(wgpu::RenderPassColorAttachment {
-                        view: target.color(),
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            load: if let Some(clear) = clear {
-                                wgpu::LoadOp::Clear(clear.into())
-                            } else {
-                                wgpu::LoadOp::Load
-                            },
-                            store: true,
-                        },
-                    }),
-                    Some(wgpu::RenderPassColorAttachment {
-                        view: target.normals(),
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            // clear color is ignored as the normal buffer should always be initialized with black
-                            load: if clear.is_some() {
-                                wgpu::LoadOp::Clear(Color::BLACK.into())
-                            } else {
-                                wgpu::LoadOp::Load
+        self.frame_steps = self
+            .frame_graph(&target, replaces_frame)
+            .compile(&Self::frame_outputs(&target));
+        for step in self.frame_steps.clone() {
+            let pass = match step {
+                FrameStep::Resolve => {
+                    target.resolve(gpu, &assets, encoder);
+                    continue;
+                }
+                FrameStep::Pass(pass) => pass,
+            };
+            match pass {
+                RendererPass::Shadows => {
+                    if let Some(shadows) = &mut self.shadows {
+                        shadows.render(
+                            world,
+                            gpu,
+                            &assets,
+                            &mesh_buffer,
+                            encoder,
+                            &bind_groups,
+                            post_submit,
+                        );
+                    }
+                }
+                RendererPass::Forward => {
+                    profiling::scope!("Forward");
+                    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("Forward"),
+                        color_attachments: &[
+                            Some(wgpu::RenderPassColorAttachment {
+                                view: target.color(),
+                                resolve_target: None,
+                                ops: wgpu::Operations {
+                                    load: if let Some(clear) = clear {
+                                        wgpu::LoadOp::Clear(clear.into())
+                                    } else {
+                                        wgpu::LoadOp::Load
+                                    },
+                                    store: true,
+                                },
+                            }),
+                            Some(wgpu::RenderPassColorAttachment {
+                                view: target.normals(),
+                                resolve_target: None,
+                                ops: wgpu::Operations {
+                                    // clear color is ignored as the normal buffer should always be
+                                    // initialized with black
+                                    load: if clear.is_some() {
+                                        wgpu::LoadOp::Clear(Color::BLACK.into())
+                                    } else {
+                                        wgpu::LoadOp::Load
+                                    },
+                                    store: true,
+                                },
+                            }),
+                        ],
+                        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                            view: target.depth_stencil(),
+                            depth_ops: Some(wgpu::Operations {
+                                load: if clear.is_some() {
+                                    wgpu::LoadOp::Clear(0.0)
+                                } else {
+                                    wgpu::LoadOp::Load
+                                },
+                                store: true,
+                            }),
+                            stencil_ops: None,
+                        }),
+                    });
+
+                    render_pass.set_index_buffer(
+                        mesh_buffer.index_buffer.buffer().slice(..),
+                        wgpu::IndexFormat::Uint32,
+                    );
+
+                    if let Some(forward) = &self.forward {
+                        forward.render(
+                            gpu,
+                            world,
+                            &mesh_buffer,
+                            &mut render_pass,
+                            &self.forward_collect_state,
+                            &bind_groups,
+                            target.size(),
+                        );
+                    }
+
+                    {
+                        profiling::scope!("Drop render pass");
+                        drop(render_pass);
+                    }
+                }
+                RendererPass::PostForward => {
+                    if let Some(post_forward) = &mut self.post_forward {
+                        post_forward.render(
+                            gpu,
+                            world,
+                            &mesh_buffer,
+                            encoder,
+                            &target,
+                            &bind_groups,
+                            post_submit,
+                        );
+                    }
+                }
+                RendererPass::Overlays => {
+                    self.overlays
+                        .render(encoder, &target, &bind_groups, &mesh_buffer);
+                }
+                RendererPass::CopySolids => {
+                    // The solids are copied from the resolved buffers
+                    if let RendererTarget::Target(target) = &target {
+                        encoder.copy_texture_to_texture(
+                            target.depth_buffer.handle.as_image_copy(),
+                            self.solids_frame.depth_buffer.handle.as_image_copy(),
+                            target.depth_buffer.size,
+                        );
+                        encoder.copy_texture_to_texture(
+                            target.color_buffer.handle.as_image_copy(),
+                            self.solids_frame.color_buffer.handle.as_image_copy(),
+                            target.color_buffer.size,
+                        );
+                        encoder.copy_texture_to_texture(
+                            target.normals_quat_buffer.handle.as_image_copy(),
+                            self.solids_frame.normals_quat_buffer.handle.as_image_copy(),
+                            target.normals_quat_buffer.size,
+                        );
+                    }
+                }
+                RendererPass::Transparent => {
+                    profiling::scope!("Transparent");
+                    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("Transparent"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: target.color(),
+                            resolve_target: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Load,
+                                store: true,
                             },
-                            store: true,
-                        },
-                    }),
-                ],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: target.depth_stencil(),
-                    depth_ops: Some(wgpu::Operations {
-                        load: if clear.is_some() {
-                            wgpu::LoadOp::Clear(0.0)
-                        } else {
-                            wgpu::LoadOp::Load
-                        },
-                        store: true,
-                    }),
-                    stencil_ops: None,
-                }),
-            });
-
-            render_pass.set_index_buffer(
-                mesh_buffer.index_buffer.buffer().slice(..),
-                wgpu::IndexFormat::Uint32,
-            );
-
-            if let Some(forward) = &self.forward {
-                forward.render(
-                    gpu,
-                    world,
-                    &mesh_buffer,
-                    &mut render_pass,
-                    &self.forward_collect_state,
-                    &bind_groups,
-                    target.size(),
-                );
-            }
-
-            {
-                profiling::scope!("Drop render pass");
-                drop(render_pass);
+                        })],
+                        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                            view: target.depth_stencil(),
+                            depth_ops: Some(wgpu::Operations {
+                                load: wgpu::LoadOp::Load,
+                                store: true,
+                            }),
+                            stencil_ops: None,
+                        }),
+                    });
+
+                    render_pass.set_index_buffer(
+                        mesh_buffer.index_buffer.buffer().slice(..),
+                        wgpu::IndexFormat::Uint32,
+                    );
+
+                    self.transparent
+                        .render(world, &mut render_pass, &bind_groups, target.size());
+
+                    {
+                        profiling::scope!("Drop render pass");
+                        drop(render_pass);
+                    }
+                }
+                RendererPass::Particles => {
+                    profiling::scope!("Particles");
+                    self.particles
+                        .render(gpu, &assets, world, encoder, &target, &bind_groups);
+                }
+                RendererPass::PostTransparent => {
+                    if let Some(post_transparent) = &mut self.post_transparent {
+                        post_transparent.render(
+                            gpu,
+                            world,
+                            &mesh_buffer,
+                            encoder,
+                            &target,
+                            &bind_groups,
+                            post_submit,
+                        );
+                    }
+                }
+                RendererPass::Outlines => {
+                    self.outlines.render(
+                        gpu,
+                        &assets,
+                        world,
+                        encoder,
+                        post_submit,
+                        &target,
+                        &bind_groups,
+                        &mesh_buffer,
+                    );
+                }
+                RendererPass::PostProcessing => {
+                    if let RendererTarget::Target(target) = &target {
+                        let camera = get_active_camera(
+                            world,
+                            self.config.scene,
+                            world.resource_opt(local_user_id()),
+                        );
+                        self.post_processing
+                            .render(gpu, &assets, world, encoder, target, camera);
+                    }
+                }
             }
         }
+    }
 
-        if let Some(post_forward) = &mut self.post_forward {
-            post_forward.render(
-                gpu,
-                world,
-                &mesh_buffer,
-                encoder,
-                &target,
-                &bind_groups,
-                post_submit,
+    /// Describes the passes of this frame. Passes that have nothing to do are left out, so that
+    /// the passes that only exist to feed them are skipped too.
+    fn frame_graph(
+        &self,
+        target: &RendererTarget,
+        replaces_frame: bool,
+    ) -> FrameGraph<RendererPass> {
+        let is_target = matches!(target, RendererTarget::Target(_));
+        let has_transparent = self.transparent.n_entities() > 0;
+
+        let mut graph = FrameGraph::new();
+        if self.shadows.is_some() {
+            graph.add_pass(RendererPass::Shadows, &[], &[FrameResource::ShadowMap]);
+        }
+        graph.add_pass(
+            RendererPass::Forward,
+            &[FrameResource::ShadowMap],
+            &[
+                FrameResource::Color,
+                FrameResource::Depth,
+                FrameResource::Normals,
+            ],
+        );
+        if self.post_forward.is_some() && !replaces_frame {
+            graph.add_pass(
+                RendererPass::PostForward,
+                &[FrameResource::Color, FrameResource::Depth],
+                &[FrameResource::Color, FrameResource::Depth],
             );
         }
-
-        self.overlays
-            .render(encoder, &target, &bind_groups, &mesh_buffer);
-
-        if let RendererTarget::Target(target) = &target {
-            encoder.copy_texture_to_texture(
-                target.depth_buffer.handle.as_image_copy(),
-                self.solids_frame.depth_buffer.handle.as_image_copy(),
-                target.depth_buffer.size,
+        graph.add_pass(
+            RendererPass::Overlays,
+            &[FrameResource::Color],
+            &[FrameResource::Color],
+        );
+        if is_target {
+            graph.add_pass(
+                RendererPass::CopySolids,
+                &[FrameResource::Resolved],
+                &[FrameResource::SolidsFrame],
             );
-            encoder.copy_texture_to_texture(
-                target.color_buffer.handle.as_image_copy(),
-                self.solids_frame.color_buffer.handle.as_image_copy(),
-                target.color_buffer.size,
+        }
+        if has_transparent {
+            graph.add_pass(
+                RendererPass::Transparent,
+                &[
+                    FrameResource::ShadowMap,
+                    FrameResource::SolidsFrame,
+                    FrameResource::Color,
+                    FrameResource::Depth,
+                ],
+                &[FrameResource::Color, FrameResource::Depth],
             );
-            encoder.copy_texture_to_texture(
-                target.normals_quat_buffer.handle.as_image_copy(),
-                self.solids_frame.normals_quat_buffer.handle.as_image_copy(),
-                target.normals_quat_buffer.size,
+        }
+        if !replaces_frame {
+            graph.add_pass(
+                RendererPass::Particles,
+                &[FrameResource::Color, FrameResource::Depth],
+                &[FrameResource::Color],
             );
         }
-
-        {
-            profiling::scope!("Transparent");
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Transparent"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: target.color(),
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
-                        store: true,
-                    },
-                })],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: target.depth_stencil(),
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
-                        store: true,
-                    }),
-                    stencil_ops: None,
-                }),
-            });
-
-            render_pass.set_index_buffer(
-                mesh_buffer.index_buffer.buffer().slice(..),
-                wgpu::IndexFormat::Uint32,
+        if self.post_transparent.is_some() && !replaces_frame {
+            graph.add_pass(
+                RendererPass::PostTransparent,
+                &[FrameResource::Color, FrameResource::Depth],
+                &[FrameResource::Color, FrameResource::Depth],
             );
-
-            self.transparent
-                .render(world, &mut render_pass, &bind_groups, target.size());
-
-            {
-                profiling::scope!("Drop render pass");
-                drop(render_pass);
-            }
         }
-
-        if let Some(post_transparent) = &mut self.post_transparent {
-            post_transparent.render(
-                gpu,
-                world,
-                &mesh_buffer,
-                encoder,
-                &target,
-                &bind_groups,
-                post_submit,
+        graph.add_pass(
+            RendererPass::Outlines,
+            &[FrameResource::Color],
+            &[FrameResource::Color],
+        );
+        if is_target && !replaces_frame {
+            graph.add_pass(
+                RendererPass::PostProcessing,
+                &[FrameResource::Resolved],
+                &[FrameResource::Color],
             );
         }
+        graph
+    }
 
-        self.outlines.render(
-            gpu,
-            &assets,
-            world,
-            encoder,
-            post_submit,
-            &target,
-            &bind_groups,
-            &mesh_buffer,
-        );
+    /// What the frame has to produce for `target`.
+    fn frame_outputs(target: &RendererTarget) -> Vec<FrameResource> {
+        match target {
+            RendererTarget::Target(_) => vec![FrameResource::Resolved],
+            RendererTarget::Direct { .. } => vec![
+                FrameResource::Color,
+                FrameResource::Depth,
+                FrameResource::Normals,
+            ],
+        }
     }
 
     pub fn dump_to_tmp_file(&self) {
@@ -638,6 +831,10 @@ impl Renderer {
         self.transparent.dump(f);
         writeln!(f, "  outlines").unwrap();
         self.outlines.dump(f);
+        writeln!(f, "  frame steps").unwrap();
+        for step in &self.frame_steps {
+            writeln!(f, "    {step:?}").unwrap();
+        }
     }
 }
 
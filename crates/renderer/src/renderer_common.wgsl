@@ -4,6 +4,10 @@
 @binding(0)
 var<storage> primitives: UVec4Buffer;
 
+@group(PRIMITIVES_BIND_GROUP)
+@binding(1)
+var<storage> material_slots: array<u32>;
+
 struct ModelToWorld {
     local: vec4<f32>,
     pos: vec4<f32>,
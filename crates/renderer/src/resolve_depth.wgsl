@@ -0,0 +1,21 @@
+@group(RESOLVE_DEPTH_BIND_GROUP)
+@binding(0)
+var depth_buffer: texture_depth_multisampled_2d;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // A triangle that covers the screen
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @builtin(frag_depth) f32 {
+    let p = vec2<i32>(position.xy);
+    // The depth is reversed, so the nearest sample has the greatest depth
+    var depth = 0.0;
+    for (var i = 0; i < i32(textureNumSamples(depth_buffer)); i = i + 1) {
+        depth = max(depth, textureLoad(depth_buffer, p, i));
+    }
+    return depth;
+}
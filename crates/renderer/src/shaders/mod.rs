@@ -26,11 +26,12 @@ impl std::fmt::Debug for StandardShaderKey {
     }
 }
 
-impl SyncAssetKey<Arc<RendererShader>> for StandardShaderKey {
-    fn load(&self, assets: AssetCache) -> Arc<RendererShader> {
+impl StandardShaderKey {
+    /// Compiles the shader, without caching it. Fails if the material shader is invalid.
+    pub fn create(&self, assets: &AssetCache) -> anyhow::Result<Arc<RendererShader>> {
         let id = format!("standard_shader_{}_{}", self.material_shader.id, self.lit);
         let shader = Shader::new(
-            &assets,
+            assets,
             id.clone(),
             &[
                 GLOBALS_BIND_GROUP,
@@ -39,12 +40,11 @@ impl SyncAssetKey<Arc<RendererShader>> for StandardShaderKey {
                 MATERIAL_BIND_GROUP,
             ],
             &ShaderModule::new("standard_material", include_file!("standard.wgsl"))
-                .with_dependencies(get_forward_modules(&assets, self.shadow_cascades))
+                .with_dependencies(get_forward_modules(assets, self.shadow_cascades))
                 .with_dependency(self.material_shader.shader.clone()),
-        )
-        .unwrap();
+        )?;
 
-        Arc::new(RendererShader {
+        Ok(Arc::new(RendererShader {
             shader,
             id,
             vs_main: "vs_main".to_string(),
@@ -59,6 +59,13 @@ impl SyncAssetKey<Arc<RendererShader>> for StandardShaderKey {
             double_sided: false,
             depth_write_enabled: true,
             transparency_group: 0,
-        })
+            bindless: self.material_shader.bindless,
+        }))
+    }
+}
+
+impl SyncAssetKey<Arc<RendererShader>> for StandardShaderKey {
+    fn load(&self, assets: AssetCache) -> Arc<RendererShader> {
+        self.create(&assets).unwrap()
     }
 }
@@ -80,6 +80,14 @@ fn fs_forward_lit_main(in: VertexOutput, @builtin(front_facing) is_front: bool)
 
     material.normal = normalize(material.normal);
 
+    // The other debug views are drawn by `shading`
+    if global_params.debug_view == 4u {
+        return MainFsOut(
+            vec4<f32>(fract(material_in.texcoord), 0.0, 1.0),
+            quat_from_mat3(material_in.normal_matrix)
+        );
+    }
+
     return MainFsOut(
         shading(material, in.world_position),
         quat_from_mat3(material_in.normal_matrix)
@@ -95,8 +103,16 @@ fn fs_forward_unlit_main(in: VertexOutput, @builtin(front_facing) is_front: bool
         discard;
     }
 
+    var color = vec4<f32>(material.base_color, material.opacity);
+    // Unlit materials are drawn like the lit ones in the debug views
+    if global_params.debug_view == 4u {
+        color = vec4<f32>(fract(material_in.texcoord), 0.0, 1.0);
+    } else if global_params.debug_view != 0u {
+        color = shading(material, in.world_position);
+    }
+
     return MainFsOut(
-        vec4<f32>(material.base_color, material.opacity),
+        color,
         quat_from_mat3(material_in.normal_matrix)
     );
 }
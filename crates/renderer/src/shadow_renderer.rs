@@ -97,6 +97,7 @@ impl ShadowsRenderer {
                         .render_mode
                         .unwrap_or_else(RenderMode::instrinsic_render_mode),
                     software_culling: settings.software_culling,
+                    sample_count: 1,
                 },
             ),
             cascades: (0..config.shadow_cascades)
@@ -1,10 +1,16 @@
 use std::sync::Arc;
 
 use ambient_gpu::{
-    gpu::{Gpu, DEFAULT_SAMPLE_COUNT},
-    shader_module::DEPTH_FORMAT,
+    gpu::{Gpu, GpuKey},
+    shader_module::{
+        BindGroupDesc, GraphicsPipeline, GraphicsPipelineInfo, Shader, ShaderModule, DEPTH_FORMAT,
+    },
     texture::{Texture, TextureView},
 };
+use ambient_native_std::{
+    asset_cache::{AssetCache, SyncAssetKey, SyncAssetKeyExt},
+    include_file,
+};
 use glam::UVec2;
 use wgpu::{TextureFormat, TextureViewDescriptor};
 
@@ -33,66 +39,78 @@ pub struct RenderTarget {
     pub color_buffer_view: TextureView,
     pub normals_quat_buffer: Arc<Texture>,
     pub normals_quat_buffer_view: TextureView,
+    /// The attachments that are rendered to when MSAA is enabled, which are resolved to the
+    /// buffers above with [RenderTarget::resolve]
+    pub multisampled: Option<MultisampledTarget>,
 }
 impl RenderTarget {
     pub fn new(gpu: &Gpu, size: UVec2, usage: Option<wgpu::TextureUsages>) -> Self {
+        Self::with_sample_count(gpu, size, usage, gpu.sample_count)
+    }
+    pub fn with_sample_count(
+        gpu: &Gpu,
+        size: UVec2,
+        usage: Option<wgpu::TextureUsages>,
+        sample_count: u32,
+    ) -> Self {
         let usage = usage.unwrap_or(
             wgpu::TextureUsages::RENDER_ATTACHMENT
                 | wgpu::TextureUsages::TEXTURE_BINDING
                 | wgpu::TextureUsages::COPY_SRC,
         );
         let sc_desc = gpu.sc_desc(size);
-        let depth_buffer = Arc::new(Texture::new(
-            gpu,
-            &wgpu::TextureDescriptor {
-                label: Some("RenderTarget.depth_buffer"),
-                size: wgpu::Extent3d {
-                    width: sc_desc.width,
-                    height: sc_desc.height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: DEFAULT_SAMPLE_COUNT,
-                dimension: wgpu::TextureDimension::D2,
-                format: DEPTH_FORMAT,
-                usage,
-                view_formats: &[],
-            },
-        ));
-        let color_buffer = Arc::new(Texture::new(
-            gpu,
-            &wgpu::TextureDescriptor {
-                label: Some("RenderTarget.color_buffer"),
-                size: wgpu::Extent3d {
-                    width: sc_desc.width,
-                    height: sc_desc.height,
-                    depth_or_array_layers: 1,
+        let create_texture = |label, format, usage, sample_count| {
+            Arc::new(Texture::new(
+                gpu,
+                &wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width: sc_desc.width,
+                        height: sc_desc.height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage,
+                    view_formats: &[],
                 },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: sc_desc.format,
-                usage,
-                view_formats: &[],
-            },
-        ));
-        let normals_buffer = Arc::new(Texture::new(
-            gpu,
-            &wgpu::TextureDescriptor {
-                label: Some("RenderTarget.normals_quat_buffer"),
-                size: wgpu::Extent3d {
-                    width: sc_desc.width,
-                    height: sc_desc.height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: to_linear_format(sc_desc.format),
-                usage,
-                view_formats: &[],
-            },
-        ));
+            ))
+        };
+        let depth_buffer = create_texture("RenderTarget.depth_buffer", DEPTH_FORMAT, usage, 1);
+        let color_buffer = create_texture("RenderTarget.color_buffer", sc_desc.format, usage, 1);
+        let normals_format = to_linear_format(sc_desc.format);
+        let normals_buffer =
+            create_texture("RenderTarget.normals_quat_buffer", normals_format, usage, 1);
+        let multisampled = (sample_count > 1).then(|| {
+            // Multisampled textures can only be rendered to and sampled
+            let usage =
+                wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
+            MultisampledTarget {
+                depth_buffer_view: create_texture(
+                    "RenderTarget.multisampled.depth_buffer",
+                    DEPTH_FORMAT,
+                    usage,
+                    sample_count,
+                )
+                .create_view(&Default::default()),
+                color_buffer_view: create_texture(
+                    "RenderTarget.multisampled.color_buffer",
+                    sc_desc.format,
+                    usage,
+                    sample_count,
+                )
+                .create_view(&Default::default()),
+                normals_quat_buffer_view: create_texture(
+                    "RenderTarget.multisampled.normals_quat_buffer",
+                    normals_format,
+                    usage,
+                    sample_count,
+                )
+                .create_view(&Default::default()),
+            }
+        });
         Self {
             depth_buffer_view: depth_buffer.create_view(&TextureViewDescriptor {
                 aspect: wgpu::TextureAspect::DepthOnly,
@@ -106,6 +124,142 @@ impl RenderTarget {
             color_buffer,
             normals_quat_buffer_view: normals_buffer.create_view(&Default::default()),
             normals_quat_buffer: normals_buffer,
+            multisampled,
         }
     }
+
+    /// Resolves the multisampled attachments to the buffers of this target, so that they can be
+    /// sampled and copied. Does nothing if MSAA is disabled.
+    pub fn resolve(&self, gpu: &Gpu, assets: &AssetCache, encoder: &mut wgpu::CommandEncoder) {
+        let Some(multisampled) = &self.multisampled else {
+            return;
+        };
+        profiling::scope!("Resolve");
+        fn resolve<'a>(
+            view: &'a wgpu::TextureView,
+            resolve_target: &'a wgpu::TextureView,
+        ) -> Option<wgpu::RenderPassColorAttachment<'a>> {
+            Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: Some(resolve_target),
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })
+        }
+        // The color attachments are resolved when the pass ends
+        drop(encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("RenderTarget.resolve"),
+            color_attachments: &[
+                resolve(&multisampled.color_buffer_view, &self.color_buffer_view),
+                resolve(
+                    &multisampled.normals_quat_buffer_view,
+                    &self.normals_quat_buffer_view,
+                ),
+            ],
+            depth_stencil_attachment: None,
+        }));
+        // Depth attachments can't be resolved by render passes
+        DepthResolverKey.get(assets).run(
+            gpu,
+            encoder,
+            &multisampled.depth_buffer_view,
+            &self.depth_stencil_view,
+        );
+    }
+}
+
+/// The multisampled attachments of a [RenderTarget].
+#[derive(Debug)]
+pub struct MultisampledTarget {
+    pub depth_buffer_view: TextureView,
+    pub color_buffer_view: TextureView,
+    pub normals_quat_buffer_view: TextureView,
+}
+
+const RESOLVE_DEPTH_BIND_GROUP: &str = "RESOLVE_DEPTH_BIND_GROUP";
+
+#[derive(Debug)]
+struct DepthResolverKey;
+impl SyncAssetKey<Arc<DepthResolver>> for DepthResolverKey {
+    fn load(&self, assets: AssetCache) -> Arc<DepthResolver> {
+        Arc::new(DepthResolver::new(&GpuKey.get(&assets), &assets))
+    }
+}
+
+/// Writes the nearest depth of the samples of a multisampled depth buffer to a depth buffer.
+struct DepthResolver {
+    pipeline: GraphicsPipeline,
+}
+impl DepthResolver {
+    fn new(gpu: &Gpu, assets: &AssetCache) -> Self {
+        let layout = BindGroupDesc {
+            entries: vec![wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: true,
+                },
+                count: None,
+            }],
+            label: RESOLVE_DEPTH_BIND_GROUP.into(),
+        };
+        let shader = Shader::new(
+            assets,
+            "resolve_depth",
+            &[RESOLVE_DEPTH_BIND_GROUP],
+            &ShaderModule::new("resolve_depth", include_file!("resolve_depth.wgsl"))
+                .with_binding_desc(layout),
+        )
+        .unwrap();
+        let pipeline = shader.to_pipeline(
+            gpu,
+            GraphicsPipelineInfo {
+                depth: Some(wgpu::DepthStencilState {
+                    format: DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Always,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                ..Default::default()
+            },
+        );
+        Self { pipeline }
+    }
+
+    fn run(
+        &self,
+        gpu: &Gpu,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::TextureView,
+        target: &wgpu::TextureView,
+    ) {
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("DepthResolver.bind_group"),
+            layout: &self.pipeline.pipeline().get_bind_group_layout(0),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(source),
+            }],
+        });
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("DepthResolver"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: target,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        });
+        render_pass.set_pipeline(self.pipeline.pipeline());
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
 }
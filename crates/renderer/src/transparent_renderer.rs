@@ -8,19 +8,19 @@ use ambient_gpu::{
     shader_module::{GraphicsPipeline, GraphicsPipelineInfo, DEPTH_FORMAT},
     typed_buffer::TypedBuffer,
 };
-use ambient_native_std::asset_cache::AssetCache;
+use ambient_native_std::asset_cache::{AssetCache, SyncAssetKeyExt};
 use ambient_settings::RenderSettings;
 use glam::{Mat4, UVec4, Vec3};
 use itertools::Itertools;
 use ordered_float::OrderedFloat;
 
 use super::{
-    double_sided, get_gpu_primitive_id, primitives, FSMain, RendererResources, RendererShader,
-    SharedMaterial,
+    double_sided, get_gpu_primitive_id, primitives, DebugView, FSMain, RendererResources,
+    RendererShader, SharedMaterial,
 };
 use crate::{
     bind_groups::BindGroups, is_transparent, scissors, set_scissors_safe, transparency_group,
-    RendererConfig,
+    BindlessMaterialsKey, RendererConfig,
 };
 
 pub struct TransparentRendererConfig {
@@ -40,10 +40,15 @@ pub struct TransparentRenderer {
     shaders: HashMap<String, Arc<ShaderNode>>,
 
     gpu_primitives: TypedBuffer<UVec4>,
+    /// The slot in [BindlessMaterials](crate::BindlessMaterials) of the material of each
+    /// primitive
+    material_slots: TypedBuffer<u32>,
     primitives_bind_group: wgpu::BindGroup,
+    bindless_bind_group: Option<Arc<wgpu::BindGroup>>,
 
     spawn_qs: QueryState,
     despawn_qs: QueryState,
+    debug_view: DebugView,
 }
 impl TransparentRenderer {
     pub fn new(gpu: &Gpu, config: TransparentRendererConfig) -> Self {
@@ -56,6 +61,12 @@ impl TransparentRenderer {
                 | wgpu::BufferUsages::COPY_SRC
                 | wgpu::BufferUsages::INDIRECT,
         );
+        let material_slots = TypedBuffer::new(
+            gpu,
+            Some("TransparentRenderer.material_slots"),
+            1,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        );
 
         Self {
             primitives: Vec::new(),
@@ -65,12 +76,41 @@ impl TransparentRenderer {
                 gpu,
                 &config.renderer_resources.primitives_layout,
                 gpu_primitives.buffer(),
+                material_slots.buffer(),
             ),
             gpu_primitives,
+            material_slots,
+            bindless_bind_group: None,
             config: Arc::new(config),
 
             spawn_qs: QueryState::new(),
             despawn_qs: QueryState::new(),
+            debug_view: DebugView::Lit,
+        }
+    }
+    /// Recreates the pipelines of the renderer for a debug view, if it changed.
+    pub fn set_debug_view(&mut self, gpu: &Gpu, debug_view: DebugView) {
+        if debug_view == self.debug_view {
+            return;
+        }
+        self.debug_view = debug_view;
+        let mut recreated = HashMap::new();
+        for node in self.shaders.values_mut() {
+            let new_node = Arc::new(ShaderNode::new(
+                gpu,
+                self.config.clone(),
+                node.shader.clone(),
+                node.double_sided,
+                node.depth_write_enabled,
+                debug_view,
+            ));
+            recreated.insert(Arc::as_ptr(node), new_node.clone());
+            *node = new_node;
+        }
+        for primitive in &mut self.primitives {
+            if let Some(node) = recreated.get(&Arc::as_ptr(&primitive.shader)) {
+                primitive.shader = node.clone();
+            }
         }
     }
     #[profiling::function]
@@ -98,6 +138,7 @@ impl TransparentRenderer {
                 let transparent = is_transparent(world, id, &primitive.material, &primitive_shader);
                 if transparent || self.config.render_opaque {
                     let config = self.config.clone();
+                    let debug_view = self.debug_view;
                     let double_sided = world.get(id, double_sided()).unwrap_or(
                         primitive
                             .material
@@ -118,6 +159,7 @@ impl TransparentRenderer {
                                 primitive_shader.clone(),
                                 double_sided,
                                 depth_write_enabled,
+                                debug_view,
                             ))
                         });
                     self.primitives.push(TransparentPrimitive {
@@ -166,23 +208,40 @@ impl TransparentRenderer {
             (x.transparency_group, OrderedFloat(point.z))
         });
 
-        if self.gpu_primitives.set_len(gpu, self.primitives.len()) {
+        let primitives_resized = self.gpu_primitives.set_len(gpu, self.primitives.len());
+        let material_slots_resized = self
+            .material_slots
+            .set_len_discard(gpu, self.primitives.len().max(1));
+        if primitives_resized || material_slots_resized {
             self.primitives_bind_group = Self::create_primitives_bind_group(
                 gpu,
                 &self.config.renderer_resources.primitives_layout,
                 self.gpu_primitives.buffer(),
+                self.material_slots.buffer(),
             );
         }
 
+        // Each primitive has its own material index
         self.gpu_primitives.write(
             gpu,
             0,
             &self
                 .primitives
                 .iter()
-                .map(|e| get_gpu_primitive_id(world, e.id, e.primitive_index, 0))
+                .enumerate()
+                .map(|(i, e)| get_gpu_primitive_id(world, e.id, e.primitive_index, i as u32))
                 .collect_vec(),
         );
+        let mut material_slots = self
+            .primitives
+            .iter()
+            .map(|e| e.material.bindless_slot().unwrap_or_default())
+            .collect_vec();
+        material_slots.resize(self.material_slots.len(), 0);
+        self.material_slots.write(gpu, 0, &material_slots);
+        self.bindless_bind_group = BindlessMaterialsKey
+            .get(assets)
+            .map(|bindless| bindless.bind_group(gpu));
     }
 
     fn remove(&mut self, id: EntityId, primitive_index: usize) {
@@ -199,6 +258,7 @@ impl TransparentRenderer {
         render_target_size: wgpu::Extent3d,
     ) {
         let mut is_bound = false;
+        let mut is_bindless_bound = false;
         // TODO: keep track of the state to avoid state switches (same pipeline multiple times etc.)
         for (i, entry) in self.primitives.iter().enumerate() {
             let bind_groups = [
@@ -215,11 +275,23 @@ impl TransparentRenderer {
             let metadata = &entry.mesh_metadata;
             if metadata.index_count > 0 {
                 render_pass.set_pipeline(entry.shader.pipeline.pipeline());
-                render_pass.set_bind_group(
-                    bind_groups.len() as _,
-                    entry.material.bind_group(),
-                    &[],
-                );
+                if !entry.shader.shader.bindless {
+                    render_pass.set_bind_group(
+                        bind_groups.len() as _,
+                        entry.material.bind_group(),
+                        &[],
+                    );
+                    is_bindless_bound = false;
+                } else if !is_bindless_bound {
+                    if let Some(bindless_bind_group) = &self.bindless_bind_group {
+                        render_pass.set_bind_group(
+                            bind_groups.len() as _,
+                            bindless_bind_group,
+                            &[],
+                        );
+                        is_bindless_bound = true;
+                    }
+                }
                 // entry.shader.pipeline.bind(render_pass, MATERIAL_BIND_GROUP, entry.material.bind());
 
                 if !set_scissors_safe(
@@ -243,13 +315,20 @@ impl TransparentRenderer {
         gpu: &Gpu,
         layout: &wgpu::BindGroupLayout,
         buffer: &wgpu::Buffer,
+        material_slots: &wgpu::Buffer,
     ) -> wgpu::BindGroup {
         gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
             layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: buffer.as_entire_binding(),
-            }],
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: material_slots.as_entire_binding(),
+                },
+            ],
             label: Some("InstanceDataBuffer.bind_group"),
         })
     }
@@ -270,6 +349,9 @@ struct TransparentPrimitive {
 }
 struct ShaderNode {
     pipeline: GraphicsPipeline,
+    shader: Arc<RendererShader>,
+    double_sided: bool,
+    depth_write_enabled: bool,
 }
 impl ShaderNode {
     pub fn new(
@@ -278,29 +360,41 @@ impl ShaderNode {
         shader: Arc<RendererShader>,
         double_sided: bool,
         depth_write_enabled: bool,
+        debug_view: DebugView,
     ) -> Self {
+        let mut targets = config.targets.clone();
         let pipeline = shader.shader.to_pipeline(
             gpu,
-            GraphicsPipelineInfo {
-                vs_main: &shader.vs_main,
-                fs_main: shader.get_fs_main_name(config.fs_main),
-                depth: Some(wgpu::DepthStencilState {
-                    format: DEPTH_FORMAT,
-                    depth_write_enabled,
-                    depth_compare: wgpu::CompareFunction::Greater,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default(),
-                }),
-                targets: &config.targets,
-                cull_mode: if double_sided {
-                    None
-                } else {
-                    Some(wgpu::Face::Back)
+            debug_view.apply_to_pipeline(
+                gpu,
+                GraphicsPipelineInfo {
+                    vs_main: &shader.vs_main,
+                    fs_main: shader.get_fs_main_name(config.fs_main),
+                    depth: Some(wgpu::DepthStencilState {
+                        format: DEPTH_FORMAT,
+                        depth_write_enabled,
+                        depth_compare: wgpu::CompareFunction::Greater,
+                        stencil: wgpu::StencilState::default(),
+                        bias: wgpu::DepthBiasState::default(),
+                    }),
+                    targets: &config.targets,
+                    cull_mode: if double_sided {
+                        None
+                    } else {
+                        Some(wgpu::Face::Back)
+                    },
+                    sample_count: gpu.sample_count,
+                    ..Default::default()
                 },
-                ..Default::default()
-            },
+                &mut targets,
+            ),
         );
 
-        Self { pipeline }
+        Self {
+            pipeline,
+            shader,
+            double_sided,
+            depth_write_enabled,
+        }
     }
 }
@@ -1,20 +1,21 @@
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
     mem,
-    sync::Arc,
+    sync::{Arc, OnceLock},
 };
 
 use ambient_ecs::{query, ArchetypeFilter, EntityId, FramedEventsReader, QueryState, World};
 use ambient_gpu::{
-    gpu::Gpu,
+    gpu::{Gpu, GpuKey},
     mesh_buffer::MeshBuffer,
     multi_buffer::{MultiBufferSizeStrategy, SubBufferId, TypedMultiBuffer},
     shader_module::{GraphicsPipeline, GraphicsPipelineInfo},
+    typed_buffer::TypedBuffer,
 };
-use ambient_native_std::asset_cache::AssetCache;
+use ambient_native_std::asset_cache::{AssetCache, SyncAssetKeyExt};
 use ambient_settings::RenderMode;
 use bytemuck::Zeroable;
-use glam::UVec4;
+use glam::{vec4, UVec4, Vec4};
 use itertools::Itertools;
 use wgpu::DepthBiasState;
 
@@ -23,10 +24,15 @@ use super::{
     RendererCollectState, RendererResources, RendererShader, SharedMaterial,
 };
 use crate::{
-    bind_groups::BindGroups, is_transparent, scissors, set_scissors_safe, DrawIndexedIndirect,
-    PostSubmitFunc, RendererConfig,
+    bind_groups::BindGroups,
+    flat_material::{get_flat_shader, FlatMaterialKey},
+    is_transparent, scissors, set_scissors_safe, BindlessMaterialsKey, DebugView,
+    DrawIndexedIndirect, PostSubmitFunc, RendererConfig,
 };
 
+/// The color of the placeholder material
+const PLACEHOLDER_COLOR: Vec4 = vec4(0.5, 0.5, 0.5, 1.0);
+
 #[repr(C)]
 #[derive(bytemuck::Pod, bytemuck::Zeroable, Clone, Copy, Debug)]
 pub struct MaterialLayout {
@@ -47,6 +53,7 @@ pub struct TreeRendererConfig {
     pub depth_bias: DepthBiasState,
     pub render_mode: RenderMode,
     pub software_culling: bool,
+    pub sample_count: u32,
 }
 
 pub struct TreeRenderer {
@@ -60,10 +67,21 @@ pub struct TreeRenderer {
     collect_primitives: BTreeMap<usize, Vec<CollectPrimitive>>,
 
     primitives: TypedMultiBuffer<CollectPrimitive>,
+    /// The slot in [BindlessMaterials](crate::BindlessMaterials) of each material index
+    material_slots: TypedBuffer<u32>,
     primitives_bind_group: Option<wgpu::BindGroup>,
+    bindless_bind_group: Option<Arc<wgpu::BindGroup>>,
+    /// What the primitives of the shaders whose pipeline is still being compiled are drawn with
+    placeholder: Option<Placeholder>,
     spawn_qs: QueryState,
     despawn_qs: QueryState,
     material_indices: MaterialIndices,
+    debug_view: DebugView,
+}
+
+struct Placeholder {
+    pipeline: GraphicsPipeline,
+    material: SharedMaterial,
 }
 
 impl TreeRenderer {
@@ -85,30 +103,75 @@ impl TreeRenderer {
                     | wgpu::BufferUsages::INDIRECT,
                 MultiBufferSizeStrategy::Pow2,
             ),
+            material_slots: TypedBuffer::new(
+                gpu,
+                Some("TreeRenderer.material_slots"),
+                1,
+                wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            ),
+            bindless_bind_group: None,
+            placeholder: None,
 
             config: Arc::new(config),
             spawn_qs: QueryState::new(),
             despawn_qs: QueryState::new(),
             material_indices: MaterialIndices::new(),
             collect_primitives: BTreeMap::new(),
+            debug_view: DebugView::Lit,
+        }
+    }
+    /// Recreates the pipelines of the renderer for a debug view, if it changed.
+    pub fn set_debug_view(&mut self, gpu: &Gpu, debug_view: DebugView) {
+        if debug_view == self.debug_view {
+            return;
+        }
+        self.debug_view = debug_view;
+        self.placeholder = None;
+        for node in self.tree.values_mut() {
+            node.pipeline = Arc::new(OnceLock::new());
+            let _ = node.pipeline.set(ShaderNode::create_pipeline(
+                gpu,
+                &self.config,
+                &node.shader,
+                node.double_sided,
+                debug_view,
+            ));
         }
     }
     fn create_primitives_bind_group(
         gpu: &Gpu,
         layout: &wgpu::BindGroupLayout,
         buffer: &wgpu::Buffer,
+        material_slots: &wgpu::Buffer,
     ) -> wgpu::BindGroup {
         gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
             layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: buffer.as_entire_binding(),
-            }],
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: material_slots.as_entire_binding(),
+                },
+            ],
             label: Some("TreeRenderer.primitives"),
         })
     }
     #[profiling::function]
     pub fn update(&mut self, gpu: &Gpu, assets: &AssetCache, world: &mut World) {
+        self.placeholder.get_or_insert_with(|| Placeholder {
+            pipeline: ShaderNode::create_pipeline(
+                gpu,
+                &self.config,
+                &get_flat_shader(assets, &self.config.renderer_config),
+                false,
+                self.debug_view,
+            ),
+            material: FlatMaterialKey::new(PLACEHOLDER_COLOR, Some(false)).get(assets),
+        });
+
         let mut to_update = HashSet::new();
         let mut spawn_qs = std::mem::replace(&mut self.spawn_qs, QueryState::new());
         let mut despawn_qs = std::mem::replace(&mut self.despawn_qs, QueryState::new());
@@ -132,6 +195,7 @@ impl TreeRenderer {
                 let primitive_shader = (primitive.shader)(assets, &self.config.renderer_config);
                 if let Some(update) = self.insert(
                     gpu,
+                    assets,
                     world,
                     id,
                     primitive_id,
@@ -238,11 +302,28 @@ impl TreeRenderer {
             }
         }
 
+        // Materials that aren't in the bindless materials are only drawn with shaders that bind
+        // them one by one, so their slot is never read
+        let mut material_slots = vec![0; self.material_indices.counter.max(1) as usize];
+        for node in self.tree.values() {
+            for mat in node.tree.values() {
+                material_slots[mat.material_index as usize] =
+                    mat.material.bindless_slot().unwrap_or_default();
+            }
+        }
+        self.material_slots
+            .set_len_discard(gpu, material_slots.len());
+        self.material_slots.write(gpu, 0, &material_slots);
+        self.bindless_bind_group = BindlessMaterialsKey
+            .get(assets)
+            .map(|bindless| bindless.bind_group(gpu));
+
         self.primitives_bind_group = if self.primitives.total_len() > 0 {
             Some(Self::create_primitives_bind_group(
                 gpu,
                 &self.config.renderer_resources.primitives_layout,
                 self.primitives.buffer(),
+                self.material_slots.buffer(),
             ))
         } else {
             None
@@ -398,9 +479,11 @@ impl TreeRenderer {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn insert(
         &mut self,
         gpu: &Gpu,
+        assets: &AssetCache,
         world: &World,
         id: EntityId,
         primitive_id: usize,
@@ -412,16 +495,16 @@ impl TreeRenderer {
             && world.get(id, cpu_lod_visible()).unwrap_or(true)
         {
             let config = &self.config;
+            let debug_view = self.debug_view;
             let double_sided = world
                 .get(id, double_sided())
                 .unwrap_or(material.double_sided().unwrap_or(shader.double_sided));
             let scissors = world.get(id, scissors()).ok();
             let shader_id = format!("{}-{}", shader.id, double_sided);
             let material_id = format!("{}-{:?}", material.id(), scissors);
-            let node = self
-                .tree
-                .entry(shader_id.clone())
-                .or_insert_with(|| ShaderNode::new(gpu, config, shader.clone(), double_sided));
+            let node = self.tree.entry(shader_id.clone()).or_insert_with(|| {
+                ShaderNode::new(assets, config, shader.clone(), double_sided, debug_view)
+            });
 
             let mat = node.tree.entry(material_id.clone()).or_insert_with(|| {
                 let index = self.primitives.create_buffer(gpu, None);
@@ -511,9 +594,17 @@ impl TreeRenderer {
         let count_state = collect_state.counts_cpu.lock();
 
         let mut is_bound = false;
+        let mut is_bindless_bound = false;
 
         for node in self.tree.values() {
-            render_pass.set_pipeline(node.pipeline.pipeline());
+            // The primitives of a shader whose pipeline is still being compiled are drawn with the
+            // placeholder instead
+            let (pipeline, placeholder) = match (node.pipeline.get(), &self.placeholder) {
+                (Some(pipeline), _) => (pipeline, None),
+                (None, Some(placeholder)) => (&placeholder.pipeline, Some(&placeholder.material)),
+                (None, None) => continue,
+            };
+            render_pass.set_pipeline(pipeline.pipeline());
             // Bind on first invocation
             let bind_groups = [
                 bind_groups.globals,
@@ -527,10 +618,27 @@ impl TreeRenderer {
                 }
             }
 
-            for mat in node.tree.values() {
-                let material = &mat.material;
-
+            // The materials of bindless shaders share one bind group, so it's only bound again
+            // if the material of another shader has been bound since
+            if let Some(material) = placeholder {
                 render_pass.set_bind_group(bind_groups.len() as _, material.bind_group(), &[]);
+                is_bindless_bound = false;
+            } else if node.shader.bindless && !is_bindless_bound {
+                if let Some(bindless_bind_group) = &self.bindless_bind_group {
+                    render_pass.set_bind_group(bind_groups.len() as _, bindless_bind_group, &[]);
+                    is_bindless_bound = true;
+                }
+            }
+
+            for mat in node.tree.values() {
+                if placeholder.is_none() && !node.shader.bindless {
+                    render_pass.set_bind_group(
+                        bind_groups.len() as _,
+                        mat.material.bind_group(),
+                        &[],
+                    );
+                    is_bindless_bound = false;
+                }
 
                 if !set_scissors_safe(render_pass, render_target_size, mat.scissors) {
                     continue;
@@ -619,16 +727,65 @@ impl TreeRenderer {
     }
 }
 struct ShaderNode {
-    pipeline: GraphicsPipeline,
+    /// Set once the pipeline has been compiled
+    pipeline: Arc<OnceLock<GraphicsPipeline>>,
+    shader: Arc<RendererShader>,
+    double_sided: bool,
     tree: HashMap<String, MaterialNode>,
 }
 impl ShaderNode {
     pub fn new(
-        gpu: &Gpu,
-        config: &TreeRendererConfig,
+        assets: &AssetCache,
+        config: &Arc<TreeRendererConfig>,
         shader: Arc<RendererShader>,
         double_sided: bool,
+        debug_view: DebugView,
     ) -> Self {
+        let pipeline = Arc::new(OnceLock::new());
+        let gpu = GpuKey.get(assets);
+
+        // The pipeline is compiled on a background thread, so that a new material permutation
+        // doesn't stall the frame. The device can't be shared between threads on the web.
+        #[cfg(not(target_os = "unknown"))]
+        {
+            let (config, shader, pipeline) = (config.clone(), shader.clone(), pipeline.clone());
+            ambient_core::RuntimeKey
+                .get(assets)
+                .spawn_blocking(move || {
+                    let _span = tracing::debug_span!("compile_pipeline", id = shader.id).entered();
+                    let _ = pipeline.set(Self::create_pipeline(
+                        &gpu,
+                        &config,
+                        &shader,
+                        double_sided,
+                        debug_view,
+                    ));
+                });
+        }
+        #[cfg(target_os = "unknown")]
+        let _ = pipeline.set(Self::create_pipeline(
+            &gpu,
+            config,
+            &shader,
+            double_sided,
+            debug_view,
+        ));
+
+        Self {
+            pipeline,
+            shader,
+            double_sided,
+            tree: HashMap::new(),
+        }
+    }
+    fn create_pipeline(
+        gpu: &Gpu,
+        config: &TreeRendererConfig,
+        shader: &RendererShader,
+        double_sided: bool,
+        debug_view: DebugView,
+    ) -> GraphicsPipeline {
+        let mut targets = config.targets.clone();
         let mut pipeline_info = GraphicsPipelineInfo {
             vs_main: &shader.vs_main,
             fs_main: shader.get_fs_main_name(config.fs_main),
@@ -636,6 +793,7 @@ impl ShaderNode {
             cull_mode: config
                 .cull_mode
                 .and_then(|f| if double_sided { None } else { Some(f) }),
+            sample_count: config.sample_count,
             ..Default::default()
         };
         if config.depth_stencil {
@@ -643,13 +801,9 @@ impl ShaderNode {
                 .with_depth()
                 .with_depth_bias(config.depth_bias);
         }
+        let pipeline_info = debug_view.apply_to_pipeline(gpu, pipeline_info, &mut targets);
 
-        let pipeline = shader.shader.to_pipeline(gpu, pipeline_info);
-
-        Self {
-            pipeline,
-            tree: HashMap::new(),
-        }
+        shader.shader.to_pipeline(gpu, pipeline_info)
     }
     fn is_empty(&self) -> bool {
         self.tree.is_empty()
@@ -685,7 +839,10 @@ impl std::fmt::Debug for MaterialNode {
 impl std::fmt::Debug for ShaderNode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ShaderNode")
-            .field("pipeline", &self.pipeline.name())
+            .field(
+                "pipeline",
+                &self.pipeline.get().map(|pipeline| pipeline.name()),
+            )
             .field("tree", &self.tree)
             .finish()
     }
@@ -5,6 +5,37 @@ pub struct GeneralSettings {
     pub user_id: Option<String>,
     pub api_token: Option<String>,
     pub sentry: Sentry,
+    /// If true, analytics events submitted by packages will not be sent.
+    #[serde(default)]
+    pub analytics_opt_out: bool,
+    /// The locale used to select localized assets (e.g. `de` or `pt_BR`). If not set, the
+    /// system locale is used.
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+impl GeneralSettings {
+    /// Returns the configured locale, falling back to the system locale and then to `en`.
+    pub fn locale(&self) -> String {
+        self.locale
+            .clone()
+            .or_else(system_locale)
+            .unwrap_or_else(|| "en".to_string())
+    }
+}
+
+/// Reads the locale from the standard POSIX environment variables (e.g. `de_DE.UTF-8` -> `de_DE`).
+fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|value| {
+            value
+                .split(['.', '@'])
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        })
+        .find(|locale| !locale.is_empty() && locale != "C" && locale != "POSIX")
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
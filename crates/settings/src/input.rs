@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct InputSettings {
+    /// How the motion of the mouse is read.
+    #[serde(default)]
+    pub mouse_mode: MouseMode,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum MouseMode {
+    /// The motion reported by the mouse, without the pointer acceleration of the operating
+    /// system. This is what shooters usually want.
+    #[default]
+    Raw,
+    /// The motion of the system pointer, with the speed and acceleration of the operating system.
+    /// The pointer does not move while the cursor is locked, so there is no motion then.
+    Pointer,
+}
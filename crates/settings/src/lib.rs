@@ -13,11 +13,21 @@ pub use render::*;
 mod general;
 pub use general::*;
 
+mod input;
+pub use input::*;
+
+mod window;
+pub use window::*;
+
 #[derive(Serialize, Deserialize, Clone, Default, Debug)]
 pub struct Settings {
     #[serde(default)]
     pub general: GeneralSettings,
     pub render: RenderSettings,
+    #[serde(default)]
+    pub input: InputSettings,
+    #[serde(default)]
+    pub window: WindowSettings,
 }
 
 #[cfg(not(target_os = "unknown"))]
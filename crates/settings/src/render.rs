@@ -11,6 +11,13 @@ pub struct RenderSettings {
     pub render_mode: Option<RenderMode>,
     #[serde(default)]
     pub software_culling: bool,
+    /// The quality tier to download package assets for (e.g. `web-low`). If `None`, the tier
+    /// is selected based on the platform and GPU
+    #[serde(default)]
+    pub quality_tier: Option<String>,
+    /// The number of samples per pixel used for multisample anti-aliasing (MSAA). 1 disables it
+    #[serde(default)]
+    pub(crate) msaa: Msaa,
 }
 
 impl RenderSettings {
@@ -21,6 +28,10 @@ impl RenderSettings {
     pub fn vsync(&self) -> bool {
         self.vsync.0
     }
+
+    pub fn msaa_sample_count(&self) -> u32 {
+        self.msaa.0
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -67,3 +78,11 @@ impl Default for Vsync {
         Self(true)
     }
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Msaa(pub u32);
+impl Default for Msaa {
+    fn default() -> Self {
+        Self(1)
+    }
+}
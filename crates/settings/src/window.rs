@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WindowSettings {
+    /// If true, the window is minimized when it loses focus while it is fullscreen (e.g. when
+    /// alt-tabbing out of the game).
+    #[serde(default = "default_true")]
+    pub minimize_on_focus_loss: bool,
+    /// If true, the audio is muted while the window does not have focus.
+    #[serde(default)]
+    pub mute_on_focus_loss: bool,
+    /// If true, the cursor is unlocked and shown while the window does not have focus, and locked
+    /// again when it regains focus.
+    #[serde(default = "default_true")]
+    pub release_cursor_on_focus_loss: bool,
+}
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            minimize_on_focus_loss: true,
+            mute_on_focus_loss: false,
+            release_cursor_on_focus_loss: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
@@ -230,6 +230,7 @@ impl SyncAssetKey<Arc<RendererShader>> for CloudShaderKey {
             double_sided: false,
             depth_write_enabled: true,
             transparency_group: 0,
+            bindless: false,
         })
     }
 }
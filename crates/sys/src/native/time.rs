@@ -94,4 +94,9 @@ impl SystemTime {
     }
 }
 
+/// Returns the offset of the local timezone from UTC at the current time, in seconds east of UTC.
+pub fn local_utc_offset() -> i32 {
+    chrono::Local::now().offset().local_minus_utc()
+}
+
 use derive_more::{From, Into};
@@ -21,7 +21,7 @@ use pin_project::{pin_project, pinned_drop};
 use slotmap::new_key_type;
 mod interval;
 
-pub use crate::platform::time::{Instant, SystemTime};
+pub use crate::platform::time::{local_utc_offset, Instant, SystemTime};
 
 pub use interval::{interval, interval_at, Interval};
 
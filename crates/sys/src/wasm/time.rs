@@ -107,3 +107,9 @@ impl SystemTime {
         ))
     }
 }
+
+/// Returns the offset of the local timezone from UTC at the current time, in seconds east of UTC.
+pub fn local_utc_offset() -> i32 {
+    // The browser gives the offset in minutes west of UTC
+    -(js_sys::Date::new_0().get_timezone_offset() * 60.0) as i32
+}
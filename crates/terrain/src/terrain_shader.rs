@@ -221,6 +221,7 @@ impl SyncAssetKey<Arc<RendererShader>> for TerrainShaderKey {
             double_sided: false,
             depth_write_enabled: true,
             transparency_group: 0,
+            bindless: false,
         })
     }
 }
@@ -35,7 +35,9 @@ use crate::text_material::{get_text_shader, TextMaterial};
 
 mod text_material;
 
-pub use ambient_ecs::generated::text::components::{font_family, font_size, font_style, text};
+pub use ambient_ecs::generated::text::components::{
+    font_family, font_from_url, font_size, font_style, text,
+};
 
 components!("text", {
     @[Debuggable]
@@ -253,25 +255,30 @@ pub fn systems(use_gpu: bool) -> SystemGroup {
                             .unwrap();
                     }
                 }),
-            query((font_family().changed(), font_style().changed())).to_system(
-                |q, world, qs, _| {
+            query((font_family().changed(), font_style().changed()))
+                .optional_changed(font_from_url())
+                .to_system(|q, world, qs, _| {
                     for (id, (font_family, font_style)) in q.collect_cloned(world, qs) {
+                        // A font URL takes precedence over the font family
+                        let font_family = match world.get_ref(id, font_from_url()) {
+                            Ok(url) => AbsAssetUrl::from_str(url)
+                                .map(FontFamily::Custom)
+                                .map_err(anyhow::Error::from),
+                            Err(_) => FontFamily::from_str(&font_family),
+                        };
+
                         let async_run = world.resource(async_run()).clone();
                         let assets = world.resource(asset_cache()).clone();
                         world.resource(runtime()).spawn(async move {
-                            let font = FontDef(
-                                unwrap_log_warn!(FontFamily::from_str(&font_family)),
-                                font_style,
-                            )
-                            .get(&assets)
-                            .await;
+                            let font = FontDef(unwrap_log_warn!(font_family), font_style)
+                                .get(&assets)
+                                .await;
                             async_run.run(move |world| {
                                 world.add_component(id, font_arc(), font).ok();
                             });
                         });
                     }
-                },
-            ),
+                }),
             query(font_arc().changed()).to_system(|q, world, qs, _| {
                 for (id, font) in q.collect_cloned(world, qs) {
                     let brush = Arc::new(Mutex::new(
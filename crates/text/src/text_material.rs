@@ -26,6 +26,7 @@ impl SyncAssetKey<Arc<MaterialShader>> for TextMaterialShaderKey {
                 ShaderModule::new("TextMaterial", include_file!("text_material.wgsl"))
                     .with_binding_desc(get_text_layout()),
             ),
+            bindless: false,
         })
     }
 }
@@ -0,0 +1,504 @@
+//! Parsing of UI markup documents.
+//!
+//! UI markup is a small subset of HTML: elements with attributes, text, comments, and `<style>`
+//! blocks containing CSS rules with `.class` and `tag` selectors. The document is parsed into a
+//! flat list of [MarkupNode]s with their style resolved, which the `ui` crate turns into elements.
+use ambient_shared_types::markup::MarkupNode;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("line {line}: {message}")]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Parses a UI markup document into its nodes, in document order.
+pub fn parse(source: &str) -> Result<Vec<MarkupNode>, ParseError> {
+    let mut parser = Parser::new(source, 1);
+    let mut nodes = Vec::new();
+    let mut elements = Vec::new();
+    let mut rules = Vec::new();
+    // The open elements, as their node index and tag
+    let mut stack: Vec<(usize, String)> = Vec::new();
+
+    while parser.peek().is_some() {
+        if parser.eat("<!--") {
+            parser.skip_past("-->")?;
+        } else if parser.eat("</") {
+            let tag = parser.name()?.to_ascii_lowercase();
+            parser.skip_whitespace();
+            parser.expect(">")?;
+            match stack.pop() {
+                Some((_, open)) if open == tag => {}
+                Some((_, open)) => {
+                    return Err(parser.error(format!("expected `</{open}>`, found `</{tag}>`")))
+                }
+                None => return Err(parser.error(format!("unexpected `</{tag}>`"))),
+            }
+        } else if parser.eat("<!") || parser.eat("<?") {
+            parser.skip_past(">")?;
+        } else if parser.eat("<") {
+            let line = parser.line();
+            let tag = parser.name()?.to_ascii_lowercase();
+            let attributes = parser.attributes()?;
+            let self_closing = parser.eat("/>");
+            if !self_closing {
+                parser.expect(">")?;
+            }
+
+            if tag == "style" {
+                if !self_closing {
+                    let first_line = parser.line();
+                    let mut css = Parser::new(parser.take_until("</style>")?, first_line);
+                    rules.extend(css.style_rules()?);
+                    parser.skip_past(">")?;
+                }
+                continue;
+            }
+
+            let inline_style = match attributes.iter().find(|(name, _)| name == "style") {
+                Some((_, style)) => {
+                    declarations(style).map_err(|message| ParseError { line, message })?
+                }
+                None => Vec::new(),
+            };
+            elements.push((nodes.len(), inline_style));
+            nodes.push(MarkupNode {
+                parent: stack.last().map(|(parent, _)| *parent as u32),
+                tag: tag.clone(),
+                attributes,
+                ..Default::default()
+            });
+            if !self_closing {
+                stack.push((nodes.len() - 1, tag));
+            }
+        } else {
+            let text = collapse_whitespace(parser.take_text());
+            if !text.is_empty() {
+                nodes.push(MarkupNode {
+                    parent: stack.last().map(|(parent, _)| *parent as u32),
+                    text: decode_entities(&text),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    if let Some((_, open)) = stack.last() {
+        return Err(parser.error(format!("`<{open}>` is never closed")));
+    }
+
+    for (index, inline_style) in elements {
+        nodes[index].style = resolve_style(&nodes[index], &rules, inline_style);
+    }
+    Ok(nodes)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Selector {
+    Tag(String),
+    Class(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StyleRule {
+    selector: Selector,
+    declarations: Vec<(String, String)>,
+}
+
+/// Applies the rules that match the tag, then the ones that match a class, and then the `style`
+/// attribute. Within each of these, later declarations override earlier ones.
+fn resolve_style(
+    node: &MarkupNode,
+    rules: &[StyleRule],
+    inline_style: Vec<(String, String)>,
+) -> Vec<(String, String)> {
+    let classes: Vec<&str> = node
+        .attribute("class")
+        .map(|classes| classes.split_whitespace().collect())
+        .unwrap_or_default();
+
+    let tag_rules = rules
+        .iter()
+        .filter(|rule| matches!(&rule.selector, Selector::Tag(tag) if *tag == node.tag));
+    let class_rules = rules.iter().filter(|rule| match &rule.selector {
+        Selector::Class(class) => classes.contains(&class.as_str()),
+        Selector::Tag(_) => false,
+    });
+
+    let mut style: Vec<(String, String)> = Vec::new();
+    let declarations = tag_rules
+        .chain(class_rules)
+        .flat_map(|rule| rule.declarations.iter().cloned())
+        .chain(inline_style);
+    for (property, value) in declarations {
+        match style.iter_mut().find(|(existing, _)| *existing == property) {
+            Some((_, existing)) => *existing = value,
+            None => style.push((property, value)),
+        }
+    }
+    style
+}
+
+/// Parses the `property: value` declarations of a style rule or a `style` attribute.
+fn declarations(source: &str) -> Result<Vec<(String, String)>, String> {
+    source
+        .split(';')
+        .filter(|declaration| !declaration.trim().is_empty())
+        .map(|declaration| match declaration.split_once(':') {
+            Some((property, value)) if !property.trim().is_empty() => Ok((
+                property.trim().to_ascii_lowercase(),
+                value.trim().to_string(),
+            )),
+            _ => Err(format!(
+                "expected `property: value`, found `{}`",
+                declaration.trim()
+            )),
+        })
+        .collect()
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Decodes the named entities that are common in text, and numeric character references.
+/// Anything else is kept as it is.
+fn decode_entities(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').and_then(|end| {
+            let c = match &rest[1..end] {
+                "lt" => '<',
+                "gt" => '>',
+                "amp" => '&',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => '\u{a0}',
+                entity => {
+                    let hex = entity
+                        .strip_prefix("#x")
+                        .or_else(|| entity.strip_prefix("#X"));
+                    let code = match hex {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => entity.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                output.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+    /// The line that `source` starts on, for error messages.
+    first_line: usize,
+}
+impl<'a> Parser<'a> {
+    fn new(source: &'a str, first_line: usize) -> Self {
+        Self {
+            source,
+            pos: 0,
+            first_line,
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn line(&self) -> usize {
+        self.first_line + self.source[..self.pos].matches('\n').count()
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line(),
+            message: message.into(),
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.rest().starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), ParseError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{token}`")))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Returns everything up to `token`, and moves to the start of it.
+    fn take_until(&mut self, token: &str) -> Result<&'a str, ParseError> {
+        let len = self
+            .rest()
+            .find(token)
+            .ok_or_else(|| self.error(format!("expected `{token}`")))?;
+        let taken = &self.rest()[..len];
+        self.pos += len;
+        Ok(taken)
+    }
+
+    fn skip_past(&mut self, token: &str) -> Result<(), ParseError> {
+        self.take_until(token)?;
+        self.pos += token.len();
+        Ok(())
+    }
+
+    fn take_text(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find('<').unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn name(&mut self) -> Result<&'a str, ParseError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.')))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    /// Parses the attributes of an element, up to the end of its opening tag. Attributes without
+    /// a value are set to an empty string.
+    fn attributes(&mut self) -> Result<Vec<(String, String)>, ParseError> {
+        let mut attributes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if matches!(self.peek(), None | Some('>')) || self.rest().starts_with("/>") {
+                return Ok(attributes);
+            }
+            let name = self.name()?.to_ascii_lowercase();
+            self.skip_whitespace();
+            let value = if self.eat("=") {
+                self.skip_whitespace();
+                self.attribute_value()?
+            } else {
+                ""
+            };
+            attributes.push((name, decode_entities(value)));
+        }
+    }
+
+    fn attribute_value(&mut self) -> Result<&'a str, ParseError> {
+        for quote in ["\"", "'"] {
+            if self.eat(quote) {
+                let value = self.take_until(quote)?;
+                self.pos += quote.len();
+                return Ok(value);
+            }
+        }
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || c == '>')
+            .unwrap_or(rest.len());
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    /// Parses the contents of a `<style>` block.
+    fn style_rules(&mut self) -> Result<Vec<StyleRule>, ParseError> {
+        let stripped = self.strip_comments()?;
+        let mut css = Parser::new(&stripped, self.first_line);
+
+        let mut rules = Vec::new();
+        loop {
+            css.skip_whitespace();
+            if css.peek().is_none() {
+                return Ok(rules);
+            }
+            let selectors = css.take_until("{")?;
+            css.expect("{")?;
+            let line = css.line();
+            let body = css.take_until("}")?;
+            let declarations =
+                declarations(body).map_err(|message| ParseError { line, message })?;
+            css.expect("}")?;
+
+            for selector in selectors.split(',').map(str::trim) {
+                let selector = match selector.strip_prefix('.') {
+                    Some(class) if is_identifier(class) => Selector::Class(class.to_string()),
+                    None if is_identifier(selector) => Selector::Tag(selector.to_ascii_lowercase()),
+                    _ => {
+                        return Err(ParseError {
+                            line,
+                            message: format!("unsupported selector `{selector}`"),
+                        })
+                    }
+                };
+                rules.push(StyleRule {
+                    selector,
+                    declarations: declarations.clone(),
+                });
+            }
+        }
+    }
+
+    /// Replaces `/* */` comments with whitespace, keeping the line breaks so that the lines of
+    /// errors are still correct.
+    fn strip_comments(&mut self) -> Result<String, ParseError> {
+        let mut output = String::with_capacity(self.source.len());
+        while let Some(start) = self.rest().find("/*") {
+            output.push_str(&self.rest()[..start]);
+            self.pos += start + 2;
+            let comment = self.take_until("*/")?;
+            output.extend(comment.chars().filter(|c| *c == '\n'));
+            output.push(' ');
+            self.pos += 2;
+        }
+        output.push_str(self.rest());
+        Ok(output)
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn style(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn tree() {
+        let nodes = parse(
+            r#"
+            <!-- The menu -->
+            <column class="menu">
+                <text>Title</text>
+                <button action="play" disabled/>
+            </column>
+            <row></row>
+            "#,
+        )
+        .unwrap();
+
+        let summary: Vec<_> = nodes
+            .iter()
+            .map(|node| (node.parent, node.tag.as_str(), node.text.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (None, "column", ""),
+                (Some(0), "text", ""),
+                (Some(1), "", "Title"),
+                (Some(0), "button", ""),
+                (None, "row", ""),
+            ]
+        );
+        assert_eq!(nodes[3].attribute("action"), Some("play"));
+        assert_eq!(nodes[3].attribute("disabled"), Some(""));
+        assert_eq!(nodes[3].attribute("missing"), None);
+    }
+
+    #[test]
+    fn style_precedence() {
+        let nodes = parse(
+            r#"
+            <style>
+                /* Classes override tags, regardless of the order */
+                .big, .huge { font-size: 24; color: #ff0000 }
+                text { font-size: 12; padding: 4 }
+                .huge { font-size: 48 }
+            </style>
+            <text class="big huge" style="color: #00ff00">Hello</text>
+            <text>World</text>
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            nodes[0].style,
+            style(&[("font-size", "48"), ("padding", "4"), ("color", "#00ff00")])
+        );
+        assert_eq!(
+            nodes[2].style,
+            style(&[("font-size", "12"), ("padding", "4")])
+        );
+        assert_eq!(nodes[2].style("padding"), Some("4"));
+    }
+
+    #[test]
+    fn text() {
+        let nodes =
+            parse("<text>\n  Fish &amp; chips\n  &lt;3 &#65;&#x42; &unknown; &\n</text>").unwrap();
+        assert_eq!(nodes[1].text, "Fish & chips <3 AB &unknown; &");
+
+        let nodes = parse(r#"<slot name='a &quot;b&quot;' />"#).unwrap();
+        assert_eq!(nodes[0].attribute("name"), Some("a \"b\""));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |source| parse(source).unwrap_err().to_string();
+        assert_eq!(
+            error("<row>\n</column>"),
+            "line 2: expected `</row>`, found `</column>`"
+        );
+        assert_eq!(error("</row>"), "line 1: unexpected `</row>`");
+        assert_eq!(error("<row>\n<text/>"), "line 2: `<row>` is never closed");
+        assert_eq!(error("<row class=\"a>"), "line 1: expected `\"`");
+        assert_eq!(
+            error("<style>\n\n#id { color: red }</style>"),
+            "line 3: unsupported selector `#id`"
+        );
+        assert_eq!(
+            error("<style>\n/* a\n */ p { color }</style>"),
+            "line 3: expected `property: value`, found `color`"
+        );
+        assert_eq!(
+            error("\n<text style=\"bold\"/>"),
+            "line 2: expected `property: value`, found `bold`"
+        );
+    }
+}
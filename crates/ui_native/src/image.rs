@@ -8,11 +8,12 @@ use ambient_gpu::{
     sampler::SamplerKey,
     std_assets::{DefaultNormalMapViewKey, PixelTextureViewKey},
     texture::TextureView,
-    texture_loaders::TextureFromBytes,
+    texture_loaders::{TextureFromBytes, TextureFromUrl},
 };
 use ambient_meshes::UIRectMeshKey;
 use ambient_native_std::{
     asset_cache::{AsyncAssetKeyExt, SyncAssetKeyExt},
+    asset_url::AbsAssetUrl,
     cb, CowStr,
 };
 use ambient_renderer::{
@@ -104,3 +105,31 @@ impl ElementComponent for ImageFromBytes {
         Image { texture }.el()
     }
 }
+
+/// An image loaded from a URL. Nothing is shown while it is loading, or if it fails to load.
+#[derive(Debug, Clone)]
+pub struct ImageFromUrl {
+    pub url: AbsAssetUrl,
+}
+
+impl ElementComponent for ImageFromUrl {
+    fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
+        let Self { url } = *self;
+
+        let texture = use_async(hooks, |w| {
+            let assets = w.resource(asset_cache()).clone();
+            async move {
+                TextureFromUrl {
+                    url,
+                    format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                }
+                .get(&assets)
+                .await
+                .map(|x| Arc::new(x.create_view(&Default::default())))
+            }
+        })
+        .and_then(Result::ok);
+
+        Image { texture }.el()
+    }
+}
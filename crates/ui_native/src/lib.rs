@@ -25,6 +25,8 @@ use winit::window::CursorGrabMode;
 mod component_editor;
 pub mod graph;
 mod image;
+pub mod notifications;
+pub mod world_panel;
 
 pub use ambient_layout as layout;
 pub use ambient_rect as rect;
@@ -54,7 +56,9 @@ use element::{use_ref_with, use_runtime_message, use_spawn};
 
 pub fn init_all_components() {
     layout::init_gpu_components();
+    layout::init_components();
     ambient_text::init_components();
+    notifications::init_components();
 }
 
 pub fn systems() -> SystemGroup {
@@ -64,6 +68,7 @@ pub fn systems() -> SystemGroup {
             Box::new(rect::systems()),
             Box::new(ambient_text::systems(true)),
             Box::new(layout::layout_systems()),
+            Box::new(world_panel::systems()),
         ],
     )
 }
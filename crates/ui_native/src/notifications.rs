@@ -0,0 +1,234 @@
+//! Notifications that pop up in the top-right corner of the screen, and disappear after a while.
+//!
+//! Notifications are queued with [notify], and shown by a [NotificationStack] element spawned in
+//! the same world. At most [MAX_VISIBLE_NOTIFICATIONS] are shown at once, stacked from the
+//! newest to the oldest; the others wait for their turn.
+use std::{collections::VecDeque, fmt::Debug, time::Duration};
+
+use ambient_core::transform::translation;
+use ambient_ecs::{components, Description, Resource, World};
+use ambient_element::{
+    element_component, use_frame, use_state, Element, ElementComponentExt, Hooks,
+};
+use ambient_native_std::{asset_url::AbsAssetUrl, Cb};
+use ambient_sys::time::Instant;
+use glam::{vec3, vec4, Vec4};
+
+use crate::{
+    border_color, border_thickness, fit_horizontal, font_size, height, min_width,
+    space_between_items,
+    style_constants::{error_color, primary_color, tooltip_background_color},
+    use_window_logical_resolution, width, Fit, FlowColumn, FlowRow, ImageFromUrl, Text, UIExt,
+};
+
+components!("ui_native::notifications", {
+    @[Resource, Description["The notifications that are shown, or waiting to be shown."]]
+    notifications: Notifications,
+});
+
+/// The number of notifications that are shown at once.
+pub const MAX_VISIBLE_NOTIFICATIONS: usize = 5;
+const NOTIFICATION_WIDTH: f32 = 300.;
+const NOTIFICATION_MARGIN: f32 = 10.;
+
+/// How important a notification is, which decides its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotificationSeverity {
+    #[default]
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+impl NotificationSeverity {
+    fn color(self) -> Vec4 {
+        match self {
+            Self::Info => primary_color().into(),
+            Self::Success => vec4(0.3, 0.8, 0.4, 1.),
+            Self::Warning => vec4(0.95, 0.7, 0.2, 1.),
+            Self::Error => error_color().into(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    /// An image shown next to the text
+    pub icon: Option<AbsAssetUrl>,
+    pub severity: NotificationSeverity,
+    /// How long the notification is shown for, once it is visible
+    pub duration: Duration,
+    /// Called with the ID of the notification when it is clicked; the notification is dismissed
+    /// afterwards
+    pub on_click: Option<Cb<dyn Fn(&mut World, u64) + Sync + Send>>,
+}
+impl Debug for Notification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Notification")
+            .field("title", &self.title)
+            .field("body", &self.body)
+            .field("icon", &self.icon)
+            .field("severity", &self.severity)
+            .field("duration", &self.duration)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct QueuedNotification {
+    id: u64,
+    notification: Notification,
+    /// When the notification became visible
+    shown_at: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Notifications {
+    last_id: u64,
+    /// The notifications, from the oldest to the newest
+    queue: VecDeque<QueuedNotification>,
+}
+impl Notifications {
+    /// Removes the expired notifications and shows the next ones in the queue. Returns the IDs of
+    /// the visible notifications.
+    fn update(&mut self, now: Instant) -> Vec<u64> {
+        self.queue.retain(|queued| {
+            queued.shown_at.map_or(true, |shown_at| {
+                now.duration_since(shown_at) < queued.notification.duration
+            })
+        });
+        self.queue
+            .iter_mut()
+            .take(MAX_VISIBLE_NOTIFICATIONS)
+            .map(|queued| {
+                queued.shown_at.get_or_insert(now);
+                queued.id
+            })
+            .collect()
+    }
+
+    fn get(&self, id: u64) -> Option<&Notification> {
+        self.queue
+            .iter()
+            .find(|queued| queued.id == id)
+            .map(|queued| &queued.notification)
+    }
+
+    fn remove(&mut self, id: u64) -> Option<Notification> {
+        let index = self.queue.iter().position(|queued| queued.id == id)?;
+        self.queue.remove(index).map(|queued| queued.notification)
+    }
+}
+
+/// Queues a notification to be shown by the [NotificationStack] of `world`, and returns its ID.
+pub fn notify(world: &mut World, notification: Notification) -> u64 {
+    if !world.has_component(world.resource_entity(), notifications()) {
+        world.add_resource(notifications(), Notifications::default());
+    }
+    let notifications = world.resource_mut(notifications());
+    notifications.last_id += 1;
+    let id = notifications.last_id;
+    notifications.queue.push_back(QueuedNotification {
+        id,
+        notification,
+        shown_at: None,
+    });
+    id
+}
+
+/// Removes the notification with the given ID, whether it is visible or not. Returns `false` if it
+/// does not exist anymore.
+pub fn dismiss(world: &mut World, id: u64) -> bool {
+    world
+        .resource_mut_opt(notifications())
+        .and_then(|notifications| notifications.remove(id))
+        .is_some()
+}
+
+/// Shows the [notifications] of the world in the top-right corner of the window.
+#[element_component]
+pub fn NotificationStack(hooks: &mut Hooks) -> Element {
+    let (visible, set_visible) = use_state(hooks, Vec::<(u64, Notification)>::new());
+    let window_width = use_window_logical_resolution(hooks).x as f32;
+
+    use_frame(hooks, {
+        let visible_ids = visible.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        move |world| {
+            let Some(notifications) = world.resource_mut_opt(notifications()) else {
+                return;
+            };
+            let ids = notifications.update(Instant::now());
+            if ids != visible_ids {
+                set_visible(
+                    ids.iter()
+                        .rev()
+                        .filter_map(|id| Some((*id, notifications.get(*id)?.clone())))
+                        .collect(),
+                );
+            }
+        }
+    });
+
+    FlowColumn::el(
+        visible
+            .into_iter()
+            .map(|(id, notification)| NotificationCard::el(id, notification)),
+    )
+    .with(space_between_items(), NOTIFICATION_MARGIN)
+    .with(
+        translation(),
+        vec3(
+            window_width - NOTIFICATION_WIDTH - NOTIFICATION_MARGIN,
+            NOTIFICATION_MARGIN,
+            -0.5,
+        ),
+    )
+}
+
+#[element_component]
+fn NotificationCard(_hooks: &mut Hooks, id: u64, notification: Notification) -> Element {
+    let Notification {
+        title,
+        body,
+        icon,
+        severity,
+        on_click,
+        ..
+    } = notification;
+
+    let text = FlowColumn::el([
+        Text::el(title).with(font_size(), 14.),
+        Text::el(body).with(font_size(), 12.),
+    ])
+    .with(space_between_items(), 4.);
+    let content = match icon {
+        Some(url) => FlowRow::el([
+            ImageFromUrl { url }
+                .el()
+                .with(width(), 32.)
+                .with(height(), 32.),
+            text,
+        ])
+        .with(space_between_items(), 8.),
+        None => text,
+    };
+
+    FlowRow::el([content])
+        .with_padding_even(8.)
+        .with(min_width(), NOTIFICATION_WIDTH)
+        .with(width(), NOTIFICATION_WIDTH)
+        .with(fit_horizontal(), Fit::None)
+        .with_background(tooltip_background_color().into())
+        .with(border_color(), severity.color())
+        .with(border_thickness(), 1.)
+        .with_clickarea()
+        .on_mouse_up(move |world, _, _| {
+            if let Some(on_click) = &on_click {
+                on_click(world, id);
+            }
+            dismiss(world, id);
+        })
+        .el()
+}
@@ -0,0 +1,54 @@
+//! Moves the UI elements inside of world panels from the UI scene to the main scene, so that they
+//! are rendered in the 3D world.
+use ambient_core::{
+    hierarchy::{children, parent},
+    main_scene, ui_scene,
+};
+use ambient_ecs::{generated::ui::components::world_panel, query, EntityId, SystemGroup, World};
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "ui/world_panel",
+        vec![
+            query(())
+                .incl(world_panel())
+                .spawned()
+                .to_system(|q, world, qs, _| {
+                    for (id, _) in q.collect_cloned(world, qs) {
+                        move_to_main_scene(world, id);
+                    }
+                }),
+            // Elements can be added to a panel after it has been spawned
+            query(parent().changed()).to_system(|q, world, qs, _| {
+                for (id, parent) in q.collect_cloned(world, qs) {
+                    if in_world_panel(world, parent) {
+                        move_to_main_scene(world, id);
+                    }
+                }
+            }),
+        ],
+    )
+}
+
+fn in_world_panel(world: &World, mut id: EntityId) -> bool {
+    loop {
+        if world.has_component(id, world_panel()) {
+            return true;
+        }
+        match world.get(id, parent()) {
+            Ok(parent) => id = parent,
+            Err(_) => return false,
+        }
+    }
+}
+
+/// Moves `id` and its descendants from the UI scene to the main scene.
+fn move_to_main_scene(world: &mut World, id: EntityId) {
+    if world.has_component(id, ui_scene()) {
+        world.remove_component(id, ui_scene()).ok();
+        world.add_component(id, main_scene(), ()).ok();
+    }
+    for child in world.get_cloned(id, children()).unwrap_or_default() {
+        move_to_main_scene(world, child);
+    }
+}
@@ -0,0 +1,433 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use ambient_audio::{
+    capture::AudioCapture, Attenuation, AudioEmitter, AudioListener, AudioMixer, Frame, SampleRate,
+    Source,
+};
+use ambient_core::{delta_time, runtime, transform::translation};
+use ambient_ecs::{
+    components,
+    generated::audio::components::{audio_velocity, voice_capturing},
+    EntityId, FnSystem, Resource, SystemGroup, World,
+};
+use ambient_native_std::asset_cache::AssetCache;
+use ambient_network::{
+    client::{client_state, datagram_handlers},
+    log_network_result, VOICE_DATAGRAM_ID,
+};
+use ambient_world_audio::{
+    audio_mixer, hrtf_lib,
+    systems::{
+        audio_listener_transform, estimate_audio_velocity, get_main_audio_listener, update_listener,
+    },
+};
+use bytes::Bytes;
+use glam::{Mat4, Vec3};
+use opus::{Application, Bitrate, Channels, Decoder, Encoder};
+use parking_lot::Mutex;
+
+use crate::{
+    decode_forwarded, decode_packet, encode_packet, microphone, FRAME_SAMPLES, SAMPLE_RATE,
+};
+
+/// The bitrate of the encoded voice, in bits per second.
+const BITRATE: i32 = 24_000;
+/// The maximum size of an Opus frame.
+const MAX_FRAME_SIZE: usize = 1275;
+/// The number of packets that are buffered before the voice of a speaker is played, to smooth out
+/// network jitter.
+const JITTER_PACKETS: usize = 2;
+/// If more packets than this are buffered, the oldest ones are skipped to keep the latency low.
+const MAX_BUFFERED_PACKETS: usize = 10;
+/// The maximum number of lost packets that are concealed by the decoder.
+const MAX_CONCEALED_PACKETS: i32 = 3;
+/// Speakers that have not sent anything for this long are stopped.
+const SPEAKER_TIMEOUT: Duration = Duration::from_secs(1);
+
+components!("voice", {
+    @[Resource]
+    voice_capture: Arc<Mutex<Option<CaptureThread>>>,
+    @[Resource]
+    voice_speakers: Arc<Mutex<HashMap<EntityId, Speaker>>>,
+    @[Resource]
+    voice_listener: Arc<Mutex<AudioListener>>,
+});
+
+pub fn initialize(world: &mut World) {
+    world.add_resource(voice_capture(), Default::default());
+    world.add_resource(voice_speakers(), Default::default());
+    microphone::initialize(world);
+    world.add_resource(
+        voice_listener(),
+        Arc::new(Mutex::new(AudioListener::new(
+            Mat4::IDENTITY,
+            Vec3::X * 0.3,
+        ))),
+    );
+
+    world.resource_mut(datagram_handlers()).insert(
+        VOICE_DATAGRAM_ID,
+        ("client_voice_datagram", Arc::new(on_datagram)),
+    );
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "voice",
+        vec![
+            Box::new(FnSystem::new(|world, _| update_capture(world))),
+            Box::new(FnSystem::new(|world, _| update_speakers(world))),
+            Box::new(FnSystem::new(|world, _| microphone::update_capture(world))),
+        ],
+    )
+}
+
+/// Starts or stops capturing when `voice_capturing` changes, and sends the captured packets.
+fn update_capture(world: &mut World) {
+    let Some(capture) = world.resource_opt(voice_capture()).cloned() else {
+        return;
+    };
+    let mut capture = capture.lock();
+
+    let capturing = world.resource_opt(voice_capturing()).is_some();
+    if capturing != capture.is_some() {
+        *capture = capturing.then(CaptureThread::start);
+    }
+
+    let Some(capture) = capture.as_ref() else {
+        return;
+    };
+    let packets = capture.packets.drain().collect::<Vec<_>>();
+    if packets.is_empty() {
+        return;
+    }
+    let Some(transport) = world
+        .resource_opt(client_state())
+        .and_then(|state| state.as_ref())
+        .map(|state| state.transport.clone())
+    else {
+        return;
+    };
+
+    world.resource(runtime()).spawn(async move {
+        for packet in packets {
+            log_network_result!(transport.send_datagram(VOICE_DATAGRAM_ID, packet).await);
+        }
+    });
+}
+
+/// Stops the speakers that went quiet, and moves the others and the listener.
+fn update_speakers(world: &mut World) {
+    let Some(speakers) = world.resource_opt(voice_speakers()).cloned() else {
+        return;
+    };
+    let mut speakers = speakers.lock();
+    speakers.retain(|_, speaker| speaker.last_packet.elapsed() < SPEAKER_TIMEOUT);
+    if speakers.is_empty() {
+        return;
+    }
+
+    let dt = *world.resource(delta_time());
+    if let Some(listener) = get_main_audio_listener(world) {
+        if let Some(transform) = audio_listener_transform(world, listener) {
+            let velocity = world.get(listener, audio_velocity()).ok();
+            update_listener(
+                &mut world.resource(voice_listener()).lock(),
+                transform,
+                velocity,
+                dt,
+            );
+        }
+    }
+
+    for (&id, speaker) in speakers.iter() {
+        if let (Some(emitter), Ok(position)) = (&speaker.emitter, world.get(id, translation())) {
+            let mut emitter = emitter.lock();
+            emitter.velocity = world
+                .get(id, audio_velocity())
+                .unwrap_or_else(|_| estimate_audio_velocity(emitter.pos, position, dt));
+            emitter.pos = position;
+        }
+    }
+}
+
+fn on_datagram(world: &mut World, _asset_cache: AssetCache, bytes: Bytes) {
+    let Some((speaker, packet)) = decode_forwarded(bytes) else {
+        tracing::debug!("Ignoring malformed voice packet");
+        return;
+    };
+    let Some((seq, frame)) = decode_packet(packet) else {
+        tracing::debug!("Ignoring malformed voice packet");
+        return;
+    };
+
+    let Some(speakers) = world.resource_opt(voice_speakers()).cloned() else {
+        return;
+    };
+    // There is nothing to play the voice with if audio is disabled
+    let Some(mixer) = world.resource_opt(audio_mixer()).cloned() else {
+        return;
+    };
+
+    let mut speakers = speakers.lock();
+    let speaker = match speakers.entry(speaker) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => match Speaker::new(world, &mixer, speaker) {
+            Ok(speaker) => entry.insert(speaker),
+            Err(err) => {
+                tracing::warn!("Failed to play voice: {err:?}");
+                return;
+            }
+        },
+    };
+    speaker.receive(seq, &frame);
+}
+
+/// Captures and encodes the microphone on a dedicated thread, as the audio stream can not be
+/// moved across threads. Capturing stops when this is dropped.
+pub struct CaptureThread {
+    stop: Arc<AtomicBool>,
+    packets: flume::Receiver<Bytes>,
+}
+impl CaptureThread {
+    fn start() -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let (packets_tx, packets) = flume::unbounded();
+
+        let result = std::thread::Builder::new()
+            .name("voice_capture".to_string())
+            .spawn({
+                let stop = stop.clone();
+                move || {
+                    if let Err(err) = run_capture(&stop, packets_tx) {
+                        tracing::error!("Voice capture failed: {err:?}");
+                    }
+                }
+            });
+        if let Err(err) = result {
+            tracing::error!("Failed to start voice capture thread: {err:?}");
+        }
+
+        Self { stop, packets }
+    }
+}
+impl Drop for CaptureThread {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn run_capture(stop: &AtomicBool, packets: flume::Sender<Bytes>) -> anyhow::Result<()> {
+    let (samples_tx, samples_rx) = flume::unbounded::<Vec<f32>>();
+    let capture = AudioCapture::new(move |samples| {
+        samples_tx.send(samples.to_vec()).ok();
+    })?;
+    tracing::info!("Started voice capture");
+
+    let mut encoder = Encoder::new(SAMPLE_RATE, Channels::Mono, Application::Voip)?;
+    encoder.set_bitrate(Bitrate::Bits(BITRATE))?;
+
+    let mut resampler = Resampler::new(capture.sample_rate());
+    let mut pending = Vec::new();
+    let mut frame = [0; MAX_FRAME_SIZE];
+    let mut seq = 0u32;
+    while !stop.load(Ordering::Relaxed) {
+        let samples = match samples_rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(samples) => samples,
+            Err(flume::RecvTimeoutError::Timeout) => continue,
+            Err(flume::RecvTimeoutError::Disconnected) => break,
+        };
+
+        resampler.process(&samples, &mut pending);
+        while pending.len() >= FRAME_SAMPLES {
+            let len = encoder.encode_float(&pending[..FRAME_SAMPLES], &mut frame)?;
+            pending.drain(..FRAME_SAMPLES);
+            if packets.send(encode_packet(seq, &frame[..len])).is_err() {
+                return Ok(());
+            }
+            seq = seq.wrapping_add(1);
+        }
+    }
+
+    tracing::info!("Stopped voice capture");
+    Ok(())
+}
+
+/// Converts captured samples to [SAMPLE_RATE] with linear interpolation.
+struct Resampler {
+    /// The distance between two output samples, in input samples.
+    step: f64,
+    /// The position of the next output sample, relative to `last`.
+    position: f64,
+    last: f32,
+}
+impl Resampler {
+    fn new(sample_rate: SampleRate) -> Self {
+        Self {
+            step: sample_rate as f64 / SAMPLE_RATE as f64,
+            position: 0.,
+            last: 0.,
+        }
+    }
+
+    fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        let Some(&end) = input.last() else {
+            return;
+        };
+
+        let sample = |index: usize| {
+            if index == 0 {
+                self.last
+            } else {
+                input[index - 1]
+            }
+        };
+        let len = input.len() as f64;
+        let mut position = self.position;
+        while position < len {
+            let index = position as usize;
+            let (a, b) = (sample(index), sample(index + 1));
+            output.push(a + (b - a) * (position - index as f64) as f32);
+            position += self.step;
+        }
+
+        self.position = position - len;
+        self.last = end;
+    }
+}
+
+/// The voice of another player.
+pub struct Speaker {
+    decoder: Decoder,
+    samples: flume::Sender<Vec<f32>>,
+    /// Set if the voice is spatialized.
+    emitter: Option<Arc<Mutex<AudioEmitter>>>,
+    next_seq: Option<u32>,
+    last_packet: Instant,
+}
+impl Speaker {
+    /// Starts playing the voice of the player `id`. The voice is spatialized if the player has a
+    /// position.
+    fn new(world: &World, mixer: &AudioMixer, id: EntityId) -> anyhow::Result<Self> {
+        let decoder = Decoder::new(SAMPLE_RATE, Channels::Mono)?;
+        let (samples, samples_rx) = flume::unbounded();
+        let source = VoiceSource {
+            packets: samples_rx,
+            current: Vec::new().into_iter(),
+            buffering: true,
+        };
+
+        let emitter = match (world.resource_opt(hrtf_lib()), world.get(id, translation())) {
+            (Some(hrtf_lib), Ok(pos)) => {
+                let emitter = Arc::new(Mutex::new(AudioEmitter {
+                    amplitude: 1.0,
+                    attenuation: Attenuation::InversePoly {
+                        quad: 0.1,
+                        lin: 0.0,
+                        constant: 1.0,
+                    },
+                    pos,
+                    velocity: Vec3::ZERO,
+                }));
+                let listener = world.resource(voice_listener()).clone();
+                mixer.play(source.spatial(hrtf_lib, listener, emitter.clone()));
+                Some(emitter)
+            }
+            _ => {
+                mixer.play(source);
+                None
+            }
+        };
+
+        Ok(Self {
+            decoder,
+            samples,
+            emitter,
+            next_seq: None,
+            last_packet: Instant::now(),
+        })
+    }
+
+    fn receive(&mut self, seq: u32, frame: &[u8]) {
+        self.last_packet = Instant::now();
+
+        if let Some(next_seq) = self.next_seq {
+            let ahead = seq.wrapping_sub(next_seq) as i32;
+            if ahead < 0 {
+                // Late or duplicated
+                return;
+            }
+            for _ in 0..ahead.min(MAX_CONCEALED_PACKETS) {
+                self.decode(&[]);
+            }
+        }
+        self.next_seq = Some(seq.wrapping_add(1));
+        self.decode(frame);
+    }
+
+    /// Decodes `frame`, or conceals a lost packet if it is empty.
+    fn decode(&mut self, frame: &[u8]) {
+        let mut samples = vec![0.; FRAME_SAMPLES];
+        match self.decoder.decode_float(frame, &mut samples, false) {
+            Ok(len) => {
+                samples.truncate(len);
+                self.samples.send(samples).ok();
+            }
+            Err(err) => tracing::debug!("Failed to decode voice packet: {err}"),
+        }
+    }
+}
+
+/// Plays the decoded voice of a speaker. It ends when the [Speaker] is dropped.
+struct VoiceSource {
+    packets: flume::Receiver<Vec<f32>>,
+    current: std::vec::IntoIter<f32>,
+    /// Set while waiting for [JITTER_PACKETS] packets to arrive.
+    buffering: bool,
+}
+impl Source for VoiceSource {
+    fn next_sample(&mut self) -> Option<Frame> {
+        loop {
+            if let Some(sample) = self.current.next() {
+                return Some(Frame::splat(sample));
+            }
+
+            if self.buffering
+                && self.packets.len() < JITTER_PACKETS
+                && !self.packets.is_disconnected()
+            {
+                return Some(Frame::ZERO);
+            }
+            while self.packets.len() > MAX_BUFFERED_PACKETS {
+                self.packets.try_recv().ok();
+            }
+
+            match self.packets.try_recv() {
+                Ok(samples) => {
+                    self.buffering = false;
+                    self.current = samples.into_iter();
+                }
+                Err(flume::TryRecvError::Empty) => {
+                    self.buffering = true;
+                    return Some(Frame::ZERO);
+                }
+                Err(flume::TryRecvError::Disconnected) => return None,
+            }
+        }
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        SAMPLE_RATE as _
+    }
+
+    fn sample_count(&self) -> Option<u64> {
+        None
+    }
+}
@@ -0,0 +1,83 @@
+//! Voice chat between the players of a session.
+//!
+//! While the `voice_capturing` resource is set, clients capture the microphone, encode it with
+//! Opus and send it to the server in datagrams. The server forwards it to the players that are
+//! within the `voice_range` of the speaker, and their clients play it back at the position of the
+//! speaker. Voice chat is opt-in: the voice of players without a `voice_range` is not forwarded.
+//!
+//! The [microphone] can also be captured for the client modules themselves.
+use ambient_ecs::EntityId;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+pub mod client;
+pub mod microphone;
+pub mod server;
+
+/// The sample rate of the encoded voice.
+pub const SAMPLE_RATE: u32 = 48_000;
+/// The number of samples in each packet, which is 20 ms of voice.
+pub const FRAME_SAMPLES: usize = 960;
+
+pub fn init_components() {
+    client::init_components();
+    microphone::init_components();
+}
+
+/// Encodes a packet sent by a speaker: its sequence number, followed by the Opus frame.
+pub(crate) fn encode_packet(seq: u32, frame: &[u8]) -> Bytes {
+    let mut bytes = BytesMut::with_capacity(4 + frame.len());
+    bytes.put_u32(seq);
+    bytes.put_slice(frame);
+    bytes.freeze()
+}
+
+/// Reverses [encode_packet].
+pub(crate) fn decode_packet(mut bytes: Bytes) -> Option<(u32, Bytes)> {
+    if bytes.len() < 4 {
+        return None;
+    }
+    let seq = bytes.get_u32();
+    Some((seq, bytes))
+}
+
+/// Encodes a packet forwarded by the server, which starts with the player entity of the speaker.
+pub(crate) fn encode_forwarded(speaker: EntityId, packet: Bytes) -> Bytes {
+    let mut bytes = BytesMut::with_capacity(16 + packet.len());
+    bytes.put_u128(speaker.0);
+    bytes.put(packet);
+    bytes.freeze()
+}
+
+/// Reverses [encode_forwarded].
+pub(crate) fn decode_forwarded(mut bytes: Bytes) -> Option<(EntityId, Bytes)> {
+    if bytes.len() < 16 {
+        return None;
+    }
+    let speaker = EntityId(bytes.get_u128());
+    Some((speaker, bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn packet_roundtrip() {
+        let speaker = EntityId(0x1234_5678_9abc_def0_1234_5678_9abc_def0);
+        let packet = encode_packet(42, b"opus");
+
+        let (decoded_speaker, packet) =
+            decode_forwarded(encode_forwarded(speaker, packet)).unwrap();
+        assert_eq!(decoded_speaker, speaker);
+
+        let (seq, frame) = decode_packet(packet).unwrap();
+        assert_eq!(seq, 42);
+        assert_eq!(frame, "opus");
+    }
+
+    #[test]
+    fn truncated_packets() {
+        assert_eq!(decode_packet(Bytes::from_static(b"abc")), None);
+        assert_eq!(decode_forwarded(Bytes::from_static(&[0; 15])), None);
+    }
+}
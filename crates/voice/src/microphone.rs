@@ -0,0 +1,124 @@
+//! Captures the microphone for the client modules.
+//!
+//! While the `microphone_capturing` resource is set, the input device it names is captured on a
+//! dedicated thread, and the samples captured during each frame are sent to the client modules in
+//! a `MicrophoneSamples` message, at the sample rate of the device.
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use ambient_audio::capture::AudioCapture;
+use ambient_ecs::{
+    components,
+    generated::audio::{components::microphone_capturing, messages::MicrophoneSamples},
+    world_events, Resource, World, WorldEventsExt,
+};
+use parking_lot::Mutex;
+
+components!("voice", {
+    @[Resource]
+    microphone_capture: Arc<Mutex<Option<MicrophoneThread>>>,
+});
+
+pub fn initialize(world: &mut World) {
+    world.add_resource(microphone_capture(), Default::default());
+}
+
+/// Starts, restarts or stops capturing when `microphone_capturing` changes, and sends the captured
+/// samples.
+pub(crate) fn update_capture(world: &mut World) {
+    let Some(capture) = world.resource_opt(microphone_capture()).cloned() else {
+        return;
+    };
+    let mut capture = capture.lock();
+
+    let device = world.resource_opt(microphone_capturing()).cloned();
+    if device.as_ref() != capture.as_ref().map(|capture| &capture.device) {
+        *capture = device.map(MicrophoneThread::start);
+    }
+
+    let Some(capture) = capture.as_ref() else {
+        return;
+    };
+    // The samples are kept until the sample rate of the device is known
+    let sample_rate = capture.sample_rate.load(Ordering::Relaxed);
+    if sample_rate == 0 {
+        return;
+    }
+    let samples = capture.samples.drain().flatten().collect::<Vec<_>>();
+    if samples.is_empty() {
+        return;
+    }
+
+    world
+        .resource_mut(world_events())
+        .add_message(MicrophoneSamples {
+            samples,
+            sample_rate,
+        });
+}
+
+/// Captures an input device on a dedicated thread, as the audio stream can not be moved across
+/// threads. Capturing stops when this is dropped.
+pub struct MicrophoneThread {
+    /// The name of the device, or empty for the default one
+    device: String,
+    stop: Arc<AtomicBool>,
+    /// Zero until the capture has started
+    sample_rate: Arc<AtomicU32>,
+    samples: flume::Receiver<Vec<f32>>,
+}
+impl MicrophoneThread {
+    fn start(device: String) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let sample_rate = Arc::new(AtomicU32::new(0));
+        let (samples_tx, samples) = flume::unbounded();
+
+        let result = std::thread::Builder::new()
+            .name("microphone_capture".to_string())
+            .spawn({
+                let device = device.clone();
+                let stop = stop.clone();
+                let sample_rate = sample_rate.clone();
+                move || {
+                    let name = Some(device.as_str()).filter(|name| !name.is_empty());
+                    let capture = AudioCapture::with_device(name, move |samples| {
+                        samples_tx.send(samples.to_vec()).ok();
+                    });
+                    let capture = match capture {
+                        Ok(capture) => capture,
+                        Err(err) => {
+                            tracing::error!("Microphone capture of {device:?} failed: {err:?}");
+                            return;
+                        }
+                    };
+                    sample_rate.store(capture.sample_rate() as u32, Ordering::Relaxed);
+                    tracing::info!("Started microphone capture of {device:?}");
+
+                    while !stop.load(Ordering::Relaxed) {
+                        std::thread::sleep(Duration::from_millis(100));
+                    }
+                    tracing::info!("Stopped microphone capture of {device:?}");
+                }
+            });
+        if let Err(err) = result {
+            tracing::error!("Failed to start microphone capture thread: {err:?}");
+        }
+
+        Self {
+            device,
+            stop,
+            sample_rate,
+            samples,
+        }
+    }
+}
+impl Drop for MicrophoneThread {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
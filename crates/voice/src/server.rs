@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use ambient_core::{
+    player::{get_by_user_id, user_id},
+    runtime,
+    transform::translation,
+};
+use ambient_ecs::{generated::audio::components::voice_range, query, World};
+use ambient_native_std::asset_cache::AssetCache;
+use ambient_network::{
+    log_network_result,
+    server::{datagram_handlers, player_transport, SharedServerState},
+    VOICE_DATAGRAM_ID,
+};
+use bytes::Bytes;
+use glam::Vec3;
+
+use crate::{decode_packet, encode_forwarded};
+
+pub fn initialize(world: &mut World) {
+    world.resource_mut(datagram_handlers()).insert(
+        VOICE_DATAGRAM_ID,
+        ("server_voice_datagram", Arc::new(on_datagram)),
+    );
+}
+
+/// Forwards the voice of a player to the other connections within its `voice_range`.
+#[allow(clippy::ptr_arg)]
+fn on_datagram(state: SharedServerState, _asset_cache: AssetCache, user_id: &str, bytes: Bytes) {
+    let mut state = state.lock();
+    let Some(world) = state.get_player_world_mut(user_id) else {
+        tracing::warn!("Failed to find player world for {user_id} when processing voice");
+        return;
+    };
+
+    // Spectators do not have a player entity, so they can listen but not speak
+    let Some(speaker) = get_by_user_id(world, user_id) else {
+        return;
+    };
+    let Ok(range) = world.get(speaker, voice_range()) else {
+        return;
+    };
+    if decode_packet(bytes.clone()).is_none() {
+        tracing::debug!("Ignoring malformed voice packet from {user_id}");
+        return;
+    }
+
+    let speaker_position = world.get(speaker, translation()).ok();
+    let payload = encode_forwarded(speaker, bytes);
+    let runtime = world.resource(runtime()).clone();
+    for (listener, (listener_user_id, transport)) in
+        query((self::user_id(), player_transport())).iter(world, None)
+    {
+        if listener_user_id == user_id {
+            continue;
+        }
+        let listener_position = world.get(listener, translation()).ok();
+        if !in_range(speaker_position, listener_position, range) {
+            continue;
+        }
+
+        let transport = transport.clone();
+        let payload = payload.clone();
+        runtime.spawn(async move {
+            log_network_result!(transport.send_datagram(VOICE_DATAGRAM_ID, payload).await);
+        });
+    }
+}
+
+/// Whether a listener can hear a speaker. Players without a position can hear, and be heard by,
+/// everyone.
+fn in_range(speaker: Option<Vec3>, listener: Option<Vec3>, range: f32) -> bool {
+    match (speaker, listener) {
+        (Some(speaker), Some(listener)) => speaker.distance(listener) <= range,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn range() {
+        let origin = Some(Vec3::ZERO);
+        assert!(in_range(origin, Some(Vec3::X * 10.), 10.));
+        assert!(!in_range(origin, Some(Vec3::X * 10.1), 10.));
+        assert!(in_range(origin, Some(Vec3::X * 1e6), f32::INFINITY));
+        assert!(in_range(origin, None, 1.));
+        assert!(in_range(None, origin, 1.));
+    }
+}
@@ -11,12 +11,102 @@ impl IntoBindgen for ambient_input::PlayerRawInput {
             keys: self.keys.into_iter().map(|k| k.into_bindgen()).collect(),
             mouse_position: self.mouse_position.into_bindgen(),
             mouse_delta: self.mouse_delta.into_bindgen(),
+            mouse_motion: self
+                .mouse_motion
+                .into_iter()
+                .map(|m| m.into_bindgen())
+                .collect(),
             mouse_wheel: self.mouse_wheel,
             mouse_buttons: self
                 .mouse_buttons
                 .into_iter()
                 .map(|b| b.into_bindgen())
                 .collect(),
+            gamepads: self
+                .gamepads
+                .into_iter()
+                .map(|g| g.into_bindgen())
+                .collect(),
+            touches: self.touches.into_iter().map(|t| t.into_bindgen()).collect(),
+        }
+    }
+}
+
+impl IntoBindgen for ambient_input::MouseMotion {
+    type Item = wit::client_input::MouseMotion;
+
+    fn into_bindgen(self) -> Self::Item {
+        Self::Item {
+            delta: self.delta.into_bindgen(),
+            time: self.time,
+        }
+    }
+}
+
+impl IntoBindgen for ambient_input::Touch {
+    type Item = wit::client_input::Touch;
+
+    fn into_bindgen(self) -> Self::Item {
+        Self::Item {
+            id: self.id,
+            phase: self.phase.into_bindgen(),
+            position: self.position.into_bindgen(),
+            pressure: self.pressure,
+        }
+    }
+}
+
+impl IntoBindgen for ambient_shared_types::TouchPhase {
+    type Item = wit::client_input::TouchPhase;
+
+    fn into_bindgen(self) -> Self::Item {
+        match self {
+            Self::Started => Self::Item::Started,
+            Self::Moved => Self::Item::Moved,
+            Self::Ended => Self::Item::Ended,
+            Self::Cancelled => Self::Item::Cancelled,
+        }
+    }
+}
+
+impl IntoBindgen for ambient_input::gamepad::GamepadState {
+    type Item = wit::client_input::Gamepad;
+
+    fn into_bindgen(self) -> Self::Item {
+        Self::Item {
+            id: self.id,
+            name: self.name,
+            buttons: self.buttons.into_iter().map(|b| b.into_bindgen()).collect(),
+            left_stick: self.left_stick.into_bindgen(),
+            right_stick: self.right_stick.into_bindgen(),
+            left_trigger: self.left_trigger,
+            right_trigger: self.right_trigger,
+        }
+    }
+}
+
+impl IntoBindgen for ambient_input::gamepad::GamepadButton {
+    type Item = wit::client_input::GamepadButton;
+
+    fn into_bindgen(self) -> Self::Item {
+        match self {
+            Self::South => Self::Item::South,
+            Self::East => Self::Item::East,
+            Self::North => Self::Item::North,
+            Self::West => Self::Item::West,
+            Self::LeftShoulder => Self::Item::LeftShoulder,
+            Self::LeftTrigger => Self::Item::LeftTrigger,
+            Self::RightShoulder => Self::Item::RightShoulder,
+            Self::RightTrigger => Self::Item::RightTrigger,
+            Self::Select => Self::Item::Select,
+            Self::Start => Self::Item::Start,
+            Self::Mode => Self::Item::Mode,
+            Self::LeftStick => Self::Item::LeftStick,
+            Self::RightStick => Self::Item::RightStick,
+            Self::DPadUp => Self::Item::DPadUp,
+            Self::DPadDown => Self::Item::DPadDown,
+            Self::DPadLeft => Self::Item::DPadLeft,
+            Self::DPadRight => Self::Item::DPadRight,
         }
     }
 }
@@ -20,6 +20,7 @@ impl wit::entity::Host for Bindings {
         shared::implementation::entity::spawn(
             unsafe { self.world_ref.world_mut() },
             &mut self.base.spawned_entities,
+            &mut self.base.snapshot,
             data,
         )
     }
@@ -31,6 +32,7 @@ impl wit::entity::Host for Bindings {
         shared::implementation::entity::despawn(
             unsafe { self.world_ref.world_mut() },
             &mut self.base.spawned_entities,
+            &mut self.base.snapshot,
             entity,
         )
     }
@@ -47,6 +49,18 @@ impl wit::entity::Host for Bindings {
         shared::implementation::entity::exists(self.world(), entity)
     }
 
+    fn snapshot_exists(&mut self, entity: wit::types::EntityId) -> wasm_bridge::Result<bool> {
+        shared::implementation::snapshot::exists(
+            unsafe { self.world_ref.world() },
+            &mut self.base.snapshot,
+            entity,
+        )
+    }
+
+    fn defer_despawn(&mut self, entity: wit::types::EntityId) -> wasm_bridge::Result<()> {
+        shared::implementation::snapshot::defer_despawn(&mut self.base.deferred, entity)
+    }
+
     fn resources(&mut self) -> wasm_bridge::Result<wit::types::EntityId> {
         shared::implementation::entity::resources(self.world())
     }
@@ -67,9 +81,40 @@ impl wit::entity::Host for Bindings {
         shared::implementation::entity::in_area(self.world_mut(), position, radius)
     }
 
+    fn in_area_tagged(
+        &mut self,
+        position: wit::types::Vec3,
+        radius: f32,
+        tag: String,
+    ) -> wasm_bridge::Result<Vec<wit::types::EntityId>> {
+        shared::implementation::entity::in_area_tagged(self.world_mut(), position, radius, tag)
+    }
+
     fn get_all(&mut self, index: u32) -> wasm_bridge::Result<Vec<wit::types::EntityId>> {
         shared::implementation::entity::get_all(self.world_mut(), index)
     }
+
+    fn get_all_tagged(&mut self, tag: String) -> wasm_bridge::Result<Vec<wit::types::EntityId>> {
+        shared::implementation::entity::get_all_tagged(self.world_mut(), tag)
+    }
+
+    fn get_stable_id(
+        &mut self,
+        entity: wit::types::EntityId,
+    ) -> wasm_bridge::Result<Option<String>> {
+        shared::implementation::entity::get_stable_id(
+            unsafe { self.world_ref.world_mut() },
+            &mut self.base.snapshot,
+            entity,
+        )
+    }
+
+    fn get_by_stable_id(
+        &mut self,
+        stable_id: String,
+    ) -> wasm_bridge::Result<Option<wit::types::EntityId>> {
+        shared::implementation::entity::get_by_stable_id(self.world_mut(), stable_id)
+    }
 }
 
 impl wit::component::Host for Bindings {
@@ -110,7 +155,13 @@ impl wit::component::Host for Bindings {
         index: u32,
         value: wit::component::Value,
     ) -> wasm_bridge::Result<()> {
-        shared::implementation::component::add_component(self.world_mut(), entity, index, value)
+        shared::implementation::component::add_component(
+            unsafe { self.world_ref.world_mut() },
+            &mut self.base.snapshot,
+            entity,
+            index,
+            value,
+        )
     }
 
     fn add_components(
@@ -118,7 +169,12 @@ impl wit::component::Host for Bindings {
         entity: wit::types::EntityId,
         data: wit::entity::EntityData,
     ) -> wasm_bridge::Result<()> {
-        shared::implementation::component::add_components(self.world_mut(), entity, data)
+        shared::implementation::component::add_components(
+            unsafe { self.world_ref.world_mut() },
+            &mut self.base.snapshot,
+            entity,
+            data,
+        )
     }
 
     fn set_component(
@@ -127,7 +183,13 @@ impl wit::component::Host for Bindings {
         index: u32,
         value: wit::component::Value,
     ) -> wasm_bridge::Result<()> {
-        shared::implementation::component::set_component(self.world_mut(), entity, index, value)
+        shared::implementation::component::set_component(
+            unsafe { self.world_ref.world_mut() },
+            &mut self.base.snapshot,
+            entity,
+            index,
+            value,
+        )
     }
 
     fn set_components(
@@ -135,7 +197,12 @@ impl wit::component::Host for Bindings {
         entity: wit::types::EntityId,
         data: wit::entity::EntityData,
     ) -> wasm_bridge::Result<()> {
-        shared::implementation::component::set_components(self.world_mut(), entity, data)
+        shared::implementation::component::set_components(
+            unsafe { self.world_ref.world_mut() },
+            &mut self.base.snapshot,
+            entity,
+            data,
+        )
     }
 
     fn has_component(
@@ -159,7 +226,12 @@ impl wit::component::Host for Bindings {
         entity: wit::types::EntityId,
         index: u32,
     ) -> wasm_bridge::Result<()> {
-        shared::implementation::component::remove_component(self.world_mut(), entity, index)
+        shared::implementation::component::remove_component(
+            unsafe { self.world_ref.world_mut() },
+            &mut self.base.snapshot,
+            entity,
+            index,
+        )
     }
 
     fn remove_components(
@@ -167,7 +239,87 @@ impl wit::component::Host for Bindings {
         entity: wit::types::EntityId,
         components: Vec<u32>,
     ) -> wasm_bridge::Result<()> {
-        shared::implementation::component::remove_components(self.world_mut(), entity, components)
+        shared::implementation::component::remove_components(
+            unsafe { self.world_ref.world_mut() },
+            &mut self.base.snapshot,
+            entity,
+            components,
+        )
+    }
+
+    fn apply_transaction(
+        &mut self,
+        writes: Vec<(wit::types::EntityId, wit::entity::EntityData)>,
+        removals: Vec<(wit::types::EntityId, Vec<u32>)>,
+    ) -> wasm_bridge::Result<Result<(), String>> {
+        shared::implementation::component::apply_transaction(
+            unsafe { self.world_ref.world_mut() },
+            &mut self.base.snapshot,
+            writes,
+            removals,
+        )
+    }
+
+    fn snapshot_get_component(
+        &mut self,
+        entity: wit::types::EntityId,
+        index: u32,
+    ) -> wasm_bridge::Result<Option<wit::component::Value>> {
+        shared::implementation::snapshot::get_component(
+            unsafe { self.world_ref.world() },
+            &mut self.base.snapshot,
+            entity,
+            index,
+        )
+    }
+
+    fn snapshot_has_component(
+        &mut self,
+        entity: wit::types::EntityId,
+        index: u32,
+    ) -> wasm_bridge::Result<bool> {
+        shared::implementation::snapshot::has_component(
+            unsafe { self.world_ref.world() },
+            &mut self.base.snapshot,
+            entity,
+            index,
+        )
+    }
+
+    fn defer_add_components(
+        &mut self,
+        entity: wit::types::EntityId,
+        data: wit::entity::EntityData,
+    ) -> wasm_bridge::Result<()> {
+        shared::implementation::snapshot::defer_add_components(
+            &mut self.base.deferred,
+            entity,
+            data,
+        )
+    }
+
+    fn defer_set_components(
+        &mut self,
+        entity: wit::types::EntityId,
+        data: wit::entity::EntityData,
+    ) -> wasm_bridge::Result<()> {
+        shared::implementation::snapshot::defer_set_components(
+            &mut self.base.deferred,
+            entity,
+            data,
+        )
+    }
+
+    fn defer_remove_components(
+        &mut self,
+        entity: wit::types::EntityId,
+        components: Vec<u32>,
+    ) -> wasm_bridge::Result<()> {
+        shared::implementation::snapshot::defer_remove_components(
+            &mut self.base.deferred,
+            entity,
+            components,
+        )
     }
 
     fn query(
@@ -222,3 +374,40 @@ impl wit::ambient_package::Host for Bindings {
         shared::implementation::package::get_entity_for_package_id(self.world(), package_id)
     }
 }
+
+impl wit::analytics::Host for Bindings {
+    fn track(&mut self, event: String, properties: Vec<(String, String)>) -> anyhow::Result<()> {
+        let id = self.id;
+        shared::implementation::analytics::track(self.world_mut(), id, event, properties)
+    }
+}
+
+impl wit::clock::Host for Bindings {
+    fn now(&mut self) -> anyhow::Result<wit::types::Duration> {
+        shared::implementation::clock::now()
+    }
+
+    fn local_utc_offset(&mut self) -> anyhow::Result<i32> {
+        shared::implementation::clock::local_utc_offset()
+    }
+}
+
+impl wit::shared_random::Host for Bindings {
+    fn world_seed(&mut self) -> anyhow::Result<u64> {
+        shared::implementation::shared_random::world_seed(self.world())
+    }
+
+    fn server_tick(&mut self) -> anyhow::Result<u64> {
+        shared::implementation::shared_random::server_tick(self.world())
+    }
+
+    fn generate(
+        &mut self,
+        seed: u64,
+        stream: u64,
+        position: u64,
+        count: u32,
+    ) -> anyhow::Result<Vec<u64>> {
+        shared::implementation::shared_random::generate(seed, stream, position, count)
+    }
+}
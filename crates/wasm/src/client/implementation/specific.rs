@@ -2,37 +2,65 @@
 //!
 //! If implementing a trait that is also available on the server, it should go in [super].
 
-use std::sync::Arc;
+use std::{io::Cursor, str::FromStr, sync::Arc, time::Duration};
 
 use ambient_core::{
+    asset_cache,
     async_ecs::async_run,
+    bounding::local_bounding_aabb,
     gpu,
     player::local_user_id,
     runtime,
     window::{window_ctl, WindowCtl},
 };
-use ambient_ecs::generated::input::messages::ClipboardGet;
-use ambient_gpu::texture::Texture;
+use ambient_ecs::{
+    generated::{
+        input::messages::ClipboardGet,
+        messages::{NotificationClicked, ScreenshotCaptured, TextureRead},
+        post_processing::types::Tonemapping,
+        procedurals::components::procedural_mesh,
+        ui::messages::UiMarkupLoaded,
+    },
+    query as ecs_query,
+};
+use ambient_gpu::{gpu::Gpu, texture::Texture};
 use ambient_input::{player_prev_raw_input, player_raw_input};
-use ambient_native_std::mesh::MeshBuilder;
-use ambient_network::client::client_state;
+use ambient_native_std::{
+    asset_url::AbsAssetUrl,
+    cb,
+    download_asset::download_uncached_bytes,
+    mesh::{Mesh, MeshBuilder},
+};
+use ambient_network::{
+    client::{client_state, connect_to_server, is_local_world, pause_server},
+    client_game_state::game_screen_render_target,
+};
 use ambient_procedurals::{
-    new_material_handle, new_mesh_handle, new_sampler_handle, new_texture_handle,
-    procedural_storage,
+    new_buffer_handle, new_compute_handle, new_material_handle, new_mesh_handle,
+    new_sampler_handle, new_texture_handle, procedural_storage, ProceduralMaterial, ProceduralMesh,
+};
+use ambient_renderer::{
+    custom_material::CustomMaterialConfig,
+    pbr_material::{PbrMaterialConfig, PbrMaterialParams},
+    post_processing::PostProcessingSettings,
+    RenderTarget,
 };
-use ambient_renderer::pbr_material::{PbrMaterialConfig, PbrMaterialParams};
+use ambient_ui_native::notifications::{self, Notification, NotificationSeverity};
 
 use anyhow::Context;
-use glam::Vec4;
-use wgpu::TextureViewDescriptor;
+use glam::{UVec2, UVec3, Vec4};
+use wgpu::{util::DeviceExt, TextureViewDescriptor};
 use winit::window::CursorGrabMode;
 
 use super::Bindings;
-use crate::shared::{
-    conversion::{FromBindgen, IntoBindgen},
-    implementation::message,
-    message::{MessageExt, Target},
-    wit,
+use crate::{
+    client::microphone,
+    shared::{
+        conversion::{FromBindgen, IntoBindgen},
+        implementation::message::{self, MessageInterests},
+        message::{MessageExt, Target},
+        wit,
+    },
 };
 
 use ambient_core::camera::{clip_position_to_world_ray, world_to_clip_space};
@@ -43,7 +71,7 @@ impl wit::client_message::Host for Bindings {
         target: wit::client_message::Target,
         name: String,
         data: Vec<u8>,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<Result<(), String>> {
         use wit::client_message::Target as WitTarget;
 
         let module_id = self.id;
@@ -51,6 +79,11 @@ impl wit::client_message::Host for Bindings {
 
         match target {
             WitTarget::ServerUnreliable | WitTarget::ServerReliable => {
+                let reliable = matches!(target, WitTarget::ServerReliable);
+                if let Err(err) = message::check_networked_size(&name, &data, reliable) {
+                    return Ok(Err(err));
+                }
+
                 let connection = world
                     .resource(client_state())
                     .as_ref()
@@ -58,17 +91,10 @@ impl wit::client_message::Host for Bindings {
                     .transport
                     .clone();
 
-                message::send_networked(
-                    world,
-                    connection,
-                    module_id,
-                    &name,
-                    &data,
-                    matches!(target, WitTarget::ServerReliable),
-                )
+                message::send_networked(world, connection, module_id, &name, &data, reliable)?;
             }
             WitTarget::LocalBroadcast(include_self) => {
-                message::send_local(world, module_id, Target::All { include_self }, name, data)
+                message::send_local(world, module_id, Target::All { include_self }, name, data)?;
             }
             WitTarget::Local(id) => message::send_local(
                 world,
@@ -76,8 +102,34 @@ impl wit::client_message::Host for Bindings {
                 Target::PackageOrModule(id.from_bindgen()),
                 name,
                 data,
-            ),
+            )?,
         }
+
+        Ok(Ok(()))
+    }
+
+    fn set_interests(
+        &mut self,
+        entities: Vec<wit::types::EntityId>,
+        areas: Vec<(wit::types::Vec3, f32)>,
+    ) -> anyhow::Result<Result<(), String>> {
+        let world = self.world();
+        let interests = MessageInterests {
+            entities: entities.from_bindgen(),
+            areas: areas
+                .into_iter()
+                .map(|(center, radius)| (center.from_bindgen(), radius))
+                .collect(),
+        };
+
+        let connection = world
+            .resource(client_state())
+            .as_ref()
+            .context("no game client")?
+            .transport
+            .clone();
+
+        Ok(message::send_interests(world, connection, &interests).map_err(|err| err.to_string()))
     }
 }
 impl wit::client_player::Host for Bindings {
@@ -87,7 +139,7 @@ impl wit::client_player::Host for Bindings {
             self.world().resource(local_user_id()).clone(),
         )
         .transpose()
-        .unwrap()
+        .context("The local player entity does not exist; the client may be spectating")?
     }
 }
 impl wit::client_input::Host for Bindings {
@@ -116,6 +168,21 @@ impl wit::client_input::Host for Bindings {
             ))?)
     }
 
+    fn set_cursor_image(
+        &mut self,
+        image: Option<wit::client_input::CursorImage>,
+    ) -> anyhow::Result<()> {
+        let image = image.map(|image| ambient_core::window::CursorImage {
+            url: image.url,
+            size: image.size.from_bindgen(),
+            hotspot: image.hotspot.from_bindgen(),
+        });
+        Ok(self
+            .world()
+            .resource(ambient_core::window::window_ctl())
+            .send(ambient_core::window::WindowCtl::SetCursorImage(image))?)
+    }
+
     fn set_cursor_visible(&mut self, visible: bool) -> anyhow::Result<()> {
         Ok(self
             .world()
@@ -148,6 +215,17 @@ impl wit::client_input::Host for Bindings {
             .resource(ambient_core::window::window_ctl())
             .send(ambient_core::window::WindowCtl::GrabCursor(grab_mode))?)
     }
+
+    fn set_cursor_confinement(
+        &mut self,
+        rect: Option<(wit::types::Vec2, wit::types::Vec2)>,
+    ) -> anyhow::Result<()> {
+        let rect = rect.map(|(min, max)| (min.from_bindgen(), max.from_bindgen()));
+        Ok(self
+            .world()
+            .resource(ambient_core::window::window_ctl())
+            .send(ambient_core::window::WindowCtl::ConfineCursor(rect))?)
+    }
 }
 impl wit::client_camera::Host for Bindings {
     fn clip_position_to_world_ray(
@@ -198,6 +276,49 @@ impl wit::client_camera::Host for Bindings {
         )?;
         Ok(ambient_core::window::clip_to_screen_space(self.world(), clip_pos).into_bindgen())
     }
+
+    fn get_post_processing(
+        &mut self,
+        camera: wit::types::EntityId,
+    ) -> anyhow::Result<wit::client_camera::PostProcessing> {
+        use wit::client_camera::Tonemapping as WitTonemapping;
+
+        let settings = PostProcessingSettings::get(self.world(), camera.from_bindgen());
+        Ok(wit::client_camera::PostProcessing {
+            bloom_threshold: settings.bloom_threshold,
+            bloom_intensity: settings.bloom_intensity,
+            tonemapping: match settings.tonemapping {
+                Tonemapping::Clamp => WitTonemapping::Clamp,
+                Tonemapping::Reinhard => WitTonemapping::Reinhard,
+                Tonemapping::Aces => WitTonemapping::Aces,
+            },
+            vignette_intensity: settings.vignette_intensity,
+            chromatic_aberration: settings.chromatic_aberration,
+            fxaa: settings.fxaa,
+        })
+    }
+
+    fn set_post_processing(
+        &mut self,
+        camera: wit::types::EntityId,
+        settings: wit::client_camera::PostProcessing,
+    ) -> anyhow::Result<()> {
+        use wit::client_camera::Tonemapping as WitTonemapping;
+
+        PostProcessingSettings {
+            bloom_threshold: settings.bloom_threshold,
+            bloom_intensity: settings.bloom_intensity,
+            tonemapping: match settings.tonemapping {
+                WitTonemapping::Clamp => Tonemapping::Clamp,
+                WitTonemapping::Reinhard => Tonemapping::Reinhard,
+                WitTonemapping::Aces => Tonemapping::Aces,
+            },
+            vignette_intensity: settings.vignette_intensity,
+            chromatic_aberration: settings.chromatic_aberration,
+            fxaa: settings.fxaa,
+        }
+        .set(self.world_mut(), camera.from_bindgen())
+    }
 }
 
 impl wit::client_clipboard::Host for Bindings {
@@ -240,6 +361,163 @@ impl wit::client_window::Host for Bindings {
             .send(WindowCtl::SetFullscreen(fullscreen))?;
         Ok(())
     }
+
+    fn set_focus_loss_behavior(
+        &mut self,
+        minimize: Option<bool>,
+        mute_audio: Option<bool>,
+        release_cursor: Option<bool>,
+    ) -> anyhow::Result<()> {
+        self.world_mut()
+            .resource(window_ctl())
+            .send(WindowCtl::OverrideFocusLossBehavior {
+                minimize,
+                mute_audio,
+                release_cursor,
+            })?;
+        Ok(())
+    }
+
+    fn screenshot(&mut self) -> anyhow::Result<u64> {
+        let screenshot_id = self.last_screenshot_id;
+        self.last_screenshot_id += 1;
+
+        let module_id = self.id;
+        let world = self.world();
+        let gpu = world.resource(gpu()).clone();
+        let render_target = world.resource_opt(game_screen_render_target()).cloned();
+        let async_run = world.resource(async_run()).clone();
+        let runtime = world.resource(runtime());
+        let task = async move {
+            let png = match render_target {
+                Some(render_target) => encode_screenshot(&gpu, &render_target).await,
+                None => Err(anyhow::anyhow!("There is no game screen to capture")),
+            };
+            let message = match png {
+                Ok(png) => ScreenshotCaptured::new(screenshot_id, png, None),
+                Err(err) => {
+                    tracing::warn!("Failed to capture a screenshot: {err:?}");
+                    ScreenshotCaptured::new(screenshot_id, vec![], Some(err.to_string()))
+                }
+            };
+            async_run.run(move |world| {
+                message.send(world, Some(module_id)).unwrap();
+            });
+        };
+
+        #[cfg(target_os = "unknown")]
+        runtime.spawn_local(task);
+        #[cfg(not(target_os = "unknown"))]
+        runtime.spawn(task);
+
+        Ok(screenshot_id)
+    }
+
+    fn screenshot_texture(
+        &mut self,
+    ) -> anyhow::Result<Result<wit::client_texture::Handle, String>> {
+        let world = self.world_mut();
+        let Some(render_target) = world.resource_opt(game_screen_render_target()).cloned() else {
+            return Ok(Err("There is no game screen to capture".to_string()));
+        };
+        let gpu = world.resource(gpu()).clone();
+
+        // The copy is queued, so the texture can be used right away
+        let color_buffer = &render_target.color_buffer;
+        let texture = Arc::new(Texture::new(
+            &gpu,
+            &wgpu::TextureDescriptor {
+                label: Some("Screenshot"),
+                size: color_buffer.size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: color_buffer.format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            },
+        ));
+        let mut encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Screenshot"),
+            });
+        encoder.copy_texture_to_texture(
+            color_buffer.handle.as_image_copy(),
+            texture.handle.as_image_copy(),
+            color_buffer.size,
+        );
+        gpu.queue.submit(Some(encoder.finish()));
+
+        let texture_view = Arc::new(texture.create_view(&TextureViewDescriptor::default()));
+        let texture_handle = new_texture_handle();
+        world
+            .resource_mut(procedural_storage())
+            .textures
+            .insert(texture_handle, texture_view);
+        Ok(Ok(texture_handle.into_bindgen()))
+    }
+}
+
+fn build_mesh(desc: wit::client_mesh::Descriptor) -> anyhow::Result<Mesh> {
+    let wit::client_mesh::Descriptor {
+        vertices,
+        indices,
+        joints,
+        weights,
+    } = desc;
+    if !joints.is_empty() || !weights.is_empty() {
+        anyhow::ensure!(
+            joints.len() == vertices.len() && weights.len() == vertices.len(),
+            "A skinned mesh needs joints and weights for each of its {} vertices, but got {} \
+             joints and {} weights",
+            vertices.len(),
+            joints.len(),
+            weights.len()
+        );
+    }
+    let mut positions = Vec::with_capacity(vertices.len());
+    let mut normals = Vec::with_capacity(vertices.len());
+    let mut tangents = Vec::with_capacity(vertices.len());
+    let mut texcoords = Vec::with_capacity(vertices.len());
+    for v in &vertices {
+        positions.push(v.position.from_bindgen());
+        normals.push(v.normal.from_bindgen());
+        tangents.push(v.tangent.from_bindgen());
+        texcoords.push(v.texcoord0.from_bindgen());
+    }
+    MeshBuilder {
+        positions,
+        normals,
+        tangents,
+        texcoords: vec![texcoords],
+        joint_indices: joints.into_iter().map(|j| j.from_bindgen()).collect(),
+        joint_weights: weights.into_iter().map(|w| w.from_bindgen()).collect(),
+        indices,
+        ..MeshBuilder::default()
+    }
+    .build()
+}
+
+/// Reads the last rendered frame of `render_target`, and encodes it as a PNG.
+async fn encode_screenshot(gpu: &Gpu, render_target: &RenderTarget) -> anyhow::Result<Vec<u8>> {
+    let mut image = render_target
+        .color_buffer
+        .reader(gpu)
+        .read_image(gpu)
+        .await
+        .context("Failed to read the game screen")?
+        .into_rgba8();
+    // The alpha of the screen is not meaningful, so the screenshot is made opaque
+    for pixel in image.pixels_mut() {
+        pixel.0[3] = 255;
+    }
+
+    let mut png = Cursor::new(Vec::new());
+    image.write_to(&mut png, image::ImageOutputFormat::Png)?;
+    Ok(png.into_inner())
 }
 
 impl wit::client_mesh::Host for Bindings {
@@ -247,33 +525,52 @@ impl wit::client_mesh::Host for Bindings {
         &mut self,
         desc: wit::client_mesh::Descriptor,
     ) -> anyhow::Result<wit::client_mesh::Handle> {
-        let wit::client_mesh::Descriptor { vertices, indices } = desc;
-        let mut positions = Vec::with_capacity(vertices.len());
-        let mut normals = Vec::with_capacity(vertices.len());
-        let mut tangents = Vec::with_capacity(vertices.len());
-        let mut texcoords = Vec::with_capacity(vertices.len());
-        for v in &vertices {
-            positions.push(v.position.from_bindgen());
-            normals.push(v.normal.from_bindgen());
-            tangents.push(v.tangent.from_bindgen());
-            texcoords.push(v.texcoord0.from_bindgen());
-        }
-        let mesh = MeshBuilder {
-            positions,
-            normals,
-            tangents,
-            texcoords: vec![texcoords],
-            indices,
-            ..MeshBuilder::default()
-        }
-        .build()?;
+        let mesh = build_mesh(desc)?;
 
         let world = self.world_mut();
+        let gpu = world.resource(gpu()).clone();
+        let assets = world.resource(asset_cache()).clone();
         let storage = world.resource_mut(procedural_storage());
         let mesh_handle = new_mesh_handle();
-        storage.meshes.insert(mesh_handle, mesh);
+        storage
+            .meshes
+            .insert(mesh_handle, ProceduralMesh::new(&gpu, &assets, &mesh));
         Ok(mesh_handle.into_bindgen())
     }
+    fn update(
+        &mut self,
+        handle: wit::client_mesh::Handle,
+        desc: wit::client_mesh::Descriptor,
+    ) -> anyhow::Result<Result<(), String>> {
+        let mesh = match build_mesh(desc) {
+            Ok(mesh) => mesh,
+            Err(err) => return Ok(Err(format!("Invalid mesh: {err}"))),
+        };
+
+        let world = self.world_mut();
+        let gpu = world.resource(gpu()).clone();
+        let assets = world.resource(asset_cache()).clone();
+        let handle = handle.from_bindgen();
+        let stored = world
+            .resource_mut(procedural_storage())
+            .meshes
+            .get_mut(handle);
+        stored.update(&gpu, &assets, &mesh);
+        let aabb = stored.aabb;
+
+        // The entities keep using the same GPU mesh, so only their bounds have to be updated. The
+        // entities that haven't been set up yet will pick up the new bounds when they are.
+        let ids = ecs_query(procedural_mesh())
+            .iter(world, None)
+            .filter_map(|(id, mesh_handle)| (*mesh_handle == handle).then_some(id))
+            .collect::<Vec<_>>();
+        for id in ids {
+            if world.has_component(id, local_bounding_aabb()) {
+                world.set(id, local_bounding_aabb(), aabb)?;
+            }
+        }
+        Ok(Ok(()))
+    }
     fn destroy(&mut self, handle: wit::client_mesh::Handle) -> anyhow::Result<()> {
         let world = self.world_mut();
         let storage = world.resource_mut(procedural_storage());
@@ -288,6 +585,19 @@ impl wit::client_texture::Host for Bindings {
     ) -> anyhow::Result<wit::client_texture::Handle> {
         let world = self.world_mut();
         let gpu = world.resource(gpu());
+        let format = desc.format.from_bindgen();
+        // Textures are also storage textures when their format allows it, so that they can be
+        // written to by compute shaders
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::COPY_DST;
+        if format
+            .guaranteed_format_features(gpu.device.features())
+            .allowed_usages
+            .contains(wgpu::TextureUsages::STORAGE_BINDING)
+        {
+            usage |= wgpu::TextureUsages::STORAGE_BINDING;
+        }
         let texture = Texture::new_with_data(
             gpu,
             &wgpu::TextureDescriptor {
@@ -300,8 +610,8 @@ impl wit::client_texture::Host for Bindings {
                 mip_level_count: 1,
                 sample_count: 1,
                 dimension: wgpu::TextureDimension::D2,
-                format: desc.format.from_bindgen(),
-                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                format,
+                usage,
                 view_formats: &[],
             },
             &desc.data,
@@ -319,6 +629,111 @@ impl wit::client_texture::Host for Bindings {
         storage.textures.remove(handle.from_bindgen());
         Ok(())
     }
+    fn read(&mut self, handle: wit::client_texture::Handle) -> anyhow::Result<u64> {
+        let read_id = self.last_texture_read_id;
+        self.last_texture_read_id += 1;
+
+        let module_id = self.id;
+        let world = self.world();
+        let gpu = world.resource(gpu()).clone();
+        // The copy is queued right away, so that the texture is read as it is after the commands
+        // that were submitted before, such as compute dispatches
+        let reader = world
+            .resource(procedural_storage())
+            .textures
+            .get(handle.from_bindgen())
+            .texture
+            .reader(&gpu);
+        let async_run = world.resource(async_run()).clone();
+        let runtime = world.resource(runtime());
+        let task = async move {
+            let data = reader
+                .read(&gpu)
+                .await
+                .context("Failed to map the texture for reading");
+            let message = match data {
+                Ok(data) => TextureRead::new(read_id, data, None),
+                Err(err) => {
+                    tracing::warn!("Failed to read a texture: {err:?}");
+                    TextureRead::new(read_id, vec![], Some(err.to_string()))
+                }
+            };
+            async_run.run(move |world| {
+                message.send(world, Some(module_id)).unwrap();
+            });
+        };
+
+        #[cfg(target_os = "unknown")]
+        runtime.spawn_local(task);
+        #[cfg(not(target_os = "unknown"))]
+        runtime.spawn(task);
+
+        Ok(read_id)
+    }
+    fn update2d(
+        &mut self,
+        handle: wit::client_texture::Handle,
+        origin: wit::types::Uvec2,
+        size: wit::types::Uvec2,
+        data: Vec<u8>,
+    ) -> anyhow::Result<Result<(), String>> {
+        let world = self.world_mut();
+        let texture = world
+            .resource(procedural_storage())
+            .textures
+            .get(handle.from_bindgen())
+            .texture
+            .clone();
+        let origin: UVec2 = origin.from_bindgen();
+        let size: UVec2 = size.from_bindgen();
+
+        let texture_size = texture.size;
+        if u64::from(origin.x) + u64::from(size.x) > u64::from(texture_size.width)
+            || u64::from(origin.y) + u64::from(size.y) > u64::from(texture_size.height)
+        {
+            return Ok(Err(format!(
+                "The region of size {size} at {origin} is out of bounds of a texture of size {}x{}",
+                texture_size.width, texture_size.height
+            )));
+        }
+        // Procedural textures only have uncompressed formats, which have a block size
+        let block_size = texture.format.block_size(None).unwrap();
+        let expected_len = size.x as usize * size.y as usize * block_size as usize;
+        if data.len() != expected_len {
+            return Ok(Err(format!(
+                "The region of size {size} needs {expected_len} bytes of data, but got {}",
+                data.len()
+            )));
+        }
+        if size.x == 0 || size.y == 0 {
+            return Ok(Ok(()));
+        }
+
+        world.resource(gpu()).queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture.handle,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: origin.x,
+                    y: origin.y,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            &data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(size.x * block_size),
+                rows_per_image: Some(size.y),
+            },
+            wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+        );
+        Ok(Ok(()))
+    }
 }
 impl wit::client_sampler::Host for Bindings {
     fn create(
@@ -356,7 +771,7 @@ impl wit::client_material::Host for Bindings {
     ) -> anyhow::Result<wit::client_material::Handle> {
         let world = self.world_mut();
         let storage = world.resource_mut(procedural_storage());
-        let material = PbrMaterialConfig {
+        let material = ProceduralMaterial::Pbr(PbrMaterialConfig {
             source: "Procedural Material".to_string(),
             name: "Procedural Material".to_string(),
             params: PbrMaterialParams {
@@ -376,11 +791,46 @@ impl wit::client_material::Host for Bindings {
             transparent: Some(desc.transparent),
             double_sided: None,
             depth_write_enabled: None,
-        };
+        });
         let material_handle = new_material_handle();
         storage.materials.insert(material_handle, material);
         Ok(material_handle.into_bindgen())
     }
+    fn create_custom(
+        &mut self,
+        desc: wit::client_material::CustomDescriptor,
+    ) -> anyhow::Result<Result<wit::client_material::Handle, String>> {
+        let world = self.world_mut();
+        let assets = world.resource(asset_cache()).clone();
+        let storage = world.resource_mut(procedural_storage());
+        let config = CustomMaterialConfig {
+            name: "Procedural Custom Material".to_string(),
+            shader: desc.shader,
+            params: desc
+                .params
+                .into_iter()
+                .map(|(name, value)| (name, value.from_bindgen()))
+                .collect(),
+            textures: desc
+                .textures
+                .into_iter()
+                .map(|(name, handle)| {
+                    let texture = storage.textures.get(handle.from_bindgen());
+                    (name, Arc::clone(texture))
+                })
+                .collect(),
+            sampler: Arc::clone(storage.samplers.get(desc.sampler.from_bindgen())),
+            transparent: Some(desc.transparent),
+        };
+        if let Err(err) = config.validate(&assets) {
+            return Ok(Err(format!("{err:#}")));
+        }
+        let material_handle = new_material_handle();
+        storage
+            .materials
+            .insert(material_handle, ProceduralMaterial::Custom(config));
+        Ok(Ok(material_handle.into_bindgen()))
+    }
     fn destroy(&mut self, handle: wit::client_material::Handle) -> anyhow::Result<()> {
         let world = self.world_mut();
         let storage = world.resource_mut(procedural_storage());
@@ -388,3 +838,353 @@ impl wit::client_material::Host for Bindings {
         Ok(())
     }
 }
+impl wit::client_buffer::Host for Bindings {
+    fn create(
+        &mut self,
+        data: Vec<u8>,
+    ) -> anyhow::Result<Result<wit::client_buffer::Handle, String>> {
+        let world = self.world_mut();
+        let gpu = world.resource(gpu());
+        let max_size = gpu.device.limits().max_buffer_size;
+        if data.is_empty() || data.len() as u64 % wgpu::COPY_BUFFER_ALIGNMENT != 0 {
+            return Ok(Err(
+                "The size of a buffer must be a non-zero multiple of 4 bytes".to_string(),
+            ));
+        }
+        if data.len() as u64 > max_size {
+            return Ok(Err(format!(
+                "The size of a buffer can be at most {max_size} bytes"
+            )));
+        }
+        let buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Procedural Buffer"),
+                contents: &data,
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST
+                    | wgpu::BufferUsages::COPY_SRC,
+            });
+        let storage = world.resource_mut(procedural_storage());
+        let buffer_handle = new_buffer_handle();
+        storage.buffers.insert(buffer_handle, Arc::new(buffer));
+        Ok(Ok(buffer_handle.into_bindgen()))
+    }
+    fn write(
+        &mut self,
+        handle: wit::client_buffer::Handle,
+        offset: u64,
+        data: Vec<u8>,
+    ) -> anyhow::Result<Result<(), String>> {
+        let world = self.world_mut();
+        let storage = world.resource(procedural_storage());
+        let buffer = storage.buffers.get(handle.from_bindgen());
+        let size = data.len() as u64;
+        if offset % wgpu::COPY_BUFFER_ALIGNMENT != 0 || size % wgpu::COPY_BUFFER_ALIGNMENT != 0 {
+            return Ok(Err(
+                "The offset and size of a write must be multiples of 4 bytes".to_string(),
+            ));
+        }
+        if offset.saturating_add(size) > buffer.size() {
+            return Ok(Err(format!(
+                "Writing {size} bytes at offset {offset} is out of bounds of a buffer of {} bytes",
+                buffer.size()
+            )));
+        }
+        world
+            .resource(gpu())
+            .queue
+            .write_buffer(buffer, offset, &data);
+        Ok(Ok(()))
+    }
+    fn destroy(&mut self, handle: wit::client_buffer::Handle) -> anyhow::Result<()> {
+        let world = self.world_mut();
+        let storage = world.resource_mut(procedural_storage());
+        storage.buffers.remove(handle.from_bindgen());
+        Ok(())
+    }
+}
+impl wit::client_compute::Host for Bindings {
+    fn create(
+        &mut self,
+        desc: wit::client_compute::Descriptor,
+    ) -> anyhow::Result<Result<wit::client_compute::Handle, String>> {
+        let world = self.world_mut();
+        let gpu = world.resource(gpu()).clone();
+        let pipeline = gpu.catch_validation_error(|| {
+            let module = gpu
+                .device
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some("Procedural Compute"),
+                    source: wgpu::ShaderSource::Wgsl(desc.shader.into()),
+                });
+            gpu.device
+                .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: Some("Procedural Compute"),
+                    // The bind group layout is derived from the bindings of the shader
+                    layout: None,
+                    module: &module,
+                    entry_point: &desc.entry_point,
+                })
+        });
+        let pipeline = match pipeline {
+            Ok(pipeline) => pipeline,
+            Err(err) => return Ok(Err(format!("Failed to create compute pipeline: {err:#}"))),
+        };
+        let storage = world.resource_mut(procedural_storage());
+        let compute_handle = new_compute_handle();
+        storage
+            .compute_pipelines
+            .insert(compute_handle, Arc::new(pipeline));
+        Ok(Ok(compute_handle.into_bindgen()))
+    }
+    fn dispatch(
+        &mut self,
+        handle: wit::client_compute::Handle,
+        bindings: Vec<wit::client_compute::Binding>,
+        workgroups: wit::types::Uvec3,
+    ) -> anyhow::Result<Result<(), String>> {
+        use wit::client_compute::Resource;
+
+        let world = self.world_mut();
+        let gpu = world.resource(gpu()).clone();
+        let storage = world.resource(procedural_storage());
+        let pipeline = storage.compute_pipelines.get(handle.from_bindgen());
+
+        let workgroups: UVec3 = workgroups.from_bindgen();
+        let max_workgroups = gpu.device.limits().max_compute_workgroups_per_dimension;
+        if workgroups.max_element() > max_workgroups {
+            return Ok(Err(format!(
+                "At most {max_workgroups} workgroups can be dispatched in each dimension"
+            )));
+        }
+
+        let entries = bindings
+            .into_iter()
+            .map(|binding| wgpu::BindGroupEntry {
+                binding: binding.binding,
+                resource: match binding.resource {
+                    Resource::Buffer(handle) => storage
+                        .buffers
+                        .get(handle.from_bindgen())
+                        .as_entire_binding(),
+                    Resource::Texture(handle) => wgpu::BindingResource::TextureView(
+                        &storage.textures.get(handle.from_bindgen()).handle,
+                    ),
+                    Resource::Sampler(handle) => {
+                        wgpu::BindingResource::Sampler(storage.samplers.get(handle.from_bindgen()))
+                    }
+                },
+            })
+            .collect::<Vec<_>>();
+
+        let commands = gpu.catch_validation_error(|| {
+            // The layout of a shader without bindings has no bind groups
+            let bind_group = (!entries.is_empty()).then(|| {
+                gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Procedural Compute"),
+                    layout: &pipeline.get_bind_group_layout(0),
+                    entries: &entries,
+                })
+            });
+            let mut encoder = gpu
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Procedural Compute"),
+                });
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("Procedural Compute"),
+                });
+                pass.set_pipeline(pipeline);
+                if let Some(bind_group) = &bind_group {
+                    pass.set_bind_group(0, bind_group, &[]);
+                }
+                pass.dispatch_workgroups(workgroups.x, workgroups.y, workgroups.z);
+            }
+            encoder.finish()
+        });
+        // Invalid commands are not submitted, as that would be a fatal error
+        match commands {
+            Ok(commands) => {
+                gpu.queue.submit(Some(commands));
+                Ok(Ok(()))
+            }
+            Err(err) => Ok(Err(format!("Failed to dispatch compute pipeline: {err:#}"))),
+        }
+    }
+    fn destroy(&mut self, handle: wit::client_compute::Handle) -> anyhow::Result<()> {
+        let world = self.world_mut();
+        let storage = world.resource_mut(procedural_storage());
+        storage.compute_pipelines.remove(handle.from_bindgen());
+        Ok(())
+    }
+}
+
+impl wit::client_voice::Host for Bindings {
+    fn start_capture(&mut self) -> anyhow::Result<()> {
+        microphone::start_voice(self.world_mut());
+        Ok(())
+    }
+
+    fn stop_capture(&mut self) -> anyhow::Result<()> {
+        microphone::stop_voice(self.world_mut())
+    }
+}
+
+impl wit::client_microphone::Host for Bindings {
+    fn input_devices(&mut self) -> anyhow::Result<Vec<String>> {
+        Ok(
+            ambient_audio::capture::input_devices().unwrap_or_else(|err| {
+                tracing::warn!("Failed to list the input devices: {err:?}");
+                vec![]
+            }),
+        )
+    }
+
+    fn start_capture(&mut self, device: Option<String>) -> anyhow::Result<()> {
+        microphone::start_device(self.world_mut(), device.unwrap_or_default());
+        Ok(())
+    }
+
+    fn stop_capture(&mut self) -> anyhow::Result<()> {
+        microphone::stop_device(self.world_mut())
+    }
+
+    fn is_allowed(&mut self) -> anyhow::Result<bool> {
+        Ok(microphone::is_granted(self.world()))
+    }
+}
+
+impl wit::client_particles::Host for Bindings {
+    fn burst(&mut self, emitter: wit::types::EntityId, count: u32) -> anyhow::Result<()> {
+        ambient_renderer::particles::burst(self.world_mut(), emitter.from_bindgen(), count)
+    }
+}
+
+impl wit::client_ui_markup::Host for Bindings {
+    fn parse(
+        &mut self,
+        source: String,
+    ) -> anyhow::Result<Result<Vec<wit::client_ui_markup::MarkupNode>, String>> {
+        Ok(ambient_ui_markup::parse(&source)
+            .map(|nodes| {
+                nodes
+                    .into_iter()
+                    .map(|node| wit::client_ui_markup::MarkupNode {
+                        parent: node.parent,
+                        tag: node.tag,
+                        attributes: node.attributes,
+                        style: node.style,
+                        text: node.text,
+                    })
+                    .collect()
+            })
+            .map_err(|err| err.to_string()))
+    }
+
+    fn load(&mut self, url: String) -> anyhow::Result<()> {
+        let module_id = self.id;
+        let assets = self.world().resource(asset_cache()).clone();
+        let async_run = self.world().resource(async_run()).clone();
+        let runtime = self.world().resource(runtime());
+        let task = async move {
+            // The document is not cached, so that edits to it are picked up when it is loaded
+            // again
+            let source = async {
+                let abs_url = AbsAssetUrl::from_str(&url)?;
+                let bytes = download_uncached_bytes(&assets, abs_url).await?;
+                anyhow::Ok(String::from_utf8(bytes)?)
+            };
+            let source = match source.await {
+                Ok(source) => Some(source),
+                Err(err) => {
+                    tracing::warn!("Failed to load UI markup from {url}: {err:?}");
+                    None
+                }
+            };
+            async_run.run(move |world| {
+                UiMarkupLoaded { url, source }
+                    .send(world, Some(module_id))
+                    .unwrap();
+            });
+        };
+
+        #[cfg(target_os = "unknown")]
+        runtime.spawn_local(task);
+        #[cfg(not(target_os = "unknown"))]
+        runtime.spawn(task);
+
+        Ok(())
+    }
+}
+
+impl wit::client_notification::Host for Bindings {
+    fn notify(
+        &mut self,
+        title: String,
+        body: String,
+        icon: Option<String>,
+        duration: f32,
+        severity: wit::client_notification::Severity,
+    ) -> anyhow::Result<u64> {
+        use wit::client_notification::Severity;
+
+        let module_id = self.id;
+        let icon = icon
+            .map(|url| AbsAssetUrl::from_str(&url))
+            .transpose()
+            .context("Invalid notification icon URL")?;
+        let severity = match severity {
+            Severity::Info => NotificationSeverity::Info,
+            Severity::Success => NotificationSeverity::Success,
+            Severity::Warning => NotificationSeverity::Warning,
+            Severity::Error => NotificationSeverity::Error,
+        };
+
+        Ok(notifications::notify(
+            self.world_mut(),
+            Notification {
+                title,
+                body,
+                icon,
+                severity,
+                duration: Duration::from_secs_f32(duration.max(0.)),
+                on_click: Some(cb(move |world, notification_id| {
+                    NotificationClicked { notification_id }
+                        .send(world, Some(module_id))
+                        .unwrap();
+                })),
+            },
+        ))
+    }
+
+    fn dismiss(&mut self, id: u64) -> anyhow::Result<()> {
+        notifications::dismiss(self.world_mut(), id);
+        Ok(())
+    }
+}
+
+impl wit::client_connection::Host for Bindings {
+    fn connect(&mut self, address: String) -> anyhow::Result<Result<(), String>> {
+        let Some(connect) = self.world().resource_opt(connect_to_server()).cloned() else {
+            return Ok(Err(
+                "This client cannot connect to other servers".to_string()
+            ));
+        };
+        connect(address);
+        Ok(Ok(()))
+    }
+
+    fn is_connected(&mut self) -> anyhow::Result<bool> {
+        Ok(self.world().resource_opt(is_local_world()).is_none())
+    }
+
+    fn set_server_paused(&mut self, paused: bool) -> anyhow::Result<Result<(), String>> {
+        let Some(pause) = self.world().resource_opt(pause_server()).cloned() else {
+            return Ok(Err("This client is not hosting the server".to_string()));
+        };
+        Ok(pause(paused).map_err(|err| err.to_string()))
+    }
+}
@@ -1,161 +1,325 @@
-// https://github.com/rust-lang/rust-clippy/issues/10243 ?
-#![allow(clippy::diverging_sub_expression)]
-//! Used to stub out all the unused host functions on the clientside.
-use super::Bindings;
-use crate::shared::{implementation::unsupported, wit};
-
-impl wit::server_asset::Host for Bindings {}
-
-impl wit::server_physics::Host for Bindings {
-    fn add_force(
-        &mut self,
-        _entity: wit::types::EntityId,
-        _force: wit::types::Vec3,
-    ) -> anyhow::Result<()> {
-        unsupported()
-    }
-
-    fn add_impulse(
-        &mut self,
-        _entity: wit::types::EntityId,
-        _force: wit::types::Vec3,
-    ) -> anyhow::Result<()> {
-        unsupported()
-    }
-
-    fn add_radial_impulse(
-        &mut self,
-        _position: wit::types::Vec3,
-        _impulse: f32,
-        _radius: f32,
-        _falloff_radius: Option<f32>,
-    ) -> anyhow::Result<()> {
-        unsupported()
-    }
-
-    fn add_force_at_position(
-        &mut self,
-        _entity: wit::types::EntityId,
-        _force: wit::types::Vec3,
-        _position: wit::types::Vec3,
-    ) -> anyhow::Result<()> {
-        unsupported()
-    }
-
-    fn add_impulse_at_position(
-        &mut self,
-        _entity: wit::types::EntityId,
-        _force: wit::types::Vec3,
-        _position: wit::types::Vec3,
-    ) -> anyhow::Result<()> {
-        unsupported()
-    }
-
-    fn get_velocity_at_position(
-        &mut self,
-        _entity: wit::types::EntityId,
-        _position: wit::types::Vec3,
-    ) -> anyhow::Result<wit::types::Vec3> {
-        unsupported()
-    }
-
-    fn set_gravity(&mut self, _gravity: wit::types::Vec3) -> anyhow::Result<()> {
-        unsupported()
-    }
-
-    fn unfreeze(&mut self, _entity: wit::types::EntityId) -> anyhow::Result<()> {
-        unsupported()
-    }
-
-    fn freeze(&mut self, _entity: wit::types::EntityId) -> anyhow::Result<()> {
-        unsupported()
-    }
-
-    fn start_motor(&mut self, _entity: wit::types::EntityId, _velocity: f32) -> anyhow::Result<()> {
-        unsupported()
-    }
-
-    fn stop_motor(&mut self, _entity: wit::types::EntityId) -> anyhow::Result<()> {
-        unsupported()
-    }
-
-    fn create_revolute_joint(
-        &mut self,
-        _entity0: wit::types::EntityId,
-        _transform0: wit::types::Mat4,
-        _entity1: wit::types::EntityId,
-        _transform1: wit::types::Mat4,
-    ) -> anyhow::Result<()> {
-        unsupported()
-    }
-
-    fn raycast_first(
-        &mut self,
-        _origin: wit::types::Vec3,
-        _direction: wit::types::Vec3,
-    ) -> anyhow::Result<Option<(wit::types::EntityId, f32)>> {
-        unsupported()
-    }
-
-    fn raycast(
-        &mut self,
-        _origin: wit::types::Vec3,
-        _direction: wit::types::Vec3,
-    ) -> anyhow::Result<Vec<(wit::types::EntityId, f32)>> {
-        unsupported()
-    }
-
-    fn move_character(
-        &mut self,
-        _entity: wit::types::EntityId,
-        _displacement: wit::types::Vec3,
-        _min_dist: f32,
-        _elapsed_time: f32,
-    ) -> anyhow::Result<wit::server_physics::CharacterCollision> {
-        unsupported()
-    }
-
-    fn set_character_position(
-        &mut self,
-        _entity: wit::types::EntityId,
-        _position: wit::types::Vec3,
-    ) -> anyhow::Result<()> {
-        unsupported()
-    }
-
-    fn set_character_foot_position(
-        &mut self,
-        _entity: wit::types::EntityId,
-        _position: wit::types::Vec3,
-    ) -> anyhow::Result<()> {
-        unsupported()
-    }
-}
-impl wit::server_message::Host for Bindings {
-    fn send(
-        &mut self,
-        _: wit::server_message::Target,
-        _: String,
-        _: Vec<u8>,
-    ) -> anyhow::Result<()> {
-        unsupported()
-    }
-}
-impl wit::server_http::Host for Bindings {
-    fn get(&mut self, _: String, _: Vec<(String, String)>) -> anyhow::Result<u64> {
-        unsupported()
-    }
-    fn post(
-        &mut self,
-        _: String,
-        _: Vec<(String, String)>,
-        _: Option<Vec<u8>>,
-    ) -> anyhow::Result<u64> {
-        unsupported()
-    }
-}
-impl wit::server_ambient_package::Host for Bindings {
-    fn load(&mut self, _: String) -> anyhow::Result<()> {
-        unsupported()
-    }
-}
+// https://github.com/rust-lang/rust-clippy/issues/10243 ?
+#![allow(clippy::diverging_sub_expression)]
+//! Used to stub out all the unused host functions on the clientside.
+use super::Bindings;
+use crate::shared::{implementation::unsupported, wit};
+
+impl wit::server_asset::Host for Bindings {}
+
+impl wit::server_physics::Host for Bindings {
+    fn add_force(
+        &mut self,
+        _entity: wit::types::EntityId,
+        _force: wit::types::Vec3,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn add_impulse(
+        &mut self,
+        _entity: wit::types::EntityId,
+        _force: wit::types::Vec3,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn add_radial_impulse(
+        &mut self,
+        _position: wit::types::Vec3,
+        _impulse: f32,
+        _radius: f32,
+        _falloff_radius: Option<f32>,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn add_force_at_position(
+        &mut self,
+        _entity: wit::types::EntityId,
+        _force: wit::types::Vec3,
+        _position: wit::types::Vec3,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn add_impulse_at_position(
+        &mut self,
+        _entity: wit::types::EntityId,
+        _force: wit::types::Vec3,
+        _position: wit::types::Vec3,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn get_velocity_at_position(
+        &mut self,
+        _entity: wit::types::EntityId,
+        _position: wit::types::Vec3,
+    ) -> anyhow::Result<wit::types::Vec3> {
+        unsupported()
+    }
+
+    fn set_gravity(&mut self, _gravity: wit::types::Vec3) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn unfreeze(&mut self, _entity: wit::types::EntityId) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn freeze(&mut self, _entity: wit::types::EntityId) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn start_motor(&mut self, _entity: wit::types::EntityId, _velocity: f32) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn stop_motor(&mut self, _entity: wit::types::EntityId) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn create_revolute_joint(
+        &mut self,
+        _entity0: wit::types::EntityId,
+        _transform0: wit::types::Mat4,
+        _entity1: wit::types::EntityId,
+        _transform1: wit::types::Mat4,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn raycast_first(
+        &mut self,
+        _origin: wit::types::Vec3,
+        _direction: wit::types::Vec3,
+    ) -> anyhow::Result<Option<(wit::types::EntityId, f32)>> {
+        unsupported()
+    }
+
+    fn raycast(
+        &mut self,
+        _origin: wit::types::Vec3,
+        _direction: wit::types::Vec3,
+    ) -> anyhow::Result<Vec<(wit::types::EntityId, f32)>> {
+        unsupported()
+    }
+
+    fn raycast_first_many(
+        &mut self,
+        _rays: Vec<wit::types::Ray>,
+    ) -> anyhow::Result<Vec<Option<(wit::types::EntityId, f32)>>> {
+        unsupported()
+    }
+
+    fn raycast_many(
+        &mut self,
+        _rays: Vec<wit::types::Ray>,
+    ) -> anyhow::Result<Vec<Vec<(wit::types::EntityId, f32)>>> {
+        unsupported()
+    }
+
+    fn sweep_sphere(
+        &mut self,
+        _radius: f32,
+        _origin: wit::types::Vec3,
+        _direction: wit::types::Vec3,
+        _max_distance: f32,
+    ) -> anyhow::Result<Option<wit::server_physics::SweepHit>> {
+        unsupported()
+    }
+
+    fn sweep_box(
+        &mut self,
+        _half_extents: wit::types::Vec3,
+        _rotation: wit::types::Quat,
+        _origin: wit::types::Vec3,
+        _direction: wit::types::Vec3,
+        _max_distance: f32,
+    ) -> anyhow::Result<Option<wit::server_physics::SweepHit>> {
+        unsupported()
+    }
+
+    fn sweep_capsule(
+        &mut self,
+        _radius: f32,
+        _half_height: f32,
+        _rotation: wit::types::Quat,
+        _origin: wit::types::Vec3,
+        _direction: wit::types::Vec3,
+        _max_distance: f32,
+    ) -> anyhow::Result<Option<wit::server_physics::SweepHit>> {
+        unsupported()
+    }
+
+    fn overlap_sphere(
+        &mut self,
+        _origin: wit::types::Vec3,
+        _radius: f32,
+    ) -> anyhow::Result<Vec<wit::types::EntityId>> {
+        unsupported()
+    }
+
+    fn overlap_box(
+        &mut self,
+        _origin: wit::types::Vec3,
+        _half_extents: wit::types::Vec3,
+        _rotation: wit::types::Quat,
+    ) -> anyhow::Result<Vec<wit::types::EntityId>> {
+        unsupported()
+    }
+
+    fn overlap_capsule(
+        &mut self,
+        _origin: wit::types::Vec3,
+        _radius: f32,
+        _half_height: f32,
+        _rotation: wit::types::Quat,
+    ) -> anyhow::Result<Vec<wit::types::EntityId>> {
+        unsupported()
+    }
+
+    fn move_character(
+        &mut self,
+        _entity: wit::types::EntityId,
+        _displacement: wit::types::Vec3,
+        _min_dist: f32,
+        _elapsed_time: f32,
+    ) -> anyhow::Result<wit::server_physics::CharacterCollision> {
+        unsupported()
+    }
+
+    fn set_character_position(
+        &mut self,
+        _entity: wit::types::EntityId,
+        _position: wit::types::Vec3,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn set_character_foot_position(
+        &mut self,
+        _entity: wit::types::EntityId,
+        _position: wit::types::Vec3,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn set_vehicle_controls(
+        &mut self,
+        _vehicle: wit::types::EntityId,
+        _throttle: f32,
+        _brake: f32,
+        _steer: f32,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn set_ragdoll_active(
+        &mut self,
+        _entity: wit::types::EntityId,
+        _active: bool,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
+impl wit::server_navigation::Host for Bindings {
+    fn find_path(
+        &mut self,
+        _: wit::types::Vec3,
+        _: wit::types::Vec3,
+    ) -> anyhow::Result<Option<Vec<wit::types::Vec3>>> {
+        unsupported()
+    }
+    fn nearest_point(
+        &mut self,
+        _: wit::types::Vec3,
+        _: f32,
+    ) -> anyhow::Result<Option<wit::types::Vec3>> {
+        unsupported()
+    }
+}
+impl wit::server_message::Host for Bindings {
+    fn send(
+        &mut self,
+        _: wit::server_message::Target,
+        _: String,
+        _: Vec<u8>,
+    ) -> anyhow::Result<Result<(), String>> {
+        unsupported()
+    }
+}
+impl wit::server_http::Host for Bindings {
+    fn get(&mut self, _: String, _: Vec<(String, String)>) -> anyhow::Result<u64> {
+        unsupported()
+    }
+    fn post(
+        &mut self,
+        _: String,
+        _: Vec<(String, String)>,
+        _: Option<Vec<u8>>,
+    ) -> anyhow::Result<u64> {
+        unsupported()
+    }
+}
+impl wit::server_websocket::Host for Bindings {
+    fn connect(&mut self, _: String, _: Vec<(String, String)>) -> anyhow::Result<u64> {
+        unsupported()
+    }
+    fn send(&mut self, _: u64, _: Vec<u8>, _: bool) -> anyhow::Result<Result<(), String>> {
+        unsupported()
+    }
+    fn close(&mut self, _: u64) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
+impl wit::server_player::Host for Bindings {
+    fn promote_spectator(&mut self, _: String) -> anyhow::Result<Option<wit::types::EntityId>> {
+        unsupported()
+    }
+    fn kick(&mut self, _: wit::types::EntityId, _: String) -> anyhow::Result<Result<(), String>> {
+        unsupported()
+    }
+    fn ban(&mut self, _: String, _: String) -> anyhow::Result<()> {
+        unsupported()
+    }
+    fn unban(&mut self, _: String) -> anyhow::Result<bool> {
+        unsupported()
+    }
+    fn get_bans(&mut self) -> anyhow::Result<Vec<(String, String)>> {
+        unsupported()
+    }
+}
+impl wit::server_instance::Host for Bindings {
+    fn current(&mut self) -> anyhow::Result<String> {
+        unsupported()
+    }
+    fn create(&mut self, _: Option<String>) -> anyhow::Result<String> {
+        unsupported()
+    }
+    fn join(&mut self, _: wit::types::EntityId, _: String) -> anyhow::Result<Result<(), String>> {
+        unsupported()
+    }
+    fn remove(&mut self, _: String) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
+impl wit::server_storage::Host for Bindings {
+    fn get(&mut self, _: String) -> anyhow::Result<Result<Option<Vec<u8>>, String>> {
+        unsupported()
+    }
+    fn set(&mut self, _: String, _: Vec<u8>) -> anyhow::Result<Result<(), String>> {
+        unsupported()
+    }
+    fn delete(&mut self, _: String) -> anyhow::Result<Result<bool, String>> {
+        unsupported()
+    }
+    fn list(&mut self, _: String) -> anyhow::Result<Result<Vec<String>, String>> {
+        unsupported()
+    }
+}
+impl wit::server_ambient_package::Host for Bindings {
+    fn load(&mut self, _: String) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
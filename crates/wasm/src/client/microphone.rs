@@ -0,0 +1,120 @@
+//! The permission of the client modules to capture the microphone.
+//!
+//! The microphone is only captured once the user has allowed it by clicking on a notification that
+//! asks for it. Until then, the captures that the modules start are kept pending. The permission
+//! is shared by all the client modules, and lasts until the client is closed.
+use std::time::Duration;
+
+use ambient_ecs::{
+    components,
+    generated::audio::components::{microphone_capturing, voice_capturing},
+    Resource, World,
+};
+use ambient_native_std::cb;
+use ambient_ui_native::notifications::{self, Notification, NotificationSeverity};
+
+components!("wasm::client", {
+    @[Resource]
+    microphone_permission: MicrophonePermission,
+});
+
+/// How long the user has to allow the microphone, before the modules have to ask again.
+const PROMPT_DURATION: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Default)]
+pub struct MicrophonePermission {
+    granted: bool,
+    /// The notification that asks for the permission, while it may be shown
+    prompt: Option<u64>,
+    /// Whether voice chat starts once the permission is granted
+    pending_voice: bool,
+    /// The device that is captured for the modules once the permission is granted
+    pending_device: Option<String>,
+}
+
+/// Whether the user has allowed the microphone to be captured.
+pub(crate) fn is_granted(world: &World) -> bool {
+    world
+        .resource_opt(microphone_permission())
+        .map_or(false, |permission| permission.granted)
+}
+
+/// Starts capturing the microphone for voice chat, or asks for the permission first.
+pub(crate) fn start_voice(world: &mut World) {
+    if is_granted(world) {
+        world.add_resource(voice_capturing(), ());
+    } else {
+        permission_mut(world).pending_voice = true;
+        prompt(world);
+    }
+}
+
+/// Stops capturing the microphone for voice chat, or cancels its pending start.
+pub(crate) fn stop_voice(world: &mut World) -> anyhow::Result<()> {
+    permission_mut(world).pending_voice = false;
+    world.remove_component(world.resource_entity(), voice_capturing())?;
+    Ok(())
+}
+
+/// Starts capturing `device` for the modules, or asks for the permission first.
+pub(crate) fn start_device(world: &mut World, device: String) {
+    if is_granted(world) {
+        world.add_resource(microphone_capturing(), device);
+    } else {
+        permission_mut(world).pending_device = Some(device);
+        prompt(world);
+    }
+}
+
+/// Stops capturing the microphone for the modules, or cancels its pending start.
+pub(crate) fn stop_device(world: &mut World) -> anyhow::Result<()> {
+    permission_mut(world).pending_device = None;
+    world.remove_component(world.resource_entity(), microphone_capturing())?;
+    Ok(())
+}
+
+fn permission_mut(world: &mut World) -> &mut MicrophonePermission {
+    if !world.has_component(world.resource_entity(), microphone_permission()) {
+        world.add_resource(microphone_permission(), MicrophonePermission::default());
+    }
+    world.resource_mut(microphone_permission())
+}
+
+/// Shows the notification that asks for the permission, replacing the previous one in case it has
+/// expired.
+fn prompt(world: &mut World) {
+    if let Some(prompt) = permission_mut(world).prompt.take() {
+        notifications::dismiss(world, prompt);
+    }
+
+    let prompt = notifications::notify(
+        world,
+        Notification {
+            title: "Allow microphone?".to_string(),
+            body: "This game wants to use your microphone. Click here to allow it until the game \
+                   is closed."
+                .to_string(),
+            icon: None,
+            severity: NotificationSeverity::Warning,
+            duration: PROMPT_DURATION,
+            on_click: Some(cb(|world, _| grant(world))),
+        },
+    );
+    permission_mut(world).prompt = Some(prompt);
+}
+
+fn grant(world: &mut World) {
+    tracing::info!("The microphone was allowed");
+    let permission = permission_mut(world);
+    permission.granted = true;
+    permission.prompt = None;
+    let voice = std::mem::take(&mut permission.pending_voice);
+    let device = permission.pending_device.take();
+
+    if voice {
+        world.add_resource(voice_capturing(), ());
+    }
+    if let Some(device) = device {
+        world.add_resource(microphone_capturing(), device);
+    }
+}
@@ -4,11 +4,13 @@ use ambient_native_std::asset_cache::AssetCache;
 use std::sync::Arc;
 
 mod implementation;
+pub mod microphone;
 mod network;
 
 pub fn initialize(
     world: &mut World,
     assets: &AssetCache,
+    analytics_opt_out: bool,
     messenger: Arc<dyn Fn(&World, EntityId, shared::MessageType, &str) + Send + Sync>,
 ) -> anyhow::Result<()> {
     shared::initialize(
@@ -19,11 +21,14 @@ pub fn initialize(
             base: Default::default(),
             world_ref: Default::default(),
             id,
+            last_screenshot_id: 0,
+            last_texture_read_id: 0,
         }),
         None,
     )?;
 
     network::initialize(world);
+    shared::analytics::initialize(world, analytics_opt_out);
 
     Ok(())
 }
@@ -36,6 +41,8 @@ struct Bindings {
     base: shared::bindings::BindingsBase,
     world_ref: shared::bindings::WorldRef,
     id: EntityId,
+    last_screenshot_id: u64,
+    last_texture_read_id: u64,
 }
 
 impl Bindings {
@@ -11,23 +11,40 @@ use ambient_core::{
     runtime,
 };
 use ambient_ecs::{
-    generated::{messages::HttpResponse, types::HttpMethod},
+    generated::{
+        messages::HttpResponse,
+        package::components::{self as package_components, http_allowed_domains},
+        types::HttpMethod,
+    },
     query, EntityId, World,
 };
-use ambient_native_std::asset_url::AbsAssetUrl;
-use ambient_network::server::player_transport;
+use ambient_native_std::{asset_url::AbsAssetUrl, friendly_id};
+use ambient_network::{
+    bans::SharedBanList,
+    server::{
+        ban_list, player_connection_id, player_transport, request_instance_change, request_kick,
+        world_instance_id, InstanceRequest, MAIN_INSTANCE_ID,
+    },
+};
+use anyhow::Context;
 use reqwest::header::{HeaderMap, HeaderName};
 
-use super::super::Bindings;
+use super::super::{
+    interest::is_interested,
+    storage::{storage, SharedStorage},
+    Bindings,
+};
 
 use crate::shared::{
     self,
-    conversion::FromBindgen,
-    implementation::message,
+    conversion::{FromBindgen, IntoBindgen},
+    implementation::message::{self, Topic},
     message::{MessageExt, Target},
 };
 
+mod navigation;
 mod physics;
+mod websocket;
 
 #[async_trait::async_trait]
 impl shared::wit::server_asset::Host for Bindings {}
@@ -39,26 +56,49 @@ impl shared::wit::server_message::Host for Bindings {
         target: shared::wit::server_message::Target,
         name: String,
         data: Vec<u8>,
-    ) -> wasm_bridge::Result<()> {
+    ) -> wasm_bridge::Result<Result<(), String>> {
         use shared::wit::server_message::Target as WitTarget;
         let module_id = self.id;
         let world = self.world_mut();
 
         match target {
             WitTarget::ClientBroadcastUnreliable => {
-                send_networked(world, None, module_id, name, data, false)
+                send_networked(world, |_, _| true, module_id, name, data, false)
             }
             WitTarget::ClientBroadcastReliable => {
-                send_networked(world, None, module_id, name, data, true)
+                send_networked(world, |_, _| true, module_id, name, data, true)
             }
             WitTarget::ClientTargetedUnreliable(user_id) => {
-                send_networked(world, Some(user_id), module_id, name, data, false)
+                send_networked(world, |_, uid| uid == user_id, module_id, name, data, false)
             }
             WitTarget::ClientTargetedReliable(user_id) => {
-                send_networked(world, Some(user_id), module_id, name, data, true)
+                send_networked(world, |_, uid| uid == user_id, module_id, name, data, true)
+            }
+            WitTarget::ClientInterestedUnreliable(topic) => {
+                let (world, topic) = (&*world, topic.from_bindgen());
+                send_networked(
+                    world,
+                    |id, _| is_interested(world, id, topic),
+                    module_id,
+                    name,
+                    data,
+                    false,
+                )
+            }
+            WitTarget::ClientInterestedReliable(topic) => {
+                let (world, topic) = (&*world, topic.from_bindgen());
+                send_networked(
+                    world,
+                    |id, _| is_interested(world, id, topic),
+                    module_id,
+                    name,
+                    data,
+                    true,
+                )
             }
             WitTarget::LocalBroadcast(include_self) => {
                 message::send_local(world, module_id, Target::All { include_self }, name, data)
+                    .map(Ok)
             }
             WitTarget::Local(id) => message::send_local(
                 world,
@@ -66,28 +106,30 @@ impl shared::wit::server_message::Host for Bindings {
                 Target::PackageOrModule(id.from_bindgen()),
                 name,
                 data,
-            ),
+            )
+            .map(Ok),
         }
     }
 }
 
+/// Sends a message to the clients of the players for which `is_recipient` returns true, given
+/// their entity and user ID.
 fn send_networked(
     world: &World,
-    target_user_id: Option<String>,
+    is_recipient: impl Fn(EntityId, &str) -> bool,
     module_id: EntityId,
     name: String,
     data: Vec<u8>,
     reliable: bool,
-) -> wasm_bridge::Result<()> {
+) -> wasm_bridge::Result<Result<(), String>> {
+    if let Err(err) = message::check_networked_size(&name, &data, reliable) {
+        return Ok(Err(err));
+    }
+
     let connections: Vec<_> = query((user_id(), player_transport()))
         .incl(is_player())
         .iter(world, None)
-        .filter(|(_, (uid, _))| {
-            target_user_id
-                .as_ref()
-                .map(|target_uid| target_uid == *uid)
-                .unwrap_or(true)
-        })
+        .filter(|(id, (uid, _))| is_recipient(*id, uid))
         .map(|(_, (_, connection))| connection.clone())
         .collect();
 
@@ -95,7 +137,17 @@ fn send_networked(
         message::send_networked(world, conn, module_id, &name, &data, reliable)?;
     }
 
-    Ok(())
+    Ok(Ok(()))
+}
+
+impl FromBindgen for shared::wit::server_message::Topic {
+    type Item = Topic;
+    fn from_bindgen(self) -> Self::Item {
+        match self {
+            Self::Entity(id) => Topic::Entity(id.from_bindgen()),
+            Self::Position(position) => Topic::Position(position.from_bindgen()),
+        }
+    }
 }
 
 impl shared::wit::server_http::Host for Bindings {
@@ -114,6 +166,44 @@ impl shared::wit::server_http::Host for Bindings {
 }
 
 impl Bindings {
+    fn ban_list(&self) -> anyhow::Result<SharedBanList> {
+        self.world()
+            .resource_opt(ban_list())
+            .cloned()
+            .context("This server does not support bans")
+    }
+
+    /// Returns the storage of the server, and the ID of the package of this module, which the
+    /// module's values are stored under.
+    fn storage(&self) -> anyhow::Result<(SharedStorage, String)> {
+        let world = self.world();
+        let storage = world
+            .resource_opt(storage())
+            .cloned()
+            .context("This server does not support storage")?;
+        let package_id = world
+            .get(self.id, shared::package_ref())
+            .and_then(|package| world.get_cloned(package, package_components::id()))
+            .context("This module does not belong to a package")?;
+        Ok((storage, package_id))
+    }
+
+    /// Returns the `allowed_domains` of the `[http]` section of the manifest of the package of
+    /// this module.
+    fn allowed_domains(&self) -> Vec<String> {
+        let world = self.world();
+        world
+            .get(self.id, shared::package_ref())
+            .ok()
+            .and_then(|package| world.get_cloned(package, http_allowed_domains()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Returns whether `host` is in the `allowed_domains` of the package of this module.
+    fn is_allowed_domain(&self, host: &str) -> bool {
+        ambient_package::allowed_domains_match(&self.allowed_domains(), host)
+    }
+
     fn http_request_impl(
         &mut self,
         method: HttpMethod,
@@ -126,10 +216,10 @@ impl Bindings {
         }
 
         let id = self.id;
-        let client = self.reqwest_client.clone();
+        let allowed_domains = self.allowed_domains();
         let response_id = self.last_http_request_id;
         self.last_http_request_id += 1;
-        let world = self.world_mut();
+        let world = self.world();
 
         let assets = world.resource(asset_cache());
         let runtime = world.resource(runtime());
@@ -139,6 +229,13 @@ impl Bindings {
             .to_download_url(assets)?
             .to_string();
 
+        let host = reqwest::Url::parse(&resolved_url)?
+            .host_str()
+            .unwrap_or_default()
+            .to_string();
+        let allowed = ambient_package::allowed_domains_match(&allowed_domains, &host);
+
+        let client = http_client(allowed_domains)?;
         let request = match method {
             HttpMethod::Get => client.get(&resolved_url),
             HttpMethod::Post => client.post(&resolved_url),
@@ -159,14 +256,25 @@ impl Bindings {
 
         runtime.spawn(async move {
             let wasm_response = run_with_error(response_id, async move {
+                anyhow::ensure!(
+                    allowed,
+                    "{host:?} is not an allowed domain; add it to `allowed_domains` in the \
+                    `[http]` section of the package manifest"
+                );
                 let response = request.send().await?;
 
                 let status = response.status().as_u16() as u32;
+                let headers = response
+                    .headers()
+                    .iter()
+                    .filter_map(|(name, value)| Some(format!("{name}: {}", value.to_str().ok()?)))
+                    .collect();
                 let body = response.bytes().await?.to_vec();
                 Ok(HttpResponse {
                     response_id,
-                    body,
                     status,
+                    headers,
+                    body,
                     error: None,
                 })
             })
@@ -186,6 +294,7 @@ impl Bindings {
                 Err(err) => HttpResponse {
                     response_id,
                     body: vec![],
+                    headers: vec![],
                     status: 0,
                     error: Some(err.to_string()),
                 },
@@ -196,6 +305,141 @@ impl Bindings {
     }
 }
 
+/// The maximum number of redirects an HTTP request follows, matching the default of reqwest.
+const MAX_HTTP_REDIRECTS: usize = 10;
+
+/// Builds a client that only follows redirects to the `allowed_domains`, so that an allowed
+/// domain can't redirect a request to any other.
+fn http_client(allowed_domains: Vec<String>) -> reqwest::Result<reqwest::Client> {
+    let policy = reqwest::redirect::Policy::custom(move |attempt| {
+        let host = attempt.url().host_str().unwrap_or_default().to_string();
+        if attempt.previous().len() > MAX_HTTP_REDIRECTS {
+            attempt.error("too many redirects")
+        } else if ambient_package::allowed_domains_match(&allowed_domains, &host) {
+            attempt.follow()
+        } else {
+            attempt.error(format!(
+                "redirected to {host:?}, which is not an allowed domain; add it to \
+                `allowed_domains` in the `[http]` section of the package manifest"
+            ))
+        }
+    });
+    reqwest::Client::builder().redirect(policy).build()
+}
+
+impl shared::wit::server_player::Host for Bindings {
+    fn promote_spectator(
+        &mut self,
+        user_id: String,
+    ) -> wasm_bridge::Result<Option<shared::wit::types::EntityId>> {
+        Ok(ambient_network::server::promote_spectator(self.world_mut(), &user_id).into_bindgen())
+    }
+
+    fn kick(
+        &mut self,
+        player: shared::wit::types::EntityId,
+        reason: String,
+    ) -> wasm_bridge::Result<Result<(), String>> {
+        let player = player.from_bindgen();
+        let world = self.world_mut();
+        if !world.has_component(player, player_connection_id()) {
+            return Ok(Err(format!("{player} is not connected to the server")));
+        }
+        let user_id = world.get_cloned(player, user_id())?;
+        request_kick(world, user_id, reason);
+        Ok(Ok(()))
+    }
+
+    fn ban(&mut self, user_id: String, reason: String) -> wasm_bridge::Result<()> {
+        self.ban_list()?.lock().ban(user_id, reason);
+        Ok(())
+    }
+
+    fn unban(&mut self, user_id: String) -> wasm_bridge::Result<bool> {
+        Ok(self.ban_list()?.lock().unban(&user_id))
+    }
+
+    fn get_bans(&mut self) -> wasm_bridge::Result<Vec<(String, String)>> {
+        Ok(self
+            .ban_list()?
+            .lock()
+            .iter()
+            .map(|(user_id, reason)| (user_id.to_string(), reason.to_string()))
+            .collect())
+    }
+}
+
+impl shared::wit::server_instance::Host for Bindings {
+    fn current(&mut self) -> anyhow::Result<String> {
+        Ok(self
+            .world()
+            .resource_opt(world_instance_id())
+            .cloned()
+            .unwrap_or_else(|| MAIN_INSTANCE_ID.to_string()))
+    }
+
+    fn create(&mut self, id: Option<String>) -> anyhow::Result<String> {
+        let id = id.unwrap_or_else(friendly_id);
+        request_instance_change(self.world_mut(), InstanceRequest::Create { id: id.clone() });
+        Ok(id)
+    }
+
+    fn join(
+        &mut self,
+        player: shared::wit::types::EntityId,
+        instance: String,
+    ) -> anyhow::Result<Result<(), String>> {
+        let player = player.from_bindgen();
+        let Ok(user_id) = self.world().get_cloned(player, user_id()) else {
+            return Ok(Err(format!("{player} is not a player")));
+        };
+        request_instance_change(
+            self.world_mut(),
+            InstanceRequest::Join {
+                user_id,
+                instance_id: instance,
+            },
+        );
+        Ok(Ok(()))
+    }
+
+    fn remove(&mut self, instance: String) -> anyhow::Result<()> {
+        request_instance_change(
+            self.world_mut(),
+            InstanceRequest::Remove {
+                instance_id: instance,
+            },
+        );
+        Ok(())
+    }
+}
+
+impl shared::wit::server_storage::Host for Bindings {
+    fn get(&mut self, key: String) -> anyhow::Result<Result<Option<Vec<u8>>, String>> {
+        let (storage, package_id) = self.storage()?;
+        let result = storage.lock().get(&package_id, &key);
+        Ok(result.map_err(|err| format!("{err:?}")))
+    }
+
+    fn set(&mut self, key: String, value: Vec<u8>) -> anyhow::Result<Result<(), String>> {
+        let (storage, package_id) = self.storage()?;
+        let result = storage.lock().set(&package_id, key, value);
+        Ok(result.map_err(|err| format!("{err:?}")))
+    }
+
+    fn delete(&mut self, key: String) -> anyhow::Result<Result<bool, String>> {
+        let (storage, package_id) = self.storage()?;
+        let result = storage.lock().delete(&package_id, &key);
+        Ok(result.map_err(|err| format!("{err:?}")))
+    }
+
+    fn list(&mut self, prefix: String) -> anyhow::Result<Result<Vec<String>, String>> {
+        let (storage, package_id) = self.storage()?;
+        let result = storage.lock().list(&package_id, &prefix);
+        Ok(result.map_err(|err| format!("{err:?}")))
+    }
+}
+
 impl shared::wit::server_ambient_package::Host for Bindings {
     fn load(&mut self, url: String) -> anyhow::Result<()> {
         ambient_package_semantic_native::add(self.world_mut(), url, false)?;
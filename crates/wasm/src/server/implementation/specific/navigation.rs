@@ -0,0 +1,38 @@
+use super::super::super::Bindings;
+use crate::shared::{
+    self,
+    conversion::{FromBindgen, IntoBindgen},
+    wit,
+};
+
+impl shared::wit::server_navigation::Host for Bindings {
+    fn find_path(
+        &mut self,
+        start: wit::types::Vec3,
+        end: wit::types::Vec3,
+    ) -> anyhow::Result<Option<Vec<wit::types::Vec3>>> {
+        let (start, end) = (start.from_bindgen(), end.from_bindgen());
+        if !(start.is_finite() && end.is_finite()) {
+            anyhow::bail!("Path ends must be finite");
+        }
+        Ok(
+            ambient_physics::navigation::find_path(self.world(), start, end)
+                .map(|path| path.into_bindgen()),
+        )
+    }
+
+    fn nearest_point(
+        &mut self,
+        position: wit::types::Vec3,
+        max_distance: f32,
+    ) -> anyhow::Result<Option<wit::types::Vec3>> {
+        let position = position.from_bindgen();
+        if !position.is_finite() || !(max_distance >= 0.) {
+            anyhow::bail!("Position must be finite, and distance must not be negative");
+        }
+        Ok(
+            ambient_physics::navigation::nearest_point(self.world(), position, max_distance)
+                .map(|point| point.into_bindgen()),
+        )
+    }
+}
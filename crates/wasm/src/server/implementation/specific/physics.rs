@@ -4,8 +4,12 @@ use crate::shared::{
     conversion::{FromBindgen, IntoBindgen},
     wit,
 };
+use ambient_ecs::generated::model::components::model_from_url;
 use ambient_native_std::shapes::Ray;
-use ambient_physics::physx::character_controller;
+use ambient_physics::{
+    physx::character_controller, ragdoll, ragdoll_active, vehicle_brake, vehicle_steer,
+    vehicle_throttle,
+};
 use anyhow::Context;
 use physxx::{PxControllerCollisionFlag, PxControllerFilters};
 
@@ -197,6 +201,167 @@ impl shared::wit::server_physics::Host for Bindings {
         Ok(result)
     }
 
+    fn raycast_first_many(
+        &mut self,
+        rays: Vec<wit::types::Ray>,
+    ) -> anyhow::Result<Vec<Option<(wit::types::EntityId, f32)>>> {
+        let rays = get_raycast_rays(rays)?;
+        let world = self.world();
+        Ok(rays
+            .into_iter()
+            .map(|ray| {
+                ambient_physics::intersection::raycast_first(world, ray)
+                    .map(|t| (t.0.into_bindgen(), t.1.into_bindgen()))
+            })
+            .collect())
+    }
+
+    fn raycast_many(
+        &mut self,
+        rays: Vec<wit::types::Ray>,
+    ) -> anyhow::Result<Vec<Vec<(wit::types::EntityId, f32)>>> {
+        let rays = get_raycast_rays(rays)?;
+        let world = self.world();
+        Ok(rays
+            .into_iter()
+            .map(|ray| {
+                ambient_physics::intersection::raycast(world, ray)
+                    .into_iter()
+                    .map(|t| (t.0.into_bindgen(), t.1.into_bindgen()))
+                    .collect()
+            })
+            .collect())
+    }
+
+    fn sweep_sphere(
+        &mut self,
+        radius: f32,
+        origin: wit::types::Vec3,
+        direction: wit::types::Vec3,
+        max_distance: f32,
+    ) -> anyhow::Result<Option<wit::server_physics::SweepHit>> {
+        let direction = get_sweep_direction(direction, max_distance)?;
+        if !(radius > 0.) {
+            anyhow::bail!("Sweep radius must be positive");
+        }
+        Ok(ambient_physics::intersection::sweep_sphere(
+            self.world(),
+            radius,
+            origin.from_bindgen(),
+            direction,
+            max_distance,
+        )
+        .map(sweep_hit_into_bindgen))
+    }
+
+    fn sweep_box(
+        &mut self,
+        half_extents: wit::types::Vec3,
+        rotation: wit::types::Quat,
+        origin: wit::types::Vec3,
+        direction: wit::types::Vec3,
+        max_distance: f32,
+    ) -> anyhow::Result<Option<wit::server_physics::SweepHit>> {
+        let direction = get_sweep_direction(direction, max_distance)?;
+        let half_extents = half_extents.from_bindgen();
+        if !half_extents.cmpgt(glam::Vec3::ZERO).all() {
+            anyhow::bail!("Sweep half extents must be positive");
+        }
+        Ok(ambient_physics::intersection::sweep_box(
+            self.world(),
+            half_extents,
+            get_shape_rotation(rotation, "Sweep")?,
+            origin.from_bindgen(),
+            direction,
+            max_distance,
+        )
+        .map(sweep_hit_into_bindgen))
+    }
+
+    fn sweep_capsule(
+        &mut self,
+        radius: f32,
+        half_height: f32,
+        rotation: wit::types::Quat,
+        origin: wit::types::Vec3,
+        direction: wit::types::Vec3,
+        max_distance: f32,
+    ) -> anyhow::Result<Option<wit::server_physics::SweepHit>> {
+        let direction = get_sweep_direction(direction, max_distance)?;
+        if !(radius > 0. && half_height >= 0.) {
+            anyhow::bail!(
+                "Sweep capsule radius must be positive, and its half height non-negative"
+            );
+        }
+        Ok(ambient_physics::intersection::sweep_capsule(
+            self.world(),
+            radius,
+            half_height,
+            get_shape_rotation(rotation, "Sweep")?,
+            origin.from_bindgen(),
+            direction,
+            max_distance,
+        )
+        .map(sweep_hit_into_bindgen))
+    }
+
+    fn overlap_sphere(
+        &mut self,
+        origin: wit::types::Vec3,
+        radius: f32,
+    ) -> anyhow::Result<Vec<wit::types::EntityId>> {
+        if !(radius > 0.) {
+            anyhow::bail!("Overlap radius must be positive");
+        }
+        Ok(ambient_physics::intersection::overlap_sphere(
+            self.world(),
+            origin.from_bindgen(),
+            radius,
+        )
+        .into_bindgen())
+    }
+
+    fn overlap_box(
+        &mut self,
+        origin: wit::types::Vec3,
+        half_extents: wit::types::Vec3,
+        rotation: wit::types::Quat,
+    ) -> anyhow::Result<Vec<wit::types::EntityId>> {
+        let half_extents = half_extents.from_bindgen();
+        if !half_extents.cmpgt(glam::Vec3::ZERO).all() {
+            anyhow::bail!("Overlap half extents must be positive");
+        }
+        Ok(ambient_physics::intersection::overlap_box(
+            self.world(),
+            origin.from_bindgen(),
+            half_extents,
+            get_shape_rotation(rotation, "Overlap")?,
+        )
+        .into_bindgen())
+    }
+
+    fn overlap_capsule(
+        &mut self,
+        origin: wit::types::Vec3,
+        radius: f32,
+        half_height: f32,
+        rotation: wit::types::Quat,
+    ) -> anyhow::Result<Vec<wit::types::EntityId>> {
+        if !(radius > 0. && half_height >= 0.) {
+            anyhow::bail!(
+                "Overlap capsule radius must be positive, and its half height non-negative"
+            );
+        }
+        Ok(ambient_physics::intersection::overlap_capsule(
+            self.world(),
+            origin.from_bindgen(),
+            radius,
+            half_height,
+            get_shape_rotation(rotation, "Overlap")?,
+        )
+        .into_bindgen())
+    }
+
     fn move_character(
         &mut self,
         entity: wit::types::EntityId,
@@ -251,6 +416,44 @@ impl shared::wit::server_physics::Host for Bindings {
             .set_foot_position(position.from_bindgen().as_dvec3());
         Ok(())
     }
+
+    fn set_vehicle_controls(
+        &mut self,
+        vehicle: wit::types::EntityId,
+        throttle: f32,
+        brake: f32,
+        steer: f32,
+    ) -> anyhow::Result<()> {
+        if !(throttle.is_finite() && brake.is_finite() && steer.is_finite()) {
+            anyhow::bail!("Vehicle controls must be finite");
+        }
+        let vehicle = vehicle.from_bindgen();
+        let world = self.world_mut();
+        if !world.exists(vehicle) {
+            anyhow::bail!("Vehicle {vehicle} does not exist");
+        }
+        world.add_component(vehicle, vehicle_throttle(), throttle.clamp(-1., 1.))?;
+        world.add_component(vehicle, vehicle_brake(), brake.clamp(0., 1.))?;
+        world.add_component(vehicle, vehicle_steer(), steer.clamp(-1., 1.))?;
+        Ok(())
+    }
+
+    fn set_ragdoll_active(
+        &mut self,
+        entity: wit::types::EntityId,
+        active: bool,
+    ) -> anyhow::Result<()> {
+        let entity = entity.from_bindgen();
+        let world = self.world_mut();
+        if !world.has_component(entity, model_from_url()) {
+            anyhow::bail!("Entity {entity} has no model to build a ragdoll from");
+        }
+        if !world.has_component(entity, ragdoll()) {
+            world.add_component(entity, ragdoll(), ())?;
+        }
+        world.add_component(entity, ragdoll_active(), active)?;
+        Ok(())
+    }
 }
 
 /// Returns an error if the direction is non-normalized.
@@ -267,3 +470,47 @@ fn get_raycast_direction(direction: wit::types::Vec3) -> anyhow::Result<glam::Ve
     }
     Ok(direction)
 }
+
+/// Returns an error if the direction is non-normalized, or the distance is not positive.
+fn get_sweep_direction(
+    direction: wit::types::Vec3,
+    max_distance: f32,
+) -> anyhow::Result<glam::Vec3> {
+    if !(max_distance > 0.) {
+        anyhow::bail!("Sweep distance must be positive");
+    }
+    get_raycast_direction(direction).context("Invalid sweep direction")
+}
+
+/// Returns an error if the rotation of the shape of a `query` is not a unit quaternion.
+fn get_shape_rotation(rotation: wit::types::Quat, query: &str) -> anyhow::Result<glam::Quat> {
+    let rotation = rotation.from_bindgen();
+    if !rotation.is_normalized() {
+        anyhow::bail!("{query} rotation must be normalized");
+    }
+    Ok(rotation)
+}
+
+fn sweep_hit_into_bindgen(
+    hit: ambient_physics::intersection::SweepHit,
+) -> wit::server_physics::SweepHit {
+    wit::server_physics::SweepHit {
+        entity: hit.entity.into_bindgen(),
+        distance: hit.distance,
+        position: hit.position.into_bindgen(),
+        normal: hit.normal.into_bindgen(),
+    }
+}
+
+/// Validates the directions of all of the `rays`, so that a batch of raycasts either fails as a
+/// whole or runs as a whole.
+fn get_raycast_rays(rays: Vec<wit::types::Ray>) -> anyhow::Result<Vec<Ray>> {
+    rays.into_iter()
+        .enumerate()
+        .map(|(index, ray)| {
+            let direction =
+                get_raycast_direction(ray.dir).with_context(|| format!("Ray {index}"))?;
+            Ok(Ray::new(ray.origin.from_bindgen(), direction))
+        })
+        .collect()
+}
@@ -0,0 +1,149 @@
+use std::str::FromStr;
+
+use ambient_core::{async_ecs::async_run, runtime};
+use ambient_ecs::generated::messages::{WebSocketClosed, WebSocketConnected, WebSocketMessage};
+use futures::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::{
+    client::IntoClientRequest,
+    http::{HeaderName, HeaderValue},
+    Message,
+};
+
+use super::super::super::Bindings;
+use crate::shared::{self, message::MessageExt};
+
+impl shared::wit::server_websocket::Host for Bindings {
+    fn connect(&mut self, url: String, headers: Vec<(String, String)>) -> anyhow::Result<u64> {
+        if self.hosted {
+            anyhow::bail!("WebSockets are not supported on hosted servers");
+        }
+
+        let id = self.id;
+        let socket_id = self.last_websocket_id;
+        self.last_websocket_id += 1;
+
+        let mut request = url.as_str().into_client_request()?;
+        for (name, value) in headers {
+            request
+                .headers_mut()
+                .insert(HeaderName::from_str(&name)?, HeaderValue::from_str(&value)?);
+        }
+        let host = request.uri().host().unwrap_or_default().to_string();
+        let allowed = self.is_allowed_domain(&host);
+
+        let (outgoing_tx, outgoing_rx) = flume::unbounded::<Message>();
+        self.websockets.insert(socket_id, outgoing_tx);
+
+        let world = self.world();
+        let runtime = world.resource(runtime());
+        let async_run = world.resource(async_run()).clone();
+
+        runtime.spawn(async move {
+            let connected = async {
+                anyhow::ensure!(
+                    allowed,
+                    "{host:?} is not an allowed domain; add it to `allowed_domains` in the \
+                    `[http]` section of the package manifest"
+                );
+                let (stream, _) = tokio_tungstenite::connect_async(request).await?;
+                Ok(stream)
+            }
+            .await;
+
+            let stream = match connected {
+                Ok(stream) => {
+                    async_run.run(move |world| {
+                        WebSocketConnected::new(socket_id, None)
+                            .send(world, Some(id))
+                            .unwrap();
+                    });
+                    stream
+                }
+                Err(err) => {
+                    let error = err.to_string();
+                    async_run.run(move |world| {
+                        WebSocketConnected::new(socket_id, Some(error))
+                            .send(world, Some(id))
+                            .unwrap();
+                    });
+                    return;
+                }
+            };
+
+            let (mut write, mut read) = stream.split();
+            let reason = loop {
+                tokio::select! {
+                    outgoing = outgoing_rx.recv_async() => match outgoing {
+                        Ok(message) => {
+                            if let Err(err) = write.send(message).await {
+                                break err.to_string();
+                            }
+                        }
+                        // The module closed the WebSocket, or was unloaded
+                        Err(_) => {
+                            write.send(Message::Close(None)).await.ok();
+                            return;
+                        }
+                    },
+                    incoming = read.next() => {
+                        let (data, is_text) = match incoming {
+                            Some(Ok(Message::Text(text))) => (text.into_bytes(), true),
+                            Some(Ok(Message::Binary(data))) => (data, false),
+                            Some(Ok(Message::Close(frame))) => {
+                                break frame
+                                    .map(|frame| frame.reason.to_string())
+                                    .unwrap_or_default();
+                            }
+                            // Pings are answered by tungstenite
+                            Some(Ok(_)) => continue,
+                            Some(Err(err)) => break err.to_string(),
+                            None => break String::new(),
+                        };
+                        async_run.run(move |world| {
+                            WebSocketMessage::new(socket_id, data, is_text)
+                                .send(world, Some(id))
+                                .unwrap();
+                        });
+                    }
+                }
+            };
+
+            async_run.run(move |world| {
+                WebSocketClosed::new(socket_id, reason)
+                    .send(world, Some(id))
+                    .unwrap();
+            });
+        });
+
+        Ok(socket_id)
+    }
+
+    fn send(
+        &mut self,
+        socket_id: u64,
+        data: Vec<u8>,
+        is_text: bool,
+    ) -> anyhow::Result<Result<(), String>> {
+        let Some(sender) = self.websockets.get(&socket_id) else {
+            return Ok(Err(format!("WebSocket {socket_id} is not open")));
+        };
+        let message = if is_text {
+            match String::from_utf8(data) {
+                Ok(text) => Message::Text(text),
+                Err(err) => return Ok(Err(err.to_string())),
+            }
+        } else {
+            Message::Binary(data)
+        };
+        if sender.send(message).is_err() {
+            self.websockets.remove(&socket_id);
+            return Ok(Err(format!("WebSocket {socket_id} has been closed")));
+        }
+        Ok(Ok(()))
+    }
+
+    fn close(&mut self, socket_id: u64) -> anyhow::Result<()> {
+        self.websockets.remove(&socket_id);
+        Ok(())
+    }
+}
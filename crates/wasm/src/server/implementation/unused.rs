@@ -10,7 +10,14 @@ impl wit::client_message::Host for Bindings {
         _: wit::client_message::Target,
         _: String,
         _: Vec<u8>,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<Result<(), String>> {
+        unsupported()
+    }
+    fn set_interests(
+        &mut self,
+        _: Vec<wit::types::EntityId>,
+        _: Vec<(wit::types::Vec3, f32)>,
+    ) -> anyhow::Result<Result<(), String>> {
         unsupported()
     }
 }
@@ -31,12 +38,24 @@ impl wit::client_input::Host for Bindings {
     fn set_cursor(&mut self, _: wit::client_input::CursorIcon) -> anyhow::Result<()> {
         unsupported()
     }
+    fn set_cursor_image(
+        &mut self,
+        _: Option<wit::client_input::CursorImage>,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
     fn set_cursor_visible(&mut self, _: bool) -> anyhow::Result<()> {
         unsupported()
     }
     fn set_cursor_lock(&mut self, _: bool) -> anyhow::Result<()> {
         unsupported()
     }
+    fn set_cursor_confinement(
+        &mut self,
+        _: Option<(wit::types::Vec2, wit::types::Vec2)>,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
 }
 
 impl wit::client_camera::Host for Bindings {
@@ -69,6 +88,21 @@ impl wit::client_camera::Host for Bindings {
     ) -> anyhow::Result<wit::types::Vec3> {
         unsupported()
     }
+
+    fn get_post_processing(
+        &mut self,
+        _camera: wit::types::EntityId,
+    ) -> anyhow::Result<wit::client_camera::PostProcessing> {
+        unsupported()
+    }
+
+    fn set_post_processing(
+        &mut self,
+        _camera: wit::types::EntityId,
+        _settings: wit::client_camera::PostProcessing,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
 }
 
 impl wit::client_clipboard::Host for Bindings {
@@ -85,6 +119,22 @@ impl wit::client_window::Host for Bindings {
     fn set_fullscreen(&mut self, _fullscreen: bool) -> anyhow::Result<()> {
         unsupported()
     }
+    fn set_focus_loss_behavior(
+        &mut self,
+        _minimize: Option<bool>,
+        _mute_audio: Option<bool>,
+        _release_cursor: Option<bool>,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+    fn screenshot(&mut self) -> anyhow::Result<u64> {
+        unsupported()
+    }
+    fn screenshot_texture(
+        &mut self,
+    ) -> anyhow::Result<Result<wit::client_texture::Handle, String>> {
+        unsupported()
+    }
 }
 
 impl wit::client_mesh::Host for Bindings {
@@ -94,6 +144,13 @@ impl wit::client_mesh::Host for Bindings {
     ) -> anyhow::Result<wit::client_mesh::Handle> {
         unsupported()
     }
+    fn update(
+        &mut self,
+        _handle: wit::client_mesh::Handle,
+        _desc: wit::client_mesh::Descriptor,
+    ) -> anyhow::Result<Result<(), String>> {
+        unsupported()
+    }
     fn destroy(&mut self, _handle: wit::client_mesh::Handle) -> anyhow::Result<()> {
         unsupported()
     }
@@ -109,6 +166,18 @@ impl wit::client_texture::Host for Bindings {
     fn destroy(&mut self, _handle: wit::client_texture::Handle) -> anyhow::Result<()> {
         unsupported()
     }
+    fn read(&mut self, _handle: wit::client_texture::Handle) -> anyhow::Result<u64> {
+        unsupported()
+    }
+    fn update2d(
+        &mut self,
+        _handle: wit::client_texture::Handle,
+        _origin: wit::types::Uvec2,
+        _size: wit::types::Uvec2,
+        _data: Vec<u8>,
+    ) -> anyhow::Result<Result<(), String>> {
+        unsupported()
+    }
 }
 
 impl wit::client_sampler::Host for Bindings {
@@ -130,7 +199,131 @@ impl wit::client_material::Host for Bindings {
     ) -> anyhow::Result<wit::client_material::Handle> {
         unsupported()
     }
+    fn create_custom(
+        &mut self,
+        _desc: wit::client_material::CustomDescriptor,
+    ) -> anyhow::Result<Result<wit::client_material::Handle, String>> {
+        unsupported()
+    }
     fn destroy(&mut self, _handle: wit::client_material::Handle) -> anyhow::Result<()> {
         unsupported()
     }
 }
+
+impl wit::client_buffer::Host for Bindings {
+    fn create(
+        &mut self,
+        _data: Vec<u8>,
+    ) -> anyhow::Result<Result<wit::client_buffer::Handle, String>> {
+        unsupported()
+    }
+    fn write(
+        &mut self,
+        _handle: wit::client_buffer::Handle,
+        _offset: u64,
+        _data: Vec<u8>,
+    ) -> anyhow::Result<Result<(), String>> {
+        unsupported()
+    }
+    fn destroy(&mut self, _handle: wit::client_buffer::Handle) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
+
+impl wit::client_compute::Host for Bindings {
+    fn create(
+        &mut self,
+        _desc: wit::client_compute::Descriptor,
+    ) -> anyhow::Result<Result<wit::client_compute::Handle, String>> {
+        unsupported()
+    }
+    fn dispatch(
+        &mut self,
+        _handle: wit::client_compute::Handle,
+        _bindings: Vec<wit::client_compute::Binding>,
+        _workgroups: wit::types::Uvec3,
+    ) -> anyhow::Result<Result<(), String>> {
+        unsupported()
+    }
+    fn destroy(&mut self, _handle: wit::client_compute::Handle) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
+
+impl wit::client_voice::Host for Bindings {
+    fn start_capture(&mut self) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn stop_capture(&mut self) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
+
+impl wit::client_microphone::Host for Bindings {
+    fn input_devices(&mut self) -> anyhow::Result<Vec<String>> {
+        unsupported()
+    }
+
+    fn start_capture(&mut self, _device: Option<String>) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn stop_capture(&mut self) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn is_allowed(&mut self) -> anyhow::Result<bool> {
+        unsupported()
+    }
+}
+
+impl wit::client_particles::Host for Bindings {
+    fn burst(&mut self, _emitter: wit::types::EntityId, _count: u32) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
+
+impl wit::client_ui_markup::Host for Bindings {
+    fn parse(
+        &mut self,
+        _source: String,
+    ) -> anyhow::Result<Result<Vec<wit::client_ui_markup::MarkupNode>, String>> {
+        unsupported()
+    }
+
+    fn load(&mut self, _url: String) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
+
+impl wit::client_notification::Host for Bindings {
+    fn notify(
+        &mut self,
+        _title: String,
+        _body: String,
+        _icon: Option<String>,
+        _duration: f32,
+        _severity: wit::client_notification::Severity,
+    ) -> anyhow::Result<u64> {
+        unsupported()
+    }
+
+    fn dismiss(&mut self, _id: u64) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
+
+impl wit::client_connection::Host for Bindings {
+    fn connect(&mut self, _address: String) -> anyhow::Result<Result<(), String>> {
+        unsupported()
+    }
+
+    fn is_connected(&mut self) -> anyhow::Result<bool> {
+        unsupported()
+    }
+
+    fn set_server_paused(&mut self, _paused: bool) -> anyhow::Result<Result<(), String>> {
+        unsupported()
+    }
+}
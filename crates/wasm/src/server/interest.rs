@@ -0,0 +1,17 @@
+//! Lets clients choose which of the messages sent to the interested clients they receive, by
+//! subscribing to entities and areas of the world.
+use ambient_ecs::{components, EntityId, World};
+
+use crate::shared::implementation::message::{MessageInterests, Topic};
+
+components!("wasm::server", {
+    /// The entities and areas that this player wants to receive the messages about
+    message_interests: MessageInterests,
+});
+
+/// Returns whether the player `id` wants to receive the messages about `topic`.
+pub(crate) fn is_interested(world: &World, id: EntityId, topic: Topic) -> bool {
+    world
+        .get_ref(id, message_interests())
+        .map_or(false, |interests| interests.matches(world, topic))
+}
@@ -2,15 +2,18 @@ use crate::shared::{self, message::MessageExt};
 use ambient_ecs::{generated::messages, query, EntityId, FnSystem, SystemGroup, World};
 use ambient_native_std::asset_cache::AssetCache;
 use ambient_network::server::{ForkingEvent, ShutdownEvent};
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 mod implementation;
+pub mod interest;
 mod network;
+pub mod storage;
 
 pub fn initialize(
     world: &mut World,
     assets: &AssetCache,
     hosted: bool,
+    analytics_opt_out: bool,
     data_path: PathBuf,
     messenger: Arc<dyn Fn(&World, EntityId, shared::MessageType, &str) + Send + Sync>,
 ) -> anyhow::Result<()> {
@@ -22,8 +25,9 @@ pub fn initialize(
             base: Default::default(),
             world_ref: Default::default(),
             id,
-            reqwest_client: reqwest::Client::new(),
             last_http_request_id: 0,
+            last_websocket_id: 0,
+            websockets: HashMap::new(),
             hosted,
         }),
         if hosted {
@@ -34,6 +38,7 @@ pub fn initialize(
     )?;
 
     network::initialize(world);
+    shared::analytics::initialize(world, analytics_opt_out);
 
     Ok(())
 }
@@ -71,6 +76,36 @@ pub fn systems() -> SystemGroup {
                     .run(world, None)
                     .unwrap();
             })),
+            Box::new(FnSystem::new(move |world, _| {
+                profiling::scope!("WASM module joint breaks");
+                // trigger joint breaks
+                let joint_breaks = match world.resource_opt(ambient_physics::joint_breaks()) {
+                    Some(joint_breaks) => joint_breaks.clone(),
+                    None => return,
+                };
+                for joint in joint_breaks {
+                    messages::JointBreak::new(joint).run(world, None).unwrap();
+                }
+            })),
+            Box::new(FnSystem::new(move |world, _| {
+                profiling::scope!("WASM module trigger events");
+                // trigger trigger enters and exits
+                let trigger_events = match world.resource_opt(ambient_physics::trigger_events()) {
+                    Some(trigger_events) => trigger_events.lock().clone(),
+                    None => return,
+                };
+                for event in trigger_events {
+                    if event.entered {
+                        messages::TriggerEnter::new(event.trigger, event.other)
+                            .run(world, None)
+                            .unwrap();
+                    } else {
+                        messages::TriggerExit::new(event.trigger, event.other)
+                            .run(world, None)
+                            .unwrap();
+                    }
+                }
+            })),
             Box::new(shared::systems()),
         ],
     )
@@ -80,8 +115,8 @@ pub fn on_forking_systems() -> SystemGroup<ForkingEvent> {
     SystemGroup::new(
         "core/wasm/server/on_forking_systems",
         vec![Box::new(FnSystem::new(move |world, _| {
-            // Reset the states of all the modules when we fork.
-            shared::reload_all(world);
+            // Restart all the modules in the new world, without affecting the original world.
+            shared::restart_all_in_copy(world);
         }))],
     )
 }
@@ -103,8 +138,11 @@ struct Bindings {
     base: shared::bindings::BindingsBase,
     world_ref: shared::bindings::WorldRef,
     id: EntityId,
-    reqwest_client: reqwest::Client,
     last_http_request_id: u64,
+    last_websocket_id: u64,
+    /// The outgoing messages of the open WebSockets of this module. Dropping a sender closes its
+    /// WebSocket.
+    websockets: HashMap<u64, flume::Sender<tokio_tungstenite::tungstenite::Message>>,
     /// Whether or not this server is running in a hosted environment,
     /// and should thus have some of its functionality disabled
     hosted: bool,
@@ -1,11 +1,14 @@
-use ambient_core::{async_ecs::async_run, runtime};
+use ambient_core::{async_ecs::async_run, player::get_by_user_id, runtime};
 use ambient_ecs::World;
 use ambient_native_std::asset_cache::AssetCache;
 use ambient_network::{
     log_network_result,
-    server::{bi_stream_handlers, datagram_handlers, uni_stream_handlers, SharedServerState},
-    unwrap_log_network_err, DynRecv, DynSend, WASM_BISTREAM_ID, WASM_DATAGRAM_ID,
-    WASM_UNISTREAM_ID,
+    server::{
+        bi_stream_handlers, datagram_handlers, is_spectator_connection, uni_stream_handlers,
+        SharedServerState,
+    },
+    unwrap_log_network_err, DynRecv, DynSend, MESSAGE_INTERESTS_UNISTREAM_ID, WASM_BISTREAM_ID,
+    WASM_DATAGRAM_ID, WASM_UNISTREAM_ID,
 };
 
 use anyhow::Context;
@@ -13,7 +16,10 @@ use bytes::Bytes;
 
 use std::sync::Arc;
 
-use crate::shared::implementation::message::{self, process_network_message, read_unistream};
+use super::interest::message_interests;
+use crate::shared::implementation::message::{
+    self, process_network_message, read_interests, read_unistream,
+};
 
 pub fn initialize(world: &mut World) {
     world.resource_mut(datagram_handlers()).insert(
@@ -30,6 +36,11 @@ pub fn initialize(world: &mut World) {
         WASM_UNISTREAM_ID,
         ("server_wasm_uni_stream", Arc::new(on_unistream)),
     );
+
+    world.resource_mut(uni_stream_handlers()).insert(
+        MESSAGE_INTERESTS_UNISTREAM_ID,
+        ("server_message_interests", Arc::new(on_interests_unistream)),
+    );
 }
 
 #[allow(clippy::ptr_arg)]
@@ -39,6 +50,10 @@ fn on_datagram(state: SharedServerState, _asset_cache: AssetCache, user_id: &str
         tracing::warn!("Failed to find player world for {user_id} when processing datagram");
         return;
     };
+    if is_spectator_connection(world, user_id) {
+        tracing::debug!("Ignoring datagram from spectator {user_id}");
+        return;
+    }
 
     log_network_result!(message::on_datagram(world, Some(user_id.to_owned()), bytes));
 }
@@ -77,6 +92,12 @@ fn on_unistream(
                 .context("Failed to read unistream"));
 
         async_run.run(move |world| {
+            // Spectators cannot send messages; checked here, as they may have been promoted in the meantime
+            if is_spectator_connection(world, &user_id) {
+                tracing::debug!("Ignoring message {name:?} from spectator {user_id}");
+                return;
+            }
+
             log_network_result!(process_network_message(
                 world,
                 Some(user_id),
@@ -87,3 +108,35 @@ fn on_unistream(
         });
     });
 }
+
+/// Replaces the message interests of the player with those sent by their client.
+#[allow(clippy::ptr_arg)]
+fn on_interests_unistream(
+    state: SharedServerState,
+    _asset_cache: AssetCache,
+    user_id: &str,
+    mut recv_stream: DynRecv,
+) {
+    let mut state = state.lock();
+    let Some(world) = state.get_player_world_mut(user_id) else {
+        tracing::warn!("Failed to find player world for {user_id} when processing interests");
+        return;
+    };
+
+    let async_run = world.resource(async_run()).clone();
+    let user_id = user_id.to_owned();
+    world.resource(runtime()).spawn(async move {
+        let interests = unwrap_log_network_err!(read_interests(recv_stream.as_mut())
+            .await
+            .context("Failed to read message interests"));
+
+        async_run.run(move |world| {
+            // The player may have left in the meantime
+            if let Some(player_id) = get_by_user_id(world, &user_id) {
+                world
+                    .add_component(player_id, message_interests(), interests)
+                    .ok();
+            }
+        });
+    });
+}
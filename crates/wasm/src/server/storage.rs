@@ -0,0 +1,166 @@
+//! Persistent key-value storage for server modules.
+//!
+//! Each package has its own store, which is saved to `<package id>.json` in the storage directory
+//! of the server. The stores are loaded when first used, and saved after every change, so that
+//! they outlive the server.
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use ambient_ecs::{components, Resource};
+use anyhow::Context;
+use data_encoding::BASE64;
+use parking_lot::Mutex;
+
+components!("wasm::server", {
+    /// The persistent storage of the packages of this server, shared between its world
+    /// instances
+    @[Resource]
+    storage: SharedStorage,
+});
+
+/// The [Storage] of a server, shared between all of its world instances.
+pub type SharedStorage = Arc<Mutex<Storage>>;
+
+/// The key-value stores of the packages of a server.
+#[derive(Debug)]
+pub struct Storage {
+    directory: PathBuf,
+    /// The loaded stores, by package ID
+    stores: HashMap<String, BTreeMap<String, Vec<u8>>>,
+}
+impl Storage {
+    /// Creates the storage for a server that keeps its stores in `directory`, which is created
+    /// when something is first stored.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            stores: HashMap::new(),
+        }
+    }
+
+    /// Returns the value stored under `key` by the given package, if any.
+    pub fn get(&mut self, package_id: &str, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self.store(package_id)?.get(key).cloned())
+    }
+
+    /// Stores `value` under `key` for the given package, replacing any previous value.
+    pub fn set(&mut self, package_id: &str, key: String, value: Vec<u8>) -> anyhow::Result<()> {
+        self.store(package_id)?.insert(key, value);
+        self.save(package_id)
+    }
+
+    /// Removes the value stored under `key` by the given package. Returns `false` if there was
+    /// none.
+    pub fn delete(&mut self, package_id: &str, key: &str) -> anyhow::Result<bool> {
+        let existed = self.store(package_id)?.remove(key).is_some();
+        if existed {
+            self.save(package_id)?;
+        }
+        Ok(existed)
+    }
+
+    /// Returns the keys stored by the given package that start with `prefix`, in order.
+    pub fn list(&mut self, package_id: &str, prefix: &str) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .store(package_id)?
+            .range(prefix.to_string()..)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+
+    fn path(&self, package_id: &str) -> PathBuf {
+        self.directory.join(format!("{package_id}.json"))
+    }
+
+    fn store(&mut self, package_id: &str) -> anyhow::Result<&mut BTreeMap<String, Vec<u8>>> {
+        if !self.stores.contains_key(package_id) {
+            let store = load(&self.path(package_id))?;
+            self.stores.insert(package_id.to_string(), store);
+        }
+        Ok(self.stores.get_mut(package_id).unwrap())
+    }
+
+    fn save(&self, package_id: &str) -> anyhow::Result<()> {
+        let path = self.path(package_id);
+        let encoded: BTreeMap<&str, String> = self.stores[package_id]
+            .iter()
+            .map(|(key, value)| (key.as_str(), BASE64.encode(value)))
+            .collect();
+        let contents = serde_json::to_string_pretty(&encoded)?;
+
+        // Write to a temporary file first, so that the store is not lost if the server stops
+        // while it is being written
+        std::fs::create_dir_all(&self.directory).with_context(|| {
+            format!(
+                "Failed to create the storage directory {:?}",
+                self.directory
+            )
+        })?;
+        let temporary_path = path.with_extension("json.tmp");
+        std::fs::write(&temporary_path, contents)
+            .and_then(|_| std::fs::rename(&temporary_path, &path))
+            .with_context(|| format!("Failed to write the store at {path:?}"))
+    }
+}
+
+fn load(path: &Path) -> anyhow::Result<BTreeMap<String, Vec<u8>>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => {
+            return Err(err).with_context(|| format!("Failed to read the store at {path:?}"))
+        }
+    };
+    let encoded: BTreeMap<String, String> = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse the store at {path:?}"))?;
+    encoded
+        .into_iter()
+        .map(|(key, value)| {
+            let value = BASE64
+                .decode(value.as_bytes())
+                .with_context(|| format!("Invalid value for {key:?} in the store at {path:?}"))?;
+            Ok((key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_saved_per_package() {
+        let directory = std::env::temp_dir().join(format!("ambient_storage_{}", ulid::Ulid::new()));
+
+        let mut storage = Storage::new(&directory);
+        storage
+            .set("game", "save/1".into(), b"first".to_vec())
+            .unwrap();
+        storage
+            .set("game", "save/2".into(), b"second".to_vec())
+            .unwrap();
+        storage
+            .set("game", "settings".into(), vec![0, 255])
+            .unwrap();
+        storage.set("other", "save/3".into(), vec![]).unwrap();
+        assert!(storage.delete("game", "save/2").unwrap());
+        assert!(!storage.delete("game", "save/2").unwrap());
+
+        let mut loaded = Storage::new(&directory);
+        assert_eq!(
+            loaded.get("game", "save/1").unwrap(),
+            Some(b"first".to_vec())
+        );
+        assert_eq!(loaded.get("game", "settings").unwrap(), Some(vec![0, 255]));
+        assert_eq!(loaded.get("other", "save/1").unwrap(), None);
+        assert_eq!(loaded.list("game", "save/").unwrap(), vec!["save/1"]);
+        assert_eq!(loaded.list("other", "").unwrap(), vec!["save/3"]);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
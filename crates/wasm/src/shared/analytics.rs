@@ -0,0 +1,246 @@
+//! Batching and delivery of analytics events submitted by guest modules.
+//!
+//! Events are queued in [`AnalyticsState`] and shipped as JSON to the HTTPS endpoint
+//! declared in the main package's `[analytics]` manifest section, either once enough
+//! events have accumulated or once the flush interval has elapsed.
+use std::{collections::BTreeMap, time::Duration};
+
+use ambient_core::{player::local_user_id, runtime};
+use ambient_ecs::{
+    components,
+    generated::package::components::{analytics_endpoint, main_package_id},
+    FnSystem, Resource, SystemGroup, World, WorldContext,
+};
+use ambient_native_std::ambient_version;
+use ambient_sys::time::{Instant, SystemTime};
+use serde::Serialize;
+
+components!("wasm::shared::analytics", {
+    @[Resource]
+    analytics_state: AnalyticsState,
+});
+
+/// The number of queued events that will trigger an immediate flush.
+pub const MAX_BATCH_SIZE: usize = 50;
+/// The maximum amount of time an event will be queued before being sent.
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+/// The maximum number of events that will be kept while the main package is loading.
+const MAX_QUEUED_EVENTS: usize = 1000;
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct AnalyticsEvent {
+    pub name: String,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package_id: Option<String>,
+    pub properties: BTreeMap<String, String>,
+}
+impl AnalyticsEvent {
+    pub fn new(
+        name: String,
+        properties: Vec<(String, String)>,
+        package_id: Option<String>,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        Self {
+            name,
+            timestamp,
+            package_id,
+            properties: properties.into_iter().collect(),
+        }
+    }
+}
+
+/// Metadata attached to every batch sent from this session.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SessionMetadata {
+    pub session_id: String,
+    pub side: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub ambient_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct AnalyticsBatch<'a> {
+    session: &'a SessionMetadata,
+    events: Vec<AnalyticsEvent>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AnalyticsState {
+    opted_out: bool,
+    session: SessionMetadata,
+    pending: Vec<AnalyticsEvent>,
+    last_flush: Instant,
+    client: reqwest::Client,
+}
+impl AnalyticsState {
+    pub fn new(context: WorldContext, opted_out: bool) -> Self {
+        Self {
+            opted_out,
+            session: SessionMetadata {
+                session_id: ulid::Ulid::new().to_string(),
+                side: match context {
+                    WorldContext::Server => "server",
+                    _ => "client",
+                },
+                os: std::env::consts::OS,
+                arch: std::env::consts::ARCH,
+                ambient_version: ambient_version().to_string(),
+                user_id: None,
+            },
+            pending: vec![],
+            last_flush: Instant::now(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn opted_out(&self) -> bool {
+        self.opted_out
+    }
+
+    /// Queues an event for delivery. Events are discarded if the user has opted out.
+    pub fn push(&mut self, event: AnalyticsEvent) {
+        if self.opted_out {
+            return;
+        }
+
+        if self.pending.len() >= MAX_QUEUED_EVENTS {
+            self.pending.remove(0);
+        }
+        self.pending.push(event);
+    }
+
+    /// Returns the events that should be sent now, if any.
+    fn take_batch(&mut self, now: Instant) -> Option<Vec<AnalyticsEvent>> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        if self.pending.len() < MAX_BATCH_SIZE && now - self.last_flush < FLUSH_INTERVAL {
+            return None;
+        }
+
+        self.last_flush = now;
+        let count = self.pending.len().min(MAX_BATCH_SIZE);
+        Some(self.pending.drain(..count).collect())
+    }
+}
+
+/// Only HTTPS endpoints are accepted, as events may contain user-identifying metadata.
+pub fn is_valid_endpoint(endpoint: &str) -> bool {
+    reqwest::Url::parse(endpoint).is_ok_and(|url| url.scheme() == "https")
+}
+
+pub fn initialize(world: &mut World, opted_out: bool) {
+    let context = world.context();
+    world.add_resource(analytics_state(), AnalyticsState::new(context, opted_out));
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "core/wasm/analytics",
+        vec![Box::new(FnSystem::new(move |world, _| {
+            profiling::scope!("WASM analytics flush");
+            // Events are kept until the main package has loaded, as its manifest declares where
+            // they are sent.
+            let Some(package_id) = world.resource_opt(main_package_id()).copied() else {
+                return;
+            };
+            let endpoint = world.get_cloned(package_id, analytics_endpoint()).ok();
+            let user_id = world.resource_opt(local_user_id()).cloned();
+
+            let Some(state) = world.resource_mut_opt(analytics_state()) else {
+                return;
+            };
+            let Some(endpoint) = endpoint.filter(|_| !state.opted_out) else {
+                state.pending.clear();
+                return;
+            };
+            if !is_valid_endpoint(&endpoint) {
+                if !state.pending.is_empty() {
+                    tracing::warn!(%endpoint, "Analytics endpoint must be a valid HTTPS URL; discarding events");
+                    state.pending.clear();
+                }
+                return;
+            }
+            state.session.user_id = user_id;
+
+            let Some(events) = state.take_batch(Instant::now()) else {
+                return;
+            };
+            let body = match serde_json::to_vec(&AnalyticsBatch {
+                session: &state.session,
+                events,
+            }) {
+                Ok(body) => body,
+                Err(err) => {
+                    tracing::warn!("Failed to serialize analytics batch: {err:?}");
+                    return;
+                }
+            };
+
+            let request = state
+                .client
+                .post(endpoint)
+                .header("Content-Type", "application/json")
+                .body(body);
+            world.resource(runtime()).spawn(async move {
+                match request.send().await.and_then(|r| r.error_for_status()) {
+                    Ok(_) => {}
+                    Err(err) => tracing::warn!("Failed to send analytics batch: {err:?}"),
+                }
+            });
+        }))],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(name: &str) -> AnalyticsEvent {
+        AnalyticsEvent::new(name.to_string(), vec![], None)
+    }
+
+    #[test]
+    fn batches_by_size_and_interval() {
+        let mut state = AnalyticsState::new(WorldContext::Server, false);
+        let start = state.last_flush;
+
+        state.push(event("a"));
+        assert_eq!(state.take_batch(start), None);
+
+        let batch = state.take_batch(start + FLUSH_INTERVAL).unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].name, "a");
+
+        for _ in 0..MAX_BATCH_SIZE + 1 {
+            state.push(event("b"));
+        }
+        let now = state.last_flush;
+        assert_eq!(state.take_batch(now).unwrap().len(), MAX_BATCH_SIZE);
+        assert_eq!(state.pending.len(), 1);
+    }
+
+    #[test]
+    fn opted_out_discards_events() {
+        let mut state = AnalyticsState::new(WorldContext::Client, true);
+        state.push(event("a"));
+        assert!(state.pending.is_empty());
+    }
+
+    #[test]
+    fn only_https_endpoints_are_valid() {
+        assert!(is_valid_endpoint("https://example.com/events"));
+        assert!(!is_valid_endpoint("http://example.com/events"));
+        assert!(!is_valid_endpoint("not a url"));
+    }
+}
@@ -2,14 +2,24 @@ use std::collections::HashSet;
 
 use ambient_ecs::{EntityId, PrimitiveComponent, Query, QueryState, World};
 
-pub type QueryStateMap =
-    slotmap::SlotMap<slotmap::DefaultKey, (Query, QueryState, Vec<PrimitiveComponent>)>;
+use super::implementation::snapshot::{DeferredCommand, Snapshot};
+
+/// The queries of a module, with their state, the components they return, and the tags the
+/// entities must have.
+pub type QueryStateMap = slotmap::SlotMap<
+    slotmap::DefaultKey,
+    (Query, QueryState, Vec<PrimitiveComponent>, Vec<String>),
+>;
 
 #[derive(Clone, Default)]
 pub struct BindingsBase {
     pub spawned_entities: HashSet<EntityId>,
     pub subscribed_messages: HashSet<String>,
     pub query_states: QueryStateMap,
+    /// What the module has changed while handling the current message
+    pub snapshot: Snapshot,
+    /// The changes the module has deferred until it has handled the current message
+    pub deferred: Vec<DeferredCommand>,
 }
 
 /// Represents all the bindings for the imported world
@@ -24,6 +34,7 @@ pub trait BindingsBound:
     + super::wit::message::Host
     + super::wit::player::Host
     + super::wit::ambient_package::Host
+    + super::wit::analytics::Host
     // Client
     + super::wit::client_message::Host
     + super::wit::client_player::Host
@@ -35,11 +46,22 @@ pub trait BindingsBound:
     + super::wit::client_texture::Host
     + super::wit::client_sampler::Host
     + super::wit::client_material::Host
+    + super::wit::client_voice::Host
+    + super::wit::client_microphone::Host
+    + super::wit::client_particles::Host
+    + super::wit::client_ui_markup::Host
+    + super::wit::client_notification::Host
+    + super::wit::client_connection::Host
     // Server
     + super::wit::server_asset::Host
     + super::wit::server_message::Host
     + super::wit::server_physics::Host
+    + super::wit::server_navigation::Host
     + super::wit::server_http::Host
+    + super::wit::server_websocket::Host
+    + super::wit::server_player::Host
+    + super::wit::server_instance::Host
+    + super::wit::server_storage::Host
     + super::wit::server_ambient_package::Host
     + Clone
     + Sync
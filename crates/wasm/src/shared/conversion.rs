@@ -3,8 +3,9 @@ use std::time::Duration;
 use ambient_ecs::EntityId;
 use ambient_native_std::shapes::Ray;
 use ambient_shared_types::{
-    procedural_storage_handle_definitions, ProceduralMaterialHandle, ProceduralMeshHandle,
-    ProceduralSamplerHandle, ProceduralTextureHandle,
+    procedural_storage_handle_definitions, ProceduralBufferHandle, ProceduralComputeHandle,
+    ProceduralMaterialHandle, ProceduralMeshHandle, ProceduralSamplerHandle,
+    ProceduralTextureHandle,
 };
 use glam::{IVec2, IVec3, IVec4, Mat4, Quat, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
 use paste::paste;
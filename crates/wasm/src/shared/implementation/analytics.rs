@@ -0,0 +1,21 @@
+use ambient_ecs::{generated::package::components as package, EntityId, World};
+
+use crate::shared::analytics::{analytics_state, AnalyticsEvent};
+
+pub fn track(
+    world: &mut World,
+    module_id: EntityId,
+    event: String,
+    properties: Vec<(String, String)>,
+) -> anyhow::Result<()> {
+    let package_id = world
+        .get(module_id, crate::shared::package_ref())
+        .ok()
+        .and_then(|package| world.get_cloned(package, package::id()).ok());
+
+    if let Some(state) = world.resource_mut_opt(analytics_state()) {
+        state.push(AnalyticsEvent::new(event, properties, package_id));
+    }
+
+    Ok(())
+}
@@ -1,8 +1,9 @@
 use std::path::Path;
 
-use ambient_core::asset_cache;
-use ambient_ecs::World;
+use ambient_core::{asset_cache, locale};
+use ambient_ecs::{generated::package::components::localized_assets, EntityId, World};
 use ambient_native_std::asset_url::ParseError;
+use ambient_package::localization;
 
 use crate::shared::{conversion::FromBindgen, wit};
 
@@ -13,12 +14,11 @@ pub(crate) fn url(
     resolve: bool,
 ) -> anyhow::Result<Result<String, wit::asset::UrlError>> {
     let assets = world.resource(asset_cache()).clone();
+    let package_id = package_id.from_bindgen();
+    let path = localized_path(world, package_id, format!("assets/{path}"));
 
-    let asset_url = ambient_package_semantic_native::file_path(
-        world,
-        package_id.from_bindgen(),
-        &Path::new("assets").join(path),
-    )?;
+    let asset_url =
+        ambient_package_semantic_native::file_path(world, package_id, Path::new(&path))?;
 
     ok_wrap(move || {
         Ok(if resolve {
@@ -32,6 +32,19 @@ pub(crate) fn url(
     })
 }
 
+/// Substitutes the variant of `path` for the active locale, if the package has one.
+fn localized_path(world: &World, package_id: EntityId, path: String) -> String {
+    let (Some(locale), Ok(variants)) = (
+        world.resource_opt(locale()),
+        world.get_ref(package_id, localized_assets()),
+    ) else {
+        return path;
+    };
+
+    localization::resolve_variant(&path, locale, |p| variants.iter().any(|v| v == p))
+        .unwrap_or(path)
+}
+
 fn ok_wrap<R>(mut f: impl FnMut() -> R) -> anyhow::Result<R> {
     Ok(f())
 }
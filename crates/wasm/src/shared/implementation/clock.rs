@@ -0,0 +1,14 @@
+//! The wall clock of the host, which is read when called, and is independent of the game time.
+use ambient_sys::time::SystemTime;
+
+use super::super::{conversion::IntoBindgen, wit};
+
+pub fn now() -> anyhow::Result<wit::types::Duration> {
+    Ok(SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)?
+        .into_bindgen())
+}
+
+pub fn local_utc_offset() -> anyhow::Result<i32> {
+    Ok(ambient_sys::time::local_utc_offset())
+}
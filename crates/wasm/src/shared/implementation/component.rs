@@ -1,7 +1,8 @@
+use ambient_core::tags;
 use ambient_ecs::{
-    with_component_registry, Component, ComponentEntry, ComponentSet, ComponentValue, Entity,
-    EntityAccessor, EntityId, Enum, PrimitiveComponent, PrimitiveComponentType as PCT, QueryEvent,
-    QueryState, World,
+    with_component_registry, Component, ComponentDesc, ComponentEntry, ComponentSet,
+    ComponentValue, Entity, EntityAccessor, EntityId, Enum, PrimitiveComponent,
+    PrimitiveComponentType as PCT, QueryEvent, QueryState, Resource, World,
 };
 use ambient_shared_types::primitive_component_definitions;
 use ambient_shared_types::{
@@ -17,9 +18,12 @@ use std::time::Duration;
 
 use crate::shared::bindings::QueryStateMap;
 
-use super::super::{
-    conversion::{FromBindgen, IntoBindgen},
-    wit,
+use super::{
+    super::{
+        conversion::{FromBindgen, IntoBindgen},
+        wit,
+    },
+    snapshot::Snapshot,
 };
 
 pub fn get_index(id: String) -> anyhow::Result<Option<u32>> {
@@ -134,10 +138,12 @@ fn enum_value_to_entry(index: u32, value: &wit::component::Value) -> Option<Comp
 
 pub(crate) fn add_component(
     world: &mut World,
+    snapshot: &mut Snapshot,
     id: wit::entity::EntityId,
     index: u32,
     value: wit::component::Value,
 ) -> anyhow::Result<()> {
+    snapshot.record_component(world, id.from_bindgen(), index);
     if let Some(entry) = enum_value_to_entry(index, &value) {
         world.add_entry(id.from_bindgen(), entry)?;
         return Ok(());
@@ -160,10 +166,12 @@ pub(crate) fn add_component(
 
 pub(crate) fn set_component(
     world: &mut World,
+    snapshot: &mut Snapshot,
     id: wit::entity::EntityId,
     index: u32,
     value: wit::component::Value,
 ) -> anyhow::Result<()> {
+    snapshot.record_component(world, id.from_bindgen(), index);
     if let Some(entry) = enum_value_to_entry(index, &value) {
         world.set_entry(id.from_bindgen(), entry)?;
         return Ok(());
@@ -186,17 +194,25 @@ pub(crate) fn set_component(
 
 pub(crate) fn set_components(
     world: &mut World,
+    snapshot: &mut Snapshot,
     id: wit::entity::EntityId,
     data: wit::entity::EntityData,
 ) -> anyhow::Result<()> {
+    for (index, _) in &data {
+        snapshot.record_component(world, id.from_bindgen(), *index);
+    }
     Ok(world.set_components(id.from_bindgen(), wit_entity_to_host_entity(data)?)?)
 }
 
 pub(crate) fn add_components(
     world: &mut World,
+    snapshot: &mut Snapshot,
     id: wit::entity::EntityId,
     data: wit::entity::EntityData,
 ) -> anyhow::Result<()> {
+    for (index, _) in &data {
+        snapshot.record_component(world, id.from_bindgen(), *index);
+    }
     Ok(world.add_components(id.from_bindgen(), wit_entity_to_host_entity(data)?)?)
 }
 
@@ -351,9 +367,11 @@ pub fn has_components(
 
 pub fn remove_component(
     world: &mut World,
+    snapshot: &mut Snapshot,
     entity_id: wit::types::EntityId,
     index: u32,
 ) -> anyhow::Result<()> {
+    snapshot.record_component(world, entity_id.from_bindgen(), index);
     let desc =
         with_component_registry(|cr| cr.get_by_index(index)).context("no component for index")?;
 
@@ -362,9 +380,13 @@ pub fn remove_component(
 
 pub fn remove_components(
     world: &mut World,
+    snapshot: &mut Snapshot,
     entity_id: wit::types::EntityId,
     components: Vec<u32>,
 ) -> anyhow::Result<()> {
+    for index in &components {
+        snapshot.record_component(world, entity_id.from_bindgen(), *index);
+    }
     let components = with_component_registry(|cr| {
         components
             .into_iter()
@@ -374,6 +396,70 @@ pub fn remove_components(
     Ok(world.remove_components(entity_id.from_bindgen(), components)?)
 }
 
+/// Applies all of the `writes` and `removals`, or none of them if any of them would fail, so that
+/// queries and replication never see some of them without the others.
+pub fn apply_transaction(
+    world: &mut World,
+    snapshot: &mut Snapshot,
+    writes: Vec<(wit::types::EntityId, wit::entity::EntityData)>,
+    removals: Vec<(wit::types::EntityId, Vec<u32>)>,
+) -> anyhow::Result<Result<(), String>> {
+    let writes = writes
+        .into_iter()
+        .map(|(id, data)| Ok((id.from_bindgen(), wit_entity_to_host_entity(data)?)))
+        .collect::<anyhow::Result<Vec<(EntityId, Entity)>>>()?;
+    let removals: Vec<(EntityId, Vec<ComponentDesc>)> = with_component_registry(|cr| {
+        removals
+            .into_iter()
+            .map(|(id, indices)| {
+                let components = indices
+                    .into_iter()
+                    .flat_map(|idx| cr.get_by_index(idx))
+                    .collect();
+                (id.from_bindgen(), components)
+            })
+            .collect()
+    });
+
+    // Check everything before changing anything
+    let ids = writes.iter().map(|(id, _)| *id);
+    for id in ids.chain(removals.iter().map(|(id, _)| *id)) {
+        if !world.exists(id) {
+            return Ok(Err(format!("The entity {id} does not exist")));
+        }
+    }
+    for (id, data) in &writes {
+        if *id == world.resource_entity() {
+            continue;
+        }
+        if let Some(entry) = data.iter().find(|entry| entry.has_attribute::<Resource>()) {
+            return Ok(Err(format!(
+                "The resource {} can't be added to the entity {id}",
+                entry.path()
+            )));
+        }
+    }
+
+    for (id, data) in &writes {
+        for entry in data.iter() {
+            snapshot.record_component(world, *id, entry.index());
+        }
+    }
+    for (id, components) in &removals {
+        for desc in components {
+            snapshot.record_component(world, *id, desc.index());
+        }
+    }
+
+    for (id, data) in writes {
+        world.add_components(id, data)?;
+    }
+    for (id, components) in removals {
+        world.remove_components(id, components)?;
+    }
+    Ok(Ok(()))
+}
+
 pub fn query(
     query_states: &mut QueryStateMap,
     query: wit::component::QueryBuild,
@@ -393,6 +479,7 @@ pub fn query(
             .collect()
     }
 
+    let query_tags = query.tags;
     let (components, include, exclude, changed) = with_component_registry(|cr| {
         anyhow::Ok((
             get_components(cr, &query.components)?,
@@ -420,9 +507,12 @@ pub fn query(
     for component in changed {
         query = query.optional_changed_ref(component.as_component());
     }
+    if !query_tags.is_empty() {
+        query = query.incl(tags());
+    }
 
     Ok(query_states
-        .insert((query, QueryState::new(), components))
+        .insert((query, QueryState::new(), components, query_tags))
         .data()
         .as_ffi())
 }
@@ -434,12 +524,18 @@ pub fn query_eval(
 ) -> anyhow::Result<Vec<(wit::types::EntityId, Vec<wit::component::Value>)>> {
     let key = slotmap::DefaultKey::from(slotmap::KeyData::from_ffi(query_index));
 
-    let (query, query_state, primitive_components) =
+    let (query, query_state, primitive_components, query_tags) =
         query_states.get(key).context("no query state for key")?;
 
     let mut query_state = query_state.clone();
     let result = query
         .iter(world, Some(&mut query_state))
+        .filter(|ea| {
+            query_tags.is_empty()
+                || ea
+                    .get_optional(world, tags())
+                    .is_some_and(|tags| query_tags.iter().all(|tag| tags.contains(tag)))
+        })
         .map(|ea| {
             (
                 ea.id().into_bindgen(),
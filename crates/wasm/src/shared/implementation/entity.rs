@@ -1,6 +1,9 @@
 use std::collections::HashSet;
 
-use ambient_core::transform::{local_to_world, translation};
+use ambient_core::{
+    stable_id, stable_ids, tag_index,
+    transform::{local_to_world, translation},
+};
 use ambient_ecs::{query as ecs_query, with_component_registry, EntityId, World};
 
 use ambient_network::ServerWorldExt;
@@ -14,25 +17,30 @@ use super::{
         wit,
     },
     component::{host_entity_to_wit_entity, wit_entity_to_host_entity},
+    snapshot::Snapshot,
 };
 
 pub fn spawn(
     world: &mut World,
     spawned_entities: &mut HashSet<EntityId>,
+    snapshot: &mut Snapshot,
     data: wit::entity::EntityData,
 ) -> anyhow::Result<wit::types::EntityId> {
     let id = wit_entity_to_host_entity(data)?.spawn(world);
     spawned_entities.insert(id);
+    snapshot.record_spawn(id);
     Ok(id.into_bindgen())
 }
 
 pub fn despawn(
     world: &mut World,
     spawned_entities: &mut HashSet<EntityId>,
+    snapshot: &mut Snapshot,
     id: wit::types::EntityId,
 ) -> anyhow::Result<Option<wit::entity::EntityData>> {
     let id = id.from_bindgen();
     spawned_entities.remove(&id);
+    snapshot.record_entity(world, id);
     world.despawn(id).map(host_entity_to_wit_entity).transpose()
 }
 
@@ -97,6 +105,48 @@ pub fn in_area(
         .collect())
 }
 
+pub fn in_area_tagged(
+    world: &mut World,
+    centre: wit::types::Vec3,
+    radius: f32,
+    tag: String,
+) -> anyhow::Result<Vec<wit::types::EntityId>> {
+    let centre = centre.from_bindgen();
+    Ok(tag_index::tagged(world, &tag)
+        .into_iter()
+        .filter(|&id| {
+            world
+                .get(id, translation())
+                .is_ok_and(|pos| (pos - centre).length() < radius)
+        })
+        .map(|id| id.into_bindgen())
+        .collect())
+}
+
+pub fn get_all_tagged(world: &mut World, tag: String) -> anyhow::Result<Vec<wit::types::EntityId>> {
+    Ok(tag_index::tagged(world, &tag)
+        .into_iter()
+        .map(|id| id.into_bindgen())
+        .collect())
+}
+
+pub fn get_stable_id(
+    world: &mut World,
+    snapshot: &mut Snapshot,
+    entity: wit::types::EntityId,
+) -> anyhow::Result<Option<String>> {
+    let id = entity.from_bindgen();
+    snapshot.record_component(world, id, stable_id().index());
+    Ok(stable_ids::get_or_assign(world, id))
+}
+
+pub fn get_by_stable_id(
+    world: &mut World,
+    stable_id: String,
+) -> anyhow::Result<Option<wit::types::EntityId>> {
+    Ok(stable_ids::get_by_stable_id(world, &stable_id).into_bindgen())
+}
+
 pub fn get_all(world: &mut World, index: u32) -> anyhow::Result<Vec<wit::types::EntityId>> {
     let desc = match with_component_registry(|r| r.get_by_index(index)) {
         Some(c) => c,
@@ -1,23 +1,82 @@
-use ambient_core::runtime;
+use ambient_core::{runtime, transform::translation};
 use ambient_ecs::{generated::wasm::components::package_ref, EntityId, World};
 use ambient_network::{
-    client::NetworkTransport, log_network_result, WASM_DATAGRAM_ID, WASM_UNISTREAM_ID,
+    client::NetworkTransport, log_network_result, MESSAGE_INTERESTS_UNISTREAM_ID, WASM_DATAGRAM_ID,
+    WASM_UNISTREAM_ID,
 };
+use ambient_sys::time::Instant;
 
 use anyhow::Context;
 use bytes::{Buf, BufMut, Bytes, BytesMut};
+use glam::Vec3;
 
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     io::{Cursor, Read},
     pin::Pin,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 
-use crate::shared::message::Target;
+use crate::shared::message::{datagram_reassembler, Target};
 
+/// The largest data of a reliable message.
 pub const MAX_STREAM_LENGTH: usize = 10 * 1024 * 1024;
 
+/// The largest unreliable message, including the header with the package and the name of the
+/// message. Larger unreliable messages are split into several datagrams, and are lost if any of
+/// them is lost, so they are kept small.
+pub const MAX_DATAGRAM_MESSAGE_LENGTH: usize = 64 * 1024;
+
+/// The size of the fragments that unreliable messages are split into, which leaves room for the
+/// headers of QUIC and of the network protocol in the smallest datagrams QUIC supports.
+const DATAGRAM_FRAGMENT_LENGTH: usize = 1000;
+
+const MAX_DATAGRAM_FRAGMENTS: usize =
+    (MAX_DATAGRAM_MESSAGE_LENGTH + DATAGRAM_FRAGMENT_LENGTH - 1) / DATAGRAM_FRAGMENT_LENGTH;
+
+/// How long to wait for the missing fragments of an unreliable message before dropping it.
+const DATAGRAM_FRAGMENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many incomplete unreliable messages are kept per sender, so that a sender that never
+/// completes its messages can't make the receiver hold on to an unbounded amount of fragments.
+/// The oldest is dropped to make room for a new one.
+const MAX_PARTIAL_DATAGRAMS_PER_SENDER: usize = 16;
+
+/// Returns an error describing why a message with this `name` and `data` cannot be sent over the
+/// network, if it is too large.
+pub fn check_networked_size(name: &str, data: &[u8], reliable: bool) -> Result<(), String> {
+    if reliable {
+        if data.len() > MAX_STREAM_LENGTH {
+            return Err(format!(
+                "The message {name:?} is {} bytes long, which is over the limit of \
+                 {MAX_STREAM_LENGTH} bytes for reliable messages",
+                data.len()
+            ));
+        }
+    } else {
+        let length = datagram_message_length(name, data);
+        if length > MAX_DATAGRAM_MESSAGE_LENGTH {
+            return Err(format!(
+                "The message {name:?} is {length} bytes long with its header, which is over the \
+                 limit of {MAX_DATAGRAM_MESSAGE_LENGTH} bytes for unreliable messages; send it \
+                 reliably instead"
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn datagram_message_length(name: &str, data: &[u8]) -> usize {
+    let header = 16 + 4 + name.len();
+    #[cfg(feature = "debug-local-datagram-latency")]
+    let header = header + 8;
+    header + data.len()
+}
+
 pub fn subscribe(subscribed_events: &mut HashSet<String>, name: String) -> anyhow::Result<()> {
     subscribed_events.insert(name);
     Ok(())
@@ -47,10 +106,19 @@ impl DatagramLatencyStat {
     }
 }
 
-/// Reads an incoming datagram and dispatches to WASM
+/// Reads an incoming datagram and dispatches to WASM once all the fragments of its message
+/// have been received
 pub fn on_datagram(world: &mut World, user_id: Option<String>, bytes: Bytes) -> anyhow::Result<()> {
     use byteorder::ReadBytesExt;
 
+    let bytes = world
+        .resource_mut_opt(datagram_reassembler())
+        .context("No datagram reassembler")?
+        .receive(Instant::now(), user_id.as_deref(), bytes)?;
+    let Some(bytes) = bytes else {
+        return Ok(());
+    };
+
     let mut cursor = Cursor::new(&bytes);
 
     #[cfg(feature = "debug-local-datagram-latency")]
@@ -200,17 +268,133 @@ fn send_datagram(
 
     payload.extend_from_slice(data);
 
+    let fragments = fragment_datagram(payload.freeze())?;
     world.resource(runtime()).spawn(async move {
-        log_network_result!(
-            transport
-                .send_datagram(WASM_DATAGRAM_ID, payload.freeze())
-                .await
-        );
+        for fragment in fragments {
+            log_network_result!(transport.send_datagram(WASM_DATAGRAM_ID, fragment).await);
+        }
     });
 
     Ok(())
 }
 
+/// Splits an unreliable message into datagrams of at most [DATAGRAM_FRAGMENT_LENGTH] bytes,
+/// each prefixed with the id of the message, the index of the fragment and the number of
+/// fragments.
+fn fragment_datagram(payload: Bytes) -> anyhow::Result<Vec<Bytes>> {
+    static NEXT_MESSAGE_ID: AtomicU32 = AtomicU32::new(0);
+
+    let count = (payload.len().max(1) + DATAGRAM_FRAGMENT_LENGTH - 1) / DATAGRAM_FRAGMENT_LENGTH;
+    anyhow::ensure!(
+        count <= MAX_DATAGRAM_FRAGMENTS,
+        "Unreliable message of {} bytes is too large",
+        payload.len()
+    );
+
+    let message_id = NEXT_MESSAGE_ID.fetch_add(1, Ordering::Relaxed);
+    Ok((0..count)
+        .map(|index| {
+            let start = index * DATAGRAM_FRAGMENT_LENGTH;
+            let end = (start + DATAGRAM_FRAGMENT_LENGTH).min(payload.len());
+
+            let mut fragment = BytesMut::with_capacity(8 + end - start);
+            fragment.put_u32(message_id);
+            fragment.put_u16(index as u16);
+            fragment.put_u16(count as u16);
+            fragment.extend_from_slice(&payload[start..end]);
+            fragment.freeze()
+        })
+        .collect())
+}
+
+/// Puts the fragments of unreliable messages back together.
+#[derive(Debug, Clone, Default)]
+pub struct DatagramReassembler {
+    /// The messages that are missing fragments, by sender and message id
+    partial: HashMap<(Option<String>, u32), PartialDatagram>,
+}
+impl DatagramReassembler {
+    /// Adds a fragment sent by `sender`, and returns its message if it is complete.
+    pub fn receive(
+        &mut self,
+        now: Instant,
+        sender: Option<&str>,
+        mut fragment: Bytes,
+    ) -> anyhow::Result<Option<Bytes>> {
+        anyhow::ensure!(fragment.len() >= 8, "Datagram fragment is too short");
+        let message_id = fragment.get_u32();
+        let index = usize::from(fragment.get_u16());
+        let count = usize::from(fragment.get_u16());
+        anyhow::ensure!(
+            index < count && count <= MAX_DATAGRAM_FRAGMENTS,
+            "Invalid datagram fragment {index} of {count}"
+        );
+
+        if count == 1 {
+            return Ok(Some(fragment));
+        }
+
+        self.partial
+            .retain(|_, partial| now.duration_since(partial.started) < DATAGRAM_FRAGMENT_TIMEOUT);
+
+        let key = (sender.map(ToOwned::to_owned), message_id);
+        if !self.partial.contains_key(&key) {
+            self.evict_oldest_if_full(&key.0);
+        }
+        let partial = self
+            .partial
+            .entry(key.clone())
+            .or_insert_with(|| PartialDatagram {
+                started: now,
+                fragments: vec![None; count],
+                missing: count,
+            });
+        anyhow::ensure!(
+            partial.fragments.len() == count,
+            "Datagram fragment count does not match the other fragments of its message"
+        );
+        if partial.fragments[index].replace(fragment).is_none() {
+            partial.missing -= 1;
+        }
+        if partial.missing > 0 {
+            return Ok(None);
+        }
+
+        let partial = self.partial.remove(&key).unwrap();
+        let mut message = BytesMut::new();
+        for fragment in partial.fragments.into_iter().flatten() {
+            message.extend_from_slice(&fragment);
+        }
+        Ok(Some(message.freeze()))
+    }
+
+    /// Drops the oldest incomplete message of `sender` if it has reached
+    /// [MAX_PARTIAL_DATAGRAMS_PER_SENDER].
+    fn evict_oldest_if_full(&mut self, sender: &Option<String>) {
+        let mut count = 0;
+        let mut oldest: Option<(&(Option<String>, u32), Instant)> = None;
+        for (key, partial) in self.partial.iter().filter(|(key, _)| &key.0 == sender) {
+            count += 1;
+            if oldest.map_or(true, |(_, started)| partial.started < started) {
+                oldest = Some((key, partial.started));
+            }
+        }
+        if count < MAX_PARTIAL_DATAGRAMS_PER_SENDER {
+            return;
+        }
+        if let Some(key) = oldest.map(|(key, _)| key.clone()) {
+            self.partial.remove(&key);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PartialDatagram {
+    started: Instant,
+    fragments: Vec<Option<Bytes>>,
+    missing: usize,
+}
+
 fn send_unistream(
     world: &World,
     transport: Arc<dyn NetworkTransport>,
@@ -237,3 +421,256 @@ fn send_unistream(
         anyhow::Ok(())
     });
 }
+
+/// What a message sent to the interested clients is about.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Topic {
+    /// An entity, such as one that an effect is attached to
+    Entity(EntityId),
+    /// A position in the world, such as that of an explosion
+    Position(Vec3),
+}
+
+/// The entities and areas that a client wants to receive the messages about.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct MessageInterests {
+    pub entities: Vec<EntityId>,
+    /// The center and radius of each area
+    pub areas: Vec<(Vec3, f32)>,
+}
+impl MessageInterests {
+    /// Returns whether the messages about `topic` are of interest: if it is one of the
+    /// [entities](Self::entities), or if it is within one of the [areas](Self::areas). Entities
+    /// are within an area if their `translation` is.
+    pub fn matches(&self, world: &World, topic: Topic) -> bool {
+        let position = match topic {
+            Topic::Entity(id) if self.entities.contains(&id) => return true,
+            Topic::Entity(id) => match world.get(id, translation()) {
+                Ok(position) => position,
+                Err(_) => return false,
+            },
+            Topic::Position(position) => position,
+        };
+        self.areas
+            .iter()
+            .any(|&(center, radius)| center.distance_squared(position) <= radius * radius)
+    }
+
+    fn to_bytes(&self) -> anyhow::Result<Bytes> {
+        let mut payload = BytesMut::new();
+        payload.put_u32(self.entities.len().try_into()?);
+        for id in &self.entities {
+            payload.put_u128(id.0);
+        }
+        payload.put_u32(self.areas.len().try_into()?);
+        for (center, radius) in &self.areas {
+            for value in center.to_array() {
+                payload.put_f32(value);
+            }
+            payload.put_f32(*radius);
+        }
+        Ok(payload.freeze())
+    }
+
+    fn from_bytes(mut payload: Bytes) -> anyhow::Result<Self> {
+        fn read_len(payload: &mut Bytes, item_size: usize) -> anyhow::Result<usize> {
+            anyhow::ensure!(payload.remaining() >= 4, "Message interests are truncated");
+            let len = payload.get_u32() as usize;
+            anyhow::ensure!(
+                payload.remaining() >= len * item_size,
+                "Message interests are truncated"
+            );
+            Ok(len)
+        }
+
+        let entities = (0..read_len(&mut payload, 16)?)
+            .map(|_| EntityId(payload.get_u128()))
+            .collect();
+        let areas = (0..read_len(&mut payload, 16)?)
+            .map(|_| {
+                let center = Vec3::new(payload.get_f32(), payload.get_f32(), payload.get_f32());
+                (center, payload.get_f32())
+            })
+            .collect();
+        Ok(Self { entities, areas })
+    }
+}
+
+/// Sends the message interests of this client to the server, which replace the previous ones.
+pub fn send_interests(
+    world: &World,
+    transport: Arc<dyn NetworkTransport>,
+    interests: &MessageInterests,
+) -> anyhow::Result<()> {
+    let payload = interests.to_bytes()?;
+    anyhow::ensure!(
+        payload.len() <= MAX_STREAM_LENGTH,
+        "Too many message interests"
+    );
+
+    world.resource(runtime()).spawn(async move {
+        log_network_result!(
+            transport
+                .request_uni(MESSAGE_INTERESTS_UNISTREAM_ID, payload)
+                .await
+        );
+    });
+    Ok(())
+}
+
+/// Reads the message interests sent by a client with [send_interests].
+pub async fn read_interests<R: ?Sized + tokio::io::AsyncRead>(
+    recv_stream: Pin<&mut R>,
+) -> anyhow::Result<MessageInterests> {
+    use tokio::io::AsyncReadExt;
+
+    let mut payload = Vec::new();
+    recv_stream
+        .take(MAX_STREAM_LENGTH as _)
+        .read_to_end(&mut payload)
+        .await?;
+    MessageInterests::from_bytes(payload.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fragments_are_reassembled() {
+        let payload: Bytes = (0..2500).map(|i| i as u8).collect::<Vec<_>>().into();
+        let fragments = fragment_datagram(payload.clone()).unwrap();
+        assert_eq!(fragments.len(), 3);
+
+        let now = Instant::now();
+        let mut reassembler = DatagramReassembler::default();
+        // Fragments can arrive out of order, and interleaved with the fragments of other senders
+        assert_eq!(
+            reassembler
+                .receive(now, Some("a"), fragments[2].clone())
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            reassembler
+                .receive(now, Some("b"), fragments[0].clone())
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            reassembler
+                .receive(now, Some("a"), fragments[0].clone())
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            reassembler
+                .receive(now, Some("a"), fragments[1].clone())
+                .unwrap(),
+            Some(payload)
+        );
+
+        let small = Bytes::from_static(b"small");
+        let fragments = fragment_datagram(small.clone()).unwrap();
+        assert_eq!(fragments.len(), 1);
+        assert_eq!(
+            reassembler
+                .receive(now, None, fragments[0].clone())
+                .unwrap(),
+            Some(small)
+        );
+    }
+
+    #[test]
+    fn incomplete_messages_expire() {
+        let payload = Bytes::from(vec![0; 1500]);
+        let fragments = fragment_datagram(payload).unwrap();
+
+        let now = Instant::now();
+        let mut reassembler = DatagramReassembler::default();
+        reassembler
+            .receive(now, None, fragments[0].clone())
+            .unwrap();
+        let later = now + DATAGRAM_FRAGMENT_TIMEOUT;
+        assert_eq!(
+            reassembler
+                .receive(later, None, fragments[1].clone())
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn oldest_incomplete_message_is_evicted() {
+        let messages = (0..=MAX_PARTIAL_DATAGRAMS_PER_SENDER)
+            .map(|_| fragment_datagram(Bytes::from(vec![1; 1500])).unwrap())
+            .collect::<Vec<_>>();
+        let other = fragment_datagram(Bytes::from(vec![2; 1500])).unwrap();
+
+        let now = Instant::now();
+        let mut reassembler = DatagramReassembler::default();
+        // Another sender's incomplete message is older than all of those of "a", but does not
+        // count towards its limit
+        reassembler
+            .receive(now, Some("b"), other[0].clone())
+            .unwrap();
+        for (i, fragments) in messages.iter().enumerate() {
+            let started = now + Duration::from_millis(i as u64 + 1);
+            reassembler
+                .receive(started, Some("a"), fragments[0].clone())
+                .unwrap();
+        }
+
+        let later = now + Duration::from_secs(1);
+        let last = messages.last().unwrap();
+        assert!(reassembler
+            .receive(later, Some("a"), last[1].clone())
+            .unwrap()
+            .is_some());
+        assert!(reassembler
+            .receive(later, Some("b"), other[1].clone())
+            .unwrap()
+            .is_some());
+        assert!(reassembler
+            .receive(later, Some("a"), messages[1][1].clone())
+            .unwrap()
+            .is_some());
+        // The first message of "a" was evicted, so its last fragment starts a new message
+        assert_eq!(
+            reassembler
+                .receive(later, Some("a"), messages[0][1].clone())
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn message_interests() {
+        ambient_ecs::init_components();
+        let near = EntityId::new();
+        let interests = MessageInterests {
+            entities: vec![near],
+            areas: vec![(Vec3::new(10., 0., 0.), 5.)],
+        };
+        let payload = interests.to_bytes().unwrap();
+        assert_eq!(
+            MessageInterests::from_bytes(payload.clone()).unwrap(),
+            interests
+        );
+        assert!(MessageInterests::from_bytes(payload.slice(..payload.len() - 1)).is_err());
+
+        let world = World::new_unknown("message_interests");
+        assert!(interests.matches(&world, Topic::Entity(near)));
+        assert!(!interests.matches(&world, Topic::Entity(EntityId::new())));
+        assert!(interests.matches(&world, Topic::Position(Vec3::new(12., 3., 0.))));
+        assert!(!interests.matches(&world, Topic::Position(Vec3::ZERO)));
+    }
+
+    #[test]
+    fn size_limits() {
+        assert!(check_networked_size("a", &[0; 1000], false).is_ok());
+        assert!(check_networked_size("a", &vec![0; MAX_DATAGRAM_MESSAGE_LENGTH], false).is_err());
+        assert!(check_networked_size("a", &vec![0; MAX_DATAGRAM_MESSAGE_LENGTH], true).is_ok());
+        assert!(check_networked_size("a", &vec![0; MAX_STREAM_LENGTH + 1], true).is_err());
+    }
+}
@@ -1,9 +1,13 @@
+pub mod analytics;
 pub mod asset;
+pub mod clock;
 pub mod component;
 pub mod entity;
 pub mod message;
 pub mod package;
 pub mod player;
+pub mod shared_random;
+pub mod snapshot;
 
 pub fn unsupported<T>() -> anyhow::Result<T> {
     anyhow::bail!("This function is not supported on this side of the API. Please report this if you were able to access this function.")
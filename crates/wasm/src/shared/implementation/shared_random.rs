@@ -0,0 +1,35 @@
+//! Random numbers that are the same on the server and the clients, as they only depend on a seed
+//! that the server shares with the clients, and on where they are read from in the sequence.
+use ambient_ecs::World;
+use ambient_network::{server, ServerWorldExt};
+use rand::RngCore;
+use rand_pcg::Pcg64;
+
+/// The maximum number of values that can be generated at once.
+const MAX_COUNT: u32 = 1 << 20;
+
+pub fn world_seed(world: &World) -> anyhow::Result<u64> {
+    Ok(world
+        .synced_resource(server::random_seed())
+        .copied()
+        .unwrap_or_default())
+}
+
+pub fn server_tick(world: &World) -> anyhow::Result<u64> {
+    Ok(world
+        .synced_resource(server::server_tick())
+        .copied()
+        .unwrap_or_default())
+}
+
+/// Returns `count` values of the sequence of `seed` and `stream`, starting from the value at
+/// `position`.
+pub fn generate(seed: u64, stream: u64, position: u64, count: u32) -> anyhow::Result<Vec<u64>> {
+    anyhow::ensure!(
+        count <= MAX_COUNT,
+        "Cannot generate more than {MAX_COUNT} random values at once"
+    );
+    let mut rng = Pcg64::new(seed as u128, stream as u128);
+    rng.advance(position as u128);
+    Ok((0..count).map(|_| rng.next_u64()).collect())
+}
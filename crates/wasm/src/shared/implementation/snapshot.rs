@@ -0,0 +1,311 @@
+//! Lets modules read the world as it was when they started handling the current message, and defer
+//! their changes until they have handled it.
+use std::collections::{HashMap, HashSet};
+
+use ambient_ecs::{
+    with_component_registry, ComponentDesc, ComponentEntry, Entity, EntityId, World,
+};
+
+use super::{
+    super::{conversion::FromBindgen, wit},
+    component::{host_entity_to_wit_entity, wit_entity_to_host_entity},
+};
+
+/// The components that a module has changed while handling the current message, with the values
+/// they had before. Components that have not been changed are read from the world.
+///
+/// Nothing is recorded until the module first reads a snapshot, so that modules which never read
+/// one do not pay for it. From then on, each message is recorded from its start.
+#[derive(Clone, Default)]
+pub struct Snapshot {
+    /// Whether the module has read a snapshot, after which its changes are recorded
+    recording: bool,
+    /// Whether each changed entity existed
+    existed: HashMap<EntityId, bool>,
+    /// The value of each changed component, or `None` if the entity did not have it
+    components: HashMap<(EntityId, u32), Option<ComponentEntry>>,
+}
+impl Snapshot {
+    /// Remembers the `index` component of `id`, unless it has already been changed.
+    pub fn record_component(&mut self, world: &World, id: EntityId, index: u32) {
+        if !self.recording {
+            return;
+        }
+        self.existed.entry(id).or_insert_with(|| world.exists(id));
+        self.components.entry((id, index)).or_insert_with(|| {
+            let desc = with_component_registry(|cr| cr.get_by_index(index))?;
+            world.get_entry(id, desc).ok()
+        });
+    }
+
+    /// Remembers all of the components of `id`, as it is about to be despawned.
+    pub fn record_entity(&mut self, world: &World, id: EntityId) {
+        if !self.recording {
+            return;
+        }
+        for desc in world.get_components(id).unwrap_or_default() {
+            self.record_component(world, id, desc.index());
+        }
+        self.existed.entry(id).or_insert_with(|| world.exists(id));
+    }
+
+    /// Remembers that `id` did not exist, as it has just been spawned.
+    pub fn record_spawn(&mut self, id: EntityId) {
+        if !self.recording {
+            return;
+        }
+        self.existed.entry(id).or_insert(false);
+    }
+
+    /// Starts recording changes, for the rest of the current message and all of the following ones.
+    pub fn start_recording(&mut self) {
+        self.recording = true;
+    }
+
+    /// Forgets the changes of the current message. Recording stays on once it has started.
+    pub fn clear(&mut self) {
+        self.existed.clear();
+        self.components.clear();
+    }
+
+    fn exists(&self, world: &World, id: EntityId) -> bool {
+        self.existed
+            .get(&id)
+            .copied()
+            .unwrap_or_else(|| world.exists(id))
+    }
+}
+
+/// A change that is applied once the module has handled the current message.
+#[derive(Clone)]
+pub enum DeferredCommand {
+    AddComponents(EntityId, Entity),
+    SetComponents(EntityId, Entity),
+    RemoveComponents(EntityId, Vec<ComponentDesc>),
+    Despawn(EntityId),
+}
+
+/// Applies the `commands`, in order. Commands for entities that no longer exist are ignored.
+pub fn apply(
+    world: &mut World,
+    spawned_entities: &mut HashSet<EntityId>,
+    commands: Vec<DeferredCommand>,
+) {
+    for command in commands {
+        match command {
+            DeferredCommand::AddComponents(id, data) => world.add_components(id, data).ok(),
+            DeferredCommand::SetComponents(id, data) => world.set_components(id, data).ok(),
+            DeferredCommand::RemoveComponents(id, components) => {
+                world.remove_components(id, components).ok()
+            }
+            DeferredCommand::Despawn(id) => {
+                spawned_entities.remove(&id);
+                world.despawn(id).map(|_| ())
+            }
+        };
+    }
+}
+
+pub fn exists(
+    world: &World,
+    snapshot: &mut Snapshot,
+    id: wit::types::EntityId,
+) -> anyhow::Result<bool> {
+    snapshot.start_recording();
+    Ok(snapshot.exists(world, id.from_bindgen()))
+}
+
+pub fn has_component(
+    world: &World,
+    snapshot: &mut Snapshot,
+    id: wit::types::EntityId,
+    index: u32,
+) -> anyhow::Result<bool> {
+    snapshot.start_recording();
+    let id = id.from_bindgen();
+    if !snapshot.exists(world, id) {
+        return Ok(false);
+    }
+    Ok(match snapshot.components.get(&(id, index)) {
+        Some(entry) => entry.is_some(),
+        None => world.has_component_index(id, index),
+    })
+}
+
+pub fn get_component(
+    world: &World,
+    snapshot: &mut Snapshot,
+    id: wit::types::EntityId,
+    index: u32,
+) -> anyhow::Result<Option<wit::component::Value>> {
+    snapshot.start_recording();
+    let host_id = id.from_bindgen();
+    if !snapshot.exists(world, host_id) {
+        return Ok(None);
+    }
+    match snapshot.components.get(&(host_id, index)) {
+        Some(Some(entry)) => {
+            let mut entity = Entity::new();
+            entity.set_entry(entry.clone());
+            Ok(host_entity_to_wit_entity(entity)?
+                .into_iter()
+                .next()
+                .map(|(_, value)| value))
+        }
+        Some(None) => Ok(None),
+        None => super::component::get_component(world, id, index),
+    }
+}
+
+pub fn defer_add_components(
+    deferred: &mut Vec<DeferredCommand>,
+    id: wit::types::EntityId,
+    data: wit::entity::EntityData,
+) -> anyhow::Result<()> {
+    deferred.push(DeferredCommand::AddComponents(
+        id.from_bindgen(),
+        wit_entity_to_host_entity(data)?,
+    ));
+    Ok(())
+}
+
+pub fn defer_set_components(
+    deferred: &mut Vec<DeferredCommand>,
+    id: wit::types::EntityId,
+    data: wit::entity::EntityData,
+) -> anyhow::Result<()> {
+    deferred.push(DeferredCommand::SetComponents(
+        id.from_bindgen(),
+        wit_entity_to_host_entity(data)?,
+    ));
+    Ok(())
+}
+
+pub fn defer_remove_components(
+    deferred: &mut Vec<DeferredCommand>,
+    id: wit::types::EntityId,
+    components: Vec<u32>,
+) -> anyhow::Result<()> {
+    let components = with_component_registry(|cr| {
+        components
+            .into_iter()
+            .flat_map(|idx| cr.get_by_index(idx))
+            .collect()
+    });
+    deferred.push(DeferredCommand::RemoveComponents(
+        id.from_bindgen(),
+        components,
+    ));
+    Ok(())
+}
+
+pub fn defer_despawn(
+    deferred: &mut Vec<DeferredCommand>,
+    id: wit::types::EntityId,
+) -> anyhow::Result<()> {
+    deferred.push(DeferredCommand::Despawn(id.from_bindgen()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use ambient_ecs::components;
+
+    use super::*;
+    use crate::shared::conversion::IntoBindgen;
+
+    components!("test", {
+        snapshot_value: u32,
+        snapshot_tag: (),
+    });
+
+    fn recorded_value(snapshot: &Snapshot, id: EntityId) -> Option<u32> {
+        snapshot.components[&(id, snapshot_value().index())]
+            .as_ref()
+            .and_then(|entry| entry.try_downcast_cloned())
+    }
+
+    #[test]
+    fn reads_old_values() {
+        init_components();
+        let mut world = World::new_unknown("reads_old_values");
+        let id = Entity::new()
+            .with(snapshot_value(), 1)
+            .with(snapshot_tag(), ())
+            .spawn(&mut world);
+        let mut snapshot = Snapshot::default();
+
+        // Nothing is recorded until the snapshot has been read
+        snapshot.record_component(&world, id, snapshot_value().index());
+        world.set(id, snapshot_value(), 2).unwrap();
+        assert!(snapshot.components.is_empty());
+
+        let value = snapshot_value().index();
+        assert!(has_component(&world, &mut snapshot, id.into_bindgen(), value).unwrap());
+        snapshot.record_component(&world, id, snapshot_value().index());
+        world.set(id, snapshot_value(), 3).unwrap();
+        snapshot.record_component(&world, id, snapshot_value().index());
+        world.set(id, snapshot_value(), 4).unwrap();
+        assert_eq!(recorded_value(&snapshot, id), Some(2));
+
+        snapshot.record_component(&world, id, snapshot_tag().index());
+        world.remove_component(id, snapshot_tag()).unwrap();
+        let tag = snapshot_tag().index();
+        assert!(has_component(&world, &mut snapshot, id.into_bindgen(), tag).unwrap());
+        assert!(!world.has_component(id, snapshot_tag()));
+
+        // The next message is recorded from its start
+        snapshot.clear();
+        assert!(snapshot.components.is_empty());
+        snapshot.record_component(&world, id, snapshot_value().index());
+        world.set(id, snapshot_value(), 5).unwrap();
+        assert_eq!(recorded_value(&snapshot, id), Some(4));
+    }
+
+    #[test]
+    fn spawned_entities_do_not_exist() {
+        init_components();
+        let mut world = World::new_unknown("spawned_entities_do_not_exist");
+        let mut snapshot = Snapshot::default();
+        snapshot.start_recording();
+
+        let id = Entity::new().with(snapshot_value(), 1).spawn(&mut world);
+        snapshot.record_spawn(id);
+        let value = snapshot_value().index();
+        assert!(world.exists(id));
+        assert!(!exists(&world, &mut snapshot, id.into_bindgen()).unwrap());
+        assert!(!has_component(&world, &mut snapshot, id.into_bindgen(), value).unwrap());
+
+        // Despawning an entity keeps it in the snapshot
+        let old = Entity::new().with(snapshot_value(), 2).spawn(&mut world);
+        snapshot.record_entity(&world, old);
+        world.despawn(old);
+        assert!(exists(&world, &mut snapshot, old.into_bindgen()).unwrap());
+        assert_eq!(recorded_value(&snapshot, old), Some(2));
+    }
+
+    #[test]
+    fn deferred_commands_after_a_despawn_are_ignored() {
+        init_components();
+        let mut world = World::new_unknown("deferred_commands_after_a_despawn_are_ignored");
+        let id = Entity::new().with(snapshot_value(), 1).spawn(&mut world);
+        let other = Entity::new().with(snapshot_value(), 1).spawn(&mut world);
+        let mut spawned_entities = HashSet::from([id, other]);
+
+        apply(
+            &mut world,
+            &mut spawned_entities,
+            vec![
+                DeferredCommand::SetComponents(other, Entity::new().with(snapshot_value(), 2)),
+                DeferredCommand::Despawn(id),
+                DeferredCommand::SetComponents(id, Entity::new().with(snapshot_value(), 3)),
+                DeferredCommand::AddComponents(id, Entity::new().with(snapshot_tag(), ())),
+                DeferredCommand::Despawn(id),
+            ],
+        );
+
+        assert!(!world.exists(id));
+        assert_eq!(world.get(other, snapshot_value()), Ok(2));
+        assert_eq!(spawned_entities, HashSet::from([other]));
+    }
+}
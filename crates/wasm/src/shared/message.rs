@@ -6,9 +6,13 @@ use ambient_ecs::{
 };
 use ambient_package_semantic_native::{client_modules, is_package, server_modules};
 
+use super::implementation::message::DatagramReassembler;
+
 components!("wasm::message", {
     @[Debuggable, Resource]
     pending_messages: Vec<SerializedMessage>,
+    @[Resource]
+    datagram_reassembler: DatagramReassembler,
 });
 
 #[derive(Clone, PartialEq, Debug)]
@@ -4,6 +4,7 @@ pub(crate) mod implementation;
 
 mod module;
 
+pub mod analytics;
 pub mod build;
 pub mod conversion;
 pub mod host_guest_state;
@@ -11,14 +12,16 @@ pub mod message;
 pub mod wit;
 
 pub use ambient_ecs::generated::wasm::components::*;
-use ambient_sys::task::PlatformBoxFuture;
+use ambient_sys::{task::PlatformBoxFuture, time::Instant};
 pub use internal::{messenger, module_bytecode, module_errors, module_state, module_state_maker};
 pub use module::*;
 use tracing::{Instrument, Span};
 
 use std::{path::Path, str::FromStr, sync::Arc};
 
-use ambient_core::{asset_cache, async_ecs::async_run, hierarchy::despawn_recursive, runtime};
+use ambient_core::{
+    asset_cache, async_ecs::async_run, frame_budget, hierarchy::despawn_recursive, memory, runtime,
+};
 use ambient_ecs::{
     dont_despawn_on_unload, generated::messages, query, world_events, EntityId, FnSystem, Message,
     SystemGroup, World, WorldContext, WorldEventReader,
@@ -81,6 +84,7 @@ pub fn init_all_components() {
     #[cfg(not(target_os = "unknown"))]
     native_bindings::init_components();
     message::init_components();
+    analytics::init_components();
 }
 
 pub const MAXIMUM_ERROR_COUNT: usize = 5;
@@ -230,6 +234,7 @@ pub fn systems() -> SystemGroup {
                     message::run(world, message);
                 }
             })),
+            Box::new(analytics::systems()),
         ],
     )
 }
@@ -253,6 +258,7 @@ pub fn initialize<'a, Bindings: bindings::BindingsBound + 'static>(
     );
 
     world.add_resource(message::pending_messages(), vec![]);
+    world.add_resource(message::datagram_reassembler(), Default::default());
 
     #[cfg(not(target_os = "unknown"))]
     if let Some(preopened_dir_path) = _preopened_dir_path {
@@ -270,14 +276,33 @@ pub fn initialize<'a, Bindings: bindings::BindingsBound + 'static>(
 }
 
 #[cfg(not(target_os = "unknown"))]
-pub(crate) fn reload_all(world: &mut World) {
+/// Restarts all the modules of a world that was copied from another world.
+///
+/// The modules of the copy share their state with the modules of the original world, so they are
+/// detached from it instead of being unloaded: the entities they spawned are despawned from the
+/// copy, and the original modules keep running untouched.
+pub(crate) fn restart_all_in_copy(world: &mut World) {
     let modules = query((is_module(), module_bytecode(), module_enabled()))
         .iter(world, None)
         .map(|(id, (_, bc, enabled))| (id, enabled.then(|| bc.clone())))
         .collect_vec();
 
     for (module_id, bytecode) in modules {
-        reload(world, module_id, bytecode);
+        if let Ok(state) = world.get_ref(module_id, module_state()).cloned() {
+            world.remove_component(module_id, module_state()).unwrap();
+            for id in state.spawned_entities() {
+                if world.exists(id) && !world.has_component(id, dont_despawn_on_unload()) {
+                    despawn_recursive(world, id);
+                }
+            }
+        }
+        if let Ok(module_errors) = world.get_mut(module_id, module_errors()) {
+            module_errors.0.clear();
+        }
+
+        if let Some(bytecode) = bytecode.filter(|bytecode| !bytecode.0.is_empty()) {
+            load(world, module_id, &bytecode.0);
+        }
     }
 }
 
@@ -410,8 +435,17 @@ fn run(
         return;
     }
 
+    let _memory = memory::is_tracking().then(|| {
+        let name = world
+            .get_ref(id, module_name())
+            .map_or("", |name| name.as_str());
+        memory::scope(&format!("wasm/{name}"))
+    });
+    let start = Instant::now();
     let result =
         run_and_catch_panics(|| state.run(world, message_source, message_name, message_data));
+    let name = world.get_cloned(id, module_name()).unwrap_or_default();
+    frame_budget::record_module(world, &name, start.elapsed());
 
     if let Err(message) = result {
         update_errors(world, &[(id, message)]);
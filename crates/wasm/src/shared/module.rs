@@ -118,6 +118,7 @@ pub trait ModuleStateBehavior: Send + Sync {
         message_data: &[u8],
     ) -> anyhow::Result<()>;
     fn drain_spawned_entities(&mut self) -> HashSet<EntityId>;
+    fn spawned_entities(&self) -> HashSet<EntityId>;
     fn listen_to_message(&mut self, event_name: String);
     fn supports_message(&self, event_name: &str) -> bool;
 }
@@ -187,6 +188,10 @@ impl ModuleStateBehavior for ModuleState {
         self.inner.write().drain_spawned_entities()
     }
 
+    fn spawned_entities(&self) -> HashSet<EntityId> {
+        self.inner.read().spawned_entities()
+    }
+
     fn listen_to_message(&mut self, message_name: String) {
         self.inner.write().listen_to_message(message_name)
     }
@@ -370,6 +375,13 @@ impl<Bindings: BindingsBound> ModuleStateBehavior for InstanceState<Bindings> {
 
         self.store.data_mut().bindings.clear_world();
 
+        // Apply the changes that were deferred while the guest was handling the message, and
+        // forget what it changed, so that the next message gets a new snapshot
+        let base = self.store.data_mut().bindings.base_mut();
+        base.snapshot.clear();
+        let deferred = std::mem::take(&mut base.deferred);
+        shared::implementation::snapshot::apply(world, &mut base.spawned_entities, deferred);
+
         self.stdout_consumer.process_incoming(world);
         self.stderr_consumer.process_incoming(world);
 
@@ -380,6 +392,10 @@ impl<Bindings: BindingsBound> ModuleStateBehavior for InstanceState<Bindings> {
         std::mem::take(&mut self.store.data_mut().bindings.base_mut().spawned_entities)
     }
 
+    fn spawned_entities(&self) -> HashSet<EntityId> {
+        self.store.data().bindings.base().spawned_entities.clone()
+    }
+
     fn listen_to_message(&mut self, event_name: String) {
         self.store
             .data_mut()
@@ -109,6 +109,7 @@ impl SyncAssetKey<Arc<MaterialShader>> for WaterMaterialShaderKey {
                 )
                 .with_binding_desc(get_water_layout()),
             ),
+            bindless: false,
         })
     }
 }
@@ -46,6 +46,7 @@ fn spawn_emitters(world: &mut World) {
                 constant: 1.0,
             },
             pos,
+            velocity: Vec3::ZERO,
         }));
 
         let id = Cube
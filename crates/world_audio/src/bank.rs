@@ -0,0 +1,110 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use ambient_native_std::{
+    asset_cache::{AssetCache, AsyncAssetKey},
+    asset_url::AbsAssetUrl,
+    download_asset::AssetResult,
+};
+use async_trait::async_trait;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The extension of the files that event banks are authored in.
+pub const AUDIO_BANK_EXTENSION: &str = "audio_bank.toml";
+/// The extension of the event banks built by the audio pipeline.
+pub const BUILT_AUDIO_BANK_EXTENSION: &str = "audio_bank.json";
+
+/// A bank of named audio events, which are played by name instead of by the URL of a sound.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AudioEventBank {
+    #[serde(default)]
+    pub events: BTreeMap<String, AudioEvent>,
+}
+impl AudioEventBank {
+    /// Checks that the events can be played, and returns the problems found.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (name, event) in &self.events {
+            if event.samples.is_empty() {
+                errors.push(format!("The event {name:?} has no samples"));
+            }
+            if !(event.volume >= 0.) || !(event.volume_variance >= 0.) {
+                errors.push(format!(
+                    "The volume and volume variance of the event {name:?} must not be negative"
+                ));
+            }
+            if !(event.pitch > 0.) || !(event.pitch_variance >= 0.) {
+                errors.push(format!(
+                    "The pitch of the event {name:?} must be positive, and its variance must not \
+                     be negative"
+                ));
+            } else if event.pitch_variance >= event.pitch {
+                errors.push(format!(
+                    "The pitch variance of the event {name:?} must be less than its pitch"
+                ));
+            }
+        }
+        errors
+    }
+}
+
+/// An event of an [AudioEventBank]: each time it is played, one of its samples is picked at
+/// random, with a random volume and pitch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AudioEvent {
+    /// The sounds picked from, relative to the bank.
+    pub samples: Vec<String>,
+    #[serde(default = "one")]
+    pub volume: f32,
+    /// The volume is picked in `volume ± volume_variance`.
+    #[serde(default)]
+    pub volume_variance: f32,
+    /// The playback rate, where 2 is an octave up and 0.5 is an octave down.
+    #[serde(default = "one")]
+    pub pitch: f32,
+    /// The pitch is picked in `pitch ± pitch_variance`.
+    #[serde(default)]
+    pub pitch_variance: f32,
+    /// The name of the bus the event is routed to.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bus: Option<String>,
+    #[serde(default)]
+    pub looping: bool,
+}
+impl AudioEvent {
+    /// Picks the sample, volume and pitch of a playback of the event.
+    pub fn pick(&self, rng: &mut impl Rng) -> Option<(&str, f32, f32)> {
+        let sample = self
+            .samples
+            .get(rng.gen_range(0..self.samples.len().max(1)))?;
+        let volume = vary(rng, self.volume, self.volume_variance).max(0.);
+        let pitch = vary(rng, self.pitch, self.pitch_variance).max(0.01);
+        Some((sample, volume, pitch))
+    }
+}
+
+fn vary(rng: &mut impl Rng, value: f32, variance: f32) -> f32 {
+    if variance > 0. {
+        value + rng.gen_range(-variance..=variance)
+    } else {
+        value
+    }
+}
+
+fn one() -> f32 {
+    1.
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct AudioEventBankFromUrl(pub AbsAssetUrl);
+
+#[async_trait]
+impl AsyncAssetKey<AssetResult<Arc<AudioEventBank>>> for AudioEventBankFromUrl {
+    async fn load(self, assets: AssetCache) -> AssetResult<Arc<AudioEventBank>> {
+        let bank: AudioEventBank = self.0.download_json(&assets).await?;
+        Ok(Arc::new(bank))
+    }
+}
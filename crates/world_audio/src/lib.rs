@@ -1,9 +1,11 @@
+mod bank;
 mod error;
 mod events;
 mod graph;
 mod sounds;
 pub mod systems;
 pub use ambient_audio as core;
+pub use bank::*;
 pub use error::*;
 pub use events::*;
 pub use graph::*;
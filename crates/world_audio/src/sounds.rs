@@ -3,7 +3,8 @@ use std::sync::Arc;
 use ambient_audio::{
     hrtf::HrtfLib,
     track::{Track, TrackDecodeStream},
-    Attenuation, AudioEmitter, AudioListener, AudioMixer, Sound, SoundId, Source, Spatial,
+    Attenuation, AudioEmitter, AudioListener, AudioMixer, EffectParams, PlaybackControls, Sound,
+    SoundId, Source, Spatial,
 };
 use ambient_ecs::{components, query, EntityId, Resource, World};
 use ambient_element::ElementComponentExt;
@@ -33,7 +34,16 @@ components!("audio", {
     panning_arc: Arc<Mutex<f32>>,
     onepole_arc: Arc<Mutex<f32>>,
     looping_arc: Arc<Mutex<bool>>,
+    /// The pitch, pause and position of a playing sound.
+    playback_controls: Arc<Mutex<PlaybackControls>>,
     sound_id: SoundId,
+    /// The volume picked for a playing event, which is applied on top of its amplitude.
+    sound_gain: f32,
+    /// The mixer that a playing sound, or an audio bus, is played on.
+    sound_mixer: AudioMixer,
+    /// The mixer that the sounds routed to an audio bus are played on, through its effects.
+    audio_bus_mixer: AudioMixer,
+    audio_bus_effects: Arc<Mutex<EffectParams>>,
 });
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -59,6 +69,10 @@ pub enum AudioMessage {
     UpdateVolume(String, f32),
     UpdatePanning(String, f32),
     AddOnePoleLpf(String, f32),
+    UpdatePitch(String, f32),
+    Pause(String),
+    Resume(String),
+    SeekTo(String, std::time::Duration),
     StopById(String),
 }
 
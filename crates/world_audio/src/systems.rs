@@ -1,24 +1,90 @@
 use std::{io::Cursor, sync::Arc};
 
-use crate::{audio_emitter, audio_listener, hrtf_lib};
-use ambient_audio::{hrtf::HrtfLib, AudioFromUrl, Source};
-use ambient_audio::{Attenuation, AudioEmitter, AudioListener};
+use crate::{audio_emitter, audio_listener, hrtf_lib, AudioEventBankFromUrl};
+use ambient_audio::{
+    blt::Lpf, hrtf::HrtfLib, AudioFromUrl, AudioMixer, EffectParams, Equalizer, PlaybackControls,
+    Reverb, Source,
+};
+use ambient_audio::{Attenuation, AudioEmitter, AudioListener, SPEED_OF_SOUND};
 use ambient_core::{
     asset_cache,
     async_ecs::async_run,
+    camera::get_active_camera,
+    delta_time, main_scene,
+    player::local_user_id,
     runtime,
     transform::{local_to_world, translation},
 };
 use ambient_ecs::EntityId;
 use ambient_ecs::{
-    generated::audio::components::*, generated::hierarchy::components::children, query,
-    SystemGroup, World,
+    generated::audio::components::*,
+    generated::hierarchy::components::{children, parent},
+    query, SystemGroup, World,
 };
 use ambient_native_std::{asset_cache::AsyncAssetKeyExt, asset_url::AbsAssetUrl, unwrap_log_warn};
-use glam::{vec4, Mat4};
+use glam::{vec4, Mat4, Vec3};
+use itertools::Itertools;
 use parking_lot::Mutex;
 use std::str::FromStr;
 
+/// The listener of the spatial audio that is not given one: the entity with a
+/// `main_audio_listener`, or else the active camera.
+pub fn get_main_audio_listener(world: &World) -> Option<EntityId> {
+    query(main_audio_listener())
+        .iter(world, None)
+        .map(|(id, _)| id)
+        .next()
+        .or_else(|| get_active_camera(world, main_scene(), world.resource_opt(local_user_id())))
+}
+
+/// The world transform of the audio `listener`, oriented by its `audio_listener_rotation` if it
+/// has one.
+pub fn audio_listener_transform(world: &World, listener: EntityId) -> Option<Mat4> {
+    let transform = world.get(listener, local_to_world()).ok()?;
+    Some(match world.get(listener, audio_listener_rotation()) {
+        Ok(rotation) => {
+            let (scale, _, translation) = transform.to_scale_rotation_translation();
+            Mat4::from_scale_rotation_translation(scale, rotation, translation)
+        }
+        Err(_) => transform,
+    })
+}
+
+/// Moves the `listener` to its world `transform`, with its `velocity` if it is known, or else
+/// the velocity estimated from its previous transform.
+pub fn update_listener(
+    listener: &mut AudioListener,
+    transform: Mat4,
+    velocity: Option<Vec3>,
+    dt: f32,
+) {
+    let transform = Y_UP_LHS * transform;
+    listener.velocity = match velocity {
+        Some(velocity) => Y_UP_LHS.transform_vector3(velocity),
+        None => estimate_audio_velocity(
+            listener.transform.w_axis.truncate(),
+            transform.w_axis.truncate(),
+            dt,
+        ),
+    };
+    listener.transform = transform;
+}
+
+/// The velocity of something that moved from `previous` to `current` in `dt`.
+///
+/// Movements faster than sound are teleports rather than motion, and have no velocity.
+pub fn estimate_audio_velocity(previous: Vec3, current: Vec3, dt: f32) -> Vec3 {
+    if dt <= 0. {
+        return Vec3::ZERO;
+    }
+    let velocity = (current - previous) / dt;
+    if velocity.length() < SPEED_OF_SOUND {
+        velocity
+    } else {
+        Vec3::ZERO
+    }
+}
+
 /// Initializes the HRTF sphere and adds the appropriate resources
 ///
 /// TODO: customizer IR sphere selection
@@ -53,7 +119,8 @@ pub fn audio_systems() -> SystemGroup {
                 }),
             // Updates the volume of audio emitters in the world
             query((audio_emitter(), local_to_world())).to_system(|q, world, qs, _| {
-                for (_, (emitter, ltw)) in q.iter(world, qs) {
+                let dt = *world.resource(delta_time());
+                for (id, (emitter, ltw)) in q.iter(world, qs) {
                     // check if mute_audio is set
                     let r = world.resource_entity();
                     if !world.has_component(r, crate::audio_mixer()) {
@@ -61,20 +128,32 @@ pub fn audio_systems() -> SystemGroup {
                     }
                     let (_, _, pos) = ltw.to_scale_rotation_translation();
                     let mut emitter = emitter.lock();
+                    emitter.velocity = world
+                        .get(id, audio_velocity())
+                        .unwrap_or_else(|_| estimate_audio_velocity(emitter.pos, pos, dt));
                     emitter.pos = pos;
                 }
             }),
             query((audio_listener(), local_to_world())).to_system_with_name(
                 "update_audio_listener",
                 |q, world, qs, _| {
-                    for (_, (listener, &ltw)) in q.iter(world, qs) {
+                    let dt = *world.resource(delta_time());
+                    for (id, (listener, _)) in q.iter(world, qs) {
                         // check if mute_audio is set
                         let r = world.resource_entity();
                         if !world.has_component(r, crate::audio_mixer()) {
                             continue;
                         }
+                        let Some(transform) = audio_listener_transform(world, id) else {
+                            continue;
+                        };
                         let mut listener = listener.lock();
-                        listener.transform = Y_UP_LHS * ltw;
+                        update_listener(
+                            &mut listener,
+                            transform,
+                            world.get(id, audio_velocity()).ok(),
+                            dt,
+                        );
                     }
                 },
             ),
@@ -86,7 +165,10 @@ pub fn audio_systems() -> SystemGroup {
                         continue;
                     }
 
-                    let mixer = world.resource(crate::audio_mixer());
+                    // Sounds routed to a bus are played on the mixer of the bus
+                    let mixer = world
+                        .get_ref(playing_entity, crate::sound_mixer())
+                        .unwrap_or_else(|_| world.resource(crate::audio_mixer()));
                     let Ok(id) = world.get(playing_entity, crate::sound_id()) else {
                         tracing::error!(
                             "No sound id component on playing entity; cannot stop audio."
@@ -115,6 +197,7 @@ pub fn audio_systems() -> SystemGroup {
                     if !world.has_component(r, crate::audio_mixer()) {
                         continue;
                     }
+                    let amp = amp * sound_gain_factor(world, playing_entity);
                     if let Ok(amp_arc) = world.get_mut(playing_entity, crate::amplitude_arc()) {
                         *amp_arc.lock() = amp;
                     }
@@ -132,6 +215,137 @@ pub fn audio_systems() -> SystemGroup {
                     }
                 }
             }),
+            query((playing_sound(), pitch())).to_system(|q, world, qs, _| {
+                for (playing_entity, (_, pitch)) in q.collect_cloned(world, qs) {
+                    if let Ok(controls) = world.get_ref(playing_entity, crate::playback_controls())
+                    {
+                        controls.lock().pitch = pitch;
+                    }
+                }
+            }),
+            query((playing_sound(), paused())).to_system(|q, world, qs, _| {
+                for (playing_entity, (_, paused)) in q.collect_cloned(world, qs) {
+                    if let Ok(controls) = world.get_ref(playing_entity, crate::playback_controls())
+                    {
+                        controls.lock().paused = paused;
+                    }
+                }
+            }),
+            query((playing_sound(), seek_now())).to_system(|q, world, qs, _| {
+                for (playing_entity, (_, time)) in q.collect_cloned(world, qs) {
+                    // The sound may still be loading, in which case it jumps once it plays
+                    let Ok(controls) = world.get_ref(playing_entity, crate::playback_controls())
+                    else {
+                        continue;
+                    };
+                    controls.lock().seek = Some(time);
+                    let _ = world.remove_component(playing_entity, seek_now());
+                }
+            }),
+            // Each audio bus mixes the sounds routed to it, and plays them through its effects
+            query(audio_bus())
+                .incl(is_audio_bus())
+                .excl(crate::audio_bus_mixer())
+                .to_system(|q, world, qs, _| {
+                    for (bus, name) in q.collect_cloned(world, qs) {
+                        let Some(main) = world.resource_opt(crate::audio_mixer()).cloned() else {
+                            continue;
+                        };
+                        let mixer = AudioMixer::new(main.inner.sample_rate);
+                        let effects = Arc::new(Mutex::new(EffectParams::default()));
+                        let sound = main.play(mixer.clone().effects(effects.clone()));
+                        let _ = world.add_component(bus, crate::audio_bus_mixer(), mixer.clone());
+                        let _ = world.add_component(bus, crate::audio_bus_effects(), effects);
+                        let _ = world.add_component(bus, crate::sound_mixer(), main);
+                        let _ = world.add_component(bus, crate::sound_id(), sound.id);
+
+                        // The sounds that were routed to the bus before it was set up
+                        let sounds = query(audio_bus())
+                            .incl(playing_sound())
+                            .iter(world, None)
+                            .filter(|(_, bus)| **bus == name)
+                            .map(|(id, _)| id)
+                            .collect_vec();
+                        for sound in sounds {
+                            move_sound(world, sound, &mixer);
+                        }
+                    }
+                }),
+            query((
+                crate::audio_bus_mixer(),
+                crate::sound_mixer(),
+                crate::sound_id(),
+            ))
+            .incl(is_audio_bus())
+            .despawned()
+            .to_system(|q, world, qs, _| {
+                for (_, (mixer, main, id)) in q.collect_cloned(world, qs) {
+                    main.stop(id);
+                    // The sounds that were routed to the bus play on the main output
+                    let sounds = query(crate::sound_mixer())
+                        .incl(playing_sound())
+                        .iter(world, None)
+                        .filter(|(_, sound_mixer)| sound_mixer.ptr_eq(&mixer))
+                        .map(|(id, _)| id)
+                        .collect_vec();
+                    for sound in sounds {
+                        move_sound(world, sound, &main);
+                    }
+                }
+            }),
+            query(crate::audio_bus_effects())
+                .incl(is_audio_bus())
+                .to_system(|q, world, qs, _| {
+                    for (bus, effects) in q.iter(world, qs) {
+                        let params = EffectParams {
+                            eq: world.get(bus, equalizer()).ok().map(|eq| Equalizer {
+                                low: eq.x,
+                                mid: eq.y,
+                                high: eq.z,
+                            }),
+                            low_pass: world.get(bus, lpf()).ok().map(|lpf| Lpf {
+                                freq: lpf.x,
+                                bandwidth: lpf.y,
+                            }),
+                            reverb: world.get(bus, reverb()).ok().map(|reverb| Reverb {
+                                room_size: reverb.x,
+                                damping: reverb.y,
+                                mix: reverb.z,
+                            }),
+                        };
+                        let mut effects = effects.lock();
+                        if *effects != params {
+                            *effects = params;
+                        }
+                    }
+                }),
+            // Changing the bus of a playing sound moves it to the new bus
+            query(audio_bus().changed())
+                .incl(playing_sound())
+                .incl(crate::sound_mixer())
+                .to_system(|q, world, qs, _| {
+                    for (sound, bus) in q.collect_cloned(world, qs) {
+                        let Some(mixer) = find_bus_mixer(world, &bus)
+                            .or_else(|| world.resource_opt(crate::audio_mixer()).cloned())
+                        else {
+                            continue;
+                        };
+                        move_sound(world, sound, &mixer);
+                    }
+                }),
+            // Playing sounds are despawned once they have finished
+            query(crate::playback_controls())
+                .incl(playing_sound())
+                .to_system(|q, world, qs, _| {
+                    let finished = q
+                        .iter(world, qs)
+                        .filter(|(_, controls)| controls.lock().finished)
+                        .map(|(id, _)| id)
+                        .collect_vec();
+                    for id in finished {
+                        world.despawn(id);
+                    }
+                }),
             query((playing_sound(), onepole_lpf())).to_system(|q, world, qs, _| {
                 for (playing_entity, (_, freq)) in q.collect_cloned(world, qs) {
                     // check if mute_audio is set
@@ -144,103 +358,239 @@ pub fn audio_systems() -> SystemGroup {
                     }
                 }
             }),
-            query((is_audio_player(), play_now(), audio_url())).to_system(|q, world, qs, _| {
-                for (player, (_, _, url)) in q.collect_cloned(world, qs) {
+            query((is_audio_player(), play_now(), audio_url()))
+                .excl(audio_event())
+                .to_system(|q, world, qs, _| {
+                    for (player, (_, _, url)) in q.collect_cloned(world, qs) {
+                        // check if mute_audio is set
+                        let r = world.resource_entity();
+                        if !world.has_component(r, crate::audio_mixer()) {
+                            continue;
+                        }
+
+                        world.remove_component(player, play_now()).unwrap();
+
+                        let assets = world.resource(asset_cache()).clone();
+                        let Ok(url) =
+                            AbsAssetUrl::from_str(&url).and_then(|u| u.to_download_url(&assets))
+                        else {
+                            continue;
+                        };
+                        play_on_player(world, player, url, None);
+                    }
+                }),
+            query((
+                is_audio_player(),
+                play_now(),
+                audio_event_bank_url(),
+                audio_event(),
+            ))
+            .to_system(|q, world, qs, _| {
+                for (player, (_, _, bank_url, event_name)) in q.collect_cloned(world, qs) {
                     // check if mute_audio is set
                     let r = world.resource_entity();
                     if !world.has_component(r, crate::audio_mixer()) {
                         continue;
                     }
 
-                    let amp = world.get(player, amplitude()).unwrap_or(1.0);
-                    let pan = world.get(player, panning()).unwrap_or(0.0);
-                    let freq = world.get(player, onepole_lpf()).unwrap_or(20000.0);
-                    let looping = world.get(player, looping()).unwrap_or(false);
-
                     world.remove_component(player, play_now()).unwrap();
 
                     let assets = world.resource(asset_cache()).clone();
                     let runtime = world.resource(runtime()).clone();
                     let async_run = world.resource(async_run()).clone();
-                    let Ok(url) =
-                        AbsAssetUrl::from_str(&url).and_then(|u| u.to_download_url(&assets))
+                    let Ok(bank_url) =
+                        AbsAssetUrl::from_str(&bank_url).and_then(|u| u.to_download_url(&assets))
                     else {
+                        tracing::warn!("Invalid audio event bank URL {bank_url:?}");
                         continue;
                     };
 
                     runtime.spawn(async move {
-                        let track =
-                            unwrap_log_warn!(AudioFromUrl { url: url.clone() }.get(&assets).await);
-                        let id_arc = Arc::new(Mutex::new(None));
-                        let id_arc_clone = id_arc.clone();
-                        let count_arc = Arc::new(Mutex::new(None));
-                        let count_arc_clone = count_arc.clone();
-                        let sr_arc = Arc::new(Mutex::new(None));
-                        let sr_arc_clone = sr_arc.clone();
+                        let bank = unwrap_log_warn!(
+                            AudioEventBankFromUrl(bank_url.clone()).get(&assets).await
+                        );
+                        let Some(event) = bank.events.get(&event_name) else {
+                            tracing::warn!(
+                                "The audio event bank {bank_url} has no event {event_name:?}"
+                            );
+                            return;
+                        };
+                        let Some((sample, gain, pitch)) = event.pick(&mut rand::thread_rng())
+                        else {
+                            return;
+                        };
+                        // The samples are relative to the bank
+                        let url = unwrap_log_warn!(bank_url.join(sample));
+                        let playback = EventPlayback {
+                            gain,
+                            pitch,
+                            bus: event.bus.clone(),
+                            looping: event.looping,
+                        };
                         async_run.run(move |world| {
-                            let Some(id) = world
-                                .get_ref(player, children())
-                                .ok()
-                                .and_then(|c| c.last())
-                                .copied()
-                            else {
-                                tracing::error!(
-                                    "No children component on parent entity; cannot play audio."
-                                );
-                                return;
-                            };
-                            id_arc.lock().replace(id);
-
-                            let mut t: Box<dyn Source> = if looping {
-                                Box::new(track.decode().repeat())
-                            } else {
-                                let decoded = track.decode();
-                                let count = decoded.sample_count().unwrap();
-                                let sr = decoded.sample_rate();
-                                *count_arc.lock() = Some(count);
-                                *sr_arc.lock() = Some(sr);
-                                Box::new(decoded)
-                            };
-                            let a = Arc::new(Mutex::new(amp));
-                            t = t.gain(a.clone());
-                            let p = Arc::new(Mutex::new(pan));
-                            t = t.pan(p.clone());
-                            let f = Arc::new(Mutex::new(freq));
-                            t = t.onepole(f.clone());
-
-                            let id = id_arc.lock().unwrap();
-                            let _ = world.add_component(id, crate::amplitude_arc(), a);
-                            let _ = world.add_component(id, crate::panning_arc(), p);
-                            let _ = world.add_component(id, crate::onepole_arc(), f);
-
-                            let mixer = world.resource(crate::audio_mixer());
-                            let sound = mixer.play(t);
-
-                            let _ = world.add_component(id, crate::sound_id(), sound.id);
+                            play_on_player(world, player, url, Some(playback));
                         });
-
-                        let count = *count_arc_clone.lock();
-                        let sr = *sr_arc_clone.lock();
-                        if let Some((count, sr)) = count.zip(sr) {
-                            let dur = count as f32 / sr as f32 * 1.001;
-                            ambient_sys::time::sleep_label(
-                                std::time::Duration::from_secs_f32(dur),
-                                "audio",
-                            )
-                            .await;
-                            async_run.run(move |world| {
-                                if let Some(id) = id_arc_clone.lock().take() {
-                                    world.despawn(id);
-                                }
-                            });
-                        };
                     });
                 }
             }),
+            // The amplitude of their player and of their bus applies to the sounds routed to a bus
+            // without an amplitude of their own
+            query((playing_sound(), audio_bus()))
+                .excl(amplitude())
+                .to_system(|q, world, qs, _| {
+                    for (playing_entity, _) in q.collect_cloned(world, qs) {
+                        // check if mute_audio is set
+                        let r = world.resource_entity();
+                        if !world.has_component(r, crate::audio_mixer()) {
+                            continue;
+                        }
+                        let amp = world
+                            .get(playing_entity, parent())
+                            .and_then(|player| world.get(player, amplitude()))
+                            .unwrap_or(1.0);
+                        let amp = amp * sound_gain_factor(world, playing_entity);
+                        if let Ok(amp_arc) = world.get_mut(playing_entity, crate::amplitude_arc()) {
+                            *amp_arc.lock() = amp;
+                        }
+                    }
+                }),
         ],
     )
 }
 
+/// How a sound picked from an [AudioEvent](crate::AudioEvent) is played.
+struct EventPlayback {
+    gain: f32,
+    pitch: f32,
+    bus: Option<String>,
+    looping: bool,
+}
+
+/// Plays the sound at `url` on the audio `player`, as its last playing sound.
+fn play_on_player(
+    world: &mut World,
+    player: EntityId,
+    url: AbsAssetUrl,
+    playback: Option<EventPlayback>,
+) {
+    let amp = world.get(player, amplitude()).unwrap_or(1.0);
+    let pan = world.get(player, panning()).unwrap_or(0.0);
+    let freq = world.get(player, onepole_lpf()).unwrap_or(20000.0);
+    let controls = PlaybackControls {
+        pitch: world.get(player, pitch()).unwrap_or(1.0),
+        ..Default::default()
+    };
+    let looping = world.get(player, looping()).unwrap_or(false)
+        || playback.as_ref().map_or(false, |playback| playback.looping);
+    let (gain, pitch, bus) = match playback {
+        Some(playback) => (playback.gain, playback.pitch, playback.bus),
+        None => (1.0, 1.0, None),
+    };
+    let bus = bus.or_else(|| world.get_cloned(player, audio_bus()).ok());
+
+    let assets = world.resource(asset_cache()).clone();
+    let runtime = world.resource(runtime()).clone();
+    let async_run = world.resource(async_run()).clone();
+
+    runtime.spawn(async move {
+        let track = unwrap_log_warn!(AudioFromUrl { url: url.clone() }.get(&assets).await);
+        async_run.run(move |world| {
+            let Some(id) = world
+                .get_ref(player, children())
+                .ok()
+                .and_then(|c| c.last())
+                .copied()
+            else {
+                tracing::error!("No children component on parent entity; cannot play audio.");
+                return;
+            };
+
+            // The playback despawns the sound once it has finished
+            let controls = Arc::new(Mutex::new(controls));
+            let mut t: Box<dyn Source> = Box::new(
+                track
+                    .decode()
+                    .pitch(pitch)
+                    .playback(looping, controls.clone()),
+            );
+            if gain != 1.0 {
+                let _ = world.add_component(id, crate::sound_gain(), gain);
+            }
+            if let Some(bus) = bus {
+                let _ = world.add_component(id, audio_bus(), bus);
+            }
+            let a = Arc::new(Mutex::new(amp * sound_gain_factor(world, id)));
+            t = t.gain(a.clone());
+            let p = Arc::new(Mutex::new(pan));
+            t = t.pan(p.clone());
+            let f = Arc::new(Mutex::new(freq));
+            t = t.onepole(f.clone());
+
+            let _ = world.add_component(id, crate::amplitude_arc(), a);
+            let _ = world.add_component(id, crate::panning_arc(), p);
+            let _ = world.add_component(id, crate::onepole_arc(), f);
+            let _ = world.add_component(id, crate::playback_controls(), controls);
+
+            let Some(mixer) = world
+                .get_ref(id, audio_bus())
+                .ok()
+                .and_then(|bus| find_bus_mixer(world, bus))
+                .or_else(|| world.resource_opt(crate::audio_mixer()).cloned())
+            else {
+                return;
+            };
+            let sound = mixer.play(t);
+
+            let _ = world.add_component(id, crate::sound_id(), sound.id);
+            let _ = world.add_component(id, crate::sound_mixer(), mixer);
+        });
+    });
+}
+
+/// The mixer of the audio bus `name`, once it has been set up.
+fn find_bus_mixer(world: &World, name: &str) -> Option<AudioMixer> {
+    query((audio_bus(), crate::audio_bus_mixer()))
+        .incl(is_audio_bus())
+        .iter(world, None)
+        .find(|(_, (bus, _))| *bus == name)
+        .map(|(_, (_, mixer))| mixer.clone())
+}
+
+/// Moves the playing `sound` to `mixer`, without interrupting it.
+fn move_sound(world: &mut World, sound: EntityId, mixer: &AudioMixer) {
+    let (Ok(current), Ok(id)) = (
+        world.get_cloned(sound, crate::sound_mixer()),
+        world.get(sound, crate::sound_id()),
+    ) else {
+        return;
+    };
+    if current.ptr_eq(mixer) {
+        return;
+    }
+    let Some(source) = current.take(id) else {
+        return;
+    };
+    let moved = mixer.play(source);
+    let _ = world.set(sound, crate::sound_id(), moved.id);
+    let _ = world.set(sound, crate::sound_mixer(), mixer.clone());
+}
+
+/// The gain applied to a playing sound on top of its amplitude: the volume picked for its event,
+/// and the amplitude of the bus it is routed to.
+fn sound_gain_factor(world: &World, sound: EntityId) -> f32 {
+    let gain = world.get(sound, crate::sound_gain()).unwrap_or(1.0);
+    match world.get_ref(sound, audio_bus()) {
+        Ok(bus) => {
+            gain * query(audio_bus())
+                .incl(is_audio_bus())
+                .iter(world, None)
+                .find(|(_, name)| *name == bus)
+                .map_or(1.0, |(id, _)| world.get(id, amplitude()).unwrap_or(1.0))
+        }
+        Err(_) => gain,
+    }
+}
+
 fn process_player(world: &mut World, player: EntityId, url: &str) {
     // check if mute_audio is set
     let r = world.resource_entity();
@@ -260,13 +610,17 @@ fn process_player(world: &mut World, player: EntityId, url: &str) {
     runtime.spawn(async move {
         let track = unwrap_log_warn!(AudioFromUrl { url: url.clone() }.get(&assets).await);
         async_run.run(move |world| {
-            let Ok(listener_id) = world.get(player, spatial_audio_listener()) else {
+            let Some(listener_id) = world
+                .get(player, spatial_audio_listener())
+                .ok()
+                .or_else(|| get_main_audio_listener(world))
+            else {
                 return;
             };
             let Ok(emitter_id) = world.get(player, spatial_audio_emitter()) else {
                 return;
             };
-            let Ok(listener_transform) = world.get(listener_id, local_to_world()) else {
+            let Some(listener_transform) = audio_listener_transform(world, listener_id) else {
                 return;
             };
 
@@ -284,6 +638,7 @@ fn process_player(world: &mut World, player: EntityId, url: &str) {
                     constant: 1.0,
                 },
                 pos: pos_emitter,
+                velocity: world.get(emitter_id, audio_velocity()).unwrap_or_default(),
             }));
             let _ = world.add_component(emitter_id, audio_emitter(), emitter.clone());
             let _ = world.add_component(listener_id, audio_listener(), listener.clone());
@@ -0,0 +1,17 @@
+use crate::internal::wit;
+
+/// Records an analytics event with the given `properties`.
+///
+/// Events are batched by the runtime and sent to the HTTPS endpoint specified in the `[analytics]`
+/// section of the main package's manifest, along with metadata about the current session
+/// (such as the platform, Ambient version and user ID).
+///
+/// Events recorded before the main package has loaded are kept until it has. If its manifest
+/// configures no endpoint, or the user has opted out of analytics, the event is discarded.
+pub fn track<'a>(event: &str, properties: impl IntoIterator<Item = (&'a str, &'a str)>) {
+    let properties = properties
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<Vec<_>>();
+    wit::analytics::track(event, &properties);
+}
@@ -1,13 +1,20 @@
 use crate::{
     core::{
         animation::components::{
-            apply_base_pose, bind_id, bind_ids, blend, clip_duration, freeze_at_percentage,
+            animation_event_names, animation_event_times, animation_parameter_names,
+            animation_parameter_values, apply_base_pose, bind_id, bind_ids, blend,
+            blend_space_parameter, blend_space_thresholds, clip_duration, freeze_at_percentage,
             freeze_at_time, is_animation_player, looping, mask_bind_ids, mask_weights,
             play_clip_from_url, retarget_animation_scaled, retarget_model_from_url, start_time,
+            state_machine_state, state_machine_states, state_machine_transition_durations,
+            state_machine_transition_from, state_machine_transition_max,
+            state_machine_transition_min, state_machine_transition_parameters,
+            state_machine_transition_to,
         },
         app::components::name,
         hierarchy::components::{children, parent},
     },
+    ecs::{Component, SupportedValue},
     entity,
     prelude::{epoch_time, Entity, EntityId},
 };
@@ -47,6 +54,38 @@ impl AnimationPlayerRef {
         entity::add_component(new_root.0, parent(), self.0);
         old_root
     }
+    /// Sets a parameter of this animation player, which drives its state machines and blend
+    /// spaces
+    pub fn set_parameter(&self, name: impl Into<String>, value: f32) {
+        let name = name.into();
+        let mut names =
+            entity::get_component(self.0, animation_parameter_names()).unwrap_or_default();
+        let mut values =
+            entity::get_component(self.0, animation_parameter_values()).unwrap_or_default();
+        let index = match names.iter().position(|n| *n == name) {
+            Some(index) => index,
+            None => {
+                names.push(name);
+                names.len() - 1
+            }
+        };
+        values.resize(names.len(), 0.);
+        values[index] = value;
+        entity::add_component(self.0, animation_parameter_names(), names);
+        entity::add_component(self.0, animation_parameter_values(), values);
+    }
+    /// Sets a boolean parameter of this animation player, which is 1 when true and 0 when false
+    pub fn set_bool_parameter(&self, name: impl Into<String>, value: bool) {
+        self.set_parameter(name, if value { 1. } else { 0. });
+    }
+    /// Get the value of a parameter of this animation player, if it has been set
+    pub fn get_parameter(&self, name: &str) -> Option<f32> {
+        let names = entity::get_component(self.0, animation_parameter_names())?;
+        let index = names.iter().position(|n| n == name)?;
+        let values =
+            entity::get_component(self.0, animation_parameter_values()).unwrap_or_default();
+        Some(values.get(index).copied().unwrap_or(0.))
+    }
     /// Despawn this animation player.
     /// Note that dropping this player won't despawn it automatically; only call this method will despawn it.
     pub fn despawn(self) {
@@ -193,6 +232,16 @@ impl PlayClipFromUrlNodeRef {
             entity::remove_component(self.0 .0, apply_base_pose());
         }
     }
+    /// Add an event at `time` seconds into the clip, in addition to the events of the clip itself.
+    /// An [AnimationEvent](crate::core::animation::messages::AnimationEvent) message is sent with
+    /// its `name` when the playback passes it.
+    pub fn add_event(&self, name: impl Into<String>, time: f32) {
+        let mut names =
+            entity::get_component(self.0 .0, animation_event_names()).unwrap_or_default();
+        set_padded_value(self.0 .0, animation_event_times(), names.len(), time, 0.);
+        names.push(name.into());
+        entity::add_component(self.0 .0, animation_event_names(), names);
+    }
     /// Returns None if the duration hasn't been loaded yet
     pub fn peek_clip_duration(&self) -> Option<f32> {
         entity::get_component(self.0 .0, clip_duration())
@@ -302,6 +351,255 @@ impl AsRef<AnimationNodeRef> for BlendNodeRef {
     }
 }
 
+/// Blend space animation node.
+/// This is an animation node which can be plugged into an animation player or other animation nodes.
+///
+/// It blends its animations by the value of a parameter of the animation player, such as the
+/// speed of a character blending between idle, walk and run.
+///
+/// This is just a reference to an entity which lives in the ecs. You need to call `despawn` to
+/// remove it.
+#[derive(Debug, Clone, Copy)]
+pub struct BlendSpaceNodeRef(pub AnimationNodeRef);
+impl BlendSpaceNodeRef {
+    /// Create a new blend space animation node, driven by the `parameter` of the animation player.
+    ///
+    /// Each animation plays on its own when the parameter is at its threshold, and the two
+    /// animations around the value of the parameter are blended in between, for example
+    /// `BlendSpaceNodeRef::new("speed", vec![(0., idle.0), (2., walk.0), (5., run.0)])`.
+    pub fn new(parameter: impl Into<String>, points: Vec<(f32, AnimationNodeRef)>) -> Self {
+        let (thresholds, nodes): (Vec<_>, Vec<_>) = points
+            .into_iter()
+            .map(|(threshold, node)| (threshold, node.0))
+            .unzip();
+        let node = Entity::new()
+            .with(blend_space_parameter(), parameter.into())
+            .with(blend_space_thresholds(), thresholds)
+            .with(name(), "Blend space".to_string())
+            .with(children(), nodes.clone())
+            .spawn();
+        for child in nodes {
+            entity::add_component(child, parent(), node);
+        }
+        Self(AnimationNodeRef(node))
+    }
+    /// Use an existing node
+    pub fn from_entity(entity: EntityId) -> Self {
+        Self(AnimationNodeRef::from_entity(entity))
+    }
+}
+impl AsRef<AnimationNodeRef> for BlendSpaceNodeRef {
+    fn as_ref(&self) -> &AnimationNodeRef {
+        &self.0
+    }
+}
+
+/// State machine animation node.
+/// This is an animation node which can be plugged into an animation player or other animation nodes.
+///
+/// It plays the animation of its current state, and blends to other states when the conditions of
+/// its transitions are met by the parameters of the animation player. It starts in the first state.
+///
+/// This is just a reference to an entity which lives in the ecs. You need to call `despawn` to
+/// remove it.
+#[derive(Debug, Clone, Copy)]
+pub struct StateMachineNodeRef(pub AnimationNodeRef);
+impl StateMachineNodeRef {
+    /// Create a new state machine animation node, with the name and animation of each state, for
+    /// example `StateMachineNodeRef::new(vec![("locomotion", locomotion.0), ("jump", jump.0)])`.
+    pub fn new(states: Vec<(&str, AnimationNodeRef)>) -> Self {
+        let (states, nodes): (Vec<_>, Vec<_>) = states
+            .into_iter()
+            .map(|(state, node)| (state.to_string(), node.0))
+            .unzip();
+        let node = Entity::new()
+            .with(state_machine_states(), states)
+            .with(name(), "State machine".to_string())
+            .with(children(), nodes.clone())
+            .spawn();
+        for child in nodes {
+            entity::add_component(child, parent(), node);
+        }
+        Self(AnimationNodeRef(node))
+    }
+    /// Use an existing node
+    pub fn from_entity(entity: EntityId) -> Self {
+        Self(AnimationNodeRef::from_entity(entity))
+    }
+    /// Add a transition to this state machine. Transitions are tested in the order they were
+    /// added, and the first one from the current state whose condition is met is taken.
+    pub fn add_transition(&self, transition: AnimationTransition) {
+        let id = self.0 .0;
+        let (parameter, min, max) = match transition.condition {
+            AnimationCondition::Always => (String::new(), f32::NEG_INFINITY, f32::INFINITY),
+            AnimationCondition::Range {
+                parameter,
+                min,
+                max,
+            } => (parameter, min, max),
+        };
+        let mut to = entity::get_component(id, state_machine_transition_to()).unwrap_or_default();
+        let index = to.len();
+        to.push(transition.to);
+        entity::add_component(id, state_machine_transition_to(), to);
+
+        // The other components may be shorter than `state_machine_transition_to`, and are padded
+        // with their defaults
+        let from = transition.from.unwrap_or_default();
+        set_padded_value(
+            id,
+            state_machine_transition_from(),
+            index,
+            from,
+            String::new(),
+        );
+        let duration = transition.blend_duration;
+        set_padded_value(
+            id,
+            state_machine_transition_durations(),
+            index,
+            duration,
+            0.,
+        );
+        set_padded_value(
+            id,
+            state_machine_transition_parameters(),
+            index,
+            parameter,
+            String::new(),
+        );
+        set_padded_value(
+            id,
+            state_machine_transition_min(),
+            index,
+            min,
+            f32::NEG_INFINITY,
+        );
+        set_padded_value(
+            id,
+            state_machine_transition_max(),
+            index,
+            max,
+            f32::INFINITY,
+        );
+    }
+    /// Get the current state of this state machine. This is only available where the animation
+    /// is played, which is usually the client.
+    pub fn current_state(&self) -> Option<String> {
+        entity::get_component(self.0 .0, state_machine_state())
+    }
+}
+impl AsRef<AnimationNodeRef> for StateMachineNodeRef {
+    fn as_ref(&self) -> &AnimationNodeRef {
+        &self.0
+    }
+}
+
+/// Sets the value at `index` of a list component, which is padded with `default` or truncated
+/// to `index` first.
+fn set_padded_value<T: Clone>(
+    id: EntityId,
+    component: Component<Vec<T>>,
+    index: usize,
+    value: T,
+    default: T,
+) where
+    Vec<T>: SupportedValue,
+{
+    let mut values = entity::get_component(id, component).unwrap_or_default();
+    values.resize(index, default);
+    values.push(value);
+    entity::add_component(id, component, values);
+}
+
+/// A transition between the states of a [StateMachineNodeRef].
+#[derive(Debug, Clone)]
+pub struct AnimationTransition {
+    /// The state that the transition can be taken from, or any state if `None`
+    pub from: Option<String>,
+    /// The state that the transition goes to
+    pub to: String,
+    /// The time in seconds over which the previous state is blended into the next one
+    pub blend_duration: f32,
+    /// When the transition is taken
+    pub condition: AnimationCondition,
+}
+impl AnimationTransition {
+    /// Create a transition from the state `from` to the state `to`, which is always taken and
+    /// doesn't blend
+    pub fn new(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: Some(from.into()),
+            to: to.into(),
+            blend_duration: 0.,
+            condition: AnimationCondition::Always,
+        }
+    }
+    /// Create a transition from any state to the state `to`, which is always taken and doesn't
+    /// blend
+    pub fn from_any(to: impl Into<String>) -> Self {
+        Self {
+            from: None,
+            to: to.into(),
+            blend_duration: 0.,
+            condition: AnimationCondition::Always,
+        }
+    }
+    /// Set the time in seconds over which the previous state is blended into the next one
+    pub fn blend_duration(mut self, seconds: f32) -> Self {
+        self.blend_duration = seconds;
+        self
+    }
+    /// Set when the transition is taken
+    pub fn when(mut self, condition: AnimationCondition) -> Self {
+        self.condition = condition;
+        self
+    }
+}
+
+/// The condition of an [AnimationTransition], which tests the parameters of the animation player.
+/// Parameters that haven't been set are 0.
+#[derive(Debug, Clone)]
+pub enum AnimationCondition {
+    /// The transition is always taken
+    Always,
+    /// The transition is taken when the parameter is between `min` and `max`, inclusive
+    Range {
+        /// The name of the parameter
+        parameter: String,
+        /// The lowest value of the parameter
+        min: f32,
+        /// The highest value of the parameter
+        max: f32,
+    },
+}
+impl AnimationCondition {
+    /// The parameter is at least `value`
+    pub fn at_least(parameter: impl Into<String>, value: f32) -> Self {
+        Self::Range {
+            parameter: parameter.into(),
+            min: value,
+            max: f32::INFINITY,
+        }
+    }
+    /// The parameter is at most `value`
+    pub fn at_most(parameter: impl Into<String>, value: f32) -> Self {
+        Self::Range {
+            parameter: parameter.into(),
+            min: f32::NEG_INFINITY,
+            max: value,
+        }
+    }
+    /// The boolean parameter is true
+    pub fn is_true(parameter: impl Into<String>) -> Self {
+        Self::at_least(parameter, 0.5)
+    }
+    /// The boolean parameter is false
+    pub fn is_false(parameter: impl Into<String>) -> Self {
+        Self::at_most(parameter, 0.5)
+    }
+}
+
 /// Animation retargeting configuration.
 #[derive(Debug, Clone)]
 pub enum AnimationRetargeting {
@@ -1,7 +1,7 @@
 #[cfg(feature = "client")]
 mod client {
     use crate::{
-        core::camera::components::active_camera,
+        core::{camera::components::active_camera, post_processing::types::Tonemapping},
         ecs::{query, Component, GeneralQuery},
         entity,
         global::{EntityId, Ray, Vec2, Vec3},
@@ -45,6 +45,78 @@ mod client {
             .from_bindgen()
     }
 
+    /// The post-processing of a camera. It is stored in the components of the camera, such as
+    /// `bloom_intensity` and `fxaa`, so it can also be set on the server.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct PostProcessing {
+        /// How bright a color must be to glow with `bloom_intensity`, from 0 to 1.
+        pub bloom_threshold: f32,
+        /// How strongly the bright parts of the image glow. Bloom is disabled when this is 0.
+        pub bloom_intensity: f32,
+        /// How colors brighter than white are mapped to the range of the screen.
+        pub tonemapping: Tonemapping,
+        /// How much the edges of the image are darkened, from 0 to 1.
+        pub vignette_intensity: f32,
+        /// How far the red and blue channels are shifted apart at the corners of the image, as a
+        /// fraction of its size.
+        pub chromatic_aberration: f32,
+        /// Whether the edges in the image are smoothed with fast approximate anti-aliasing.
+        pub fxaa: bool,
+    }
+    impl Default for PostProcessing {
+        fn default() -> Self {
+            Self {
+                bloom_threshold: 0.8,
+                bloom_intensity: 0.,
+                tonemapping: Tonemapping::Clamp,
+                vignette_intensity: 0.,
+                chromatic_aberration: 0.,
+                fxaa: false,
+            }
+        }
+    }
+
+    /// Gets the post-processing of `camera`. Effects that are disabled are set to their defaults.
+    pub fn get_post_processing(camera: EntityId) -> PostProcessing {
+        use wit::client_camera::Tonemapping as WitTonemapping;
+
+        let settings = wit::client_camera::get_post_processing(camera.into_bindgen());
+        PostProcessing {
+            bloom_threshold: settings.bloom_threshold,
+            bloom_intensity: settings.bloom_intensity,
+            tonemapping: match settings.tonemapping {
+                WitTonemapping::Clamp => Tonemapping::Clamp,
+                WitTonemapping::Reinhard => Tonemapping::Reinhard,
+                WitTonemapping::Aces => Tonemapping::Aces,
+            },
+            vignette_intensity: settings.vignette_intensity,
+            chromatic_aberration: settings.chromatic_aberration,
+            fxaa: settings.fxaa,
+        }
+    }
+
+    /// Sets the post-processing of `camera` on this client, which takes effect on the next
+    /// frame. The components of the effects that are disabled are removed.
+    pub fn set_post_processing(camera: EntityId, settings: PostProcessing) {
+        use wit::client_camera::Tonemapping as WitTonemapping;
+
+        wit::client_camera::set_post_processing(
+            camera.into_bindgen(),
+            wit::client_camera::PostProcessing {
+                bloom_threshold: settings.bloom_threshold,
+                bloom_intensity: settings.bloom_intensity,
+                tonemapping: match settings.tonemapping {
+                    Tonemapping::Clamp => WitTonemapping::Clamp,
+                    Tonemapping::Reinhard => WitTonemapping::Reinhard,
+                    Tonemapping::Aces => WitTonemapping::Aces,
+                },
+                vignette_intensity: settings.vignette_intensity,
+                chromatic_aberration: settings.chromatic_aberration,
+                fxaa: settings.fxaa,
+            },
+        );
+    }
+
     /// Get the active camera.
     // TODO: consider moving this to the host
     pub fn get_active() -> Option<EntityId> {
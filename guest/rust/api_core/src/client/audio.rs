@@ -9,7 +9,7 @@ use crate::{
         transform::components::translation,
     },
     entity,
-    prelude::{game_time, Entity, EntityId, Vec3},
+    prelude::{game_time, Entity, EntityId, Vec2, Vec3},
 };
 
 /// stop the audio on the given entity
@@ -21,6 +21,39 @@ pub fn stop(entity: EntityId) {
     }
 }
 
+/// Pause the playing sound `sound`, which can be resumed with [resume]
+pub fn pause(sound: EntityId) {
+    entity::add_component(sound, paused(), true);
+}
+
+/// Resume the playing sound `sound` from where it was paused
+pub fn resume(sound: EntityId) {
+    entity::add_component(sound, paused(), false);
+}
+
+/// Make the playing sound `sound` jump to `time`. The time of a looping sound wraps around its
+/// length
+pub fn seek(sound: EntityId, time: Duration) {
+    entity::add_component(sound, seek_now(), time);
+}
+
+/// Set the rate the playing sound `sound` is played at, which changes both its pitch and its
+/// speed. 2.0 is an octave up, 0.5 is an octave down
+pub fn set_pitch(sound: EntityId, pitch: f32) {
+    entity::add_component(sound, crate::core::audio::components::pitch(), pitch);
+}
+
+/// Set the panning of the playing sound `sound`. -1.0 is 100% left, 1.0 is 100% right
+pub fn set_panning(sound: EntityId, pan: f32) {
+    entity::add_component(sound, panning(), pan);
+}
+
+/// Route the playing sound `sound` to the [AudioBus] `bus_name`. If there is no bus with that
+/// name, the sound plays on the main output
+pub fn route(sound: EntityId, bus_name: impl Into<String>) {
+    entity::add_component(sound, audio_bus(), bus_name.into());
+}
+
 /// play spatial audio
 #[derive(Debug, Clone)]
 pub struct SpatialAudioPlayer {
@@ -43,6 +76,8 @@ impl SpatialAudioPlayer {
         Self { player }
     }
 
+    /// Sets the entity that hears the sounds of this player. Without one, the entity with a
+    /// `main_audio_listener` component, or else the active camera, hears them.
     pub fn set_listener(&self, listener: EntityId) {
         entity::add_component(self.player, spatial_audio_listener(), listener);
     }
@@ -69,12 +104,14 @@ impl SpatialAudioPlayer {
     /// Plays a sound at the given position. Note that the returned [`SpatialAudioPlayer`]
     /// will be removed after 60 seconds.
     ///
-    /// If no camera is available as a listener, no player will be created.
+    /// The sound is heard by the entity with a `main_audio_listener` component, or else by the
+    /// active camera. If there is neither, no player will be created.
     // TODO: Should we encourage use of this API? It's temporary, but it's also a lot easier to use.
     pub fn oneshot(position: Vec3, url: impl Into<String>) -> Option<SpatialAudioPlayer> {
-        let Some(active_camera) = crate::camera::get_active() else {
-            return None;
-        };
+        let listener = entity::get_all(main_audio_listener())
+            .first()
+            .copied()
+            .or_else(crate::camera::get_active)?;
 
         let player = SpatialAudioPlayer::new();
         entity::add_component(player.player, translation(), position);
@@ -83,7 +120,7 @@ impl SpatialAudioPlayer {
             remove_at_game_time(),
             game_time() + Duration::from_secs(60),
         );
-        player.set_listener(active_camera);
+        player.set_listener(listener);
         player.play_sound_on_entity(url.into(), player.player);
 
         Some(player)
@@ -132,9 +169,33 @@ impl AudioPlayer {
     pub fn set_panning(&self, pan: f32) {
         entity::add_component(self.entity, panning(), pan);
     }
+    /// Route the sounds played from now on to the [AudioBus] `bus_name`, unless their audio event
+    /// has a bus.
+    pub fn set_bus(&self, bus_name: impl Into<String>) {
+        entity::add_component(self.entity, audio_bus(), bus_name.into());
+    }
+    /// Set the rate the sounds are played at, which changes both their pitch and their speed.
+    /// 2.0 is an octave up, 0.5 is an octave down.
+    pub fn set_pitch(&self, pitch: f32) {
+        entity::add_component(self.entity, crate::core::audio::components::pitch(), pitch);
+    }
     /// Play the sound, this will generate a new entity that represents the playing sound.
     pub fn play(&self, url: String) -> EntityId {
+        entity::remove_component(self.entity, audio_event());
         entity::add_component(self.entity, audio_url(), url);
+        self.spawn_playing_sound()
+    }
+    /// Play the event `event` of the audio event bank at `bank_url` (an `.audio_bank.json` file,
+    /// built from an `.audio_bank.toml` file). One of the samples of the event is picked, with a
+    /// random volume and pitch, and routed to the bus of the event.
+    ///
+    /// This will generate a new entity that represents the playing sound.
+    pub fn play_event(&self, bank_url: impl Into<String>, event: impl Into<String>) -> EntityId {
+        entity::add_component(self.entity, audio_event_bank_url(), bank_url.into());
+        entity::add_component(self.entity, audio_event(), event.into());
+        self.spawn_playing_sound()
+    }
+    fn spawn_playing_sound(&self) -> EntityId {
         entity::add_component(self.entity, play_now(), ());
         let id = Entity::new()
             .with(playing_sound(), ())
@@ -147,3 +208,54 @@ impl AudioPlayer {
         id
     }
 }
+
+/// A named audio bus, which the sounds of the audio events whose `bus` is its name, and the sounds
+/// routed with [AudioPlayer::set_bus] or [route], are routed to. Its volume and effects apply to
+/// all of the sounds routed to it.
+#[derive(Debug, Clone)]
+pub struct AudioBus {
+    /// The entity that represents the audio bus
+    pub entity: EntityId,
+}
+
+impl AudioBus {
+    /// Create the audio bus `bus_name`
+    pub fn new(bus_name: impl Into<String>) -> Self {
+        let bus_name = bus_name.into();
+        let bus = Entity::new()
+            .with(is_audio_bus(), ())
+            .with(name(), format!("Audio bus {bus_name}"))
+            .with(audio_bus(), bus_name)
+            .spawn();
+        Self { entity: bus }
+    }
+    /// Set the amp/volume of all of the sounds routed to the bus. 0.0 is 0%, 1.0 is 100%
+    pub fn set_amplitude(&self, amp: f32) {
+        entity::add_component(self.entity, amplitude(), amp);
+    }
+    /// Filter the sounds routed to the bus with a low pass filter, e.g. to muffle them
+    /// underwater. The bandwidth is in octaves
+    pub fn set_low_pass(&self, cutoff_freq: f32, bandwidth: f32) {
+        entity::add_component(self.entity, lpf(), Vec2::new(cutoff_freq, bandwidth));
+    }
+    /// Apply a three band equalizer to the sounds routed to the bus. The gains are in dB, and are
+    /// applied to a low shelf at 250 Hz, a peak at 1 kHz, and a high shelf at 4 kHz
+    pub fn set_equalizer(&self, low_gain: f32, mid_gain: f32, high_gain: f32) {
+        entity::add_component(
+            self.entity,
+            equalizer(),
+            Vec3::new(low_gain, mid_gain, high_gain),
+        );
+    }
+    /// Add a simple reverb to the sounds routed to the bus, e.g. for indoor spaces. The room size,
+    /// the damping of the high frequencies, and the wet/dry mix are from 0.0 to 1.0
+    pub fn set_reverb(&self, room_size: f32, damping: f32, mix: f32) {
+        entity::add_component(self.entity, reverb(), Vec3::new(room_size, damping, mix));
+    }
+    /// Remove the low pass filter, equalizer and reverb of the bus
+    pub fn clear_effects(&self) {
+        entity::remove_component(self.entity, lpf());
+        entity::remove_component(self.entity, equalizer());
+        entity::remove_component(self.entity, reverb());
+    }
+}
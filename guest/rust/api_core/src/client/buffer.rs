@@ -0,0 +1,21 @@
+use crate::global::ProceduralBufferHandle;
+use crate::internal::conversion::*;
+use crate::internal::wit;
+
+/// Creates a GPU buffer that contains `data`, which can be bound to compute shaders as a storage
+/// or uniform buffer.
+///
+/// The size of the buffer is the size of `data`, which must be a non-zero multiple of 4 bytes.
+pub fn create(data: &[u8]) -> Result<ProceduralBufferHandle, String> {
+    wit::client_buffer::create(data).map(|handle| handle.from_bindgen())
+}
+
+/// Writes `data` to the buffer, starting at `offset` bytes. Both the offset and the size of
+/// `data` must be multiples of 4 bytes.
+pub fn write(handle: ProceduralBufferHandle, offset: u64, data: &[u8]) -> Result<(), String> {
+    wit::client_buffer::write(handle.into_bindgen(), offset, data)
+}
+
+pub fn destroy(handle: ProceduralBufferHandle) {
+    wit::client_buffer::destroy(handle.into_bindgen());
+}
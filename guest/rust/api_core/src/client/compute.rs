@@ -0,0 +1,79 @@
+use crate::{
+    global::{
+        ProceduralBufferHandle, ProceduralComputeHandle, ProceduralSamplerHandle,
+        ProceduralTextureHandle, UVec3,
+    },
+    internal::{conversion::*, wit},
+};
+
+pub struct Descriptor {
+    /// The WGSL code of the compute shader. Its bindings must be in `@group(0)`.
+    pub shader: String,
+    /// The name of the `@compute` function of the shader.
+    pub entry_point: String,
+}
+
+impl IntoBindgen for &Descriptor {
+    type Item = wit::client_compute::Descriptor;
+
+    fn into_bindgen(self) -> Self::Item {
+        Self::Item {
+            shader: self.shader.clone(),
+            entry_point: self.entry_point.clone(),
+        }
+    }
+}
+
+/// A resource that is bound to a binding of a compute shader.
+#[derive(Clone, Copy, Debug)]
+pub enum Resource {
+    /// A `var<storage>` or `var<uniform>` binding.
+    Buffer(ProceduralBufferHandle),
+    /// A `texture_2d` binding, or a `texture_storage_2d` binding that the shader writes to.
+    Texture(ProceduralTextureHandle),
+    /// A `sampler` binding.
+    Sampler(ProceduralSamplerHandle),
+}
+
+impl IntoBindgen for Resource {
+    type Item = wit::client_compute::Resource;
+
+    fn into_bindgen(self) -> Self::Item {
+        match self {
+            Resource::Buffer(handle) => Self::Item::Buffer(handle.into_bindgen()),
+            Resource::Texture(handle) => Self::Item::Texture(handle.into_bindgen()),
+            Resource::Sampler(handle) => Self::Item::Sampler(handle.into_bindgen()),
+        }
+    }
+}
+
+/// Creates a compute pipeline from a WGSL shader.
+///
+/// Returns an error if the shader fails to compile, or doesn't have the entry point.
+pub fn create(desc: &Descriptor) -> Result<ProceduralComputeHandle, String> {
+    wit::client_compute::create(&desc.into_bindgen()).map(|handle| handle.from_bindgen())
+}
+
+/// Runs the compute pipeline on the GPU with `workgroups` workgroups, and the resources bound to
+/// the bindings of its shader by their binding index.
+///
+/// The dispatch is run before the next frame is rendered, so textures that it writes to can be
+/// used by materials in that frame.
+pub fn dispatch(
+    handle: ProceduralComputeHandle,
+    bindings: &[(u32, Resource)],
+    workgroups: UVec3,
+) -> Result<(), String> {
+    let bindings = bindings
+        .iter()
+        .map(|&(binding, resource)| wit::client_compute::Binding {
+            binding,
+            resource: resource.into_bindgen(),
+        })
+        .collect::<Vec<_>>();
+    wit::client_compute::dispatch(handle.into_bindgen(), &bindings, workgroups.into_bindgen())
+}
+
+pub fn destroy(handle: ProceduralComputeHandle) {
+    wit::client_compute::destroy(handle.into_bindgen());
+}
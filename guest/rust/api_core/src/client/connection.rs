@@ -0,0 +1,32 @@
+use crate::internal::wit;
+
+/// Connects to the server at `address` (e.g. `example.com:9000`, or `example.com` to use the
+/// default port), replacing the current world with the world of that server.
+///
+/// This is how a main menu started with `ambient run --menu` joins the server chosen by the
+/// player. The modules of this world are unloaded once the connection is made; the modules of
+/// the server's packages are loaded in the new world.
+///
+/// Returns an error if this client cannot switch servers (e.g. on the web).
+pub fn connect(address: &str) -> Result<(), String> {
+    wit::client_connection::connect(address)
+}
+
+/// Returns whether this world is connected to a server.
+///
+/// This is `false` in the world of a main menu started with `ambient run --menu`, which runs the
+/// client modules of the package without a server.
+pub fn is_connected() -> bool {
+    wit::client_connection::is_connected()
+}
+
+/// Pauses or resumes the server of a single-player game, which runs in the same process as
+/// this client when the game was started with `ambient run`.
+///
+/// While paused, the server does not step its simulation, so nothing changes in the world until
+/// it is resumed. The server resumes by itself if another player joins.
+///
+/// Returns an error if this client is not hosting the server, or if other players are connected.
+pub fn set_server_paused(paused: bool) -> Result<(), String> {
+    wit::client_connection::set_server_paused(paused)
+}
@@ -11,7 +11,7 @@ use crate::{
     prelude::ModuleMessage,
 };
 
-pub use ambient_shared_types::MouseButton;
+pub use ambient_shared_types::{GamepadButton, MouseButton, TouchPhase};
 
 /// Gets the local player's most recent raw input state.
 ///
@@ -39,6 +39,29 @@ pub fn set_cursor(icon: CursorIcon) {
     wit::client_input::set_cursor(icon.into_bindgen());
 }
 
+/// An image drawn in place of the cursor. See [set_cursor_image].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CursorImage {
+    /// The URL of the image, e.g. from `packages::this::assets::url`.
+    pub url: String,
+    /// The size of the image, in screen pixels.
+    pub size: Vec2,
+    /// The point of the image that is at the position of the cursor, in screen pixels from its
+    /// top-left corner.
+    pub hotspot: Vec2,
+}
+
+/// Draws an image in place of the cursor, or restores the cursor icon if `None`.
+///
+/// The image is hidden with the cursor by [set_cursor_visible].
+pub fn set_cursor_image(image: Option<CursorImage>) {
+    wit::client_input::set_cursor_image(image.map(|image| wit::client_input::CursorImage {
+        url: image.url,
+        size: image.size.into_bindgen(),
+        hotspot: image.hotspot.into_bindgen(),
+    }));
+}
+
 /// Sets the cursor's visibility.
 pub fn set_cursor_visible(visible: bool) {
     wit::client_input::set_cursor_visible(visible);
@@ -51,6 +74,17 @@ pub fn set_cursor_lock(locked: bool) {
     wit::client_input::set_cursor_lock(locked);
 }
 
+/// Keeps the cursor within the rectangle from `min` to `max`, in screen pixels, or releases it if
+/// `None`. This is useful for edge scrolling in windowed mode.
+///
+/// The cursor is also kept within the window, so releasing the confinement releases the lock of
+/// [set_cursor_lock].
+pub fn set_cursor_confinement(rect: Option<(Vec2, Vec2)>) {
+    wit::client_input::set_cursor_confinement(
+        rect.map(|(min, max)| (min.into_bindgen(), max.into_bindgen())),
+    );
+}
+
 /// Focus id of the "game"; i.e. 3D world rather than any UI element
 pub const GAME_FOCUS_ID: &str = "Game";
 
@@ -457,6 +491,128 @@ impl FromBindgen for wit::client_input::MouseButton {
     }
 }
 
+impl FromBindgen for wit::client_input::GamepadButton {
+    type Item = GamepadButton;
+
+    fn from_bindgen(self) -> Self::Item {
+        match self {
+            Self::South => Self::Item::South,
+            Self::East => Self::Item::East,
+            Self::North => Self::Item::North,
+            Self::West => Self::Item::West,
+            Self::LeftShoulder => Self::Item::LeftShoulder,
+            Self::LeftTrigger => Self::Item::LeftTrigger,
+            Self::RightShoulder => Self::Item::RightShoulder,
+            Self::RightTrigger => Self::Item::RightTrigger,
+            Self::Select => Self::Item::Select,
+            Self::Start => Self::Item::Start,
+            Self::Mode => Self::Item::Mode,
+            Self::LeftStick => Self::Item::LeftStick,
+            Self::RightStick => Self::Item::RightStick,
+            Self::DPadUp => Self::Item::DPadUp,
+            Self::DPadDown => Self::Item::DPadDown,
+            Self::DPadLeft => Self::Item::DPadLeft,
+            Self::DPadRight => Self::Item::DPadRight,
+        }
+    }
+}
+
+/// The state of a connected gamepad.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gamepad {
+    /// Identifies this gamepad for as long as it is connected. The same ID is sent in the
+    /// `GamepadConnected` and `GamepadDisconnected` messages.
+    pub id: u32,
+    /// The name of the gamepad, as reported by the operating system.
+    pub name: String,
+    /// All of the buttons being pressed this frame.
+    pub buttons: HashSet<GamepadButton>,
+    /// The position of the left stick, from -1 to 1 on each axis. Positive Y is up.
+    pub left_stick: Vec2,
+    /// The position of the right stick, from -1 to 1 on each axis. Positive Y is up.
+    pub right_stick: Vec2,
+    /// How far the left trigger is pressed, from 0 to 1.
+    pub left_trigger: f32,
+    /// How far the right trigger is pressed, from 0 to 1.
+    pub right_trigger: f32,
+}
+
+impl FromBindgen for wit::client_input::Gamepad {
+    type Item = Gamepad;
+
+    fn from_bindgen(self) -> Self::Item {
+        Self::Item {
+            id: self.id,
+            name: self.name,
+            buttons: self.buttons.into_iter().map(|b| b.from_bindgen()).collect(),
+            left_stick: self.left_stick.from_bindgen(),
+            right_stick: self.right_stick.from_bindgen(),
+            left_trigger: self.left_trigger,
+            right_trigger: self.right_trigger,
+        }
+    }
+}
+
+impl FromBindgen for wit::client_input::TouchPhase {
+    type Item = TouchPhase;
+
+    fn from_bindgen(self) -> Self::Item {
+        match self {
+            Self::Started => Self::Item::Started,
+            Self::Moved => Self::Item::Moved,
+            Self::Ended => Self::Item::Ended,
+            Self::Cancelled => Self::Item::Cancelled,
+        }
+    }
+}
+
+/// A finger or stylus touching the window.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Touch {
+    /// Identifies this touch until it has ended. The ID may be reused by later touches.
+    pub id: u64,
+    /// The phase of the touch as of its last event. Touches that have ended or been cancelled
+    /// are included for one frame.
+    pub phase: TouchPhase,
+    /// The position of the touch, in the same space as [Input::mouse_position].
+    pub position: Vec2,
+    /// How hard the screen is being pressed, from 0 to 1, if the device supports it.
+    pub pressure: Option<f32>,
+}
+
+impl FromBindgen for wit::client_input::Touch {
+    type Item = Touch;
+
+    fn from_bindgen(self) -> Self::Item {
+        Self::Item {
+            id: self.id,
+            phase: self.phase.from_bindgen(),
+            position: self.position.from_bindgen(),
+            pressure: self.pressure,
+        }
+    }
+}
+
+/// A movement of the mouse. See [Input::mouse_motion].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MouseMotion {
+    /// How far the mouse moved.
+    pub delta: Vec2,
+    /// When the mouse moved, in seconds since the start of the previous frame.
+    pub time: f32,
+}
+
+impl FromBindgen for wit::client_input::MouseMotion {
+    type Item = MouseMotion;
+
+    fn from_bindgen(self) -> Self::Item {
+        Self::Item {
+            delta: self.delta.from_bindgen(),
+            time: self.time,
+        }
+    }
+}
+
 /// The state of a player's raw input. Get these with [get] or [get_previous].
 #[derive(Clone, Debug, PartialEq)]
 pub struct Input {
@@ -468,10 +624,19 @@ pub struct Input {
     ///
     /// Use this for any kind of movement that should be relative to the mouse's position, such as camera rotation.
     pub mouse_delta: Vec2,
+    /// The movements of the mouse that add up to [Self::mouse_delta], in the order they happened.
+    ///
+    /// Each movement has the time it happened at, so that fast movements can be integrated
+    /// precisely however long the frame was.
+    pub mouse_motion: Vec<MouseMotion>,
     /// The current scroll position.
     pub mouse_wheel: f32,
     /// All of the mouse buttons being pressed this frame.
     pub mouse_buttons: HashSet<MouseButton>,
+    /// The connected gamepads, ordered by ID. They are only updated while the window is focused.
+    pub gamepads: Vec<Gamepad>,
+    /// The fingers or styluses touching the window, on devices with a touchscreen.
+    pub touches: Vec<Touch>,
 }
 
 impl FromBindgen for wit::client_input::Input {
@@ -481,12 +646,23 @@ impl FromBindgen for wit::client_input::Input {
             keys: self.keys.into_iter().map(|k| k.from_bindgen()).collect(),
             mouse_position: self.mouse_position.from_bindgen(),
             mouse_delta: self.mouse_delta.from_bindgen(),
+            mouse_motion: self
+                .mouse_motion
+                .into_iter()
+                .map(|m| m.from_bindgen())
+                .collect(),
             mouse_wheel: self.mouse_wheel,
             mouse_buttons: self
                 .mouse_buttons
                 .into_iter()
                 .map(|b| b.from_bindgen())
                 .collect(),
+            gamepads: self
+                .gamepads
+                .into_iter()
+                .map(|g| g.from_bindgen())
+                .collect(),
+            touches: self.touches.into_iter().map(|t| t.from_bindgen()).collect(),
         }
     }
 }
@@ -509,12 +685,35 @@ pub struct InputDelta {
     pub mouse_buttons: HashSet<MouseButton>,
     /// All of the mouse buttons that were released this frame.
     pub mouse_buttons_released: HashSet<MouseButton>,
+    /// All of the gamepad buttons that were pressed this frame, but not last frame, with the ID
+    /// of their gamepad.
+    pub gamepad_buttons: HashSet<(u32, GamepadButton)>,
+    /// All of the gamepad buttons that were released this frame, with the ID of their gamepad.
+    pub gamepad_buttons_released: HashSet<(u32, GamepadButton)>,
 }
 
 impl Input {
+    /// Returns the gamepad with the given `id`, if it is connected.
+    pub fn gamepad(&self, id: u32) -> Option<&Gamepad> {
+        self.gamepads.iter().find(|gamepad| gamepad.id == id)
+    }
+
+    /// Returns the touch with the given `id`, if it is touching the window or ended this frame.
+    pub fn touch(&self, id: u64) -> Option<&Touch> {
+        self.touches.iter().find(|touch| touch.id == id)
+    }
+
+    fn gamepad_buttons(&self) -> HashSet<(u32, GamepadButton)> {
+        self.gamepads
+            .iter()
+            .flat_map(|gamepad| gamepad.buttons.iter().map(|&button| (gamepad.id, button)))
+            .collect()
+    }
+
     /// Returns whether or not each input has changed from `previous` to this [Input].
     pub fn delta(&self, previous: &Input) -> InputDelta {
         let (p, c) = (previous, self);
+        let (p_gamepad_buttons, c_gamepad_buttons) = (p.gamepad_buttons(), c.gamepad_buttons());
 
         InputDelta {
             keys: &c.keys - &p.keys,
@@ -523,6 +722,8 @@ impl Input {
             mouse_wheel: c.mouse_wheel - p.mouse_wheel,
             mouse_buttons: &c.mouse_buttons - &p.mouse_buttons,
             mouse_buttons_released: &p.mouse_buttons - &c.mouse_buttons,
+            gamepad_buttons: &c_gamepad_buttons - &p_gamepad_buttons,
+            gamepad_buttons_released: &p_gamepad_buttons - &c_gamepad_buttons,
         }
     }
 }
@@ -1,4 +1,6 @@
-use crate::global::{ProceduralMaterialHandle, ProceduralSamplerHandle, ProceduralTextureHandle};
+use crate::global::{
+    ProceduralMaterialHandle, ProceduralSamplerHandle, ProceduralTextureHandle, Vec4,
+};
 use crate::internal::conversion::*;
 use crate::internal::wit;
 
@@ -29,6 +31,55 @@ pub fn create(desc: &Descriptor) -> ProceduralMaterialHandle {
     wit::client_material::create(desc.into_bindgen()).from_bindgen()
 }
 
+/// A material with a custom WGSL shader, which runs in the standard vertex pipeline.
+///
+/// See the [custom materials documentation](https://ambientrun.github.io/Ambient/reference/custom_materials.html).
+#[derive(Clone, Debug)]
+pub struct CustomDescriptor {
+    /// The WGSL code of the material, which must define
+    /// `fn get_material(in: MaterialInput) -> MaterialOutput`.
+    pub shader: String,
+    /// The parameters of the material, by their name. They are available to the shader as the
+    /// `vec4<f32>` fields of `material_params`.
+    pub params: Vec<(String, Vec4)>,
+    /// The textures of the material, by their name. They are available to the shader as
+    /// `texture_2d<f32>` variables with their name.
+    pub textures: Vec<(String, ProceduralTextureHandle)>,
+    /// The sampler of the textures, available to the shader as `material_sampler`.
+    pub sampler: ProceduralSamplerHandle,
+    pub transparent: bool,
+}
+
+impl IntoBindgen for &CustomDescriptor {
+    type Item = wit::client_material::CustomDescriptor;
+
+    fn into_bindgen(self) -> Self::Item {
+        Self::Item {
+            shader: self.shader.clone(),
+            params: self
+                .params
+                .iter()
+                .map(|(name, value)| (name.clone(), value.into_bindgen()))
+                .collect(),
+            textures: self
+                .textures
+                .iter()
+                .map(|(name, texture)| (name.clone(), texture.into_bindgen()))
+                .collect(),
+            sampler: self.sampler.into_bindgen(),
+            transparent: self.transparent,
+        }
+    }
+}
+
+/// Creates a material with a custom WGSL shader.
+///
+/// Returns an error if the parameters or textures are invalid, or if the shader fails to
+/// compile.
+pub fn create_custom(desc: &CustomDescriptor) -> Result<ProceduralMaterialHandle, String> {
+    wit::client_material::create_custom(&desc.into_bindgen()).map(|handle| handle.from_bindgen())
+}
+
 pub fn destroy(handle: ProceduralMaterialHandle) {
     wit::client_material::destroy(handle.into_bindgen());
 }
@@ -1,4 +1,4 @@
-use crate::global::{ProceduralMeshHandle, Vec2, Vec3};
+use crate::global::{ProceduralMeshHandle, UVec4, Vec2, Vec3, Vec4};
 use crate::internal::conversion::*;
 use crate::internal::wit;
 
@@ -23,10 +23,18 @@ impl IntoBindgen for Vertex {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct Descriptor<'a> {
     pub vertices: &'a [Vertex],
     pub indices: &'a [u32],
+    /// The indices of the four joints that each vertex is skinned to, or nothing if the mesh is
+    /// not skinned. If the mesh is skinned, there must be as many joints and weights as vertices.
+    ///
+    /// The matrices of the joints are set with the `joint_matrices` component of the entities
+    /// that use the mesh, and index into it.
+    pub joints: &'a [UVec4],
+    /// The weights of the joints of each vertex, or nothing if the mesh is not skinned.
+    pub weights: &'a [Vec4],
 }
 impl<'a> IntoBindgen for &'a Descriptor<'a> {
     type Item = wit::client_mesh::Descriptor;
@@ -35,6 +43,8 @@ impl<'a> IntoBindgen for &'a Descriptor<'a> {
         Self::Item {
             vertices: self.vertices.iter().map(|v| v.into_bindgen()).collect(),
             indices: self.indices.to_vec(),
+            joints: self.joints.iter().map(|j| j.into_bindgen()).collect(),
+            weights: self.weights.iter().map(|w| w.into_bindgen()).collect(),
         }
     }
 }
@@ -43,6 +53,15 @@ pub fn create(desc: &Descriptor) -> ProceduralMeshHandle {
     wit::client_mesh::create(&desc.into_bindgen()).from_bindgen()
 }
 
+/// Replaces the vertices and indices of the mesh, without creating a new mesh.
+///
+/// The entities that use the mesh keep using it, and their bounds are updated. The GPU buffers of
+/// the mesh are written in place if the new vertices and indices fit in the space the mesh already
+/// has, so this can be used for meshes that deform every frame, such as water surfaces.
+pub fn update(handle: ProceduralMeshHandle, desc: &Descriptor) -> Result<(), String> {
+    wit::client_mesh::update(handle.into_bindgen(), &desc.into_bindgen())
+}
+
 pub fn destroy(handle: ProceduralMeshHandle) {
     wit::client_mesh::destroy(handle.into_bindgen());
 }
@@ -0,0 +1,38 @@
+use crate::{
+    core::audio::components::microphone_capturing,
+    entity::{has_component, resources},
+    internal::wit,
+};
+
+/// The names of the input devices, such as microphones, that can be passed to [start_capture].
+pub fn input_devices() -> Vec<String> {
+    wit::client_microphone::input_devices()
+}
+
+/// Start capturing the input device named `device`, or the default one if it is `None`.
+///
+/// While it is captured, the mono samples captured during each frame are sent to the client
+/// modules in a [MicrophoneSamples](crate::core::audio::messages::MicrophoneSamples) message.
+///
+/// The user is asked to allow the microphone first, if they have not done so yet; nothing is
+/// captured until they do. See [is_allowed].
+pub fn start_capture(device: Option<&str>) {
+    wit::client_microphone::start_capture(device);
+}
+
+/// Stop capturing the input device, or cancel the capture that is waiting for the user to allow the
+/// microphone.
+pub fn stop_capture() {
+    wit::client_microphone::stop_capture();
+}
+
+/// Whether the input device is being captured.
+pub fn is_capturing() -> bool {
+    has_component(resources(), microphone_capturing())
+}
+
+/// Whether the user has allowed the microphone to be used, for capturing or for voice chat. The
+/// permission lasts until the game is closed.
+pub fn is_allowed() -> bool {
+    wit::client_microphone::is_allowed()
+}
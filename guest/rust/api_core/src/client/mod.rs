@@ -7,6 +7,24 @@ pub mod input;
 /// **\[Client-only\]** Manipulating the operating system's clipboard.
 pub mod clipboard;
 
+/// **\[Client-only\]** Voice chat with the other players.
+pub mod voice;
+
+/// **\[Client-only\]** Capturing the microphone.
+pub mod microphone;
+
+/// **\[Client-only\]** Bursts of particles from particle emitters.
+pub mod particles;
+
+/// **\[Client-only\]** Loading UI markup documents.
+pub mod ui_markup;
+
+/// **\[Client-only\]** Notifications shown on top of the game.
+pub mod notification;
+
+/// **\[Client-only\]** Connecting to servers from a main menu, and pausing single-player servers.
+pub mod connection;
+
 /// **\[Client-only\]** Window-specific functionality.
 pub mod window;
 
@@ -21,3 +39,9 @@ pub mod sampler;
 
 /// **\[Client-only\]** Procedural material generation.
 pub mod material;
+
+/// **\[Client-only\]** GPU buffers for compute shaders.
+pub mod buffer;
+
+/// **\[Client-only\]** Compute shaders that run on the GPU.
+pub mod compute;
@@ -0,0 +1,59 @@
+use crate::internal::{conversion::IntoBindgen, wit};
+
+/// How important a notification is, which decides its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Severity {
+    /// A neutral notification.
+    #[default]
+    Info,
+    /// Something succeeded.
+    Success,
+    /// Something may need the attention of the user.
+    Warning,
+    /// Something failed.
+    Error,
+}
+
+/// Shows a notification in the top-right corner of the window for `duration` seconds, and returns
+/// its ID.
+///
+/// `icon` is the URL of an image shown next to the text, such as
+/// `packages::this::assets::url("trophy.png")`. Notifications are queued and stacked by the host,
+/// so this can be called any number of times.
+///
+/// When the user clicks on the notification, it is dismissed and a
+/// [NotificationClicked](crate::core::messages::NotificationClicked) message with its ID is sent
+/// to this module.
+pub fn notify(
+    title: impl Into<String>,
+    body: impl Into<String>,
+    icon: Option<&str>,
+    duration: f32,
+    severity: Severity,
+) -> u64 {
+    wit::client_notification::notify(
+        &title.into(),
+        &body.into(),
+        icon,
+        duration,
+        severity.into_bindgen(),
+    )
+}
+
+/// Removes the notification with the given ID, whether it is shown or still queued.
+pub fn dismiss(id: u64) {
+    wit::client_notification::dismiss(id);
+}
+
+impl IntoBindgen for Severity {
+    type Item = wit::client_notification::Severity;
+
+    fn into_bindgen(self) -> Self::Item {
+        match self {
+            Severity::Info => Self::Item::Info,
+            Severity::Success => Self::Item::Success,
+            Severity::Warning => Self::Item::Warning,
+            Severity::Error => Self::Item::Error,
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use crate::{
+    global::EntityId,
+    internal::{conversion::IntoBindgen, wit},
+};
+
+/// Emits `count` particles at once from the particle `emitter` on the next frame, on top of
+/// the ones it emits at its `particle_rate`.
+///
+/// This is useful for one-off effects like explosions, impacts and muzzle flashes, which can use
+/// an emitter with a `particle_rate` of 0. Emitters are simulated on the client, so to burst from
+/// server code, send a message to the clients and burst from there.
+pub fn burst(emitter: EntityId, count: u32) {
+    wit::client_particles::burst(emitter.into_bindgen(), count);
+}
@@ -1,6 +1,8 @@
-use crate::global::ProceduralTextureHandle;
+use crate::core::messages::TextureRead;
+use crate::global::{ProceduralTextureHandle, UVec2};
 use crate::internal::conversion::*;
 use crate::internal::wit;
+use crate::prelude::wait_for_runtime_message;
 
 #[derive(Clone, Copy)]
 pub enum Format {
@@ -127,3 +129,42 @@ pub fn create_2d(desc: &Descriptor2D) -> ProceduralTextureHandle {
 pub fn destroy(handle: ProceduralTextureHandle) {
     wit::client_texture::destroy(handle.into_bindgen());
 }
+
+/// Replaces the pixels of the region of the texture that starts at `origin` and has the given
+/// `size` with `data`, without creating a new texture.
+///
+/// `data` must contain the pixels of the region in the format of the texture, row by row with no
+/// padding between the rows. This is much cheaper than destroying and recreating the texture, so
+/// it should be used for textures that are animated, such as every frame.
+pub fn update_2d(
+    handle: ProceduralTextureHandle,
+    origin: UVec2,
+    size: UVec2,
+    data: &[u8],
+) -> Result<(), String> {
+    wit::client_texture::update2d(
+        handle.into_bindgen(),
+        origin.into_bindgen(),
+        size.into_bindgen(),
+        data,
+    )
+}
+
+/// Copies the contents of the texture back from the GPU, and returns its pixels.
+///
+/// The pixels are in the format of the texture, row by row from the top left with no padding
+/// between the rows. This includes what compute shaders have written to the texture, and can be
+/// used with [screenshot_texture](crate::client::window::screenshot_texture) to read back the game
+/// screen without encoding it.
+///
+/// This must be used with `.await` in either an `async fn` or an `async` block.
+pub async fn read(handle: ProceduralTextureHandle) -> Result<Vec<u8>, String> {
+    let read_id = wit::client_texture::read(handle.into_bindgen());
+    let read =
+        wait_for_runtime_message(move |message: &TextureRead| message.read_id == read_id).await;
+
+    match read.error {
+        Some(error) => Err(error),
+        None => Ok(read.data),
+    }
+}
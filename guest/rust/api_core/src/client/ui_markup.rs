@@ -0,0 +1,49 @@
+pub use ambient_shared_types::markup::MarkupNode;
+
+use crate::{
+    core::ui::messages::UiMarkupLoaded,
+    internal::{conversion::FromBindgen, wit},
+    prelude::wait_for_runtime_message,
+};
+
+/// Parses a UI markup document.
+///
+/// Returns the nodes of the document, which can be turned into UI elements with the `Markup`
+/// element, or a description of the first syntax error in it.
+pub fn parse(source: &str) -> Result<Vec<MarkupNode>, String> {
+    wit::client_ui_markup::parse(source).map(|nodes| nodes.from_bindgen())
+}
+
+/// Loads and parses the UI markup document at `url`, such as
+/// `packages::this::assets::url("menu.html")`.
+///
+/// The document is downloaded each time it is loaded, so that edits to it are picked up without
+/// rebuilding the package.
+pub async fn load(url: impl Into<String>) -> Result<Vec<MarkupNode>, String> {
+    let url = url.into();
+    wit::client_ui_markup::load(&url);
+    let loaded = wait_for_runtime_message::<UiMarkupLoaded>({
+        let url = url.clone();
+        move |message| message.url == url
+    })
+    .await;
+
+    match loaded.source {
+        Some(source) => parse(&source),
+        None => Err(format!("Failed to load UI markup from {url}")),
+    }
+}
+
+impl FromBindgen for wit::client_ui_markup::MarkupNode {
+    type Item = MarkupNode;
+
+    fn from_bindgen(self) -> Self::Item {
+        MarkupNode {
+            parent: self.parent,
+            tag: self.tag,
+            attributes: self.attributes,
+            style: self.style,
+            text: self.text,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::{
+    core::audio::components::voice_capturing,
+    entity::{has_component, resources},
+    internal::wit,
+};
+
+/// Start capturing the microphone and sending it to the server for voice chat.
+///
+/// The server only forwards the voice of players that have a `voice_range`. The user is asked to
+/// allow the microphone first, if they have not done so yet; nothing is captured until they do.
+pub fn start_capture() {
+    wit::client_voice::start_capture();
+}
+
+/// Stop capturing the microphone, or cancel the capture that is waiting for the user to allow it.
+pub fn stop_capture() {
+    wit::client_voice::stop_capture();
+}
+
+/// Whether the microphone is being captured for voice chat.
+pub fn is_capturing() -> bool {
+    has_component(resources(), voice_capturing())
+}
@@ -1,6 +1,62 @@
-use crate::internal::wit;
+use crate::{
+    core::messages::ScreenshotCaptured,
+    global::ProceduralTextureHandle,
+    internal::{conversion::FromBindgen, wit},
+    prelude::wait_for_runtime_message,
+};
 
 /// Request that the window enters or exits fullscreen mode.
 pub fn set_fullscreen(fullscreen: bool) {
     wit::client_window::set_fullscreen(fullscreen)
 }
+
+/// What the client does when its window loses focus, e.g. when the player alt-tabs out of the
+/// game. Each part that is `None` keeps its current value, which defaults to the `[window]`
+/// settings of the player.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FocusLossBehavior {
+    /// Whether the window is minimized, if it is fullscreen.
+    pub minimize: Option<bool>,
+    /// Whether the audio is muted until the window regains focus.
+    pub mute_audio: Option<bool>,
+    /// Whether the cursor is unlocked and shown until the window regains focus.
+    pub release_cursor: Option<bool>,
+}
+
+/// Overrides what the client does when its window loses focus, e.g. so that music keeps playing
+/// in the background.
+pub fn set_focus_loss_behavior(behavior: FocusLossBehavior) {
+    wit::client_window::set_focus_loss_behavior(
+        behavior.minimize,
+        behavior.mute_audio,
+        behavior.release_cursor,
+    )
+}
+
+/// Captures the most recently rendered frame of the game, and returns it encoded as a PNG.
+///
+/// This is useful for photo modes, or for sharing the game. The UI of the game is included, but
+/// not the UI of the runtime, such as the debugger.
+///
+/// This must be used with `.await` in either an `async fn` or an `async` block.
+pub async fn screenshot() -> Result<Vec<u8>, String> {
+    let screenshot_id = wit::client_window::screenshot();
+    let captured = wait_for_runtime_message(move |message: &ScreenshotCaptured| {
+        message.screenshot_id == screenshot_id
+    })
+    .await;
+
+    match captured.error {
+        Some(error) => Err(error),
+        None => Ok(captured.png),
+    }
+}
+
+/// Copies the most recently rendered frame of the game into a new procedural texture, which can
+/// be used right away, such as in a material.
+///
+/// The texture stays on the GPU, so this is much cheaper than [screenshot]. It should be
+/// destroyed with [destroy](crate::client::texture::destroy) once it is no longer needed.
+pub fn screenshot_texture() -> Result<ProceduralTextureHandle, String> {
+    wit::client_window::screenshot_texture().map(|handle| handle.from_bindgen())
+}
@@ -1,5 +1,10 @@
+use thiserror::Error;
+
 use crate::{
-    core::hierarchy::components::{children, parent},
+    core::{
+        app::components::tags,
+        hierarchy::components::{children, parent},
+    },
     global::{EntityId, Vec3},
     internal::{
         component::{Component, Entity, SupportedValue, UntypedComponent},
@@ -87,6 +92,54 @@ pub fn in_area(position: Vec3, radius: f32) -> Vec<EntityId> {
     wit::entity::in_area(position.into_bindgen(), radius).from_bindgen()
 }
 
+/// Gets all of the entities with the given `tag` in their `tags`.
+///
+/// The host keeps an index of the tags, so this does not have to go through all of the entities.
+pub fn get_all_tagged(tag: &str) -> Vec<EntityId> {
+    wit::entity::get_all_tagged(tag).from_bindgen()
+}
+
+/// Gets the stable ID of `entity`, assigning one if it does not have one yet, or `None` if the
+/// entity does not exist.
+///
+/// Unlike the [EntityId], the stable ID of an entity stays the same across saves, loads and server
+/// restarts, so it should be used to refer to entities in persistent storage.
+pub fn get_stable_id(entity: EntityId) -> Option<String> {
+    wit::entity::get_stable_id(entity.into_bindgen())
+}
+
+/// Gets the entity with the given stable ID, if there is one.
+///
+/// The host keeps an index of the stable IDs, so this does not have to go through all of the
+/// entities.
+pub fn get_by_stable_id(stable_id: &str) -> Option<EntityId> {
+    wit::entity::get_by_stable_id(stable_id).from_bindgen()
+}
+
+/// Gets all of the entities with the given `tag` within `radius` of `position`.
+pub fn in_area_tagged(position: Vec3, radius: f32, tag: &str) -> Vec<EntityId> {
+    wit::entity::in_area_tagged(position.into_bindgen(), radius, tag).from_bindgen()
+}
+
+/// Adds `tag` to the `tags` of `entity`, if it does not have it already.
+pub fn add_tag(entity: EntityId, tag: &str) {
+    mutate_component_with_default(entity, tags(), vec![tag.to_string()], |tags| {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    });
+}
+
+/// Removes `tag` from the `tags` of `entity`.
+pub fn remove_tag(entity: EntityId, tag: &str) {
+    mutate_component(entity, tags(), |tags| tags.retain(|t| t != tag));
+}
+
+/// Checks if `entity` has `tag` in its `tags`.
+pub fn has_tag(entity: EntityId, tag: &str) -> bool {
+    get_component(entity, tags()).is_some_and(|tags| tags.iter().any(|t| t == tag))
+}
+
 /// Retrieves the component `component` for `entity` if it exists, or `None` if it doesn't.
 pub fn get_component<T: SupportedValue>(entity: EntityId, component: Component<T>) -> Option<T> {
     T::from_result(wit::component::get_component(
@@ -245,6 +298,184 @@ pub fn remove_child(entity: EntityId, child: EntityId) {
     remove_component(child, parent());
 }
 
+/// Returns a read-only view of the world as it was when this module started handling the current
+/// message or frame.
+///
+/// The changes this module makes to entities and components during the message are not visible in
+/// the snapshot, so callbacks that are run one after the other can all read the same state. Changes
+/// made by the host or through other APIs, such as physics, are not tracked.
+///
+/// Changes are tracked once this module has read a snapshot, from the start of every message after
+/// that. In the message in which it first reads one, the changes made before that read are visible.
+pub fn snapshot() -> Snapshot {
+    Snapshot(())
+}
+
+/// A read-only view of the world as it was when this module started handling the current message
+/// or frame. See [snapshot].
+#[derive(Clone, Copy, Debug)]
+pub struct Snapshot(());
+impl Snapshot {
+    /// Checks if the `entity` existed.
+    pub fn exists(&self, entity: EntityId) -> bool {
+        wit::entity::snapshot_exists(entity.into_bindgen())
+    }
+
+    /// Retrieves the component `component` that `entity` had, or `None` if it didn't have it.
+    pub fn get_component<T: SupportedValue>(
+        &self,
+        entity: EntityId,
+        component: Component<T>,
+    ) -> Option<T> {
+        T::from_result(wit::component::snapshot_get_component(
+            entity.into_bindgen(),
+            component.index(),
+        )?)
+    }
+
+    /// Checks if the `entity` had a `component`.
+    pub fn has_component<T: SupportedValue>(
+        &self,
+        entity: EntityId,
+        component: Component<T>,
+    ) -> bool {
+        wit::component::snapshot_has_component(entity.into_bindgen(), component.index())
+    }
+}
+
+/// Adds the component `component` for `entity` with `value` once this module has handled the
+/// current message or frame, instead of immediately.
+///
+/// Deferred changes are applied in the order they were made. Changes to entities that no longer
+/// exist by then are ignored.
+pub fn defer_add_component<T: SupportedValue>(entity: EntityId, component: Component<T>, value: T) {
+    defer_add_components(entity, Entity::new().with(component, value))
+}
+
+/// Adds the components `components` for `entity` once this module has handled the current message
+/// or frame. See [defer_add_component].
+pub fn defer_add_components(entity: EntityId, components: impl Into<Entity>) {
+    wit::component::defer_add_components(entity.into_bindgen(), &components.into().into_bindgen())
+}
+
+/// Sets the component `component` for `entity` with `value` once this module has handled the
+/// current message or frame. See [defer_add_component].
+pub fn defer_set_component<T: SupportedValue>(entity: EntityId, component: Component<T>, value: T) {
+    defer_set_components(entity, Entity::new().with(component, value))
+}
+
+/// Sets the components `components` for `entity` once this module has handled the current message
+/// or frame. See [defer_add_component].
+pub fn defer_set_components(entity: EntityId, components: Entity) {
+    wit::component::defer_set_components(entity.into_bindgen(), &components.into_bindgen())
+}
+
+/// Removes the `component` from `entity` once this module has handled the current message or
+/// frame. See [defer_add_component].
+pub fn defer_remove_component<T>(entity: EntityId, component: Component<T>) {
+    wit::component::defer_remove_components(entity.into_bindgen(), &[component.index()])
+}
+
+/// Removes the `components` from `entity` once this module has handled the current message or
+/// frame. See [defer_add_component].
+pub fn defer_remove_components(entity: EntityId, components: &[&dyn UntypedComponent]) {
+    let components: Vec<_> = components.iter().map(|c| c.index()).collect();
+    wit::component::defer_remove_components(entity.into_bindgen(), &components)
+}
+
+/// Despawns `entity` once this module has handled the current message or frame. See
+/// [defer_add_component].
+pub fn defer_despawn(entity: EntityId) {
+    wit::entity::defer_despawn(entity.into_bindgen())
+}
+
+/// A set of component changes across one or more entities, which are all applied at once by
+/// [Transaction::commit].
+///
+/// Queries, other modules and clients will see either all of the changes or none of them. This can
+/// be used to make sure that a teleported character is never seen at its new position with its old
+/// rotation, for example, even if the changes are made across `.await`s.
+#[derive(Clone, Debug, Default)]
+pub struct Transaction {
+    writes: Vec<(EntityId, Entity)>,
+    removals: Vec<(EntityId, Vec<u32>)>,
+}
+impl Transaction {
+    /// Creates an empty transaction.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the component `component` to `entity` with `value`, or replaces its current value.
+    pub fn set_component<T: SupportedValue>(
+        &mut self,
+        entity: EntityId,
+        component: Component<T>,
+        value: T,
+    ) -> &mut Self {
+        self.set_components(entity, Entity::new().with(component, value))
+    }
+
+    /// Adds the components `components` to `entity`, or replaces their current values.
+    pub fn set_components(&mut self, entity: EntityId, components: impl Into<Entity>) -> &mut Self {
+        let components = components.into();
+        // A later write cancels an earlier removal of the same component
+        if let Some((_, removed)) = self.removals.iter_mut().find(|(id, _)| *id == entity) {
+            removed.retain(|index| !components.0.contains_key(index));
+        }
+        match self.writes.iter_mut().find(|(id, _)| *id == entity) {
+            Some((_, written)) => written.merge(components),
+            None => self.writes.push((entity, components)),
+        }
+        self
+    }
+
+    /// Removes the `component` from `entity`. Does nothing if it does not have the component.
+    pub fn remove_component<T: SupportedValue>(
+        &mut self,
+        entity: EntityId,
+        component: Component<T>,
+    ) -> &mut Self {
+        // A later removal cancels an earlier write of the same component
+        if let Some((_, written)) = self.writes.iter_mut().find(|(id, _)| *id == entity) {
+            written.remove(component);
+        }
+        match self.removals.iter_mut().find(|(id, _)| *id == entity) {
+            Some((_, removed)) => removed.push(component.index()),
+            None => self.removals.push((entity, vec![component.index()])),
+        }
+        self
+    }
+
+    /// Checks if the transaction has no changes.
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty() && self.removals.is_empty()
+    }
+
+    /// Applies all of the changes at once.
+    ///
+    /// If any of the entities does not exist, none of the changes are applied and an error is
+    /// returned.
+    pub fn commit(self) -> Result<(), TransactionError> {
+        let writes: Vec<_> = self
+            .writes
+            .into_iter()
+            .map(|(id, data)| (id.into_bindgen(), data.into_bindgen()))
+            .collect();
+        let removals: Vec<_> = self
+            .removals
+            .into_iter()
+            .map(|(id, indices)| (id.into_bindgen(), indices))
+            .collect();
+        wit::component::apply_transaction(&writes, &removals).map_err(TransactionError)
+    }
+}
+
+/// The error returned when a [Transaction] could not be committed.
+#[derive(Error, Debug, Clone)]
+#[error("Transaction failed: {0}")]
+pub struct TransactionError(pub String);
+
 /// Gets the resource entity. The components of this entity contain global state for this ECS world.
 ///
 /// Components with the `Resource` attribute can be found here.
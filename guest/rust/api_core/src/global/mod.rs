@@ -13,6 +13,9 @@ pub use shapes::*;
 mod procedurals;
 pub use procedurals::*;
 
+mod shared_random;
+pub use shared_random::*;
+
 // Re-exports from other crates.
 pub use ambient_shared_types::{CursorIcon, ModifiersState, MouseButton, VirtualKeyCode};
 pub use futures::{Future, FutureExt};
@@ -10,7 +10,7 @@ use crate::{
     core::{app, messages::Frame},
     entity,
     global::{OkEmpty, ResultEmpty},
-    internal::executor::EXECUTOR,
+    internal::{conversion::FromBindgen, executor::EXECUTOR, wit},
     message::Listener,
     prelude::RuntimeMessage,
 };
@@ -25,6 +25,24 @@ pub fn epoch_time() -> Duration {
     entity::get_component(entity::resources(), app::components::epoch_time()).unwrap()
 }
 
+/// The current time, relative to Jan 1, 1970 UTC, read from the system clock when this is called.
+///
+/// Unlike [epoch_time], this is not the time at the start of the frame, and unlike [game_time], it
+/// keeps advancing when the game is paused or falls behind. Use it for timestamps, such as in a
+/// chat, and for events scheduled at a real date and time. Not guaranteed to be monotonic.
+pub fn utc_now() -> Duration {
+    wit::clock::now().from_bindgen()
+}
+
+/// The offset of the local timezone from UTC at the current time, in seconds east of UTC; add it
+/// to [utc_now] to get the local time.
+///
+/// On the client, this is the timezone of the player. On the server, this is the timezone of the
+/// machine it is running on, which is often UTC.
+pub fn local_utc_offset() -> i32 {
+    wit::clock::local_utc_offset()
+}
+
 /// The length of the previous frame, in seconds.
 pub fn delta_time() -> f32 {
     entity::get_component(entity::resources(), app::components::delta_time()).unwrap()
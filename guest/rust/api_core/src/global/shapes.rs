@@ -2,7 +2,10 @@ use super::EntityId;
 use crate::{
     core::transform::components::local_to_world,
     entity::get_component,
-    internal::{conversion::FromBindgen, wit},
+    internal::{
+        conversion::{FromBindgen, IntoBindgen},
+        wit,
+    },
 };
 use glam::{vec3, Vec3};
 
@@ -25,6 +28,15 @@ impl Ray {
     }
 }
 
+impl IntoBindgen for Ray {
+    type Item = wit::types::Ray;
+    fn into_bindgen(self) -> Self::Item {
+        wit::types::Ray {
+            origin: self.origin.into_bindgen(),
+            dir: self.dir.into_bindgen(),
+        }
+    }
+}
 impl FromBindgen for wit::types::Ray {
     type Item = Ray;
     fn from_bindgen(self) -> Self::Item {
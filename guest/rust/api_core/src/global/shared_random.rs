@@ -0,0 +1,128 @@
+use rand::{Error, RngCore};
+
+use crate::internal::wit;
+
+/// The number of values fetched from the host at once.
+const BATCH_SIZE: u32 = 32;
+
+/// The seed of the world, which the server chooses when the world is created and shares with
+/// its clients.
+pub fn world_seed() -> u64 {
+    wit::shared_random::world_seed()
+}
+
+/// The number of ticks the server has simulated in this world.
+///
+/// On the client, this is the tick of the latest state received from the server.
+pub fn server_tick() -> u64 {
+    wit::shared_random::server_tick()
+}
+
+/// A random number generator that produces the same sequence on the server and on the clients.
+///
+/// The sequence only depends on its seed and its stream, so code that creates a [SharedRng] with
+/// the same seed and stream, and reads the same number of values from it, gets the same values
+/// wherever it runs. This can be used for cosmetic effects that should look the same to every
+/// player, such as debris, or for gameplay that can be rolled back and replayed.
+///
+/// The generator is seekable: [SharedRng::seek] jumps to any position of the sequence, without
+/// generating the values before it. The values are generated by the host with a PCG generator
+/// (`Pcg64`).
+///
+/// This must not be used for anything where security matters, as the seed is known to every client.
+#[derive(Debug, Clone)]
+pub struct SharedRng {
+    seed: u64,
+    stream: u64,
+    /// The position of the first value of `buffer` in the sequence
+    buffer_position: u64,
+    buffer: Vec<u64>,
+    /// The index of the next value in `buffer`
+    index: usize,
+}
+impl SharedRng {
+    /// Creates a generator for the sequence of `seed` and `stream`, starting at its first value.
+    ///
+    /// Different streams of the same seed are independent sequences; this can be used to give
+    /// each system its own sequence.
+    pub fn new(seed: u64, stream: u64) -> Self {
+        Self {
+            seed,
+            stream,
+            buffer_position: 0,
+            buffer: Vec::new(),
+            index: 0,
+        }
+    }
+
+    /// Creates a generator seeded with the [world_seed].
+    pub fn for_world(stream: u64) -> Self {
+        Self::new(world_seed(), stream)
+    }
+
+    /// Creates a generator seeded with the [world_seed] and the given server tick, such as
+    /// [server_tick], so that each tick has its own sequence.
+    pub fn for_tick(tick: u64, stream: u64) -> Self {
+        Self::new(mix(world_seed() ^ mix(tick)), stream)
+    }
+
+    /// The position of the next value in the sequence; that is, the number of values read so far.
+    ///
+    /// Each call to [RngCore::next_u32] or [RngCore::next_u64] reads one value, and filling bytes
+    /// reads one value per 8 bytes.
+    pub fn position(&self) -> u64 {
+        self.buffer_position + self.index as u64
+    }
+
+    /// Moves to `position` in the sequence, so that the next value read is the one at `position`.
+    pub fn seek(&mut self, position: u64) {
+        self.buffer_position = position;
+        self.buffer.clear();
+        self.index = 0;
+    }
+
+    fn next_value(&mut self) -> u64 {
+        if self.index == self.buffer.len() {
+            self.buffer_position = self.position();
+            self.buffer = wit::shared_random::generate(
+                self.seed,
+                self.stream,
+                self.buffer_position,
+                BATCH_SIZE,
+            );
+            self.index = 0;
+        }
+        let value = self.buffer[self.index];
+        self.index += 1;
+        value
+    }
+}
+impl RngCore for SharedRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_value() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.next_value()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_value().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Scrambles the bits of `value` (SplitMix64), so that close seeds give unrelated sequences.
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
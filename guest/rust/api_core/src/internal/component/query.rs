@@ -101,6 +101,12 @@ impl<Components: ComponentsTuple + Copy + Clone + 'static> GeneralQueryBuilder<C
         self
     }
 
+    /// The entities must have `tag` in their `tags`.
+    pub fn tagged(mut self, tag: impl Into<String>) -> Self {
+        self.0.tagged(tag.into());
+        self
+    }
+
     /// Builds a [GeneralQuery].
     pub fn build(self) -> GeneralQuery<Components> {
         GeneralQuery(QueryImpl::new(
@@ -164,6 +170,12 @@ impl<Components: ComponentsTuple + Copy + Clone + 'static> ChangeQuery<Component
         self
     }
 
+    /// The entities must have `tag` in their `tags`.
+    pub fn tagged(mut self, tag: impl Into<String>) -> Self {
+        self.0 .0.tagged(tag.into());
+        self
+    }
+
     /// The query will return results when these components change values.
     ///
     /// Note that this does *not* implicitly [requires](Self::requires) the components; this allows you to track
@@ -215,6 +227,12 @@ impl<Components: ComponentsTuple + Copy + Clone + 'static> EventQuery<Components
         self
     }
 
+    /// The entities must have `tag` in their `tags`.
+    pub fn tagged(mut self, tag: impl Into<String>) -> Self {
+        self.0.tagged(tag.into());
+        self
+    }
+
     /// Each time the entity associated with `components` experiences the event,
     /// the `callback` (`fn`) is called with the result of the query.
     pub fn bind<R: CallbackReturn>(
@@ -276,6 +294,7 @@ struct QueryBuilderImpl<Components: ComponentsTuple + Copy + Clone + 'static> {
     components: Vec<ComponentIndex>,
     include: Vec<ComponentIndex>,
     exclude: Vec<ComponentIndex>,
+    tags: Vec<String>,
     _data: PhantomData<Components>,
 }
 impl<Components: ComponentsTuple + Copy + Clone + 'static> QueryBuilderImpl<Components> {
@@ -284,6 +303,7 @@ impl<Components: ComponentsTuple + Copy + Clone + 'static> QueryBuilderImpl<Comp
             components,
             include: vec![],
             exclude: vec![],
+            tags: vec![],
             _data: PhantomData,
         }
     }
@@ -293,6 +313,9 @@ impl<Components: ComponentsTuple + Copy + Clone + 'static> QueryBuilderImpl<Comp
     pub fn excludes(&mut self, exclude: impl ComponentsTuple) {
         self.exclude.extend_from_slice(&exclude.as_indices());
     }
+    pub fn tagged(&mut self, tag: String) {
+        self.tags.push(tag);
+    }
     fn build_impl(self, changed: Vec<ComponentIndex>, event: wit::component::QueryEvent) -> u64 {
         wit::component::query(
             &wit::component::QueryBuild {
@@ -300,6 +323,7 @@ impl<Components: ComponentsTuple + Copy + Clone + 'static> QueryBuilderImpl<Comp
                 includes: self.include,
                 excludes: self.exclude,
                 changed,
+                tags: self.tags,
             },
             event,
         )
@@ -2,9 +2,9 @@ use std::time::Duration;
 
 use crate::{
     global::{
-        IVec2, IVec3, IVec4, Mat4, ProceduralMaterialHandle, ProceduralMeshHandle,
-        ProceduralSamplerHandle, ProceduralTextureHandle, Quat, UVec2, UVec3, UVec4, Vec2, Vec3,
-        Vec4,
+        IVec2, IVec3, IVec4, Mat4, ProceduralBufferHandle, ProceduralComputeHandle,
+        ProceduralMaterialHandle, ProceduralMeshHandle, ProceduralSamplerHandle,
+        ProceduralTextureHandle, Quat, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4,
     },
     internal::wit,
 };
@@ -15,6 +15,8 @@ pub mod client;
 #[doc(hidden)]
 pub mod server;
 
+/// Usage analytics for your package.
+pub mod analytics;
 /// Retrieval of assets and where to find them.
 pub mod asset;
 /// Helper functions for the camera.
@@ -105,7 +105,8 @@ pub enum Target {
     // Client
     /// An unreliable transmission to the server.
     ///
-    /// Not guaranteed to be received, and must be below one kilobyte.
+    /// Not guaranteed to be received. Messages larger than a datagram are split into several
+    /// datagrams, and are lost if any of them is lost. They cannot be larger than 64 kilobytes.
     ///
     /// Unreliable messages are implemented using QUIC datagrams. This makes them ideal
     /// for messages that are sent frequently, but are not critical to the functioning
@@ -120,6 +121,7 @@ pub enum Target {
     ///
     /// Reliable messages are implemented using QUIC streams. This makes them ideal
     /// for messages that are sent infrequently, but must be received by the server.
+    /// They cannot be larger than 10 megabytes.
     ///
     /// Note that this message will only be received by the corresponding package
     /// on the server, and not by any other packages. You will need to explicitly
@@ -130,7 +132,8 @@ pub enum Target {
     // Server
     /// An unreliable transmission to all clients.
     ///
-    /// Not guaranteed to be received, and must be below one kilobyte.
+    /// Not guaranteed to be received. Messages larger than a datagram are split into several
+    /// datagrams, and are lost if any of them is lost. They cannot be larger than 64 kilobytes.
     ///
     /// Unreliable messages are implemented using QUIC datagrams. This makes them ideal
     /// for messages that are sent frequently, but are not critical to the functioning
@@ -145,6 +148,7 @@ pub enum Target {
     ///
     /// Reliable messages are implemented using QUIC streams. This makes them ideal
     /// for messages that are sent infrequently, but must be received by the client.
+    /// They cannot be larger than 10 megabytes.
     ///
     /// Note that this message will only be received by the corresponding package
     /// on the client, and not by any other packages. You will need to explicitly
@@ -153,7 +157,8 @@ pub enum Target {
     ClientBroadcastReliable,
     /// An unreliable transmission to a specific client.
     ///
-    /// Not guaranteed to be received, and must be below one kilobyte.
+    /// Not guaranteed to be received. Messages larger than a datagram are split into several
+    /// datagrams, and are lost if any of them is lost. They cannot be larger than 64 kilobytes.
     ///
     /// Unreliable messages are implemented using QUIC datagrams. This makes them ideal
     /// for messages that are sent frequently, but are not critical to the functioning
@@ -171,6 +176,7 @@ pub enum Target {
     ///
     /// Reliable messages are implemented using QUIC streams. This makes them ideal
     /// for messages that are sent infrequently, but must be received by the client.
+    /// They cannot be larger than 10 megabytes.
     ///
     /// Note that this message will only be received by the corresponding package
     /// on the client, and not by any other packages. You will need to explicitly
@@ -220,25 +226,108 @@ impl<'a> IntoBindgen for &'a Target {
     }
 }
 
+#[cfg(feature = "server")]
+#[derive(Clone, Copy, PartialEq, Debug)]
+/// What a message sent with [send_interested] is about.
+pub enum Topic {
+    /// An entity, such as one that an effect is attached to.
+    ///
+    /// The clients that are interested in the entity, or in an area that contains its
+    /// `translation`, receive the message.
+    Entity(EntityId),
+    /// A position in the world, such as that of an explosion.
+    ///
+    /// The clients that are interested in an area that contains the position receive the message.
+    Position(crate::global::Vec3),
+}
+
+#[cfg(feature = "server")]
+/// Sends a message from this package to the clients that are interested in `topic`, as set with
+/// `set_interests` on the client. This avoids sending localized events, such as effects, to
+/// clients that would ignore them.
+///
+/// `reliable` is the same as for [Target::ClientBroadcastReliable] and
+/// [Target::ClientBroadcastUnreliable]. Errors are printed. Use [try_send_interested] to handle
+/// them instead.
+pub fn send_interested<T: Message>(topic: Topic, reliable: bool, data: &T) {
+    if let Err(err) = try_send_interested(topic, reliable, data) {
+        eprintln!("Failed to send message: {err}");
+    }
+}
+
+#[cfg(feature = "server")]
+/// Sends a message from this package to the clients that are interested in `topic`. See
+/// [send_interested].
+///
+/// Returns an error if the message could not be sent, such as when it is too large.
+pub fn try_send_interested<T: Message>(
+    topic: Topic,
+    reliable: bool,
+    data: &T,
+) -> Result<(), String> {
+    use wit::server_message::{Target as WitTarget, Topic as WitTopic};
+
+    let data = data.serialize_message().map_err(|err| err.to_string())?;
+    let topic = match topic {
+        Topic::Entity(id) => WitTopic::Entity(id.into_bindgen()),
+        Topic::Position(position) => WitTopic::Position(position.into_bindgen()),
+    };
+    let target = if reliable {
+        WitTarget::ClientInterestedReliable(topic)
+    } else {
+        WitTarget::ClientInterestedUnreliable(topic)
+    };
+    wit::server_message::send(&target, T::id(), &data)
+}
+
+#[cfg(feature = "client")]
+/// Sets the entities and areas that this client wants to receive the messages about, when the
+/// server sends them with `send_interested`. Each area is a sphere, given by its center and
+/// radius. These replace the interests that were set before, including by other packages.
+///
+/// Returns an error if there are too many interests to send to the server.
+pub fn set_interests(
+    entities: &[EntityId],
+    areas: &[(crate::global::Vec3, f32)],
+) -> Result<(), String> {
+    let entities = entities
+        .iter()
+        .map(|id| id.into_bindgen())
+        .collect::<Vec<_>>();
+    let areas = areas
+        .iter()
+        .map(|(center, radius)| (center.into_bindgen(), *radius))
+        .collect::<Vec<_>>();
+    wit::client_message::set_interests(&entities, &areas)
+}
+
 /// Send a message from this package to a specific `target`.
+///
+/// Errors, such as the message being too large for `target`, are printed. Use [try_send] to
+/// handle them instead.
 pub fn send<T: Message>(target: Target, data: &T) {
+    if let Err(err) = try_send(target, data) {
+        eprintln!("Failed to send message: {err}");
+    }
+}
+
+/// Send a message from this package to a specific `target`.
+///
+/// Returns an error if the message could not be sent, such as when it is too large for `target`.
+pub fn try_send<T: Message>(target: Target, data: &T) -> Result<(), String> {
+    let data = data.serialize_message().map_err(|err| err.to_string())?;
+
     #[cfg(all(feature = "client", not(feature = "server")))]
-    wit::client_message::send(
-        target.into_bindgen(),
-        T::id(),
-        &data.serialize_message().unwrap(),
-    );
+    wit::client_message::send(target.into_bindgen(), T::id(), &data)?;
     #[cfg(all(feature = "server", not(feature = "client")))]
-    wit::server_message::send(
-        &(&target).into_bindgen(),
-        T::id(),
-        &data.serialize_message().unwrap(),
-    );
+    wit::server_message::send(&(&target).into_bindgen(), T::id(), &data)?;
     #[cfg(any(
         all(not(feature = "server"), not(feature = "client")),
         all(feature = "server", feature = "client")
     ))]
     let _ = (target, data);
+
+    Ok(())
 }
 
 /// Handle to a message listener that can be used to stop listening.
@@ -333,6 +422,12 @@ pub trait ModuleMessage: Message {
         self::send(target, self)
     }
 
+    /// Sends this [Message] to `target`, and returns an error if it could not be sent.
+    /// Wrapper around [self::try_send].
+    fn try_send(&self, target: Target) -> Result<(), String> {
+        self::try_send(target, self)
+    }
+
     /// Sends a message to every package on this side.
     ///
     /// `include_self` controls whether or not the message is sent to the package that originally sent the message.
@@ -417,6 +512,20 @@ pub trait ModuleMessage: Message {
         self.send(Target::ClientTargetedReliable(user_id))
     }
 
+    #[cfg(feature = "server")]
+    /// Sends an unreliable message to the clients that are interested in `topic`. Wrapper around
+    /// [self::send_interested].
+    fn send_client_interested_unreliable(&self, topic: Topic) {
+        self::send_interested(topic, false, self)
+    }
+
+    #[cfg(feature = "server")]
+    /// Sends a reliable message to the clients that are interested in `topic`. Wrapper around
+    /// [self::send_interested].
+    fn send_client_interested_reliable(&self, topic: Topic) {
+        self::send_interested(topic, true, self)
+    }
+
     /// Subscribes to this [Message]. Wrapper around [self::subscribe].
     fn subscribe<R: CallbackReturn>(
         callback: impl FnMut(MessageContext, Self) -> R + 'static,
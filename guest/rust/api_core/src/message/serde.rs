@@ -5,8 +5,8 @@ use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use paste::paste;
 
 use crate::global::{
-    EntityId, ProceduralMaterialHandle, ProceduralMeshHandle, ProceduralSamplerHandle,
-    ProceduralTextureHandle,
+    EntityId, ProceduralBufferHandle, ProceduralComputeHandle, ProceduralMaterialHandle,
+    ProceduralMeshHandle, ProceduralSamplerHandle, ProceduralTextureHandle,
 };
 
 impl MessageSerde for EntityId {
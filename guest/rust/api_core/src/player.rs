@@ -3,13 +3,58 @@ use crate::{
     internal::{conversion::FromBindgen, wit},
 };
 
+#[cfg(feature = "server")]
+use crate::internal::conversion::IntoBindgen;
+
 /// Get a player's entity ID from their user ID.
 pub fn get_by_user_id(user_id: &str) -> Option<EntityId> {
     wit::player::get_by_user_id(user_id).from_bindgen()
 }
 
 /// Get the local player's entity ID.
+///
+/// This will fail if the client is connected as a spectator, as spectators do not have a player entity.
 #[cfg(feature = "client")]
 pub fn get_local() -> EntityId {
     wit::client_player::get_local().from_bindgen()
 }
+
+/// Promote the spectator with the given user ID to a player, returning their new player entity.
+///
+/// Spectators receive the world state, but do not have a player entity and cannot send messages
+/// to the server. Returns `None` if the user is not connected as a spectator.
+#[cfg(feature = "server")]
+pub fn promote_spectator(user_id: &str) -> Option<EntityId> {
+    wit::server_player::promote_spectator(user_id).from_bindgen()
+}
+
+/// Disconnect `player` from the server, telling their client that they were kicked for `reason`.
+///
+/// The player is disconnected at the end of the current tick. They can connect again; use [ban]
+/// to stop them from doing so. Returns an error if `player` is not connected to the server.
+#[cfg(feature = "server")]
+pub fn kick(player: EntityId, reason: &str) -> Result<(), String> {
+    wit::server_player::kick(player.into_bindgen(), reason)
+}
+
+/// Ban the user with the given user ID from the server for `reason`, which is shown to them when
+/// they try to connect.
+///
+/// The bans are saved, so that they apply to later runs of the server. Banning a user does not
+/// disconnect them; use [kick] for that.
+#[cfg(feature = "server")]
+pub fn ban(user_id: &str, reason: &str) {
+    wit::server_player::ban(user_id, reason)
+}
+
+/// Lift the ban of the user with the given user ID. Returns `false` if they were not banned.
+#[cfg(feature = "server")]
+pub fn unban(user_id: &str) -> bool {
+    wit::server_player::unban(user_id)
+}
+
+/// Get the user IDs of the banned users, and the reasons of their bans.
+#[cfg(feature = "server")]
+pub fn get_bans() -> Vec<(String, String)> {
+    wit::server_player::get_bans()
+}
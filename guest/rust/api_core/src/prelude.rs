@@ -1,5 +1,5 @@
 pub use crate::{
-    asset, camera,
+    analytics, asset, camera,
     ecs::{
         change_query, despawn_query, query, spawn_query, Component, ComponentsTuple, Concept,
         ConceptComponents, ConceptQuery, ConceptSuggested, Entity, QueryEvent,
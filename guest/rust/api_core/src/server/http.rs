@@ -13,16 +13,44 @@ impl fmt::Display for HttpError {
     }
 }
 
-/// Sends an HTTP GET request to the given URL, and returns the response body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The response to an HTTP request.
+pub struct Response {
+    /// The status code of the response (e.g. 200).
+    pub status: u16,
+    /// The headers of the response, as `(name, value)` pairs in the order they were received.
+    pub headers: Vec<(String, String)>,
+    /// The body of the response.
+    pub body: Vec<u8>,
+}
+impl Response {
+    /// Returns the value of the first header named `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns whether the status code of the response is in the 200-299 range.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Sends an HTTP GET request to the given URL, and returns the response.
 ///
-/// Any errors in sending or receiving will be returned as an [HttpError].
+/// The domain of the URL must be in the `allowed_domains` of the `[http]` section of the
+/// package manifest, as must the domain of every redirect it follows. Any errors in sending or
+/// receiving will be returned as an [HttpError]; responses with an error status code are
+/// returned as a [Response].
 ///
 /// **NOTE**: This may be replaced with `wasi-http` support in the future,
 /// which will allow the use of native Rust libraries like `reqwest`.
 pub async fn get(
     url: impl AsRef<str>,
     headers: Option<HashMap<String, String>>,
-) -> Result<Vec<u8>, HttpError> {
+) -> Result<Response, HttpError> {
     let url = url.as_ref();
     let headers = headers.unwrap_or_default().into_iter().collect::<Vec<_>>();
     let response_id = wit::server_http::get(url, &headers);
@@ -30,9 +58,12 @@ pub async fn get(
     wait_for_response(response_id).await
 }
 
-/// Sends an HTTP POST request to the given URL, and returns the response body.
+/// Sends an HTTP POST request to the given URL, and returns the response.
 ///
-/// Any errors in sending or receiving will be returned as an [HttpError].
+/// The domain of the URL must be in the `allowed_domains` of the `[http]` section of the
+/// package manifest, as must the domain of every redirect it follows. Any errors in sending or
+/// receiving will be returned as an [HttpError]; responses with an error status code are
+/// returned as a [Response].
 ///
 /// **NOTE**: This may be replaced with `wasi-http` support in the future,
 /// which will allow the use of native Rust libraries like `reqwest`.
@@ -40,7 +71,7 @@ pub async fn post(
     url: impl AsRef<str>,
     headers: Option<HashMap<String, String>>,
     body: Option<&[u8]>,
-) -> Result<Vec<u8>, HttpError> {
+) -> Result<Response, HttpError> {
     let url = url.as_ref();
     let headers = headers.unwrap_or_default().into_iter().collect::<Vec<_>>();
     let response_id = wit::server_http::post(url, &headers, body);
@@ -48,7 +79,7 @@ pub async fn post(
     wait_for_response(response_id).await
 }
 
-async fn wait_for_response(response_id: u64) -> Result<Vec<u8>, HttpError> {
+async fn wait_for_response(response_id: u64) -> Result<Response, HttpError> {
     let response = global::wait_for_runtime_message(move |message: &HttpResponse| {
         message.response_id == response_id
     })
@@ -56,6 +87,17 @@ async fn wait_for_response(response_id: u64) -> Result<Vec<u8>, HttpError> {
 
     match response.error {
         Some(error) => Err(HttpError(error)),
-        None => Ok(response.body),
+        None => Ok(Response {
+            status: response.status as u16,
+            headers: response
+                .headers
+                .into_iter()
+                .filter_map(|header| {
+                    let (name, value) = header.split_once(": ")?;
+                    Some((name.to_string(), value.to_string()))
+                })
+                .collect(),
+            body: response.body,
+        }),
     }
 }
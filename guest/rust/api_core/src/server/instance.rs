@@ -0,0 +1,42 @@
+use crate::{
+    global::EntityId,
+    internal::{conversion::IntoBindgen, wit},
+};
+
+/// The ID of the main instance, which the players join when they connect.
+pub const MAIN: &str = "main";
+
+/// Returns the ID of the instance this module is running in.
+pub fn current() -> String {
+    wit::server_instance::current()
+}
+
+/// Creates a new instance as a copy of the current one, and returns its ID.
+///
+/// The new instance starts with the entities of the current instance, except for the players and
+/// the entities spawned by modules; the modules are then started again in it. Use [current] when
+/// a module starts to find out which instance it is running in, and spawn the entities that
+/// instance needs.
+///
+/// If `id` is `None`, a random ID is used. If an instance with the given ID already exists, it is
+/// left as it is. The instance is created at the end of the current tick.
+pub fn create(id: Option<&str>) -> String {
+    wit::server_instance::create(id)
+}
+
+/// Moves `player` to the instance `instance`, at the end of the current tick.
+///
+/// The player entity is despawned from the current instance and spawned in the other one. If the
+/// player was the last one in their previous instance, and it is not the [MAIN] instance, that
+/// instance is removed.
+///
+/// Returns an error if `player` is not a player.
+pub fn join(player: EntityId, instance: &str) -> Result<(), String> {
+    wit::server_instance::join(player.into_bindgen(), instance)
+}
+
+/// Removes the instance `instance` at the end of the current tick, after moving its players to
+/// the [MAIN] instance. The main instance cannot be removed.
+pub fn remove(instance: &str) {
+    wit::server_instance::remove(instance)
+}
@@ -1,5 +1,17 @@
 /// **\[Server-only\]** Physics-related functionality, including applying forces, changing physical properties, and more.
 pub mod physics;
 
+/// **\[Server-only\]** Navigation meshes and pathfinding, for entities that find their way around the world.
+pub mod navigation;
+
 /// **\[Server-only\]** HTTP-related functionality, including sending requests and receiving responses.
 pub mod http;
+
+/// **\[Server-only\]** World instances, which allow one server to host several isolated worlds, such as lobbies and dungeons.
+pub mod instance;
+
+/// **\[Server-only\]** Persistent key-value storage, which is kept by the server between runs.
+pub mod storage;
+
+/// **\[Server-only\]** WebSocket connections to other servers, such as chat services, matchmakers and custom backends.
+pub mod websocket;
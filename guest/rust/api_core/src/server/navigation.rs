@@ -0,0 +1,22 @@
+use crate::{
+    global::Vec3,
+    internal::{
+        conversion::{FromBindgen, IntoBindgen},
+        wit,
+    },
+};
+
+/// Finds a path from `start` to `end` on the navigation meshes, which are baked for the entities
+/// with a `navmesh` component. Returns `None` if no navigation mesh connects them.
+///
+/// The path starts at `start` and ends at `end`, both moved onto the navigation mesh. To move an
+/// entity along a path, consider giving it a `nav_agent_target` instead.
+pub fn find_path(start: Vec3, end: Vec3) -> Option<Vec<Vec3>> {
+    wit::server_navigation::find_path(start.into_bindgen(), end.into_bindgen()).from_bindgen()
+}
+
+/// Returns the point of the navigation meshes which is the closest to `position`, within
+/// `max_distance` horizontally.
+pub fn nearest_point(position: Vec3, max_distance: f32) -> Option<Vec3> {
+    wit::server_navigation::nearest_point(position.into_bindgen(), max_distance).from_bindgen()
+}
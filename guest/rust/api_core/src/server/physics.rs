@@ -1,5 +1,5 @@
 use crate::{
-    global::{EntityId, Vec3},
+    global::{EntityId, Quat, Ray, Vec3},
     internal::{
         conversion::{FromBindgen, IntoBindgen},
         wit,
@@ -169,6 +169,47 @@ pub fn raycast_first(origin: Vec3, direction: Vec3) -> Option<RaycastHit> {
     wit::server_physics::raycast_first(origin.into_bindgen(), direction.into_bindgen())
         .map(|(entity, distance)| raycast_result_to_hit(origin, direction, entity, distance))
 }
+/// Casts each of the `rays`, and returns the [RaycastHit]s along each of them, in the order of
+/// the rays.
+///
+/// This is equivalent to calling [raycast] for each ray, but only calls the host once, which is
+/// much faster when casting many rays per frame, such as for vision cones. The directions of the
+/// rays must be normalized.
+pub fn raycast_many(rays: &[Ray]) -> Vec<Vec<RaycastHit>> {
+    wit::server_physics::raycast_many(
+        &rays
+            .iter()
+            .map(|ray| ray.into_bindgen())
+            .collect::<Vec<_>>(),
+    )
+    .into_iter()
+    .zip(rays)
+    .map(|(hits, ray)| {
+        hits.into_iter()
+            .map(|(entity, distance)| raycast_result_to_hit(ray.origin, ray.dir, entity, distance))
+            .collect()
+    })
+    .collect()
+}
+/// Casts each of the `rays`, and returns the first [RaycastHit] of each of them, in the order of
+/// the rays.
+///
+/// This is equivalent to calling [raycast_first] for each ray, but only calls the host once. The
+/// directions of the rays must be normalized.
+pub fn raycast_first_many(rays: &[Ray]) -> Vec<Option<RaycastHit>> {
+    wit::server_physics::raycast_first_many(
+        &rays
+            .iter()
+            .map(|ray| ray.into_bindgen())
+            .collect::<Vec<_>>(),
+    )
+    .into_iter()
+    .zip(rays)
+    .map(|(hit, ray)| {
+        hit.map(|(entity, distance)| raycast_result_to_hit(ray.origin, ray.dir, entity, distance))
+    })
+    .collect()
+}
 fn raycast_result_to_hit(
     origin: Vec3,
     direction: Vec3,
@@ -182,6 +223,136 @@ fn raycast_result_to_hit(
     }
 }
 
+/// Where a sweep ([sweep_sphere], [sweep_box] or [sweep_capsule]) hit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepHit {
+    /// The entity that was hit.
+    pub entity: EntityId,
+    /// The distance the shape travelled before the hit.
+    pub distance: f32,
+    /// The point of contact.
+    pub position: Vec3,
+    /// The normal of the surface that was hit, at the point of contact.
+    pub normal: Vec3,
+}
+/// Sweeps a sphere of `radius` centered on `origin` in `direction`, for up to `max_distance`, and
+/// returns the first [SweepHit] if it hits.
+///
+/// Unlike a raycast, a sweep takes the size of the moving object into account, so it does not
+/// miss thin gaps or edges. A sweep that starts out overlapping a collider, such as the collider
+/// of the entity being moved, hits it at a distance of zero.
+///
+/// `direction` must be normalized.
+pub fn sweep_sphere(
+    radius: f32,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<SweepHit> {
+    wit::server_physics::sweep_sphere(
+        radius,
+        origin.into_bindgen(),
+        direction.into_bindgen(),
+        max_distance,
+    )
+    .map(sweep_result_to_hit)
+}
+/// Sweeps a box of `half_extents`, rotated by `rotation` and centered on `origin`, in
+/// `direction`, for up to `max_distance`, and returns the first [SweepHit] if it hits.
+///
+/// See [sweep_sphere] for more details.
+pub fn sweep_box(
+    half_extents: Vec3,
+    rotation: Quat,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<SweepHit> {
+    wit::server_physics::sweep_box(
+        half_extents.into_bindgen(),
+        rotation.into_bindgen(),
+        origin.into_bindgen(),
+        direction.into_bindgen(),
+        max_distance,
+    )
+    .map(sweep_result_to_hit)
+}
+/// Sweeps a capsule centered on `origin` in `direction`, for up to `max_distance`, and returns
+/// the first [SweepHit] if it hits.
+///
+/// Before it is rotated by `rotation`, the capsule is upright: its axis is along Z, and it is
+/// `2 * (half_height + radius)` tall, like the collider of a character controller. Use
+/// [Quat::IDENTITY] for an upright capsule.
+///
+/// See [sweep_sphere] for more details.
+pub fn sweep_capsule(
+    radius: f32,
+    half_height: f32,
+    rotation: Quat,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<SweepHit> {
+    wit::server_physics::sweep_capsule(
+        radius,
+        half_height,
+        rotation.into_bindgen(),
+        origin.into_bindgen(),
+        direction.into_bindgen(),
+        max_distance,
+    )
+    .map(sweep_result_to_hit)
+}
+fn sweep_result_to_hit(hit: wit::server_physics::SweepHit) -> SweepHit {
+    SweepHit {
+        entity: hit.entity.from_bindgen(),
+        distance: hit.distance,
+        position: hit.position.from_bindgen(),
+        normal: hit.normal.from_bindgen(),
+    }
+}
+
+/// Returns the entities whose colliders overlap a sphere of `radius` centered on `origin`.
+///
+/// This can be used to find everything within an area, such as the entities caught in an
+/// explosion, the pickups a player can reach or the entities an AI is aware of. Each entity is
+/// returned once, in no particular order, even if several of its colliders overlap the sphere.
+pub fn overlap_sphere(origin: Vec3, radius: f32) -> Vec<EntityId> {
+    wit::server_physics::overlap_sphere(origin.into_bindgen(), radius).from_bindgen()
+}
+/// Returns the entities whose colliders overlap a box of `half_extents`, rotated by `rotation`
+/// and centered on `origin`.
+///
+/// See [overlap_sphere] for more details.
+pub fn overlap_box(origin: Vec3, half_extents: Vec3, rotation: Quat) -> Vec<EntityId> {
+    wit::server_physics::overlap_box(
+        origin.into_bindgen(),
+        half_extents.into_bindgen(),
+        rotation.into_bindgen(),
+    )
+    .from_bindgen()
+}
+/// Returns the entities whose colliders overlap a capsule centered on `origin`.
+///
+/// Before it is rotated by `rotation`, the capsule is upright: its axis is along Z, and it is
+/// `2 * (half_height + radius)` tall. Use [Quat::IDENTITY] for an upright capsule.
+///
+/// See [overlap_sphere] for more details.
+pub fn overlap_capsule(
+    origin: Vec3,
+    radius: f32,
+    half_height: f32,
+    rotation: Quat,
+) -> Vec<EntityId> {
+    wit::server_physics::overlap_capsule(
+        origin.into_bindgen(),
+        radius,
+        half_height,
+        rotation.into_bindgen(),
+    )
+    .from_bindgen()
+}
+
 /// Collision results when using [move_character].
 pub struct CharacterCollision {
     /// Side
@@ -235,3 +406,20 @@ pub fn set_character_foot_position(entity: EntityId, position: Vec3) {
         position.into_bindgen(),
     );
 }
+
+/// Sets the controls of a `vehicle`: its `throttle` from -1 (full reverse) to 1 (full forward),
+/// its `brake` from 0 to 1, and its `steer` from -1 (right) to 1 (left). Values out of range are clamped.
+///
+/// The vehicle is driven by the entities with a `wheel_vehicle` component pointing to it.
+pub fn set_vehicle_controls(vehicle: EntityId, throttle: f32, brake: f32, steer: f32) {
+    wit::server_physics::set_vehicle_controls(vehicle.into_bindgen(), throttle, brake, steer)
+}
+
+/// Activates or deactivates the ragdoll of the model of `entity`.
+///
+/// The first time this is called, a ragdoll of capsules and joints is generated from the skeleton
+/// of the model. While active, the skeleton is simulated instead of following its animations; when
+/// deactivated, it blends back to its animations over `ragdoll_blend_time`.
+pub fn set_ragdoll_active(entity: EntityId, active: bool) {
+    wit::server_physics::set_ragdoll_active(entity.into_bindgen(), active)
+}
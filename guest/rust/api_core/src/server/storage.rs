@@ -0,0 +1,29 @@
+use crate::internal::wit;
+
+/// Returns the value stored under `key`, if any.
+///
+/// Each package has its own storage, which is kept by the server between runs; packages cannot
+/// see the values of other packages.
+pub fn get(key: &str) -> Result<Option<Vec<u8>>, String> {
+    wit::server_storage::get(key)
+}
+
+/// Stores `value` under `key`, replacing any previous value.
+///
+/// The value is saved to disk before this returns, so it is kept even if the server stops
+/// afterwards. Use a format like JSON for structured data.
+pub fn set(key: &str, value: &[u8]) -> Result<(), String> {
+    wit::server_storage::set(key, value)
+}
+
+/// Removes the value stored under `key`. Returns `false` if there was none.
+pub fn delete(key: &str) -> Result<bool, String> {
+    wit::server_storage::delete(key)
+}
+
+/// Returns the keys that start with `prefix`, in order. Use an empty prefix to list all keys.
+///
+/// Keys can be grouped with prefixes, such as `players/<user id>` for the data of each player.
+pub fn list(prefix: &str) -> Result<Vec<String>, String> {
+    wit::server_storage::list(prefix)
+}
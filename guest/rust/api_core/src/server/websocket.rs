@@ -0,0 +1,154 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    fmt,
+    rc::Rc,
+    task::Poll,
+};
+
+use thiserror::Error;
+
+use crate::{
+    core::messages::{WebSocketClosed, WebSocketConnected, WebSocketMessage},
+    global,
+    internal::wit,
+    message::{Listener, RuntimeMessage},
+};
+
+#[derive(Error, Debug, Clone)]
+/// Errors that can occur when connecting to or sending on a WebSocket.
+pub struct WebSocketError(pub String);
+impl fmt::Display for WebSocketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WebSocket error: {}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A message sent or received on a [WebSocket].
+pub enum Message {
+    /// A UTF-8 text message.
+    Text(String),
+    /// A binary message.
+    Binary(Vec<u8>),
+}
+
+#[derive(Default)]
+struct State {
+    messages: VecDeque<Message>,
+    /// Why the WebSocket was closed, if it has been
+    closed: Option<String>,
+}
+
+/// A WebSocket connection to another server, such as a chat service or a matchmaker.
+///
+/// The connection is closed when this is dropped, or when the module is unloaded.
+pub struct WebSocket {
+    socket_id: u64,
+    state: Rc<RefCell<State>>,
+    listeners: [Listener; 2],
+}
+impl WebSocket {
+    /// Connects to the WebSocket server at `url`, which starts with `ws://` or `wss://`, sending
+    /// the given `headers` with the opening handshake.
+    ///
+    /// The domain of the URL must be in the `allowed_domains` of the `[http]` section of the
+    /// package manifest.
+    pub async fn connect(
+        url: impl AsRef<str>,
+        headers: Option<HashMap<String, String>>,
+    ) -> Result<Self, WebSocketError> {
+        let headers = headers.unwrap_or_default().into_iter().collect::<Vec<_>>();
+        let socket_id = wit::server_websocket::connect(url.as_ref(), &headers);
+
+        // Subscribe before waiting, so that no messages received right after connecting are lost
+        let state = Rc::new(RefCell::new(State::default()));
+        let listeners = [
+            WebSocketMessage::subscribe({
+                let state = state.clone();
+                move |message| {
+                    if message.socket_id != socket_id {
+                        return;
+                    }
+                    let message = if message.is_text {
+                        Message::Text(String::from_utf8_lossy(&message.data).into_owned())
+                    } else {
+                        Message::Binary(message.data)
+                    };
+                    state.borrow_mut().messages.push_back(message);
+                }
+            }),
+            WebSocketClosed::subscribe({
+                let state = state.clone();
+                move |message| {
+                    if message.socket_id == socket_id {
+                        state.borrow_mut().closed = Some(message.reason);
+                    }
+                }
+            }),
+        ];
+        let socket = Self {
+            socket_id,
+            state,
+            listeners,
+        };
+
+        let connected = global::wait_for_runtime_message(move |message: &WebSocketConnected| {
+            message.socket_id == socket_id
+        })
+        .await;
+        match connected.error {
+            Some(error) => Err(WebSocketError(error)),
+            None => Ok(socket),
+        }
+    }
+
+    /// Queues `message` to be sent. Returns an error if the WebSocket has been closed.
+    pub fn send(&self, message: Message) -> Result<(), WebSocketError> {
+        let result = match message {
+            Message::Text(text) => {
+                wit::server_websocket::send(self.socket_id, text.as_bytes(), true)
+            }
+            Message::Binary(data) => wit::server_websocket::send(self.socket_id, &data, false),
+        };
+        result.map_err(WebSocketError)
+    }
+
+    /// Returns the oldest received message that has not been returned yet, if any.
+    pub fn try_recv(&self) -> Option<Message> {
+        self.state.borrow_mut().messages.pop_front()
+    }
+
+    /// Waits for the next message, and returns it. Returns `None` once the WebSocket has been
+    /// closed and all of its messages have been returned.
+    ///
+    /// This must be used with `.await` in either an `async fn` or an `async` block.
+    pub async fn recv(&self) -> Option<Message> {
+        std::future::poll_fn(|_cx| {
+            let mut state = self.state.borrow_mut();
+            match state.messages.pop_front() {
+                Some(message) => Poll::Ready(Some(message)),
+                None if state.closed.is_some() => Poll::Ready(None),
+                None => Poll::Pending,
+            }
+        })
+        .await
+    }
+
+    /// Returns why the WebSocket was closed by the other end or by an error, if it has been.
+    /// The reason may be empty.
+    pub fn close_reason(&self) -> Option<String> {
+        self.state.borrow().closed.clone()
+    }
+
+    /// Closes the WebSocket. This is the same as dropping it.
+    pub fn close(self) {}
+}
+impl Drop for WebSocket {
+    fn drop(&mut self) {
+        for listener in &self.listeners {
+            listener.stop();
+        }
+        wit::server_websocket::close(self.socket_id);
+    }
+}
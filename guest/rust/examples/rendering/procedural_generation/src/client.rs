@@ -256,6 +256,7 @@ fn make_procedural<BaseColorFn, NormalFn, MetallicRoughnessFn, SamplerFn>(
     let mesh = mesh::create(&mesh::Descriptor {
         vertices: &vertices,
         indices: &indices,
+        ..Default::default()
     });
     let base_color_map = make_texture(base_color_fn);
     let normal_map = make_texture(normal_fn);
@@ -3,7 +3,7 @@ use ambient_api::{
         layout::components::{height, space_between_items, width},
         rect::components::{
             background_color, border_color, border_radius, border_thickness, line_from, line_to,
-            line_width,
+            line_width, shadow_blur, shadow_color, shadow_offset,
         },
     },
     prelude::*,
@@ -29,6 +29,15 @@ fn App(_hooks: &mut Hooks) -> Element {
                 .with(border_color(), vec4(0., 1., 0., 1.))
                 .with(border_thickness(), 10.)
                 .with(border_radius(), vec4(20., 10., 5., 0.)),
+            Rectangle
+                .el()
+                .with(width(), 150.)
+                .with(height(), 50.)
+                .with(background_color(), vec4(0.9, 0.9, 0.9, 1.))
+                .with(border_radius(), vec4(8., 8., 8., 8.))
+                .with(shadow_color(), vec4(0., 0., 0., 0.5))
+                .with(shadow_offset(), vec2(4., 4.))
+                .with(shadow_blur(), 8.),
         ])
         .with(space_between_items(), 10.)
         .with_padding_even(STREET),
@@ -11,7 +11,7 @@ use ambient_api::{
         messages::Collision,
         model::components::model_from_url,
         physics::components::{
-            angular_velocity, collider_from_url, dynamic, kinematic, linear_velocity,
+            angular_velocity, ccd_enabled, collider_from_url, dynamic, kinematic, linear_velocity,
             physics_controlled, sphere_collider,
         },
         player::components::{is_player, user_id},
@@ -430,6 +430,8 @@ fn make_golf_ball() -> Entity {
     .with(physics_controlled(), ())
     .with(dynamic(), true)
     .with(sphere_collider(), BALL_RADIUS)
+    // Hard shots are fast enough to go through the thin walls of the course
+    .with(ccd_enabled(), true)
     .with(model_from_url(), assets::url("ball.glb"))
 }
 
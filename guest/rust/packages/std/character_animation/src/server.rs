@@ -0,0 +1,23 @@
+use ambient_api::{core::physics::components::ragdoll_active, prelude::*};
+use packages::{
+    game_object::components::health,
+    this::components::{basic_character_animations, ragdoll_on_death},
+};
+
+pub mod packages;
+
+#[main]
+pub fn main() {
+    change_query((health(), basic_character_animations()))
+        .track_change(health())
+        .requires(ragdoll_on_death())
+        .bind(|units| {
+            for (id, (health, target)) in units {
+                let target = if target.is_null() { id } else { target };
+                let dead = health <= 0.;
+                if entity::get_component(target, ragdoll_active()) != Some(dead) {
+                    physics::set_ragdoll_active(target, dead);
+                }
+            }
+        });
+}
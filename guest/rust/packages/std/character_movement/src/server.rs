@@ -1,5 +1,6 @@
 use crate::packages::unit_schema::components::air_speed_multiplier;
 use ambient_api::{
+    core::physics::components::character_controller_up,
     entity::{add_component, get_component, set_component},
     prelude::*,
 };
@@ -20,9 +21,12 @@ pub fn main() {
     query(CharacterMovement::as_query()).each_frame(move |list| {
         for (unit_id, data) in list {
             let speed = get_speed(unit_id, &data);
-            let mut displace =
-                data.rotation * (data.run_direction.normalize_or_zero() * speed).extend(0.);
-            displace.z = data.vertical_velocity;
+            let up = get_component(unit_id, character_controller_up())
+                .and_then(|up| up.try_normalize())
+                .unwrap_or(Vec3::Z);
+            let displace = data.rotation
+                * (data.run_direction.normalize_or_zero() * speed).extend(0.)
+                + up * data.vertical_velocity;
             add_component(unit_id, unit_displacement(), displace);
             let collision = physics::move_character(unit_id, displace, 0.01, delta_time());
             entity::add_component(unit_id, is_on_ground(), collision.down);
@@ -85,7 +85,7 @@ async fn process_request() -> anyhow::Result<PackageRemoteResponse> {
     let api_url = ambient_shared_types::urls::package_list_url(list_params);
 
     let api_packages =
-        serde_json::from_slice::<Vec<PackageListApiJson>>(&http::get(&api_url, None).await?)?;
+        serde_json::from_slice::<Vec<PackageListApiJson>>(&http::get(&api_url, None).await?.body)?;
 
     let mut packages_json = vec![];
     for api_package in api_packages {
@@ -95,7 +95,7 @@ async fn process_request() -> anyhow::Result<PackageRemoteResponse> {
         );
 
         let manifest: Manifest =
-            toml::from_str(std::str::from_utf8(&http::get(&url, None).await?)?)?;
+            toml::from_str(std::str::from_utf8(&http::get(&url, None).await?.body)?)?;
 
         if let Some(id) = &mod_manager_for {
             let ambient_package::PackageContent::Mod { for_playables } = manifest.package.content
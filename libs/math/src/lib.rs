@@ -3,6 +3,8 @@ use std::ops::{Add, Div, Mul, Sub};
 use glam::{Vec2, Vec3, Vec4};
 use serde::{Deserialize, Serialize};
 
+pub mod simd;
+
 pub trait Saturate {
     // Clamps a value between 0 and 1
     fn saturate(&self) -> Self;
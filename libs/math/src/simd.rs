@@ -0,0 +1,141 @@
+//! Math that is run on several values at once with SIMD instructions.
+//!
+//! The values are split into their lanes, so that e.g. the `x` of four translations are in one
+//! [Vec4] (a "structure of arrays" layout). Each operation then works on four values at the cost
+//! of one.
+
+use glam::{Mat4, Quat, Vec3, Vec4};
+
+/// The number of values that are processed together.
+pub const LANES: usize = 4;
+
+/// Four translations, rotations and scales, stored by lane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransformLanes {
+    pub translation: [Vec4; 3],
+    pub rotation: [Vec4; 4],
+    pub scale: [Vec4; 3],
+}
+
+impl TransformLanes {
+    pub fn new(translation: [Vec3; LANES], rotation: [Quat; LANES], scale: [Vec3; LANES]) -> Self {
+        Self {
+            translation: [
+                lane(translation, |v| v.x),
+                lane(translation, |v| v.y),
+                lane(translation, |v| v.z),
+            ],
+            rotation: [
+                lane(rotation, |q| q.x),
+                lane(rotation, |q| q.y),
+                lane(rotation, |q| q.z),
+                lane(rotation, |q| q.w),
+            ],
+            scale: [
+                lane(scale, |v| v.x),
+                lane(scale, |v| v.y),
+                lane(scale, |v| v.z),
+            ],
+        }
+    }
+
+    /// Returns the same matrices as [Mat4::from_scale_rotation_translation] for each of the four
+    /// transforms.
+    pub fn to_matrices(&self) -> [Mat4; LANES] {
+        let [x, y, z, w] = self.rotation;
+        let (x2, y2, z2) = (x + x, y + y, z + z);
+        let (xx, xy, xz) = (x * x2, x * y2, x * z2);
+        let (yy, yz, zz) = (y * y2, y * z2, z * z2);
+        let (wx, wy, wz) = (w * x2, w * y2, w * z2);
+        let [sx, sy, sz] = self.scale;
+
+        // `columns[c][r]` holds the element at column `c` and row `r` of the four matrices
+        let columns = [
+            [
+                (Vec4::ONE - (yy + zz)) * sx,
+                (xy + wz) * sx,
+                (xz - wy) * sx,
+                Vec4::ZERO,
+            ],
+            [
+                (xy - wz) * sy,
+                (Vec4::ONE - (xx + zz)) * sy,
+                (yz + wx) * sy,
+                Vec4::ZERO,
+            ],
+            [
+                (xz + wy) * sz,
+                (yz - wx) * sz,
+                (Vec4::ONE - (xx + yy)) * sz,
+                Vec4::ZERO,
+            ],
+            [
+                self.translation[0],
+                self.translation[1],
+                self.translation[2],
+                Vec4::ONE,
+            ],
+        ];
+        let columns = columns.map(|column| column.map(|lane| lane.to_array()));
+
+        std::array::from_fn(|i| {
+            Mat4::from_cols_array_2d(&columns.map(|column| column.map(|lane| lane[i])))
+        })
+    }
+}
+
+fn lane<T: Copy>(values: [T; LANES], f: impl Fn(T) -> f32) -> Vec4 {
+    Vec4::from(values.map(f))
+}
+
+/// Calls [Mat4::from_scale_rotation_translation] with the scale, rotation and translation that
+/// `transform` returns for each item, and writes the result to the matrix that `matrix` returns
+/// for it, four items at a time.
+pub fn scale_rotation_translation_batch<T>(
+    items: &mut [T],
+    transform: impl Fn(&T) -> (Vec3, Quat, Vec3),
+    matrix: impl Fn(&mut T) -> &mut Mat4,
+) {
+    let mut chunks = items.chunks_exact_mut(LANES);
+    for chunk in &mut chunks {
+        let transforms: [_; LANES] = std::array::from_fn(|i| transform(&chunk[i]));
+        let lanes = TransformLanes::new(
+            transforms.map(|(_, _, translation)| translation),
+            transforms.map(|(_, rotation, _)| rotation),
+            transforms.map(|(scale, _, _)| scale),
+        );
+        for (item, lane_matrix) in chunk.iter_mut().zip(lanes.to_matrices()) {
+            *matrix(item) = lane_matrix;
+        }
+    }
+    for item in chunks.into_remainder() {
+        let (scale, rotation, translation) = transform(item);
+        *matrix(item) = Mat4::from_scale_rotation_translation(scale, rotation, translation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_matches_glam() {
+        let mut items = (0..11)
+            .map(|i| {
+                let i = i as f32;
+                let transform = (
+                    Vec3::new(1. + i, 0.5, 2. - i * 0.1),
+                    Quat::from_euler(glam::EulerRot::ZYX, i * 0.3, -i * 0.2, i * 0.7),
+                    Vec3::new(i, -3. * i, 0.25),
+                );
+                (transform, Mat4::ZERO)
+            })
+            .collect::<Vec<_>>();
+        scale_rotation_translation_batch(&mut items, |(transform, _)| *transform, |(_, out)| out);
+
+        for ((scale, rotation, translation), out) in items {
+            let expected = Mat4::from_scale_rotation_translation(scale, rotation, translation);
+            assert!(out.abs_diff_eq(expected, 1e-4), "{out} != {expected}");
+        }
+    }
+}
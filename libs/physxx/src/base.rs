@@ -1,8 +1,8 @@
 use crate::{
     AsPxJoint, AsPxRigidActor, AsPxRigidBody, PxAggregateRef, PxArticulationLinkRef,
-    PxConstraintRef, PxConvexMesh, PxFixedJointRef, PxHeightField, PxJointRef, PxMaterial,
-    PxRevoluteJointRef, PxRigidActorRef, PxRigidBodyRef, PxRigidDynamicRef, PxRigidStaticRef,
-    PxShape,
+    PxConstraintRef, PxConvexMesh, PxD6JointRef, PxFixedJointRef, PxHeightField, PxJointRef,
+    PxMaterial, PxPrismaticJointRef, PxRevoluteJointRef, PxRigidActorRef, PxRigidBodyRef,
+    PxRigidDynamicRef, PxRigidStaticRef, PxShape, PxSphericalJointRef,
 };
 
 pub trait AsPxBase: Sync + Send {
@@ -56,6 +56,9 @@ impl<T: AsPxBase + 'static> PxBase for T {
         match self.as_px_any() {
             PxAny::PxFixedJoint(o) => Some(o.as_joint()),
             PxAny::PxRevoluteJoint(o) => Some(o.as_joint()),
+            PxAny::PxPrismaticJoint(o) => Some(o.as_joint()),
+            PxAny::PxSphericalJoint(o) => Some(o.as_joint()),
+            PxAny::PxD6Joint(o) => Some(o.as_joint()),
             _ => None,
         }
     }
@@ -94,6 +97,9 @@ pub enum PxAny {
     PxShape(PxShape),
     PxFixedJoint(PxFixedJointRef),
     PxRevoluteJoint(PxRevoluteJointRef),
+    PxPrismaticJoint(PxPrismaticJointRef),
+    PxSphericalJoint(PxSphericalJointRef),
+    PxD6Joint(PxD6JointRef),
     PxConstraint(PxConstraintRef),
     PxArticulationLink(PxArticulationLinkRef),
 }
@@ -163,7 +169,7 @@ impl PxAny {
                 physx_sys::PxJointConcreteType::eCONTACT => {
                     panic!("PhysX object type is not supported")
                 }
-                physx_sys::PxJointConcreteType::eD6 => panic!("PhysX object type is not supported"),
+                physx_sys::PxJointConcreteType::eD6 => PxAny::PxD6Joint(PxD6JointRef(obj as _)),
                 physx_sys::PxJointConcreteType::eDISTANCE => {
                     panic!("PhysX object type is not supported")
                 }
@@ -174,13 +180,13 @@ impl PxAny {
                     panic!("PhysX object type is not supported")
                 }
                 physx_sys::PxJointConcreteType::ePRISMATIC => {
-                    panic!("PhysX object type is not supported")
+                    PxAny::PxPrismaticJoint(PxPrismaticJointRef(obj as _))
                 }
                 physx_sys::PxJointConcreteType::eREVOLUTE => {
                     PxAny::PxRevoluteJoint(PxRevoluteJointRef(obj as _))
                 }
                 physx_sys::PxJointConcreteType::eSPHERICAL => {
-                    panic!("PhysX object type is not supported")
+                    PxAny::PxSphericalJoint(PxSphericalJointRef(obj as _))
                 }
 
                 _ => panic!("Unknown type"),
@@ -321,3 +321,302 @@ impl PxJointAngularLimitPair {
         }
     }
 }
+
+bitflags! {
+    #[derive(Serialize, Deserialize)]
+    pub struct PxPrismaticJointFlag: u32 {
+        const LIMIT_ENABLED = physx_sys::PxPrismaticJointFlag::eLIMIT_ENABLED;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PxPrismaticJointRef(pub(crate) *mut physx_sys::PxPrismaticJoint);
+impl PxPrismaticJointRef {
+    pub fn new(
+        physics: PxPhysicsRef,
+        actor0: Option<PxRigidActorRef>,
+        local_frame_0: &PxTransform,
+        actor1: Option<PxRigidActorRef>,
+        local_frame_1: &PxTransform,
+    ) -> Self {
+        Self(unsafe {
+            physx_sys::phys_PxPrismaticJointCreate(
+                physics.0,
+                actor0.map_or(null_mut(), |v| v.0),
+                &local_frame_0.0,
+                actor1.map_or(null_mut(), |v| v.0),
+                &local_frame_1.0,
+            )
+        })
+    }
+    pub fn set_prismatic_flag(&self, flag: PxPrismaticJointFlag, value: bool) {
+        unsafe {
+            physx_sys::PxPrismaticJoint_setPrismaticJointFlag_mut(self.0, flag.bits() as _, value)
+        }
+    }
+    pub fn get_limit(&self) -> PxJointLinearLimitPair {
+        PxJointLinearLimitPair::from_physx(unsafe { physx_sys::PxPrismaticJoint_getLimit(self.0) })
+    }
+    pub fn set_limit(&self, limits: &PxJointLinearLimitPair) {
+        unsafe { physx_sys::PxPrismaticJoint_setLimit_mut(self.0, &limits.to_physx() as _) }
+    }
+}
+impl AsPxBase for PxPrismaticJointRef {
+    fn as_base(&self) -> PxBaseRef {
+        PxBaseRef(self.0 as _)
+    }
+}
+impl AsPxJoint for PxPrismaticJointRef {
+    fn as_joint(&self) -> PxJointRef {
+        PxJointRef(self.0 as _)
+    }
+}
+unsafe impl Sync for PxPrismaticJointRef {}
+unsafe impl Send for PxPrismaticJointRef {}
+
+bitflags! {
+    #[derive(Serialize, Deserialize)]
+    pub struct PxSphericalJointFlag: u32 {
+        const LIMIT_ENABLED = physx_sys::PxSphericalJointFlag::eLIMIT_ENABLED;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PxSphericalJointRef(pub(crate) *mut physx_sys::PxSphericalJoint);
+impl PxSphericalJointRef {
+    pub fn new(
+        physics: PxPhysicsRef,
+        actor0: Option<PxRigidActorRef>,
+        local_frame_0: &PxTransform,
+        actor1: Option<PxRigidActorRef>,
+        local_frame_1: &PxTransform,
+    ) -> Self {
+        Self(unsafe {
+            physx_sys::phys_PxSphericalJointCreate(
+                physics.0,
+                actor0.map_or(null_mut(), |v| v.0),
+                &local_frame_0.0,
+                actor1.map_or(null_mut(), |v| v.0),
+                &local_frame_1.0,
+            )
+        })
+    }
+    pub fn set_spherical_flag(&self, flag: PxSphericalJointFlag, value: bool) {
+        unsafe {
+            physx_sys::PxSphericalJoint_setSphericalJointFlag_mut(self.0, flag.bits() as _, value)
+        }
+    }
+    pub fn get_limit_cone(&self) -> PxJointLimitCone {
+        PxJointLimitCone::from_physx(unsafe { physx_sys::PxSphericalJoint_getLimitCone(self.0) })
+    }
+    pub fn set_limit_cone(&self, limit: &PxJointLimitCone) {
+        unsafe { physx_sys::PxSphericalJoint_setLimitCone_mut(self.0, &limit.to_physx() as _) }
+    }
+}
+impl AsPxBase for PxSphericalJointRef {
+    fn as_base(&self) -> PxBaseRef {
+        PxBaseRef(self.0 as _)
+    }
+}
+impl AsPxJoint for PxSphericalJointRef {
+    fn as_joint(&self) -> PxJointRef {
+        PxJointRef(self.0 as _)
+    }
+}
+unsafe impl Sync for PxSphericalJointRef {}
+unsafe impl Send for PxSphericalJointRef {}
+
+/// The degrees of freedom of a [PxD6JointRef]. The twist is around the X axis of the joint frame,
+/// and the swings around its Y and Z axes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum PxD6Axis {
+    X = physx_sys::PxD6Axis::eX,
+    Y = physx_sys::PxD6Axis::eY,
+    Z = physx_sys::PxD6Axis::eZ,
+    Twist = physx_sys::PxD6Axis::eTWIST,
+    Swing1 = physx_sys::PxD6Axis::eSWING1,
+    Swing2 = physx_sys::PxD6Axis::eSWING2,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum PxD6Motion {
+    Locked = physx_sys::PxD6Motion::eLOCKED,
+    Limited = physx_sys::PxD6Motion::eLIMITED,
+    Free = physx_sys::PxD6Motion::eFREE,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PxD6JointRef(pub(crate) *mut physx_sys::PxD6Joint);
+impl PxD6JointRef {
+    pub fn new(
+        physics: PxPhysicsRef,
+        actor0: Option<PxRigidActorRef>,
+        local_frame_0: &PxTransform,
+        actor1: Option<PxRigidActorRef>,
+        local_frame_1: &PxTransform,
+    ) -> Self {
+        Self(unsafe {
+            physx_sys::phys_PxD6JointCreate(
+                physics.0,
+                actor0.map_or(null_mut(), |v| v.0),
+                &local_frame_0.0,
+                actor1.map_or(null_mut(), |v| v.0),
+                &local_frame_1.0,
+            )
+        })
+    }
+    pub fn set_motion(&self, axis: PxD6Axis, motion: PxD6Motion) {
+        unsafe { physx_sys::PxD6Joint_setMotion_mut(self.0, axis as u32, motion as u32) }
+    }
+    /// Limits the distance between the origins of the joint frames, along the limited linear axes.
+    pub fn set_distance_limit(&self, limit: &PxJointLinearLimit) {
+        unsafe { physx_sys::PxD6Joint_setDistanceLimit_mut(self.0, &limit.to_physx() as _) }
+    }
+    pub fn set_twist_limit(&self, limit: &PxJointAngularLimitPair) {
+        unsafe { physx_sys::PxD6Joint_setTwistLimit_mut(self.0, &limit.to_physx() as _) }
+    }
+    pub fn set_swing_limit(&self, limit: &PxJointLimitCone) {
+        unsafe { physx_sys::PxD6Joint_setSwingLimit_mut(self.0, &limit.to_physx() as _) }
+    }
+}
+impl AsPxBase for PxD6JointRef {
+    fn as_base(&self) -> PxBaseRef {
+        PxBaseRef(self.0 as _)
+    }
+}
+impl AsPxJoint for PxD6JointRef {
+    fn as_joint(&self) -> PxJointRef {
+        PxJointRef(self.0 as _)
+    }
+}
+unsafe impl Sync for PxD6JointRef {}
+unsafe impl Send for PxD6JointRef {}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PxJointLinearLimitPair {
+    pub restitution: f32,
+    pub bounce_threshold: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+    pub contact_distance: f32,
+    pub upper: f32,
+    pub lower: f32,
+}
+impl PxJointLinearLimitPair {
+    /// A hard limit between `lower` and `upper`.
+    pub fn new(lower: f32, upper: f32, contact_distance: f32) -> Self {
+        Self {
+            restitution: 0.,
+            bounce_threshold: 0.,
+            stiffness: 0.,
+            damping: 0.,
+            contact_distance,
+            upper,
+            lower,
+        }
+    }
+    fn from_physx(limit: physx_sys::PxJointLinearLimitPair) -> Self {
+        Self {
+            restitution: limit.restitution,
+            bounce_threshold: limit.bounceThreshold,
+            stiffness: limit.stiffness,
+            damping: limit.damping,
+            contact_distance: limit.contactDistance,
+            upper: limit.upper,
+            lower: limit.lower,
+        }
+    }
+    fn to_physx(&self) -> physx_sys::PxJointLinearLimitPair {
+        physx_sys::PxJointLinearLimitPair {
+            restitution: self.restitution,
+            bounceThreshold: self.bounce_threshold,
+            stiffness: self.stiffness,
+            damping: self.damping,
+            contactDistance: self.contact_distance,
+            upper: self.upper,
+            lower: self.lower,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PxJointLinearLimit {
+    pub restitution: f32,
+    pub bounce_threshold: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+    pub contact_distance: f32,
+    pub value: f32,
+}
+impl PxJointLinearLimit {
+    /// A hard limit at `value`.
+    pub fn new(value: f32, contact_distance: f32) -> Self {
+        Self {
+            restitution: 0.,
+            bounce_threshold: 0.,
+            stiffness: 0.,
+            damping: 0.,
+            contact_distance,
+            value,
+        }
+    }
+    fn to_physx(&self) -> physx_sys::PxJointLinearLimit {
+        physx_sys::PxJointLinearLimit {
+            restitution: self.restitution,
+            bounceThreshold: self.bounce_threshold,
+            stiffness: self.stiffness,
+            damping: self.damping,
+            contactDistance: self.contact_distance,
+            value: self.value,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PxJointLimitCone {
+    pub restitution: f32,
+    pub bounce_threshold: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+    pub contact_distance: f32,
+    pub y_angle: f32,
+    pub z_angle: f32,
+}
+impl PxJointLimitCone {
+    /// A hard limit of `y_angle` around the Y axis and `z_angle` around the Z axis.
+    pub fn new(y_angle: f32, z_angle: f32, contact_distance: f32) -> Self {
+        Self {
+            restitution: 0.,
+            bounce_threshold: 0.,
+            stiffness: 0.,
+            damping: 0.,
+            contact_distance,
+            y_angle,
+            z_angle,
+        }
+    }
+    fn from_physx(limit: physx_sys::PxJointLimitCone) -> Self {
+        Self {
+            restitution: limit.restitution,
+            bounce_threshold: limit.bounceThreshold,
+            stiffness: limit.stiffness,
+            damping: limit.damping,
+            contact_distance: limit.contactDistance,
+            y_angle: limit.yAngle,
+            z_angle: limit.zAngle,
+        }
+    }
+    fn to_physx(&self) -> physx_sys::PxJointLimitCone {
+        physx_sys::PxJointLimitCone {
+            restitution: self.restitution,
+            bounceThreshold: self.bounce_threshold,
+            stiffness: self.stiffness,
+            damping: self.damping,
+            contactDistance: self.contact_distance,
+            yAngle: self.y_angle,
+            zAngle: self.z_angle,
+        }
+    }
+}
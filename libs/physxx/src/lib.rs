@@ -141,6 +141,12 @@ impl PxMaterial {
             )
         })
     }
+    pub fn set_friction_combine_mode(&self, mode: PxCombineMode) {
+        unsafe { physx_sys::PxMaterial_setFrictionCombineMode_mut(self.0, mode as u32) }
+    }
+    pub fn set_restitution_combine_mode(&self, mode: PxCombineMode) {
+        unsafe { physx_sys::PxMaterial_setRestitutionCombineMode_mut(self.0, mode as u32) }
+    }
     pub(crate) fn from_ptr(ptr: *mut physx_sys::PxMaterial) -> Self {
         let mut s = Self(ptr);
         s.acquire_reference();
@@ -168,6 +174,17 @@ impl Clone for PxMaterial {
 unsafe impl Sync for PxMaterial {}
 unsafe impl Send for PxMaterial {}
 
+/// How the friction or restitution of two materials in contact are combined. When the materials
+/// have different modes, the mode that comes last in this enum is used.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PxCombineMode {
+    Average = physx_sys::PxCombineMode::eAVERAGE,
+    Min = physx_sys::PxCombineMode::eMIN,
+    Multiply = physx_sys::PxCombineMode::eMULTIPLY,
+    Max = physx_sys::PxCombineMode::eMAX,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct PxAggregateRef(*mut physx_sys::PxAggregate);
 impl PxAggregateRef {
@@ -90,9 +90,12 @@ impl PxSceneDesc {
             );
         }
     }
-    pub fn set_simulation_event_callbacks<C: FnMut(&PxContactPairHeader, Vec<PxContactPoint>)>(
+    pub fn set_simulation_event_callbacks<
+        C: FnMut(&PxContactPairHeader, Vec<PxContactPoint>),
+        T: FnMut(&[PxTriggerPair]),
+    >(
         &mut self,
-        callbacks: PxSimulationEventCallback<C>,
+        callbacks: PxSimulationEventCallback<C, T>,
     ) {
         unsafe {
             unsafe extern "C" fn collision_callback_trampoline<
@@ -152,6 +155,39 @@ impl PxSceneDesc {
                 Box::into_raw(cb); // Convert the box back into a raw pointer.
             }
 
+            unsafe extern "C" fn trigger_callback_trampoline<T: FnMut(&[PxTriggerPair])>(
+                user_data: *mut std::ffi::c_void,
+                pairs: *const physx_sys::PxTriggerPair,
+                nb_pairs: u32,
+            ) {
+                let mut cb: Box<T> = Box::from_raw(user_data as _);
+                let pairs = std::slice::from_raw_parts(pairs, nb_pairs as usize)
+                    .iter()
+                    .map(|pair| {
+                        let flags = PxTriggerPairFlag::from_bits_truncate(pair.flags.mBits);
+                        PxTriggerPair {
+                            trigger_shape: if flags
+                                .contains(PxTriggerPairFlag::REMOVED_SHAPE_TRIGGER)
+                            {
+                                None
+                            } else {
+                                Some(PxShape::from_ptr(pair.triggerShape))
+                            },
+                            other_shape: if flags.contains(PxTriggerPairFlag::REMOVED_SHAPE_OTHER) {
+                                None
+                            } else {
+                                Some(PxShape::from_ptr(pair.otherShape))
+                            },
+                            touch_found: pair.status as u32
+                                == physx_sys::PxPairFlag::eNOTIFY_TOUCH_FOUND as u32,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                cb(&pairs);
+
+                Box::into_raw(cb);
+            }
+
             let mut cbs = physx_sys::SimulationEventCallbackInfo {
                 ..Default::default()
             };
@@ -159,6 +195,10 @@ impl PxSceneDesc {
                 cbs.collision_callback = Some(collision_callback_trampoline::<C>);
                 cbs.collision_user_data = Box::into_raw(cb) as _;
             }
+            if let Some(cb) = callbacks.trigger_callback {
+                cbs.trigger_callback = Some(trigger_callback_trampoline::<T>);
+                cbs.trigger_user_data = Box::into_raw(cb) as _;
+            }
             self.0.simulationEventCallback = physx_sys::create_simulation_event_callbacks(&cbs);
         }
     }
@@ -186,8 +226,22 @@ pub struct PxContactPairHeader {
     pub actors: [Option<PxRigidActorRef>; 2],
 }
 
-pub struct PxSimulationEventCallback<C: FnMut(&PxContactPairHeader, Vec<PxContactPoint>)> {
+/// A pair of shapes that started or stopped overlapping, where one of them is a trigger.
+pub struct PxTriggerPair {
+    /// `None` if the trigger shape was removed.
+    pub trigger_shape: Option<PxShape>,
+    /// `None` if the other shape was removed.
+    pub other_shape: Option<PxShape>,
+    /// Whether the shapes started overlapping, rather than stopped overlapping.
+    pub touch_found: bool,
+}
+
+pub struct PxSimulationEventCallback<
+    C: FnMut(&PxContactPairHeader, Vec<PxContactPoint>),
+    T: FnMut(&[PxTriggerPair]),
+> {
     pub collision_callback: Option<Box<C>>,
+    pub trigger_callback: Option<Box<T>>,
 }
 
 bitflags! {
@@ -197,6 +251,13 @@ bitflags! {
     }
 }
 
+bitflags! {
+    pub struct PxTriggerPairFlag: u8 {
+        const REMOVED_SHAPE_TRIGGER = physx_sys::PxTriggerPairFlag::eREMOVED_SHAPE_TRIGGER as u8;
+        const REMOVED_SHAPE_OTHER = physx_sys::PxTriggerPairFlag::eREMOVED_SHAPE_OTHER as u8;
+    }
+}
+
 bitflags! {
     pub struct PxSceneFlags: u32 {
         const ADAPTIVE_FORCE = physx_sys::PxSceneFlag::eADAPTIVE_FORCE;
@@ -113,6 +113,15 @@ impl PxShape {
     pub fn set_rest_offset(&self, offset: f32) {
         unsafe { physx_sys::PxShape_setRestOffset_mut(self.0, offset) }
     }
+    pub fn set_materials(&self, materials: &[&PxMaterial]) {
+        let mats = materials
+            .iter()
+            .map(|x| x.0)
+            .collect::<Vec<*mut physx_sys::PxMaterial>>();
+        unsafe {
+            physx_sys::PxShape_setMaterials_mut(self.0, mats.as_ptr(), materials.len() as u16)
+        }
+    }
 }
 impl AsPxBase for PxShape {
     fn as_base(&self) -> PxBaseRef {
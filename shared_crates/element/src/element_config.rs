@@ -19,6 +19,8 @@ pub(crate) struct ElementConfig {
     #[derivative(Debug = "ignore")]
     pub despawner: Arc<dyn Fn(&mut World, EntityId) + Sync + Send>,
     #[derivative(Debug = "ignore")]
+    pub deferred_despawner: Option<Arc<dyn Fn(&mut World, EntityId) -> bool + Sync + Send>>,
+    #[derivative(Debug = "ignore")]
     pub on_spawned: Option<Arc<dyn Fn(&mut World, EntityId, &str) + Sync + Send>>,
     #[derivative(Debug = "ignore")]
     pub on_despawn: Option<Arc<dyn Fn(&mut World, EntityId, &str) + Sync + Send>>,
@@ -35,6 +37,7 @@ impl ElementConfig {
             despawner: Arc::new(|world, entity| {
                 world.despawn(entity);
             }),
+            deferred_despawner: None,
             on_spawned: None,
             on_despawn: None,
             key: "".to_string(),
@@ -220,6 +220,18 @@ impl Element {
         self.config.despawner = Arc::new(handler);
         self
     }
+    /// Set a function that can take over despawning the element, e.g. to play an exit animation first.
+    ///
+    /// If it returns `true`, neither the entity of this element nor the entities of its descendants
+    /// are despawned by the tree; the function is responsible for despawning them later. If it returns
+    /// `false`, the element is despawned as usual.
+    pub fn deferred_despawner<F: Fn(&mut World, EntityId) -> bool + Sync + Send + 'static>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.config.deferred_despawner = Some(Arc::new(handler));
+        self
+    }
     /// Set the callback to call when the element is spawned. The third argument is the instance ID.
     pub fn on_spawned<F: Fn(&mut World, EntityId, &str) + Sync + Send + 'static>(
         mut self,
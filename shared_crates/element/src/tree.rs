@@ -473,6 +473,11 @@ impl ElementTree {
         entity
     }
     fn remove(&mut self, world: &mut World, instance_id: &str) {
+        self.remove_instance(world, instance_id, true);
+    }
+    /// Removes the instance and its descendants. If `despawn` is false, their entities are left
+    /// in the world, as an ancestor has taken responsibility for despawning them.
+    fn remove_instance(&mut self, world: &mut World, instance_id: &str, mut despawn: bool) {
         let mut instance = self.instances.remove(instance_id).unwrap();
 
         if let Some(on_despawn) = &instance.config.on_despawn {
@@ -481,7 +486,12 @@ impl ElementTree {
         for on_despawn in std::mem::take(&mut instance.hooks_on_despawn) {
             on_despawn(world);
         }
-        if instance.config.part.is_none() {
+        if despawn {
+            if let Some(deferred_despawner) = &instance.config.deferred_despawner {
+                despawn = !deferred_despawner(world, instance.entity);
+            }
+        }
+        if despawn && instance.config.part.is_none() {
             (instance.config.despawner)(world, instance.entity);
         }
         instance.entity = EntityId::null();
@@ -497,10 +507,10 @@ impl ElementTree {
         }
 
         if let Some(super_) = &instance.super_ {
-            self.remove(world, super_);
+            self.remove_instance(world, super_, despawn);
         }
         for child in &instance.children {
-            self.remove(world, child);
+            self.remove_instance(world, child, despawn);
         }
     }
 
@@ -21,6 +21,12 @@ pub struct BuildMetadata {
     pub settings: BuildSettings,
     #[serde(default)]
     pub asset: Vec<BuildAsset>,
+    /// Paths of the built assets that are locale-specific variants of another asset
+    #[serde(default)]
+    pub localized_assets: Vec<String>,
+    /// The names of the quality tiers that the assets were additionally built for
+    #[serde(default)]
+    pub quality_tiers: Vec<String>,
 }
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default)]
 pub struct BuildSettings {
@@ -1,3 +1,4 @@
+use ambient_shared_types::Replication;
 use serde::{Deserialize, Serialize};
 
 use crate::ItemPathBuf;
@@ -12,6 +13,9 @@ pub struct Component {
     pub attributes: Vec<ItemPathBuf>,
     #[serde(default)]
     pub default: Option<toml::Value>,
+    /// How often changes to this component are sent to the clients, if it is `Networked`
+    #[serde(default)]
+    pub replication: Option<Replication>,
 }
 
 #[derive(Deserialize, Clone, Debug, PartialEq, Eq, Serialize)]
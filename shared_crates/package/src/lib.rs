@@ -12,3 +12,5 @@ mod enum_;
 pub use enum_::*;
 mod build_metadata;
 pub use build_metadata::*;
+pub mod localization;
+pub mod quality_tier;
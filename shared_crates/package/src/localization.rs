@@ -0,0 +1,100 @@
+//! Helpers for locale-specific asset variants.
+//!
+//! A variant of an asset is named after the default asset, with the locale appended to its
+//! file stem using an underscore: `sign_de.png` is the German variant of `sign.png`. Only locales
+//! listed in the `[localization]` section of the manifest are recognized.
+
+/// If `path` is a variant for one of the given `locales`, returns that locale.
+pub fn variant_locale<'a>(path: &str, locales: &'a [String]) -> Option<&'a str> {
+    let (_, stem, _) = split_path(path);
+    locales
+        .iter()
+        .map(|l| l.as_str())
+        // prefer the longest matching locale, so that `pt_BR` is not mistaken for `BR`
+        .filter(|locale| {
+            stem.strip_suffix(locale)
+                .and_then(|s| s.strip_suffix('_'))
+                .is_some_and(|s| !s.is_empty())
+        })
+        .max_by_key(|locale| locale.len())
+}
+
+/// Returns the path of the variant of `path` for `locale`.
+pub fn variant_path(path: &str, locale: &str) -> String {
+    let (directory, stem, extension) = split_path(path);
+    format!("{directory}{stem}_{locale}{extension}")
+}
+
+/// Returns the best variant of `path` for `locale`, if any, using `exists` to check for the
+/// existence of a variant.
+///
+/// The full locale is tried first (e.g. `pt-BR`), followed by its language (e.g. `pt`).
+pub fn resolve_variant(path: &str, locale: &str, exists: impl Fn(&str) -> bool) -> Option<String> {
+    let language = locale.split(['-', '_']).next().unwrap_or(locale);
+    [locale, language]
+        .into_iter()
+        .filter(|l| !l.is_empty())
+        .map(|l| variant_path(path, l))
+        .find(|p| exists(p))
+}
+
+/// Splits `path` into its directory (including the trailing separator), file stem and extension
+/// (including the leading dot).
+fn split_path(path: &str) -> (&str, &str, &str) {
+    let (directory, file_name) = match path.rfind('/') {
+        Some(index) => path.split_at(index + 1),
+        None => ("", path),
+    };
+    match file_name.find('.') {
+        Some(index) if index > 0 => {
+            let (stem, extension) = file_name.split_at(index);
+            (directory, stem, extension)
+        }
+        _ => (directory, file_name, ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locales() -> Vec<String> {
+        vec!["de".to_string(), "pt".to_string(), "pt_BR".to_string()]
+    }
+
+    #[test]
+    fn variant_locale_recognizes_declared_locales_only() {
+        let locales = locales();
+        assert_eq!(variant_locale("assets/sign_de.png", &locales), Some("de"));
+        assert_eq!(
+            variant_locale("assets/sign_pt_BR.ogg", &locales),
+            Some("pt_BR")
+        );
+        assert_eq!(variant_locale("assets/sign_fr.png", &locales), None);
+        assert_eq!(variant_locale("assets/sign.png", &locales), None);
+        assert_eq!(variant_locale("assets/de.png", &locales), None);
+    }
+
+    #[test]
+    fn variant_path_inserts_locale_before_extension() {
+        assert_eq!(variant_path("assets/sign.png", "de"), "assets/sign_de.png");
+        assert_eq!(variant_path("vo/line.tar.gz", "de"), "vo/line_de.tar.gz");
+        assert_eq!(variant_path("readme", "de"), "readme_de");
+    }
+
+    #[test]
+    fn resolve_variant_falls_back_to_language() {
+        let available = ["assets/sign_pt.png", "assets/voice_pt_BR.ogg"];
+        let exists = |p: &str| available.contains(&p);
+
+        assert_eq!(
+            resolve_variant("assets/voice.ogg", "pt_BR", exists).as_deref(),
+            Some("assets/voice_pt_BR.ogg")
+        );
+        assert_eq!(
+            resolve_variant("assets/sign.png", "pt-BR", exists).as_deref(),
+            Some("assets/sign_pt.png")
+        );
+        assert_eq!(resolve_variant("assets/sign.png", "de", exists), None);
+    }
+}
@@ -8,7 +8,8 @@ use sha2::Digest;
 use thiserror::Error;
 
 use crate::{
-    Component, Concept, Enum, ItemPathBuf, Message, PascalCaseIdentifier, SnakeCaseIdentifier,
+    quality_tier::QualityTier, Component, Concept, Enum, ItemPathBuf, Message,
+    PascalCaseIdentifier, SnakeCaseIdentifier,
 };
 
 #[derive(Error, Debug, PartialEq)]
@@ -42,6 +43,14 @@ pub struct Manifest {
     pub dependencies: IndexMap<SnakeCaseIdentifier, Dependency>,
     #[serde(default)]
     pub hosting: Hosting,
+    #[serde(default)]
+    pub analytics: Analytics,
+    #[serde(default)]
+    pub localization: Localization,
+    #[serde(default)]
+    pub loading_screen: LoadingScreen,
+    #[serde(default)]
+    pub http: Http,
 }
 impl Manifest {
     pub fn parse(manifest: &str) -> Result<Self, ManifestParseError> {
@@ -183,7 +192,8 @@ fn return_true() -> bool {
     true
 }
 
-// ----- NOTE: Update docs/reference/package.md when changing this ----
+// ----- NOTE: Update docs/reference/package.md and crates/build/src/validate/manifest.schema.json
+// when changing this ----
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -230,6 +240,10 @@ impl Default for PackageContent {
 pub struct Build {
     #[serde(default)]
     pub rust: BuildRust,
+    /// Additional builds of the assets for less capable platforms, keyed by tier name
+    #[serde(default)]
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub tiers: IndexMap<String, QualityTier>,
 }
 
 #[derive(Deserialize, Clone, Debug, PartialEq, Serialize)]
@@ -275,7 +289,65 @@ pub struct Hosting {
     pub region: Region,
     /// The maximum number of players that can be connected at once (0 = unlimited)
     #[serde(default)]
-    pub max_players: usize,
+    pub max_players: u32,
+    /// The number of times per second the server simulation is stepped (defaults to 60)
+    #[serde(default)]
+    pub tick_rate: Option<u32>,
+    /// Whether a client takes over as the server when the player hosting the session quits
+    #[serde(default)]
+    pub host_migration: bool,
+}
+
+#[derive(Deserialize, Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Analytics {
+    /// The HTTPS endpoint that analytics events will be sent to. If not specified, events are discarded.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Localization {
+    /// The locales that this package has asset variants for (e.g. `de` or `pt_BR`)
+    #[serde(default)]
+    pub locales: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default, PartialEq, Serialize)]
+pub struct LoadingScreen {
+    /// The path of an image in the assets of this package, shown behind the loading screen
+    #[serde(default)]
+    pub background: Option<String>,
+    /// Tips shown one at a time on the loading screen
+    #[serde(default)]
+    pub tips: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Http {
+    /// The domains that the modules of this package can send HTTP requests to. `*.example.com`
+    /// allows all subdomains of `example.com`.
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+}
+impl Http {
+    /// Returns whether `host` is matched by one of the [Self::allowed_domains].
+    pub fn allows(&self, host: &str) -> bool {
+        allowed_domains_match(&self.allowed_domains, host)
+    }
+}
+
+/// Returns whether `host` is matched by one of the `allowed_domains` of an [Http] section.
+pub fn allowed_domains_match(allowed_domains: &[String], host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    allowed_domains.iter().any(|domain| {
+        let domain = domain.to_ascii_lowercase();
+        match domain.strip_prefix("*.") {
+            Some(parent) => host
+                .strip_suffix(parent)
+                .map_or(false, |subdomain| subdomain.ends_with('.')),
+            None => host == domain,
+        }
+    })
 }
 
 #[derive(Deserialize, Clone, Debug, Default, PartialEq, Serialize)]
@@ -293,12 +365,14 @@ pub enum Region {
 mod tests {
     use std::path::PathBuf;
 
+    use ambient_shared_types::Replication;
     use indexmap::IndexMap;
 
     use crate::{
-        Build, BuildRust, Component, ComponentType, Components, Concept, ConceptValue,
-        ContainerType, Dependency, Enum, Identifier, ItemPathBuf, Manifest, ManifestParseError,
-        Package, PackageId, PascalCaseIdentifier, SnakeCaseIdentifier,
+        quality_tier::QualityTier, Analytics, Build, BuildRust, Component, ComponentType,
+        Components, Concept, ConceptValue, ContainerType, Dependency, Enum, Identifier,
+        ItemPathBuf, LoadingScreen, Manifest, ManifestParseError, Package, PackageId,
+        PascalCaseIdentifier, SnakeCaseIdentifier,
     };
     use semver::Version;
 
@@ -342,6 +416,105 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_parse_analytics_section() {
+        const TOML: &str = r#"
+        [package]
+        id = "lktsfudbjw2qikhyumt573ozxhadkiwm"
+        name = "Test"
+        version = "0.0.1"
+        content = { type = "Playable" }
+
+        [analytics]
+        endpoint = "https://analytics.example.com/events"
+        "#;
+
+        assert_eq!(
+            Manifest::parse(TOML),
+            Ok(Manifest {
+                package: Package {
+                    id: Some(PackageId("lktsfudbjw2qikhyumt573ozxhadkiwm".to_string())),
+                    name: "Test".to_string(),
+                    version: Version::parse("0.0.1").unwrap(),
+                    ..Default::default()
+                },
+                analytics: Analytics {
+                    endpoint: Some("https://analytics.example.com/events".to_string()),
+                },
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn can_parse_loading_screen_section() {
+        const TOML: &str = r#"
+        [package]
+        id = "lktsfudbjw2qikhyumt573ozxhadkiwm"
+        name = "Test"
+        version = "0.0.1"
+        content = { type = "Playable" }
+
+        [loading_screen]
+        background = "loading.png"
+        tips = ["Press E to interact", "Crouch to sneak past guards"]
+        "#;
+
+        assert_eq!(
+            Manifest::parse(TOML),
+            Ok(Manifest {
+                package: Package {
+                    id: Some(PackageId("lktsfudbjw2qikhyumt573ozxhadkiwm".to_string())),
+                    name: "Test".to_string(),
+                    version: Version::parse("0.0.1").unwrap(),
+                    ..Default::default()
+                },
+                loading_screen: LoadingScreen {
+                    background: Some("loading.png".to_string()),
+                    tips: vec![
+                        "Press E to interact".to_string(),
+                        "Crouch to sneak past guards".to_string(),
+                    ],
+                },
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn can_parse_http_section() {
+        const TOML: &str = r#"
+        [package]
+        id = "lktsfudbjw2qikhyumt573ozxhadkiwm"
+        name = "Test"
+        version = "0.0.1"
+        content = { type = "Playable" }
+
+        [http]
+        allowed_domains = ["leaderboard.example.com", "*.example.net"]
+        "#;
+
+        let manifest = Manifest::parse(TOML).unwrap();
+        assert_eq!(
+            manifest.http,
+            Http {
+                allowed_domains: vec![
+                    "leaderboard.example.com".to_string(),
+                    "*.example.net".to_string()
+                ],
+            }
+        );
+
+        assert!(manifest.http.allows("leaderboard.example.com"));
+        assert!(manifest.http.allows("LEADERBOARD.example.com"));
+        assert!(!manifest.http.allows("example.com"));
+        assert!(!manifest.http.allows("evil-leaderboard.example.com"));
+        assert!(manifest.http.allows("api.example.net"));
+        assert!(manifest.http.allows("eu.api.example.net"));
+        assert!(!manifest.http.allows("example.net"));
+        assert!(!manifest.http.allows("notexample.net"));
+    }
+
     #[test]
     fn will_fail_on_legacy_project_toml() {
         const TOML: &str = r#"
@@ -388,7 +561,8 @@ mod tests {
                 build: Build {
                     rust: BuildRust {
                         feature_multibuild: vec!["client".to_string(), "server".to_string()]
-                    }
+                    },
+                    ..Default::default()
                 },
                 components: IndexMap::from_iter([(
                     ipb("cell"),
@@ -398,6 +572,7 @@ mod tests {
                         type_: ComponentType::Item(i("i32").into()),
                         attributes: vec![i("store").into()],
                         default: None,
+                        replication: None,
                     }
                 )]),
                 concepts: IndexMap::from_iter([(
@@ -446,7 +621,58 @@ mod tests {
                 build: Build {
                     rust: BuildRust {
                         feature_multibuild: vec!["client".to_string()]
-                    }
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+        )
+    }
+
+    #[test]
+    fn can_parse_build_tiers() {
+        const TOML: &str = r#"
+        [package]
+        id = "lktsfudbjw2qikhyumt573ozxhadkiwm"
+        name = "Test"
+        version = "0.0.1"
+        content = { type = "Playable" }
+
+        [build.tiers.web-low]
+        max_texture_size = 512
+        max_lod_levels = 2
+
+        [build.tiers.desktop-low]
+        max_texture_size = 1024
+        "#;
+
+        assert_eq!(
+            Manifest::parse(TOML),
+            Ok(Manifest {
+                package: Package {
+                    id: Some(PackageId("lktsfudbjw2qikhyumt573ozxhadkiwm".to_string())),
+                    name: "Test".to_string(),
+                    version: Version::parse("0.0.1").unwrap(),
+                    ..Default::default()
+                },
+                build: Build {
+                    tiers: IndexMap::from_iter([
+                        (
+                            "web-low".to_string(),
+                            QualityTier {
+                                max_texture_size: Some(512),
+                                max_lod_levels: Some(2),
+                            }
+                        ),
+                        (
+                            "desktop-low".to_string(),
+                            QualityTier {
+                                max_texture_size: Some(1024),
+                                max_lod_levels: None,
+                            }
+                        ),
+                    ]),
+                    ..Default::default()
                 },
                 ..Default::default()
             })
@@ -497,7 +723,8 @@ mod tests {
                 build: Build {
                     rust: BuildRust {
                         feature_multibuild: vec!["client".to_string(), "server".to_string()]
-                    }
+                    },
+                    ..Default::default()
                 },
                 components: IndexMap::from_iter([
                     (
@@ -508,6 +735,7 @@ mod tests {
                             type_: ComponentType::Item(i("quat").into()),
                             attributes: vec![],
                             default: None,
+                            replication: None,
                         }
                     ),
                     (
@@ -518,6 +746,7 @@ mod tests {
                             type_: ComponentType::Item(i("vec3").into()),
                             attributes: vec![],
                             default: None,
+                            replication: None,
                         }
                     ),
                     (
@@ -528,6 +757,7 @@ mod tests {
                             type_: ComponentType::Item(i("empty").into()),
                             attributes: vec![],
                             default: None,
+                            replication: None,
                         }
                     ),
                     (
@@ -538,6 +768,7 @@ mod tests {
                             type_: ComponentType::Item(i("vec3").into()),
                             attributes: vec![],
                             default: None,
+                            replication: None,
                         }
                     ),
                 ]),
@@ -686,7 +917,8 @@ mod tests {
                 build: Build {
                     rust: BuildRust {
                         feature_multibuild: vec!["client".to_string(), "server".to_string()]
-                    }
+                    },
+                    ..Default::default()
                 },
                 components: IndexMap::from_iter([
                     (
@@ -697,6 +929,7 @@ mod tests {
                             type_: ComponentType::Item(i("I32").into()),
                             attributes: vec![],
                             default: None,
+                            replication: None,
                         }
                     ),
                     (
@@ -710,6 +943,7 @@ mod tests {
                             },
                             attributes: vec![],
                             default: None,
+                            replication: None,
                         }
                     ),
                     (
@@ -723,6 +957,7 @@ mod tests {
                             },
                             attributes: vec![],
                             default: None,
+                            replication: None,
                         }
                     )
                 ]),
@@ -731,6 +966,28 @@ mod tests {
         )
     }
 
+    #[test]
+    fn can_parse_replication() {
+        const TOML: &str = r#"
+        [package]
+        id = "lktsfudbjw2qikhyumt573ozxhadkiwm"
+        name = "Test"
+        version = "0.0.1"
+        content = { type = "Playable" }
+
+        [components]
+        position = { type = "Vec3", attributes = ["Networked"], replication = "every_tick" }
+        health = { type = "F32", attributes = ["Networked"], replication = { interval = 10 } }
+        inventory = { type = { container_type = "Vec", element_type = "String" }, attributes = ["Networked"], replication = "on_change" }
+        "#;
+
+        let manifest = Manifest::parse(TOML).unwrap();
+        let replication = |path: &str| manifest.components.get(&ipb(path)).unwrap().replication;
+        assert_eq!(replication("position"), Some(Replication::EveryTick));
+        assert_eq!(replication("health"), Some(Replication::Interval(10)));
+        assert_eq!(replication("inventory"), Some(Replication::OnChange));
+    }
+
     #[test]
     fn can_parse_dependencies() {
         const TOML: &str = r#"
@@ -0,0 +1,89 @@
+//! Helpers for quality tiers.
+//!
+//! A quality tier is an additional build of a package's assets with reduced texture sizes and
+//! model LODs, declared in the `[build.tiers]` section of the manifest. Each tier is built to
+//! `tiers/<name>/assets`, mirroring the default `assets` directory.
+//!
+//! Tiers are named after the platform and GPU tier they target (e.g. `web-low` or
+//! `desktop-high`); a client selects the tier matching its own platform and GPU tier, falling
+//! back to a tier for its platform (e.g. `web`), and then to the default assets.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Clone, Debug, Default, PartialEq, Serialize)]
+pub struct QualityTier {
+    /// The maximum width and height of textures in this tier. Larger textures are downscaled.
+    #[serde(default)]
+    pub max_texture_size: Option<u32>,
+    /// The maximum number of LOD levels of models in this tier. The most detailed levels are
+    /// dropped first.
+    #[serde(default)]
+    pub max_lod_levels: Option<usize>,
+}
+
+/// The capability of a client's GPU, used to select a quality tier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GpuTier {
+    Low,
+    High,
+}
+impl GpuTier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GpuTier::Low => "low",
+            GpuTier::High => "high",
+        }
+    }
+}
+
+/// Returns the name of the tier that best matches a client on `platform` (`web` or `desktop`)
+/// with a GPU of `gpu_tier`.
+pub fn target_tier(platform: &str, gpu_tier: GpuTier) -> String {
+    format!("{platform}-{}", gpu_tier.as_str())
+}
+
+/// Returns the path of the assets directory of `tier`, relative to the package's build directory.
+pub fn assets_path(tier: &str) -> String {
+    format!("tiers/{tier}/assets")
+}
+
+/// Returns the best of the available `tiers` for `target`, if any.
+///
+/// The full tier is tried first (e.g. `web-low`), followed by its platform (e.g. `web`).
+pub fn resolve_tier<'a>(target: &str, tiers: &'a [String]) -> Option<&'a str> {
+    let platform = target.split('-').next().unwrap_or(target);
+    [target, platform]
+        .into_iter()
+        .filter(|t| !t.is_empty())
+        .find_map(|t| tiers.iter().find(|tier| *tier == t))
+        .map(|t| t.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_tier_falls_back_to_platform() {
+        let tiers = vec!["web".to_string(), "desktop-low".to_string()];
+
+        assert_eq!(
+            resolve_tier(&target_tier("desktop", GpuTier::Low), &tiers),
+            Some("desktop-low")
+        );
+        assert_eq!(
+            resolve_tier(&target_tier("web", GpuTier::High), &tiers),
+            Some("web")
+        );
+        assert_eq!(
+            resolve_tier(&target_tier("desktop", GpuTier::High), &tiers),
+            None
+        );
+        assert_eq!(resolve_tier("web-low", &[]), None);
+    }
+
+    #[test]
+    fn assets_path_is_relative_to_build_directory() {
+        assert_eq!(assets_path("web-low"), "tiers/web-low/assets");
+    }
+}
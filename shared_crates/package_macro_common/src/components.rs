@@ -1,4 +1,5 @@
 use ambient_package_semantic::{ItemMap, Scope};
+use ambient_shared_types::Replication;
 use proc_macro2::TokenStream;
 use quote::quote;
 
@@ -77,6 +78,9 @@ pub fn generate(
             if let Some(default) = component.default.as_ref().and_then(|c| c.as_resolved()) {
                 doc_comment += &format!("\n\n*Suggested Default*: {default}")
             }
+            if let Some(replication) = component.replication {
+                doc_comment += &format!("\n\n*Replication*: {replication:?}")
+            }
 
             let doc_comment = doc_comment.trim();
 
@@ -88,10 +92,18 @@ pub fn generate(
                         .map(|s| make_path(s.as_str()))
                         .collect();
                     let description = component.description.to_owned().unwrap_or_default();
+                    let replication = component.replication.map(|replication| {
+                        let value = match replication {
+                            Replication::EveryTick => quote! { EveryTick },
+                            Replication::Interval(ticks) => quote! { Interval(#ticks) },
+                            Replication::OnChange => quote! { OnChange },
+                        };
+                        quote! { crate::Replication[crate::Replication::#value], }
+                    });
 
                     Ok(quote! {
                         #[doc = #doc_comment]
-                        @[#(#attributes,)* Name[#name], Description[#description]]
+                        @[#(#attributes,)* #replication Name[#name], Description[#description]]
                         #ident: #ty,
                     })
                 }
@@ -1,4 +1,5 @@
 use ambient_package::ItemPathBuf;
+use ambient_shared_types::Replication;
 use anyhow::Context as AnyhowContext;
 
 use crate::{
@@ -15,6 +16,7 @@ pub struct Component {
     pub type_: ResolvableItemId<Type>,
     pub attributes: Vec<ResolvableItemId<Attribute>>,
     pub default: Option<ResolvableValue>,
+    pub replication: Option<Replication>,
 
     resolved: bool,
 }
@@ -92,6 +94,13 @@ impl Resolve for Component {
             default.resolve_in_place(&semantic.items, type_id)?;
         }
 
+        if self.replication == Some(Replication::Interval(0)) {
+            anyhow::bail!(
+                "The replication interval of component `{}` must be at least 1 tick",
+                self.data.id
+            );
+        }
+
         self.resolved = true;
 
         Ok(self)
@@ -117,6 +126,7 @@ impl Component {
                 .default
                 .as_ref()
                 .map(|v| ResolvableValue::Unresolved(v.clone())),
+            replication: value.replication,
             resolved: false,
         }
     }
@@ -150,6 +150,9 @@ impl Printer {
             p.print_indent();
             println!("default: {:?}", component.default);
 
+            p.print_indent();
+            println!("replication: {:?}", component.replication);
+
             p.print_indent();
             println!("attributes:");
             p.with_indent(|p| {
@@ -29,4 +29,11 @@ pub enum AssetType {
     /// Represents a vorbis backed file
     VorbisTrack,
     SoundGraph,
+    /// A bank of named audio events
+    AudioEventBank,
+
+    /// A TrueType or OpenType font
+    Font,
+    /// A signed distance field atlas generated from a font
+    FontAtlas,
 }
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+/// A button of a gamepad, named after its position on an Xbox-style controller.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, EnumString, Display, Serialize, Deserialize)]
+pub enum GamepadButton {
+    /// The bottom button of the action pad (A on Xbox controllers).
+    South,
+    /// The right button of the action pad (B on Xbox controllers).
+    East,
+    /// The top button of the action pad (Y on Xbox controllers).
+    North,
+    /// The left button of the action pad (X on Xbox controllers).
+    West,
+    /// The left shoulder button, above the left trigger.
+    LeftShoulder,
+    /// The left trigger.
+    LeftTrigger,
+    /// The right shoulder button, above the right trigger.
+    RightShoulder,
+    /// The right trigger.
+    RightTrigger,
+    /// The left menu button, such as Back or Share.
+    Select,
+    /// The right menu button, such as Start or Options.
+    Start,
+    /// The button in the middle of the controller, such as the Xbox or PlayStation button.
+    Mode,
+    /// Pressing down the left stick.
+    LeftStick,
+    /// Pressing down the right stick.
+    RightStick,
+    /// Up on the directional pad.
+    DPadUp,
+    /// Down on the directional pad.
+    DPadDown,
+    /// Left on the directional pad.
+    DPadLeft,
+    /// Right on the directional pad.
+    DPadRight,
+}
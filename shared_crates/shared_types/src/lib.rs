@@ -1,10 +1,17 @@
 mod winit;
 pub use crate::winit::*;
 
+mod gamepad;
+pub use crate::gamepad::*;
+
 mod procedurals;
 pub use crate::procedurals::*;
 
+mod replication;
+pub use crate::replication::*;
+
 pub mod asset;
+pub mod markup;
 pub mod urls;
 
 pub type ComponentIndex = u32;
@@ -0,0 +1,45 @@
+//! The parsed form of UI markup documents.
+//!
+//! Documents are parsed by the runtime, and turned into UI elements by the `ui` crate.
+
+/// A node of a parsed UI markup document.
+///
+/// A document is a list of nodes in document order, where each node refers to its parent by its
+/// index in the list.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MarkupNode {
+    /// The index of the parent node, or `None` for the nodes at the root of the document.
+    pub parent: Option<u32>,
+    /// The tag of the element, or an empty string for text nodes.
+    pub tag: String,
+    /// The attributes of the element, in the order they were written in.
+    pub attributes: Vec<(String, String)>,
+    /// The style properties of the element, after the style rules and the `style` attribute
+    /// have been applied.
+    pub style: Vec<(String, String)>,
+    /// The text of a text node.
+    pub text: String,
+}
+impl MarkupNode {
+    /// Whether this is a text node.
+    pub fn is_text(&self) -> bool {
+        self.tag.is_empty()
+    }
+
+    /// Returns the value of the attribute `name`, if it is set.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        find(&self.attributes, name)
+    }
+
+    /// Returns the value of the style property `name`, if it is set.
+    pub fn style(&self, name: &str) -> Option<&str> {
+        find(&self.style, name)
+    }
+}
+
+fn find<'a>(pairs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    pairs
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
@@ -6,7 +6,7 @@ use ulid::Ulid;
 macro_rules! procedural_storage_handle_definitions {
     ($macro_to_instantiate:ident) => {
         // Handle names must be in snake_case.
-        $macro_to_instantiate!(mesh, texture, sampler, material);
+        $macro_to_instantiate!(mesh, texture, sampler, material, buffer, compute);
     };
 }
 
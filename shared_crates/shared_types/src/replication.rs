@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// How often the changes to a networked component are sent to the clients.
+///
+/// In a package manifest, this is written as `replication = "every_tick"`,
+/// `replication = { interval = 10 }` or `replication = "on_change"`.
+#[derive(Debug, Default, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Replication {
+    /// The changes are sent at the end of the tick they were made in.
+    #[default]
+    EveryTick,
+    /// The changes are sent at most once every this many ticks, with the latest value of the
+    /// component. Suited to data that changes often, but that the clients don't need right away.
+    Interval(u32),
+    /// The changes are only sent when the value differs from the last value that was sent. Suited
+    /// to slowly-changing data that is written every tick, such as names or inventories.
+    OnChange,
+}
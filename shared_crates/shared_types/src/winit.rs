@@ -803,3 +803,27 @@ impl From<MouseButton> for winit::event::MouseButton {
         }
     }
 }
+
+/// The phase of a touch on a touchscreen.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, EnumString, Display, Serialize, Deserialize)]
+pub enum TouchPhase {
+    /// The finger or stylus started touching the screen.
+    Started,
+    /// The finger or stylus moved while touching the screen.
+    Moved,
+    /// The finger or stylus stopped touching the screen.
+    Ended,
+    /// The system cancelled the touch, for instance because the window lost focus.
+    Cancelled,
+}
+#[cfg(feature = "native")]
+impl From<winit::event::TouchPhase> for TouchPhase {
+    fn from(value: winit::event::TouchPhase) -> Self {
+        match value {
+            winit::event::TouchPhase::Started => Self::Started,
+            winit::event::TouchPhase::Moved => Self::Moved,
+            winit::event::TouchPhase::Ended => Self::Ended,
+            winit::event::TouchPhase::Cancelled => Self::Cancelled,
+        }
+    }
+}
@@ -11,8 +11,8 @@ use std::{
 use ambient_cb::{cb, Callback, Cb};
 use ambient_color::Color;
 use ambient_element::{
-    element_component, to_owned, use_effect, use_runtime_message, use_state, use_state_with,
-    Element, ElementComponent, ElementComponentExt, Hooks,
+    element_component, to_owned, use_effect, use_module_message, use_runtime_message, use_state,
+    use_state_with, Element, ElementComponent, ElementComponentExt, Hooks,
 };
 use ambient_guest_bridge::{
     core::{
@@ -27,6 +27,10 @@ use ambient_guest_bridge::{
         rect::components::{border_color, border_radius, border_thickness},
         rendering::components::color,
         text::{components::font_style, types::FontStyle},
+        ui::{
+            components::{focus_cancel_action, focus_default_action, focus_navigable, focusable},
+            messages::FocusActivated,
+        },
     },
     ecs::World,
     run_async,
@@ -41,7 +45,7 @@ use crate::{
     dropdown::Tooltip,
     layout::{FlowColumn, FlowRow},
     text::Text,
-    use_keyboard_input, UIBase, UIElement, UIExt,
+    use_focus, use_keyboard_input, UIBase, UIElement, UIExt,
 };
 
 #[derive(Clone, Debug)]
@@ -96,6 +100,7 @@ impl ButtonStyle {
         disabled: bool,
         toggled: bool,
         hover: bool,
+        focused: bool,
         hotkey: Option<VirtualKeyCode>,
         hotkey_modifier: ModifiersState,
         tooltip: Option<Element>,
@@ -168,7 +173,11 @@ impl ButtonStyle {
                     .el()
                     .with(fit_horizontal(), Fit::Parent)
                     .with(height(), 2.)
-                    .with_background(Color::WHITE.into())
+                    .with_background(if focused {
+                        primary_color().into()
+                    } else {
+                        Color::WHITE.into()
+                    })
                     .with(margin(), vec4(2., 0., 0., 0.)),
             ])
             .with_background(background.into())
@@ -218,7 +227,7 @@ impl ButtonStyle {
                         _ => Vec4::ONE * 26. / 2.,
                     },
                 )
-                .with(border_thickness(), 0.)
+                .with(border_thickness(), if focused { 2. } else { 0. })
                 .with(border_color(), Color::WHITE.into());
             if *self != Self::Flat {
                 el = el.with(min_height(), 26.);
@@ -254,11 +263,26 @@ pub fn Button(
     on_invoked: ButtonCb,
     /// The callback to invoke when the current pressed state changes.
     on_is_pressed_changed: Option<Cb<dyn Fn(&mut World, bool) + Sync + Send>>,
+    /// Whether or not the button is invoked when Enter is pressed while no other button is focused.
+    default_action: bool,
+    /// Whether or not the button is invoked when Escape is pressed.
+    cancel_action: bool,
 ) -> Element {
     let (is_pressed, set_is_pressed) = use_state(hooks, false);
     let (hover, set_hover) = use_state(hooks, false);
     let (is_working, set_is_working) = use_state(hooks, false);
     let (is_pressed_immediate, _) = use_state_with(hooks, |_| Arc::new(AtomicBool::new(false)));
+    let focus_id = hooks.instance_id().to_string();
+    let (focused, _) = use_focus(hooks);
+
+    use_module_message::<FocusActivated>(hooks, {
+        to_owned![focus_id, on_invoked, set_is_working];
+        move |world, _, event| {
+            if event.focus == focus_id && !disabled {
+                on_invoked.invoke(world, set_is_working.clone());
+            }
+        }
+    });
 
     use_effect(hooks, is_pressed, move |world, _| {
         if let Some(on_is_pressed_changed) = on_is_pressed_changed {
@@ -294,6 +318,7 @@ pub fn Button(
             disabled,
             toggled,
             hover,
+            focused && !disabled,
             hotkey,
             hotkey_modifier,
             tooltip,
@@ -314,8 +339,20 @@ pub fn Button(
         .el();
 
     if disabled {
-        content
-    } else if let Some(hotkey) = hotkey {
+        return content;
+    }
+
+    let mut content = content
+        .with(focusable(), focus_id)
+        .with(focus_navigable(), ());
+    if default_action {
+        content = content.with(focus_default_action(), ());
+    }
+    if cancel_action {
+        content = content.with(focus_cancel_action(), ());
+    }
+
+    if let Some(hotkey) = hotkey {
         Hotkey {
             hotkey,
             hotkey_modifier,
@@ -355,6 +392,8 @@ impl Button {
             tooltip: None,
             on_invoked: ButtonCb::Sync(on_invoked),
             on_is_pressed_changed: None,
+            default_action: false,
+            cancel_action: false,
         }
     }
     /// Create a new one-shot [Button] with the given content and a callback that is only invoked once.
@@ -388,6 +427,8 @@ impl Button {
             tooltip: None,
             on_invoked: ButtonCb::Async(cb(move |_w| on_invoked().boxed())),
             on_is_pressed_changed: None,
+            default_action: false,
+            cancel_action: false,
         }
     }
     /// Create a new one-shot [Button] with the given content and a callback that returns a [Future] (i.e. is `async`) and is only invoked once.
@@ -456,6 +497,17 @@ impl Button {
         self.on_is_pressed_changed = Some(cb(handle));
         self
     }
+    /// Set whether or not the button is invoked when Enter is pressed while no other button is
+    /// focused, e.g. to confirm a dialog.
+    pub fn default_action(mut self, default_action: bool) -> Self {
+        self.default_action = default_action;
+        self
+    }
+    /// Set whether or not the button is invoked when Escape is pressed, e.g. to close a menu.
+    pub fn cancel_action(mut self, cancel_action: bool) -> Self {
+        self.cancel_action = cancel_action;
+        self
+    }
 }
 
 #[derive(Clone, Debug)]
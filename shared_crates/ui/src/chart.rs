@@ -0,0 +1,376 @@
+//! Line charts, bar charts and histograms, for debug overlays and game tools.
+//!
+//! Charts are usually fed by a [TimeSeries], such as the frame times kept by [use_time_series].
+use std::collections::VecDeque;
+
+use ambient_element::{
+    element_component, use_frame, use_ref_with, use_state_with, Element, ElementComponentExt, Hooks,
+};
+use ambient_guest_bridge::{
+    core::{
+        layout::components::{height, width},
+        rect::components::{background_color, line_from, line_to, line_width},
+        text::components::font_size,
+        transform::components::translation,
+    },
+    ecs::World,
+};
+use glam::{vec3, Vec2, Vec4};
+
+use crate::{
+    default_theme::{cutout_color, primary_color},
+    text::Text,
+    with_rect, Line, Rectangle, UIBase,
+};
+
+/// The latest values of a time series, such as frame times, bandwidth or a custom metric.
+///
+/// Once the series holds `capacity` values, adding a value removes the oldest one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeSeries {
+    values: VecDeque<f32>,
+    capacity: usize,
+}
+impl TimeSeries {
+    /// Creates an empty series that keeps the latest `capacity` values.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            values: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Adds a value to the series, and removes the oldest value if the series is full.
+    pub fn push(&mut self, value: f32) {
+        if self.values.len() == self.capacity {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+    }
+
+    /// Removes all the values.
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// The values of the series, from the oldest to the latest.
+    pub fn values(&self) -> impl Iterator<Item = f32> + '_ {
+        self.values.iter().copied()
+    }
+
+    /// The number of values in the series.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the series has no values.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The maximum number of values the series keeps.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The latest value, if any.
+    pub fn latest(&self) -> Option<f32> {
+        self.values.back().copied()
+    }
+
+    /// The smallest value, if any.
+    pub fn min(&self) -> Option<f32> {
+        self.values().reduce(f32::min)
+    }
+
+    /// The largest value, if any.
+    pub fn max(&self) -> Option<f32> {
+        self.values().reduce(f32::max)
+    }
+
+    /// The mean of the values, if any.
+    pub fn mean(&self) -> Option<f32> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.values().sum::<f32>() / self.len() as f32)
+        }
+    }
+}
+
+/// Keeps a [TimeSeries] of the values returned by `sample`, which is called every frame with the
+/// latest `capacity` values kept. The element is re-rendered every frame with the new series.
+///
+/// For instance, `use_time_series(hooks, 120, |world| world.resource(delta_time()) * 1000.)`
+/// keeps the frame times of the last 120 frames in milliseconds.
+pub fn use_time_series(
+    hooks: &mut Hooks,
+    capacity: usize,
+    sample: impl Fn(&mut World) -> f32 + Sync + Send + 'static,
+) -> TimeSeries {
+    let (series, set_series) = use_state_with(hooks, |_| TimeSeries::new(capacity));
+    let buffer = use_ref_with(hooks, |_| TimeSeries::new(capacity));
+    use_frame(hooks, move |world| {
+        let mut buffer = buffer.lock();
+        buffer.push(sample(world));
+        set_series(buffer.clone());
+    });
+    series
+}
+
+/// Shows values as a line, from left to right.
+#[element_component]
+pub fn LineChart(
+    _hooks: &mut Hooks,
+    /// The values to show
+    values: Vec<f32>,
+    /// The size of the chart, in logical pixels
+    size: Vec2,
+    /// The values at the bottom and the top of the chart. Deduced from the values if not set
+    range: Option<(f32, f32)>,
+    /// The color of the line
+    color: Vec4,
+    /// The width of the line, in logical pixels
+    line_width: f32,
+) -> Element {
+    let (min, max) = chart_range(&values, range);
+    let step = size.x / (values.len().max(2) - 1) as f32;
+    let points = values
+        .iter()
+        .enumerate()
+        .map(|(index, &value)| vec3(index as f32 * step, value_y(value, min, max, size.y), 0.))
+        .collect::<Vec<_>>();
+
+    let lines = points
+        .windows(2)
+        .map(|segment| {
+            Line.el()
+                .with(line_from(), segment[0])
+                .with(line_to(), segment[1])
+                .with(self::line_width(), line_width)
+                .with(background_color(), color)
+        })
+        .collect();
+    chart_frame(size, min, max, lines)
+}
+impl LineChart {
+    /// Creates a line chart of `values`, with the default style.
+    pub fn new(values: impl IntoIterator<Item = f32>, size: Vec2) -> Self {
+        Self {
+            values: values.into_iter().collect(),
+            size,
+            range: None,
+            color: primary_color().into(),
+            line_width: 1.5,
+        }
+    }
+    /// Sets the values at the bottom and the top of the chart.
+    pub fn range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+    /// Sets the color of the line.
+    pub fn color(mut self, color: Vec4) -> Self {
+        self.color = color;
+        self
+    }
+    /// Sets the width of the line.
+    pub fn line_width(mut self, line_width: f32) -> Self {
+        self.line_width = line_width;
+        self
+    }
+}
+
+/// Shows values as vertical bars, from left to right.
+#[element_component]
+pub fn BarChart(
+    _hooks: &mut Hooks,
+    /// The values to show
+    values: Vec<f32>,
+    /// The size of the chart, in logical pixels
+    size: Vec2,
+    /// The values at the bottom and the top of the chart. Deduced from the values if not set
+    range: Option<(f32, f32)>,
+    /// The color of the bars
+    color: Vec4,
+    /// The space between the bars, in logical pixels
+    gap: f32,
+) -> Element {
+    let (min, max) = chart_range(&values, range);
+    let step = size.x / values.len().max(1) as f32;
+    let bar_width = (step - gap).max(1.);
+    let baseline = value_y(min.max(0.).min(max), min, max, size.y);
+
+    let bars = values
+        .iter()
+        .enumerate()
+        .map(|(index, &value)| {
+            let y = value_y(value, min, max, size.y);
+            Rectangle
+                .el()
+                .with(
+                    translation(),
+                    vec3(index as f32 * step, y.min(baseline), -0.001),
+                )
+                .with(width(), bar_width)
+                .with(height(), (y - baseline).abs())
+                .with(background_color(), color)
+        })
+        .collect();
+    chart_frame(size, min, max, bars)
+}
+impl BarChart {
+    /// Creates a bar chart of `values`, with the default style.
+    pub fn new(values: impl IntoIterator<Item = f32>, size: Vec2) -> Self {
+        Self {
+            values: values.into_iter().collect(),
+            size,
+            range: None,
+            color: primary_color().into(),
+            gap: 1.,
+        }
+    }
+    /// Sets the values at the bottom and the top of the chart.
+    pub fn range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+    /// Sets the color of the bars.
+    pub fn color(mut self, color: Vec4) -> Self {
+        self.color = color;
+        self
+    }
+    /// Sets the space between the bars.
+    pub fn gap(mut self, gap: f32) -> Self {
+        self.gap = gap;
+        self
+    }
+}
+
+/// Shows how values are distributed: the range of the values is split into buckets of the same
+/// width, and each bar shows how many values fall in its bucket.
+#[element_component]
+pub fn Histogram(
+    _hooks: &mut Hooks,
+    /// The values to count
+    values: Vec<f32>,
+    /// The size of the chart, in logical pixels
+    size: Vec2,
+    /// The number of buckets
+    buckets: usize,
+    /// The values covered by the buckets. Deduced from the values if not set; values outside of
+    /// it are not counted
+    range: Option<(f32, f32)>,
+    /// The color of the bars
+    color: Vec4,
+) -> Element {
+    let counts = bucket_counts(&values, buckets.max(1), range);
+    let max_count = counts.iter().copied().fold(1., f32::max);
+    BarChart::new(counts, size)
+        .range(0., max_count)
+        .color(color)
+        .el()
+}
+impl Histogram {
+    /// Creates a histogram of `values` with `buckets` buckets, with the default style.
+    pub fn new(values: impl IntoIterator<Item = f32>, size: Vec2, buckets: usize) -> Self {
+        Self {
+            values: values.into_iter().collect(),
+            size,
+            buckets,
+            range: None,
+            color: primary_color().into(),
+        }
+    }
+    /// Sets the values covered by the buckets.
+    pub fn range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+    /// Sets the color of the bars.
+    pub fn color(mut self, color: Vec4) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// The background of a chart, with the values at its bottom and top in its corners.
+fn chart_frame(size: Vec2, min: f32, max: f32, content: Vec<Element>) -> Element {
+    let label = |value: f32, y: f32| {
+        Text::el(format_value(value))
+            .with(font_size(), 10.)
+            .with(translation(), vec3(2., y, -0.002))
+    };
+
+    let mut children = content;
+    children.push(label(max, 0.));
+    children.push(label(min, size.y - 12.));
+
+    with_rect(UIBase.el())
+        .with(width(), size.x)
+        .with(height(), size.y)
+        .with(background_color(), cutout_color().into())
+        .children(children)
+}
+
+fn format_value(value: f32) -> String {
+    if value.abs() >= 100. || value.fract() == 0. {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+/// Returns the range of the chart, deduced from the values if it is not set. Deduced ranges
+/// include zero, so that the bars and lines are not misleading.
+fn chart_range(values: &[f32], range: Option<(f32, f32)>) -> (f32, f32) {
+    let (min, max) = range.unwrap_or_else(|| {
+        let finite = values.iter().copied().filter(|value| value.is_finite());
+        let min = finite.clone().fold(0., f32::min);
+        let max = finite.fold(0., f32::max);
+        (min, max)
+    });
+    if max > min {
+        (min, max)
+    } else {
+        (min, min + 1.)
+    }
+}
+
+/// The position of `value` from the top of a chart of the given `height`.
+fn value_y(value: f32, min: f32, max: f32, height: f32) -> f32 {
+    let t = ((value - min) / (max - min)).clamp(0., 1.);
+    if t.is_nan() {
+        height
+    } else {
+        (1. - t) * height
+    }
+}
+
+fn bucket_counts(values: &[f32], buckets: usize, range: Option<(f32, f32)>) -> Vec<f32> {
+    let (min, max) = range.unwrap_or_else(|| {
+        let finite = values.iter().copied().filter(|value| value.is_finite());
+        let min = finite.clone().fold(f32::INFINITY, f32::min);
+        let max = finite.fold(f32::NEG_INFINITY, f32::max);
+        if min <= max {
+            (min, max)
+        } else {
+            (0., 1.)
+        }
+    });
+
+    let mut counts = vec![0.; buckets];
+    for &value in values {
+        if !(min..=max).contains(&value) {
+            continue;
+        }
+        let t = if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.
+        };
+        let bucket = ((t * buckets as f32) as usize).min(buckets - 1);
+        counts[bucket] += 1.;
+    }
+    counts
+}
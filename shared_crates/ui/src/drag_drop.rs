@@ -0,0 +1,120 @@
+//! Defines elements for dragging payloads between UI elements.
+//!
+//! The dragging itself is handled by the runtime; these elements attach the components it looks
+//! for, and listen to the messages it sends.
+use ambient_cb::Cb;
+use ambient_element::{
+    element_component, to_owned, use_frame, use_module_message, use_ref_with, use_state, Element,
+    ElementComponentExt, Hooks,
+};
+use ambient_guest_bridge::{
+    core::{
+        app::components::cursor_position,
+        input::components::{mouse_pickable_max, mouse_pickable_min},
+        transform::components::translation,
+        ui::{
+            components::{drag_source, drop_target},
+            messages::{DragCancelled, DragDropped, DragStarted},
+        },
+    },
+    ecs::{EntityId, World},
+};
+use glam::{Vec2, Vec3};
+
+use crate::UIBase;
+
+/// Makes `inner` draggable with the left mouse button, carrying `payload` to a [DropTarget].
+///
+/// Add a [DragGhost] to show the payload under the cursor while it is dragged.
+#[element_component]
+pub fn Draggable(
+    _hooks: &mut Hooks,
+    /// The payload that is delivered to the drop target
+    payload: String,
+    /// The element that can be dragged
+    inner: Element,
+) -> Element {
+    inner
+        .with(drag_source(), payload)
+        .init(mouse_pickable_min(), Vec3::ZERO)
+        .init(mouse_pickable_max(), Vec3::ZERO)
+}
+
+/// Lets payloads that are dragged from a [Draggable] be dropped on `inner`.
+///
+/// While a payload is dragged over it, `inner` has the `drop_target_hovered` component.
+#[element_component]
+pub fn DropTarget(
+    hooks: &mut Hooks,
+    /// The element that payloads can be dropped on
+    inner: Element,
+    /// Called with the payload when it is dropped
+    on_drop: Cb<dyn Fn(&mut World, String) + Sync + Send>,
+) -> Element {
+    let id = use_ref_with(hooks, |_| EntityId::null());
+    use_module_message::<DragDropped>(hooks, {
+        to_owned![id];
+        move |world, _, event| {
+            if event.target == *id.lock() {
+                on_drop(world, event.payload.clone());
+            }
+        }
+    });
+
+    inner
+        .with(drop_target(), ())
+        .on_spawned(move |_, target_id, _| *id.lock() = target_id)
+}
+
+/// Shows an element under the cursor while a payload is dragged.
+///
+/// Place it at the root of the UI, so that it is positioned relative to the window.
+#[element_component]
+pub fn DragGhost(
+    hooks: &mut Hooks,
+    /// Creates the element that is shown for the dragged payload
+    render: Cb<dyn Fn(String) -> Element + Sync + Send>,
+) -> Element {
+    let (payload, set_payload) = use_state(hooks, None::<String>);
+    let (position, set_position) = use_state(hooks, Vec2::ZERO);
+
+    use_module_message::<DragStarted>(hooks, {
+        to_owned![set_payload, set_position];
+        move |world, _, event| {
+            set_position(get_cursor_position(world));
+            set_payload(Some(event.payload.clone()));
+        }
+    });
+    use_module_message::<DragDropped>(hooks, {
+        to_owned![set_payload];
+        move |_, _, _| set_payload(None)
+    });
+    use_module_message::<DragCancelled>(hooks, {
+        to_owned![set_payload];
+        move |_, _, _| set_payload(None)
+    });
+
+    let dragging = payload.is_some();
+    use_frame(hooks, move |world| {
+        if dragging {
+            let cursor = get_cursor_position(world);
+            if cursor != position {
+                set_position(cursor);
+            }
+        }
+    });
+
+    match payload {
+        Some(payload) => UIBase
+            .el()
+            .with(translation(), position.extend(-0.1))
+            .children(vec![render(payload)]),
+        None => Element::new(),
+    }
+}
+
+fn get_cursor_position(world: &World) -> Vec2 {
+    // The resource is a ref on host and a copy on guest
+    let position = world.resource(cursor_position());
+    Vec2::new(position.x, position.y)
+}
@@ -50,6 +50,8 @@ pub fn TextEditor(
     let (command, set_command) = use_state(hooks, false);
     let intermediate_value = use_ref_with(hooks, |_| value.clone());
     let cursor_position = use_ref_with(hooks, |_| value.len());
+    // The text that is being composed by an input method, which is not part of the value yet
+    let composition = use_ref_with(hooks, |_| String::new());
     let rerender = use_rerender_signal(hooks);
     {
         let mut inter = intermediate_value.lock();
@@ -74,17 +76,29 @@ pub fn TextEditor(
         }
     });
 
-    use_runtime_message::<messages::WindowKeyboardCharacter>(hooks, {
-        to_owned![intermediate_value, on_change, cursor_position];
+    use_runtime_message::<messages::TextInput>(hooks, {
+        to_owned![
+            intermediate_value,
+            on_change,
+            cursor_position,
+            composition,
+            rerender
+        ];
         move |_world, event| {
-            let c = event.character.chars().next().unwrap();
             if command || !focused {
                 return;
             }
 
+            if event.composing {
+                *composition.lock() = event.text.clone();
+                rerender();
+                return;
+            }
+            composition.lock().clear();
+
             let mut value = intermediate_value.lock();
-            value.insert(*cursor_position.lock(), c);
-            *cursor_position.lock() += 1;
+            value.insert_str(*cursor_position.lock(), &event.text);
+            *cursor_position.lock() += event.text.len();
             on_change.0(value.clone());
         }
     });
@@ -124,31 +138,45 @@ pub fn TextEditor(
                             }
                         })
                     }
+                    // Keys that edit the value are left to the input method while it composes
+                    _ if !composition.lock().is_empty() => {}
                     VirtualKeyCode::Left => {
-                        if pressed && *cursor_position.lock() > 0 {
-                            *cursor_position.lock() -= 1;
-                            rerender();
+                        let mut cursor = cursor_position.lock();
+                        if let Some(previous) = previous_char(&intermediate_value.lock(), *cursor) {
+                            if pressed {
+                                *cursor = previous;
+                                rerender();
+                            }
                         }
                     }
                     VirtualKeyCode::Right => {
-                        if pressed && *cursor_position.lock() < intermediate_value.lock().len() {
-                            *cursor_position.lock() += 1;
-                            rerender();
+                        let mut cursor = cursor_position.lock();
+                        if let Some(next) = next_char(&intermediate_value.lock(), *cursor) {
+                            if pressed {
+                                *cursor = next;
+                                rerender();
+                            }
                         }
                     }
                     VirtualKeyCode::Back => {
-                        if pressed && *cursor_position.lock() > 0 {
-                            let mut value = intermediate_value.lock();
-                            value.remove(*cursor_position.lock() - 1);
-                            *cursor_position.lock() -= 1;
-                            on_change.0(value.clone());
+                        let mut value = intermediate_value.lock();
+                        let mut cursor = cursor_position.lock();
+                        if let Some(previous) = previous_char(&value, *cursor) {
+                            if pressed {
+                                value.replace_range(previous..*cursor, "");
+                                *cursor = previous;
+                                on_change.0(value.clone());
+                            }
                         }
                     }
                     VirtualKeyCode::Delete => {
-                        if pressed && *cursor_position.lock() < intermediate_value.lock().len() {
-                            let mut value = intermediate_value.lock();
-                            value.remove(*cursor_position.lock());
-                            on_change.0(value.clone());
+                        let mut value = intermediate_value.lock();
+                        let cursor = *cursor_position.lock();
+                        if let Some(next) = next_char(&value, cursor) {
+                            if pressed {
+                                value.replace_range(cursor..next, "");
+                                on_change.0(value.clone());
+                            }
                         }
                     }
                     VirtualKeyCode::Return => {
@@ -183,11 +211,20 @@ pub fn TextEditor(
         .unwrap();
 
     with_rect(if focused {
+        let composition = composition.lock().clone();
+        let mut children = Vec::new();
         if !cursor_left.is_empty() {
-            FlowRow::el([a, Cursor.el(), b])
-        } else {
-            FlowRow::el([Cursor.el(), b])
+            children.push(a);
+        }
+        if !composition.is_empty() {
+            children.push(
+                Text.el()
+                    .with(text(), composition)
+                    .with(color(), vec4(0.9, 0.9, 0.9, 0.5)),
+            );
         }
+        children.extend([Cursor.el(), b]);
+        FlowRow::el(children)
     } else if value.is_empty() && !focused && placeholder.is_some() {
         FlowRow::el([Text
             .el()
@@ -244,6 +281,16 @@ impl TextEditor {
     }
 }
 
+/// Returns the byte index of the character before `index` in `value`, if there is one.
+fn previous_char(value: &str, index: usize) -> Option<usize> {
+    value[..index].char_indices().next_back().map(|(i, _)| i)
+}
+
+/// Returns the byte index of the character after the one at `index` in `value`, if there is one.
+fn next_char(value: &str, index: usize) -> Option<usize> {
+    value[index..].chars().next().map(|c| index + c.len_utf8())
+}
+
 #[element_component]
 fn Cursor(_hooks: &mut Hooks) -> Element {
     CursorInner::el(Instant::now())
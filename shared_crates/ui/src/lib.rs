@@ -40,11 +40,15 @@ use ambient_guest_bridge::{
         layout::components::{
             gpu_ui_size, height, margin, mesh_to_local_from_size, padding, width,
         },
-        rect::components::{background_color, background_url, rect},
+        rect::components::{background_color, background_slice, background_url, rect},
         transform::components::{
             local_to_parent, local_to_world, mesh_to_local, mesh_to_world, scale, translation,
         },
-        ui::{components::focus, messages::FocusChanged},
+        ui::{
+            components::{focus, transition_duration, transition_easing, transition_exiting},
+            messages::FocusChanged,
+            types::Easing,
+        },
     },
     ecs::{EntityId, World},
 };
@@ -53,11 +57,14 @@ use clickarea::ClickArea;
 use glam::{vec3, Mat4, UVec2, Vec3, Vec4};
 
 pub mod button;
+pub mod chart;
 pub mod clickarea;
 pub mod default_theme;
+pub mod drag_drop;
 pub mod dropdown;
 pub mod editor;
 pub mod layout;
+pub mod markup;
 pub mod prelude;
 pub mod prompt;
 pub mod screens;
@@ -67,6 +74,7 @@ pub mod tabs;
 pub mod text;
 pub mod throbber;
 pub mod window;
+pub mod world_panel;
 
 /// A base element for all UI elements. It contains all the components needed for a UI element to work.
 #[element_component]
@@ -123,6 +131,21 @@ pub fn ImageFromUrl(
         .with(background_url(), url)
 }
 
+/// Show an image loaded from a url, using nine-slice scaling: the corners of the image keep their size,
+/// the edges are stretched along one axis, and the center is stretched to fill the rest.
+///
+/// This makes it possible to use a single image for panels and buttons of any size.
+#[element_component]
+pub fn NineSliceImage(
+    _: &mut Hooks,
+    /// Url to load the image from
+    url: String,
+    /// The insets of the slices, in pixels of the image: `x` = left, `y` = top, `z` = right, `w` = bottom
+    slice: Vec4,
+) -> Element {
+    ImageFromUrl { url }.el().with(background_slice(), slice)
+}
+
 /// A simple UI line. Use components like `line_from`, `line_to`, `line_width`, `background_color`, `border_color`, `border_radius` and `border_thickness`
 /// to control its appearance.
 #[element_component]
@@ -193,6 +216,14 @@ pub trait UIExt {
     fn with_padding_even(self, padding: f32) -> Self;
     /// Adds margin to all sides of this element.
     fn with_margin_even(self, margin: f32) -> Self;
+    /// Makes changes to the `translation`, `scale`, `color` and `background_color` of this element
+    /// transition smoothly over `duration` seconds, instead of changing immediately.
+    fn with_transition(self, duration: f32, easing: Easing) -> Self;
+    /// Plays the exit transition of this element (see `transition_exit_offset`, `transition_exit_opacity`
+    /// and `transition_exit_scale`) when it is removed, before despawning it and its children.
+    ///
+    /// Requires [UIExt::with_transition] to be used as well; otherwise, the element is despawned immediately.
+    fn with_exit_transition(self) -> Self;
 }
 impl UIExt for Element {
     fn with_clickarea(self) -> ClickArea {
@@ -207,6 +238,16 @@ impl UIExt for Element {
     fn with_margin_even(self, value: f32) -> Self {
         self.with(margin(), Vec4::ONE * value)
     }
+    fn with_transition(self, duration: f32, easing: Easing) -> Self {
+        self.with(transition_duration(), duration)
+            .with(transition_easing(), easing)
+    }
+    fn with_exit_transition(self) -> Self {
+        self.deferred_despawner(|world, id| {
+            world.has_component(id, transition_duration())
+                && world.add_component(id, transition_exiting(), ()).is_ok()
+        })
+    }
 }
 
 /// Helper wrapper around [use_runtime_message] that listens to `WindowKeyboardInput` messages
@@ -0,0 +1,297 @@
+//! Builds UI elements from UI markup documents, so that UI can be authored without rebuilding the
+//! package for every change.
+//!
+//! Documents are parsed by the runtime (see `client::ui_markup` in the API) into a list of
+//! [MarkupNode]s, which [Markup] turns into elements. The following tags are supported:
+//!
+//! - `column`, `row` and `dock`: a [FlowColumn], [FlowRow] or [Dock] of the children.
+//! - `scroll`: a [ScrollArea] around a column of the children.
+//! - `text`: a [Text] of the text inside of it. Text outside of a `text` tag is shown as well.
+//! - `button`: a [Button] that calls the action bound to its `action` attribute when clicked.
+//!   It is disabled if it has the `disabled` attribute.
+//! - `rect`: a [Rectangle].
+//! - `slot`: the element bound to its `name` attribute.
+//!
+//! Other tags are not shown. `{name}` in text and attribute values is replaced with the text bound
+//! to `name`.
+//!
+//! The supported style properties are `width`, `height`, `min-width`, `min-height`, `max-width`,
+//! `max-height`, `padding`, `margin`, `gap`, `color`, `background-color`, `border-color`,
+//! `border-radius`, `border-thickness`, `font-size`, `align-horizontal` and `align-vertical`.
+//! Lengths are in logical pixels, colors are hex colors like `#ff8800`, and `padding`, `margin`
+//! and `border-radius` take one to four lengths like in CSS.
+use std::collections::HashMap;
+
+use ambient_cb::{cb, Cb};
+use ambient_color::Color;
+use ambient_element::{element_component, Element, ElementComponentExt, Group, Hooks};
+use ambient_guest_bridge::{
+    core::{
+        rect::components::{background_color, border_color, border_radius, border_thickness},
+        rendering::components::color,
+        text::components::font_size,
+    },
+    ecs::{Component, World},
+};
+use ambient_shared_types::markup::MarkupNode;
+use glam::Vec4;
+
+use crate::{
+    button::Button,
+    layout::{
+        align_horizontal, align_vertical, fit_horizontal, fit_vertical, height, margin, max_height,
+        max_width, min_height, min_width, padding, space_between_items, width, Align, Dock, Fit,
+        FlowColumn, FlowRow,
+    },
+    scroll_area::{ScrollArea, ScrollAreaSizing},
+    text::Text,
+    with_rect, Rectangle,
+};
+
+/// The text, elements and actions that fill the data-binding slots of a UI markup document.
+#[derive(Debug, Clone, Default)]
+pub struct MarkupBindings {
+    text: HashMap<String, String>,
+    elements: HashMap<String, Element>,
+    actions: HashMap<String, Cb<dyn Fn(&mut World) + Sync + Send>>,
+}
+impl MarkupBindings {
+    /// Creates bindings that fill none of the slots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces `{name}` in the text and attribute values of the document with `value`.
+    pub fn text(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.text.insert(name.into(), value.into());
+        self
+    }
+
+    /// Replaces `<slot name="name"/>` in the document with `element`.
+    pub fn element(mut self, name: impl Into<String>, element: Element) -> Self {
+        self.elements.insert(name.into(), element);
+        self
+    }
+
+    /// Calls `action` when a `<button action="name">` in the document is clicked.
+    pub fn action(
+        mut self,
+        name: impl Into<String>,
+        action: impl Fn(&mut World) + Sync + Send + 'static,
+    ) -> Self {
+        self.actions.insert(name.into(), cb(action));
+        self
+    }
+
+    /// Replaces the `{name}`s in `text` that have bound text. The others are kept as they are,
+    /// so that they stand out.
+    fn fill(&self, text: &str) -> String {
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('{') {
+            output.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let value = rest
+                .find('}')
+                .and_then(|end| Some((self.text.get(&rest[1..end])?, end)));
+            match value {
+                Some((value, end)) => {
+                    output.push_str(value);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    output.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        output.push_str(rest);
+        output
+    }
+}
+
+/// Turns the nodes of a UI markup document into elements, with its slots filled by `bindings`.
+///
+/// See the [module documentation](crate::markup) for the supported tags and style properties.
+#[element_component]
+pub fn Markup(
+    _hooks: &mut Hooks,
+    /// The nodes of the document, as returned by `client::ui_markup::load`
+    nodes: Vec<MarkupNode>,
+    /// The text, elements and actions that fill the slots of the document
+    bindings: MarkupBindings,
+) -> Element {
+    let mut roots = Vec::new();
+    let mut children = vec![Vec::new(); nodes.len()];
+    for (index, node) in nodes.iter().enumerate() {
+        match node
+            .parent
+            .and_then(|parent| children.get_mut(parent as usize))
+        {
+            Some(siblings) => siblings.push(index),
+            None => roots.push(index),
+        }
+    }
+
+    let document = Document {
+        nodes: &nodes,
+        children,
+        bindings: &bindings,
+    };
+    let mut roots = document.build_all(&roots);
+    if roots.len() == 1 {
+        roots.pop().unwrap()
+    } else {
+        Group::el(roots)
+    }
+}
+
+struct Document<'a> {
+    nodes: &'a [MarkupNode],
+    /// The indices of the children of each node
+    children: Vec<Vec<usize>>,
+    bindings: &'a MarkupBindings,
+}
+impl Document<'_> {
+    fn build_all(&self, indices: &[usize]) -> Vec<Element> {
+        indices.iter().map(|&index| self.build(index)).collect()
+    }
+
+    fn build(&self, index: usize) -> Element {
+        let node = &self.nodes[index];
+        let children = &self.children[index];
+        let attribute = |name: &str| node.attribute(name).map(|value| self.bindings.fill(value));
+
+        let element = match node.tag.as_str() {
+            "" => Text::el(self.bindings.fill(&node.text)),
+            "column" => FlowColumn::el(self.build_all(children)),
+            "row" => FlowRow::el(self.build_all(children)),
+            "dock" => Dock::el(self.build_all(children)),
+            "scroll" => ScrollArea::el(
+                ScrollAreaSizing::FitParentWidth,
+                FlowColumn::el(self.build_all(children)),
+            ),
+            "text" => {
+                let text = children
+                    .iter()
+                    .map(|&child| &self.nodes[child])
+                    .filter(|child| child.is_text())
+                    .map(|child| self.bindings.fill(&child.text))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                Text::el(text)
+            }
+            "rect" => Rectangle.el(),
+            "button" => {
+                let action = attribute("action")
+                    .and_then(|action| self.bindings.actions.get(&action))
+                    .cloned();
+                let mut content = self.build_all(children);
+                let content = if content.len() == 1 {
+                    content.pop().unwrap()
+                } else {
+                    FlowRow::el(content)
+                };
+                Button::new(content, move |world| {
+                    if let Some(action) = &action {
+                        action(world);
+                    }
+                })
+                .disabled(node.attribute("disabled").is_some())
+                .el()
+            }
+            "slot" => {
+                return attribute("name")
+                    .and_then(|name| self.bindings.elements.get(&name))
+                    .cloned()
+                    .unwrap_or_default()
+            }
+            _ => return Element::new(),
+        };
+        apply_style(element, node)
+    }
+}
+
+/// Applies the style properties of `node` to `element`. Unknown properties, and values that cannot
+/// be parsed, are ignored.
+fn apply_style(mut element: Element, node: &MarkupNode) -> Element {
+    // Flows fit their children unless they are given a size
+    let is_flow = matches!(node.tag.as_str(), "column" | "row");
+    for (property, value) in &node.style {
+        element = match property.as_str() {
+            "width" if is_flow => {
+                with_length(element, width(), value).with(fit_horizontal(), Fit::None)
+            }
+            "width" => with_length(element, width(), value),
+            "height" if is_flow => {
+                with_length(element, height(), value).with(fit_vertical(), Fit::None)
+            }
+            "height" => with_length(element, height(), value),
+            "min-width" => with_length(element, min_width(), value),
+            "min-height" => with_length(element, min_height(), value),
+            "max-width" => with_length(element, max_width(), value),
+            "max-height" => with_length(element, max_height(), value),
+            "padding" => with_sides(element, padding(), value),
+            "margin" => with_sides(element, margin(), value),
+            "gap" => with_length(element, space_between_items(), value),
+            "color" => with_color(element, color(), value),
+            "background-color" => with_color(with_rect(element), background_color(), value),
+            "border-color" => with_color(element, border_color(), value),
+            "border-radius" => with_sides(element, border_radius(), value),
+            "border-thickness" => with_length(element, border_thickness(), value),
+            "font-size" => with_length(element, font_size(), value),
+            "align-horizontal" => with_align(element, align_horizontal(), value),
+            "align-vertical" => with_align(element, align_vertical(), value),
+            _ => element,
+        };
+    }
+    element
+}
+
+fn with_length(element: Element, component: Component<f32>, value: &str) -> Element {
+    match parse_length(value) {
+        Some(length) => element.with(component, length),
+        None => element,
+    }
+}
+
+fn with_sides(element: Element, component: Component<Vec4>, value: &str) -> Element {
+    let lengths = value
+        .split_whitespace()
+        .map(parse_length)
+        .collect::<Option<Vec<_>>>();
+    let sides = match lengths.as_deref() {
+        Some(&[all]) => Vec4::splat(all),
+        Some(&[vertical, horizontal]) => Vec4::new(vertical, horizontal, vertical, horizontal),
+        Some(&[top, horizontal, bottom]) => Vec4::new(top, horizontal, bottom, horizontal),
+        Some(&[top, right, bottom, left]) => Vec4::new(top, right, bottom, left),
+        _ => return element,
+    };
+    element.with(component, sides)
+}
+
+fn with_color(element: Element, component: Component<Vec4>, value: &str) -> Element {
+    let color = match value {
+        "transparent" => Vec4::ZERO,
+        hex => match Color::hex(hex) {
+            Ok(color) => color.into(),
+            Err(_) => return element,
+        },
+    };
+    element.with(component, color)
+}
+
+fn with_align(element: Element, component: Component<Align>, value: &str) -> Element {
+    let align = match value {
+        "begin" => Align::Begin,
+        "center" => Align::Center,
+        "end" => Align::End,
+        _ => return element,
+    };
+    element.with(component, align)
+}
+
+fn parse_length(value: &str) -> Option<f32> {
+    value.trim().trim_end_matches("px").parse().ok()
+}
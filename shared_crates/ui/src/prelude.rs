@@ -1,9 +1,9 @@
 //! A prelude for users of the crate. Imports all the most commonly used types and functions.
 
 pub use crate::{
-    button::*, clickarea::*, default_theme::*, dropdown::*, editor::*, layout::*, prompt::*,
-    screens::*, scroll_area::*, select::*, tabs::*, text::*, throbber::*, window::*, with_rect,
-    Line, Rectangle, UIBase, UIElement, UIExt,
+    button::*, chart::*, clickarea::*, default_theme::*, drag_drop::*, dropdown::*, editor::*,
+    layout::*, markup::*, prompt::*, screens::*, scroll_area::*, select::*, tabs::*, text::*,
+    throbber::*, window::*, with_rect, world_panel::*, Line, Rectangle, UIBase, UIElement, UIExt,
 };
 pub use ambient_cb::{cb, Cb};
 pub use ambient_element::{
@@ -1,28 +1,139 @@
-//! Defines a scroll area.
+//! Defines a scroll area and a virtualized list.
+use std::sync::Arc;
+
+use ambient_cb::Cb;
 use ambient_element::{
-    element_component, to_owned, use_frame, use_ref_with, use_runtime_message, use_state,
-    use_state_with, Element, ElementComponentExt, Hooks,
+    element_component, to_owned, use_frame, use_ref_with, use_runtime_message, use_state, Element,
+    ElementComponentExt, Hooks,
 };
 use ambient_guest_bridge::core::{
-    app::components::window_scale_factor,
     hierarchy::components::children,
     input::components::{is_mouse_over, mouse_pickable_max, mouse_pickable_min},
     layout::{
-        components::{fit_horizontal, fit_vertical, height, width},
+        components::{clip_children, fit_horizontal, fit_vertical, height, width},
         types::Fit,
     },
     messages,
     rect::components::{background_color, border_radius},
-    rendering::components::scissors_recursive,
     transform::components::{local_to_parent, local_to_world, translation},
 };
-use glam::{uvec4, vec3, vec4, Vec2, Vec3, Vec4};
+use ambient_guest_bridge::ecs::EntityId;
+use ambient_shared_types::MouseButton;
+use glam::{vec3, vec4, Vec2, Vec3, Vec4};
+use parking_lot::Mutex;
 
 use crate::{
-    layout::{Flow, MeasureAbsolutePosition, MeasureSize},
+    layout::{Flow, MeasureSize},
     Rectangle, UIBase,
 };
 
+/// How far a scroll area is scrolled by one line of the mouse wheel.
+const SCROLL_LINE_HEIGHT: f32 = 20.;
+
+/// Scrolls the element whose id is stored in the returned ref with the mouse wheel, or by dragging
+/// it with the left mouse button.
+///
+/// Returns the scroll offset, which is between `-max_scroll` and 0.
+fn use_scroll(hooks: &mut Hooks, max_scroll: f32) -> (f32, Arc<Mutex<Option<EntityId>>>) {
+    let (scroll, set_scroll) = use_state(hooks, 0.);
+    let id = use_ref_with(hooks, |_| None);
+    let mouse_over = use_ref_with(hooks, |_| false);
+    let dragging = use_ref_with(hooks, |_| false);
+    // Multiple inputs can arrive before the element is re-rendered
+    let current = use_ref_with(hooks, |_| 0.);
+
+    let scroll_by = {
+        to_owned![current, set_scroll];
+        move |delta: f32| {
+            let mut current = current.lock();
+            *current = (*current + delta).clamp(-max_scroll, 0.);
+            set_scroll(*current);
+        }
+    };
+
+    use_frame(hooks, {
+        to_owned![id, mouse_over, scroll_by];
+        move |world| {
+            if let Some(id) = *id.lock() {
+                *mouse_over.lock() = world.get(id, is_mouse_over()).unwrap_or(0) > 0;
+            }
+            // The content may have shrunk
+            if scroll < -max_scroll {
+                scroll_by(0.);
+            }
+        }
+    });
+    use_runtime_message::<messages::WindowMouseWheel>(hooks, {
+        to_owned![mouse_over, scroll_by];
+        move |_world, event| {
+            if !*mouse_over.lock() {
+                return;
+            }
+            let delta = event.delta.y;
+            scroll_by(if event.pixels {
+                delta
+            } else {
+                delta * SCROLL_LINE_HEIGHT
+            });
+        }
+    });
+    use_runtime_message::<messages::WindowMouseInput>(hooks, {
+        to_owned![mouse_over, dragging];
+        move |_world, event| {
+            if event.button != u32::from(MouseButton::Left) {
+                return;
+            }
+            *dragging.lock() = event.pressed && *mouse_over.lock();
+        }
+    });
+    use_runtime_message::<messages::WindowMouseMotion>(hooks, {
+        to_owned![dragging];
+        move |_world, event| {
+            if *dragging.lock() {
+                scroll_by(event.delta.y);
+            }
+        }
+    });
+
+    (scroll, id)
+}
+
+/// The scroll bar of a scroll area of the given `size`, with content of height `content_height`.
+fn scroll_bar(size: Vec2, content_height: f32, scroll: f32) -> Element {
+    let scroll_height = content_height - size.y;
+    if scroll_height <= 0.0 {
+        return Element::new();
+    }
+
+    let bar_height = size.y / content_height * size.y;
+    let offset = scroll / scroll_height * (size.y - bar_height);
+    Rectangle::el()
+        .with(width(), 5.)
+        .with(height(), bar_height)
+        .with(border_radius(), Vec4::ONE * 4.0)
+        .with(background_color(), vec4(0.6, 0.6, 0.6, 1.0))
+        .with(local_to_parent(), Default::default())
+        .with(local_to_world(), Default::default())
+        .with(translation(), vec3(size.x - 5.0, -offset, -0.1))
+}
+
+/// The element that contains the content of a scroll area, and receives its mouse input.
+fn viewport(
+    id: Arc<Mutex<Option<EntityId>>>,
+    set_size: Cb<dyn Fn(Vec2) + Sync + Send + 'static>,
+    content: Vec<Element>,
+) -> Element {
+    MeasureSize::el(UIBase::el(), set_size)
+        .on_spawned(move |_world, viewport_id, _| {
+            *id.lock() = Some(viewport_id);
+        })
+        .with(clip_children(), ())
+        .init(mouse_pickable_min(), Vec3::ZERO)
+        .init(mouse_pickable_max(), Vec3::ZERO)
+        .init_default(children())
+        .children(content)
+}
+
 /// Sizing config of a scroll area
 #[derive(Debug, Clone)]
 pub enum ScrollAreaSizing {
@@ -33,6 +144,8 @@ pub enum ScrollAreaSizing {
 }
 
 /// A scroll area that can be used to scroll its child.
+///
+/// It can be scrolled with the mouse wheel, or by dragging it. Its child is clipped to its bounds.
 #[element_component]
 pub fn ScrollArea(
     hooks: &mut Hooks,
@@ -41,125 +154,84 @@ pub fn ScrollArea(
     /// The child element
     inner: Element,
 ) -> Element {
-    let (scroll, set_scroll) = use_state(hooks, 0.);
-    let (ratio, _set_ratio) = use_state_with(hooks, |world| {
-        #[allow(clippy::clone_on_copy)]
-        let r = world.resource(window_scale_factor()).clone();
-        r as f32
-    });
     let (outer_size, set_outer_size) = use_state(hooks, Vec2::ZERO);
     let (inner_size, set_inner_size) = use_state(hooks, Vec2::ZERO);
-    // this will be auto-calculated by inner height - outer height
-    let scroll_height = if inner_size.y - outer_size.y > 0.0 {
-        inner_size.y - outer_size.y
-    } else {
-        0.0
-    };
-    let mouse_over_count = use_ref_with(hooks, |_| 0);
-    let bar_height = outer_size.y / (outer_size.y + scroll_height) * outer_size.y;
-    let offset = scroll / scroll_height * (outer_size.y - bar_height);
-    let id = use_ref_with(hooks, |_| None);
-    let inner_flow_id = use_ref_with(hooks, |_| None);
-    let (canvas_offset, set_canvas_offset) = use_state(hooks, Vec3::ZERO);
-
-    use_frame(hooks, {
-        to_owned![id, mouse_over_count, scroll, set_scroll, scroll_height];
-        move |world| {
-            if let Some(id) = *id.lock() {
-                let number = world.get(id, is_mouse_over()).unwrap_or(0);
-                *mouse_over_count.lock() = number;
-            }
-            if scroll_height <= 0.0 && scroll != 0.0 {
-                set_scroll(0.0);
-            };
-        }
-    });
-    use_runtime_message::<messages::WindowMouseWheel>(hooks, {
-        to_owned![mouse_over_count];
-        move |_world, event| {
-            if *mouse_over_count.lock() == 0 {
-                return;
-            };
-            let delta = event.delta;
-            let mouse_pos = scroll + if event.pixels { delta.y } else { delta.y * 20. };
-            set_scroll(mouse_pos.clamp(-scroll_height, 0.0));
-        }
-    });
+    let (scroll, id) = use_scroll(hooks, (inner_size.y - outer_size.y).max(0.));
 
-    let canvas = MeasureSize::el(
-        MeasureAbsolutePosition::el(UIBase::el(), set_canvas_offset),
+    let canvas = viewport(
+        id,
         set_outer_size,
-    )
-    .on_spawned({
-        to_owned![id];
-        move |_world, canvas_id, _| {
-            *id.lock() = Some(canvas_id);
-        }
-    })
-    .init(mouse_pickable_min(), Vec3::ZERO)
-    .init(mouse_pickable_max(), Vec3::ZERO)
-    .init_default(children())
-    .children(vec![
-        // TODO: For some reason it didn't work to set the translation on self.0 directly, so had to introduce a Flow in between
-        MeasureSize::el(
-            {
-                let flow = Flow(vec![inner])
-                    .el()
-                    .with(scissors_recursive(), {
-                        let (y, h) = if canvas_offset.y > 0.0 {
-                            (
-                                (canvas_offset.y * ratio) as u32,
-                                (outer_size.y * ratio) as u32,
-                            )
-                        } else {
-                            (0, ((outer_size.y + canvas_offset.y) * ratio) as u32)
-                        };
-
-                        let (x, w) = if canvas_offset.x > 0.0 {
-                            (
-                                (canvas_offset.x * ratio) as u32,
-                                (outer_size.x * ratio) as u32,
-                            )
-                        } else {
-                            (0, ((outer_size.x + canvas_offset.x) * ratio) as u32)
-                        };
-                        uvec4(x, y, w, h)
-                    })
-                    .on_spawned({
-                        to_owned![inner_flow_id];
-                        move |_world, flow_id, _| {
-                            *inner_flow_id.lock() = Some(flow_id);
-                        }
-                    })
-                    .with(translation(), vec3(0., scroll, 0.));
-                match sizing {
-                    ScrollAreaSizing::FitParentWidth => flow
-                        .with(fit_vertical(), Fit::Children)
-                        .with(fit_horizontal(), Fit::Parent)
-                        .with(width(), outer_size.x),
-                    ScrollAreaSizing::FitChildrenWidth => flow
-                        .with(fit_vertical(), Fit::Children)
-                        .with(fit_horizontal(), Fit::Children),
-                }
-            },
-            set_inner_size,
-        ),
-        if scroll_height > 0.0 {
-            Rectangle::el()
-                .with(width(), 5.)
-                .with(height(), bar_height)
-                .with(border_radius(), Vec4::ONE * 4.0)
-                .with(background_color(), vec4(0.6, 0.6, 0.6, 1.0))
-                .with(local_to_parent(), Default::default())
-                .with(local_to_world(), Default::default())
-                .with(translation(), vec3(outer_size.x - 5.0, -offset, -0.1))
-        } else {
-            Element::new()
-        },
-    ]);
+        vec![
+            // TODO: For some reason it didn't work to set the translation on self.0 directly, so had to introduce a Flow in between
+            MeasureSize::el(
+                {
+                    let flow = Flow(vec![inner])
+                        .el()
+                        .with(translation(), vec3(0., scroll, 0.));
+                    match sizing {
+                        ScrollAreaSizing::FitParentWidth => flow
+                            .with(fit_vertical(), Fit::Children)
+                            .with(fit_horizontal(), Fit::Parent)
+                            .with(width(), outer_size.x),
+                        ScrollAreaSizing::FitChildrenWidth => flow
+                            .with(fit_vertical(), Fit::Children)
+                            .with(fit_horizontal(), Fit::Children),
+                    }
+                },
+                set_inner_size,
+            ),
+            scroll_bar(outer_size, inner_size.y, scroll),
+        ],
+    );
 
     match sizing {
         ScrollAreaSizing::FitChildrenWidth => canvas.with(width(), inner_size.x),
         ScrollAreaSizing::FitParentWidth => canvas,
     }
 }
+
+/// A scrollable list of rows of the same height, which only creates the elements of the rows that
+/// are visible.
+///
+/// Use this instead of a [ScrollArea] for long lists, such as inventories, server browsers or chat
+/// histories. The list fills its parent; give it a `height` if its parent does not have one.
+#[element_component]
+pub fn VirtualList(
+    hooks: &mut Hooks,
+    /// The number of items in the list
+    item_count: usize,
+    /// The height of each row
+    item_height: f32,
+    /// Creates the element of the item with the given index
+    render_item: Cb<dyn Fn(usize) -> Element + Sync + Send>,
+) -> Element {
+    let (size, set_size) = use_state(hooks, Vec2::ZERO);
+    let content_height = item_count as f32 * item_height;
+    let (scroll, id) = use_scroll(hooks, (content_height - size.y).max(0.));
+
+    let visible = if item_height > 0. {
+        let first = ((-scroll / item_height).floor() as usize).min(item_count);
+        let last = (((size.y - scroll) / item_height).ceil() as usize).min(item_count);
+        first..last
+    } else {
+        0..0
+    };
+
+    let mut rows = visible
+        .map(|index| {
+            UIBase::el()
+                .with(width(), size.x)
+                .with(height(), item_height)
+                .with(
+                    translation(),
+                    vec3(0., index as f32 * item_height + scroll, 0.),
+                )
+                .init_default(children())
+                .children(vec![render_item(index)])
+                .key(index.to_string())
+        })
+        .collect::<Vec<_>>();
+    rows.push(scroll_bar(size, content_height, scroll));
+
+    viewport(id, set_size, rows)
+}
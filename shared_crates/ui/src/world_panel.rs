@@ -0,0 +1,45 @@
+//! Shows UI in the 3D world, for computer screens, keypads and holograms inside of the game world.
+use std::f32::consts::FRAC_PI_2;
+
+use ambient_element::{element_component, Element, Hooks};
+use ambient_guest_bridge::core::{
+    transform::components::{local_to_parent, local_to_world, rotation, scale},
+    ui::components::world_panel,
+};
+use glam::{vec3, Quat};
+
+/// Shows `inner` on a panel in the 3D world, instead of on the screen.
+///
+/// The panel stands upright and faces the negative Y axis of its entity, with the top-left corner
+/// of the UI at its origin. Use the `translation`, `rotation` and `parent` components to place it
+/// in the world.
+///
+/// The UI elements on the panel react to the mouse like on the screen: the mouse is cast into the
+/// world with the main camera. While the mouse is over one of them, the `world_panel_cursor`
+/// component of the panel has the position of the mouse in the logical pixels of the UI.
+#[element_component]
+pub fn WorldPanel(
+    _hooks: &mut Hooks,
+    /// The UI to show on the panel
+    inner: Element,
+    /// How many logical pixels of the UI fit in a meter
+    pixels_per_meter: f32,
+) -> Element {
+    // The UI is laid out with X to the right, Y downwards and Z away from the viewer, so it is
+    // rotated to stand upright. Depth is not scaled, so that the layers of the UI stay apart.
+    let panel = Element::new()
+        .with(world_panel(), ())
+        .init_default(local_to_world())
+        .init_default(local_to_parent())
+        .with(rotation(), Quat::from_rotation_x(-FRAC_PI_2))
+        .with(
+            scale(),
+            vec3(1. / pixels_per_meter, 1. / pixels_per_meter, 1.),
+        )
+        .children(vec![inner]);
+
+    Element::new()
+        .init_default(local_to_world())
+        .init_default(local_to_parent())
+        .children(vec![panel])
+}
@@ -1,11 +1,11 @@
 use crate::{wasm, Settings};
 use ambient_cameras::UICamera;
-use ambient_client_shared::{game_view::GameView, player};
+use ambient_client_shared::{game_view::GameView, loading_screen, player, quality_tier};
 use ambient_ecs::{Entity, SystemGroup};
 use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
 use ambient_network::{server::RpcArgs, web::client::GameClientView};
 use ambient_rpc::RpcRegistry;
-use ambient_ui_native::cb;
+use ambient_ui_native::{cb, notifications::NotificationStack};
 use std::collections::HashMap;
 
 #[element_component]
@@ -17,6 +17,7 @@ pub fn MainApp(_hooks: &mut Hooks, server_url: String, settings: Settings) -> El
         user_id: settings
             .user_id
             .unwrap_or_else(ambient_client_shared::util::random_username),
+        spectator: settings.spectate,
         fail_on_version_mismatch: !settings.allow_version_mismatch,
         systems_and_resources: cb(|| {
             let mut resources = Entity::new();
@@ -41,9 +42,12 @@ pub fn MainApp(_hooks: &mut Hooks, server_url: String, settings: Settings) -> El
         on_loaded: cb(move |_, game_state| {
             let world = &mut game_state.world;
 
+            let quality_tier = quality_tier::target_tier(world);
+            world.add_resource(ambient_core::quality_tier(), quality_tier);
             wasm::initialize(world).unwrap();
 
             UICamera.el().spawn_static(world);
+            NotificationStack.el().spawn_interactive(world);
 
             Ok(Box::new(|| {
                 tracing::debug!("Disconnecting client");
@@ -52,6 +56,7 @@ pub fn MainApp(_hooks: &mut Hooks, server_url: String, settings: Settings) -> El
         create_rpc_registry: cb(create_server_rpc_registry),
         inner: GameView {
             show_debug: settings.debugger,
+            on_preloaded: None,
         }
         .el(),
     }
@@ -71,6 +76,8 @@ fn systems() -> SystemGroup {
             // Box::new(ambient_physics::client_systems()),
             // Box::new(ambient_gizmos::client_systems()),
             Box::new(wasm::systems()),
+            Box::new(quality_tier::systems()),
+            Box::new(loading_screen::systems()),
             Box::new(player::systems_final()),
         ],
     )
@@ -38,6 +38,8 @@ pub struct Settings {
     #[serde(default)]
     user_id: Option<String>,
     #[serde(default)]
+    spectate: bool,
+    #[serde(default)]
     debugger: bool,
 }
 
@@ -5,6 +5,10 @@ use ambient_wasm::shared::{module_name, MessageType};
 
 use std::sync::Arc;
 
+/// The `localStorage` key under which the page stores whether the user has opted out of
+/// analytics, as the native client does in its settings.
+const ANALYTICS_OPT_OUT_KEY: &str = "ambient.analyticsOptOut";
+
 /// Initiates the wasm client systems
 pub fn systems() -> SystemGroup {
     SystemGroup::new(
@@ -42,7 +46,14 @@ pub fn initialize(world: &mut World) -> anyhow::Result<()> {
     //     world.add_resource(ambient_world_audio::audio_mixer(), mixer);
     // }
 
-    ambient_wasm::client::initialize(world, &assets, messenger)?;
+    ambient_wasm::client::initialize(world, &assets, analytics_opt_out(), messenger)?;
 
     Ok(())
 }
+
+fn analytics_opt_out() -> bool {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .and_then(|storage| storage.get_item(ANALYTICS_OPT_OUT_KEY).ok().flatten())
+        .map_or(false, |value| value == "true")
+}